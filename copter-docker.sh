@@ -22,6 +22,7 @@ IMAGE_NAME="${COPTER_DOCKER_IMAGE:-ghcr.io/imazen/cargo-copter:latest}"
 WORKSPACE="$(pwd)"
 COPTER_DIR="${COPTER_DIR:-$WORKSPACE/.copter}"
 CARGO_HOME_CACHE="${COPTER_CARGO_CACHE:-$COPTER_DIR/docker-cargo}"
+REPORT_DEST="${COPTER_REPORT_DEST:-$WORKSPACE/copter-report}"
 
 # Colors for output
 RED='\033[0;31m'
@@ -72,6 +73,13 @@ build_image() {
         warn "Failed to pull image from registry, building locally instead"
     fi
 
+    # A dependent's own image (copter.toml `image`) can't be built from our Dockerfile
+    if [ "${COPTER_PULL_ONLY:-}" = "1" ]; then
+        info "Pulling Docker image: $IMAGE_NAME"
+        docker pull "$IMAGE_NAME" || error "Failed to pull Docker image: $IMAGE_NAME"
+        return 0
+    fi
+
     info "Building Docker image: $IMAGE_NAME (this may take a few minutes)"
 
     # Create temporary Dockerfile
@@ -104,6 +112,25 @@ DOCKERFILE
     info "Docker image built successfully"
 }
 
+# Layer the system packages declared in copter.toml (COPTER_SYSTEM_PACKAGES) onto the image.
+# The derived image is tagged by package list, so it is built once and reused.
+add_system_packages() {
+    local packages="${COPTER_SYSTEM_PACKAGES:-}"
+    if [ -z "$packages" ]; then
+        return 0
+    fi
+
+    local tag
+    tag="cargo-copter-sys:$(echo "$IMAGE_NAME $packages" | sha256sum | cut -c1-12)"
+    if ! docker image inspect "$tag" &>/dev/null; then
+        info "Installing system packages into the image: $packages"
+        printf 'FROM %s\nUSER root\nRUN apt-get update && apt-get install -y --no-install-recommends %s && rm -rf /var/lib/apt/lists/*\n' \
+            "$IMAGE_NAME" "$packages" | docker build -t "$tag" - || \
+            error "Failed to install system packages: $packages"
+    fi
+    IMAGE_NAME="$tag"
+}
+
 # Prepare directories
 prepare_directories() {
     mkdir -p "$COPTER_DIR/staging"
@@ -236,15 +263,15 @@ run_copter() {
     # Copy reports to workspace
     # Handle both new directory structure (copter-report/) and old flat files
     echo ""
-    mkdir -p "$WORKSPACE/copter-report"
+    mkdir -p "$REPORT_DEST"
     if [ -d "$COPTER_DIR/copter-report" ]; then
-        cp -r "$COPTER_DIR/copter-report/"* "$WORKSPACE/copter-report/" 2>/dev/null
-        info "Reports copied to: $WORKSPACE/copter-report/"
+        cp -r "$COPTER_DIR/copter-report/"* "$REPORT_DEST/" 2>/dev/null
+        info "Reports copied to: $REPORT_DEST/"
     elif [ -f "$COPTER_DIR/copter-report.md" ] || [ -f "$COPTER_DIR/copter-report.json" ]; then
         # Old flat file structure (pre-0.3 cargo-copter)
-        cp "$COPTER_DIR/copter-report.md" "$WORKSPACE/copter-report/report.md" 2>/dev/null
-        cp "$COPTER_DIR/copter-report.json" "$WORKSPACE/copter-report/report.json" 2>/dev/null
-        info "Reports copied to: $WORKSPACE/copter-report/"
+        cp "$COPTER_DIR/copter-report.md" "$REPORT_DEST/report.md" 2>/dev/null
+        cp "$COPTER_DIR/copter-report.json" "$REPORT_DEST/report.json" 2>/dev/null
+        info "Reports copied to: $REPORT_DEST/"
     fi
 
     return $copter_exit
@@ -254,6 +281,7 @@ run_copter() {
 main() {
     check_prerequisites
     build_image
+    add_system_packages
     prepare_directories
     run_copter "$@"
 }
@@ -283,6 +311,7 @@ Environment Variables:
   COPTER_DOCKER_IMAGE    Docker image name (default: cargo-copter:local)
   COPTER_DIR             Copter data directory (default: ./.copter)
   COPTER_CARGO_CACHE     Cargo cache directory (default: ./.copter/docker-cargo)
+  COPTER_REPORT_DEST     Where reports are copied (default: ./copter-report)
 
 Security Features:
   - Read-only workspace mount (your source code is protected)
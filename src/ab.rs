@@ -0,0 +1,273 @@
+/// `ab`: two variants of the base crate, head to head over the same dependents
+///
+/// Comparing a branch against main used to take two runs and a diff of their reports, each
+/// run testing the baseline and downloading every dependent again. `ab --a path:../main
+/// --b path:../feature-branch` puts both variants into one matrix next to the shared
+/// baseline, so each dependent is fetched and its baseline built once, and both are
+/// offered the same way (forced, like the WIP). A variant is `path:DIR` or a published
+/// version (`0.8.91`, or a keyword like `latest`). When both have the same version, the
+/// local ones are staged as copies versioned `0.8.92+a` and `0.8.92+b` (cargo ignores
+/// build metadata when matching requirements) so their rows stay apart. The head-to-head
+/// is written to copter-report/ab.md and summarized at the end of the run.
+use crate::messages::Catalog;
+use crate::registry::Registry;
+use crate::types::{CrateSource, OfferedRow, OverrideMode, Severity, TestMatrix, VersionSpec, VersionedCrate};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// The two variants as tested
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadToHead {
+    pub a: Side,
+    pub b: Side,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Side {
+    /// As given: "path:../main", "0.8.91"
+    pub spec: String,
+    /// The offered version its rows carry
+    pub version: String,
+}
+
+/// `path:DIR` from a variant, if it is one
+pub fn local_path(spec: &str) -> Option<PathBuf> {
+    spec.strip_prefix("path:").map(PathBuf::from)
+}
+
+/// Replace the matrix's offered versions with the two variants, after the baseline
+pub fn prepare(matrix: &mut TestMatrix, a: &str, b: &str) -> Result<HeadToHead, String> {
+    let mut sides =
+        [resolve(&matrix.registry, &matrix.base_crate, a)?, resolve(&matrix.registry, &matrix.base_crate, b)?];
+    let version = |spec: &VersionSpec| spec.crate_ref.version.display();
+    if version(&sides[0]) == version(&sides[1]) {
+        if sides.iter().all(|s| s.crate_ref.source == CrateSource::Registry) {
+            return Err(format!("--a and --b are the same version ({})", version(&sides[0])));
+        }
+        for (side, letter) in sides.iter_mut().zip(["a", "b"]) {
+            if let CrateSource::Local { path } = &side.crate_ref.source {
+                let tagged = format!("{}+{}", version(side), letter);
+                let dest = matrix.staging_dir.join(format!("{}-ab-{}", matrix.base_crate, letter));
+                let copy = crate::simulate_update::copy_with_version(
+                    path.parent().unwrap_or(path),
+                    &dest,
+                    &tagged,
+                    matrix.staging_copy,
+                )?;
+                side.crate_ref = VersionedCrate::from_local(&matrix.base_crate, tagged, copy.join("Cargo.toml"));
+            }
+        }
+    }
+
+    let [a_spec, b_spec] = sides;
+    let head_to_head = HeadToHead {
+        a: Side { spec: a.to_string(), version: version(&a_spec) },
+        b: Side { spec: b.to_string(), version: version(&b_spec) },
+    };
+    matrix.base_versions.retain(|v| v.is_baseline);
+    if matrix.base_versions.is_empty() {
+        return Err(format!("No published version of {} to use as the baseline", matrix.base_crate));
+    }
+    matrix.base_versions.extend([a_spec, b_spec]);
+    Ok(head_to_head)
+}
+
+/// A variant as an offered version: local crates and published versions, both forced
+fn resolve(registry: &Registry, crate_name: &str, spec: &str) -> Result<VersionSpec, String> {
+    let crate_ref = match local_path(spec) {
+        Some(dir) => {
+            let manifest = if dir.ends_with("Cargo.toml") { dir } else { dir.join("Cargo.toml") };
+            let (name, version) = crate::manifest::get_crate_info(&manifest)
+                .map_err(|e| format!("{}: failed to read {}: {}", spec, manifest.display(), e))?;
+            if name != crate_name {
+                return Err(format!("{}: {} is {}, not {}", spec, manifest.display(), name, crate_name));
+            }
+            VersionedCrate::from_local(crate_name, version, manifest)
+        }
+        None => match crate::version::resolve_version_keyword(registry, spec, crate_name, None)? {
+            Some(crate::compile::VersionSource::Published { version, .. }) => {
+                VersionedCrate::from_registry(crate_name, version)
+            }
+            _ => return Err(format!("Can't resolve variant '{}' (use path:DIR or a published version)", spec)),
+        },
+    };
+    Ok(VersionSpec { crate_ref, override_mode: OverrideMode::Force, is_baseline: false })
+}
+
+/// One dependent's results with each variant
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pair<'a> {
+    pub dependent: String,
+    pub a: Option<&'a OfferedRow>,
+    pub b: Option<&'a OfferedRow>,
+}
+
+impl Pair<'_> {
+    fn passes(row: Option<&OfferedRow>) -> Option<bool> {
+        let status = row?.status();
+        (!status.is_skipped()).then(|| status.severity() == Severity::Ok)
+    }
+
+    fn cell(row: Option<&OfferedRow>, messages: &Catalog) -> String {
+        row.map_or("-".to_string(), |row| row.status().label(messages))
+    }
+}
+
+/// Counts of the head-to-head, and the dependents where the variants disagree
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Comparison<'a> {
+    pub both_pass: usize,
+    pub both_fail: usize,
+    /// Not tested with one of the variants (skipped, not used, stopped early)
+    pub untested: usize,
+    pub only_a: Vec<Pair<'a>>,
+    pub only_b: Vec<Pair<'a>>,
+}
+
+impl<'a> Comparison<'a> {
+    pub fn from_rows(rows: &'a [OfferedRow], head_to_head: &HeadToHead) -> Self {
+        let mut pairs: BTreeMap<String, Pair<'a>> = BTreeMap::new();
+        for row in rows {
+            let Some(offered) = &row.offered else { continue };
+            let dependent = format!("{} {}", row.primary.dependent_name, row.primary.dependent_version);
+            let pair = pairs.entry(dependent.clone()).or_insert(Pair { dependent, a: None, b: None });
+            if offered.version == head_to_head.a.version {
+                pair.a = Some(row);
+            } else if offered.version == head_to_head.b.version {
+                pair.b = Some(row);
+            }
+        }
+        let mut comparison = Comparison::default();
+        for pair in pairs.into_values() {
+            match (Pair::passes(pair.a), Pair::passes(pair.b)) {
+                (Some(true), Some(true)) => comparison.both_pass += 1,
+                (Some(false), Some(false)) => comparison.both_fail += 1,
+                (Some(true), Some(false)) => comparison.only_a.push(pair),
+                (Some(false), Some(true)) => comparison.only_b.push(pair),
+                _ => comparison.untested += 1,
+            }
+        }
+        comparison
+    }
+
+    /// "A/B: A (path:../main) passes 40, B (path:../feature) passes 38; only A: png 1.0.0"
+    pub fn summary_line(&self, head_to_head: &HeadToHead) -> String {
+        let names = |pairs: &[Pair]| pairs.iter().map(|p| p.dependent.as_str()).collect::<Vec<_>>().join(", ");
+        let mut line = format!(
+            "A/B: A ({}) passes {}, B ({}) passes {}",
+            head_to_head.a.spec,
+            self.both_pass + self.only_a.len(),
+            head_to_head.b.spec,
+            self.both_pass + self.only_b.len()
+        );
+        if !self.only_a.is_empty() {
+            line.push_str(&format!("; only A: {}", names(&self.only_a)));
+        }
+        if !self.only_b.is_empty() {
+            line.push_str(&format!("; only B: {}", names(&self.only_b)));
+        }
+        if self.only_a.is_empty() && self.only_b.is_empty() {
+            line.push_str("; no dependent differs");
+        }
+        line
+    }
+
+    /// ab.md: the counts, then the dependents passing with only one variant
+    pub fn write_markdown(
+        &self,
+        out: &mut impl Write,
+        crate_name: &str,
+        head_to_head: &HeadToHead,
+        messages: &Catalog,
+    ) -> std::io::Result<()> {
+        let (a, b) = (&head_to_head.a, &head_to_head.b);
+        writeln!(out, "# A/B: {}\n", crate_name)?;
+        writeln!(out, "- **A**: `{}` ({})", a.spec, a.version)?;
+        writeln!(out, "- **B**: `{}` ({})\n", b.spec, b.version)?;
+        writeln!(out, "| | Dependents |")?;
+        writeln!(out, "|---|---:|")?;
+        writeln!(out, "| Pass with both | {} |", self.both_pass)?;
+        writeln!(out, "| Pass with A only | {} |", self.only_a.len())?;
+        writeln!(out, "| Pass with B only | {} |", self.only_b.len())?;
+        writeln!(out, "| Fail with both | {} |", self.both_fail)?;
+        writeln!(out, "| Not tested with both | {} |\n", self.untested)?;
+        for (title, pairs) in [("Pass with A only", &self.only_a), ("Pass with B only", &self.only_b)] {
+            if pairs.is_empty() {
+                continue;
+            }
+            writeln!(out, "## {}\n", title)?;
+            writeln!(out, "| Dependent | A | B |")?;
+            writeln!(out, "|-----------|---|---|")?;
+            for pair in pairs {
+                writeln!(
+                    out,
+                    "| {} | {} | {} |",
+                    pair.dependent,
+                    Pair::cell(pair.a, messages),
+                    Pair::cell(pair.b, messages)
+                )?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write ab.md
+pub fn export(
+    rows: &[OfferedRow],
+    crate_name: &str,
+    head_to_head: &HeadToHead,
+    path: &Path,
+    messages: &Catalog,
+) -> Result<(), String> {
+    let mut out = std::io::BufWriter::new(
+        std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?,
+    );
+    Comparison::from_rows(rows, head_to_head)
+        .write_markdown(&mut out, crate_name, head_to_head, messages)
+        .and_then(|()| out.flush())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(dependent: &str, offered: &str, passed: bool) -> OfferedRow {
+        crate::test_rows::row(dependent).offered(offered).forced(true).local().passed(passed).build()
+    }
+
+    #[test]
+    fn test_head_to_head() {
+        let head_to_head = HeadToHead {
+            a: Side { spec: "path:../main".to_string(), version: "0.8.92+a".to_string() },
+            b: Side { spec: "path:../simd".to_string(), version: "0.8.92+b".to_string() },
+        };
+        let rows = vec![
+            row("image", "0.8.92+a", true),
+            row("image", "0.8.92+b", true),
+            row("png", "0.8.92+a", true),
+            row("png", "0.8.92+b", false),
+            row("ravif", "0.8.92+a", false),
+            row("ravif", "0.8.92+b", false),
+            row("resize", "0.8.92+b", true),
+        ];
+        let comparison = Comparison::from_rows(&rows, &head_to_head);
+        assert_eq!((comparison.both_pass, comparison.both_fail, comparison.untested), (1, 1, 1));
+        assert_eq!(
+            comparison.summary_line(&head_to_head),
+            "A/B: A (path:../main) passes 2, B (path:../simd) passes 1; only A: png 1.0.0"
+        );
+
+        let mut md = Vec::new();
+        comparison.write_markdown(&mut md, "rgb", &head_to_head, &Catalog::default()).unwrap();
+        let md = String::from_utf8(md).unwrap();
+        assert!(md.contains("| Pass with A only | 1 |\n"));
+        assert!(md.contains("## Pass with A only\n\n| Dependent | A | B |\n|-----------|---|---|\n| png 1.0.0 | passed | build failed |\n"));
+        assert!(!md.contains("## Pass with B only"));
+        assert_eq!(local_path("path:../main"), Some(PathBuf::from("../main")));
+        assert_eq!(local_path("0.8.91"), None);
+    }
+}
@@ -0,0 +1,275 @@
+/// How quickly regressed dependents adopted the base crate's previous breaking releases
+///
+/// A dependent that took a year to move from rgb 0.7 to 0.8 needs an earlier and louder
+/// heads-up than one that moved within a week. For every regressed dependent this looks
+/// at the last few release lines of the base crate (`0.6`, `0.7`, `0.8`; a line is a run of
+/// semver-compatible versions) and finds the first release of the dependent whose
+/// requirement accepts that line or a later one. Release dates come from crates.io; each
+/// dependent release's requirement comes from the history DB (the `report.json` copies
+/// of earlier runs under `history/`) or else from crates.io, and crates.io answers are
+/// cached in `history/requirements-<crate>.json`. Releases are binary-searched by date,
+/// so a dependent costs a handful of lookups per line, once.
+use crate::registry::Registry;
+use crate::types::OfferedRow;
+use crate::version::compat_line;
+use chrono::NaiveDate;
+use semver::{Version, VersionReq};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Release lines of the base crate to report, newest last
+pub const LINES: usize = 3;
+
+/// One dependent's adoption of one base crate release line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineAdoption {
+    /// `0.8`
+    pub line: String,
+    /// Days from the line's first release to the dependent's first release accepting it,
+    /// or until today if it hasn't moved yet
+    pub days: i64,
+    /// The dependent release that moved (`None`: still on an older line)
+    pub adopted_in: Option<String>,
+}
+
+/// Adoption history of one dependent, oldest line first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Adoption {
+    pub dependent: String,
+    pub lines: Vec<LineAdoption>,
+}
+
+impl Adoption {
+    /// Median days to adopt a line (lines not adopted yet count with the days so far)
+    pub fn median_days(&self) -> Option<i64> {
+        let mut days: Vec<i64> = self.lines.iter().map(|l| l.days).collect();
+        days.sort_unstable();
+        days.get(days.len() / 2).copied()
+    }
+}
+
+/// A release line: its name, first release date and every version on it
+struct Line {
+    name: String,
+    released: NaiveDate,
+    versions: Vec<Version>,
+}
+
+/// Adoption history of every regressed dependent in `rows`, slowest first
+pub fn regressed(registry: &Registry, report_dir: &Path, base_crate: &str, rows: &[OfferedRow]) -> Vec<Adoption> {
+    let mut names: Vec<&str> = Vec::new();
+    for row in rows.iter().filter(|r| r.is_regression()) {
+        if !names.contains(&row.primary.dependent_name.as_str()) {
+            names.push(&row.primary.dependent_name);
+        }
+    }
+    if names.is_empty() {
+        return vec![];
+    }
+    let lines = match crate::api::fetch_release_dates(registry, base_crate) {
+        Ok(releases) => release_lines(&releases),
+        Err(e) => {
+            log::debug!("no release dates for {}: {}", base_crate, e);
+            return vec![];
+        }
+    };
+    if lines.is_empty() {
+        return vec![];
+    }
+
+    let cache_path = cache_path(report_dir, base_crate);
+    let mut requirements = load_cache(&cache_path);
+    for (key, spec) in history_requirements(report_dir, base_crate) {
+        requirements.entry(key).or_insert(Some(spec));
+    }
+    let today = chrono::Local::now().date_naive();
+
+    let mut found = Vec::new();
+    for name in names {
+        let releases = match crate::api::fetch_release_dates(registry, name) {
+            Ok(releases) => releases,
+            Err(e) => {
+                log::debug!("no release dates for {}: {}", name, e);
+                continue;
+            }
+        };
+        let releases = dated(&releases);
+        let mut requirement = |version: &str| {
+            let key = format!("{}@{}", name, version);
+            if let Some(spec) = requirements.get(&key) {
+                return spec.clone();
+            }
+            match crate::api::fetch_requirement(registry, name, version, base_crate) {
+                Ok(spec) => {
+                    requirements.insert(key, spec.clone());
+                    spec
+                }
+                Err(e) => {
+                    log::debug!("no dependencies for {} {}: {}", name, version, e);
+                    None
+                }
+            }
+        };
+        let adoption = adopt(&lines, &releases, &mut requirement, today);
+        if !adoption.is_empty() {
+            found.push(Adoption { dependent: name.to_string(), lines: adoption });
+        }
+    }
+
+    if let Err(e) = save_cache(&cache_path, &requirements) {
+        log::debug!("{}", e);
+    }
+    found.sort_by_key(|a| std::cmp::Reverse(a.median_days()));
+    found
+}
+
+/// Group non-prerelease versions into lines, keeping the last `LINES` after the first one
+/// (nobody adopts the line a crate started on)
+fn release_lines(releases: &[(String, String)]) -> Vec<Line> {
+    let mut lines: Vec<Line> = Vec::new();
+    for (version, date) in dated(releases) {
+        let Ok(version) = Version::parse(&version) else { continue };
+        if !version.pre.is_empty() {
+            continue;
+        }
+        let name = compat_line(&version);
+        match lines.iter_mut().find(|l| l.name == name) {
+            Some(line) => line.versions.push(version),
+            None => lines.push(Line { name, released: date, versions: vec![version] }),
+        }
+    }
+    let skip = lines.len().saturating_sub(LINES).max(1);
+    lines.split_off(skip.min(lines.len()))
+}
+
+/// Parse `YYYY-MM-DD` dates, dropping entries that don't have one
+fn dated(releases: &[(String, String)]) -> Vec<(String, NaiveDate)> {
+    releases.iter().filter_map(|(v, d)| Some((v.clone(), NaiveDate::parse_from_str(d, "%Y-%m-%d").ok()?))).collect()
+}
+
+/// Find, for each line, the dependent's first release (oldest first in `releases`) that requires
+/// that line or a later one
+fn adopt(
+    lines: &[Line],
+    releases: &[(String, NaiveDate)],
+    requirement: &mut impl FnMut(&str) -> Option<String>,
+    today: NaiveDate,
+) -> Vec<LineAdoption> {
+    let mut adoption = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        // Dependents that didn't exist yet when the line came out have nothing to adopt
+        if releases.first().is_none_or(|(_, first)| *first > line.released) {
+            continue;
+        }
+        let candidates: Vec<&(String, NaiveDate)> = releases.iter().filter(|(_, d)| *d >= line.released).collect();
+        let targets: Vec<&Version> = lines[index..].iter().flat_map(|l| &l.versions).collect();
+        // Requirements only move forward in practice, so the first accepting release can be bisected
+        let (mut low, mut high) = (0, candidates.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            let req = requirement(&candidates[mid].0).and_then(|spec| VersionReq::parse(&spec).ok());
+            if req.is_some_and(|req| targets.iter().any(|v| req.matches(v))) {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        adoption.push(match candidates.get(low) {
+            Some((version, date)) => LineAdoption {
+                line: line.name.clone(),
+                days: (*date - line.released).num_days(),
+                adopted_in: Some(version.clone()),
+            },
+            None => {
+                LineAdoption { line: line.name.clone(), days: (today - line.released).num_days(), adopted_in: None }
+            }
+        });
+    }
+    adoption
+}
+
+/// Requirements seen in earlier runs' reports, as `dependent@version` → spec
+fn history_requirements(report_dir: &Path, base_crate: &str) -> Vec<(String, String)> {
+    let Ok(entries) = std::fs::read_dir(report_dir.join(crate::history::HISTORY_DIR)) else {
+        return vec![];
+    };
+    let mut found = Vec::new();
+    for entry in entries.flatten() {
+        let Ok(report) = crate::report::import_json_report(&entry.path().join("report.json")) else { continue };
+        if report.get("crate_name").and_then(|c| c.as_str()) != Some(base_crate) {
+            continue;
+        }
+        for row in report.get("test_results").and_then(|r| r.as_array()).into_iter().flatten() {
+            let field = |name: &str| row.pointer(&format!("/primary/{}", name)).and_then(|v| v.as_str());
+            if let (Some(name), Some(version), Some(spec)) =
+                (field("dependent_name"), field("dependent_version"), field("spec"))
+                && spec != "?"
+            {
+                found.push((format!("{}@{}", name, version), spec.to_string()));
+            }
+        }
+    }
+    found
+}
+
+pub fn cache_path(report_dir: &Path, base_crate: &str) -> PathBuf {
+    report_dir.join(crate::history::HISTORY_DIR).join(format!("requirements-{}.json", base_crate))
+}
+
+pub fn load_cache(path: &Path) -> BTreeMap<String, Option<String>> {
+    std::fs::read_to_string(path).ok().and_then(|text| serde_json::from_str(&text).ok()).unwrap_or_default()
+}
+
+pub fn save_cache(path: &Path, requirements: &BTreeMap<String, Option<String>>) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(requirements).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn releases(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter().map(|(v, d)| (v.to_string(), d.to_string())).collect()
+    }
+
+    #[test]
+    fn test_adoption_of_release_lines() {
+        let base = releases(&[
+            ("0.7.0", "2020-01-01"),
+            ("0.8.0", "2021-01-01"),
+            ("0.8.5", "2021-06-01"),
+            ("0.9.0-beta.1", "2022-01-01"),
+            ("0.9.0", "2022-02-01"),
+        ]);
+        let lines = release_lines(&base);
+        assert_eq!(lines.iter().map(|l| l.name.as_str()).collect::<Vec<_>>(), vec!["0.8", "0.9"]);
+
+        let dependent = dated(&releases(&[
+            ("1.0.0", "2020-06-01"),
+            ("1.1.0", "2021-01-11"),
+            ("1.2.0", "2021-03-02"),
+            ("1.3.0", "2022-06-01"),
+        ]));
+        let specs = BTreeMap::from([("1.0.0", "^0.7"), ("1.1.0", "^0.7.2"), ("1.2.0", "0.8.0"), ("1.3.0", "^0.8.5")]);
+        let mut asked = Vec::new();
+        let mut requirement = |v: &str| {
+            asked.push(v.to_string());
+            specs.get(v).map(|s| s.to_string())
+        };
+        let today = NaiveDate::from_ymd_opt(2023, 2, 1).unwrap();
+        let adoption = adopt(&lines, &dependent, &mut requirement, today);
+        assert_eq!(
+            adoption,
+            vec![
+                LineAdoption { line: "0.8".to_string(), days: 60, adopted_in: Some("1.2.0".to_string()) },
+                LineAdoption { line: "0.9".to_string(), days: 365, adopted_in: None },
+            ]
+        );
+        assert!(asked.len() <= 4);
+        assert_eq!(Adoption { dependent: "image".to_string(), lines: adoption }.median_days(), Some(365));
+    }
+}
@@ -0,0 +1,144 @@
+/// Dependents of forks and renames of the base crate (`--alias`)
+///
+/// When a crate has been forked or republished under another name (`rgb` → `rgb2`), its
+/// dependents that moved no longer show up among the base crate's reverse dependencies
+/// at all, so a run can't tell "few users" from "users left". For each alias this looks at
+/// the alias's top dependents (as many as `--top-dependents`) and sorts them by whether
+/// their latest release still requires the base crate (both), an earlier release did
+/// (moved), or none did (fork only). Requirements are read from crates.io and cached
+/// with the adoption history's (`history/requirements-<crate>.json`).
+use crate::registry::Registry;
+use std::path::Path;
+
+/// Releases per dependent searched (newest first) for an earlier requirement on the base crate
+pub const MAX_RELEASES_CHECKED: usize = 20;
+
+/// Dependents of one alias, by relationship to the base crate
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fork {
+    pub alias: String,
+    /// Earlier releases required the base crate, the latest doesn't: (dependent, last release that did)
+    pub moved: Vec<(String, String)>,
+    /// The latest release requires both
+    pub both: Vec<String>,
+    /// Never required the base crate in the releases checked
+    pub fork_only: Vec<String>,
+}
+
+impl Fork {
+    /// "rgb2: 12 dependents moved from rgb, 3 use both, 5 only ever used rgb2"
+    pub fn summary(&self, base_crate: &str) -> String {
+        format!(
+            "{}: {} dependents moved from {}, {} use both, {} only ever used {}",
+            self.alias,
+            self.moved.len(),
+            base_crate,
+            self.both.len(),
+            self.fork_only.len(),
+            self.alias
+        )
+    }
+}
+
+/// Classify the top `limit` dependents of every alias
+pub fn find(registry: &Registry, report_dir: &Path, base_crate: &str, aliases: &[String], limit: usize) -> Vec<Fork> {
+    if aliases.is_empty() {
+        return vec![];
+    }
+    let cache_path = crate::adoption::cache_path(report_dir, base_crate);
+    let mut requirements = crate::adoption::load_cache(&cache_path);
+
+    let mut forks = Vec::new();
+    for alias in aliases {
+        let dependents = match crate::api::get_reverse_dependencies(registry, alias, Some(limit)) {
+            Ok(dependents) => dependents,
+            Err(e) => {
+                eprintln!("warning: no dependents for alias {}: {}", alias, e);
+                continue;
+            }
+        };
+        let mut fork = Fork { alias: alias.clone(), ..Fork::default() };
+        for dependent in dependents {
+            let name = dependent.name;
+            let releases = match crate::api::fetch_release_dates(registry, &name) {
+                Ok(releases) => releases.into_iter().map(|(version, _)| version).collect::<Vec<_>>(),
+                Err(e) => {
+                    log::debug!("no releases for {}: {}", name, e);
+                    continue;
+                }
+            };
+            let mut requirement = |version: &str| {
+                let key = format!("{}@{}", name, version);
+                if let Some(spec) = requirements.get(&key) {
+                    return spec.clone();
+                }
+                match crate::api::fetch_requirement(registry, &name, version, base_crate) {
+                    Ok(spec) => {
+                        requirements.insert(key, spec.clone());
+                        spec
+                    }
+                    Err(e) => {
+                        log::debug!("no dependencies for {} {}: {}", name, version, e);
+                        None
+                    }
+                }
+            };
+            match classify(&releases, &mut requirement) {
+                Relation::Both => fork.both.push(name),
+                Relation::Moved(version) => fork.moved.push((name, version)),
+                Relation::ForkOnly => fork.fork_only.push(name),
+            }
+        }
+        forks.push(fork);
+    }
+
+    if let Err(e) = crate::adoption::save_cache(&cache_path, &requirements) {
+        log::debug!("{}", e);
+    }
+    forks
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Relation {
+    Both,
+    /// The last release that required the base crate
+    Moved(String),
+    ForkOnly,
+}
+
+/// `releases` oldest first; `requirement` gives a release's requirement on the base crate
+fn classify(releases: &[String], requirement: &mut impl FnMut(&str) -> Option<String>) -> Relation {
+    let Some((latest, earlier)) = releases.split_last() else {
+        return Relation::ForkOnly;
+    };
+    if requirement(latest).is_some() {
+        return Relation::Both;
+    }
+    earlier
+        .iter()
+        .rev()
+        .take(MAX_RELEASES_CHECKED)
+        .find(|version| requirement(version).is_some())
+        .map_or(Relation::ForkOnly, |version| Relation::Moved(version.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_dependents_of_a_fork() {
+        let releases: Vec<String> = ["1.0.0", "1.1.0", "2.0.0"].iter().map(|v| v.to_string()).collect();
+        let mut asked = Vec::new();
+        let mut moved_in_2 = |v: &str| {
+            asked.push(v.to_string());
+            (v != "2.0.0").then(|| "^0.8".to_string())
+        };
+        assert_eq!(classify(&releases, &mut moved_in_2), Relation::Moved("1.1.0".to_string()));
+        assert_eq!(asked, vec!["2.0.0", "1.1.0"]);
+
+        assert_eq!(classify(&releases, &mut |_: &str| Some("^0.8".to_string())), Relation::Both);
+        assert_eq!(classify(&releases, &mut |_: &str| None), Relation::ForkOnly);
+        assert_eq!(classify(&[], &mut |_: &str| None), Relation::ForkOnly);
+    }
+}
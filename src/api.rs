@@ -1,15 +1,32 @@
+use crate::registry::Registry;
 /// API module for interacting with crates.io
 ///
 /// This module provides functions for fetching reverse dependencies,
 /// resolving versions, and downloading crate files.
+#[cfg(feature = "network")]
 use crates_io_api::SyncClient;
 use log::debug;
 use std::time::Duration;
 
+pub mod github;
+
+#[cfg(feature = "network")]
 const USER_AGENT: &str = "cargo-copter/0.3.0 (https://github.com/imazen/cargo-copter)";
 const CRATES_IO_PAGE_SIZE: usize = 100;
 const MAX_API_PAGES: usize = 100; // Safety limit: don't fetch more than 10,000 deps
 
+/// Default registry web API
+pub const CRATES_IO_API: &str = "https://crates.io/api/v1";
+
+/// Environment variable that points API and download calls at another registry API
+/// (e.g. `http://127.0.0.1:8080/api/v1` served by `cargo-copter mock-registry`)
+pub const REGISTRY_API_ENV: &str = "COPTER_REGISTRY_API";
+
+/// Why registry calls fail in builds without the `network` feature
+#[cfg(not(feature = "network"))]
+pub const OFFLINE_BUILD: &str = "this cargo-copter was built with `offline-only` (no network clients)";
+
+#[cfg(feature = "network")]
 lazy_static::lazy_static! {
     static ref CRATES_IO_CLIENT: SyncClient = {
         SyncClient::new(USER_AGENT, Duration::from_millis(1000))
@@ -17,18 +34,68 @@ lazy_static::lazy_static! {
     };
 }
 
-/// Get the shared crates.io API client
-pub fn get_client() -> &'static SyncClient {
-    &CRATES_IO_CLIENT
+/// Alternative registry API base from `COPTER_REGISTRY_API`, if set
+pub fn registry_api_override() -> Option<String> {
+    std::env::var(REGISTRY_API_ENV).ok().filter(|s| !s.trim().is_empty()).map(|s| s.trim_end_matches('/').to_string())
+}
+
+/// GET a registry API endpoint and parse the JSON body
+fn get_json(registry: &Registry, url: &str) -> Result<serde_json::Value, String> {
+    let body = registry.get(url).map_err(|e| format!("GET {} failed: {}", url, e))?;
+    serde_json::from_slice(&body).map_err(|e| format!("Invalid JSON from {}: {}", url, e))
 }
 
 /// A reverse dependency (crate that depends on our crate)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ReverseDependency {
     pub name: String,
     pub downloads: u64,
 }
 
+/// How `--top-dependents` ranks a crate's dependents
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DependentSort {
+    /// All-time downloads of the dependent version, as crates.io lists them
+    #[default]
+    AllTime,
+    /// Downloads of the dependent crate in the last 90 days
+    Recent,
+}
+
+impl DependentSort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DependentSort::AllTime => "all-time downloads",
+            DependentSort::Recent => "recent downloads",
+        }
+    }
+}
+
+/// How many dependents a crate has, and how many of them a run took
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Population {
+    /// Crates on the registry depending on the base crate
+    pub total: usize,
+    /// The top ones tested
+    pub sampled: usize,
+    pub sort: DependentSort,
+}
+
+impl Population {
+    /// "top 50 of 2345 by recent downloads"
+    pub fn describe(&self) -> String {
+        format!("top {} of {} by {}", self.sampled, self.total, self.sort.as_str())
+    }
+}
+
+/// The top dependents of a crate and the number it has in all
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DependentSample {
+    pub dependents: Vec<ReverseDependency>,
+    pub total: usize,
+}
+
 /// Get reverse dependencies with pagination and optional limiting
 ///
 /// This uses the paginated API to avoid downloading all reverse deps at once.
@@ -37,72 +104,171 @@ pub struct ReverseDependency {
 /// # Arguments
 /// * `crate_name` - The crate to find reverse dependencies for
 /// * `limit` - Maximum number of dependents to return (default: all)
-pub fn get_reverse_dependencies(crate_name: &str, limit: Option<usize>) -> Result<Vec<ReverseDependency>, String> {
+pub fn get_reverse_dependencies(
+    registry: &Registry,
+    crate_name: &str,
+    limit: Option<usize>,
+) -> Result<Vec<ReverseDependency>, String> {
+    Ok(fetch_reverse_dependencies(registry, crate_name, limit)?.dependents)
+}
+
+/// Page through reverse dependencies until `limit` distinct crates (or all of them, up to
+/// `MAX_API_PAGES` pages) are fetched; `total` is the registry's count of dependents
+fn fetch_reverse_dependencies(
+    registry: &Registry,
+    crate_name: &str,
+    limit: Option<usize>,
+) -> Result<DependentSample, String> {
     debug!("fetching reverse dependencies for {}", crate_name);
 
-    let mut all_deps = Vec::new();
+    let mut all_deps: Vec<ReverseDependency> = Vec::new();
+    let mut total = 0;
 
-    // Determine how many pages we need
-    let max_pages = match limit {
-        Some(lim) => lim.div_ceil(CRATES_IO_PAGE_SIZE),
-        None => MAX_API_PAGES,
-    };
+    // Pages to give up after: crates listed once per dependency edge can leave the
+    // pages `limit` alone needs short of `limit` distinct crates
+    let max_pages = limit.map_or(MAX_API_PAGES, |lim| lim.div_ceil(CRATES_IO_PAGE_SIZE).max(MAX_API_PAGES));
 
-    for page in 1..=max_pages {
+    let mut page = 1;
+    loop {
         debug!("fetching page {} of reverse dependencies", page);
 
-        let deps = CRATES_IO_CLIENT
-            .crate_reverse_dependencies_page(crate_name, page as u64)
-            .map_err(|e| format!("Failed to fetch reverse dependencies: {}", e))?;
+        let (deps, page_total) = match registry.custom_api_base() {
+            Some(base) => fetch_reverse_dependencies_page_with_total(registry, &base, crate_name, page)?,
+            None => crates_io_reverse_dependencies_page(crate_name, page)?,
+        };
 
-        let page_size = deps.dependencies.len();
+        let page_size = deps.len();
         debug!("got {} dependencies on page {}", page_size, page);
-
-        // Extract dependency info
-        for dep in deps.dependencies {
-            all_deps.push(ReverseDependency {
-                name: dep.crate_version.crate_name.clone(),
-                downloads: dep.crate_version.downloads,
-            });
+        // A crate depending on us in several ways (normal and dev) is listed once per edge
+        for dep in deps {
+            match all_deps.iter_mut().find(|d| d.name == dep.name) {
+                Some(seen) => seen.downloads = seen.downloads.max(dep.downloads),
+                None => all_deps.push(dep),
+            }
         }
+        total = total.max(page_total.unwrap_or(0));
 
-        // If we got less than expected, we've reached the end
-        if page_size < CRATES_IO_PAGE_SIZE {
+        // If we got less than expected, or everything there is, we've reached the end
+        if page_size < CRATES_IO_PAGE_SIZE || (page_total.is_some() && page * CRATES_IO_PAGE_SIZE >= total) {
             break;
         }
 
         // If we have enough, stop
-        if let Some(lim) = limit
-            && all_deps.len() >= lim
-        {
+        if limit.is_some_and(|lim| all_deps.len() >= lim) || page >= max_pages {
             break;
         }
+        page += 1;
     }
 
     // Sort by downloads descending
     all_deps.sort_by_key(|d| std::cmp::Reverse(d.downloads));
+    // Registries without a count: at least what was seen
+    let total = total.max(all_deps.len());
 
     // Apply limit
     if let Some(lim) = limit {
         all_deps.truncate(lim);
     }
 
-    debug!("found {} reverse dependencies for {}", all_deps.len(), crate_name);
+    debug!("found {} of {} reverse dependencies for {}", all_deps.len(), total, crate_name);
 
-    Ok(all_deps)
+    Ok(DependentSample { dependents: all_deps, total })
 }
 
-/// Get top N reverse dependencies sorted by download count
+/// Fetch one page of reverse dependencies from a registry API via plain HTTP
 ///
-/// # Arguments
-/// * `crate_name` - The crate to find reverse dependencies for
-/// * `limit` - Number of top dependents to return
-pub fn get_top_dependents(crate_name: &str, limit: usize) -> Result<Vec<ReverseDependency>, String> {
-    get_reverse_dependencies(crate_name, Some(limit))
+/// The response mirrors crates.io: `versions[]` holds the dependent crate versions.
+#[cfg(all(test, feature = "network"))]
+pub fn fetch_reverse_dependencies_page(
+    registry: &Registry,
+    crate_name: &str,
+    page: usize,
+) -> Result<Vec<ReverseDependency>, String> {
+    Ok(fetch_reverse_dependencies_page_with_total(registry, &registry.api_base(), crate_name, page)?.0)
+}
+
+/// One page of reverse dependencies and the `meta.total` count, if the registry gives one
+fn fetch_reverse_dependencies_page_with_total(
+    registry: &Registry,
+    api_base: &str,
+    crate_name: &str,
+    page: usize,
+) -> Result<(Vec<ReverseDependency>, Option<usize>), String> {
+    let url = format!(
+        "{}/crates/{}/reverse_dependencies?page={}&per_page={}",
+        api_base, crate_name, page, CRATES_IO_PAGE_SIZE
+    );
+    let json = get_json(registry, &url).map_err(|e| format!("Failed to fetch reverse dependencies: {}", e))?;
+    let versions = json.get("versions").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    let deps = versions
+        .iter()
+        .filter_map(|v| {
+            Some(ReverseDependency {
+                name: v.get("crate")?.as_str()?.to_string(),
+                downloads: v.get("downloads").and_then(|d| d.as_u64()).unwrap_or(0),
+            })
+        })
+        .collect();
+    let total = json.pointer("/meta/total").and_then(|t| t.as_u64()).map(|t| t as usize);
+    Ok((deps, total))
+}
+
+/// Recent (90-day) downloads of each crate, looked up 100 at a time through the crate list API
+fn fetch_recent_downloads(
+    registry: &Registry,
+    names: &[String],
+) -> Result<std::collections::HashMap<String, u64>, String> {
+    let mut recent = std::collections::HashMap::new();
+    for (i, batch) in names.chunks(CRATES_IO_PAGE_SIZE).enumerate() {
+        if i > 0 && registry.custom_api_base().is_none() {
+            // crates.io's crawler policy: one request per second
+            std::thread::sleep(Duration::from_millis(1000));
+        }
+        let ids: Vec<String> = batch.iter().map(|name| format!("ids[]={}", name)).collect();
+        let url = format!("{}/crates?per_page={}&{}", registry.api_base(), CRATES_IO_PAGE_SIZE, ids.join("&"));
+        let json = get_json(registry, &url)?;
+        recent.extend(parse_recent_downloads(&json));
+    }
+    Ok(recent)
+}
+
+fn parse_recent_downloads(json: &serde_json::Value) -> Vec<(String, u64)> {
+    let crates = json.get("crates").and_then(|c| c.as_array()).cloned().unwrap_or_default();
+    crates
+        .iter()
+        .filter_map(|c| Some((c.get("name")?.as_str()?.to_string(), c.get("recent_downloads")?.as_u64()?)))
+        .collect()
+}
+
+/// Top `limit` dependents by `sort`, with the size of the whole population
+///
+/// Ranking by recent downloads needs every dependent (crates.io lists them by all-time
+/// downloads), so it pages through all of them and then looks up their recent downloads;
+/// `downloads` of the result holds the recent count.
+pub fn get_top_dependents_by(
+    registry: &Registry,
+    crate_name: &str,
+    limit: usize,
+    sort: DependentSort,
+) -> Result<DependentSample, String> {
+    match sort {
+        DependentSort::AllTime => fetch_reverse_dependencies(registry, crate_name, Some(limit)),
+        DependentSort::Recent => {
+            let mut sample = fetch_reverse_dependencies(registry, crate_name, None)?;
+            let names: Vec<String> = sample.dependents.iter().map(|d| d.name.clone()).collect();
+            let recent = fetch_recent_downloads(registry, &names)?;
+            for dep in &mut sample.dependents {
+                dep.downloads = recent.get(&dep.name).copied().unwrap_or(0);
+            }
+            sample.dependents.sort_by_key(|d| std::cmp::Reverse(d.downloads));
+            sample.dependents.truncate(limit);
+            Ok(sample)
+        }
+    }
 }
 
 /// A version with its download count
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct VersionDownloads {
     pub version: String,
     pub downloads: u64,
@@ -112,22 +278,16 @@ pub struct VersionDownloads {
 /// Get all versions of a crate with download counts, sorted by downloads descending
 ///
 /// Excludes yanked and pre-release versions by default.
-pub fn get_version_downloads(crate_name: &str) -> Result<Vec<VersionDownloads>, String> {
+pub fn get_version_downloads(registry: &Registry, crate_name: &str) -> Result<Vec<VersionDownloads>, String> {
     debug!("fetching version downloads for {}", crate_name);
 
-    let krate = CRATES_IO_CLIENT
-        .get_crate(crate_name)
-        .map_err(|e| format!("Failed to fetch crate info for {}: {}", crate_name, e))?;
-
-    let mut versions: Vec<VersionDownloads> = krate
-        .versions
-        .iter()
+    let mut versions: Vec<VersionDownloads> = get_all_versions(registry, crate_name)?
+        .into_iter()
         .filter(|v| !v.yanked)
         .filter(|v| {
             // Exclude pre-release versions
-            semver::Version::parse(&v.num).map(|sv| sv.pre.is_empty()).unwrap_or(false)
+            semver::Version::parse(&v.version).map(|sv| sv.pre.is_empty()).unwrap_or(false)
         })
-        .map(|v| VersionDownloads { version: v.num.clone(), downloads: v.downloads, yanked: v.yanked })
         .collect();
 
     versions.sort_by_key(|v| std::cmp::Reverse(v.downloads));
@@ -136,17 +296,224 @@ pub fn get_version_downloads(crate_name: &str) -> Result<Vec<VersionDownloads>,
     Ok(versions)
 }
 
+/// Get every published version of a crate (including yanked and pre-releases)
+pub fn get_all_versions(registry: &Registry, crate_name: &str) -> Result<Vec<VersionDownloads>, String> {
+    if let Some(saved) = registry.offline.as_ref().and_then(|mode| mode.saved_versions(crate_name)) {
+        return saved;
+    }
+    let versions = if registry.custom_api_base().is_some() {
+        fetch_all_versions(registry, crate_name)?
+    } else if let Some(versions) = registry.mirror.as_ref().map(|m| m.fetch_versions(crate_name)).transpose()?.flatten()
+    {
+        versions
+    } else {
+        crates_io_versions(crate_name)?
+    };
+    if let Some(mode) = &registry.offline {
+        mode.record_versions(crate_name, &versions);
+    }
+    Ok(versions)
+}
+
+#[cfg(feature = "network")]
+fn crates_io_versions(crate_name: &str) -> Result<Vec<VersionDownloads>, String> {
+    let krate = CRATES_IO_CLIENT
+        .get_crate(crate_name)
+        .map_err(|e| format!("Failed to fetch crate info for {}: {}", crate_name, e))?;
+    Ok(krate
+        .versions
+        .iter()
+        .map(|v| VersionDownloads { version: v.num.clone(), downloads: v.downloads, yanked: v.yanked })
+        .collect())
+}
+
+#[cfg(not(feature = "network"))]
+fn crates_io_versions(crate_name: &str) -> Result<Vec<VersionDownloads>, String> {
+    Err(format!("Failed to fetch crate info for {}: {}", crate_name, OFFLINE_BUILD))
+}
+
+/// One page of crates.io's reverse dependencies, with the total
+#[cfg(feature = "network")]
+fn crates_io_reverse_dependencies_page(
+    crate_name: &str,
+    page: usize,
+) -> Result<(Vec<ReverseDependency>, Option<usize>), String> {
+    let response = CRATES_IO_CLIENT
+        .crate_reverse_dependencies_page(crate_name, page as u64)
+        .map_err(|e| format!("Failed to fetch reverse dependencies: {}", e))?;
+    let deps = response
+        .dependencies
+        .into_iter()
+        .map(|dep| ReverseDependency {
+            name: dep.crate_version.crate_name.clone(),
+            downloads: dep.crate_version.downloads,
+        })
+        .collect();
+    Ok((deps, Some(response.meta.total as usize)))
+}
+
+#[cfg(not(feature = "network"))]
+fn crates_io_reverse_dependencies_page(
+    crate_name: &str,
+    _page: usize,
+) -> Result<(Vec<ReverseDependency>, Option<usize>), String> {
+    Err(format!("Failed to fetch reverse dependencies of {}: {}", crate_name, OFFLINE_BUILD))
+}
+
+/// Fetch every version of a crate from the registry's API via plain HTTP
+pub fn fetch_all_versions(registry: &Registry, crate_name: &str) -> Result<Vec<VersionDownloads>, String> {
+    let url = format!("{}/crates/{}", registry.api_base(), crate_name);
+    let json = get_json(registry, &url).map_err(|e| format!("Failed to fetch crate info for {}: {}", crate_name, e))?;
+    let versions = json.get("versions").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    Ok(versions
+        .iter()
+        .filter_map(|v| {
+            Some(VersionDownloads {
+                version: v.get("num")?.as_str()?.to_string(),
+                downloads: v.get("downloads").and_then(|d| d.as_u64()).unwrap_or(0),
+                yanked: v.get("yanked").and_then(|y| y.as_bool()).unwrap_or(false),
+            })
+        })
+        .collect())
+}
+
+/// Who maintains a crate and where to reach them
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct CrateContact {
+    pub name: String,
+    pub repository: Option<String>,
+    pub issues: Option<String>,
+    /// Owner logins (users and teams)
+    pub owners: Vec<String>,
+    /// Newest non-yanked release and its date (YYYY-MM-DD)
+    pub last_release: Option<(String, String)>,
+}
+
+/// Fetch repository, owners and last release of a crate from the registry's API
+pub fn fetch_crate_contact(registry: &Registry, crate_name: &str) -> Result<CrateContact, String> {
+    let api_base = registry.api_base();
+    let info = get_json(registry, &format!("{}/crates/{}", api_base, crate_name))?;
+    // Owners are optional; mirrors and mock registries may not serve them
+    let owners = get_json(registry, &format!("{}/crates/{}/owners", api_base, crate_name)).unwrap_or_default();
+    Ok(parse_crate_contact(crate_name, &info, &owners))
+}
+
+fn parse_crate_contact(crate_name: &str, info: &serde_json::Value, owners: &serde_json::Value) -> CrateContact {
+    let repository = info
+        .pointer("/crate/repository")
+        .and_then(|r| r.as_str())
+        .map(|r| r.trim().trim_end_matches('/').trim_end_matches(".git").to_string())
+        .filter(|r| !r.is_empty());
+    let issues = repository.as_deref().and_then(issue_tracker);
+
+    let last_release = info
+        .get("versions")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|v| !v.get("yanked").and_then(|y| y.as_bool()).unwrap_or(false))
+        .filter_map(|v| Some((v.get("num")?.as_str()?, v.get("created_at")?.as_str()?)))
+        .max_by(|a, b| a.1.cmp(b.1))
+        .map(|(num, at)| (num.to_string(), at.chars().take(10).collect()));
+
+    let owners = owners
+        .get("users")
+        .and_then(|u| u.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|u| u.get("login").and_then(|l| l.as_str()).map(String::from))
+        .collect();
+
+    CrateContact { name: crate_name.to_string(), repository, issues, owners, last_release }
+}
+
+/// Release dates (YYYY-MM-DD) of every non-yanked version of a crate, oldest first
+pub fn fetch_release_dates(registry: &Registry, crate_name: &str) -> Result<Vec<(String, String)>, String> {
+    let info = get_json(registry, &format!("{}/crates/{}", registry.api_base(), crate_name))?;
+    Ok(parse_release_dates(&info))
+}
+
+fn parse_release_dates(info: &serde_json::Value) -> Vec<(String, String)> {
+    let mut releases: Vec<(String, String)> = info
+        .get("versions")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|v| !v.get("yanked").and_then(|y| y.as_bool()).unwrap_or(false))
+        .filter_map(|v| {
+            Some((v.get("num")?.as_str()?.to_string(), v.get("created_at")?.as_str()?.chars().take(10).collect()))
+        })
+        .collect();
+    releases.sort_by(|a, b| a.1.cmp(&b.1));
+    releases
+}
+
+/// The requirement one published version of `crate_name` has on `dependency` (`None`: no dependency)
+pub fn fetch_requirement(
+    registry: &Registry,
+    crate_name: &str,
+    version: &str,
+    dependency: &str,
+) -> Result<Option<String>, String> {
+    let url = format!("{}/crates/{}/{}/dependencies", registry.api_base(), crate_name, version);
+    let deps = get_json(registry, &url)?;
+    Ok(deps
+        .get("dependencies")
+        .and_then(|d| d.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|d| d.get("kind").and_then(|k| k.as_str()) != Some("dev"))
+        .find(|d| d.get("crate_id").and_then(|c| c.as_str()) == Some(dependency))
+        .and_then(|d| d.get("req")?.as_str().map(str::to_string)))
+}
+
+/// Issue tracker URL for repositories on well-known forges
+pub fn issue_tracker(repository: &str) -> Option<String> {
+    let forges = ["https://github.com/", "https://gitlab.com/", "https://codeberg.org/"];
+    forges.iter().any(|f| repository.starts_with(f)).then(|| format!("{}/issues", repository))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_crate_contact() {
+        let info = serde_json::json!({
+            "crate": {"id": "image", "repository": "https://github.com/image-rs/image.git"},
+            "versions": [
+                {"num": "0.25.6", "created_at": "2025-04-01T10:00:00Z", "yanked": true},
+                {"num": "0.25.5", "created_at": "2025-01-10T10:00:00Z", "yanked": false},
+                {"num": "0.24.9", "created_at": "2024-03-01T10:00:00Z", "yanked": false}
+            ]
+        });
+        let owners = serde_json::json!({"users": [{"login": "HeroicKatora"}, {"login": "github:image-rs:publish"}]});
+        let contact = parse_crate_contact("image", &info, &owners);
+        assert_eq!(contact.repository.as_deref(), Some("https://github.com/image-rs/image"));
+        assert_eq!(contact.issues.as_deref(), Some("https://github.com/image-rs/image/issues"));
+        assert_eq!(contact.owners, vec!["HeroicKatora", "github:image-rs:publish"]);
+        assert_eq!(contact.last_release, Some(("0.25.5".to_string(), "2025-01-10".to_string())));
+
+        let bare = parse_crate_contact(
+            "x",
+            &serde_json::json!({"crate": {"repository": "https://example.org/x"}}),
+            &serde_json::Value::Null,
+        );
+        assert_eq!(bare.issues, None);
+        assert!(bare.owners.is_empty() && bare.last_release.is_none());
+        assert_eq!(
+            parse_release_dates(&info),
+            vec![("0.24.9".to_string(), "2024-03-01".to_string()), ("0.25.5".to_string(), "2025-01-10".to_string())]
+        );
+    }
+
     // Note: These tests require network access and hit the real crates.io API
     // They are here to verify the API works but should not be run in CI
 
     #[test]
     #[ignore] // Requires network access
     fn test_get_top_dependents() {
-        let deps = get_top_dependents("serde", 5).unwrap();
+        let deps = get_top_dependents_by(&Registry::default(), "serde", 5, DependentSort::AllTime).unwrap().dependents;
         assert_eq!(deps.len(), 5);
 
         // Should be sorted by downloads descending
@@ -158,10 +525,25 @@ mod tests {
     #[test]
     #[ignore] // Requires network access
     fn test_get_reverse_dependencies_with_limit() {
-        let deps = get_reverse_dependencies("log", Some(10)).unwrap();
+        let deps = get_reverse_dependencies(&Registry::default(), "log", Some(10)).unwrap();
         assert_eq!(deps.len(), 10);
     }
 
+    #[test]
+    fn test_recent_downloads_and_population() {
+        let json = serde_json::json!({
+            "crates": [
+                {"name": "image", "downloads": 90000, "recent_downloads": 1200},
+                {"name": "ravif", "downloads": 5000, "recent_downloads": 3400},
+                {"name": "old", "downloads": 700, "recent_downloads": null}
+            ],
+            "meta": {"total": 3}
+        });
+        assert_eq!(parse_recent_downloads(&json), [("image".to_string(), 1200), ("ravif".to_string(), 3400)]);
+        let population = Population { total: 2345, sampled: 50, sort: DependentSort::Recent };
+        assert_eq!(population.describe(), "top 50 of 2345 by recent downloads");
+    }
+
     #[test]
     fn test_reverse_dependency_structure() {
         let dep = ReverseDependency { name: "test-crate".to_string(), downloads: 1000 };
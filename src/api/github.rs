@@ -0,0 +1,229 @@
+/// `--github-org`: an organization's repositories that depend on the base crate
+///
+/// Internal consumers of a crate are rarely published, so crates.io's reverse dependencies
+/// never list them. `--github-org myorg` asks the GitHub API for the org's repositories
+/// (a user account works too), keeps the Rust ones that aren't archived or forks, and reads
+/// each one's root Cargo.toml from its default branch: a repository depends on the base
+/// crate when that manifest declares it (`[workspace.dependencies]` included) or one of its
+/// workspace members' manifests does. The matching repositories are then cloned and tested
+/// like `--dependent-git` URLs.
+///
+/// Private repositories need a token: `GITHUB_TOKEN` (or `GH_TOKEN`) is sent with every API
+/// call, and cloning uses your git credentials. `GITHUB_API_URL` points at GitHub
+/// Enterprise. Without a token GitHub allows 60 API calls an hour, enough for a small org.
+use crate::registry::Registry;
+use serde_json::Value;
+
+const GITHUB_API: &str = "https://api.github.com";
+const PAGE_SIZE: usize = 100;
+/// Safety limit: don't list more than 5,000 repositories
+const MAX_PAGES: usize = 50;
+/// Workspace member manifests read per repository
+const MAX_MEMBERS: usize = 50;
+
+/// A repository whose manifests declare the base crate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrgRepository {
+    /// "myorg/viewer"
+    pub full_name: String,
+    pub clone_url: String,
+    pub default_branch: String,
+}
+
+/// The repositories of `org` that depend on `crate_name`
+pub fn discover(registry: &Registry, org: &str, crate_name: &str) -> Result<Vec<OrgRepository>, String> {
+    let client = Client::from_env(registry);
+    if client.token.is_none() {
+        eprintln!("--github-org {}: no GITHUB_TOKEN set; only public repositories, 60 API calls an hour", org);
+    }
+    let mut found = Vec::new();
+    for repo in client.rust_repositories(org)? {
+        let mut files = RepoFiles { client: &client, repo: &repo };
+        if depends_on(&mut files, crate_name) {
+            found.push(repo);
+        }
+    }
+    Ok(found)
+}
+
+/// GitHub REST calls with the token from the environment
+struct Client {
+    api: String,
+    token: Option<String>,
+    /// Refuses the calls under `--offline`
+    registry: Registry,
+}
+
+impl Client {
+    fn from_env(registry: &Registry) -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        Client {
+            api: var("GITHUB_API_URL").unwrap_or_else(|| GITHUB_API.to_string()).trim_end_matches('/').to_string(),
+            token: var("GITHUB_TOKEN").or_else(|| var("GH_TOKEN")),
+            registry: registry.clone(),
+        }
+    }
+
+    /// GET `path` under the API; `accept` picks the media type (raw file contents or JSON)
+    fn get(&self, path: &str, accept: &str) -> Result<Vec<u8>, String> {
+        let url = format!("{}/{}", self.api, path);
+        let authorization = self.token.as_ref().map(|token| format!("Bearer {}", token));
+        let mut headers = vec![("Accept", accept), ("X-GitHub-Api-Version", "2022-11-28")];
+        if let Some(authorization) = &authorization {
+            headers.push(("Authorization", authorization));
+        }
+        self.registry.get_with(&url, &headers).map_err(|e| format!("GET {} failed: {}", url, e))
+    }
+
+    fn get_json(&self, path: &str) -> Result<Value, String> {
+        let body = self.get(path, "application/vnd.github+json")?;
+        serde_json::from_slice(&body).map_err(|e| format!("Invalid JSON from {}: {}", path, e))
+    }
+
+    /// Every page of the org's repositories (or the user's, when `org` is an account)
+    fn rust_repositories(&self, org: &str) -> Result<Vec<OrgRepository>, String> {
+        let mut repos = Vec::new();
+        let mut owner = "orgs";
+        for page in 1..=MAX_PAGES {
+            let path = |owner: &str| format!("{}/{}/repos?type=all&per_page={}&page={}", owner, org, PAGE_SIZE, page);
+            let listed = match self.get_json(&path(owner)) {
+                Err(e) if page == 1 => match self.get_json(&path("users")) {
+                    Ok(listed) => {
+                        owner = "users";
+                        listed
+                    }
+                    Err(_) => return Err(e),
+                },
+                listed => listed?,
+            };
+            let count = listed.as_array().map_or(0, Vec::len);
+            repos.extend(rust_repositories(&listed));
+            if count < PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(repos)
+    }
+}
+
+/// The Rust repositories in a page of the repository list, without archived ones and forks
+fn rust_repositories(page: &Value) -> Vec<OrgRepository> {
+    let Some(repos) = page.as_array() else { return vec![] };
+    repos
+        .iter()
+        .filter(|repo| repo["language"] == "Rust" && repo["archived"] != true && repo["fork"] != true)
+        .filter_map(|repo| {
+            Some(OrgRepository {
+                full_name: repo["full_name"].as_str()?.to_string(),
+                clone_url: repo["clone_url"].as_str()?.to_string(),
+                default_branch: repo["default_branch"].as_str().unwrap_or("HEAD").to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Files of a repository at its default branch
+trait Files {
+    /// The text of the file at `path`, if there is one
+    fn file(&mut self, path: &str) -> Option<String>;
+    /// The subdirectories of `dir`, as paths from the repository root
+    fn subdirs(&mut self, dir: &str) -> Vec<String>;
+}
+
+struct RepoFiles<'a> {
+    client: &'a Client,
+    repo: &'a OrgRepository,
+}
+
+impl RepoFiles<'_> {
+    fn contents_path(&self, path: &str) -> String {
+        format!("repos/{}/contents/{}?ref={}", self.repo.full_name, path, self.repo.default_branch)
+    }
+}
+
+impl Files for RepoFiles<'_> {
+    fn file(&mut self, path: &str) -> Option<String> {
+        let body = self.client.get(&self.contents_path(path), "application/vnd.github.raw+json");
+        body.map_err(|e| log::debug!("{}: {}", self.repo.full_name, e)).ok().map(|b| String::from_utf8_lossy(&b).into())
+    }
+
+    fn subdirs(&mut self, dir: &str) -> Vec<String> {
+        let listing = self.client.get_json(&self.contents_path(dir)).unwrap_or_default();
+        let entries = listing.as_array().into_iter().flatten();
+        entries.filter(|e| e["type"] == "dir").filter_map(|e| Some(e["path"].as_str()?.to_string())).collect()
+    }
+}
+
+/// Whether the root manifest, or one of its workspace members', declares `crate_name`
+fn depends_on(files: &mut impl Files, crate_name: &str) -> bool {
+    let Some(root) = files.file("Cargo.toml").and_then(|text| toml::from_str::<toml::Value>(&text).ok()) else {
+        return false;
+    };
+    if crate::manifest::declares(&root, crate_name)
+        || root.get("workspace").and_then(|w| w.get("dependencies")).and_then(|d| d.get(crate_name)).is_some()
+    {
+        return true;
+    }
+    let members = root.get("workspace").and_then(|w| w.get("members")).and_then(|m| m.as_array());
+    let mut dirs = Vec::new();
+    for member in members.into_iter().flatten().filter_map(|m| m.as_str()) {
+        match member.strip_suffix("/*") {
+            Some(parent) => dirs.extend(files.subdirs(parent)),
+            None if !member.contains('*') => dirs.push(member.trim_end_matches('/').to_string()),
+            None => log::debug!("--github-org: skipping workspace member pattern {}", member),
+        }
+    }
+    dirs.iter().take(MAX_MEMBERS).any(|dir| {
+        let manifest =
+            files.file(&format!("{}/Cargo.toml", dir)).and_then(|text| toml::from_str::<toml::Value>(&text).ok());
+        manifest.is_some_and(|manifest| crate::manifest::declares(&manifest, crate_name))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    struct FakeRepo(BTreeMap<&'static str, &'static str>);
+
+    impl Files for FakeRepo {
+        fn file(&mut self, path: &str) -> Option<String> {
+            self.0.get(path).map(|text| text.to_string())
+        }
+
+        fn subdirs(&mut self, dir: &str) -> Vec<String> {
+            let prefix = format!("{}/", dir);
+            let dirs = self.0.keys().filter_map(|path| path.strip_prefix(&prefix)?.split_once('/').map(|(d, _)| d));
+            dirs.map(|d| format!("{}{}", prefix, d)).collect()
+        }
+    }
+
+    #[test]
+    fn test_org_repositories_declaring_the_base_crate() {
+        let page = serde_json::json!([
+            {"full_name": "myorg/viewer", "clone_url": "https://github.com/myorg/viewer.git", "default_branch": "main", "language": "Rust", "archived": false, "fork": false},
+            {"full_name": "myorg/old", "clone_url": "https://github.com/myorg/old.git", "default_branch": "main", "language": "Rust", "archived": true, "fork": false},
+            {"full_name": "myorg/site", "clone_url": "https://github.com/myorg/site.git", "default_branch": "main", "language": "TypeScript", "archived": false, "fork": false},
+        ]);
+        let repos = rust_repositories(&page);
+        assert_eq!(repos.len(), 1);
+        assert_eq!((repos[0].full_name.as_str(), repos[0].default_branch.as_str()), ("myorg/viewer", "main"));
+
+        let direct = "[package]\nname = \"viewer\"\n[target.'cfg(unix)'.dependencies]\nrgb = \"0.8\"\n";
+        assert!(depends_on(&mut FakeRepo(BTreeMap::from([("Cargo.toml", direct)])), "rgb"));
+
+        let inherited = "[workspace]\nmembers = [\"app\"]\n[workspace.dependencies]\nrgb = \"0.8\"\n";
+        assert!(depends_on(&mut FakeRepo(BTreeMap::from([("Cargo.toml", inherited)])), "rgb"));
+
+        let mut workspace = FakeRepo(BTreeMap::from([
+            ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\", \"tools/cli\"]\n"),
+            ("crates/core/Cargo.toml", "[package]\nname = \"core\"\n[dependencies]\nserde = \"1\"\n"),
+            ("crates/codec/Cargo.toml", "[package]\nname = \"codec\"\n[dev-dependencies]\nrgb = \"0.8\"\n"),
+            ("tools/cli/Cargo.toml", "[package]\nname = \"cli\"\n"),
+        ]));
+        assert!(depends_on(&mut workspace, "rgb"));
+        assert!(!depends_on(&mut workspace, "png"));
+        assert!(!depends_on(&mut FakeRepo(BTreeMap::new()), "rgb"));
+    }
+}
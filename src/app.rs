@@ -0,0 +1,945 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// The `cargo-copter` command: parse the arguments, run the matrix, print and write the reports
+use crate::error::CopterError;
+use crate::registry::Registry;
+use crate::types::*;
+use crate::{
+    ab, adoption, aliases, audit, bridge, checkpoint, cli, column_widths, compare, compile, config, console_format,
+    dependents_cache, deterministic, disk, docker, feature_usage, flakiness, history, history_export, jsonl, lanes,
+    live, messages, partial_report, project_config, quarantine, queue, reexports, report, report_diff, repro,
+    run_metadata, runner, scheduler, schema, scrub, self_test, source_cache, stage_publish, template, testsupport,
+    timing, toolchains, version, what_if,
+};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub fn main() {
+    env_logger::init();
+    compile::heartbeat::install_interrupt_handler();
+
+    // Parse CLI arguments
+    let mut args = cli::CliArgs::parse_args();
+    let console = args.console();
+    let check_release = args.apply_check_release();
+    let stage_index = args.apply_stage_publish();
+    let removal = args.apply_what_if();
+    let ab_variants = args.apply_ab();
+    if args.apply_smoke() {
+        dependents_cache::set_fresh_for(dependents_cache::SMOKE_FRESH_FOR);
+    }
+
+    let messages = match &args.messages {
+        Some(path) => messages::load(path).unwrap_or_else(|e| fail(&CopterError::Config(e), &args)),
+        None => messages::Catalog::default(),
+    };
+    let messages = Arc::new(messages);
+    args.apply_prefetch();
+    let registry = Registry::from_args(&args).unwrap_or_else(|e| fail(&e, &args));
+
+    // Utility subcommands run instead of the test matrix
+    if let Some(command) = &args.command {
+        let outcome = match command {
+            cli::CopterCommand::SelfTest { fixtures, keep } => self_test::run(fixtures.as_deref(), *keep),
+            cli::CopterCommand::MockRegistry { fixtures, port } => testsupport::serve(fixtures.as_deref(), *port),
+            cli::CopterCommand::VerifyClean { staging_dir, fix } => {
+                let audit = audit::AuditLog::new(
+                    PathBuf::from("copter-report").join(audit::AUDIT_LOG_NAME),
+                    report_scrubber(&args),
+                );
+                audit::verify_clean(&staging_dir.clone().unwrap_or_else(|| args.get_staging_dir()), *fix, &audit)
+            }
+            cli::CopterCommand::CheckRelease { .. } => unreachable!("check-release runs the test matrix"),
+            cli::CopterCommand::StagePublish { .. } => unreachable!("stage-publish runs the test matrix"),
+            cli::CopterCommand::WhatIf { .. } => unreachable!("what-if runs the test matrix"),
+            cli::CopterCommand::Ab { .. } => unreachable!("ab runs the test matrix"),
+            cli::CopterCommand::Smoke { .. } => unreachable!("smoke runs the test matrix"),
+            cli::CopterCommand::Init { path, top, yes, force } => {
+                project_config::init(&registry, path.as_deref(), *top, *yes, *force)
+            }
+            cli::CopterCommand::Worker { queue_dir, wait, jobs: 0 | 1 } => {
+                queue::work(&registry, queue_dir, &args.get_staging_dir(), *wait)
+            }
+            cli::CopterCommand::Worker { queue_dir, wait, jobs } => {
+                queue::work_in_parallel(queue_dir, &args.get_staging_dir(), *wait, *jobs)
+            }
+            cli::CopterCommand::Messages => {
+                print!("{}", messages::default_toml());
+                Ok(())
+            }
+            cli::CopterCommand::Diff { old, new } => report_diff::run(&registry, old, new, &messages),
+            cli::CopterCommand::ValidateReport { file, migrate: None } => schema::validate_file(file),
+            cli::CopterCommand::ValidateReport { file, migrate: Some(out) } => schema::migrate_file(file, out),
+            cli::CopterCommand::History {
+                action: cli::HistoryCommand::Export { format, crate_name, label, output },
+            } => history_export::export(
+                &PathBuf::from("copter-report"),
+                *format,
+                crate_name.as_deref(),
+                label.as_deref(),
+                output.as_deref(),
+                &messages,
+            ),
+        };
+        if let Err(e) = outcome {
+            console.print_error(&e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Settings from the base crate's copter.toml fill in what the command line left unset
+    if let Err(e) = project_config::load_into(&mut args) {
+        fail(&CopterError::Config(e), &args);
+    }
+
+    // Handle --docker flag: re-execute inside Docker container
+    if args.docker {
+        let original_args: Vec<String> = std::env::args().skip(1).collect();
+        match docker::run_in_docker(&original_args, &args.dependent_settings) {
+            Ok(status) => {
+                std::process::exit(status.code().unwrap_or(1));
+            }
+            Err(e) => {
+                console.print_error(&e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Validate arguments
+    if let Err(e) = args.validate() {
+        fail(&CopterError::Config(e), &args);
+    }
+
+    // Clean staging directory if requested
+    if args.clean {
+        let staging_dir = args.get_staging_dir();
+        if staging_dir.exists() {
+            match fs::remove_dir_all(&staging_dir) {
+                Ok(_) => {
+                    println!("Cleaned staging directory: {}", staging_dir.display());
+                }
+                Err(e) => {
+                    eprintln!("Warning: Failed to clean staging directory: {}", e);
+                }
+            }
+        }
+    }
+
+    // Create report directory
+    let report_dir = PathBuf::from("copter-report");
+    if let Err(e) = fs::create_dir_all(&report_dir) {
+        eprintln!("Warning: Failed to create report directory: {}", e);
+    }
+    let audit = audit::AuditLog::new(report_dir.join(audit::AUDIT_LOG_NAME), report_scrubber(&args));
+    // Append copter-report/ to .gitignore if it exists and doesn't already have it
+    let gitignore_path = PathBuf::from(".gitignore");
+    if gitignore_path.exists()
+        && let Ok(content) = fs::read_to_string(&gitignore_path)
+        && !content.lines().any(|line| line.trim() == "copter-report" || line.trim() == "copter-report/")
+    {
+        let entry = if content.ends_with('\n') { "copter-report/\n" } else { "\ncopter-report/\n" };
+        let _ = fs::OpenOptions::new().append(true).open(&gitignore_path).and_then(|mut f| {
+            use std::io::Write;
+            f.write_all(entry.as_bytes())
+        });
+    }
+
+    // Leave out dependents whose baseline keeps failing, unless they're due for a re-test
+    let today = chrono::Local::now().date_naive();
+    let mut quarantine =
+        config::base_crate_name(&args).map(|name| (quarantine::Quarantine::load(&report_dir, &name), name));
+    let quarantined = match &quarantine {
+        Some((q, _)) if args.quarantine_after > 0 && !args.include_quarantined => q.apply(&mut args, today),
+        _ => vec![],
+    };
+
+    // Build test matrix
+    let mut matrix = match config::build_test_matrix(&args, &registry) {
+        Ok(m) => m,
+        Err(e) => fail(&e.context("Configuration error"), &args),
+    };
+    matrix.quarantined = quarantined;
+    matrix.audit = audit;
+    let publish_manifest = match stage_index.as_deref().map(|index| stage_publish::prepare(&matrix, index)).transpose()
+    {
+        Ok(staged) => staged.map(|(registry, manifest)| {
+            matrix.staging_registry = Some(registry);
+            manifest
+        }),
+        Err(e) => {
+            console.print_error(&e);
+            std::process::exit(1);
+        }
+    };
+    let head_to_head = match ab_variants.map(|(a, b)| ab::prepare(&mut matrix, &a, &b)).transpose() {
+        Ok(head_to_head) => head_to_head,
+        Err(e) => {
+            console.print_error(&e);
+            std::process::exit(1);
+        }
+    };
+    if let Some(removal) = &removal
+        && let Err(e) = what_if::prepare(&mut matrix, removal)
+    {
+        console.print_error(&e);
+        std::process::exit(1);
+    }
+    if let Some(image) = &args.docker_steps {
+        match docker::StepSandbox::new(image, &matrix.staging_dir, local_base_dirs(&matrix)) {
+            Ok(sandbox) => matrix.cross.sandbox = Some(sandbox),
+            Err(e) => fail(&CopterError::Config(e), &args),
+        }
+    }
+
+    // Don't silently redo hours of identical work
+    if !args.force
+        && !args.resume
+        && let Some(prior) =
+            history::find(&report_dir, &history::fingerprint(&matrix, &history::latest_versions(&matrix)))
+        && !history::confirm_rerun(console, &prior, &report_dir, args.skip_if_seen)
+    {
+        std::process::exit(if prior.regressed > 0 { -2 } else { 0 });
+    }
+
+    // stage-publish: the WIP rows resolve from the registry, so it has to be there first
+    if let (Some(manifest), Some(registry)) = (&publish_manifest, &matrix.staging_registry)
+        && let Err(e) = stage_publish::publish(manifest, &registry.index)
+    {
+        console.print_error(&e);
+        std::process::exit(1);
+    }
+
+    // Fetch the reference report before spending hours testing
+    let reference_rows =
+        match args.compare_with.as_deref().map(|source| compare::load_reference(&registry, source)).transpose() {
+            Ok(rows) => rows,
+            Err(e) => fail(&e.context("--compare-with"), &args),
+        };
+
+    // Warn up front if the run is unlikely to fit in the staging filesystem
+    disk::preflight(&matrix);
+
+    // Initialize table widths for console output (only needed for table format)
+    let version_strs: Vec<String> = matrix.base_versions.iter().map(|v| v.crate_ref.version.display()).collect();
+    let display_version = version_strs.first().map(|s| s.as_str()).unwrap_or("unknown");
+    let force_versions = matrix.base_versions.iter().any(|v| v.override_mode == OverrideMode::Force);
+
+    let simple_mode = args.simple;
+    let jsonl = console.jsonl;
+    let base_crate = matrix.base_crate.clone();
+    let observed = if simple_mode { None } else { column_widths::load(&report_dir, &matrix.base_crate) };
+    let mut layout = report::table_layout(
+        console_format::terminal_width(args.console_width),
+        &version_strs,
+        display_version,
+        force_versions,
+        observed.as_ref(),
+        messages.clone(),
+    )
+    .with_time_columns(args.timestamps, args.step_times)
+    .with_diagnostics_column(args.diagnostic_counts)
+    .with_status_style(args.status_style)
+    .with_color_scheme(args.colorscheme);
+
+    if let Some(gates) = &matrix.nightly_gates {
+        console.say(&format!("Nightly gates: {}", gates.describe()));
+    }
+
+    // Distributed and --parallel runs finish out of order: a line per dependent, updated in
+    // place, and the table once all results are in
+    let board = if simple_mode || jsonl || args.check_first || args.resume {
+        None
+    } else {
+        let dependents =
+            matrix.dependents.iter().map(|d| (d.crate_ref.name.clone(), d.crate_ref.version.display())).collect();
+        let parallel = (args.backend != cli::Backend::Local && args.queue_dir.is_some()) || args.parallel > 1;
+        live::Board::start(args.progress, parallel, dependents, matrix.base_versions.len(), messages.clone())
+    };
+    let print_header = |layout: &console_format::Layout| {
+        let test_plan = format_test_plan_string(&matrix);
+        let this_path = matrix.base_versions.iter().find_map(|v| match &v.crate_ref.source {
+            CrateSource::Local { path } => Some(path.display().to_string()),
+            _ => None,
+        });
+        report::print_table_header(
+            layout,
+            &matrix.base_crate,
+            display_version,
+            matrix.dependents.len(),
+            Some(&test_plan),
+            this_path.as_deref(),
+        );
+    };
+
+    if simple_mode {
+        // Simple output header with list of all dependents
+        let dependent_names: Vec<String> = matrix
+            .dependents
+            .iter()
+            .map(|d| format!("{}:{}", d.crate_ref.name, d.crate_ref.version.display()))
+            .collect();
+        report::print_simple_header(&matrix.base_crate, display_version, &dependent_names, &version_strs);
+    } else if jsonl {
+        let rows = matrix.test_count() * matrix.toolchains.len().max(1);
+        jsonl::emit(&jsonl::start_line(&matrix.base_crate, matrix.dependents.len(), rows));
+    } else if board.is_none() && !args.collapse_identical {
+        // Table output header
+        print_header(&layout);
+    }
+
+    // Run tests with streaming output
+    let mut offered_rows = Vec::new();
+    let mut prev_dependent: Option<String> = None;
+    let mut error_clusters = report::ErrorClusters::default();
+    let flakiness = flakiness::Flakiness::load(&report_dir, &matrix.base_crate);
+    let report_dir_clone = report_dir.clone();
+    let staging_dir = matrix.staging_dir.clone();
+    let registry = matrix.registry.clone();
+
+    // For simple mode: buffer results per dependent
+    let mut current_dependent_results = report::DependentResults::default();
+
+    // Failed steps are written to copter-failures.log and logs/<dependent>-<version>/ by one writer thread
+    let scrubber = report_scrubber(&args);
+    let failure_log = compile::failure_log::FailureLogWriter::start(&report_dir, scrubber.clone(), args.save_artifacts);
+    let worker_log = failure_log.log();
+
+    // --resume reads the last run's checkpoint before this run starts replacing it
+    let previous = match args.resume.then(|| checkpoint::load(&report_dir, &matrix)) {
+        Some(Err(e)) => {
+            console.print_error(&e);
+            std::process::exit(1);
+        }
+        previous => previous.and_then(Result::ok),
+    };
+    let mut checkpoint = checkpoint::Writer::create(&report_dir, &matrix, args.save_artifacts)
+        .map_err(|e| eprintln!("Warning: no checkpoint for --resume: {}", e))
+        .ok();
+
+    // report.md grows as dependents finish, so a killed run still leaves one
+    let mut partial_report = partial_report::PartialReport::create(
+        &report_dir.join("report.md"),
+        &matrix.base_crate,
+        display_version,
+        matrix.dependents.len(),
+        matrix.base_versions.len(),
+        &run_metadata::RunMetadata { label: args.label.clone(), ..run_metadata::RunMetadata::collect(&matrix) },
+        scrubber.clone(),
+    )
+    .map(|partial| partial.with_style(layout.status_style).with_messages(layout.messages.clone()))
+    .map_err(|e| eprintln!("Warning: no partial report.md during the run: {}", e))
+    .ok();
+
+    let mut on_result = |result: &TestResult| {
+        if let Some(writer) = &mut checkpoint
+            && let Err(e) = writer.record(result)
+        {
+            eprintln!("Warning: {}", e);
+            checkpoint = None;
+        }
+
+        // Convert to OfferedRow immediately
+        let row = bridge::test_result_to_offered_row(result);
+        if let Some(partial) = &mut partial_report
+            && let Err(e) = partial.record(&row)
+        {
+            eprintln!("Warning: Failed to update report.md: {}", e);
+            partial_report = None;
+        }
+
+        if jsonl {
+            jsonl::emit(&jsonl::row_line(&row));
+        } else if simple_mode {
+            // Simple output mode - buffer results until all versions for dependent are tested
+            // Check if we've moved to a new dependent
+            if current_dependent_results.dependent_name != row.primary.dependent_name
+                || current_dependent_results.dependent_version != row.primary.dependent_version
+            {
+                // Print previous dependent's results if any
+                if !current_dependent_results.dependent_name.is_empty() {
+                    report::print_simple_dependent_result(
+                        &current_dependent_results,
+                        &base_crate,
+                        &flakiness,
+                        &messages,
+                    );
+                }
+                // Start new dependent
+                current_dependent_results = report::DependentResults {
+                    dependent_name: row.primary.dependent_name.clone(),
+                    dependent_version: row.primary.dependent_version.clone(),
+                    baseline: None,
+                    offered_versions: Vec::new(),
+                };
+            }
+
+            // Add to current dependent's results
+            if row.offered.is_none() {
+                current_dependent_results.baseline = Some(row.clone());
+            } else {
+                current_dependent_results.offered_versions.push(row.clone());
+            }
+        } else if let Some(board) = &board {
+            board.record(&row);
+        } else if args.collapse_identical {
+            // Identical regressions are only known once every row is in: the table comes at the end
+        } else {
+            // Table output mode
+            // Print separator between different dependents (a resize already reopened the table)
+            if !report::follow_terminal_resize(&mut layout, console_format::terminal_width(args.console_width))
+                && let Some(ref prev) = prev_dependent
+                && *prev != row.primary.dependent_name
+            {
+                report::print_separator_line(&layout);
+            }
+
+            // Determine if this is the last row for this dependent
+            // (We can't know this in streaming mode, so always pass false)
+            let is_last = false;
+
+            // Print the row immediately
+            let note = flakiness.note(&row);
+            report::print_offered_row(&layout, &row, is_last, &mut error_clusters, args.error_lines, note.as_deref());
+        }
+
+        // Write failure log for failed tests
+        if !result.execution.is_success() {
+            report::write_failure_log(&report_dir_clone, &staging_dir, result, scrubber.as_ref(), args.save_artifacts);
+            if let Err(e) = repro::write_scripts(&registry, &report_dir_clone, &staging_dir, result, scrubber.as_ref())
+            {
+                eprintln!("Warning: {}", e);
+            }
+        }
+
+        // Update tracking
+        prev_dependent = Some(row.primary.dependent_name.clone());
+
+        // Save for later report generation
+        offered_rows.push(row);
+    };
+    let cpu_before = timing::children_cpu_secs();
+    let test_started = std::time::Instant::now();
+    let outcomes = match args.schedule {
+        scheduler::Order::Priority => flakiness::load_outcomes(&report_dir, Some(&matrix.base_crate)),
+        scheduler::Order::Listed => vec![],
+    };
+    let mut run_pass = |pass: &TestMatrix, on_result: &mut dyn FnMut(&TestResult)| match (args.backend, &args.queue_dir)
+    {
+        (cli::Backend::Local, _) | (_, None) => {
+            let scheduler = scheduler::build(args.schedule, args.parallel, pass, &outcomes);
+            runner::run_with(pass.clone(), scheduler, &runner::Local { failure_log: Some(&worker_log) }, on_result)
+        }
+        (backend, Some(queue_dir)) => {
+            let timeout = std::time::Duration::from_secs(args.queue_timeout * 60);
+            queue::run_distributed(pass, backend, queue_dir, args.queue_workers, timeout, on_result)
+                .map_err(|e| CopterError::Internal(e).context("distributed run"))
+        }
+    };
+    let outcome = match previous {
+        Some(previous) => {
+            let from = args.from.unwrap_or(CommandType::Test);
+            lanes::resume(&matrix, previous, from, &mut run_pass, &mut on_result)
+        }
+        None if args.check_first => lanes::run(&matrix, &mut run_pass, &mut on_result),
+        None if !matrix.toolchains.is_empty() => {
+            toolchains::run(&matrix, &matrix.toolchains, &mut run_pass, &mut on_result)
+        }
+        None => run_pass(&matrix, &mut on_result),
+    };
+    if let Some(partial) = partial_report.take()
+        && let Err(e) = partial.finish()
+    {
+        eprintln!("Warning: Failed to update report.md: {}", e);
+    }
+    let test_results = match outcome {
+        Ok(results) => results,
+        Err(e) => fail(&e.context("Test execution failed"), &args),
+    };
+    // Both passes were streamed; the reports only keep each dependent's final rows
+    if args.check_first || args.resume {
+        offered_rows = test_results.iter().map(bridge::test_result_to_offered_row).collect();
+    }
+    drop(worker_log);
+    failure_log.finish();
+
+    let table_at_end = board.is_some() || (args.collapse_identical && !simple_mode && !jsonl);
+    if let Some(board) = board {
+        board.finish();
+        println!();
+    }
+    if table_at_end {
+        print_header(&layout);
+        let position = |name: &str| matrix.dependents.iter().position(|d| d.crate_ref.name == name);
+        let mut rows: Vec<&OfferedRow> = offered_rows.iter().collect();
+        rows.sort_by_key(|r| (position(&r.primary.dependent_name), r.offered.is_some()));
+        let rows = if args.collapse_identical {
+            report::collapse_identical(&rows)
+        } else {
+            rows.into_iter().map(|row| (row, None)).collect()
+        };
+        for (i, (row, collapsed)) in rows.iter().enumerate() {
+            if i > 0 && rows[i - 1].0.primary.dependent_name != row.primary.dependent_name {
+                report::print_separator_line(&layout);
+            }
+            let note = match (flakiness.note(row), collapsed) {
+                (Some(flaky), Some(collapsed)) => Some(format!("{}; {}", flaky, collapsed)),
+                (flaky, collapsed) => flaky.or_else(|| collapsed.clone()),
+            };
+            report::print_offered_row(&layout, row, false, &mut error_clusters, args.error_lines, note.as_deref());
+        }
+    }
+
+    // Print final dependent's results in simple mode
+    if simple_mode && !current_dependent_results.dependent_name.is_empty() {
+        report::print_simple_dependent_result(&current_dependent_results, &base_crate, &flakiness, &messages);
+    }
+
+    // Write combined log file (for simple mode, also useful for table mode)
+    let mut run_metadata = run_metadata::RunMetadata::collect(&matrix);
+    run_metadata.label = args.label.clone();
+    run_metadata.wall_secs = Some(test_started.elapsed().as_secs_f64());
+    run_metadata.cargo_cpu_secs = cpu_before.zip(timing::children_cpu_secs()).map(|(before, after)| after - before);
+    // --scrub: what gets published is scrubbed as it is written; history and the checkpoint keep the real values
+    let published = scrubber.as_ref().map(|s| (s.rows(&offered_rows), s.run(&run_metadata)));
+    let (published_rows, published_run) =
+        published.as_ref().map_or((offered_rows.as_slice(), &run_metadata), |(rows, run)| (rows.as_slice(), run));
+    let combined_log_path =
+        report::write_combined_log(&report_dir, published_rows, &base_crate, published_run, args.save_artifacts);
+
+    if jsonl {
+        jsonl::emit(&jsonl::summary_line(&report::summarize_offered_rows(&offered_rows)));
+    } else if simple_mode {
+        // Simple mode: print simple summary with regressions listed
+        report::print_simple_summary(&offered_rows, &report_dir, &base_crate, &combined_log_path, &messages);
+    } else {
+        // Table mode: print table footer
+        report::print_table_footer(&layout);
+    }
+
+    // Generate non-console reports (markdown, JSON) - always do this
+    let tables = !simple_mode && !jsonl;
+    generate_non_console_reports(&offered_rows, &args, &matrix, &layout, &run_metadata, &report_dir, tables);
+    if check_release {
+        let comment_path = report_dir.join("pr-comment.md");
+        let comment_rows = args.deterministic.then(|| deterministic::Normalizer::new(&matrix).rows(published_rows));
+        match report::export_pr_comment(
+            comment_rows.as_deref().unwrap_or(published_rows),
+            &comment_path,
+            &matrix.base_crate,
+            display_version,
+            matrix.dependents.len(),
+            published_run,
+            &messages,
+        ) {
+            Ok(()) => console.say(&format!("PR comment: {}", comment_path.display())),
+            Err(e) => eprintln!("Warning: Failed to save PR comment: {}", e),
+        }
+    }
+
+    if let Some(head_to_head) = &head_to_head {
+        let ab_path = report_dir.join("ab.md");
+        match ab::export(published_rows, &matrix.base_crate, head_to_head, &ab_path, &messages) {
+            Ok(()) => console.say(&format!("A/B report: {}", ab_path.display())),
+            Err(e) => eprintln!("Warning: Failed to save A/B report: {}", e),
+        }
+        console.say(&ab::Comparison::from_rows(&offered_rows, head_to_head).summary_line(head_to_head));
+    }
+
+    // If using top-dependents and there were failures, suggest a targeted re-test
+    if args.dependents.is_empty() && args.dependent_paths.is_empty() {
+        suggest_failed_retest(&offered_rows, &args, &matrix);
+    }
+
+    // Determine exit code
+    let summary = report::summarize_offered_rows(&offered_rows);
+    if let Err(e) = history::record(
+        &report_dir,
+        &history::RunRecord {
+            label: args.label.clone(),
+            ..history::RunRecord::new(&matrix, &summary, &history::tested_versions(&offered_rows))
+        },
+    ) {
+        eprintln!("Warning: Failed to record run history: {}", e);
+    }
+    if let Err(e) = flakiness::record(&report_dir, &matrix, &offered_rows, &run_metadata) {
+        eprintln!("Warning: {}", e);
+    }
+    if !simple_mode
+        && !offered_rows.is_empty()
+        && let Err(e) = column_widths::save(
+            &report_dir,
+            &matrix.base_crate,
+            &column_widths::ObservedWidths::from_rows(&offered_rows),
+        )
+    {
+        eprintln!("Warning: {}", e);
+    }
+    if let Some((q, name)) = &mut quarantine {
+        for change in q.update(&offered_rows, args.quarantine_after, today) {
+            console.say(&format!("Quarantine: {}", change));
+        }
+        if let Err(e) = q.save(&report_dir, name) {
+            eprintln!("Warning: {}", e);
+        }
+    }
+    let failed = match (&args.compare_with, &reference_rows) {
+        (Some(source), Some(reference)) => {
+            let gate = compare::RegressionGate::new(source, &offered_rows, reference);
+            gate.print(console);
+            gate.has_new_regressions()
+        }
+        _ => summary.regressed > 0,
+    };
+    let failed = if args.fail_on_skipped && summary.skipped > 0 {
+        console
+            .say(&format!("\n--fail-on-skipped: {} offered row(s) never tested the offered version", summary.skipped));
+        true
+    } else {
+        failed
+    };
+    let flaky: Vec<String> = offered_rows
+        .iter()
+        .filter(|row| row.is_regression())
+        .filter_map(|row| Some(format!("{} {}", row.primary.dependent_name, flakiness.note(row)?)))
+        .collect();
+    if !flaky.is_empty() {
+        console.say("\nRegressions in historically flaky dependents (re-run before acting on them):");
+        for line in &flaky {
+            console.say(&format!("  {}", line));
+        }
+    }
+    if let Some(hazard) = version::SemverHazard::find(&matrix, &offered_rows) {
+        console.say(&format!("\nSemver hazard: {}", hazard.message()));
+        console.say(&format!("  Regressed with a compatible requirement: {}", hazard.broken.join(", ")));
+    }
+    if !matrix.toolchains.is_empty() {
+        console.say("\nBy toolchain:");
+        for line in toolchains::summary_lines(&offered_rows, &matrix.toolchains) {
+            console.say(&format!("  {}", line));
+        }
+    }
+    let exit_code = if failed { -2 } else { 0 };
+
+    std::process::exit(exit_code);
+}
+
+/// `--scrub` / `--scrub-env`: the replacements for the published reports and logs
+fn report_scrubber(args: &cli::CliArgs) -> Option<scrub::Scrubber> {
+    (args.scrub || !args.scrub_env.is_empty()).then(|| scrub::Scrubber::from_env(&args.scrub_env))
+}
+
+/// Directories of local offered versions and their path-dependency siblings (`--docker-steps`)
+fn local_base_dirs(matrix: &TestMatrix) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for spec in &matrix.base_versions {
+        if let CrateSource::Local { path } = &spec.crate_ref.source {
+            let dir = if path.ends_with("Cargo.toml") { path.parent().unwrap_or(path) } else { path };
+            dirs.extend(compile::discover_path_dep_siblings(dir).into_iter().map(|(_, sibling)| sibling));
+            dirs.push(dir.to_path_buf());
+        }
+    }
+    dirs
+}
+
+/// Report a failure of copter itself and exit with its kind's code; with `--json` the
+/// error goes to stdout as JSON
+fn fail(e: &CopterError, args: &cli::CliArgs) -> ! {
+    if args.json {
+        println!("{}", e.to_json());
+    } else {
+        args.console().print_error(&e.to_string());
+    }
+    std::process::exit(e.exit_code());
+}
+
+/// Generate non-console reports (markdown, JSON) and comparison table
+fn generate_non_console_reports(
+    rows: &[OfferedRow],
+    args: &cli::CliArgs,
+    matrix: &TestMatrix,
+    layout: &console_format::Layout,
+    run: &run_metadata::RunMetadata,
+    report_dir: &std::path::Path,
+    tables: bool,
+) {
+    let console = args.console();
+    // Export markdown report
+    let markdown_path = report_dir.join("report.md");
+    let test_plan = format_test_plan_string(matrix);
+    let reexporters = reexports::find(matrix, rows);
+    let adoption = adoption::regressed(&matrix.registry, report_dir, &matrix.base_crate, rows);
+    let forks = aliases::find(&matrix.registry, report_dir, &matrix.base_crate, &args.aliases, args.top_dependents);
+    let this_path = matrix.base_versions.iter().find_map(|v| match &v.crate_ref.source {
+        CrateSource::Local { path } => Some(path.display().to_string()),
+        _ => None,
+    });
+
+    // --deterministic: the exports get sorted rows without times or machine paths
+    let normalizer = args.deterministic.then(|| deterministic::Normalizer::new(matrix));
+    let normalized = normalizer.as_ref().map(|n| (n.rows(rows), n.run(run)));
+    let (export_rows, export_run) = normalized.as_ref().map_or((rows, run), |(rows, run)| (rows.as_slice(), run));
+    let this_path = this_path.map(|path| match &normalizer {
+        Some(n) => n.text(&path),
+        None => path,
+    });
+    let scrubber = report_scrubber(args);
+    let scrubbed = scrubber.as_ref().map(|s| (s.rows(export_rows), s.run(export_run)));
+    let (export_rows, export_run) =
+        scrubbed.as_ref().map_or((export_rows, export_run), |(rows, run)| (rows.as_slice(), run));
+    let this_path = this_path.map(|path| match &scrubber {
+        Some(s) => s.text(&path),
+        None => path,
+    });
+
+    // report.md's table has a fixed width, whatever the terminal's
+    if let Err(e) = report::export_markdown_table_report(
+        export_rows,
+        &markdown_path,
+        &layout.clone().with_width(console_format::DEFAULT_TERMINAL_WIDTH),
+        &matrix.base_crate,
+        &matrix.base_versions.first().map(|v| v.crate_ref.version.display()).unwrap_or_else(|| "unknown".to_string()),
+        matrix.dependents.len(),
+        Some(&test_plan),
+        this_path.as_deref(),
+        &report::regressed_contacts(&matrix.registry, export_rows),
+        &reexporters,
+        &adoption,
+        &forks,
+        export_run,
+    ) {
+        eprintln!("Warning: Failed to save markdown report: {}", e);
+    }
+
+    // Export JSON report
+    let json_path = report_dir.join("report.json");
+    if let Err(e) = report::export_json_report(
+        export_rows,
+        &json_path,
+        &matrix.base_crate,
+        &matrix.base_versions.first().map(|v| v.crate_ref.version.display()).unwrap_or_else(|| "unknown".to_string()),
+        matrix.dependents.len(),
+        export_run,
+        args.save_artifacts,
+        args.json_error_limit,
+        &layout.messages,
+    ) {
+        eprintln!("Warning: Failed to save JSON report: {}", e);
+    }
+    let regressions_path = report_dir.join("regressions.json");
+    if let Err(e) = report::export_regressions_json(export_rows, &regressions_path, &matrix.base_crate, export_run) {
+        eprintln!("Warning: Failed to save regressions report: {}", e);
+    }
+    if let Err(e) = report::export_junit_report(
+        export_rows,
+        &report_dir.join("junit.xml"),
+        &matrix.base_crate,
+        export_run,
+        args.save_artifacts,
+        &layout.messages,
+    ) {
+        eprintln!("Warning: Failed to save JUnit report: {}", e);
+    }
+    if args.github
+        && let Err(e) = report::github::emit(
+            console,
+            export_rows,
+            &matrix.base_crate,
+            &matrix
+                .base_versions
+                .first()
+                .map(|v| v.crate_ref.version.display())
+                .unwrap_or_else(|| "unknown".to_string()),
+            matrix.dependents.len(),
+            export_run,
+            &layout.messages,
+        )
+    {
+        eprintln!("Warning: {}", e);
+    }
+
+    // Custom reports from user templates, with the JSON report as context
+    if !args.report_template.is_empty() {
+        let context = report::json_report(
+            export_rows,
+            &matrix.base_crate,
+            &matrix
+                .base_versions
+                .first()
+                .map(|v| v.crate_ref.version.display())
+                .unwrap_or_else(|| "unknown".to_string()),
+            matrix.dependents.len(),
+            export_run,
+            args.save_artifacts,
+            &layout.messages,
+        );
+        for template_path in &args.report_template {
+            match template::render_file(template_path, &context, report_dir) {
+                Ok(path) => console.say(&format!("Custom report: {}", path.display())),
+                Err(e) => eprintln!("Warning: Failed to render report template {}", e),
+            }
+        }
+    }
+
+    if tables {
+        // Print comparison table (only for table mode - simple mode has its own summary)
+        let comparison_stats = report::generate_comparison_table(rows);
+        report::print_comparison_table(&comparison_stats);
+    }
+
+    // Print compatibility report (table mode only — simple mode has its own summary)
+    if tables {
+        let compat_report = report::build_compatibility_report(rows);
+        report::print_compatibility_report(&compat_report, report_dir, &layout.messages);
+    }
+
+    if !adoption.is_empty() {
+        let slowest: Vec<String> =
+            adoption.iter().filter_map(|a| Some(format!("{} ({} days)", a.dependent, a.median_days()?))).collect();
+        console.say(&format!("Slowest to adopt past {} releases (median): {}", matrix.base_crate, slowest.join(", ")));
+    }
+    for fork in &forks {
+        console.say(&format!("Forks and renames: {}", fork.summary(&matrix.base_crate)));
+    }
+    for line in timing::Timing::from_rows(rows, run.wall_secs, run.cargo_cpu_secs).summary_lines() {
+        console.say(&line);
+    }
+    if let Some(line) = feature_usage::FeatureUsage::from_rows(rows).summary_line(&matrix.base_crate) {
+        console.say(&line);
+    }
+    if let Some((wip, without)) = what_if::versions(matrix) {
+        console.say(&what_if::summary_line(rows, &wip, &without));
+    }
+    if let Some(line) = source_cache::summary_line() {
+        console.say(&line);
+    }
+    if let Some(line) = matrix.registry.offline.as_ref().and_then(crate::offline::Mode::summary_line) {
+        console.say(&line);
+    }
+    if !reexporters.is_empty() {
+        let names: Vec<String> = reexporters.iter().map(|r| format!("{} {}", r.name, r.version)).collect();
+        console.say(&format!(
+            "Re-exporters: {} expose {} items publicly (see report.md)",
+            names.join(", "),
+            matrix.base_crate
+        ));
+    }
+}
+
+/// Format test plan as a string
+fn format_test_plan_string(matrix: &TestMatrix) -> String {
+    let deps_display: Vec<String> = matrix
+        .dependents
+        .iter()
+        .take(5)
+        .map(|d| {
+            let version = d.crate_ref.version.display();
+            if version == "latest" { d.crate_ref.name.clone() } else { format!("{}:{}", d.crate_ref.name, version) }
+        })
+        .collect();
+
+    let mut more_deps = if matrix.dependents.len() > 5 {
+        format!(" ... and {} more", matrix.dependents.len() - 5)
+    } else {
+        String::new()
+    };
+    if let Some(population) = &matrix.population {
+        more_deps.push_str(&format!(" ({})", population.describe()));
+    }
+
+    let mut versions_display = vec!["baseline".to_string()];
+    for version_spec in &matrix.base_versions {
+        let version_str = version_spec.crate_ref.version.display();
+        if version_spec.override_mode == OverrideMode::Force {
+            versions_display.push(format!("{} [!]", version_str));
+        } else {
+            versions_display.push(version_str);
+        }
+    }
+
+    format!("  Dependents: {}{}\n  Versions:   {}", deps_display.join(", "), more_deps, versions_display.join(", "))
+}
+
+/// Suggest a command to re-test only the failed dependents
+fn suggest_failed_retest(rows: &[OfferedRow], args: &cli::CliArgs, matrix: &TestMatrix) {
+    let console = args.console();
+    // Collect dependents that had any failures
+    let mut failed_dependents: std::collections::HashSet<String> = std::collections::HashSet::new();
+
+    for row in rows {
+        // Check if this dependent had any failures (regression or baseline failed)
+        let failed = match row.baseline_passed {
+            Some(true) => !row.test.all_passed(), // Regression: baseline passed, offered failed
+            Some(false) => true,                  // Baseline already broken
+            None => !row.test.all_passed(),       // This IS the baseline and it failed
+        };
+
+        if failed {
+            failed_dependents.insert(row.primary.dependent_name.clone());
+        }
+    }
+
+    // If there are failures and some passed, suggest a focused re-test
+    if !failed_dependents.is_empty() && failed_dependents.len() < matrix.dependents.len() {
+        console.say("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        console.say(&format!("💡 To re-test only the {} failed dependent(s):", failed_dependents.len()));
+        console.say("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+
+        // Build the command
+        let mut cmd = String::from("cargo-copter");
+
+        // Add path or crate argument
+        if let Some(ref path) = args.path {
+            cmd.push_str(&format!(" --path {}", path.display()));
+            if let Some(ref package) = args.package {
+                cmd.push_str(&format!(" --package {}", package));
+            }
+        } else if let Some(ref crate_name) = args.crate_name {
+            cmd.push_str(&format!(" --crate {}", crate_name));
+        }
+
+        // Add test-versions if specified
+        if !args.test_versions.is_empty() {
+            cmd.push_str(" --test-versions");
+            for v in &args.test_versions {
+                cmd.push_str(&format!(" {}", v));
+            }
+        }
+
+        // Add force-versions if specified
+        if !args.force_versions.is_empty() {
+            cmd.push_str(" --force-versions");
+            for v in &args.force_versions {
+                cmd.push_str(&format!(" {}", v));
+            }
+        }
+
+        // Add the failed dependents
+        cmd.push_str(" --dependents");
+        let mut sorted_failed: Vec<_> = failed_dependents.iter().collect();
+        sorted_failed.sort();
+        for dep in sorted_failed {
+            cmd.push_str(&format!(" {}", dep));
+        }
+
+        // Add other relevant flags
+        if args.skip_normal_testing {
+            cmd.push_str(" --skip-normal-testing");
+        }
+        if args.error_lines != 10 {
+            cmd.push_str(&format!(" --error-lines {}", args.error_lines));
+        }
+
+        console.say(&format!("  {}\n", cmd));
+    }
+}
@@ -0,0 +1,118 @@
+/// `--save-artifacts`: how much of each failed step's output is written to copter-report/
+///
+/// Rows keep a failed step's whole message in memory (compiler diagnostics, or all of stderr
+/// when cargo printed none) for the console and report.md. What reaches disk — report.json,
+/// failures.log, checkpoint.jsonl, the per-result `.txt` logs and `copter-failures.log`/
+/// `logs/` — follows this level instead, so a dependent whose build script compiles a C
+/// library can't put megabytes of compiler noise into report.json unless asked to.
+use crate::compile::CompileResult;
+use crate::types::{OfferedRow, TestResult};
+
+/// Lines of stderr kept when `diagnostics` drops the rest
+const MAX_ERROR_LINES: usize = 20;
+
+/// What a failed step leaves in the report directory
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SaveArtifacts {
+    /// Nothing: no failure logs, empty error messages in report.json
+    None,
+    /// Compiler diagnostics; steps without any keep only stderr's `error` lines
+    Diagnostics,
+    /// Diagnostics, or all of stderr when there are none
+    #[default]
+    Stderr,
+    /// As `stderr`, plus each failed step's stdout (test output) in the logs
+    All,
+}
+
+impl SaveArtifacts {
+    /// Whether failure logs are written at all
+    pub fn logs(self) -> bool {
+        self != SaveArtifacts::None
+    }
+
+    /// Whether logs include the steps' stdout
+    pub fn stdout(self) -> bool {
+        self == SaveArtifacts::All
+    }
+
+    /// A failed step's message as written to disk; `raw` marks stderr (no diagnostics parsed)
+    pub fn message(self, message: &str, raw: bool) -> String {
+        match self {
+            SaveArtifacts::None => String::new(),
+            SaveArtifacts::Diagnostics if raw => error_lines(message),
+            _ => message.to_string(),
+        }
+    }
+
+    /// `rows` with their failure messages cut down to this level
+    pub fn rows(self, rows: &[OfferedRow]) -> Vec<OfferedRow> {
+        rows.iter().map(|row| self.row(row)).collect()
+    }
+
+    /// One row of `rows()`
+    pub fn row(self, row: &OfferedRow) -> OfferedRow {
+        let mut row = row.clone();
+        for cmd in &mut row.test.commands {
+            for failure in &mut cmd.result.failures {
+                failure.error_message = self.message(&failure.error_message, failure.raw_stderr);
+            }
+        }
+        row
+    }
+
+    /// `result` with each step's output cut down to this level (stdout only kept by `all`)
+    pub fn result(self, result: &TestResult) -> TestResult {
+        let mut result = result.clone();
+        let execution = &mut result.execution;
+        let steps =
+            std::iter::once(&mut execution.fetch).chain(execution.check.as_mut()).chain(execution.test.as_mut());
+        for step in steps {
+            self.trim(step);
+        }
+        result
+    }
+
+    fn trim(self, step: &mut CompileResult) {
+        if !self.stdout() {
+            step.stdout.clear();
+        }
+        let raw = !step.diagnostics.iter().any(|d| d.level.is_error());
+        step.stderr = self.message(&step.stderr, raw);
+    }
+}
+
+/// The `error` lines of raw stderr, and how many lines were left out
+fn error_lines(stderr: &str) -> String {
+    let total = stderr.lines().count();
+    let errors: Vec<&str> =
+        stderr.lines().filter(|l| l.trim_start().starts_with("error")).take(MAX_ERROR_LINES).collect();
+    let mut out = errors.join("\n");
+    if total > errors.len() {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("({} more lines of stderr not saved; --save-artifacts stderr)", total - errors.len()));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_cut_raw_stderr() {
+        let stderr = "   Compiling openssl-sys v0.9.1\nwarning: cc noise\nerror: failed to run custom build command for `openssl-sys`\n  --- stderr\n  fatal error: openssl/ssl.h: No such file";
+        let diagnostic = "error[E0308]: mismatched types\n --> src/lib.rs:3:5";
+
+        assert_eq!(
+            SaveArtifacts::Diagnostics.message(stderr, true),
+            "error: failed to run custom build command for `openssl-sys`\n(4 more lines of stderr not saved; --save-artifacts stderr)"
+        );
+        assert_eq!(SaveArtifacts::Diagnostics.message(diagnostic, false), diagnostic);
+        assert_eq!(SaveArtifacts::Stderr.message(stderr, true), stderr);
+        assert_eq!(SaveArtifacts::None.message(diagnostic, false), "");
+        assert!(!SaveArtifacts::None.logs() && SaveArtifacts::All.stdout() && !SaveArtifacts::Stderr.stdout());
+    }
+}
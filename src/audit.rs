@@ -0,0 +1,242 @@
+/// Audit trail of dependent manifest mutations, and the `verify-clean` staging check
+///
+/// Every time copter rewrites a dependent's `Cargo.toml` (force override, transitive
+/// `[patch.crates-io]`, restore from backup) the change is appended as a line diff to
+/// `copter-report/manifest-audit.log`. `verify-clean` scans the staging cache for
+/// manifests left modified by an interrupted run and for leftover backup files.
+use crate::compile::patching::{BACKUP_NAME, TEMP_NAME, write_atomic};
+use crate::scrub::Scrubber;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Audit log file name inside the report directory
+pub const AUDIT_LOG_NAME: &str = "manifest-audit.log";
+
+/// Backup file names copter writes (or older versions wrote) next to a dependent's Cargo.toml
+pub const BACKUP_NAMES: &[&str] = &[BACKUP_NAME, "Cargo.toml.original", "Cargo.toml.copter-backup"];
+
+/// Where manifest rewrites are recorded; the default records nothing
+#[derive(Debug, Clone, Default)]
+pub struct AuditLog {
+    log: Option<(PathBuf, Option<Scrubber>)>,
+}
+
+impl AuditLog {
+    /// Record manifest mutations to `log_path`, scrubbed by `scrubber` (`--scrub`)
+    pub fn new(log_path: PathBuf, scrubber: Option<Scrubber>) -> Self {
+        AuditLog { log: Some((log_path, scrubber)) }
+    }
+
+    /// Record one manifest rewrite; no-op when unchanged or when nothing is recorded
+    pub fn record(&self, manifest: &Path, reason: &str, before: &str, after: &str) {
+        let Some((log_path, scrubber)) = &self.log else {
+            return;
+        };
+        if before == after {
+            return;
+        }
+
+        let mut entry =
+            format!("=== {} {}: {}\n", chrono::Local::now().format("%Y-%m-%d %H:%M:%S"), reason, manifest.display());
+        for line in line_diff(before, after) {
+            entry.push_str(&line);
+            entry.push('\n');
+        }
+        if let Some(scrubber) = scrubber {
+            entry = scrubber.text(&entry);
+        }
+        let _ = fs::OpenOptions::new().create(true).append(true).open(log_path).and_then(|mut f| {
+            fs2::FileExt::lock_exclusive(&f)?;
+            f.write_all(entry.as_bytes())
+        });
+    }
+}
+
+/// Changed lines between two texts, `-` removed and `+` added, in file order
+pub fn line_diff(before: &str, after: &str) -> Vec<String> {
+    diff_ops(before, after)
+        .into_iter()
+        .filter(|(op, _)| *op != ' ')
+        .map(|(op, line)| format!("{}{}", op, line))
+        .collect()
+}
+
+/// Single-hunk unified diff of `name` (every line as context), applicable with `patch -p1` or `git apply`
+pub fn unified_diff(name: &str, before: &str, after: &str) -> String {
+    let ops = diff_ops(before, after);
+    let old_len = ops.iter().filter(|(op, _)| *op != '+').count();
+    let new_len = ops.iter().filter(|(op, _)| *op != '-').count();
+    let mut out = format!("--- a/{name}\n+++ b/{name}\n@@ -1,{} +1,{} @@\n", old_len, new_len);
+    for (op, line) in ops {
+        out.push(op);
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Line-level edit script: (' ' | '-' | '+', line)
+fn diff_ops<'a>(before: &'a str, after: &'a str) -> Vec<(char, &'a str)> {
+    let a: Vec<&str> = before.lines().collect();
+    let b: Vec<&str> = after.lines().collect();
+
+    // LCS table; manifests are small enough for the quadratic version
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            out.push((' ', a[i]));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push(('-', a[i]));
+            i += 1;
+        } else {
+            out.push(('+', b[j]));
+            j += 1;
+        }
+    }
+    out
+}
+
+/// Something left behind in the staging cache
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Residue {
+    /// Cargo.toml differs from the pristine backup next to it
+    ModifiedManifest { manifest: PathBuf, backup: PathBuf },
+    /// Backup file left behind (manifest itself matches it)
+    Backup(PathBuf),
+    /// Temp file from a manifest write that never completed
+    PartialWrite(PathBuf),
+}
+
+impl Residue {
+    pub fn describe(&self) -> String {
+        match self {
+            Residue::ModifiedManifest { manifest, .. } => format!("modified manifest: {}", manifest.display()),
+            Residue::Backup(path) => format!("leftover backup: {}", path.display()),
+            Residue::PartialWrite(path) => format!("interrupted write: {}", path.display()),
+        }
+    }
+}
+
+/// Scan every staged crate (`<staging>/<name>-<version>/`) for residue
+pub fn scan_staging(staging_dir: &Path) -> Result<Vec<Residue>, String> {
+    let entries = fs::read_dir(staging_dir).map_err(|e| format!("Failed to read {}: {}", staging_dir.display(), e))?;
+    let mut dirs: Vec<PathBuf> = entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect();
+    dirs.sort();
+
+    let mut found = Vec::new();
+    for dir in dirs {
+        let manifest = dir.join("Cargo.toml");
+        if dir.join(TEMP_NAME).exists() {
+            found.push(Residue::PartialWrite(dir.join(TEMP_NAME)));
+        }
+        for name in BACKUP_NAMES {
+            let backup = dir.join(name);
+            if !backup.exists() {
+                continue;
+            }
+            if fs::read(&manifest).ok() != fs::read(&backup).ok() {
+                found.push(Residue::ModifiedManifest { manifest: manifest.clone(), backup });
+            } else {
+                found.push(Residue::Backup(backup));
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Restore a modified manifest from its backup (recorded in `audit`), then remove the
+/// leftover file
+pub fn fix(residue: &Residue, audit: &AuditLog) -> Result<(), String> {
+    let backup = match residue {
+        Residue::ModifiedManifest { manifest, backup } => {
+            let before = fs::read_to_string(manifest).unwrap_or_default();
+            let original = fs::read_to_string(backup).map_err(|e| format!("{}: {}", backup.display(), e))?;
+            write_atomic(manifest, &original)?;
+            audit.record(manifest, "verify-clean restore", &before, &original);
+            backup
+        }
+        Residue::Backup(path) | Residue::PartialWrite(path) => path,
+    };
+    fs::remove_file(backup).map_err(|e| format!("Failed to remove {}: {}", backup.display(), e))
+}
+
+/// The `verify-clean` subcommand; `Err` when residue remains
+pub fn verify_clean(staging_dir: &Path, apply_fix: bool, audit: &AuditLog) -> Result<(), String> {
+    if !staging_dir.exists() {
+        println!("Staging directory {} does not exist; nothing to check", staging_dir.display());
+        return Ok(());
+    }
+    let found = scan_staging(staging_dir)?;
+    if found.is_empty() {
+        println!("Staging cache is clean: {}", staging_dir.display());
+        return Ok(());
+    }
+
+    for residue in &found {
+        println!("  {}", residue.describe());
+        if apply_fix {
+            fix(residue, audit)?;
+        }
+    }
+    if apply_fix {
+        println!("Cleaned {} item(s) in {}", found.len(), staging_dir.display());
+        return Ok(());
+    }
+    Err(format!(
+        "{} item(s) of residue in {} (re-run with --fix to restore and remove)",
+        found.len(),
+        staging_dir.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        let before = "[package]\nname = \"a\"\n\n[dependencies]\nrgb = \"0.8\"\n";
+        let after = "[package]\nname = \"a\"\n\n[dependencies]\nrgb = { path = \"/wip\" }\n";
+        assert_eq!(line_diff(before, after), vec!["-rgb = \"0.8\"", "+rgb = { path = \"/wip\" }"]);
+        assert!(line_diff(before, before).is_empty());
+
+        let patch = unified_diff("Cargo.toml", before, after);
+        assert!(patch.starts_with("--- a/Cargo.toml\n+++ b/Cargo.toml\n@@ -1,5 +1,5 @@\n [package]\n"));
+        assert!(patch.ends_with("-rgb = \"0.8\"\n+rgb = { path = \"/wip\" }\n"));
+    }
+
+    #[test]
+    fn test_scan_and_fix_staging() {
+        let staging = tempfile::tempdir().unwrap();
+        let clean = staging.path().join("clean-1.0.0");
+        let dirty = staging.path().join("dirty-1.0.0");
+        fs::create_dir_all(&clean).unwrap();
+        fs::create_dir_all(&dirty).unwrap();
+        fs::write(clean.join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(dirty.join("Cargo.toml"), "[package]\n[patch.crates-io]\n").unwrap();
+        fs::write(dirty.join("Cargo.toml.original.txt"), "[package]\n").unwrap();
+
+        let found = scan_staging(staging.path()).unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(matches!(found[0], Residue::ModifiedManifest { .. }));
+        assert!(verify_clean(staging.path(), false, &AuditLog::default()).is_err());
+
+        let log = staging.path().join(AUDIT_LOG_NAME);
+        verify_clean(staging.path(), true, &AuditLog::new(log.clone(), None)).unwrap();
+        assert_eq!(fs::read_to_string(dirty.join("Cargo.toml")).unwrap(), "[package]\n");
+        assert!(fs::read_to_string(&log).unwrap().contains("verify-clean restore"));
+        assert!(!dirty.join("Cargo.toml.original.txt").exists());
+        assert!(scan_staging(staging.path()).unwrap().is_empty());
+    }
+}
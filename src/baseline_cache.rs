@@ -0,0 +1,238 @@
+/// Baseline results from earlier runs, so a rerun only tests the offered versions
+///
+/// The baseline row of a dependent (its own resolution of the published base crate) comes
+/// out the same every run until something it depends on changes, yet it used to be
+/// fetched, checked and tested again each time. Its result is now saved to
+/// `<staging>/baseline-cache.json` under a key naming the dependent and its version, the
+/// baseline version, the rustc the dependent builds with (its rust-toolchain file
+/// included) and everything else that changes the build: steps, enabled base crate
+/// features (`--nightly-gates`), target, runner, `--docker-steps` image, patching options
+/// and the dependent's copter.toml settings (docker image, system deps). A later run with
+/// the same key reuses it. Only registry dependents against a published baseline are
+/// cached, and never a result that timed out or failed for environmental reasons.
+/// `--no-baseline-cache` runs (and saves) every baseline again.
+use crate::compile::ThreeStepResult;
+use crate::types::{CrateSource, TestMatrix, VersionedCrate};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const FILE_NAME: &str = "baseline-cache.json";
+
+/// Saved baseline results by key
+pub struct BaselineCache {
+    path: PathBuf,
+    entries: BTreeMap<String, ThreeStepResult>,
+    /// Whether saved results are used (not with `--no-baseline-cache`)
+    reuse: bool,
+    /// Results reused this run
+    pub hits: usize,
+}
+
+impl BaselineCache {
+    /// The cache in `staging_dir`; empty when missing or unreadable
+    pub fn open(staging_dir: &Path, reuse: bool) -> Self {
+        let path = staging_dir.join(FILE_NAME);
+        let entries =
+            std::fs::read_to_string(&path).ok().and_then(|text| serde_json::from_str(&text).ok()).unwrap_or_default();
+        BaselineCache { path, entries, reuse, hits: 0 }
+    }
+
+    /// The saved result for `key`, if reusing
+    pub fn get(&mut self, key: &str) -> Option<ThreeStepResult> {
+        let result = self.entries.get(key).filter(|_| self.reuse).cloned()?;
+        self.hits += 1;
+        Some(result)
+    }
+
+    /// Save a baseline result, unless it depends on the environment; written right away so an
+    /// interrupted run keeps what it finished
+    pub fn insert(&mut self, key: String, result: &ThreeStepResult) -> Result<(), String> {
+        let steps = [Some(&result.fetch), result.check.as_ref(), result.test.as_ref()];
+        if result.env_failure().is_some() || steps.into_iter().flatten().any(|step| step.timed_out) {
+            return Ok(());
+        }
+        self.entries.insert(key, result.clone());
+        let json = serde_json::to_string(&self.entries).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, json).map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))
+    }
+}
+
+/// The key of `dependent`'s baseline row, or None when it can't be cached (local or git
+/// sources change without a version bump)
+pub fn key(matrix: &TestMatrix, baseline: &VersionedCrate, dependent: &VersionedCrate, rustc: &str) -> Option<String> {
+    if baseline.source != CrateSource::Registry || dependent.source != CrateSource::Registry {
+        return None;
+    }
+    let features = matrix.nightly_gates.as_ref().map_or_else(|| "default".to_string(), |gates| gates.describe());
+    let mut key = format!(
+        "{} {} | {} {} | {} | {} | features: {} | target: {}",
+        dependent.name,
+        dependent.version.display(),
+        baseline.name,
+        baseline.version.display(),
+        rustc,
+        matrix.steps,
+        features,
+        matrix.cross.target.as_deref().unwrap_or("host"),
+    );
+    if let Some(runner) = &matrix.cross.runner {
+        key.push_str(&format!(" | runner: {}", runner.describe()));
+    }
+    if let Some(sandbox) = &matrix.cross.sandbox {
+        key.push_str(&format!(" | docker-steps: {}", sandbox.image));
+    }
+    if matrix.patch_transitive {
+        key.push_str(" | patch-transitive");
+    }
+    if matrix.force_resolver {
+        key.push_str(" | force-resolver-2");
+    }
+    key.push_str(&format!(" | dependent-patches: {:?}", matrix.dependent_patches).to_lowercase());
+    if let Some(settings) = matrix.dependent_settings.get(&dependent.name) {
+        key.push_str(&format!(" | settings: {}", serde_json::to_string(settings).unwrap_or_default()));
+    }
+    Some(key)
+}
+
+/// `key` for a registry dependent, staging its source to find the rustc it builds with
+pub fn key_for(matrix: &TestMatrix, baseline: &VersionedCrate, dependent: &VersionedCrate) -> Option<String> {
+    if dependent.source != CrateSource::Registry {
+        return None;
+    }
+    let version = semver::Version::parse(&dependent.version.display()).ok()?;
+    let dir = matrix.staging_dir.join(format!("{}-{}", dependent.name, version));
+    crate::source_cache::stage(matrix, &dependent.name, &version, &dir).ok()?;
+    key(matrix, baseline, dependent, &rustc_version(&dir, matrix)?)
+}
+
+/// `rustc -V` as the dependent in `dir` builds (its rust-toolchain file applies, unless a
+/// toolchain is named)
+pub fn rustc_version(dir: &Path, matrix: &TestMatrix) -> Option<String> {
+    let mut rustc = Command::new("rustc");
+    if matrix.nightly_gates.is_some() {
+        rustc.arg(crate::compile::nightly::TOOLCHAIN);
+    } else if let Some(toolchain) = &matrix.cross.toolchain {
+        rustc.arg(format!("+{}", toolchain));
+    }
+    let output = rustc.arg("-V").current_dir(dir).output().ok().filter(|o| o.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{CompileResult, CompileStep, PatchDepth};
+    use crate::types::{CommandType, Steps, VersionSpec};
+
+    fn result(stderr: &str, timed_out: bool) -> ThreeStepResult {
+        ThreeStepResult {
+            fetch: CompileResult {
+                step: CompileStep::Fetch,
+                success: stderr.is_empty(),
+                stdout: String::new(),
+                stderr: stderr.to_string(),
+                duration: std::time::Duration::from_secs(1),
+                diagnostics: vec![],
+                args: vec![],
+                manifest_diff: None,
+                timed_out,
+            },
+            check: None,
+            test: None,
+            actual_version: Some("0.8.91".to_string()),
+            expected_version: None,
+            forced_version: false,
+            original_requirement: Some("^0.8".to_string()),
+            all_crate_versions: vec![],
+            patch_depth: PatchDepth::None,
+            started: None,
+            fork: None,
+            blocking: vec![],
+            repository: None,
+            base_features: None,
+            resolver: None,
+            toolchain: None,
+            skipped: None,
+        }
+    }
+
+    fn matrix() -> TestMatrix {
+        TestMatrix {
+            base_crate: "rgb".to_string(),
+            base_versions: vec![VersionSpec::baseline(VersionedCrate::from_registry("rgb", "0.8.91"))],
+            dependents: vec![],
+            staging_dir: PathBuf::from("/staging"),
+            steps: Steps::through(CommandType::Check),
+            error_lines: 10,
+            patch_transitive: false,
+            min_free_bytes: 0,
+            heartbeat: Default::default(),
+            cross: Default::default(),
+            nightly_gates: None,
+            dependent_patches: Default::default(),
+            simulate_update: false,
+            staging_registry: None,
+            dependent_settings: Default::default(),
+            population: None,
+            baseline_cache: true,
+            staging_copy: crate::staging::CopyMethod::Auto,
+            source_cache: Default::default(),
+            registry: Default::default(),
+            audit: Default::default(),
+            force_resolver: false,
+            toolchains: vec![],
+            quarantined: vec![],
+        }
+    }
+
+    #[test]
+    fn test_saved_baselines_by_key() {
+        let matrix = matrix();
+        let baseline = VersionedCrate::from_registry("rgb", "0.8.91");
+        let image = VersionedCrate::from_registry("image", "0.25.8");
+        let key = key(&matrix, &baseline, &image, "rustc 1.95.0").unwrap();
+        assert_eq!(
+            key,
+            "image 0.25.8 | rgb 0.8.91 | rustc 1.95.0 | fetch,check | features: default | target: host | dependent-patches: keep"
+        );
+        let local = VersionedCrate::from_local("image", "0.25.8".to_string(), PathBuf::from("/src/image/Cargo.toml"));
+        assert_eq!(super::key(&matrix, &baseline, &local, "rustc 1.95.0"), None);
+        let emulated = TestMatrix {
+            cross: crate::compile::target::CrossSettings {
+                target: Some("aarch64-unknown-linux-gnu".to_string()),
+                runner: Some(crate::compile::target::Runner::parse("qemu-aarch64 -L /usr/aarch64-linux-gnu").unwrap()),
+                toolchain: None,
+                sandbox: None,
+                build_jobs: None,
+            },
+            ..matrix.clone()
+        };
+        let mut imaged = matrix.clone();
+        let settings =
+            crate::project_config::DependentSettings { image: Some("rust:1.84".to_string()), ..Default::default() };
+        imaged.dependent_settings.insert("image".to_string(), settings);
+        assert!(
+            super::key(&imaged, &baseline, &image, "rustc 1.95.0")
+                .unwrap()
+                .ends_with(" | settings: {\"system-deps\":[],\"image\":\"rust:1.84\"}")
+        );
+        assert!(
+            super::key(&emulated, &baseline, &image, "rustc 1.95.0")
+                .unwrap()
+                .ends_with("target: aarch64-unknown-linux-gnu | runner: qemu-aarch64 -L /usr/aarch64-linux-gnu | dependent-patches: keep")
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = BaselineCache::open(dir.path(), true);
+        cache.insert(key.clone(), &result("", false)).unwrap();
+        cache.insert("timed out".to_string(), &result("error: timed out", true)).unwrap();
+        cache.insert("offline".to_string(), &result("error: failed to download from `https://x`", false)).unwrap();
+
+        let mut reopened = BaselineCache::open(dir.path(), true);
+        assert_eq!(reopened.get(&key).unwrap().original_requirement.as_deref(), Some("^0.8"));
+        assert!(reopened.get("timed out").is_none() && reopened.get("offline").is_none());
+        assert_eq!(reopened.hits, 1);
+        assert!(BaselineCache::open(dir.path(), false).get(&key).is_none());
+    }
+}
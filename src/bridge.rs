@@ -33,6 +33,14 @@ pub fn test_result_to_offered_row(result: &TestResult) -> OfferedRow {
             .as_ref()
             .map(|actual| actual == &base_version_str)
             .unwrap_or(false),
+        fork: result.execution.fork.clone(),
+        repository: result.execution.repository.clone(),
+        git: match &result.dependent.source {
+            CrateSource::Git { url, rev, .. } => Some(format!("{}#{}", url, rev.as_deref().unwrap_or(""))),
+            _ => None,
+        },
+        base_features: result.execution.base_features.clone(),
+        resolver: result.execution.resolver.clone(),
     };
 
     // Create offered version (None for baseline)
@@ -53,25 +61,63 @@ pub fn test_result_to_offered_row(result: &TestResult) -> OfferedRow {
     let baseline_check_passed = result.baseline.as_ref().and_then(|b| b.baseline_check_passed);
 
     // Convert ThreeStepResult to TestExecution
-    let test = TestExecution { commands: three_step_to_commands(&result.execution) };
-
-    // Convert transitive dependencies
-    let transitive = result
-        .execution
-        .all_crate_versions
-        .iter()
-        .map(|(spec, resolved, dep_name)| TransitiveTest {
-            dependency: DependencyRef {
-                dependent_name: dep_name.clone(),
-                dependent_version: "?".to_string(), // Not available in TestResult
-                spec: spec.clone(),
-                resolved_version: resolved.clone(),
-                resolved_source: VersionSource::CratesIo, // Assume registry for transitives
-                used_offered_version: false,
-            },
-            depth: 1, // Assume depth 1 for all transitives
-        })
-        .collect();
+    let test = TestExecution {
+        commands: match result.execution.skipped {
+            Some(_) => vec![],
+            None => three_step_to_commands(&result.execution),
+        },
+        started: result.execution.started.clone(),
+        toolchain: result.execution.toolchain.clone(),
+        skipped: result.execution.skipped.clone(),
+    };
+
+    // Convert transitive dependencies (the resolve graph's blockers carry versions and paths)
+    let blocking = &result.execution.blocking;
+    let transitive = if !blocking.is_empty() {
+        blocking
+            .iter()
+            .map(|b| TransitiveTest {
+                dependency: DependencyRef {
+                    dependent_name: b.crate_name.clone(),
+                    dependent_version: b.version.clone(),
+                    spec: b.spec.clone(),
+                    resolved_version: b.resolved.clone(),
+                    resolved_source: VersionSource::CratesIo,
+                    used_offered_version: false,
+                    fork: None,
+                    repository: None,
+                    git: None,
+                    base_features: None,
+                    resolver: None,
+                },
+                depth: b.path.len().saturating_sub(1).max(1),
+                path: b.path.clone(),
+            })
+            .collect()
+    } else {
+        result
+            .execution
+            .all_crate_versions
+            .iter()
+            .map(|(spec, resolved, dep_name)| TransitiveTest {
+                dependency: DependencyRef {
+                    dependent_name: dep_name.clone(),
+                    dependent_version: "?".to_string(), // Not available in TestResult
+                    spec: spec.clone(),
+                    resolved_version: resolved.clone(),
+                    resolved_source: VersionSource::CratesIo, // Assume registry for transitives
+                    used_offered_version: false,
+                    fork: None,
+                    repository: None,
+                    git: None,
+                    base_features: None,
+                    resolver: None,
+                },
+                depth: 1, // Assume depth 1 for all transitives
+                path: vec![],
+            })
+            .collect()
+    };
 
     let row = OfferedRow { baseline_passed, baseline_check_passed, primary, offered, test, transitive };
 
@@ -93,53 +139,69 @@ fn three_step_to_commands(result: &crate::compile::ThreeStepResult) -> Vec<TestC
     let mut commands = Vec::new();
 
     // Fetch step
-    commands.push(TestCommand {
-        command: CommandType::Fetch,
-        features: vec![],
-        result: CommandResult {
-            passed: result.fetch.success,
-            duration: result.fetch.duration.as_secs_f64(),
-            failures: compile_result_to_failures(&result.fetch),
-        },
-    });
+    commands.push(TestCommand { command: CommandType::Fetch, features: vec![], result: command_result(&result.fetch) });
 
     // Check step (if present)
     if let Some(ref check) = result.check {
-        commands.push(TestCommand {
-            command: CommandType::Check,
-            features: vec![],
-            result: CommandResult {
-                passed: check.success,
-                duration: check.duration.as_secs_f64(),
-                failures: compile_result_to_failures(check),
-            },
-        });
+        commands.push(TestCommand { command: CommandType::Check, features: vec![], result: command_result(check) });
     }
 
     // Test step (if present)
     if let Some(ref test) = result.test {
-        commands.push(TestCommand {
-            command: CommandType::Test,
-            features: vec![],
-            result: CommandResult {
-                passed: test.success,
-                duration: test.duration.as_secs_f64(),
-                failures: compile_result_to_failures(test),
-            },
-        });
+        commands.push(TestCommand { command: CommandType::Test, features: vec![], result: command_result(test) });
     }
 
     commands
 }
 
+/// One step's CommandResult
+fn command_result(result: &crate::compile::CompileResult) -> CommandResult {
+    let (errors, warnings) = result.diagnostic_counts();
+    CommandResult {
+        passed: result.success,
+        env_failure: result.env_failure().map(String::from),
+        timed_out: result.timed_out,
+        duration: result.duration.as_secs_f64(),
+        failures: compile_result_to_failures(result),
+        errors,
+        warnings,
+    }
+}
+
 /// Convert CompileResult to CrateFailure list
 fn compile_result_to_failures(result: &crate::compile::CompileResult) -> Vec<CrateFailure> {
     if result.success {
-        vec![]
+        return vec![];
+    }
+    // A `--workspace` build: one failure per package with errors (a member, or a dependency)
+    let mut packages: Vec<&str> = Vec::new();
+    if result.args.iter().any(|arg| arg == "--workspace") {
+        for package in result.diagnostics.iter().filter(|d| d.level.is_error()).filter_map(|d| d.package.as_deref()) {
+            if !packages.contains(&package) {
+                packages.push(package);
+            }
+        }
+    }
+    if !packages.is_empty() {
+        packages
+            .into_iter()
+            .map(|package| {
+                let diagnostics: Vec<_> =
+                    result.diagnostics.iter().filter(|d| d.package.as_deref() == Some(package)).cloned().collect();
+                CrateFailure {
+                    crate_name: package.to_string(),
+                    error_message: extract_error_with_fallback(&diagnostics, &result.stderr, 0),
+                    raw_stderr: false,
+                    error_file: None,
+                }
+            })
+            .collect()
     } else {
         vec![CrateFailure {
             crate_name: "dependent".to_string(), // Generic - actual name in context
             error_message: extract_error_with_fallback(&result.diagnostics, &result.stderr, 0),
+            raw_stderr: !result.diagnostics.iter().any(|d| d.level.is_error()),
+            error_file: None,
         }]
     }
 }
@@ -48,6 +48,81 @@ mod tests {
         assert!(row.baseline_passed.is_some(), "Non-baseline row should have baseline_passed field");
     }
 
+    /// A failed `--workspace` check is split into one failure per member with errors
+    #[test]
+    fn test_workspace_failures_attributed_to_members() {
+        let message = |package_id: &str, text: &str| {
+            format!(
+                r#"{{"reason":"compiler-message","package_id":"{}","message":{{"message":"{}","level":"error","spans":[],"children":[],"rendered":"error: {}"}}}}"#,
+                package_id, text, text
+            )
+        };
+        let stdout = [
+            message("path+file:///ws/viewer#0.3.0", "mismatched types"),
+            message("path+file:///ws/cli#viewer-cli@0.3.0", "no method named `as_rgb`"),
+            message("viewer 0.3.0 (path+file:///ws/viewer)", "cannot find type `RGB8`"),
+        ]
+        .join("\n");
+        let mut result = create_non_baseline_result();
+        result.execution.check = Some(CompileResult {
+            step: CompileStep::Check,
+            success: false,
+            stdout: stdout.clone(),
+            stderr: String::new(),
+            duration: Duration::from_secs(1),
+            diagnostics: crate::error_extract::parse_cargo_json(&stdout),
+            args: vec!["check".to_string(), "--message-format=json".to_string(), "--workspace".to_string()],
+            manifest_diff: None,
+            timed_out: false,
+        });
+        let row = test_result_to_offered_row(&result);
+        let failures = &row.test.commands[1].result.failures;
+        let attributed: Vec<(&str, &str)> =
+            failures.iter().map(|f| (f.crate_name.as_str(), f.error_message.as_str())).collect();
+        assert_eq!(
+            attributed,
+            [
+                ("viewer", "error: mismatched types\n\nerror: cannot find type `RGB8`"),
+                ("viewer-cli", "error: no method named `as_rgb`")
+            ]
+        );
+    }
+
+    /// Errors and warnings are counted per step, without rustc's summary lines
+    #[test]
+    fn test_diagnostic_counts() {
+        let message = |level: &str, text: &str| {
+            format!(
+                r#"{{"reason":"compiler-message","package_id":"image 0.25.8","message":{{"message":"{}","level":"{}","spans":[],"children":[],"rendered":"{}: {}"}}}}"#,
+                text, level, level, text
+            )
+        };
+        let stdout = [
+            message("warning", "unused import: `Rgb`"),
+            message("warning", "unused variable: `x`"),
+            message("error", "mismatched types"),
+            message("warning", "2 warnings emitted"),
+            message("error", "aborting due to 1 previous error"),
+        ]
+        .join("\n");
+        let mut result = create_non_baseline_result();
+        result.execution.check = Some(CompileResult {
+            step: CompileStep::Check,
+            success: false,
+            stdout: stdout.clone(),
+            stderr: String::new(),
+            duration: Duration::from_secs(1),
+            diagnostics: crate::error_extract::parse_cargo_json(&stdout),
+            args: vec![],
+            manifest_diff: None,
+            timed_out: false,
+        });
+        let row = test_result_to_offered_row(&result);
+        let check = &row.test.commands[1].result;
+        assert_eq!((check.errors, check.warnings), (1, 2));
+        assert_eq!((row.test.commands[0].result.errors, row.test.commands[0].result.warnings), (0, 0));
+    }
+
     // Helper functions
 
     fn create_test_result_with_requirement(req: &str) -> TestResult {
@@ -62,6 +137,9 @@ mod tests {
                     stderr: String::new(),
                     duration: Duration::from_secs(1),
                     diagnostics: Vec::new(),
+                    args: vec![],
+                    manifest_diff: None,
+                    timed_out: false,
                 },
                 check: None,
                 test: None,
@@ -71,6 +149,14 @@ mod tests {
                 original_requirement: Some(req.to_string()),
                 all_crate_versions: vec![],
                 patch_depth: PatchDepth::None,
+                started: None,
+                fork: None,
+                blocking: vec![],
+                repository: None,
+                base_features: None,
+                resolver: None,
+                toolchain: None,
+                skipped: None,
             },
             baseline: None, // This IS the baseline
         }
@@ -88,6 +174,9 @@ mod tests {
                     stderr: String::new(),
                     duration: Duration::from_secs(1),
                     diagnostics: Vec::new(),
+                    args: vec![],
+                    manifest_diff: None,
+                    timed_out: false,
                 },
                 check: None,
                 test: None,
@@ -97,6 +186,14 @@ mod tests {
                 original_requirement: None, // No requirement provided
                 all_crate_versions: vec![],
                 patch_depth: PatchDepth::None,
+                started: None,
+                fork: None,
+                blocking: vec![],
+                repository: None,
+                base_features: None,
+                resolver: None,
+                toolchain: None,
+                skipped: None,
             },
             baseline: None,
         }
@@ -114,6 +211,9 @@ mod tests {
                     stderr: String::new(),
                     duration: Duration::from_secs(1),
                     diagnostics: Vec::new(),
+                    args: vec![],
+                    manifest_diff: None,
+                    timed_out: false,
                 },
                 check: None,
                 test: None,
@@ -123,6 +223,14 @@ mod tests {
                 original_requirement: Some("^0.8.0".to_string()),
                 all_crate_versions: vec![],
                 patch_depth: PatchDepth::None,
+                started: None,
+                fork: None,
+                blocking: vec![],
+                repository: None,
+                base_features: None,
+                resolver: None,
+                toolchain: None,
+                skipped: None,
             },
             baseline: None, // No baseline comparison = this IS the baseline
         }
@@ -140,6 +248,9 @@ mod tests {
                     stderr: String::new(),
                     duration: Duration::from_secs(1),
                     diagnostics: Vec::new(),
+                    args: vec![],
+                    manifest_diff: None,
+                    timed_out: false,
                 },
                 check: None,
                 test: None,
@@ -149,6 +260,14 @@ mod tests {
                 original_requirement: Some("^0.8.0".to_string()),
                 all_crate_versions: vec![],
                 patch_depth: PatchDepth::None,
+                started: None,
+                fork: None,
+                blocking: vec![],
+                repository: None,
+                base_features: None,
+                resolver: None,
+                toolchain: None,
+                skipped: None,
             },
             baseline: Some(BaselineComparison {
                 baseline_passed: true,
@@ -2,11 +2,14 @@
 ///
 /// Classifies test failures by root cause to help users distinguish
 /// "your fault" from "not your problem."
-use crate::types::{CommandType, OfferedRow};
+use crate::messages::Catalog;
+use crate::types::OfferedRow;
 
 /// Category of a failure
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum FailureCategory {
+    /// Harness or environment problem (disk full, network, missing toolchain)
+    Environment,
     /// Dependency was yanked from crates.io
     YankedDeps,
     /// build.rs / custom build command failed
@@ -24,16 +27,18 @@ pub enum FailureCategory {
 }
 
 impl FailureCategory {
-    pub fn label(&self) -> &'static str {
-        match self {
-            FailureCategory::YankedDeps => "Yanked deps",
-            FailureCategory::BuildScript => "build.rs",
-            FailureCategory::SystemLibrary => "System libs",
-            FailureCategory::NightlyFeature => "Nightly",
-            FailureCategory::VersionConflict => "Version conflicts",
-            FailureCategory::PlatformSpecific => "Platform",
-            FailureCategory::Other => "Other",
-        }
+    /// Category name in `messages`
+    pub fn label(&self, messages: &Catalog) -> String {
+        messages.text(match self {
+            FailureCategory::Environment => "category.environment",
+            FailureCategory::YankedDeps => "category.yanked_deps",
+            FailureCategory::BuildScript => "category.build_script",
+            FailureCategory::SystemLibrary => "category.system_library",
+            FailureCategory::NightlyFeature => "category.nightly_feature",
+            FailureCategory::VersionConflict => "category.version_conflict",
+            FailureCategory::PlatformSpecific => "category.platform_specific",
+            FailureCategory::Other => "category.other",
+        })
     }
 }
 
@@ -41,28 +46,18 @@ impl FailureCategory {
 #[derive(Debug, Clone)]
 pub struct CategorizedFailure {
     pub dependent_name: String,
-    pub dependent_version: String,
     pub category: FailureCategory,
-    /// Whether the error text mentions the base crate name
-    pub mentions_base_crate: bool,
     /// First error line for display
     pub error_snippet: Option<String>,
 }
 
 /// Categorize a single failed row
-pub fn categorize_failure(row: &OfferedRow, base_crate_name: &str) -> CategorizedFailure {
+pub fn categorize_failure(row: &OfferedRow) -> CategorizedFailure {
     let error_text = collect_error_text(row);
     let category = detect_category(&error_text, &row.primary.dependent_name);
-    let mentions_base_crate = mentions_crate(&error_text, base_crate_name);
     let error_snippet = first_error_line_from_text(&error_text);
 
-    CategorizedFailure {
-        dependent_name: row.primary.dependent_name.clone(),
-        dependent_version: row.primary.dependent_version.clone(),
-        category,
-        mentions_base_crate,
-        error_snippet,
-    }
+    CategorizedFailure { dependent_name: row.primary.dependent_name.clone(), category, error_snippet }
 }
 
 /// Collect all error text from a row's failed commands
@@ -83,6 +78,11 @@ fn collect_error_text(row: &OfferedRow) -> String {
 fn detect_category(error_text: &str, dependent_name: &str) -> FailureCategory {
     // Check in priority order (most specific first)
 
+    // Environment problems say nothing about the dependent
+    if environment_failure(error_text, true).is_some() {
+        return FailureCategory::Environment;
+    }
+
     // Yanked deps
     if error_text.contains("is yanked") || error_text.contains("was yanked") {
         return FailureCategory::YankedDeps;
@@ -112,6 +112,7 @@ fn detect_category(error_text: &str, dependent_name: &str) -> FailureCategory {
         || error_text.contains("Could not find ")
         || error_text.contains("library not found")
         || error_text.contains("pkg_config")
+        || error_text.contains("is not installed")
         || error_text.contains("vcpkg")
     {
         return FailureCategory::SystemLibrary;
@@ -157,19 +158,69 @@ fn detect_category(error_text: &str, dependent_name: &str) -> FailureCategory {
     FailureCategory::Other
 }
 
-/// Check if error text mentions the base crate name as a separate word
-fn mentions_crate(error_text: &str, crate_name: &str) -> bool {
-    // Check for crate name in error messages (as word boundary)
-    // Simple heuristic: check for `crate_name` preceded/followed by non-alphanumeric
-    let name_lower = crate_name.to_lowercase();
-    let text_lower = error_text.to_lowercase();
-
-    // Check common patterns
-    text_lower.contains(&format!("`{}`", name_lower))
-        || text_lower.contains(&format!("{}::", name_lower))
-        || text_lower.contains(&format!("crate `{}`", name_lower))
-        || text_lower.contains(&format!(" {} ", name_lower))
-        || text_lower.contains(&format!("/{}/", name_lower))
+/// Detect failures caused by the harness or machine rather than the code under test
+///
+/// Returns a short reason ("disk full", "network error", ...) when cargo output
+/// shows the build never got a fair chance. These are excluded from regression
+/// counts and retried once by the runner.
+///
+/// Only cargo's and rustup's own messages count: `error:` lines at the start of a line and
+/// the `Caused by:` chain under them, plus cargo's `warning: spurious network error`. A
+/// dependent's test output or build script saying "Connection reset by peer" is its own
+/// failure. Download and registry errors only count while `fetching`; a kill signal only
+/// when cargo reports the process it ran died of it.
+pub fn environment_failure(output: &str, fetching: bool) -> Option<&'static str> {
+    const ANY_STEP: &[(&str, &str)] = &[
+        ("No space left on device", "disk full"),
+        ("os error 28", "disk full"),
+        ("Disk quota exceeded", "disk full"),
+        ("rustup could not choose a version", "toolchain missing"),
+        ("linker `cc` not found", "toolchain missing"),
+    ];
+    const DOWNLOADS: &[(&str, &str)] = &[
+        ("failed to clone into", "git clone failed"),
+        ("failed to fetch into", "git clone failed"),
+        ("failed to authenticate when downloading repository", "git clone failed"),
+        ("spurious network error", "network error"),
+        ("Could not resolve host", "network error"),
+        ("Couldn't resolve host", "network error"),
+        ("Connection reset by peer", "network error"),
+        ("SSL connect error", "network error"),
+        ("failed to download from", "network error"),
+        ("500 Internal Server Error", "registry error"),
+        ("502 Bad Gateway", "registry error"),
+        ("503 Service Unavailable", "registry error"),
+        ("504 Gateway Timeout", "registry error"),
+    ];
+    let lines = cargo_error_lines(output);
+    let find = |patterns: &[(&str, &'static str)]| {
+        patterns.iter().find(|(needle, _)| lines.iter().any(|line| line.contains(needle))).map(|(_, reason)| *reason)
+    };
+    let toolchain_missing =
+        lines.iter().any(|line| line.starts_with("error: toolchain '") && line.ends_with("is not installed"));
+    let killed = lines.iter().any(|line| line.contains("process didn't exit successfully") && line.contains("SIGKILL"));
+    find(ANY_STEP)
+        .or(toolchain_missing.then_some("toolchain missing"))
+        .or(killed.then_some("killed (out of memory?)"))
+        .or_else(|| find(DOWNLOADS).filter(|_| fetching))
+}
+
+/// Cargo's and rustup's `error:` lines and the `Caused by:` chains under them, and cargo's
+/// spurious network warnings
+fn cargo_error_lines(output: &str) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let mut in_error = false;
+    for line in output.lines() {
+        if line.starts_with("error:") || line.starts_with("warning: spurious network error") {
+            in_error = true;
+        } else if !(line.is_empty() || line.starts_with(' ') || line.starts_with("Caused by:")) {
+            in_error = false;
+        }
+        if in_error {
+            lines.push(line.trim());
+        }
+    }
+    lines
 }
 
 /// Extract first error line from error text
@@ -204,13 +255,14 @@ impl FailureSummary {
 
         let order = |cat: &FailureCategory| -> u8 {
             match cat {
-                FailureCategory::YankedDeps => 0,
-                FailureCategory::SystemLibrary => 1,
-                FailureCategory::BuildScript => 2,
-                FailureCategory::NightlyFeature => 3,
-                FailureCategory::PlatformSpecific => 4,
-                FailureCategory::VersionConflict => 5,
-                FailureCategory::Other => 6,
+                FailureCategory::Environment => 0,
+                FailureCategory::YankedDeps => 1,
+                FailureCategory::SystemLibrary => 2,
+                FailureCategory::BuildScript => 3,
+                FailureCategory::NightlyFeature => 4,
+                FailureCategory::PlatformSpecific => 5,
+                FailureCategory::VersionConflict => 6,
+                FailureCategory::Other => 7,
             }
         };
 
@@ -275,14 +327,46 @@ mod tests {
     }
 
     #[test]
-    fn test_detect_other() {
-        assert_eq!(detect_category("mismatched types", "image"), FailureCategory::Other);
+    fn test_detect_environment() {
+        assert_eq!(
+            environment_failure("error: failed to write: No space left on device (os error 28)", false),
+            Some("disk full")
+        );
+        assert_eq!(
+            environment_failure(
+                "warning: spurious network error (2 tries remaining): [6] Could not resolve host",
+                true
+            ),
+            Some("network error")
+        );
+        assert_eq!(
+            environment_failure("error: toolchain '1.70.0-x86_64' is not installed", false),
+            Some("toolchain missing")
+        );
+        assert_eq!(environment_failure("error[E0308]: mismatched types", false), None);
+        assert_eq!(detect_category("error: failed to clone into: /tmp/git/db", "foo"), FailureCategory::Environment);
+
+        // Cargo's own chain: a test binary killed by the OOM killer
+        let killed = "error: test failed, to rerun pass `--lib`\n\nCaused by:\n  process didn't exit successfully: \
+                      `/t/deps/png-1f2e` (signal: 9, SIGKILL: kill)";
+        assert_eq!(environment_failure(killed, false), Some("killed (out of memory?)"));
+        // The dependent's own output saying the same words is its failure
+        let test_output = "thread 'fetch_tile' panicked at src/net.rs:12:5:\nConnection reset by peer\n\
+                           sent SIGKILL to child\nerror: test failed, to rerun pass `--lib`";
+        assert_eq!(environment_failure(test_output, false), None);
+        let build_script = "error: failed to run custom build command for `openssl-sys v0.9.102`\n\n\
+                            Caused by:\n  --- stderr\n  libssl is not installed";
+        assert_eq!(environment_failure(build_script, false), None);
+        assert_eq!(detect_category(build_script, "foo"), FailureCategory::SystemLibrary);
+        // Downloads only fail the environment while fetching
+        let download = "error: failed to download from `https://static.crates.io/crates/rgb/0.8.91/download`\n\n\
+                        Caused by:\n  [52] Server returned nothing (500 Internal Server Error)";
+        assert_eq!(environment_failure(download, true), Some("network error"));
+        assert_eq!(environment_failure(download, false), None);
     }
 
     #[test]
-    fn test_mentions_crate() {
-        assert!(mentions_crate("expected `rgb::Rgb<u8>`", "rgb"));
-        assert!(mentions_crate("crate `rgb` has multiple versions", "rgb"));
-        assert!(!mentions_crate("failed to run custom build command", "rgb"));
+    fn test_detect_other() {
+        assert_eq!(detect_category("mismatched types", "image"), FailureCategory::Other);
     }
 }
@@ -0,0 +1,208 @@
+/// Results of the current run as they finish, for `--resume --from <step>`
+///
+/// Every result is appended to `copter-report/checkpoint.jsonl` the moment it streams in,
+/// after a header line with the matrix fingerprint (ignoring the step flags, so an
+/// `--only-check` or `--check-first` run and a resumed full run match). A later run of the
+/// same matrix with `--resume --from test` reads it back instead of redoing the earlier
+/// steps: it shows the saved rows and runs only the dependents that passed check there,
+/// plus any the interrupted run never reached. Where a dependent appears more than once
+/// (both passes of `--check-first`, or a resumed run) the last rows win.
+use crate::artifacts::SaveArtifacts;
+use crate::types::{Steps, TestMatrix, TestResult};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = "checkpoint.jsonl";
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct Header {
+    fingerprint: String,
+}
+
+/// Appends each result of the running matrix to the checkpoint
+pub struct Writer {
+    file: File,
+    level: SaveArtifacts,
+}
+
+impl Writer {
+    /// Start a new checkpoint for `matrix`, replacing the last one; results keep as much
+    /// output as `level` does
+    pub fn create(report_dir: &Path, matrix: &TestMatrix, level: SaveArtifacts) -> Result<Self, String> {
+        fs::create_dir_all(report_dir).map_err(|e| format!("Failed to create {}: {}", report_dir.display(), e))?;
+        let path = path(report_dir);
+        let mut file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let header = serde_json::to_string(&Header { fingerprint: fingerprint(matrix) }).map_err(|e| e.to_string())?;
+        writeln!(file, "{}", header).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(Writer { file, level })
+    }
+
+    pub fn record(&mut self, result: &TestResult) -> Result<(), String> {
+        let line = serde_json::to_string(&self.level.result(result)).map_err(|e| e.to_string())?;
+        writeln!(self.file, "{}", line).map_err(|e| format!("Failed to write checkpoint: {}", e))
+    }
+}
+
+/// Results saved by the last run of `matrix`, one set of rows per dependent
+pub fn load(report_dir: &Path, matrix: &TestMatrix) -> Result<Vec<TestResult>, String> {
+    let path = path(report_dir);
+    let text =
+        fs::read_to_string(&path).map_err(|e| format!("No checkpoint to resume from ({}: {})", path.display(), e))?;
+    let mut lines = text.lines();
+    let header: Header = lines
+        .next()
+        .and_then(|line| serde_json::from_str(line).ok())
+        .ok_or_else(|| format!("{} has no checkpoint header", path.display()))?;
+    if header.fingerprint != fingerprint(matrix) {
+        return Err(format!(
+            "{} is from a different run (base versions, dependents or options changed)",
+            path.display()
+        ));
+    }
+    // A line cut short by an interrupted write is dropped; skipped dependents (out of time,
+    // quarantined, missing system dep) get another chance
+    let results = lines
+        .filter_map(|line| serde_json::from_str::<TestResult>(line).ok())
+        .filter(|result| result.execution.skipped.is_none())
+        .collect();
+    Ok(latest_rows(results))
+}
+
+/// Keep each dependent's rows from its last appearance, in order of first appearance
+fn latest_rows(results: Vec<TestResult>) -> Vec<TestResult> {
+    let key = |r: &TestResult| (r.dependent.name.clone(), r.dependent.version.display());
+    let mut groups: Vec<((String, String), Vec<TestResult>)> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    let mut previous: Option<(String, String)> = None;
+    for result in results {
+        let dependent = key(&result);
+        let i = *index.entry(dependent.clone()).or_insert_with(|| {
+            groups.push((dependent.clone(), vec![]));
+            groups.len() - 1
+        });
+        // A dependent showing up again after other rows starts over
+        if previous.as_ref() != Some(&dependent) {
+            groups[i].1.clear();
+        }
+        groups[i].1.push(result);
+        previous = Some(dependent);
+    }
+    groups.into_iter().flat_map(|(_, rows)| rows).collect()
+}
+
+/// The run fingerprint with the step flags left out, and "latest" dependents unresolved
+/// (their saved rows are kept whatever version they tested)
+fn fingerprint(matrix: &TestMatrix) -> String {
+    let mut all_steps = matrix.clone();
+    all_steps.steps = Steps::all();
+    crate::history::fingerprint(&all_steps, &Default::default())
+}
+
+fn path(report_dir: &Path) -> PathBuf {
+    report_dir.join(FILE_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{CompileResult, CompileStep, PatchDepth, ThreeStepResult};
+    use crate::types::{CommandType, OverrideMode, VersionSpec, VersionedCrate};
+
+    fn result(dependent: &str, base: &str) -> TestResult {
+        TestResult {
+            base_version: VersionedCrate::from_registry("rgb", base),
+            dependent: VersionedCrate::from_registry(dependent, "1.0.0"),
+            execution: ThreeStepResult {
+                fetch: CompileResult {
+                    step: CompileStep::Fetch,
+                    success: true,
+                    stdout: String::new(),
+                    stderr: String::new(),
+                    duration: std::time::Duration::from_secs(1),
+                    diagnostics: vec![],
+                    args: vec![],
+                    manifest_diff: None,
+                    timed_out: false,
+                },
+                check: None,
+                test: None,
+                actual_version: None,
+                expected_version: None,
+                forced_version: false,
+                original_requirement: None,
+                all_crate_versions: vec![],
+                patch_depth: PatchDepth::None,
+                started: None,
+                fork: None,
+                blocking: vec![],
+                repository: None,
+                base_features: None,
+                resolver: None,
+                toolchain: None,
+                skipped: None,
+            },
+            baseline: None,
+        }
+    }
+
+    fn matrix() -> TestMatrix {
+        TestMatrix {
+            base_crate: "rgb".to_string(),
+            base_versions: vec![VersionSpec::baseline(VersionedCrate::from_registry("rgb", "0.8.91"))],
+            dependents: vec![VersionSpec {
+                crate_ref: VersionedCrate::latest_from_registry("image"),
+                override_mode: OverrideMode::None,
+                is_baseline: false,
+            }],
+            staging_dir: PathBuf::from("/staging"),
+            steps: Steps::through(CommandType::Check),
+            error_lines: 10,
+            patch_transitive: false,
+            min_free_bytes: 0,
+            heartbeat: Default::default(),
+            cross: Default::default(),
+            nightly_gates: None,
+            dependent_patches: Default::default(),
+            simulate_update: false,
+            staging_registry: None,
+            dependent_settings: Default::default(),
+            population: None,
+            baseline_cache: false,
+            staging_copy: crate::staging::CopyMethod::Auto,
+            source_cache: Default::default(),
+            registry: Default::default(),
+            audit: Default::default(),
+            force_resolver: false,
+            toolchains: vec![],
+            quarantined: vec![],
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_keeps_each_dependents_last_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let check_run = matrix();
+        let mut writer = Writer::create(dir.path(), &check_run, SaveArtifacts::Stderr).unwrap();
+        for (dependent, base) in [("image", "0.8.91"), ("image", "0.9.0"), ("png", "0.8.91"), ("image", "0.8.91")] {
+            writer.record(&result(dependent, base)).unwrap();
+        }
+        drop(writer);
+        let mut interrupted = fs::read_to_string(path(dir.path())).unwrap();
+        interrupted.push_str("{\"base_version\":");
+        fs::write(path(dir.path()), interrupted).unwrap();
+
+        let mut full_run = matrix();
+        full_run.steps = Steps::all();
+        let rows: Vec<(String, String)> = load(dir.path(), &full_run)
+            .unwrap()
+            .iter()
+            .map(|r| (r.dependent.name.clone(), r.base_version.version.display()))
+            .collect();
+        assert_eq!(rows, [("image".to_string(), "0.8.91".to_string()), ("png".to_string(), "0.8.91".to_string())]);
+
+        full_run.dependents.clear();
+        assert!(load(dir.path(), &full_run).unwrap_err().contains("different run"));
+    }
+}
@@ -1,4 +1,5 @@
-use clap::Parser;
+use crate::types::{CommandType, Steps};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 /// Get the default cache directory for cargo-copter
@@ -15,6 +16,10 @@ pub fn default_cache_dir() -> PathBuf {
 #[command(about = "Test the downstream impact of crate changes before publishing")]
 #[command(version)]
 pub struct CliArgs {
+    /// Utility subcommand (omit to run the normal test matrix)
+    #[command(subcommand)]
+    pub command: Option<CopterCommand>,
+
     /// Path to the crate to test (directory or Cargo.toml file)
     #[arg(long, short = 'p', value_name = "PATH")]
     pub path: Option<PathBuf>,
@@ -23,21 +28,65 @@ pub struct CliArgs {
     #[arg(long = "crate", visible_alias = "crate-name", short = 'c', value_name = "CRATE")]
     pub crate_name: Option<String>,
 
+    /// Workspace member to test when --path is a workspace (default: its root package, or its only member)
+    #[arg(long, value_name = "NAME")]
+    pub package: Option<String>,
+
     /// Test top N reverse dependencies by download count
     #[arg(long, default_value = "5")]
     pub top_dependents: usize,
 
+    /// Rank --top-dependents by all-time or recent (90-day) downloads
+    #[arg(long, value_enum, value_name = "ORDER", default_value = "all-time")]
+    pub dependents_sort: crate::api::DependentSort,
+
     /// Explicitly test these crates from crates.io (supports "name:version" syntax)
     /// Examples: "image", "image:0.25.8"
     /// Can specify multiple: --dependents image serde tokio
     #[arg(long, value_name = "CRATE[:VERSION]", num_args = 1.., value_delimiter = ' ')]
     pub dependents: Vec<String>,
 
+    /// Never test these dependents (e.g. ones known to be broken); merged with copter.toml's `exclude`
+    #[arg(long, value_name = "CRATE", num_args = 1..)]
+    pub exclude: Vec<String>,
+
+    /// Other names the base crate is published under (forks, renames); the report counts
+    /// the aliases' top dependents that moved from the base crate. Merged with copter.toml's `aliases`
+    #[arg(long = "alias", value_name = "CRATE", num_args = 1..)]
+    pub aliases: Vec<String>,
+
+    /// Test only these of the resolved dependents (used by `--docker` for per-dependent images)
+    #[arg(long, hide = true, value_name = "CRATE", num_args = 1..)]
+    pub only_dependents: Vec<String>,
+
+    /// Quarantine a dependent after its baseline failed in more than this many consecutive runs
+    /// (excluded from later runs, re-tested once a week; 0 turns quarantine off)
+    #[arg(long, value_name = "RUNS", default_value = "3")]
+    pub quarantine_after: u32,
+
+    /// Test quarantined dependents in this run as well
+    #[arg(long)]
+    pub include_quarantined: bool,
+
+    /// `[dependent.<name>]` settings from copter.toml (no command-line form)
+    #[arg(skip)]
+    pub dependent_settings: std::collections::BTreeMap<String, crate::project_config::DependentSettings>,
+
     /// Test local crates at these paths
     /// Can specify multiple: --dependent-paths ./crate1 ./crate2
     #[arg(long, value_name = "PATH", num_args = 1..)]
     pub dependent_paths: Vec<PathBuf>,
 
+    /// Test dependents from git repositories: URL, optionally #branch, tag or commit
+    /// Every package in the repository that depends on the base crate is tested
+    #[arg(long, value_name = "URL[#REV]", num_args = 1..)]
+    pub dependent_git: Vec<String>,
+
+    /// Test the repositories of these GitHub organizations (or users) that depend on the base crate
+    /// Reads each Rust repository's Cargo.toml through the GitHub API (GITHUB_TOKEN for private ones)
+    #[arg(long, value_name = "ORG", num_args = 1..)]
+    pub github_org: Vec<String>,
+
     /// Discover local dependents via glob patterns matching Cargo.toml files
     /// Only includes crates that actually depend on the base crate
     /// Example: --dependent-glob "~/work/*/Cargo.toml" "~/work/zen/*/Cargo.toml"
@@ -61,6 +110,7 @@ pub struct CliArgs {
     /// Test against specific versions of the base crate (e.g., "0.3.0 4.1.1")
     /// When specified with --path, includes "this" (WIP version) automatically
     /// Supports versions with hyphens: "0.8.0 1.0.0-rc.1 1.0.0-alpha.2"
+    /// Keywords: this, latest (stable), latest-preview, prev, major-N, published
     #[arg(long, value_name = "VERSION", num_args = 1..)]
     pub test_versions: Vec<String>,
 
@@ -68,23 +118,108 @@ pub struct CliArgs {
     #[arg(long = "output-html", default_value = "copter-report.html")]
     pub output: PathBuf,
 
+    /// Render a Handlebars-style template with the JSON report as context (repeatable)
+    /// Output goes to copter-report/<name without .hbs/.tera>, e.g. notes.md.hbs → notes.md
+    #[arg(long, value_name = "FILE")]
+    pub report_template: Vec<PathBuf>,
+
+    /// Message catalog (TOML) to translate or rename labels, headings and column titles
+    /// Print the defaults with `cargo-copter messages`
+    #[arg(long, value_name = "FILE")]
+    pub messages: Option<PathBuf>,
+
+    /// Reference report.json (URL or file), e.g. from the main branch's scheduled run
+    /// Only regressions that report doesn't already have make the run fail
+    #[arg(long, value_name = "URL|FILE")]
+    pub compare_with: Option<String>,
+
+    /// Also fail the run when an offered version went untested for some dependent
+    /// (not selected by cargo, a fork of the base crate, or no steps run)
+    #[arg(long)]
+    pub fail_on_skipped: bool,
+
     /// Directory for staging unpacked crates (enables caching across runs)
     /// Default: ~/.cache/cargo-copter/staging (Linux), ~/Library/Caches/cargo-copter/staging (macOS)
     #[arg(long)]
     pub staging_dir: Option<PathBuf>,
 
-    /// Only fetch dependencies (skip check and test)
+    /// crates.io mirror: a sparse index (sparse+https://…/index/) or registry API URL [env: COPTER_INDEX]
+    #[arg(long, value_name = "URL")]
+    pub index_url: Option<String>,
+
+    /// Fetch only, vendoring every crate the run needs into DIR for a later --offline DIR run
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["offline", "steps", "only_check"])]
+    pub prefetch: Option<PathBuf>,
+
+    /// Run without network access from a directory filled by --prefetch DIR (same options otherwise)
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["index_url", "refresh"])]
+    pub offline: Option<PathBuf>,
+
+    /// How local crates are copied into staging: auto (reflinks where supported), reflink, hardlink, copy
+    #[arg(long, value_enum, value_name = "METHOD", default_value = "auto")]
+    pub staging_copy: crate::staging::CopyMethod,
+
+    /// Steps to run, in order: a prefix of fetch,check,test (e.g. "fetch,check")
+    #[arg(long, value_name = "STEPS", conflicts_with_all = ["only_fetch", "only_check"])]
+    pub steps: Option<Steps>,
+
+    /// Only fetch dependencies (shorthand for --steps fetch)
     #[arg(long)]
     pub only_fetch: bool,
 
-    /// Only fetch and check (skip tests)
+    /// Only fetch and check (shorthand for --steps fetch,check)
     #[arg(long)]
     pub only_check: bool,
 
+    /// Fetch and check every dependent first, then test the ones that passed check
+    /// Compile regressions show up in minutes instead of after hours of tests
+    #[arg(long)]
+    pub check_first: bool,
+
+    /// Reuse the last run's results (copter-report/checkpoint.jsonl) up to --from
+    /// Only the dependents that passed the step before it, or never ran, are run again
+    #[arg(long)]
+    pub resume: bool,
+
+    /// Step to resume from: fetch, check or test (default test)
+    #[arg(long, value_name = "STEP", requires = "resume")]
+    pub from: Option<CommandType>,
+
     /// Output results as JSON
     #[arg(long)]
     pub json: bool,
 
+    /// Diff-friendly report.md/report.json: rows sorted by dependent, no times, machine paths replaced
+    #[arg(long)]
+    pub deterministic: bool,
+
+    /// GitHub Actions: annotate regressions and write a summary to $GITHUB_STEP_SUMMARY
+    #[arg(long)]
+    pub github: bool,
+
+    /// Failed steps' output written to copter-report/ (report.json, failure logs): none,
+    /// diagnostics (compiler errors, stderr cut to its error lines), stderr, or all (plus stdout)
+    #[arg(long, value_enum, value_name = "LEVEL", default_value = "stderr")]
+    pub save_artifacts: crate::artifacts::SaveArtifacts,
+
+    /// Longest failure message kept in report.json, in bytes (0: no limit); longer ones are
+    /// cut there and written whole to copter-report/errors/
+    #[arg(long, value_name = "BYTES", default_value = "16384")]
+    pub json_error_limit: usize,
+
+    /// Console colors: default (red/green), deuteranopia (blue/orange/purple) or none;
+    /// the latter two also spell each result's status out (PASSED, REGRESSED, ...)
+    #[arg(long, value_enum, value_name = "SCHEME", default_value = "default")]
+    pub colorscheme: crate::console_format::ColorScheme,
+
+    /// Statuses in report.md as symbols (✓ ✗ ⊘, plus the console table) or words (PASSED, REGRESSED, ...)
+    #[arg(long, value_enum, value_name = "STYLE", default_value = "symbols")]
+    pub status_style: crate::report::StatusStyle,
+
+    /// Name this run (a branch, PR or experiment) in the reports and run history
+    #[arg(long, value_name = "LABEL")]
+    pub label: Option<String>,
+
     /// Force testing specific versions, bypassing semver requirements
     /// Accepts multiple versions like --test-versions (e.g., "0.7.0 1.0.0-rc.1")
     /// These versions are tested even if they don't satisfy dependent's requirements
@@ -95,7 +230,39 @@ pub struct CliArgs {
     #[arg(long)]
     pub clean: bool,
 
-    /// Number of error lines to show for compilation failures (default: 10)
+    /// Download every dependent and base crate again instead of using the source cache
+    #[arg(long)]
+    pub refresh: bool,
+
+    /// Download cached crate sources again once they are older than this many days (0: never)
+    #[arg(long, value_name = "DAYS", default_value = "0")]
+    pub cache_ttl: u64,
+
+    /// Run every baseline again instead of reusing results saved by earlier runs
+    /// (<staging>/baseline-cache.json)
+    #[arg(long)]
+    pub no_baseline_cache: bool,
+
+    /// Re-run even if this exact test matrix was already run (see copter-report/history)
+    #[arg(long)]
+    pub force: bool,
+
+    /// Don't repeat a matrix that was already run: print its summary and exit with its result
+    /// (without this, a non-interactive run repeats it and an interactive one asks)
+    #[arg(long, conflicts_with = "force")]
+    pub skip_if_seen: bool,
+
+    /// Scrub the home directory, user name and secret-looking environment values out of
+    /// the reports and logs published from copter-report/ (for CI artifacts)
+    #[arg(long)]
+    pub scrub: bool,
+
+    /// Also scrub the values of these environment variables (implies --scrub)
+    #[arg(long, value_name = "VAR", num_args = 1..)]
+    pub scrub_env: Vec<String>,
+
+    /// Number of error lines to show for the first occurrence of each distinct error (default: 10)
+    /// Later rows failing with the same error signature get a one-line "same as above (xN)" reference.
     #[arg(long, default_value = "10")]
     pub error_lines: usize,
 
@@ -108,11 +275,101 @@ pub struct CliArgs {
     #[arg(long, value_name = "COLUMNS")]
     pub console_width: Option<usize>,
 
+    /// Show each row's start time (local HH:MM:SS) next to its duration
+    #[arg(long)]
+    pub timestamps: bool,
+
+    /// Break the Time column down into fetch/check/test durations, e.g. 0.4/3.1/58.2s
+    #[arg(long)]
+    pub step_times: bool,
+
+    /// Add the error and warning counts of each row's last step to the Result column, e.g. 3E 12W
+    #[arg(long)]
+    pub diagnostic_counts: bool,
+
+    /// Print the table once the run ends, with regressions that share an offered version and
+    /// error signature shown as one row ("and 11 more: ...")
+    #[arg(long)]
+    pub collapse_identical: bool,
+
+    /// Build every dependent for this target triple (default: the host, or a dependent's own
+    /// embedded/wasm target). Tests on a foreign target need --runner.
+    #[arg(long, value_name = "TRIPLE")]
+    pub target: Option<String>,
+
+    /// Run foreign-target tests through `cross`, or through a runner command such as
+    /// "qemu-aarch64 -L /usr/aarch64-linux-gnu" or "wasmtime"
+    #[arg(long, value_name = "CMD")]
+    pub runner: Option<String>,
+
+    /// Build on nightly with RUSTFLAGS="--cfg docsrs" and these base crate features enabled
+    /// in every dependent (default: the base crate's features named *nightly* or *unstable*)
+    #[arg(long, value_name = "FEATURE", num_args = 0..)]
+    pub nightly_gates: Option<Vec<String>>,
+
+    /// Run the whole matrix once per rustup toolchain, e.g. 1.70,1.75,stable, to see whether
+    /// an offered version raises the MSRV of its dependents
+    #[arg(
+        long,
+        value_name = "TOOLCHAIN,...",
+        value_delimiter = ',',
+        conflicts_with_all = ["nightly_gates", "check_first", "resume"]
+    )]
+    pub toolchains: Vec<String>,
+
+    /// Run every step on this exact toolchain (a release like 1.84.0, or nightly-YYYY-MM-DD),
+    /// installed with rustup if missing, and record it in the reports
+    #[arg(long, value_name = "TOOLCHAIN", conflicts_with_all = ["toolchains", "nightly_gates"])]
+    pub pin_toolchain: Option<String>,
+
+    /// Where tests run: here, or as jobs for `cargo-copter worker` processes sharing --queue-dir
+    /// (`k8s` also starts the workers as a Kubernetes Job)
+    #[arg(long, value_enum, default_value = "local")]
+    pub backend: Backend,
+
+    /// Order dependents are tested in: listed, or priority (the ones that failed with an offered
+    /// version in their last recorded run first, then the quickest)
+    #[arg(long, value_enum, default_value = "listed")]
+    pub schedule: crate::scheduler::Order,
+
+    /// Test this many dependents at once (--backend local)
+    #[arg(long, value_name = "N", default_value = "1")]
+    pub parallel: usize,
+
+    /// Console while tests run: lines (one per dependent, updated in place), stream (table rows
+    /// as they finish), or auto (lines for --backend queue/k8s and --parallel on a terminal)
+    #[arg(long, value_enum, value_name = "MODE", default_value = "auto")]
+    pub progress: crate::live::Progress,
+
+    /// Shared directory (NFS, Kubernetes volume, mounted bucket) for --backend queue/k8s
+    #[arg(long, value_name = "DIR")]
+    pub queue_dir: Option<PathBuf>,
+
+    /// Number of worker pods for --backend k8s
+    #[arg(long, value_name = "N", default_value = "8")]
+    pub queue_workers: usize,
+
+    /// --backend queue/k8s: a job claimed this long without a result is queued again (once),
+    /// and the run stops waiting once no job was claimed or finished for this long
+    #[arg(long, value_name = "MINUTES", default_value = "120")]
+    pub queue_timeout: u64,
+
     /// Run inside a Docker container for security isolation (Linux only)
     /// This protects your system from potentially malicious code in dependencies
     #[arg(long)]
     pub docker: bool,
 
+    /// Run each cargo step in a throwaway container of IMAGE (default: rust:latest) that only
+    /// sees the staging directory and the offered crate; cargo-copter itself stays on the host
+    #[arg(
+        long,
+        value_name = "IMAGE",
+        num_args = 0..=1,
+        default_missing_value = crate::docker::DEFAULT_STEP_IMAGE,
+        conflicts_with_all = ["docker", "runner"]
+    )]
+    pub docker_steps: Option<String>,
+
     /// [DEPRECATED] Patch transitive dependencies when using --force-versions
     ///
     /// DEPRECATED: Auto-retry now handles this automatically. When --force-versions
@@ -123,17 +380,310 @@ pub struct CliArgs {
     #[arg(long, requires = "force_versions", hide = true)]
     pub patch_transitive: bool,
 
+    /// What to do with dependents' own [patch], [replace] and .cargo/config.toml overrides
+    /// that conflict with copter's patch: keep them (and name them when a step fails),
+    /// merge (drop only the conflicting ones) or strip (drop all of them)
+    #[arg(long, value_enum, value_name = "MODE", default_value = "keep")]
+    pub dependent_patches: crate::compile::dependent_patches::DependentPatches,
+
+    /// Simulate each dependent's next `cargo update` instead of patching every one: an offered
+    /// version (a prerelease counts as its release) is only tested where the dependent's
+    /// requirement would pick it up from the registry; the others are skipped with the reason
+    #[arg(long, conflicts_with = "force_versions")]
+    pub simulate_update: bool,
+
+    /// Build dependents that use cargo's resolver 1 (edition 2015/2018 without `resolver =
+    /// "2"`) with resolver 2, to tell feature unification differences from real regressions
+    #[arg(long)]
+    pub force_resolver_2: bool,
+
     /// Use simple, verbal output format instead of table
     /// Better for AI parsing and large dependency counts.
     /// Shows clear PASS/FAIL/REGRESSION status for each test.
     #[arg(long)]
     pub simple: bool,
+
+    /// Console output: the table, or one JSON object per finished row on stdout (jsonl),
+    /// with everything else on stderr
+    #[arg(long = "output", value_enum, value_name = "FORMAT", default_value = "table", conflicts_with = "simple")]
+    pub output_format: OutputFormat,
+
+    /// Pause when free disk space in the staging directory drops below this many GB
+    /// Old build output is cleaned first; 0 disables the check.
+    #[arg(long, value_name = "GB", default_value = "2")]
+    pub min_free_gb: u64,
+
+    /// Warn when a cargo step prints nothing for this many minutes (0 disables)
+    /// The warning repeats while the step stays silent.
+    #[arg(long, value_name = "MINUTES", default_value = "10")]
+    pub stall_minutes: u64,
+
+    /// With each stall warning, also print the stalled command's process tree
+    #[arg(long)]
+    pub stall_sample: bool,
+
+    /// Kill a cargo step (and everything it started) after this many minutes; the row
+    /// shows "timeout" and the run goes on
+    #[arg(long, value_name = "MINUTES")]
+    pub timeout_per_step: Option<u64>,
+
+    /// Kill whatever is running once a dependent's steps, across all versions, have taken
+    /// this many minutes; its remaining steps time out too
+    #[arg(long, value_name = "MINUTES")]
+    pub timeout_per_dependent: Option<u64>,
+
+    /// Stop starting dependents, and kill running steps, once the run has taken this many
+    /// minutes; what finished is reported
+    #[arg(long, value_name = "MINUTES")]
+    pub time_budget: Option<u64>,
+}
+
+/// Where the test matrix runs (`--backend`)
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// In this process, one test at a time
+    Local,
+    /// As jobs in --queue-dir, for workers started separately
+    Queue,
+    /// As jobs in --queue-dir, with the workers run as a Kubernetes Job
+    K8s,
+}
+
+/// What the console shows (`--output`)
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// The results table, row by row
+    Table,
+    /// JSON lines for tooling: start, one per row, summary
+    Jsonl,
+}
+
+/// Utility subcommands that replace the normal test run
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum CopterCommand {
+    /// Run end-to-end against the bundled integration fixtures and check the expected statuses
+    /// Works offline; use it to validate your toolchain/environment.
+    SelfTest {
+        /// Fixture directory (default: test-crates/integration-fixtures in the source checkout)
+        #[arg(long, value_name = "DIR")]
+        fixtures: Option<PathBuf>,
+
+        /// Keep the temporary working copy for inspection
+        #[arg(long)]
+        keep: bool,
+    },
+
+    /// Serve local crate directories through a minimal crates.io-compatible API
+    /// Point runs at it with COPTER_REGISTRY_API=<printed url> to test offline or
+    /// validate custom-registry setups.
+    MockRegistry {
+        /// Directory whose subdirectories are crates (default: the integration fixtures)
+        #[arg(long, value_name = "DIR")]
+        fixtures: Option<PathBuf>,
+
+        /// Port to listen on (0 picks a free port)
+        #[arg(long, default_value = "0")]
+        port: u16,
+    },
+
+    /// Check the staging cache for manifests left modified by an interrupted run
+    /// Also reports leftover Cargo.toml backup files; exits non-zero if any are found.
+    VerifyClean {
+        /// Staging directory to check (default: the same as --staging-dir)
+        #[arg(long, value_name = "DIR")]
+        staging_dir: Option<PathBuf>,
+
+        /// Restore modified manifests from their backups and remove the backups
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Release gate with presets: your local WIP vs the latest published version,
+    /// against the top 30 dependents; fails on regressions and writes a PR-comment-ready
+    /// copter-report/pr-comment.md. Options given before `check-release` still apply.
+    CheckRelease {
+        /// Number of top dependents (by downloads) to test
+        #[arg(long, default_value = "30")]
+        top_dependents: usize,
+
+        /// Crate to release (directory or Cargo.toml; default: the current directory)
+        #[arg(long, short = 'p', value_name = "PATH")]
+        path: Option<PathBuf>,
+    },
+
+    /// Publish the local crate to a staging registry (kellnr, margo, ...), then test dependents
+    /// against the published version, resolved from that registry's index. The token is read
+    /// from CARGO_REGISTRIES_COPTER_STAGING_TOKEN. Options given before `stage-publish` still apply.
+    StagePublish {
+        /// The staging registry's index URL (e.g. sparse+http://localhost:8000/api/v1/crates/)
+        #[arg(long, value_name = "URL")]
+        index: String,
+
+        /// Crate to publish (directory or Cargo.toml; default: the current directory)
+        #[arg(long, short = 'p', value_name = "PATH")]
+        path: Option<PathBuf>,
+    },
+
+    /// Test two variants of the crate head to head over the same dependents in one run,
+    /// sharing the baseline and downloads; writes copter-report/ab.md. Options given
+    /// before `ab` still apply.
+    Ab {
+        /// First variant: path:DIR or a published version
+        #[arg(long = "a", value_name = "VARIANT")]
+        a: String,
+
+        /// Second variant: path:DIR or a published version
+        #[arg(long = "b", value_name = "VARIANT")]
+        b: String,
+    },
+
+    /// Quick early warning for pre-push hooks: fetch and check only, against the top 10
+    /// dependents, within a hard 5-minute budget; reuses a dependents list cached in the
+    /// last day and, for an unchanged crate, the last verdict. Options given before
+    /// `smoke` still apply.
+    Smoke {
+        /// Number of top dependents (by downloads) to check
+        #[arg(long, default_value = "10")]
+        top_dependents: usize,
+
+        /// Minutes the whole run may take
+        #[arg(long, value_name = "MINUTES", default_value = "5")]
+        budget: u64,
+
+        /// Crate to check (directory or Cargo.toml; default: the current directory)
+        #[arg(long, short = 'p', value_name = "PATH")]
+        path: Option<PathBuf>,
+    },
+
+    /// Test dependents against the local crate with features removed or modules cfg'd out,
+    /// next to the unmodified crate, and count the dependents the removal would break.
+    /// Options given before `what-if` still apply.
+    WhatIf {
+        /// Feature (or optional dependency) to remove from the manifest, along with its
+        /// mentions in other features
+        #[arg(long, value_name = "FEATURE", num_args = 1..)]
+        disable_feature: Vec<String>,
+
+        /// Module (path from the crate root, e.g. codec::png) to put behind #[cfg(any())]
+        #[arg(long, value_name = "MODULE", num_args = 1..)]
+        cfg_out: Vec<String>,
+
+        /// Crate to change (directory or Cargo.toml; default: the current directory)
+        #[arg(long, short = 'p', value_name = "PATH")]
+        path: Option<PathBuf>,
+    },
+
+    /// Write a copter.toml for this crate: proposes its top dependents and excludes the ones
+    /// already broken in the last run (copter-report/report.json), asking before each part.
+    Init {
+        /// Crate directory (default: the current directory)
+        #[arg(long, short = 'p', value_name = "PATH")]
+        path: Option<PathBuf>,
+
+        /// Number of top dependents (by downloads) to propose
+        #[arg(long, default_value = "10")]
+        top: usize,
+
+        /// Write the proposal without asking
+        #[arg(long)]
+        yes: bool,
+
+        /// Replace an existing copter.toml
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Run jobs queued by `--backend queue|k8s` until the queue is empty
+    Worker {
+        /// The coordinator's --queue-dir, as seen from this machine
+        #[arg(long, value_name = "DIR")]
+        queue_dir: PathBuf,
+
+        /// Keep polling for new jobs instead of exiting when none are left
+        #[arg(long)]
+        wait: bool,
+
+        /// Run N workers on this machine, splitting its cores between their cargo builds
+        #[arg(long, short = 'j', value_name = "N", default_value = "1")]
+        jobs: usize,
+    },
+
+    /// Print the default message catalog, a starting point for --messages
+    Messages,
+
+    /// Check a report.json against the published JSON Schema (schema/copter-report.schema.json)
+    /// Exits non-zero and lists each violation if the file doesn't match.
+    ValidateReport {
+        /// Report file to check
+        file: PathBuf,
+
+        /// Upgrade a report from an older copter to the current schema and write it here
+        #[arg(long, value_name = "OUT")]
+        migrate: Option<PathBuf>,
+    },
+
+    /// Compare two report.json files (paths or URLs): dependents newly regressed, fixed, or
+    /// failing with a different error. Exits non-zero if any newly regressed.
+    Diff {
+        /// The earlier report
+        old: String,
+
+        /// The later report
+        new: String,
+    },
+
+    /// Work with the run history in copter-report/history
+    History {
+        #[command(subcommand)]
+        action: HistoryCommand,
+    },
+}
+
+/// `history` subcommands
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum HistoryCommand {
+    /// Every recorded result as one (run, dependent, base version, status, duration) CSV row,
+    /// or an HTML page of pass-rate trends and the regressions each run introduced
+    Export {
+        #[arg(long, value_enum, default_value = "csv")]
+        format: HistoryFormat,
+
+        /// Only results for this base crate
+        #[arg(long = "crate", value_name = "NAME")]
+        crate_name: Option<String>,
+
+        /// Only runs given this `--label`
+        #[arg(long, value_name = "LABEL")]
+        label: Option<String>,
+
+        /// Write to this file instead of stdout
+        #[arg(long, short = 'o', value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Output format for `history export`
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryFormat {
+    /// One line per recorded result
+    Csv,
+    /// Pass-rate trends per version line and a dependents × runs status grid
+    Html,
 }
 
 impl CliArgs {
     /// Parse command-line arguments
     pub fn parse_args() -> Self {
-        let mut args = CliArgs::parse();
+        Self::try_parse_args(strip_cargo_subcommand(std::env::args())).unwrap_or_else(|e| e.exit())
+    }
+
+    /// Parse `args` (program name first) as the command line is parsed
+    pub fn try_parse_args<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let mut args = CliArgs::try_parse_from(args)?;
 
         // Split test_versions on whitespace to support quoted lists like '0.8.51 0.8.91-alpha.3'
         args.test_versions =
@@ -143,7 +693,7 @@ impl CliArgs {
         args.force_versions =
             args.force_versions.iter().flat_map(|s| s.split_whitespace().map(|v| v.to_string())).collect();
 
-        args
+        Ok(args)
     }
 
     /// Validate argument combinations
@@ -153,6 +703,14 @@ impl CliArgs {
             return Err("Cannot specify both --only-fetch and --only-check".to_string());
         }
 
+        if let Some(missing) = self.report_template.iter().find(|t| !t.is_file()) {
+            return Err(format!("Report template not found: {}", missing.display()));
+        }
+
+        if self.backend != Backend::Local && self.queue_dir.is_none() {
+            return Err("--backend queue/k8s needs --queue-dir <DIR> on storage the workers share".to_string());
+        }
+
         // Need at least one of: top_dependents, dependents, dependent_paths, dependent_glob, or dependent_dir
         if self.top_dependents == 0
             && self.dependents.is_empty()
@@ -179,14 +737,107 @@ impl CliArgs {
         Ok(())
     }
 
-    /// Should we skip cargo check?
-    pub fn should_skip_check(&self) -> bool {
-        self.only_fetch
+    /// Turn `check-release` into the flags it stands for; true when it was given
+    pub fn apply_check_release(&mut self) -> bool {
+        match self.command.take() {
+            Some(CopterCommand::CheckRelease { top_dependents, path }) => {
+                self.top_dependents = top_dependents;
+                self.path = path.or_else(|| self.path.take());
+                true
+            }
+            other => {
+                self.command = other;
+                false
+            }
+        }
+    }
+
+    /// Take a `stage-publish` command apart into the options it implies; returns its index URL
+    pub fn apply_stage_publish(&mut self) -> Option<String> {
+        match self.command.take() {
+            Some(CopterCommand::StagePublish { index, path }) => {
+                self.path = path.or_else(|| self.path.take());
+                Some(index)
+            }
+            other => {
+                self.command = other;
+                None
+            }
+        }
+    }
+
+    /// `--prefetch` fetches only, and runs every baseline so its dependencies are vendored
+    pub fn apply_prefetch(&mut self) {
+        if self.prefetch.is_some() {
+            self.only_fetch = true;
+            self.no_baseline_cache = true;
+        }
+    }
+
+    /// Turn `smoke` into the flags it stands for; true when it was given
+    pub fn apply_smoke(&mut self) -> bool {
+        match self.command.take() {
+            Some(CopterCommand::Smoke { top_dependents, budget, path }) => {
+                self.top_dependents = top_dependents;
+                self.only_check = true;
+                self.time_budget = Some(budget);
+                self.path = path.or_else(|| self.path.take());
+                true
+            }
+            other => {
+                self.command = other;
+                false
+            }
+        }
+    }
+
+    /// Take an `ab` command apart into its two variants; a local variant names the base
+    /// crate when neither --path nor --crate does
+    pub fn apply_ab(&mut self) -> Option<(String, String)> {
+        match self.command.take() {
+            Some(CopterCommand::Ab { a, b }) => {
+                if self.path.is_none() && self.crate_name.is_none() {
+                    self.path = crate::ab::local_path(&a).or_else(|| crate::ab::local_path(&b));
+                }
+                Some((a, b))
+            }
+            other => {
+                self.command = other;
+                None
+            }
+        }
     }
 
-    /// Should we skip cargo test?
-    pub fn should_skip_test(&self) -> bool {
-        self.only_fetch || self.only_check
+    /// Take a `what-if` command apart into the local crate's path and what to remove
+    pub fn apply_what_if(&mut self) -> Option<crate::what_if::Removal> {
+        match self.command.take() {
+            Some(CopterCommand::WhatIf { disable_feature, cfg_out, path }) => {
+                self.path = path.or_else(|| self.path.take());
+                Some(crate::what_if::Removal { features: disable_feature, modules: cfg_out })
+            }
+            other => {
+                self.command = other;
+                None
+            }
+        }
+    }
+
+    /// Where lines for people go (stderr with `--output jsonl`)
+    pub fn console(&self) -> crate::ui::Console {
+        crate::ui::Console { jsonl: self.output_format == OutputFormat::Jsonl }
+    }
+
+    /// Steps to run, from --steps or the --only-* shorthands (default: all)
+    pub fn steps(&self) -> Steps {
+        if let Some(steps) = &self.steps {
+            steps.clone()
+        } else if self.only_fetch {
+            Steps::through(CommandType::Fetch)
+        } else if self.only_check {
+            Steps::through(CommandType::Check)
+        } else {
+            Steps::all()
+        }
     }
 
     /// Get the staging directory, using the default cache location if not specified
@@ -195,6 +846,15 @@ impl CliArgs {
     }
 }
 
+/// `cargo copter ...` runs `cargo-copter copter ...`; drop the subcommand name cargo passes along
+fn strip_cargo_subcommand(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut args: Vec<String> = args.into_iter().collect();
+    if args.get(1).map(String::as_str) == Some("copter") {
+        args.remove(1);
+    }
+    args
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,6 +884,70 @@ mod tests {
             docker: false,
             patch_transitive: false,
             simple: false,
+            min_free_gb: 2,
+            command: None,
+            force: false,
+            steps: None,
+            report_template: vec![],
+            messages: None,
+            stall_minutes: 10,
+            stall_sample: false,
+            compare_with: None,
+            exclude: vec![],
+            dependent_settings: Default::default(),
+            only_dependents: vec![],
+            backend: Backend::Local,
+            queue_dir: None,
+            queue_workers: 8,
+            target: None,
+            runner: None,
+            nightly_gates: None,
+            quarantine_after: 3,
+            include_quarantined: false,
+            scrub: false,
+            scrub_env: vec![],
+            dependent_patches: Default::default(),
+            simulate_update: false,
+            aliases: vec![],
+            timestamps: false,
+            step_times: false,
+            check_first: false,
+            resume: false,
+            from: None,
+            deterministic: false,
+            label: None,
+            staging_copy: crate::staging::CopyMethod::Auto,
+            index_url: None,
+            dependents_sort: crate::api::DependentSort::AllTime,
+            fail_on_skipped: false,
+            status_style: crate::report::StatusStyle::Symbols,
+            colorscheme: crate::console_format::ColorScheme::Default,
+            progress: crate::live::Progress::Auto,
+            save_artifacts: crate::artifacts::SaveArtifacts::Stderr,
+            json_error_limit: 16384,
+            refresh: false,
+            cache_ttl: 0,
+            dependent_git: vec![],
+            github: false,
+            timeout_per_step: None,
+            timeout_per_dependent: None,
+            time_budget: None,
+            no_baseline_cache: false,
+            schedule: Default::default(),
+            parallel: 1,
+            package: None,
+            diagnostic_counts: false,
+            toolchains: vec![],
+            docker_steps: None,
+            collapse_identical: false,
+            prefetch: None,
+            offline: None,
+            force_resolver_2: false,
+            pin_toolchain: None,
+            output_format: OutputFormat::Table,
+            github_org: vec![],
+            skip_if_seen: false,
+            queue_timeout: 120,
         };
         assert!(args.validate().is_err());
     }
@@ -256,9 +980,88 @@ mod tests {
             docker: false,
             patch_transitive: false,
             simple: false,
+            min_free_gb: 2,
+            command: None,
+            force: false,
+            steps: None,
+            report_template: vec![],
+            messages: None,
+            stall_minutes: 10,
+            stall_sample: false,
+            compare_with: None,
+            exclude: vec![],
+            dependent_settings: Default::default(),
+            only_dependents: vec![],
+            backend: Backend::Local,
+            queue_dir: None,
+            queue_workers: 8,
+            target: None,
+            runner: None,
+            nightly_gates: None,
+            quarantine_after: 3,
+            include_quarantined: false,
+            scrub: false,
+            scrub_env: vec![],
+            dependent_patches: Default::default(),
+            simulate_update: false,
+            aliases: vec![],
+            timestamps: false,
+            step_times: false,
+            check_first: false,
+            resume: false,
+            from: None,
+            deterministic: false,
+            label: None,
+            staging_copy: crate::staging::CopyMethod::Auto,
+            index_url: None,
+            dependents_sort: crate::api::DependentSort::AllTime,
+            fail_on_skipped: false,
+            status_style: crate::report::StatusStyle::Symbols,
+            colorscheme: crate::console_format::ColorScheme::Default,
+            progress: crate::live::Progress::Auto,
+            save_artifacts: crate::artifacts::SaveArtifacts::Stderr,
+            json_error_limit: 16384,
+            refresh: false,
+            cache_ttl: 0,
+            dependent_git: vec![],
+            github: false,
+            timeout_per_step: None,
+            timeout_per_dependent: None,
+            time_budget: None,
+            no_baseline_cache: false,
+            schedule: Default::default(),
+            parallel: 1,
+            package: None,
+            diagnostic_counts: false,
+            toolchains: vec![],
+            docker_steps: None,
+            collapse_identical: false,
+            prefetch: None,
+            offline: None,
+            force_resolver_2: false,
+            pin_toolchain: None,
+            output_format: OutputFormat::Table,
+            github_org: vec![],
+            skip_if_seen: false,
+            queue_timeout: 120,
         };
         let result = args.validate();
         std::fs::remove_file("./Cargo.toml.test").ok();
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_cargo_subcommand_and_check_release() {
+        let argv = ["cargo-copter", "copter", "--top-versions", "5", "check-release", "--path", "../rgb"];
+        let mut args = CliArgs::parse_from(strip_cargo_subcommand(argv.iter().map(|s| s.to_string())));
+        assert!(args.apply_check_release());
+        assert!(args.command.is_none());
+        assert_eq!(args.top_dependents, 30);
+        assert_eq!(args.path, Some(PathBuf::from("../rgb")));
+        assert_eq!(args.top_versions, Some(5));
+
+        let mut args = CliArgs::parse_from(["cargo-copter", "messages"]);
+        assert!(!args.apply_check_release());
+        assert!(matches!(args.command, Some(CopterCommand::Messages)));
+    }
 }
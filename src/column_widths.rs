@@ -0,0 +1,49 @@
+/// Console column widths learned from earlier runs
+///
+/// The table's Spec and Resolved columns have fixed guesses (12 and 18 characters) and the
+/// Dependent column gets what's left, so short specs waste space while long dependent names
+/// get truncated. After each table run the widest spec, resolved and dependent cells are
+/// saved to `copter-report/history/column-widths-<crate>.json`; the next run for that crate
+/// sizes Spec and Resolved to fit them and gives the rest to Dependent. Only the last run
+/// counts, so a one-off long value doesn't widen the table for good.
+use crate::types::OfferedRow;
+use std::path::{Path, PathBuf};
+
+/// Widest cell contents seen in one run, in display columns (without cell padding)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ObservedWidths {
+    pub spec: usize,
+    pub resolved: usize,
+    pub dependent: usize,
+}
+
+impl ObservedWidths {
+    /// Widest cells of `rows` as the console table would render them
+    pub fn from_rows(rows: &[OfferedRow]) -> Self {
+        rows.iter().map(crate::report::cell_widths).fold(Self::default(), |max, w| ObservedWidths {
+            spec: max.spec.max(w.spec),
+            resolved: max.resolved.max(w.resolved),
+            dependent: max.dependent.max(w.dependent),
+        })
+    }
+}
+
+/// Widths saved by the last run for `base_crate`, if any
+pub fn load(report_dir: &Path, base_crate: &str) -> Option<ObservedWidths> {
+    let text = std::fs::read_to_string(path(report_dir, base_crate)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Save this run's widths for the next run
+pub fn save(report_dir: &Path, base_crate: &str, observed: &ObservedWidths) -> Result<(), String> {
+    let path = path(report_dir, base_crate);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(observed).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn path(report_dir: &Path, base_crate: &str) -> PathBuf {
+    report_dir.join(crate::history::HISTORY_DIR).join(format!("column-widths-{}.json", base_crate))
+}
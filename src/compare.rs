@@ -0,0 +1,106 @@
+/// Gating against a reference ("blessed") report (`--compare-with`)
+///
+/// PR CI wants to fail only on regressions the PR introduces. The reference is the
+/// `report.json` of a trusted run, usually the main branch's scheduled one, given as a
+/// URL or a file. A regression in this run is *known* when the reference has a
+/// regression for the same dependent (name and version), whichever base version it was
+/// against; everything else is *new* and decides the exit code.
+use crate::error::CopterError;
+use crate::registry::Registry;
+use crate::types::OfferedRow;
+use crate::ui::Console;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Regressions of this run, split by whether the reference already had them
+#[derive(Debug, Default, PartialEq)]
+pub struct RegressionGate {
+    /// Label of the reference report, for messages
+    pub source: String,
+    /// "dependent:version with base-version", regressed only in this run
+    pub new: Vec<String>,
+    /// Regressions the reference report also has
+    pub known: Vec<String>,
+}
+
+/// Load the reference report's rows from a URL (refused with `--offline`) or a file,
+/// upgrading older report versions
+pub fn load_reference(registry: &Registry, source: &str) -> Result<Vec<OfferedRow>, CopterError> {
+    let report = if source.starts_with("http://") || source.starts_with("https://") {
+        let bytes =
+            registry.get(source).map_err(|e| e.context(format!("Failed to download reference report {}", source)))?;
+        let report = serde_json::from_slice(&bytes)
+            .map_err(|e| CopterError::Config(format!("Reference report {} is not valid JSON: {}", source, e)))?;
+        crate::report::migrate_json_report(report).map_err(|e| CopterError::Config(format!("{}: {}", source, e)))?
+    } else {
+        crate::report::import_json_report(Path::new(source)).map_err(CopterError::Config)?
+    };
+    let rows = report.get("test_results").cloned().unwrap_or_else(|| serde_json::json!([]));
+    serde_json::from_value(rows).map_err(|e| CopterError::Config(format!("{}: unreadable test_results: {}", source, e)))
+}
+
+impl RegressionGate {
+    /// Split this run's regressions into new and known
+    pub fn new(source: &str, current: &[OfferedRow], reference: &[OfferedRow]) -> Self {
+        let known_regressed: HashSet<(&str, &str)> = reference
+            .iter()
+            .filter(|row| row.is_regression())
+            .map(|row| (row.primary.dependent_name.as_str(), row.primary.dependent_version.as_str()))
+            .collect();
+
+        let mut gate = RegressionGate { source: source.to_string(), ..Default::default() };
+        for row in current.iter().filter(|row| row.is_regression()) {
+            let offered = row.offered.as_ref().map(|o| o.version.as_str()).unwrap_or("baseline");
+            let (name, version) = (row.primary.dependent_name.as_str(), row.primary.dependent_version.as_str());
+            let label = format!("{}:{} with {}", name, version, offered);
+            if known_regressed.contains(&(name, version)) {
+                gate.known.push(label);
+            } else {
+                gate.new.push(label);
+            }
+        }
+        gate
+    }
+
+    /// Whether the run should fail
+    pub fn has_new_regressions(&self) -> bool {
+        !self.new.is_empty()
+    }
+
+    /// One line per regression, new ones first
+    pub fn print(&self, console: Console) {
+        console.say(&format!("\nCompared with {}:", self.source));
+        if self.new.is_empty() && self.known.is_empty() {
+            console.say("  no regressions");
+        }
+        for label in &self.new {
+            console.say(&format!("  NEW   {}", label));
+        }
+        for label in &self.known {
+            console.say(&format!("  known {} (also regressed in the reference report; not failing the run)", label));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+
+    fn row(dependent: &str, offered: &str, passed: bool) -> OfferedRow {
+        crate::test_rows::row(dependent).offered(offered).passed(passed).build()
+    }
+
+    #[test]
+    fn test_only_new_regressions_gate() {
+        let reference = vec![row("image", "0.8.50", false), row("png", "0.8.50", true)];
+        let current = vec![row("image", "0.9.0", false), row("png", "0.9.0", false), row("gif", "0.9.0", true)];
+
+        let gate = RegressionGate::new("main-report.json", &current, &reference);
+        assert_eq!(gate.known, vec!["image:1.0.0 with 0.9.0"]);
+        assert_eq!(gate.new, vec!["png:1.0.0 with 0.9.0"]);
+        assert!(gate.has_new_regressions());
+
+        assert!(!RegressionGate::new("x", &current[..1], &reference).has_new_regressions());
+    }
+}
@@ -1,240 +1,32 @@
+use crate::audit::AuditLog;
+use crate::error::CopterError;
 use crate::error_extract::{Diagnostic, extract_crates_needing_patch, has_multiple_version_conflict, parse_cargo_json};
 use crate::metadata;
-use fs2::FileExt;
-use lazy_static::lazy_static;
+use crate::paths;
+use crate::stage_publish::StagingRegistry;
+use crate::types::{CommandType, Steps};
+use dependent_patches::DependentPatches;
+use failure_log::{FailureEntry, FailureLog};
+use heartbeat::Heartbeat;
 use log::{debug, warn};
-use std::env;
-use std::fs::{self, OpenOptions};
-use std::io::{BufWriter, Read, Write};
+use patching::{
+    DependencyOverrideMode, absolute, apply_dependency_override, apply_registry_override, restore_cargo_toml,
+};
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
+pub mod dependent_patches;
+pub mod failure_log;
+pub mod heartbeat;
+pub mod nightly;
+pub mod patching;
+pub mod target;
+
 // Constants for formatting and limits
-const LOG_SEPARATOR_LENGTH: usize = 100;
 const MAX_METADATA_LOG_LINES: usize = 100;
 
-// Failure log file path
-lazy_static! {
-    static ref FAILURE_LOG: Mutex<Option<PathBuf>> = Mutex::new(None);
-    static ref BUILD_FAILURE_LOG: Mutex<Option<PathBuf>> = Mutex::new(None);
-    // Track last error signature for deduplication
-    static ref LAST_ERROR_SIGNATURE: Mutex<Option<String>> = Mutex::new(None);
-}
-
-/// Initialize the failure log file
-pub fn init_failure_log(log_path: PathBuf) {
-    let mut log = FAILURE_LOG.lock().unwrap();
-    *log = Some(log_path.clone());
-
-    // Also initialize build-only log
-    let build_log_path = log_path.with_file_name("copter-build-failures.log");
-    let mut build_log = BUILD_FAILURE_LOG.lock().unwrap();
-    *build_log = Some(build_log_path);
-
-    // Clear the error signature when initializing
-    let mut sig = LAST_ERROR_SIGNATURE.lock().unwrap();
-    *sig = None;
-}
-
-/// Log a compilation failure to the failure log file with proper locking
-#[allow(clippy::too_many_arguments)]
-pub fn log_failure(
-    dependent: &str,
-    dependent_version: &str,
-    base_crate: &str,
-    test_label: &str, // "baseline", "WIP", or version number
-    command: &str,
-    exit_code: Option<i32>,
-    stdout: &str,
-    stderr: &str,
-) {
-    log_failure_with_diagnostics(
-        dependent,
-        dependent_version,
-        base_crate,
-        test_label,
-        command,
-        exit_code,
-        stdout,
-        stderr,
-        &[],
-    );
-}
-
-/// Log a compilation failure with parsed diagnostics for better readability
-#[allow(clippy::too_many_arguments)]
-pub fn log_failure_with_diagnostics(
-    dependent: &str,
-    dependent_version: &str,
-    base_crate: &str,
-    test_label: &str, // "baseline", "WIP", or version number
-    command: &str,
-    exit_code: Option<i32>,
-    stdout: &str,
-    stderr: &str,
-    diagnostics: &[Diagnostic],
-) {
-    let (log_path, build_log_path) = {
-        let log = FAILURE_LOG.lock().unwrap();
-        let build_log = BUILD_FAILURE_LOG.lock().unwrap();
-        match (&*log, &*build_log) {
-            (Some(path), Some(build_path)) => (path.clone(), Some(build_path.clone())),
-            (Some(path), None) => (path.clone(), None),
-            _ => return, // Logging not initialized
-        }
-    };
-
-    // Generate error signature for deduplication
-    let current_signature = if !diagnostics.is_empty() {
-        let error_text = diagnostics.iter().map(|d| d.rendered.as_str()).collect::<Vec<_>>().join("\n");
-        crate::report::error_signature(&error_text)
-    } else {
-        crate::report::error_signature(stderr)
-    };
-
-    // Check if this error matches the previous one
-    let is_duplicate = {
-        let mut last_sig = LAST_ERROR_SIGNATURE.lock().unwrap();
-        let duplicate = last_sig.as_ref().map(|s| s == &current_signature).unwrap_or(false);
-        *last_sig = Some(current_signature);
-        duplicate
-    };
-
-    // Write to main debug log
-    write_failure_to_log(
-        &log_path,
-        "FAILURE",
-        dependent,
-        dependent_version,
-        base_crate,
-        test_label,
-        command,
-        exit_code,
-        stderr,
-        diagnostics,
-        is_duplicate,
-    );
-
-    // If this is a build/check failure, also write to build-specific log
-    let is_build_failure = command.contains("cargo fetch") || command.contains("cargo check");
-    if is_build_failure && let Some(build_path) = build_log_path {
-        write_failure_to_log(
-            &build_path,
-            "BUILD FAILURE",
-            dependent,
-            dependent_version,
-            base_crate,
-            test_label,
-            command,
-            exit_code,
-            stderr,
-            diagnostics,
-            is_duplicate,
-        );
-    }
-}
-
-/// Helper function to write a failure entry to a specific log file
-#[allow(clippy::too_many_arguments)]
-fn write_failure_to_log(
-    log_path: &Path,
-    log_type: &str, // "FAILURE" or "BUILD FAILURE"
-    dependent: &str,
-    dependent_version: &str,
-    base_crate: &str,
-    test_label: &str,
-    command: &str,
-    exit_code: Option<i32>,
-    stderr: &str,
-    diagnostics: &[Diagnostic],
-    is_duplicate: bool,
-) {
-    // Open file with append mode
-    let file = match OpenOptions::new().create(true).append(true).open(log_path) {
-        Ok(f) => f,
-        Err(e) => {
-            eprintln!("Failed to open {} log: {}", log_type, e);
-            return;
-        }
-    };
-
-    // Lock the file for exclusive write access
-    if let Err(e) = file.lock_exclusive() {
-        eprintln!("Failed to lock {} log: {}", log_type, e);
-        return;
-    }
-
-    let mut writer = BufWriter::new(&file);
-    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
-    let exit_str = exit_code.map(|c| c.to_string()).unwrap_or_else(|| "N/A".to_string());
-
-    let _ = writeln!(writer, "\n{}", "=".repeat(LOG_SEPARATOR_LENGTH));
-    let _ = writeln!(
-        writer,
-        "[{}] {}: {} {} testing {} {}",
-        timestamp, log_type, dependent, dependent_version, base_crate, test_label
-    );
-    let _ = writeln!(writer, "{}", "=".repeat(LOG_SEPARATOR_LENGTH));
-    let _ = writeln!(writer, "Command: {}", command);
-    let _ = writeln!(writer, "Exit code: {}", exit_str);
-
-    if is_duplicate {
-        let _ = writeln!(writer, "\n--- SAME FAILURE AS PREVIOUS ---");
-    } else if !diagnostics.is_empty() {
-        let _ = writeln!(writer, "\n--- ERRORS ---");
-        for (idx, diag) in diagnostics.iter().enumerate() {
-            let level_str = match diag.level {
-                crate::error_extract::DiagnosticLevel::Error => "error",
-                crate::error_extract::DiagnosticLevel::Warning => "warning",
-                crate::error_extract::DiagnosticLevel::Help => "help",
-                crate::error_extract::DiagnosticLevel::Note => "note",
-                crate::error_extract::DiagnosticLevel::Other(ref s) => s.as_str(),
-            };
-            let _ = writeln!(writer, "\n{}. [{}] {}", idx + 1, level_str, diag.message);
-
-            if !diag.rendered.is_empty() {
-                let _ = writeln!(writer, "{}", diag.rendered);
-            }
-        }
-    } else {
-        let _ = writeln!(writer, "\n--- STDERR (no structured errors) ---");
-        for line in stderr.lines() {
-            if !line.trim_start().starts_with('{') {
-                let _ = writeln!(writer, "{}", line);
-            }
-        }
-    }
-
-    let _ = writeln!(writer, "\n{}", "=".repeat(LOG_SEPARATOR_LENGTH));
-    let _ = writer.flush();
-    // Unlock is automatic when file goes out of scope
-}
-
-/// Restore Cargo.toml from the original backup before testing
-/// This prevents contamination between test runs in the cached staging directory
-///
-/// CRITICAL: This is idempotent and Ctrl+C safe. If a backup exists from a previous
-/// (possibly interrupted) run, we restore from it rather than overwriting it.
-pub fn restore_cargo_toml(staging_path: &Path) -> Result<(), String> {
-    let cargo_toml = staging_path.join("Cargo.toml");
-    let original = staging_path.join("Cargo.toml.original.txt");
-
-    // CRITICAL: Never overwrite existing .original - it might be from an interrupted run
-    if !original.exists() {
-        if cargo_toml.exists() {
-            fs::copy(&cargo_toml, &original).map_err(|e| format!("Failed to save original Cargo.toml: {}", e))?;
-            debug!("Saved original Cargo.toml to {:?}", original);
-        }
-    } else {
-        // Restore from existing original (might be from interrupted run)
-        fs::copy(&original, &cargo_toml).map_err(|e| format!("Failed to restore Cargo.toml from original: {}", e))?;
-        debug!("Restored Cargo.toml from existing original backup in {:?}", staging_path);
-    }
-    Ok(())
-}
-
 /// The type of compilation step being performed
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum CompileStep {
@@ -273,12 +65,45 @@ pub struct CompileResult {
     pub stderr: String,
     pub duration: Duration,
     pub diagnostics: Vec<Diagnostic>,
+    /// Arguments passed to `cargo` for this step
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Unified diff of the dependent's Cargo.toml against its pristine copy when this step ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest_diff: Option<String>,
+    /// Killed at its time limit (`--timeout-per-step`, `--timeout-per-dependent`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
 }
 
 impl CompileResult {
     pub fn failed(&self) -> bool {
         !self.success
     }
+
+    /// If this step failed because of the environment (disk, network, toolchain), why
+    pub fn env_failure(&self) -> Option<&'static str> {
+        if self.success || self.timed_out {
+            return None;
+        }
+        crate::categorize::environment_failure(&self.stderr, self.step == CompileStep::Fetch)
+    }
+
+    /// (errors, warnings) among the diagnostics, without rustc's "aborting due to 3 previous
+    /// errors" and "12 warnings emitted" summaries
+    pub fn diagnostic_counts(&self) -> (usize, usize) {
+        let summary = |message: &str| {
+            message.starts_with("aborting due to")
+                || message.ends_with("warnings emitted")
+                || message.ends_with("warning emitted")
+        };
+        let counted = self.diagnostics.iter().filter(|d| d.primary_span.is_some() || !summary(&d.message));
+        counted.fold((0, 0), |(errors, warnings), d| match d.level {
+            crate::error_extract::DiagnosticLevel::Error => (errors + 1, warnings),
+            crate::error_extract::DiagnosticLevel::Warning => (errors, warnings + 1),
+            _ => (errors, warnings),
+        })
+    }
 }
 
 /// Verify that the correct version of a dependency is being used
@@ -306,7 +131,15 @@ fn verify_dependency_version(crate_path: &Path, dep_name: &str) -> Option<String
         }
     };
 
-    // First try resolve.nodes to find the actually-used version (handles multiple versions correctly)
+    // The root's own edge, or the one its build script or proc macros compile against
+    if let Ok(parsed) = metadata::parse_metadata(&stdout)
+        && let Some(usage) = metadata::root_usage(&parsed, dep_name)
+    {
+        debug!("✓ Verified {} version: {} (via {:?})", dep_name, usage.version, usage.via);
+        return Some(usage.version);
+    }
+
+    // Otherwise the first edge in resolve.nodes (e.g. a virtual workspace root)
     if let Some(resolve) = metadata.get("resolve")
         && let Some(nodes) = resolve.get("nodes").and_then(|n| n.as_array())
     {
@@ -355,6 +188,34 @@ fn verify_dependency_version(crate_path: &Path, dep_name: &str) -> Option<String
     None
 }
 
+/// The git or path copy of `dep_name` the dependent at `crate_path` builds against instead
+/// of a registry version or copter's patch in `ours`, e.g. "git https://github.com/someone/rgb"
+pub fn detect_fork(crate_path: &Path, dep_name: &str, ours: Option<&Path>) -> Option<String> {
+    let parsed = cargo_metadata(crate_path)?;
+    let fork = metadata::root_usage(&parsed, dep_name)?.fork(&crate::paths::canonicalize(crate_path), ours);
+    debug!("{} resolved from {:?}", dep_name, fork);
+    fork
+}
+
+/// Features of `dep_name` enabled in the build of the dependent at `crate_path`, from its
+/// resolve graph (`["default", "serde"]`); None when cargo can't resolve it
+pub fn base_features(crate_path: &Path, dep_name: &str) -> Option<Vec<String>> {
+    let features = metadata::root_usage(&cargo_metadata(crate_path)?, dep_name)?.features;
+    debug!("{} features: {:?}", dep_name, features);
+    Some(features)
+}
+
+/// `cargo metadata` of the crate at `crate_path`, parsed
+fn cargo_metadata(crate_path: &Path) -> Option<metadata::ParsedMetadata> {
+    let output =
+        Command::new("cargo").args(["metadata", "--format-version=1"]).current_dir(crate_path).output().ok()?;
+    if !output.status.success() {
+        debug!("cargo metadata failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        return None;
+    }
+    metadata::parse_metadata(&String::from_utf8_lossy(&output.stdout)).ok()
+}
+
 /// Extract the version requirement spec for a dependency using cargo metadata
 /// Returns None if the dependency is not found
 fn extract_dependency_spec(crate_path: &Path, dep_name: &str) -> Result<Option<String>, String> {
@@ -391,6 +252,15 @@ fn extract_dependency_spec(crate_path: &Path, dep_name: &str) -> Result<Option<S
         }
     }
 
+    // Used only by a proc-macro crate or build-dependency the dependent pulls in: the
+    // [patch] reaches it there, and check/test compile it, so it is still worth testing
+    if let Some(usage) = metadata::root_usage(&parsed, dep_name)
+        && !usage.via.is_empty()
+    {
+        debug!("  Extracted spec {} of {} (build time, via {})", usage.spec, dep_name, usage.via.join(" → "));
+        return Ok(Some(usage.spec));
+    }
+
     Ok(None)
 }
 
@@ -430,142 +300,6 @@ fn extract_spec_from_toml(crate_path: &Path, dep_name: &str) -> Result<Option<St
     Ok(None)
 }
 
-/// How to apply a dependency override
-#[derive(Debug, Clone, Copy)]
-enum DependencyOverrideMode {
-    /// Replace dependency spec directly - bypasses semver requirements
-    Force,
-}
-
-/// Apply a dependency override to Cargo.toml - Force mode only
-fn apply_dependency_override(
-    crate_path: &Path,
-    dep_name: &str,
-    override_path: &Path,
-    mode: DependencyOverrideMode,
-) -> Result<(), String> {
-    use std::io::{Read, Write};
-
-    // Convert to absolute path
-    let override_path = if override_path.is_absolute() {
-        override_path.to_path_buf()
-    } else {
-        env::current_dir().map_err(|e| format!("Failed to get current dir: {}", e))?.join(override_path)
-    };
-
-    let cargo_toml_path = crate_path.join("Cargo.toml");
-    let mut content = String::new();
-
-    // Read original Cargo.toml
-    let mut file = fs::File::open(&cargo_toml_path).map_err(|e| format!("Failed to open Cargo.toml: {}", e))?;
-    file.read_to_string(&mut content).map_err(|e| format!("Failed to read Cargo.toml: {}", e))?;
-    drop(file);
-
-    // Parse as TOML
-    let mut doc: toml_edit::DocumentMut = content.parse().map_err(|e| format!("Failed to parse Cargo.toml: {}", e))?;
-
-    match mode {
-        DependencyOverrideMode::Force => {
-            // Update dependency in all sections (force mode - replaces the spec entirely)
-            let sections = vec!["dependencies", "dev-dependencies", "build-dependencies"];
-
-            for section in sections {
-                if let Some(deps) = doc.get_mut(section).and_then(|s| s.as_table_mut())
-                    && let Some(dep) = deps.get_mut(dep_name)
-                {
-                    debug!("Force-replacing {} in [{}] with path {:?}", dep_name, section, override_path);
-
-                    // Preserve existing fields (optional, default-features, features, etc.)
-                    let mut new_dep = toml_edit::InlineTable::new();
-                    new_dep.insert("path", override_path.display().to_string().into());
-
-                    // Copy fields from original dependency if it's a table
-                    if let Some(old_table) = dep.as_inline_table() {
-                        // Preserve important fields
-                        for key in ["optional", "default-features", "features", "package"] {
-                            if let Some(value) = old_table.get(key) {
-                                new_dep.insert(key, value.clone());
-                                debug!("Preserving field '{}' = {:?}", key, value);
-                            }
-                        }
-                    } else if let Some(old_table) = dep.as_table_like() {
-                        // Handle table-like dependencies
-                        for key in ["optional", "default-features", "features", "package"] {
-                            if let Some(value) = old_table.get(key)
-                                && let Some(v) = value.as_value()
-                            {
-                                new_dep.insert(key, v.clone());
-                                debug!("Preserving field '{}' = {:?}", key, v);
-                            }
-                        }
-                    }
-
-                    *dep = toml_edit::Item::Value(toml_edit::Value::InlineTable(new_dep));
-                }
-            }
-
-            debug!("Force-replaced {} dependency spec with path: {}", dep_name, override_path.display());
-        }
-    }
-
-    // Write back
-    let mut file = fs::File::create(&cargo_toml_path).map_err(|e| format!("Failed to create Cargo.toml: {}", e))?;
-    file.write_all(doc.to_string().as_bytes()).map_err(|e| format!("Failed to write Cargo.toml: {}", e))?;
-
-    Ok(())
-}
-
-/// Apply a [patch.crates-io] section to Cargo.toml to patch ALL transitive dependencies
-///
-/// This adds or updates the [patch.crates-io] section in the dependent's Cargo.toml,
-/// which causes cargo to unify ALL versions of the specified crate across the entire
-/// dependency tree (including transitive dependencies).
-fn apply_patch_crates_io(crate_path: &Path, crate_name: &str, override_path: &Path) -> Result<(), String> {
-    use std::io::{Read, Write};
-
-    // Convert to absolute path
-    let override_path = if override_path.is_absolute() {
-        override_path.to_path_buf()
-    } else {
-        env::current_dir().map_err(|e| format!("Failed to get current dir: {}", e))?.join(override_path)
-    };
-
-    let cargo_toml_path = crate_path.join("Cargo.toml");
-    let mut content = String::new();
-
-    // Read original Cargo.toml
-    let mut file = fs::File::open(&cargo_toml_path).map_err(|e| format!("Failed to open Cargo.toml: {}", e))?;
-    file.read_to_string(&mut content).map_err(|e| format!("Failed to read Cargo.toml: {}", e))?;
-    drop(file);
-
-    // Parse as TOML
-    let mut doc: toml_edit::DocumentMut = content.parse().map_err(|e| format!("Failed to parse Cargo.toml: {}", e))?;
-
-    // Get or create [patch.crates-io] section
-    if doc.get("patch").is_none() {
-        doc["patch"] = toml_edit::Item::Table(toml_edit::Table::new());
-    }
-    let patch = doc["patch"].as_table_mut().ok_or("Failed to get patch table")?;
-
-    if patch.get("crates-io").is_none() {
-        patch["crates-io"] = toml_edit::Item::Table(toml_edit::Table::new());
-    }
-    let crates_io = patch["crates-io"].as_table_mut().ok_or("Failed to get crates-io table")?;
-
-    // Add the patch entry
-    let mut patch_entry = toml_edit::InlineTable::new();
-    patch_entry.insert("path", override_path.display().to_string().into());
-    crates_io[crate_name] = toml_edit::Item::Value(toml_edit::Value::InlineTable(patch_entry));
-
-    debug!("Applied [patch.crates-io].{} = {{ path = \"{}\" }}", crate_name, override_path.display());
-
-    // Write back
-    let mut file = fs::File::create(&cargo_toml_path).map_err(|e| format!("Failed to create Cargo.toml: {}", e))?;
-    file.write_all(doc.to_string().as_bytes()).map_err(|e| format!("Failed to write Cargo.toml: {}", e))?;
-
-    Ok(())
-}
-
 /// Recursively discover the local path-dependency siblings of a crate — the
 /// workspace crates it (transitively) path-depends on. Returns `(crate_name,
 /// absolute_dir)` pairs, never including `base_crate_dir` itself.
@@ -575,7 +309,7 @@ fn apply_patch_crates_io(crate_path: &Path, crate_name: &str, override_path: &Pa
 /// between the dependent's crates.io copy and the base's workspace copy →
 /// `error[E0308]: ... multiple versions of crate archmage`. Patching every
 /// sibling too unifies the whole local workspace for the dependent.
-fn discover_path_dep_siblings(base_crate_dir: &Path) -> Vec<(String, std::path::PathBuf)> {
+pub fn discover_path_dep_siblings(base_crate_dir: &Path) -> Vec<(String, std::path::PathBuf)> {
     let mut out = Vec::new();
     let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
     let mut stack = vec![base_crate_dir.to_path_buf()];
@@ -598,7 +332,7 @@ fn discover_path_dep_siblings(base_crate_dir: &Path) -> Vec<(String, std::path::
                 // Honor `package = "..."` renames; otherwise the dep name is the key.
                 let name = item.get("package").and_then(|p| p.as_str()).unwrap_or(key).to_string();
                 let abs = dir.join(path_str);
-                let abs = abs.canonicalize().unwrap_or(abs);
+                let abs = paths::canonicalize(&abs);
                 if seen.insert(name.clone()) {
                     out.push((name, abs.clone()));
                     stack.push(abs); // follow the sibling's own path-deps
@@ -609,71 +343,178 @@ fn discover_path_dep_siblings(base_crate_dir: &Path) -> Vec<(String, std::path::
     out
 }
 
+/// `patch.crates-io.<name>.path=...` for the base crate at `override_path` and its local
+/// path-dependency siblings
+///
+/// Cargo applies `--config` patches at the build root whatever the workspace layout, so
+/// they reach every copy of the base crate in the tree, transitive ones included. Patching
+/// the siblings too means a dependent that ALSO depends on one of them (e.g. magetypes
+/// path-depends on archmage, and the dependent depends on both) resolves a single copy
+/// instead of "multiple versions of crate X" (E0308). Unused patches are harmless (cargo
+/// just warns), so they are always added.
+pub fn patch_config(crate_name: &str, override_path: &Path) -> Result<Vec<String>, CopterError> {
+    let override_path = absolute(override_path)?;
+    let mut config = vec![format!("patch.crates-io.{}.path={}", crate_name, paths::to_toml_string(&override_path))];
+    for (sib_name, sib_path) in discover_path_dep_siblings(&override_path) {
+        if sib_name != crate_name {
+            config.push(format!("patch.crates-io.{}.path={}", sib_name, paths::to_toml_string(&sib_path)));
+        }
+    }
+    Ok(config)
+}
+
+/// The workspace `crate_path` builds in, when it has other members: `crate_path` itself or
+/// the nearest ancestor whose `[workspace]` lists it
+///
+/// Cargo only reads `[patch]` and Cargo.lock at the workspace root, and a member's build
+/// leaves the other members unchecked, so the runner builds a member's whole workspace.
+pub fn workspace_root(crate_path: &Path) -> Option<PathBuf> {
+    let dir = paths::canonicalize(crate_path);
+    let root = dir.ancestors().find(|a| fs::read_to_string(a.join("Cargo.toml")).is_ok_and(|m| has_workspace(&m)))?;
+    let members = crate::manifest::workspace_members(&root.join("Cargo.toml")).ok()?;
+    let member = members.iter().any(|(_, manifest)| manifest.parent().map(paths::canonicalize).as_ref() == Some(&dir));
+    (members.len() > 1 && (member || root == dir)).then(|| root.to_path_buf())
+}
+
+/// The resolver cargo uses for the dependent built in `build_path`, from its pristine manifest
+pub fn declared_resolver(build_path: &Path) -> Option<String> {
+    let backup = build_path.join(patching::BACKUP_NAME);
+    crate::manifest::resolver(&if backup.exists() { backup } else { build_path.join("Cargo.toml") })
+}
+
+/// `--force-resolver-2`: set `resolver = "2"` in the root manifest of a resolver 1 dependent,
+/// where cargo reads it (`[workspace]` when it declares one or there is no `[package]`)
+fn force_resolver_2(build_path: &Path, audit: &AuditLog) -> Result<(), CopterError> {
+    if declared_resolver(build_path).as_deref() != Some("1") {
+        return Ok(());
+    }
+    patching::mutate(build_path, audit, "force resolver 2", |doc| {
+        let in_workspace = doc.get("workspace").is_some_and(|w| w.get("resolver").is_some());
+        let table = if in_workspace || doc.get("package").is_none() { "workspace" } else { "package" };
+        doc[table]["resolver"] = toml_edit::value("2");
+        Ok(())
+    })
+}
+
+/// Whether the manifest in `crate_path` is a workspace with more than one package
+fn is_workspace_root(crate_path: &Path) -> bool {
+    let manifest = crate_path.join("Cargo.toml");
+    fs::read_to_string(&manifest).is_ok_and(|m| has_workspace(&m))
+        && crate::manifest::workspace_members(&manifest).is_ok_and(|members| members.len() > 1)
+}
+
+fn has_workspace(manifest: &str) -> bool {
+    manifest.parse::<toml::Table>().is_ok_and(|table| table.contains_key("workspace"))
+}
+
 pub fn compile_crate(
     crate_path: &Path,
     step: CompileStep,
     override_spec: Option<(&str, &Path)>,
-) -> Result<CompileResult, String> {
+    registry: Option<&StagingRegistry>,
+    target: &target::TargetPlan,
+    gates: Option<&nightly::NightlyGates>,
+    heartbeat: &Heartbeat,
+) -> Result<CompileResult, CopterError> {
     debug!("compiling {:?} with step {:?}", crate_path, step);
 
     // Run the cargo command with JSON output for better error extraction
     let start = Instant::now();
-    let mut cmd = Command::new("cargo");
+    let program = if target.uses_cross() && step != CompileStep::Fetch { "cross" } else { "cargo" };
+    let mut cmd = Command::new(program);
+    if gates.is_some() {
+        cmd.arg(nightly::TOOLCHAIN);
+    } else if let Some(toolchain) = &target.toolchain {
+        cmd.arg(format!("+{}", toolchain));
+    }
     cmd.arg(step.cargo_subcommand());
+    if let Some(gates) = gates {
+        gates.apply(&mut cmd, step != CompileStep::Fetch);
+    }
 
     // Add --message-format=json for check and test (not fetch)
     if step != CompileStep::Fetch {
         cmd.arg("--message-format=json");
+        if is_workspace_root(crate_path) {
+            cmd.arg("--workspace");
+        }
     }
 
-    // If override is provided, use --config flag instead of creating .cargo/config file
+    if let Some(triple) = &target.target {
+        cmd.arg("--target").arg(triple);
+    }
+    if let Some(runner) = target.runner_config() {
+        cmd.arg("--config").arg(runner);
+    }
+    // `--index-url`: fetch crates.io packages from the mirror's index;
+    // `--offline`: build from the prefetched vendor directory only
+    cmd.args(target.registry.cargo_args());
+
+    // The override is a `[patch.crates-io]` given as --config values: the dependent's
+    // Cargo.toml and .cargo/config.toml are never touched for it
     if let Some((crate_name, override_path)) = override_spec {
-        // Convert to absolute path if needed
-        let override_path = if override_path.is_absolute() {
-            override_path.to_path_buf()
-        } else {
-            env::current_dir().map_err(|e| format!("Failed to get current dir: {}", e))?.join(override_path)
-        };
+        for config in patch_config(crate_name, override_path)? {
+            debug!("using --config: {}", config);
+            cmd.arg("--config").arg(config);
+        }
+    }
 
-        let config_str = format!("patch.crates-io.{}.path=\"{}\"", crate_name, override_path.display());
-        cmd.arg("--config").arg(&config_str);
-        debug!("using --config: {}", config_str);
-
-        // Also patch the base crate's local workspace siblings, so a dependent
-        // that ALSO depends on one of them (e.g. magetypes path-depends on
-        // archmage, and the dependent depends on both) resolves a single unified
-        // copy instead of "multiple versions of crate X" (E0308). Unused patches
-        // are harmless (cargo just warns), so this is safe to apply unconditionally.
-        for (sib_name, sib_path) in discover_path_dep_siblings(&override_path) {
-            if sib_name == crate_name {
-                continue;
-            }
-            let sib_config = format!("patch.crates-io.{}.path=\"{}\"", sib_name, sib_path.display());
-            cmd.arg("--config").arg(&sib_config);
-            debug!("using --config (sibling): {}", sib_config);
+    // `stage-publish`: the base crate comes from the staging registry instead of a path
+    if let Some(registry) = registry {
+        for config in registry.config_args() {
+            cmd.arg("--config").arg(config);
         }
     }
 
+    // Parallel workers each get their share of the cores
+    if let Some(jobs) = target.build_jobs {
+        cmd.env(crate::queue::BUILD_JOBS_ENV, jobs.to_string());
+    }
+
     cmd.current_dir(crate_path);
+    let args: Vec<String> = cmd.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+    let manifest_diff = patching::manifest_diff(crate_path);
+
+    // `--docker-steps`: the same command in a throwaway container
+    let container = target.sandbox.as_ref().map(|sandbox| {
+        let name = crate::docker::container_name();
+        cmd = sandbox.wrap(&cmd, &name);
+        name
+    });
 
     debug!("running cargo: {:?}", cmd);
-    let output = cmd.output().map_err(|e| format!("Failed to execute cargo: {}", e))?;
+    let _tracked = container.as_deref().map(heartbeat::track_container);
+    let heartbeat::Finished { output, timed_out } = heartbeat::run(&mut cmd, heartbeat)
+        .map_err(|e| CopterError::Cargo(format!("Failed to execute cargo: {}", e)))?;
+    if timed_out && let Some(name) = &container {
+        crate::docker::remove_container(name);
+    }
 
     let duration = start.elapsed();
-    let success = output.status.success();
+    let mut success = output.status.success();
 
     debug!("result: {:?}, duration: {:?}", success, duration);
 
     // Parse stdout for JSON messages (cargo writes JSON to stdout)
     let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
-    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    let mut stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+    // `--prefetch`: keep what this fetch resolved for a later `--offline` run
+    if success
+        && step == CompileStep::Fetch
+        && let Some(mode) = &target.registry.offline
+        && let Err(e) = mode.vendor(crate_path, &args)
+    {
+        success = false;
+        stderr.push_str(&e);
+    }
 
     // Parse diagnostics from JSON output (only for check/test, not fetch)
     let diagnostics = if step != CompileStep::Fetch { parse_cargo_json(&stdout) } else { Vec::new() };
 
     debug!("parsed {} diagnostics", diagnostics.len());
 
-    Ok(CompileResult { step, success, stdout, stderr, duration, diagnostics })
+    Ok(CompileResult { step, success, stdout, stderr, duration, diagnostics, args, manifest_diff, timed_out })
 }
 
 /// Source of a version being tested
@@ -685,40 +526,6 @@ pub enum VersionSource {
     Local { path: PathBuf, forced: bool },
 }
 
-impl VersionSource {
-    pub fn label(&self) -> String {
-        match self {
-            VersionSource::Published { version, .. } => version.clone(),
-            VersionSource::Local { .. } => "this".to_string(),
-        }
-    }
-
-    pub fn is_local(&self) -> bool {
-        matches!(self, VersionSource::Local { .. })
-    }
-
-    pub fn is_forced(&self) -> bool {
-        match self {
-            VersionSource::Published { forced, .. } => *forced,
-            VersionSource::Local { forced, .. } => *forced,
-        }
-    }
-
-    pub fn version_string(&self) -> Option<String> {
-        match self {
-            VersionSource::Published { version, .. } => Some(version.clone()),
-            VersionSource::Local { .. } => None,
-        }
-    }
-
-    pub fn path(&self) -> Option<&PathBuf> {
-        match self {
-            VersionSource::Published { .. } => None,
-            VersionSource::Local { path, .. } => Some(path),
-        }
-    }
-}
-
 /// Depth of patching applied to resolve version conflicts
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
 pub enum PatchDepth {
@@ -771,9 +578,75 @@ pub struct ThreeStepResult {
     pub all_crate_versions: Vec<(String, String, String)>, // (spec, resolved_version, dependent_name)
     /// Depth of patching applied to resolve version conflicts
     pub patch_depth: PatchDepth,
+    /// Local time the steps started, RFC 3339
+    #[serde(default)]
+    pub started: Option<String>,
+    /// The git or path copy of the base crate the dependent resolved instead of the offered version
+    #[serde(default)]
+    pub fork: Option<String>,
+    /// `package.repository` of the dependent's manifest
+    #[serde(default)]
+    pub repository: Option<String>,
+    /// Packages still pinning another version of the base crate after the deep patch (`!!!`)
+    #[serde(default)]
+    pub blocking: Vec<metadata::Blocker>,
+    /// Features of the base crate enabled in the dependent's build; None when unknown
+    #[serde(default)]
+    pub base_features: Option<Vec<String>>,
+    /// Cargo resolver the steps ran with ("2 (forced from 1)" with `--force-resolver-2`)
+    #[serde(default)]
+    pub resolver: Option<String>,
+    /// rustup toolchain the steps ran with (`--toolchains`)
+    #[serde(default)]
+    pub toolchain: Option<String>,
+    /// Why no step ran (e.g. "missing system dep: libclang"); the row is reported as skipped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
 }
 
 impl ThreeStepResult {
+    /// A pair that was never run, and why
+    pub fn skipped(reason: String) -> Self {
+        ThreeStepResult {
+            fetch: CompileResult {
+                step: CompileStep::Fetch,
+                success: true,
+                stdout: String::new(),
+                stderr: String::new(),
+                duration: Duration::ZERO,
+                diagnostics: vec![],
+                args: vec![],
+                manifest_diff: None,
+                timed_out: false,
+            },
+            check: None,
+            test: None,
+            actual_version: None,
+            expected_version: None,
+            forced_version: false,
+            original_requirement: None,
+            all_crate_versions: vec![],
+            patch_depth: PatchDepth::None,
+            started: None,
+            fork: None,
+            repository: None,
+            blocking: vec![],
+            base_features: None,
+            resolver: None,
+            toolchain: None,
+            skipped: Some(reason),
+        }
+    }
+
+    /// Environment failure reason for the first failed step, if that is what stopped the run
+    pub fn env_failure(&self) -> Option<&'static str> {
+        [Some(&self.fetch), self.check.as_ref(), self.test.as_ref()]
+            .into_iter()
+            .flatten()
+            .find(|step| step.failed())
+            .and_then(CompileResult::env_failure)
+    }
+
     /// Determine if all executed steps succeeded
     pub fn is_success(&self) -> bool {
         if !self.fetch.success {
@@ -879,10 +752,8 @@ pub struct TestConfig<'a> {
     pub base_crate_name: &'a str,
     /// Optional path to override the dependency with
     pub override_path: Option<&'a Path>,
-    /// Skip cargo check step
-    pub skip_check: bool,
-    /// Skip cargo test step
-    pub skip_test: bool,
+    /// Steps to run (fetch always runs)
+    pub steps: Steps,
     /// Expected version to verify after fetch
     pub expected_version: Option<String>,
     /// Force version (bypass semver requirements)
@@ -895,6 +766,24 @@ pub struct TestConfig<'a> {
     pub test_label: Option<&'a str>,
     /// Use [patch.crates-io] to patch all transitive dependencies
     pub patch_transitive: bool,
+    /// Where failed steps are logged (needs `dependent_info` and `test_label` too)
+    pub failure_log: Option<FailureLog>,
+    /// Warn when a cargo step goes quiet for too long
+    pub heartbeat: Heartbeat,
+    /// Target and runner for every cargo step (see `target::detect`)
+    pub target: target::TargetPlan,
+    /// `--nightly-gates`: nightly toolchain, `--cfg docsrs` and the base crate's gated features
+    pub nightly_gates: Option<nightly::NightlyGates>,
+    /// What to do with the dependent's own `[patch]` and `.cargo/config.toml` overrides
+    pub dependent_patches: DependentPatches,
+    /// `stage-publish`: take the base crate from the staging registry (instead of `override_path`)
+    pub staging_registry: Option<StagingRegistry>,
+    /// The workspace the dependent is a member of (`workspace_root`): cargo runs there
+    pub workspace_root: Option<&'a Path>,
+    /// `--force-resolver-2`: build with resolver 2 where the dependent uses resolver 1
+    pub force_resolver: bool,
+    /// Where the dependent's manifest rewrites are recorded
+    pub audit: AuditLog,
 }
 
 impl<'a> TestConfig<'a> {
@@ -904,14 +793,22 @@ impl<'a> TestConfig<'a> {
             crate_path,
             base_crate_name,
             override_path: None,
-            skip_check: false,
-            skip_test: false,
+            steps: Steps::all(),
             expected_version: None,
             force_versions: false,
             original_requirement: None,
             dependent_info: None,
             test_label: None,
             patch_transitive: false,
+            failure_log: None,
+            heartbeat: Heartbeat::default(),
+            target: target::TargetPlan::host(),
+            nightly_gates: None,
+            dependent_patches: DependentPatches::Keep,
+            staging_registry: None,
+            workspace_root: None,
+            force_resolver: false,
+            audit: AuditLog::default(),
         }
     }
 
@@ -927,10 +824,9 @@ impl<'a> TestConfig<'a> {
         self
     }
 
-    /// Set skip flags (builder pattern)
-    pub fn with_skip_flags(mut self, skip_check: bool, skip_test: bool) -> Self {
-        self.skip_check = skip_check;
-        self.skip_test = skip_test;
+    /// Set the steps to run (builder pattern)
+    pub fn with_steps(mut self, steps: Steps) -> Self {
+        self.steps = steps;
         self
     }
 
@@ -953,6 +849,60 @@ impl<'a> TestConfig<'a> {
         self.test_label = test_label;
         self
     }
+
+    /// Set the failure log handle (builder pattern)
+    pub fn with_failure_log(mut self, failure_log: Option<FailureLog>) -> Self {
+        self.failure_log = failure_log;
+        self
+    }
+
+    /// Record manifest rewrites in `audit` (builder pattern)
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    /// Set stall detection (builder pattern)
+    pub fn with_heartbeat(mut self, heartbeat: Heartbeat) -> Self {
+        self.heartbeat = heartbeat;
+        self
+    }
+
+    /// Build for `target` instead of the host (builder pattern)
+    pub fn with_target(mut self, target: target::TargetPlan) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Build with nightly feature gates on (builder pattern)
+    pub fn with_nightly_gates(mut self, gates: Option<nightly::NightlyGates>) -> Self {
+        self.nightly_gates = gates;
+        self
+    }
+
+    /// Set how the dependent's own overrides are handled (builder pattern)
+    pub fn with_dependent_patches(mut self, dependent_patches: DependentPatches) -> Self {
+        self.dependent_patches = dependent_patches;
+        self
+    }
+
+    /// Take the base crate from a staging registry (builder pattern)
+    pub fn with_staging_registry(mut self, registry: Option<StagingRegistry>) -> Self {
+        self.staging_registry = registry;
+        self
+    }
+
+    /// Build and test the dependent's whole workspace from its root (builder pattern)
+    pub fn with_workspace_root(mut self, root: Option<&'a Path>) -> Self {
+        self.workspace_root = root;
+        self
+    }
+
+    /// Build resolver 1 dependents with resolver 2 (builder pattern)
+    pub fn with_force_resolver(mut self, force_resolver: bool) -> Self {
+        self.force_resolver = force_resolver;
+        self
+    }
 }
 
 /// Run three-step ICT (Install/Check/Test) test with early stopping
@@ -960,22 +910,71 @@ impl<'a> TestConfig<'a> {
 /// # Returns
 /// ThreeStepResult with cumulative early stopping:
 /// - Fetch always runs
-/// - Check only runs if fetch succeeds (and !skip_check)
-/// - Test only runs if check succeeds (and !skip_test)
-pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, String> {
+/// - Check only runs if fetch succeeds (and check is in `steps`)
+/// - Test only runs if check succeeds (and test is in `steps`)
+pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, CopterError> {
+    let failure_log = config.failure_log.clone();
+    let base_crate_name = config.base_crate_name;
+    let started = chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+    let mut result = run_steps(config)?;
+    result.started = Some(started);
+
+    let resolved: std::collections::HashSet<&String> = result.all_crate_versions.iter().map(|(_, r, _)| r).collect();
+    if let Some(log) = failure_log
+        && resolved.len() > 1
+    {
+        log.multi_version(base_crate_name, &result.all_crate_versions);
+    }
+    Ok(result)
+}
+
+fn run_steps(config: TestConfig) -> Result<ThreeStepResult, CopterError> {
     let TestConfig {
         crate_path,
         base_crate_name,
         override_path,
-        skip_check,
-        skip_test,
+        steps,
         expected_version,
         force_versions,
         original_requirement,
         dependent_info,
         test_label,
         patch_transitive,
+        failure_log,
+        heartbeat,
+        target,
+        nightly_gates,
+        dependent_patches,
+        staging_registry,
+        workspace_root,
+        force_resolver,
+        audit,
     } = config;
+    let target = &target;
+    let registry = staging_registry.as_ref();
+    let gates = nightly_gates.as_ref();
+    let context = match &failure_log {
+        Some(_) => failure_log::dependent_context(crate_path, base_crate_name),
+        None => vec![],
+    };
+    let log_failure = |command: &str, result: &CompileResult| {
+        if let (Some(log), Some(dep_info), Some(label)) = (&failure_log, &dependent_info, test_label) {
+            log.failure(FailureEntry {
+                dependent: dep_info.name.to_string(),
+                dependent_version: dep_info.version.to_string(),
+                base_crate: base_crate_name.to_string(),
+                test_label: label.to_string(),
+                command: command.to_string(),
+                exit_code: None,
+                stderr: result.stderr.clone(),
+                stdout: result.stdout.clone(),
+                diagnostics: result.diagnostics.clone(),
+                context: context.clone(),
+            });
+        }
+    };
+    let run_check = steps.runs(CommandType::Check);
+    let run_test = steps.runs(CommandType::Test);
     debug!(
         "running three-step ICT for {:?} (force={}, expected_version={:?}, patch_transitive={}, has_override_path={})",
         crate_path,
@@ -992,70 +991,81 @@ pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, String>
         debug!("BASELINE MODE: no override, testing natural resolution");
     }
 
-    // Always restore Cargo.toml from original backup to prevent contamination
-    restore_cargo_toml(crate_path)?;
+    // A workspace member is built from the workspace root, where cargo reads [patch] and
+    // Cargo.lock; its manifest is restored and patched along with the member's
+    let build_path = workspace_root.unwrap_or(crate_path);
+    let manifest_dirs: Vec<&Path> =
+        if build_path == crate_path { vec![crate_path] } else { vec![crate_path, build_path] };
+    let restore =
+        || -> Result<(), CopterError> { manifest_dirs.iter().try_for_each(|dir| restore_cargo_toml(dir, &audit)) };
+
+    // The dependent's own overrides of what copter patches: keep, merge or strip them
+    let mut patched = vec![base_crate_name.to_string()];
+    if let Some(op) = override_path {
+        patched.extend(discover_path_dep_siblings(&paths::canonicalize(op)).into_iter().map(|(name, _)| name));
+    }
+    // Always restore Cargo.toml (and config files) from the original backup to prevent contamination
+    let reset = || -> Result<Vec<dependent_patches::Conflict>, CopterError> {
+        let mut conflicts = Vec::new();
+        for dir in &manifest_dirs {
+            restore_cargo_toml(dir, &audit)?;
+            dependent_patches::restore_configs(dir, &audit)?;
+            conflicts.extend(dependent_patches::apply(dir, &patched, dependent_patches, &audit)?);
+        }
+        if force_resolver {
+            force_resolver_2(build_path, &audit)?;
+        }
+        Ok(conflicts)
+    };
+    let conflicts = reset()?;
+    if !conflicts.is_empty() {
+        debug!("{} of the dependent's own overrides conflict with copter's patch", conflicts.len());
+    }
 
     // Always delete Cargo.lock to force fresh dependency resolution
-    let lock_file = crate_path.join("Cargo.lock");
+    let lock_file = build_path.join("Cargo.lock");
     if lock_file.exists() {
         debug!("Deleting Cargo.lock to force dependency resolution");
-        fs::remove_file(&lock_file).map_err(|e| format!("Failed to remove Cargo.lock: {}", e))?;
-    }
-
-    // Setup: Choose patching strategy based on mode
-    // For FORCE mode: Modify Cargo.toml to bypass semver (direct dependency)
-    //   - If patch_transitive is also enabled, add [patch.crates-io] for transitive deps
-    // For PATCH mode (non-force): Use --config flag (clean, no file modifications)
-    // For BASELINE: No override at all
-    //
-    // IMPORTANT: patch_transitive ONLY applies with force mode, because:
-    // 1. Baseline should never be modified
-    // 2. Non-forced versions use --config which doesn't modify files
-    let override_path_buf = if let Some(override_path) = override_path {
-        if force_versions {
-            // FORCE MODE: bypass semver on the dependent's DIRECT dep by
-            // rewriting its manifest spec to the WIP path.
-            // (restore_cargo_toml already saved the .original backup.)
-            apply_dependency_override(crate_path, base_crate_name, override_path, DependencyOverrideMode::Force)?;
-
-            // The direct override does NOT reach copies of the base crate (or its
-            // workspace siblings) that the dependent pulls TRANSITIVELY via other
-            // deps — those resolve to crates.io and collide with the WIP ("multiple
-            // versions of crate X", E0308). A manifest [patch.crates-io] can't fix
-            // that either: cargo only honors [patch] in the WORKSPACE ROOT, so for
-            // a dependent that is itself a workspace member it lands in the wrong
-            // manifest and is silently ignored. Route base + siblings through
-            // `--config patch.crates-io` instead (returning Some hands them to
-            // compile_crate's --config path below), which cargo applies at the
-            // build-root level regardless of workspace layout. The WIP version
-            // satisfies the transitive requirement, so the patch applies cleanly.
-            let _ = patch_transitive; // subsumed: --config is always transitive
-            let abs_path = if override_path.is_absolute() {
-                override_path.to_path_buf()
-            } else {
-                env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?.join(override_path)
-            };
-            Some(abs_path)
-        } else {
-            // PATCH MODE: Use --config flag (clean, no file modifications)
-            let abs_path = if override_path.is_absolute() {
-                override_path.to_path_buf()
-            } else {
-                env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?.join(override_path)
-            };
+        fs::remove_file(&lock_file)
+            .map_err(|e| CopterError::Patching(format!("Failed to remove Cargo.lock: {}", e)))?;
+    }
 
-            debug!("Using --config for patch mode with override_path={:?}, abs_path={:?}", override_path, abs_path);
-            Some(abs_path) // Use --config, no file modifications
+    // Every override reaches cargo as `--config patch.crates-io...` values (see `patch_config`),
+    // which cargo applies at the build root whatever the workspace layout, so they also reach
+    // copies of the base crate (and its path-dependency siblings) pulled in transitively.
+    // Baseline rows have no override at all.
+    let _ = patch_transitive; // subsumed: --config is always transitive
+    let override_path_buf = override_path.map(absolute).transpose()?;
+    debug!("patching {} from {:?}", base_crate_name, override_path_buf);
+
+    // FORCE MODE also rewrites the dependent's DIRECT requirement to the WIP path (mutate
+    // saves the .original backup first): cargo ignores a [patch] the requirement rejects,
+    // so bypassing semver can't be said in cargo config. Auto-retries re-apply it after
+    // each reset.
+    let force_override = || -> Result<(), CopterError> {
+        match override_path {
+            Some(op) => {
+                apply_dependency_override(crate_path, base_crate_name, op, DependencyOverrideMode::Force, &audit)
+            }
+            None => Ok(()),
         }
-    } else {
-        None // No override (baseline test)
     };
+    if force_versions {
+        force_override()?;
+    }
 
-    // Build override_spec for compile_crate calls (only used in regular patch mode)
+    if let Some(registry) = registry
+        && force_versions
+    {
+        apply_registry_override(crate_path, base_crate_name, registry, &audit)?;
+    }
+
+    // Every step gets the override as --config
     let override_spec = override_path_buf.as_ref().map(|path| (base_crate_name, path.as_path()));
 
     // Step 1: Fetch (always runs)
-    let fetch = compile_crate(crate_path, CompileStep::Fetch, override_spec)?;
+    let mut fetch = compile_crate(build_path, CompileStep::Fetch, override_spec, registry, target, gates, &heartbeat)?;
+    dependent_patches::annotate(&mut fetch, &conflicts);
 
     // Verify the actual version after fetch
     let actual_version = if fetch.success { verify_dependency_version(crate_path, base_crate_name) } else { None };
@@ -1071,9 +1081,9 @@ pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, String>
                 // e.g. a historical version released before that dependency was
                 // added. There is nothing to patch or test, so signal a skip
                 // rather than panicking and aborting the whole run.
-                return Err(format!(
+                return Err(CopterError::Config(format!(
                     "`{base_crate_name}` is not a dependency of this version — skipping"
-                ));
+                )));
             }
             extracted
         } else {
@@ -1087,20 +1097,7 @@ pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, String>
     };
 
     if fetch.failed() {
-        // Log failure with diagnostics
-        if let (Some(dep_info), Some(label)) = (dependent_info.as_ref(), test_label) {
-            log_failure_with_diagnostics(
-                dep_info.name,
-                dep_info.version,
-                base_crate_name,
-                label,
-                "cargo fetch",
-                None,
-                &fetch.stdout,
-                &fetch.stderr,
-                &fetch.diagnostics,
-            );
-        }
+        log_failure("cargo fetch", &fetch);
 
         // Fetch failed - stop here with dashes for remaining steps
         return Ok(ThreeStepResult {
@@ -1113,90 +1110,80 @@ pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, String>
             original_requirement,
             all_crate_versions: vec![],
             patch_depth: if force_versions { PatchDepth::Force } else { PatchDepth::None },
+            started: None,
+            fork: None,
+            blocking: vec![],
+            repository: None,
+            base_features: None,
+            resolver: None,
+            toolchain: None,
+            skipped: None,
         });
     }
 
     // Step 2: Check (only if fetch succeeded and not skipped)
-    let check = if !skip_check {
-        let result = compile_crate(crate_path, CompileStep::Check, override_spec)?;
+    let check = if run_check {
+        let mut result =
+            compile_crate(build_path, CompileStep::Check, override_spec, registry, target, gates, &heartbeat)?;
+        dependent_patches::annotate(&mut result, &conflicts);
         if result.failed() {
-            // Log failure with diagnostics
-            if let (Some(dep_info), Some(label)) = (dependent_info.as_ref(), test_label) {
-                log_failure_with_diagnostics(
-                    dep_info.name,
-                    dep_info.version,
-                    base_crate_name,
-                    label,
-                    "cargo check",
-                    None,
-                    &result.stdout,
-                    &result.stderr,
-                    &result.diagnostics,
-                );
-            }
+            log_failure("cargo check", &result);
 
             // Check failed - try auto-retry with [patch.crates-io] if it's a multi-version conflict
             let combined_output = format!("{}\n{}", result.stdout, result.stderr);
             if force_versions
+                && registry.is_none()
                 && (has_multiple_version_conflict(&combined_output)
                     || has_multiple_resolved_versions(crate_path, base_crate_name))
             {
                 debug!("Multi-version conflict detected, attempting auto-retry with [patch.crates-io]");
 
                 // Restore Cargo.toml and apply both force AND patch.crates-io
-                restore_cargo_toml(crate_path)?;
+                reset()?;
 
                 // Delete Cargo.lock again for fresh resolution
-                let lock_file = crate_path.join("Cargo.lock");
+                let lock_file = build_path.join("Cargo.lock");
                 if lock_file.exists() {
                     let _ = fs::remove_file(&lock_file);
                 }
 
-                // Apply force override
-                if let Some(override_path) = override_path {
-                    apply_dependency_override(
-                        crate_path,
-                        base_crate_name,
-                        override_path,
-                        DependencyOverrideMode::Force,
-                    )?;
-                    // Also apply [patch.crates-io] for transitive deps
-                    apply_patch_crates_io(crate_path, base_crate_name, override_path)?;
-                    debug!("Applied FORCE + [patch.crates-io] for auto-retry");
-                }
+                force_override()?;
+                debug!("Applied FORCE + --config [patch.crates-io] for auto-retry");
 
                 // Retry fetch and check
-                let retry_fetch = compile_crate(crate_path, CompileStep::Fetch, None)?;
+                let retry_fetch =
+                    compile_crate(build_path, CompileStep::Fetch, override_spec, None, target, gates, &heartbeat)?;
                 if retry_fetch.success {
-                    let retry_check = compile_crate(crate_path, CompileStep::Check, None)?;
+                    let retry_check =
+                        compile_crate(build_path, CompileStep::Check, override_spec, None, target, gates, &heartbeat)?;
                     if retry_check.success {
                         // Auto-retry succeeded! Continue with test step
                         debug!("Auto-retry with [patch.crates-io] succeeded!");
 
                         // Run test if not skipped
-                        let test =
-                            if !skip_test { Some(compile_crate(crate_path, CompileStep::Test, None)?) } else { None };
+                        let test = if run_test {
+                            Some(compile_crate(
+                                build_path,
+                                CompileStep::Test,
+                                override_spec,
+                                None,
+                                target,
+                                gates,
+                                &heartbeat,
+                            )?)
+                        } else {
+                            None
+                        };
 
                         // Log test failure if needed
                         if let Some(ref test_result) = test
                             && test_result.failed()
-                            && let (Some(dep_info), Some(label)) = (dependent_info.as_ref(), test_label)
                         {
-                            log_failure_with_diagnostics(
-                                dep_info.name,
-                                dep_info.version,
-                                base_crate_name,
-                                label,
-                                "cargo test",
-                                None,
-                                &test_result.stdout,
-                                &test_result.stderr,
-                                &test_result.diagnostics,
-                            );
+                            log_failure("cargo test", test_result);
                         }
 
                         // Cleanup and return success with Patch depth
-                        restore_cargo_toml(crate_path).ok();
+                        restore().ok();
                         let all_crate_versions = extract_all_crate_versions(crate_path, base_crate_name);
 
                         return Ok(ThreeStepResult {
@@ -1209,14 +1196,32 @@ pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, String>
                             original_requirement: original_requirement.clone(),
                             all_crate_versions,
                             patch_depth: PatchDepth::Patch, // !! marker
+                            started: None,
+                            fork: None,
+                            blocking: vec![],
+                            repository: None,
+                            base_features: None,
+                            resolver: None,
+                            toolchain: None,
+                            skipped: None,
                         });
                     }
                     // Retry check also failed - check if still multi-version conflict
                     let retry_output = format!("{}\n{}", retry_check.stdout, retry_check.stderr);
                     let still_multi_version = has_multiple_version_conflict(&retry_output);
 
-                    // Extract blocking crates for !!! case
-                    let blocking_crates = if still_multi_version {
+                    // Extract blocking crates for !!! case: from the resolve graph (with the
+                    // force patch still applied), else the crates the compiler error names
+                    let blocking = match (still_multi_version, &expected_version) {
+                        (true, Some(offered)) => cargo_metadata(crate_path)
+                            .map(|parsed| metadata::blockers(&parsed, base_crate_name, offered))
+                            .unwrap_or_default(),
+                        _ => vec![],
+                    };
+                    let blocking_crates = if !blocking.is_empty() {
+                        debug!("Auto-retry still has multi-version conflict - blocking crates: {:?}", blocking);
+                        blocking.iter().map(|b| (b.spec.clone(), b.resolved.clone(), b.crate_name.clone())).collect()
+                    } else if still_multi_version {
                         let crates = extract_crates_needing_patch(&retry_output, base_crate_name);
                         debug!("Auto-retry still has multi-version conflict - blocking crates: {:?}", crates);
                         // Convert to all_crate_versions format: (spec, version, crate_name)
@@ -1226,7 +1231,7 @@ pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, String>
                         vec![]
                     };
 
-                    restore_cargo_toml(crate_path).ok();
+                    restore().ok();
                     return Ok(ThreeStepResult {
                         fetch: retry_fetch,
                         check: Some(retry_check),
@@ -1238,11 +1243,19 @@ pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, String>
                         all_crate_versions: blocking_crates,
                         // !!! if still multi-version (deep transitive issue), !! otherwise
                         patch_depth: if still_multi_version { PatchDepth::DeepPatch } else { PatchDepth::Patch },
+                        started: None,
+                        fork: None,
+                        blocking,
+                        repository: None,
+                        base_features: None,
+                        resolver: None,
+                        toolchain: None,
+                        skipped: None,
                     });
                 }
                 // Retry fetch failed - return original failure
                 debug!("Auto-retry fetch failed");
-                restore_cargo_toml(crate_path).ok();
+                restore().ok();
             }
 
             // Check failed - stop here with dash for test
@@ -1256,6 +1269,14 @@ pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, String>
                 original_requirement: original_requirement.clone(),
                 all_crate_versions: vec![],
                 patch_depth: if force_versions { PatchDepth::Force } else { PatchDepth::None },
+                started: None,
+                fork: None,
+                blocking: vec![],
+                repository: None,
+                base_features: None,
+                resolver: None,
+                toolchain: None,
+                skipped: None,
             });
         }
         Some(result)
@@ -1263,21 +1284,22 @@ pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, String>
         None
     };
 
-    // Step 3: Test (only if check succeeded or was skipped, and not skip_test)
+    // Step 3: Test (only if check succeeded or was skipped, and test is in `steps`)
     // If test fails with force_versions, check for multi-version conflicts in the dep tree
     // and retry with [patch.crates-io] (mirrors the check-step auto-retry logic).
     // This catches cases where dev-dependencies (only compiled during tests) bring in
     // a second version of the base crate, causing trait mismatches that the compiler
     // doesn't always annotate with "multiple different versions of crate".
-    let (test, _test_patch_depth): (Option<CompileResult>, Option<PatchDepth>) = if !skip_test {
+    let (test, _test_patch_depth): (Option<CompileResult>, Option<PatchDepth>) = if run_test {
         let should_run = match &check {
             Some(c) => c.success,
             None => true, // check was skipped, proceed
         };
 
         if should_run {
-            let result = compile_crate(crate_path, CompileStep::Test, override_spec)?;
-            if result.failed() && force_versions {
+            let result =
+                compile_crate(build_path, CompileStep::Test, override_spec, registry, target, gates, &heartbeat)?;
+            if result.failed() && force_versions && registry.is_none() {
                 // Check if there are multiple resolved versions in the dep tree
                 let multi_version_in_tree = has_multiple_resolved_versions(crate_path, base_crate_name);
                 let combined_output = format!("{}\n{}", result.stdout, result.stderr);
@@ -1290,42 +1312,44 @@ pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, String>
                     );
 
                     // Restore Cargo.toml and apply both force AND patch.crates-io
-                    restore_cargo_toml(crate_path)?;
-                    let lock_file = crate_path.join("Cargo.lock");
+                    reset()?;
+                    let lock_file = build_path.join("Cargo.lock");
                     if lock_file.exists() {
                         let _ = fs::remove_file(&lock_file);
                     }
 
-                    if let Some(op) = override_path {
-                        apply_dependency_override(crate_path, base_crate_name, op, DependencyOverrideMode::Force)?;
-                        apply_patch_crates_io(crate_path, base_crate_name, op)?;
-                        debug!("Applied FORCE + [patch.crates-io] for test auto-retry");
-                    }
+                    force_override()?;
+                    debug!("Applied FORCE + --config [patch.crates-io] for test auto-retry");
 
                     // Retry fetch + check + test
-                    let retry_fetch = compile_crate(crate_path, CompileStep::Fetch, None)?;
+                    let retry_fetch =
+                        compile_crate(build_path, CompileStep::Fetch, override_spec, None, target, gates, &heartbeat)?;
                     if retry_fetch.success {
-                        let retry_check = compile_crate(crate_path, CompileStep::Check, None)?;
+                        let retry_check = compile_crate(
+                            build_path,
+                            CompileStep::Check,
+                            override_spec,
+                            None,
+                            target,
+                            gates,
+                            &heartbeat,
+                        )?;
                         if retry_check.success {
-                            let retry_test = compile_crate(crate_path, CompileStep::Test, None)?;
-
-                            if let (Some(dep_info), Some(label)) = (dependent_info.as_ref(), test_label)
-                                && retry_test.failed()
-                            {
-                                log_failure_with_diagnostics(
-                                    dep_info.name,
-                                    dep_info.version,
-                                    base_crate_name,
-                                    label,
-                                    "cargo test",
-                                    None,
-                                    &retry_test.stdout,
-                                    &retry_test.stderr,
-                                    &retry_test.diagnostics,
-                                );
+                            let retry_test = compile_crate(
+                                build_path,
+                                CompileStep::Test,
+                                override_spec,
+                                None,
+                                target,
+                                gates,
+                                &heartbeat,
+                            )?;
+
+                            if retry_test.failed() {
+                                log_failure("cargo test", &retry_test);
                             }
 
-                            restore_cargo_toml(crate_path).ok();
+                            restore().ok();
                             let all_crate_versions = extract_all_crate_versions(crate_path, base_crate_name);
 
                             return Ok(ThreeStepResult {
@@ -1338,45 +1362,29 @@ pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, String>
                                 original_requirement: original_requirement.clone(),
                                 all_crate_versions,
                                 patch_depth: PatchDepth::Patch, // !! marker
+                                started: None,
+                                fork: None,
+                                blocking: vec![],
+                                repository: None,
+                                base_features: None,
+                                resolver: None,
+                                toolchain: None,
+                                skipped: None,
                             });
                         }
                     }
 
                     // Retry failed, restore and fall through with original result
                     debug!("Test auto-retry with [patch.crates-io] failed");
-                    restore_cargo_toml(crate_path).ok();
+                    restore().ok();
                 }
 
                 // Log original failure
-                if let (Some(dep_info), Some(label)) = (dependent_info.as_ref(), test_label) {
-                    log_failure_with_diagnostics(
-                        dep_info.name,
-                        dep_info.version,
-                        base_crate_name,
-                        label,
-                        "cargo test",
-                        None,
-                        &result.stdout,
-                        &result.stderr,
-                        &result.diagnostics,
-                    );
-                }
+                log_failure("cargo test", &result);
                 (Some(result), None)
             } else {
-                if result.failed()
-                    && let (Some(dep_info), Some(label)) = (dependent_info.as_ref(), test_label)
-                {
-                    log_failure_with_diagnostics(
-                        dep_info.name,
-                        dep_info.version,
-                        base_crate_name,
-                        label,
-                        "cargo test",
-                        None,
-                        &result.stdout,
-                        &result.stderr,
-                        &result.diagnostics,
-                    );
+                if result.failed() {
+                    log_failure("cargo test", &result);
                 }
                 (Some(result), None)
             }
@@ -1389,7 +1397,7 @@ pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, String>
 
     // Cleanup: Always restore Cargo.toml to original state
     // This handles both FORCE mode (where we modified it) and ensures clean state
-    restore_cargo_toml(crate_path).ok(); // Ignore errors on cleanup
+    restore().ok(); // Ignore errors on cleanup
     debug!("Restored Cargo.toml to original state");
 
     // Extract all versions of the base crate from the dependency tree (if fetch succeeded)
@@ -1415,6 +1423,14 @@ pub fn run_three_step_ict(config: TestConfig) -> Result<ThreeStepResult, String>
         original_requirement,
         all_crate_versions,
         patch_depth,
+        started: None,
+        fork: None,
+        blocking: vec![],
+        repository: None,
+        base_features: None,
+        resolver: None,
+        toolchain: None,
+        skipped: None,
     })
 }
 
@@ -1514,16 +1530,6 @@ fn extract_all_crate_versions(crate_dir: &Path, crate_name: &str) -> Vec<(String
         for (spec, resolved, dependent) in &all_versions {
             warn!("  {} requires {} → resolved to {} (via {})", dependent, spec, resolved, crate_name);
         }
-
-        // Log to failure log file if initialized
-        if let Some(ref log_path) = *FAILURE_LOG.lock().unwrap()
-            && let Ok(mut file) = std::fs::OpenOptions::new().create(true).append(true).open(log_path)
-        {
-            let _ = writeln!(file, "\n=== Multi-version detection for '{}' ===", crate_name);
-            for (spec, resolved, dependent) in &all_versions {
-                let _ = writeln!(file, "  {} requires {} → resolved to {}", dependent, spec, resolved);
-            }
-        }
     }
 
     all_versions
@@ -1554,6 +1560,9 @@ mod tests {
             stderr: String::new(),
             duration: Duration::from_secs(1),
             diagnostics: Vec::new(),
+            args: vec![],
+            manifest_diff: None,
+            timed_out: false,
         };
         assert!(result.failed());
 
@@ -1564,75 +1573,88 @@ mod tests {
             stderr: String::new(),
             duration: Duration::from_secs(1),
             diagnostics: Vec::new(),
+            args: vec![],
+            manifest_diff: None,
+            timed_out: false,
         };
         assert!(!result.failed());
     }
 
     #[test]
-    fn test_apply_patch_crates_io() {
-        use tempfile::TempDir;
-
-        let temp_dir = TempDir::new().unwrap();
-        let crate_path = temp_dir.path();
-
-        // Create a basic Cargo.toml
-        let cargo_toml = crate_path.join("Cargo.toml");
-        fs::write(
-            &cargo_toml,
-            r#"[package]
-name = "test-crate"
-version = "0.1.0"
-
-[dependencies]
-rgb = "0.8.50"
-"#,
-        )
-        .unwrap();
-
-        // Apply the patch
-        let override_path = PathBuf::from("/some/local/path");
-        apply_patch_crates_io(crate_path, "rgb", &override_path).unwrap();
+    fn test_workspace_root_of_member() {
+        let tmp = tempfile::tempdir().unwrap();
+        let write = |path: &str, text: &str| {
+            let path = tmp.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        };
+        write("Cargo.toml", "[workspace]\nmembers = [\"viewer\", \"cli\"]\n");
+        write("viewer/Cargo.toml", "[package]\nname = \"viewer\"\nversion = \"0.3.0\"\n");
+        write("cli/Cargo.toml", "[package]\nname = \"viewer-cli\"\nversion = \"0.3.0\"\n");
+        write("tools/Cargo.toml", "[package]\nname = \"tools\"\nversion = \"0.1.0\"\n");
+        write("single/Cargo.toml", "[package]\nname = \"single\"\nversion = \"0.1.0\"\n[workspace]\n");
+
+        let root = paths::canonicalize(tmp.path());
+        assert_eq!(workspace_root(&tmp.path().join("viewer")), Some(root.clone()));
+        assert_eq!(workspace_root(tmp.path()), Some(root.clone()));
+        assert!(is_workspace_root(tmp.path()) && !is_workspace_root(&tmp.path().join("viewer")));
+        // Not a member, and a workspace of one
+        assert_eq!(workspace_root(&tmp.path().join("tools")), None);
+        assert_eq!(workspace_root(&tmp.path().join("single")), None);
+    }
 
-        // Verify the result
-        let content = fs::read_to_string(&cargo_toml).unwrap();
-        assert!(content.contains("[patch.crates-io]"), "Should have [patch.crates-io] section");
-        assert!(content.contains("rgb"), "Should have rgb entry");
-        assert!(content.contains("/some/local/path"), "Should have the override path");
+    #[test]
+    fn test_patch_config_covers_siblings_without_touching_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let write = |path: &str, text: &str| {
+            let path = tmp.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        };
+        write(
+            "magetypes/Cargo.toml",
+            "[package]\nname = \"magetypes\"\n[dependencies]\narchmage = { path = \"../archmage\" }\n",
+        );
+        write("archmage/Cargo.toml", "[package]\nname = \"archmage\"\n");
+
+        let wip = paths::canonicalize(&tmp.path().join("magetypes"));
+        let config = patch_config("magetypes", &wip).unwrap();
+        assert_eq!(
+            config,
+            [
+                format!("patch.crates-io.magetypes.path={}", paths::to_toml_string(&wip)),
+                format!("patch.crates-io.archmage.path={}", paths::to_toml_string(&tmp.path().join("archmage"))),
+            ]
+        );
+        assert!(!tmp.path().join("magetypes").join(patching::BACKUP_NAME).exists());
     }
 
     #[test]
-    fn test_apply_patch_crates_io_preserves_existing_content() {
-        use tempfile::TempDir;
-
-        let temp_dir = TempDir::new().unwrap();
-        let crate_path = temp_dir.path();
-
-        // Create a Cargo.toml with existing patch section
-        let cargo_toml = crate_path.join("Cargo.toml");
-        fs::write(
-            &cargo_toml,
-            r#"[package]
-name = "test-crate"
-version = "0.1.0"
-
-[dependencies]
-rgb = "0.8.50"
-serde = "1.0"
-
-[patch.crates-io]
-other-crate = { path = "/other/path" }
-"#,
-        )
-        .unwrap();
-
-        // Apply the patch
-        let override_path = PathBuf::from("/rgb/path");
-        apply_patch_crates_io(crate_path, "rgb", &override_path).unwrap();
-
-        // Verify the result
-        let content = fs::read_to_string(&cargo_toml).unwrap();
-        assert!(content.contains("other-crate"), "Should preserve existing patches");
-        assert!(content.contains("/other/path"), "Should preserve existing patch path");
-        assert!(content.contains("/rgb/path"), "Should have new rgb path");
+    fn test_resolver_declared_inferred_and_forced() {
+        let tmp = tempfile::tempdir().unwrap();
+        let write = |dir: &str, text: &str| {
+            fs::create_dir_all(tmp.path().join(dir)).unwrap();
+            fs::write(tmp.path().join(dir).join("Cargo.toml"), text).unwrap();
+        };
+        write("old", "[package]\nname = \"old\"\nversion = \"1.0.0\"\nedition = \"2018\"\n");
+        write("new", "[package]\nname = \"new\"\nversion = \"1.0.0\"\nedition = \"2021\"\n");
+        write("pinned", "[package]\nname = \"pinned\"\nversion = \"1.0.0\"\nedition = \"2024\"\nresolver = \"1\"\n");
+        write("virtual", "[workspace]\nmembers = [\"a\"]\n");
+        let resolver = |dir: &str| declared_resolver(&tmp.path().join(dir));
+        assert_eq!(resolver("old").as_deref(), Some("1"));
+        assert_eq!(resolver("new").as_deref(), Some("2"));
+        assert_eq!(resolver("pinned").as_deref(), Some("1"));
+        assert_eq!(resolver("virtual").as_deref(), Some("1"));
+
+        for dir in ["old", "new", "pinned", "virtual"] {
+            force_resolver_2(&tmp.path().join(dir), &AuditLog::default()).unwrap();
+        }
+        let manifest = |dir: &str| fs::read_to_string(tmp.path().join(dir).join("Cargo.toml")).unwrap();
+        assert!(manifest("old").ends_with("edition = \"2018\"\nresolver = \"2\"\n"));
+        assert!(!manifest("new").contains("resolver"));
+        assert!(manifest("pinned").contains("resolver = \"2\""));
+        assert_eq!(manifest("virtual"), "[workspace]\nmembers = [\"a\"]\nresolver = \"2\"\n");
+        // What the dependent declares is still read from the pristine backup
+        assert_eq!(resolver("old").as_deref(), Some("1"));
     }
 }
@@ -0,0 +1,287 @@
+/// Dependents' own `[patch]`, `[replace]` and `.cargo/config.toml` overrides
+///
+/// Some dependents ship overrides of their own: a `[patch.crates-io]` entry for the base
+/// crate, a `[patch]` pointing at a sibling directory that isn't in the published crate, or
+/// a `.cargo/config.toml` that replaces crates.io with a vendor directory. Cargo picks them
+/// up next to copter's patch and the run fails with a resolution error that says nothing
+/// about why. Before each test the staged dependent is scanned for overrides that
+/// conflict with copter's: ones that name the base crate (or a workspace sibling it
+/// patches), ones whose `path` doesn't exist, and source replacements into a directory
+/// that doesn't exist. `--dependent-patches` picks what happens to them:
+///
+/// - `keep` (default): leave them; a failed step's output starts with the list
+/// - `merge`: drop just the conflicting ones, so copter's patch and the rest coexist
+/// - `strip`: drop every `[patch]`, `[replace]`, `paths` and source replacement
+///
+/// Manifest edits go through [`super::patching::mutate`]; config files are snapshotted
+/// to `<file>.original.txt` the same way and restored before every test.
+use super::CompileResult;
+use super::patching;
+use crate::audit::AuditLog;
+use crate::error::CopterError;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Config files cargo reads from the dependent's `.cargo` directory
+const CONFIG_FILES: [&str; 2] = ["config.toml", "config"];
+
+/// What to do with a dependent's own overrides (`--dependent-patches`)
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependentPatches {
+    /// Leave them in place and name the conflicting ones when a step fails
+    #[default]
+    Keep,
+    /// Drop the ones that conflict with copter's patch, keep the rest
+    Merge,
+    /// Drop all of them
+    Strip,
+}
+
+/// An override that clashes with copter's patch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// `Cargo.toml` or `.cargo/config.toml`
+    pub file: String,
+    /// `[patch.crates-io] rgb`
+    pub entry: String,
+    pub reason: String,
+}
+
+/// One override: where it sits in the document and why it conflicts, if it does
+struct Override {
+    keys: Vec<String>,
+    entry: String,
+    conflict: Option<String>,
+}
+
+/// Restore the dependent's config files from their snapshots; true if one had been changed
+pub fn restore_configs(crate_path: &Path, audit: &AuditLog) -> Result<bool, CopterError> {
+    let mut restored = false;
+    for path in config_paths(crate_path) {
+        let Ok(pristine) = fs::read_to_string(backup_path(&path)) else { continue };
+        let current = fs::read_to_string(&path).unwrap_or_default();
+        if current != pristine {
+            patching::write_atomic(&path, &pristine)?;
+            audit.record(&path, "restore from backup", &current, &pristine);
+            restored = true;
+        }
+    }
+    Ok(restored)
+}
+
+/// Handle the dependent's overrides per `mode`; returns the conflicts left in place
+///
+/// `patched` are the crates copter patches: the base crate and its workspace siblings.
+pub fn apply(
+    crate_path: &Path,
+    patched: &[String],
+    mode: DependentPatches,
+    audit: &AuditLog,
+) -> Result<Vec<Conflict>, CopterError> {
+    let mut remaining = Vec::new();
+    let files = std::iter::once(crate_path.join("Cargo.toml")).chain(config_paths(crate_path));
+    for path in files.filter(|p| p.exists()) {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| CopterError::Patching(format!("Failed to read {}: {}", path.display(), e)))?;
+        let Ok(doc) = content.parse::<toml_edit::DocumentMut>() else { continue };
+        let is_manifest = path.file_name().is_some_and(|n| n == "Cargo.toml");
+        let (dropped, kept): (Vec<Override>, Vec<Override>) =
+            overrides(&doc, crate_path, patched, !is_manifest).into_iter().partition(|o| match mode {
+                DependentPatches::Keep => false,
+                DependentPatches::Merge => o.conflict.is_some(),
+                DependentPatches::Strip => true,
+            });
+
+        let file = path.strip_prefix(crate_path).unwrap_or(&path).display().to_string();
+        remaining.extend(
+            kept.into_iter().filter_map(|o| Some(Conflict { file: file.clone(), entry: o.entry, reason: o.conflict? })),
+        );
+        if dropped.is_empty() {
+            continue;
+        }
+        let reason = format!("--dependent-patches {:?}", mode).to_lowercase();
+        let remove_all = |doc: &mut toml_edit::DocumentMut| {
+            for o in &dropped {
+                remove(doc, &o.keys);
+            }
+        };
+        if is_manifest {
+            patching::mutate(crate_path, audit, &reason, |doc| {
+                remove_all(doc);
+                Ok(())
+            })?;
+        } else {
+            let backup = backup_path(&path);
+            if !backup.exists() {
+                patching::write_atomic(&backup, &content)?;
+            }
+            let mut doc = doc;
+            remove_all(&mut doc);
+            let updated = doc.to_string();
+            audit.record(&path, &reason, &content, &updated);
+            patching::write_atomic(&path, &updated)?;
+        }
+    }
+    Ok(remaining)
+}
+
+/// Explain a failed step that the conflicts may have caused
+///
+/// Only steps without compiler diagnostics: resolution and fetch errors are where
+/// conflicting overrides show up, and there the output is otherwise opaque.
+pub fn annotate(result: &mut CompileResult, conflicts: &[Conflict]) {
+    if conflicts.is_empty() || !result.failed() || !result.diagnostics.is_empty() {
+        return;
+    }
+    let mut note = "copter: the dependent's own overrides conflict with copter's patch:\n".to_string();
+    for c in conflicts {
+        note.push_str(&format!("  {} {}: {}\n", c.file, c.entry, c.reason));
+    }
+    note.push_str("  (--dependent-patches merge drops these; strip drops all of the dependent's overrides)\n\n");
+    result.stderr.insert_str(0, &note);
+}
+
+/// Every `[patch]`, `[replace]`, and for config files `paths` and source replacement, in `doc`
+fn overrides(doc: &toml_edit::DocumentMut, dir: &Path, patched: &[String], is_config: bool) -> Vec<Override> {
+    let missing = |item: &toml_edit::Item| {
+        let path = item.get("path").and_then(|p| p.as_str())?;
+        (!dir.join(path).exists()).then(|| format!("path {} isn't part of the staged crate", path))
+    };
+    let overrides_patched = |name: &str, item: &toml_edit::Item| {
+        let package = item.get("package").and_then(|p| p.as_str()).unwrap_or(name);
+        patched.iter().any(|p| p == package).then(|| format!("overrides {}, which copter patches", package))
+    };
+
+    let mut found = Vec::new();
+    if let Some(registries) = doc.get("patch").and_then(|p| p.as_table_like()) {
+        for (registry, entries) in registries.iter() {
+            for (name, item) in entries.as_table_like().into_iter().flat_map(|t| t.iter()) {
+                found.push(Override {
+                    keys: vec!["patch".to_string(), registry.to_string(), name.to_string()],
+                    entry: format!("[patch.{}] {}", registry, name),
+                    conflict: overrides_patched(name, item).or_else(|| missing(item)),
+                });
+            }
+        }
+    }
+    if let Some(replace) = doc.get("replace").and_then(|r| r.as_table_like()) {
+        for (spec, item) in replace.iter() {
+            let name = spec.split(':').next().unwrap_or(spec);
+            found.push(Override {
+                keys: vec!["replace".to_string(), spec.to_string()],
+                entry: format!("[replace] {}", spec),
+                conflict: overrides_patched(name, item).or_else(|| missing(item)),
+            });
+        }
+    }
+    if !is_config {
+        return found;
+    }
+
+    if let Some(paths) = doc.get("paths").and_then(|p| p.as_array()) {
+        let gone = paths.iter().filter_map(|p| p.as_str()).find(|p| !dir.join(p).exists());
+        found.push(Override {
+            keys: vec!["paths".to_string()],
+            entry: "paths".to_string(),
+            conflict: gone.map(|p| format!("path {} isn't part of the staged crate", p)),
+        });
+    }
+    if let Some(sources) = doc.get("source").and_then(|s| s.as_table_like()) {
+        for (name, source) in sources.iter() {
+            let Some(with) = source.get("replace-with").and_then(|w| w.as_str()) else { continue };
+            let target = sources.get(with);
+            let directory = target.and_then(|t| t.get("directory").or_else(|| t.get("local-registry")));
+            let conflict = match directory.and_then(|d| d.as_str()) {
+                _ if target.is_none() => Some(format!("replacement source {} isn't defined", with)),
+                Some(d) if !dir.join(d).exists() => Some(format!("replaces {} with {}, which isn't there", name, d)),
+                _ => None,
+            };
+            found.push(Override {
+                keys: vec!["source".to_string(), name.to_string(), "replace-with".to_string()],
+                entry: format!("[source.{}] replace-with = \"{}\"", name, with),
+                conflict,
+            });
+        }
+    }
+    found
+}
+
+/// Remove the item at `keys`, and the tables it leaves empty
+fn remove(doc: &mut toml_edit::DocumentMut, keys: &[String]) {
+    fn remove_in(table: &mut dyn toml_edit::TableLike, keys: &[String]) {
+        match keys {
+            [] => {}
+            [last] => {
+                table.remove(last);
+            }
+            [first, rest @ ..] => {
+                let Some(inner) = table.get_mut(first).and_then(|t| t.as_table_like_mut()) else { return };
+                remove_in(inner, rest);
+                if inner.is_empty() {
+                    table.remove(first);
+                }
+            }
+        }
+    }
+    remove_in(doc.as_table_mut(), keys);
+}
+
+fn config_paths(crate_path: &Path) -> impl Iterator<Item = PathBuf> + '_ {
+    CONFIG_FILES.iter().map(move |name| crate_path.join(".cargo").join(name))
+}
+
+fn backup_path(config: &Path) -> PathBuf {
+    let name = config.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    config.with_file_name(format!("{}.original.txt", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_drops_only_conflicting_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let crate_path = dir.path();
+        fs::create_dir_all(crate_path.join("vendor")).unwrap();
+        let manifest = "[package]\nname = \"dep\"\n\n[dependencies]\nrgb = \"0.8\"\n\n\
+                        [patch.crates-io]\nrgb = { git = \"https://example.com/rgb\" }\n\
+                        sibling = { path = \"../sibling\" }\nlocal = { path = \"vendor\" }\n";
+        fs::write(crate_path.join("Cargo.toml"), manifest).unwrap();
+        fs::create_dir_all(crate_path.join(".cargo")).unwrap();
+        let config = "[source.crates-io]\nreplace-with = \"vendored\"\n\n\
+                      [source.vendored]\ndirectory = \"vendor-missing\"\n\n[build]\njobs = 2\n";
+        fs::write(crate_path.join(".cargo/config.toml"), config).unwrap();
+        let patched = vec!["rgb".to_string()];
+        let audit = AuditLog::default();
+
+        let conflicts = apply(crate_path, &patched, DependentPatches::Keep, &audit).unwrap();
+        let entries: Vec<(&str, &str)> = conflicts.iter().map(|c| (c.file.as_str(), c.entry.as_str())).collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("Cargo.toml", "[patch.crates-io] rgb"),
+                ("Cargo.toml", "[patch.crates-io] sibling"),
+                (".cargo/config.toml", "[source.crates-io] replace-with = \"vendored\""),
+            ]
+        );
+        assert_eq!(conflicts[0].reason, "overrides rgb, which copter patches");
+
+        assert!(apply(crate_path, &patched, DependentPatches::Merge, &audit).unwrap().is_empty());
+        let merged = fs::read_to_string(crate_path.join("Cargo.toml")).unwrap();
+        assert!(merged.contains("local = { path = \"vendor\" }"));
+        assert!(!merged.contains("example.com") && !merged.contains("../sibling"));
+        let merged_config = fs::read_to_string(crate_path.join(".cargo/config.toml")).unwrap();
+        assert!(!merged_config.contains("replace-with") && merged_config.contains("jobs = 2"));
+
+        // Both files come back before the next test
+        patching::restore_cargo_toml(crate_path, &audit).unwrap();
+        assert!(restore_configs(crate_path, &audit).unwrap());
+        assert_eq!(fs::read_to_string(crate_path.join("Cargo.toml")).unwrap(), manifest);
+        assert_eq!(fs::read_to_string(crate_path.join(".cargo/config.toml")).unwrap(), config);
+
+        apply(crate_path, &patched, DependentPatches::Strip, &audit).unwrap();
+        assert!(!fs::read_to_string(crate_path.join("Cargo.toml")).unwrap().contains("[patch"));
+    }
+}
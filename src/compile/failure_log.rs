@@ -0,0 +1,427 @@
+/// Failure log: per-worker handles feeding one writer thread
+///
+/// Workers never touch the log files. Each holds a cheap `FailureLog` handle and sends
+/// whole entries over a channel; a single writer thread (`FailureLogWriter`) appends them,
+/// so entries from parallel workers never interleave and no file locking is needed.
+///
+/// Layout inside the log directory (normally `copter-report/`):
+/// - `copter-failures.log` — every failure, in arrival order
+/// - `copter-build-failures.log` — fetch/check failures only
+/// - `logs/<dependent>-<version>/<label>.log` — the failures of one dependent against one
+///   base version ("baseline", "WIP" or a version number)
+///
+/// Each per-dependent file starts with what the dependent's manifest says about it (its
+/// description, repository, and how it declares the base crate), as does each entry of the
+/// combined logs, so an unfamiliar crate's failure can be read without looking it up.
+use crate::artifacts::SaveArtifacts;
+use crate::error_extract::{Diagnostic, DiagnosticLevel};
+use crate::scrub::Scrubber;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::JoinHandle;
+
+/// Main log file name
+pub const FAILURE_LOG_NAME: &str = "copter-failures.log";
+/// Fetch/check-only log file name
+pub const BUILD_FAILURE_LOG_NAME: &str = "copter-build-failures.log";
+/// Per-dependent log directory
+pub const DEPENDENT_LOG_DIR: &str = "logs";
+
+const SEPARATOR_LENGTH: usize = 100;
+
+/// One failed cargo step
+#[derive(Debug, Clone)]
+pub struct FailureEntry {
+    pub dependent: String,
+    pub dependent_version: String,
+    pub base_crate: String,
+    /// "baseline", "WIP", or version number
+    pub test_label: String,
+    /// e.g. "cargo check"
+    pub command: String,
+    pub exit_code: Option<i32>,
+    pub stderr: String,
+    /// Written only with `--save-artifacts all`
+    pub stdout: String,
+    pub diagnostics: Vec<Diagnostic>,
+    /// "Description: …", "Repository: …", "Declares: …" (`dependent_context`)
+    pub context: Vec<String>,
+}
+
+impl FailureEntry {
+    fn is_build_failure(&self) -> bool {
+        self.command.contains("cargo fetch") || self.command.contains("cargo check")
+    }
+
+    fn signature(&self) -> String {
+        if self.diagnostics.is_empty() {
+            crate::report::error_signature(&self.stderr)
+        } else {
+            let text: Vec<&str> = self.diagnostics.iter().map(|d| d.rendered.as_str()).collect();
+            crate::report::error_signature(&text.join("\n"))
+        }
+    }
+}
+
+#[derive(Debug)]
+enum LogEvent {
+    Failure(FailureEntry),
+    /// (spec, resolved_version, dependent_name) for each copy of `crate_name` in the tree
+    MultiVersion {
+        crate_name: String,
+        versions: Vec<(String, String, String)>,
+    },
+}
+
+/// A worker's handle on the failure log; clone it (or call `for_worker`) per worker
+#[derive(Debug, Clone)]
+pub struct FailureLog {
+    worker: usize,
+    tx: Sender<(usize, LogEvent)>,
+}
+
+impl FailureLog {
+    /// Handle for another worker; "same failure as previous" is tracked per worker
+    pub fn for_worker(&self, worker: usize) -> Self {
+        Self { worker, tx: self.tx.clone() }
+    }
+
+    /// Record a failed step
+    pub fn failure(&self, entry: FailureEntry) {
+        let _ = self.tx.send((self.worker, LogEvent::Failure(entry)));
+    }
+
+    /// Record that several versions of `crate_name` ended up in a dependent's tree
+    pub fn multi_version(&self, crate_name: &str, versions: &[(String, String, String)]) {
+        let event = LogEvent::MultiVersion { crate_name: crate_name.to_string(), versions: versions.to_vec() };
+        let _ = self.tx.send((self.worker, event));
+    }
+}
+
+/// Owns the writer thread; `finish` (or drop) waits for every queued entry to be written
+pub struct FailureLogWriter {
+    log: Option<FailureLog>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl FailureLogWriter {
+    /// Start the writer thread, logging into `dir` as much as `level` keeps; `--scrub` applies
+    /// `scrubber` to every entry
+    pub fn start(dir: &Path, scrubber: Option<Scrubber>, level: SaveArtifacts) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let writer = Writer { dir: dir.to_path_buf(), last_signature: HashMap::new(), scrubber, level };
+        let handle = std::thread::Builder::new()
+            .name("failure-log".to_string())
+            .spawn(move || writer.run(rx))
+            .expect("failed to spawn failure log writer");
+        Self { log: Some(FailureLog { worker: 0, tx }), handle: Some(handle) }
+    }
+
+    /// Handle for worker 0
+    pub fn log(&self) -> FailureLog {
+        self.log.clone().expect("failure log already finished")
+    }
+
+    /// Stop accepting entries and wait until everything sent so far is on disk
+    ///
+    /// Handles still held elsewhere keep the writer alive, so drop them first.
+    pub fn finish(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.log = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for FailureLogWriter {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+struct Writer {
+    dir: PathBuf,
+    last_signature: HashMap<usize, String>,
+    scrubber: Option<Scrubber>,
+    level: SaveArtifacts,
+}
+
+impl Writer {
+    fn run(mut self, rx: Receiver<(usize, LogEvent)>) {
+        for (worker, event) in rx {
+            if let Err(e) = self.write(worker, event) {
+                eprintln!("Warning: failed to write failure log: {}", e);
+            }
+        }
+    }
+
+    fn write(&mut self, worker: usize, event: LogEvent) -> std::io::Result<()> {
+        match event {
+            LogEvent::Failure(_) if !self.level.logs() => Ok(()),
+            LogEvent::Failure(entry) => {
+                let signature = entry.signature();
+                let duplicate = self.last_signature.insert(worker, signature.clone()).as_ref() == Some(&signature);
+
+                let text = format_entry(&entry, "FAILURE", duplicate, !duplicate, self.level);
+                self.append(&self.dir.join(FAILURE_LOG_NAME), &text)?;
+                if entry.is_build_failure() {
+                    let text = format_entry(&entry, "BUILD FAILURE", duplicate, !duplicate, self.level);
+                    self.append(&self.dir.join(BUILD_FAILURE_LOG_NAME), &text)?;
+                }
+                // The per-dependent file always has the full text; "same as previous" only makes sense in the combined log
+                let path = dependent_log_path(&self.dir, &entry);
+                let text = format_entry(&entry, "FAILURE", false, !path.exists(), self.level);
+                self.append(&path, &text)
+            }
+            LogEvent::MultiVersion { crate_name, versions } => {
+                let mut text = format!("\n=== Multi-version detection for '{}' ===\n", crate_name);
+                for (spec, resolved, dependent) in &versions {
+                    text.push_str(&format!("  {} requires {} → resolved to {}\n", dependent, spec, resolved));
+                }
+                self.append(&self.dir.join(FAILURE_LOG_NAME), &text)
+            }
+        }
+    }
+
+    fn append(&self, path: &Path, text: &str) -> std::io::Result<()> {
+        match &self.scrubber {
+            Some(scrubber) => append(path, &scrubber.text(text)),
+            None => append(path, text),
+        }
+    }
+}
+
+/// `logs/<dependent>-<version>/<label>.log`
+pub fn dependent_log_path(dir: &Path, entry: &FailureEntry) -> PathBuf {
+    dir.join(DEPENDENT_LOG_DIR)
+        .join(file_safe(&format!("{}-{}", entry.dependent, entry.dependent_version)))
+        .join(format!("{}.log", file_safe(&entry.test_label)))
+}
+
+fn file_safe(name: &str) -> String {
+    name.chars().map(|c| if c.is_ascii_alphanumeric() || "._-+".contains(c) { c } else { '_' }).collect()
+}
+
+fn append(path: &Path, text: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    OpenOptions::new().create(true).append(true).open(path)?.write_all(text.as_bytes())
+}
+
+/// What the dependent's manifest in `crate_path` says about it: description, repository
+/// and each declaration of `base_crate` (the manifest as published, before any patching)
+pub fn dependent_context(crate_path: &Path, base_crate: &str) -> Vec<String> {
+    let pristine = crate_path.join(super::patching::BACKUP_NAME);
+    let manifest = fs::read_to_string(&pristine).or_else(|_| fs::read_to_string(crate_path.join("Cargo.toml")));
+    let Some(manifest) = manifest.ok().and_then(|text| text.parse::<toml_edit::DocumentMut>().ok()) else {
+        return vec![];
+    };
+    let field = |key: &str| manifest.get("package").and_then(|p| p.get(key)).and_then(|v| v.as_str());
+    let mut lines = Vec::new();
+    if let Some(description) = field("description") {
+        lines.push(format!("Description: {}", description.split_whitespace().collect::<Vec<_>>().join(" ")));
+    }
+    if let Some(repository) = field("repository") {
+        lines.push(format!("Repository: {}", repository));
+    }
+
+    // As written: key order and formatting kept, `[dependencies.rgb]` tables shown inline
+    let mut tables: Vec<(String, &dyn toml_edit::TableLike)> = Vec::new();
+    let sections = ["dependencies", "dev-dependencies", "build-dependencies"];
+    for section in sections {
+        if let Some(deps) = manifest.get(section).and_then(|d| d.as_table_like()) {
+            tables.push((section.to_string(), deps));
+        }
+    }
+    for (cfg, target) in manifest.get("target").and_then(|t| t.as_table_like()).into_iter().flat_map(|t| t.iter()) {
+        for section in sections {
+            if let Some(deps) = target.get(section).and_then(|d| d.as_table_like()) {
+                tables.push((format!("target.'{}'.{}", cfg, section), deps));
+            }
+        }
+    }
+    for (section, deps) in tables {
+        for (key, spec) in deps.iter() {
+            let package = spec.get("package").and_then(|p| p.as_str()).unwrap_or(key);
+            if package != base_crate {
+                continue;
+            }
+            let spec = match spec.as_table() {
+                Some(table) => table.clone().into_inline_table().to_string(),
+                None => spec.to_string(),
+            };
+            lines.push(format!("Declares: [{}] {} = {}", section, key, spec.trim()));
+        }
+    }
+    lines
+}
+
+fn format_entry(
+    entry: &FailureEntry,
+    log_type: &str,
+    duplicate: bool,
+    with_context: bool,
+    level: SaveArtifacts,
+) -> String {
+    let separator = "=".repeat(SEPARATOR_LENGTH);
+    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+    let exit_str = entry.exit_code.map(|c| c.to_string()).unwrap_or_else(|| "N/A".to_string());
+
+    let mut out = format!("\n{}\n", separator);
+    out.push_str(&format!(
+        "[{}] {}: {} {} testing {} {}\n",
+        timestamp, log_type, entry.dependent, entry.dependent_version, entry.base_crate, entry.test_label
+    ));
+    out.push_str(&format!("{}\n", separator));
+    if with_context && !entry.context.is_empty() {
+        out.push_str(&format!("{}\n\n", entry.context.join("\n")));
+    }
+    out.push_str(&format!("Command: {}\n", entry.command));
+    out.push_str(&format!("Exit code: {}\n", exit_str));
+
+    if duplicate {
+        out.push_str("\n--- SAME FAILURE AS PREVIOUS ---\n");
+    } else if !entry.diagnostics.is_empty() {
+        out.push_str("\n--- ERRORS ---\n");
+        for (idx, diag) in entry.diagnostics.iter().enumerate() {
+            let level_str = match diag.level {
+                DiagnosticLevel::Error => "error",
+                DiagnosticLevel::Warning => "warning",
+                DiagnosticLevel::Help => "help",
+                DiagnosticLevel::Note => "note",
+                DiagnosticLevel::Other(ref s) => s.as_str(),
+            };
+            out.push_str(&format!("\n{}. [{}] {}\n", idx + 1, level_str, diag.message));
+            if !diag.rendered.is_empty() {
+                out.push_str(&format!("{}\n", diag.rendered));
+            }
+        }
+    } else {
+        out.push_str("\n--- STDERR (no structured errors) ---\n");
+        let lines: Vec<&str> = entry.stderr.lines().filter(|l| !l.trim_start().starts_with('{')).collect();
+        out.push_str(&level.message(&lines.join("\n"), true));
+        out.push('\n');
+    }
+    if !duplicate && level.stdout() && !entry.stdout.is_empty() {
+        out.push_str("\n--- STDOUT ---\n");
+        out.push_str(&entry.stdout);
+        if !entry.stdout.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+
+    out.push_str(&format!("\n{}\n", separator));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(dependent: &str, label: &str, command: &str, stderr: &str) -> FailureEntry {
+        FailureEntry {
+            dependent: dependent.to_string(),
+            dependent_version: "1.0.0".to_string(),
+            base_crate: "rgb".to_string(),
+            test_label: label.to_string(),
+            command: command.to_string(),
+            exit_code: Some(101),
+            stderr: stderr.to_string(),
+            stdout: String::new(),
+            diagnostics: vec![],
+            context: vec![format!("Description: The {} crate", dependent)],
+        }
+    }
+
+    #[test]
+    fn test_workers_write_whole_entries_through_one_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = FailureLogWriter::start(dir.path(), None, SaveArtifacts::Stderr);
+
+        let handles: Vec<_> = (0..4)
+            .map(|worker| {
+                let log = writer.log().for_worker(worker);
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        let stderr = format!("error: worker {} failure {}\n", worker, i);
+                        log.failure(entry(&format!("dep{}", worker), "baseline", "cargo check", &stderr));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        writer.log().multi_version("rgb", &[("^0.8".into(), "0.8.1".into(), "a".into())]);
+        writer.finish();
+
+        let main = fs::read_to_string(dir.path().join(FAILURE_LOG_NAME)).unwrap();
+        assert_eq!(main.matches("Command: cargo check").count(), 100);
+        assert!(main.contains("=== Multi-version detection for 'rgb' ==="));
+        assert_eq!(
+            fs::read_to_string(dir.path().join(BUILD_FAILURE_LOG_NAME)).unwrap().matches("BUILD FAILURE").count(),
+            100
+        );
+
+        let dep2 = fs::read_to_string(dir.path().join("logs/dep2-1.0.0/baseline.log")).unwrap();
+        let positions: Vec<usize> = (0..25).map(|i| dep2.find(&format!("worker 2 failure {}\n", i)).unwrap()).collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]), "entries stay in each worker's order");
+        assert!(!dep2.contains("worker 1"));
+    }
+
+    #[test]
+    fn test_same_failure_tracked_per_worker() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = FailureLogWriter::start(dir.path(), None, SaveArtifacts::Stderr);
+        let (a, b) = (writer.log(), writer.log().for_worker(1));
+        a.failure(entry("a", "baseline", "cargo test", "error: boom"));
+        b.failure(entry("b", "baseline", "cargo test", "error: other"));
+        a.failure(entry("a", "0.9.0", "cargo test", "error: boom"));
+        drop((a, b));
+        writer.finish();
+
+        let main = fs::read_to_string(dir.path().join(FAILURE_LOG_NAME)).unwrap();
+        assert_eq!(main.matches("SAME FAILURE AS PREVIOUS").count(), 1);
+        assert!(!dir.path().join(BUILD_FAILURE_LOG_NAME).exists());
+        let label = fs::read_to_string(dir.path().join("logs/a-1.0.0/0.9.0.log")).unwrap();
+        assert!(label.contains("error: boom"));
+    }
+
+    #[test]
+    fn test_dependent_context_heads_each_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = "[package]\nname = \"image\"\nversion = \"0.25.8\"\n\
+                        description = \"Imaging library.   Encoders and decoders.\"\n\
+                        repository = \"https://github.com/image-rs/image\"\n\n\
+                        [dependencies]\nrgb = { version = \"0.8.50\", optional = true }\n\n\
+                        [target.'cfg(unix)'.dev-dependencies]\nrgb2 = { package = \"rgb\", version = \"0.8\" }\n";
+        fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"patched\"\n").unwrap();
+        fs::write(dir.path().join(crate::compile::patching::BACKUP_NAME), manifest).unwrap();
+        assert_eq!(
+            dependent_context(dir.path(), "rgb"),
+            [
+                "Description: Imaging library. Encoders and decoders.",
+                "Repository: https://github.com/image-rs/image",
+                "Declares: [dependencies] rgb = { version = \"0.8.50\", optional = true }",
+                "Declares: [target.'cfg(unix)'.dev-dependencies] rgb2 = { package = \"rgb\", version = \"0.8\" }",
+            ]
+        );
+
+        let writer = FailureLogWriter::start(dir.path(), None, SaveArtifacts::Stderr);
+        writer.log().failure(entry("a", "0.9.0", "cargo check", "error: boom"));
+        writer.log().failure(entry("a", "0.9.0", "cargo test", "error: boom"));
+        writer.finish();
+        let label = fs::read_to_string(dir.path().join("logs/a-1.0.0/0.9.0.log")).unwrap();
+        assert_eq!(label.matches("Description: The a crate").count(), 1);
+        let main = fs::read_to_string(dir.path().join(FAILURE_LOG_NAME)).unwrap();
+        assert_eq!(main.matches("Description: The a crate").count(), 1, "not repeated for the same failure");
+    }
+}
@@ -0,0 +1,442 @@
+/// Heartbeat, stall detection and time limits for cargo steps (`--stall-minutes`,
+/// `--stall-sample`, `--timeout-per-step`, `--timeout-per-dependent`, `--time-budget`)
+///
+/// A dependent's test suite that hangs looks exactly like a hung copter: nothing moves.
+/// `run` executes a cargo command like `Command::output`, but while it waits it tracks
+/// when the child last wrote to stdout/stderr. After `stall_after` of silence it prints
+/// a warning naming the command, its directory and the elapsed time, and repeats every
+/// `stall_after` while the silence lasts. With `sample` it also prints the child's
+/// process tree (via `ps`), which shows whether a test binary is still alive and what
+/// it has spawned.
+///
+/// Warnings don't stop a doctest that waits on the network forever. With a time limit
+/// the child runs in its own process group, and when the limit passes the whole group
+/// (cargo, rustc, test binaries and whatever they spawned) is killed; the step fails as
+/// timed out and the run moves on to the next one. A terminal's Ctrl+C only reaches the
+/// foreground group, so `install_interrupt_handler` kills the running steps' groups (and
+/// removes their `--docker-steps` containers) before copter exits.
+use lazy_static::lazy_static;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Default silence before the first warning
+pub const DEFAULT_STALL_MINUTES: u64 = 10;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Stall warning settings and time limits for one run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Heartbeat {
+    /// Silence before warning; `None` disables monitoring
+    pub stall_after: Option<Duration>,
+    /// Print the child's process tree with each warning
+    pub sample: bool,
+    /// Longest a single cargo step may run
+    #[serde(default)]
+    pub step_timeout: Option<Duration>,
+    /// Longest all steps of one dependent may take together, across base versions
+    #[serde(default)]
+    pub dependent_timeout: Option<Duration>,
+    /// When the current dependent's time is up (set by the runner from `dependent_timeout`)
+    #[serde(skip)]
+    pub deadline: Option<Instant>,
+    /// When the whole run's time is up (`--time-budget`)
+    #[serde(skip)]
+    pub run_deadline: Option<Instant>,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::from_minutes(DEFAULT_STALL_MINUTES, false)
+    }
+}
+
+impl Heartbeat {
+    /// `minutes == 0` disables monitoring
+    pub fn from_minutes(minutes: u64, sample: bool) -> Self {
+        let stall_after = (minutes > 0).then(|| Duration::from_secs(minutes * 60));
+        Self { stall_after, sample, ..Self::off() }
+    }
+
+    /// Monitoring turned off
+    pub fn off() -> Self {
+        Self {
+            stall_after: None,
+            sample: false,
+            step_timeout: None,
+            dependent_timeout: None,
+            deadline: None,
+            run_deadline: None,
+        }
+    }
+
+    /// With time limits in minutes per step and per dependent (`None` or 0: no limit)
+    pub fn with_timeouts(self, step_minutes: Option<u64>, dependent_minutes: Option<u64>) -> Self {
+        let limit = |minutes: Option<u64>| minutes.filter(|&m| m > 0).map(|m| Duration::from_secs(m * 60));
+        Self { step_timeout: limit(step_minutes), dependent_timeout: limit(dependent_minutes), ..self }
+    }
+
+    /// With the run ending `minutes` from now (`None`: no budget)
+    pub fn with_run_budget(self, minutes: Option<u64>) -> Self {
+        Self { run_deadline: minutes.map(|m| Instant::now() + Duration::from_secs(m * 60)), ..self }
+    }
+
+    /// Whether the run's time budget is used up
+    pub fn out_of_time(&self) -> bool {
+        self.run_deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// For a dependent starting now: its steps share `dependent_timeout`, and end with the run's budget
+    pub fn for_dependent(self) -> Self {
+        let own = self.dependent_timeout.map(|timeout| Instant::now() + timeout);
+        let deadline = match (own, self.run_deadline) {
+            (Some(own), Some(run)) => Some(own.min(run)),
+            (own, run) => own.or(run),
+        };
+        Self { deadline, ..self }
+    }
+
+    /// The time limit of a step starting now: the step limit, cut short by the deadline
+    pub fn limit(&self) -> Option<Duration> {
+        let remaining = self.deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        match (self.step_timeout, remaining) {
+            (Some(step), Some(remaining)) => Some(step.min(remaining)),
+            (step, remaining) => step.or(remaining),
+        }
+    }
+}
+
+lazy_static! {
+    /// Steps running now, for the interrupt handler (which has no other way to find them)
+    static ref RUNNING: Mutex<Vec<Running>> = Mutex::new(Vec::new());
+}
+
+/// What the interrupt handler has to stop for one running step
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Running {
+    /// A step in its own process group
+    Group(u32),
+    /// A `--docker-steps` container
+    Container(String),
+}
+
+/// A running step known to the interrupt handler until dropped
+#[must_use]
+pub struct Tracked(Running);
+
+impl Tracked {
+    fn new(running: Running) -> Self {
+        RUNNING.lock().unwrap().push(running.clone());
+        Tracked(running)
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        let mut running = RUNNING.lock().unwrap();
+        if let Some(i) = running.iter().position(|r| *r == self.0) {
+            running.remove(i);
+        }
+    }
+}
+
+/// Have Ctrl+C remove `name`, a step's container, while the result lives
+pub fn track_container(name: &str) -> Tracked {
+    Tracked::new(Running::Container(name.to_string()))
+}
+
+/// On Ctrl+C, kill the running steps' process groups and remove their containers, then exit
+pub fn install_interrupt_handler() {
+    let result = ctrlc::set_handler(|| {
+        for running in std::mem::take(&mut *RUNNING.lock().unwrap()) {
+            match running {
+                Running::Group(pid) => kill_process_group(pid),
+                Running::Container(name) => crate::docker::remove_container(&name),
+            }
+        }
+        std::process::exit(130);
+    });
+    if let Err(e) = result {
+        eprintln!("warning: Ctrl+C won't stop cargo steps that have a time limit: {}", e);
+    }
+}
+
+/// A finished cargo command
+#[derive(Debug)]
+pub struct Finished {
+    pub output: Output,
+    /// Killed at its time limit
+    pub timed_out: bool,
+}
+
+/// Decides when silence deserves a (repeated) warning
+#[derive(Debug)]
+struct StallMonitor {
+    after: Duration,
+    next: Duration,
+}
+
+impl StallMonitor {
+    fn new(after: Duration) -> Self {
+        Self { after, next: after }
+    }
+
+    /// True when `silent` has crossed the next warning threshold; output resets it
+    fn due(&mut self, silent: Duration) -> bool {
+        if silent < self.after {
+            self.next = self.after;
+            return false;
+        }
+        if silent >= self.next {
+            self.next = silent + self.after;
+            return true;
+        }
+        false
+    }
+}
+
+/// Run `cmd` to completion, capturing output like `Command::output`, with stall warnings;
+/// past the heartbeat's time limit its process group is killed
+pub fn run(cmd: &mut Command, heartbeat: &Heartbeat) -> std::io::Result<Finished> {
+    let limit = heartbeat.limit();
+    if heartbeat.stall_after.is_none() && limit.is_none() {
+        return cmd.output().map(|output| Finished { output, timed_out: false });
+    }
+    let description = describe(cmd);
+
+    #[cfg(unix)]
+    if limit.is_some() {
+        use std::os::unix::process::CommandExt;
+        // Its own group, so the kill reaches test binaries and what they spawned
+        cmd.process_group(0);
+    }
+    let mut child = cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    let pid = child.id();
+    let _group = (cfg!(unix) && limit.is_some()).then(|| Tracked::new(Running::Group(pid)));
+    let start = Instant::now();
+    // Milliseconds after `start` at which the child last produced output
+    let last_output = Arc::new(AtomicU64::new(0));
+
+    let stdout = drain(child.stdout.take(), start, last_output.clone());
+    let stderr = drain(child.stderr.take(), start, last_output.clone());
+    let (done_tx, done_rx) = mpsc::channel();
+    let waiter = std::thread::spawn(move || {
+        let status = child.wait();
+        let _ = done_tx.send(());
+        status
+    });
+
+    let mut monitor = heartbeat.stall_after.map(StallMonitor::new);
+    let mut timed_out = false;
+    let poll = limit.map_or(POLL_INTERVAL, |limit| limit.min(POLL_INTERVAL));
+    while let Err(mpsc::RecvTimeoutError::Timeout) = done_rx.recv_timeout(poll) {
+        let elapsed = start.elapsed();
+        if let Some(limit) = limit
+            && elapsed >= limit
+            && !timed_out
+        {
+            eprintln!("warning: killing {} after {} (time limit)", description, format_elapsed(elapsed));
+            kill_process_group(pid);
+            timed_out = true;
+        }
+        let silent = elapsed.saturating_sub(Duration::from_millis(last_output.load(Ordering::Relaxed)));
+        if let Some(monitor) = &mut monitor
+            && monitor.due(silent)
+        {
+            eprintln!(
+                "warning: no output for {} from {} (running {}); a dependent's build or tests may be hung",
+                format_elapsed(silent),
+                description,
+                format_elapsed(elapsed)
+            );
+            if heartbeat.sample {
+                match sample_process_tree(pid) {
+                    Some(tree) => tree.lines().for_each(|line| eprintln!("    {}", line)),
+                    None => eprintln!("    (process sample unavailable on this platform)"),
+                }
+            }
+        }
+    }
+
+    let status = waiter.join().map_err(|_| std::io::Error::other("cargo wait thread panicked"))??;
+    let mut stderr = stderr.join().unwrap_or_default();
+    if timed_out {
+        let elapsed = format_elapsed(limit.unwrap_or_default());
+        stderr.extend_from_slice(format!("\nerror: timed out: killed by cargo-copter after {}\n", elapsed).as_bytes());
+    }
+    Ok(Finished { output: Output { status, stdout: stdout.join().unwrap_or_default(), stderr }, timed_out })
+}
+
+/// Kill `pid`'s process group (the child and everything it started), or its process tree on Windows
+fn kill_process_group(pid: u32) {
+    let result = if cfg!(windows) {
+        Command::new("taskkill").args(["/F", "/T", "/PID", &pid.to_string()]).output()
+    } else {
+        Command::new("kill").args(["-KILL", "--", &format!("-{}", pid)]).output()
+    };
+    if let Err(e) = result {
+        eprintln!("warning: failed to kill process group {}: {}", pid, e);
+    }
+}
+
+/// Read a pipe to the end on its own thread, stamping `last_output` on every chunk
+fn drain<R: Read + Send + 'static>(
+    pipe: Option<R>,
+    start: Instant,
+    last_output: Arc<AtomicU64>,
+) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut collected = Vec::new();
+        let Some(mut pipe) = pipe else {
+            return collected;
+        };
+        let mut buf = [0u8; 8192];
+        while let Ok(n) = pipe.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            collected.extend_from_slice(&buf[..n]);
+            last_output.store(start.elapsed().as_millis() as u64, Ordering::Relaxed);
+        }
+        collected
+    })
+}
+
+/// `` `cargo test --message-format=json` in <dir> ``
+fn describe(cmd: &Command) -> String {
+    let mut text = format!("`{}", cmd.get_program().to_string_lossy());
+    for arg in cmd.get_args() {
+        text.push(' ');
+        text.push_str(&arg.to_string_lossy());
+    }
+    text.push('`');
+    if let Some(dir) = cmd.get_current_dir() {
+        text.push_str(&format!(" in {}", crate::paths::canonicalize(Path::new(dir)).display()));
+    }
+    text
+}
+
+/// `12m 5s`, `1h 3m`, `42s`
+pub fn format_elapsed(d: Duration) -> String {
+    let secs = d.as_secs();
+    match (secs / 3600, (secs % 3600) / 60, secs % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, m, _) => format!("{}h {}m", h, m),
+    }
+}
+
+/// Process tree under `pid` from `ps`; `None` where `ps` isn't available
+fn sample_process_tree(pid: u32) -> Option<String> {
+    if cfg!(windows) {
+        return None;
+    }
+    let output = Command::new("ps").args(["-eo", "pid=,ppid=,etime=,stat=,args="]).output().ok()?;
+    output.status.success().then(|| process_tree(&String::from_utf8_lossy(&output.stdout), pid).join("\n"))
+}
+
+/// Lines of `ps -eo pid,ppid,...` output for `root` and its descendants, indented by depth
+fn process_tree(ps_output: &str, root: u32) -> Vec<String> {
+    let rows: Vec<(u32, u32, &str)> = ps_output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid = fields.next()?.parse().ok()?;
+            Some((pid, ppid, line.trim()))
+        })
+        .collect();
+
+    fn walk(rows: &[(u32, u32, &str)], pid: u32, depth: usize, out: &mut Vec<String>) {
+        for (child, _, line) in rows.iter().filter(|(_, ppid, _)| *ppid == pid) {
+            out.push(format!("{}{}", "  ".repeat(depth), line));
+            walk(rows, *child, depth + 1, out);
+        }
+    }
+
+    let mut out = Vec::new();
+    if let Some((_, _, line)) = rows.iter().find(|(pid, _, _)| *pid == root) {
+        out.push(line.to_string());
+        walk(&rows, root, 1, &mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stall_monitor_repeats_and_resets() {
+        let min = Duration::from_secs(60);
+        let mut monitor = StallMonitor::new(min);
+        assert!(!monitor.due(Duration::from_secs(59)));
+        assert!(monitor.due(Duration::from_secs(60)));
+        assert!(!monitor.due(Duration::from_secs(61)));
+        assert!(monitor.due(Duration::from_secs(121)));
+        // Output resumed, so the next silence starts a fresh countdown
+        assert!(!monitor.due(Duration::from_secs(5)));
+        assert!(monitor.due(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_process_tree() {
+        let ps = "    1     0  10:00 Ss   init\n  100     1  05:00 S    cargo test\n  \
+                  101   100  04:59 S    target/debug/deps/foo-abc\n  102   101  04:00 S    sleep 1000\n  \
+                  200     1  01:00 S    unrelated\n";
+        assert_eq!(
+            process_tree(ps, 100),
+            vec![
+                "100     1  05:00 S    cargo test",
+                "  101   100  04:59 S    target/debug/deps/foo-abc",
+                "    102   101  04:00 S    sleep 1000",
+            ]
+        );
+        assert!(process_tree(ps, 999).is_empty());
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_secs(42)), "42s");
+        assert_eq!(format_elapsed(Duration::from_secs(725)), "12m 5s");
+        assert_eq!(format_elapsed(Duration::from_secs(3780)), "1h 3m");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_captures_output_like_command_output() {
+        let heartbeat = Heartbeat { stall_after: Some(Duration::from_secs(60)), sample: true, ..Heartbeat::off() };
+        let finished = run(Command::new("sh").args(["-c", "echo out; echo err >&2; exit 3"]), &heartbeat).unwrap();
+        assert_eq!(finished.output.stdout, b"out\n");
+        assert_eq!(finished.output.stderr, b"err\n");
+        assert_eq!(finished.output.status.code(), Some(3));
+        assert!(!finished.timed_out);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_kills_process_group_at_limit() {
+        let start = Instant::now();
+        // The grandchild `sleep` holds the pipes open; only a group kill ends it
+        let heartbeat = Heartbeat { step_timeout: Some(Duration::from_millis(200)), ..Heartbeat::off() };
+        let finished = run(Command::new("sh").args(["-c", "sleep 30 & wait"]), &heartbeat).unwrap();
+        assert!(finished.timed_out);
+        assert!(!finished.output.status.success());
+        assert!(String::from_utf8_lossy(&finished.output.stderr).contains("timed out"));
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        let step = Heartbeat::off().with_timeouts(Some(30), Some(0));
+        assert_eq!(step.for_dependent().limit(), Some(Duration::from_secs(1800)));
+        let dependent = Heartbeat::off().with_timeouts(Some(30), Some(1)).for_dependent();
+        assert!(dependent.limit().unwrap() <= Duration::from_secs(60));
+        assert_eq!(Heartbeat::off().limit(), None);
+        let budget = Heartbeat::off().with_timeouts(None, Some(30)).with_run_budget(Some(0));
+        assert!(budget.out_of_time());
+        assert_eq!(budget.for_dependent().limit(), Some(Duration::ZERO));
+    }
+}
@@ -0,0 +1,94 @@
+/// Nightly feature-gate mode (`--nightly-gates`)
+///
+/// Crates with a `nightly` or `unstable` feature keep part of their API behind it, and
+/// docs.rs builds them with `--cfg docsrs`. Dependents that turn those on only break
+/// when the gated code is active, which a normal stable run never compiles. In this
+/// mode every step runs as `cargo +nightly` with `--cfg docsrs` added to `RUSTFLAGS`,
+/// and check/test enable the base crate's gated features through the dependent
+/// (`--features rgb/nightly`). Baseline and offered versions run the same way, so a
+/// regression means the offered version's gated code broke the dependent.
+use std::path::Path;
+use std::process::Command;
+
+/// cfg set for every crate in the build, as docs.rs does
+pub const CFG: &str = "docsrs";
+
+/// Toolchain argument; it has to come before the cargo subcommand
+pub const TOOLCHAIN: &str = "+nightly";
+
+/// What the nightly-gate mode turns on
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct NightlyGates {
+    /// The base crate, as the dependents name it in `--features <crate>/<feature>`
+    pub base_crate: String,
+    /// Base crate features to enable (e.g. `nightly`)
+    pub features: Vec<String>,
+}
+
+impl NightlyGates {
+    /// `rgb/nightly,rgb/unstable`
+    fn feature_list(&self) -> String {
+        self.features.iter().map(|f| format!("{}/{}", self.base_crate, f)).collect::<Vec<_>>().join(",")
+    }
+
+    /// Add the features (for check/test) and the cfg to a cargo command that already has `TOOLCHAIN`
+    pub fn apply(&self, cmd: &mut Command, enable_features: bool) {
+        if enable_features && !self.features.is_empty() {
+            cmd.arg("--features").arg(self.feature_list());
+        }
+        let rustflags = std::env::var("RUSTFLAGS").unwrap_or_default();
+        cmd.env("RUSTFLAGS", format!("{} --cfg {}", rustflags, CFG).trim_start());
+    }
+
+    /// One line for the run header
+    pub fn describe(&self) -> String {
+        let features = if self.features.is_empty() { "no features".to_string() } else { self.feature_list() };
+        format!("cargo {}, RUSTFLAGS=--cfg {}, {}", TOOLCHAIN, CFG, features)
+    }
+}
+
+/// Features of the base crate that look nightly-gated (`nightly`, `unstable`, `nightly-simd`)
+pub fn gated_features(manifest: &Path) -> Vec<String> {
+    let Some(table) = std::fs::read_to_string(manifest).ok().and_then(|t| t.parse::<toml::Table>().ok()) else {
+        return vec![];
+    };
+    let Some(features) = table.get("features").and_then(|f| f.as_table()) else {
+        return vec![];
+    };
+    features.keys().filter(|name| name.contains("nightly") || name.contains("unstable")).cloned().collect()
+}
+
+/// Whether `cargo +nightly` works here
+pub fn toolchain_installed() -> bool {
+    Command::new("cargo").args(["+nightly", "--version"]).output().is_ok_and(|o| o.status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gated_features() {
+        let tmp = tempfile::tempdir().unwrap();
+        let manifest = tmp.path().join("Cargo.toml");
+        std::fs::write(
+            &manifest,
+            "[package]\nname = \"rgb\"\n\n[features]\ndefault = [\"std\"]\nstd = []\nnightly = []\nunstable-simd = []\n",
+        )
+        .unwrap();
+        assert_eq!(gated_features(&manifest), vec!["nightly", "unstable-simd"]);
+    }
+
+    #[test]
+    fn test_apply() {
+        let gates = NightlyGates {
+            base_crate: "rgb".to_string(),
+            features: vec!["nightly".to_string(), "unstable".to_string()],
+        };
+        let mut cmd = Command::new("cargo");
+        gates.apply(&mut cmd, true);
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        assert_eq!(args, vec!["--features", "rgb/nightly,rgb/unstable"]);
+        assert_eq!(gates.describe(), "cargo +nightly, RUSTFLAGS=--cfg docsrs, rgb/nightly,rgb/unstable");
+    }
+}
@@ -0,0 +1,256 @@
+/// Crash-safe mutation of staged dependents' Cargo.toml
+///
+/// Overrides reach cargo as `--config patch.crates-io...` values (`compile::patch_config`),
+/// so baseline and patch-mode rows never touch a dependent's files. What can't be said in
+/// cargo config still edits the manifest: a forced version (cargo ignores a `[patch]` the
+/// dependency's requirement rejects, so the requirement itself is rewritten), the staging
+/// registry's forced spec, `--force-resolver-2` and `--dependent-patches merge|strip`.
+/// Every such change goes through this module:
+///
+/// 1. [`mutate`] snapshots the pristine manifest to `Cargo.toml.original.txt` the first
+///    time it changes a crate, then parses, edits and writes the manifest back.
+/// 2. [`restore_cargo_toml`] puts the snapshot back before and after every test.
+///
+/// All writes (snapshot, restore, edit) go to a temp file in the same directory and are
+/// renamed into place, so an interrupted run leaves either the old or the new file, never
+/// a truncated one. [`recover_staging`] runs before a matrix starts and puts back any
+/// manifest a crashed run left modified.
+use crate::audit::AuditLog;
+use crate::error::CopterError;
+use crate::paths;
+use log::debug;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Pristine manifest snapshot kept next to each staged Cargo.toml
+pub const BACKUP_NAME: &str = "Cargo.toml.original.txt";
+
+/// In-flight write; only survives a crash between write and rename
+pub const TEMP_NAME: &str = "Cargo.toml.copter-tmp";
+
+/// How to apply a dependency override
+#[derive(Debug, Clone, Copy)]
+pub(super) enum DependencyOverrideMode {
+    /// Replace dependency spec directly - bypasses semver requirements
+    Force,
+}
+
+/// Write `content` to `path` via a sibling temp file and rename
+pub fn write_atomic(path: &Path, content: &str) -> Result<(), CopterError> {
+    let temp = path.with_file_name(TEMP_NAME);
+    fs::write(&temp, content)
+        .map_err(|e| CopterError::Patching(format!("Failed to write {}: {}", temp.display(), e)))?;
+    fs::rename(&temp, path).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        CopterError::Patching(format!("Failed to replace {}: {}", path.display(), e))
+    })
+}
+
+/// Restore Cargo.toml from the original backup before testing
+/// This prevents contamination between test runs in the cached staging directory
+///
+/// CRITICAL: This is idempotent and Ctrl+C safe. If a backup exists from a previous
+/// (possibly interrupted) run, we restore from it rather than overwriting it. A crate
+/// that was never changed has no backup and is left alone.
+pub fn restore_cargo_toml(staging_path: &Path, audit: &AuditLog) -> Result<(), CopterError> {
+    let cargo_toml = staging_path.join("Cargo.toml");
+    let original = staging_path.join(BACKUP_NAME);
+    if !original.exists() {
+        return Ok(());
+    }
+
+    // Restore from existing original (might be from interrupted run)
+    let pristine = fs::read_to_string(&original)
+        .map_err(|e| CopterError::Patching(format!("Failed to restore Cargo.toml from original: {}", e)))?;
+    let current = fs::read_to_string(&cargo_toml).unwrap_or_default();
+    if current != pristine {
+        write_atomic(&cargo_toml, &pristine)?;
+        audit.record(&cargo_toml, "restore from backup", &current, &pristine);
+        debug!("Restored Cargo.toml from existing original backup in {:?}", staging_path);
+    }
+    Ok(())
+}
+
+/// Edit a staged crate's Cargo.toml in place
+///
+/// Snapshots the pristine manifest first if that hasn't happened yet, records the
+/// change in `audit`, and writes atomically.
+pub fn mutate<F>(crate_path: &Path, audit: &AuditLog, reason: &str, edit: F) -> Result<(), CopterError>
+where
+    F: FnOnce(&mut toml_edit::DocumentMut) -> Result<(), String>,
+{
+    let cargo_toml_path = crate_path.join("Cargo.toml");
+    let content = fs::read_to_string(&cargo_toml_path)
+        .map_err(|e| CopterError::Patching(format!("Failed to read Cargo.toml: {}", e)))?;
+    // CRITICAL: Never overwrite an existing .original - it might be from an interrupted run
+    let original = crate_path.join(BACKUP_NAME);
+    if !original.exists() {
+        write_atomic(&original, &content)?;
+        debug!("Saved original Cargo.toml to {:?}", original);
+    }
+    let mut doc: toml_edit::DocumentMut =
+        content.parse().map_err(|e| CopterError::Patching(format!("Failed to parse Cargo.toml: {}", e)))?;
+    edit(&mut doc).map_err(CopterError::Patching)?;
+
+    let updated = doc.to_string();
+    audit.record(&cargo_toml_path, reason, &content, &updated);
+    write_atomic(&cargo_toml_path, &updated)
+}
+
+/// Put back manifests that an interrupted run left modified
+///
+/// Scans `<staging>/<name>-<version>/` dirs, drops half-written temp files and
+/// restores any Cargo.toml (or `.cargo` config, see [`super::dependent_patches`]) that
+/// differs from its pristine backup. Returns the number of crates restored.
+pub fn recover_staging(staging_dir: &Path, audit: &AuditLog) -> Result<usize, CopterError> {
+    let Ok(entries) = fs::read_dir(staging_dir) else {
+        return Ok(0);
+    };
+    let mut restored = 0;
+    for dir in entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()) {
+        let _ = fs::remove_file(dir.join(TEMP_NAME));
+        let backup = dir.join(BACKUP_NAME);
+        let manifest_changed = backup.exists() && fs::read(dir.join("Cargo.toml")).ok() != fs::read(&backup).ok();
+        if manifest_changed {
+            restore_cargo_toml(&dir, audit)?;
+        }
+        if super::dependent_patches::restore_configs(&dir, audit)? || manifest_changed {
+            restored += 1;
+        }
+    }
+    Ok(restored)
+}
+
+/// Diff of a staged crate's Cargo.toml against its pristine backup, if it has been changed
+pub fn manifest_diff(crate_path: &Path) -> Option<String> {
+    let pristine = fs::read_to_string(crate_path.join(BACKUP_NAME)).ok()?;
+    let current = fs::read_to_string(crate_path.join("Cargo.toml")).ok()?;
+    (pristine != current).then(|| crate::audit::unified_diff("Cargo.toml", &pristine, &current))
+}
+
+/// Resolve a possibly relative override path against the current directory
+pub(super) fn absolute(override_path: &Path) -> Result<PathBuf, CopterError> {
+    if override_path.is_absolute() {
+        Ok(override_path.to_path_buf())
+    } else {
+        Ok(env::current_dir()
+            .map_err(|e| CopterError::Internal(format!("Failed to get current dir: {}", e)))?
+            .join(override_path))
+    }
+}
+
+/// Apply a dependency override to Cargo.toml - Force mode only
+pub(super) fn apply_dependency_override(
+    crate_path: &Path,
+    dep_name: &str,
+    override_path: &Path,
+    mode: DependencyOverrideMode,
+    audit: &AuditLog,
+) -> Result<(), CopterError> {
+    let override_path = absolute(override_path)?;
+
+    mutate(crate_path, audit, &format!("force override of {}", dep_name), |doc| {
+        match mode {
+            DependencyOverrideMode::Force => {
+                // Update dependency in all sections (force mode - replaces the spec entirely)
+                let mut new_dep = toml_edit::InlineTable::new();
+                new_dep.insert("path", paths::to_manifest_string(&override_path).into());
+                replace_dependency(doc, dep_name, new_dep);
+                debug!("Force-replaced {} dependency spec with path: {}", dep_name, override_path.display());
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Force mode against a staging registry: the dependency becomes `{ version = "=V", registry = ... }`
+pub(super) fn apply_registry_override(
+    crate_path: &Path,
+    dep_name: &str,
+    registry: &crate::stage_publish::StagingRegistry,
+    audit: &AuditLog,
+) -> Result<(), CopterError> {
+    mutate(crate_path, audit, &format!("force override of {} from {}", dep_name, registry.index), |doc| {
+        let mut new_dep = toml_edit::InlineTable::new();
+        new_dep.insert("version", format!("={}", registry.version).into());
+        new_dep.insert("registry", crate::stage_publish::REGISTRY_NAME.into());
+        replace_dependency(doc, dep_name, new_dep);
+        debug!("Force-replaced {} dependency spec with {} from the staging registry", dep_name, registry.version);
+        Ok(())
+    })
+}
+
+/// Replace `dep_name` in every dependency section with `new_dep`, keeping its
+/// optional/default-features/features/package fields
+fn replace_dependency(doc: &mut toml_edit::DocumentMut, dep_name: &str, new_dep: toml_edit::InlineTable) {
+    let sections = vec!["dependencies", "dev-dependencies", "build-dependencies"];
+
+    for section in sections {
+        if let Some(deps) = doc.get_mut(section).and_then(|s| s.as_table_mut())
+            && let Some(dep) = deps.get_mut(dep_name)
+        {
+            debug!("Force-replacing {} in [{}] with {}", dep_name, section, new_dep);
+            let mut new_dep = new_dep.clone();
+
+            // Copy fields from original dependency if it's a table
+            if let Some(old_table) = dep.as_inline_table() {
+                // Preserve important fields
+                for key in ["optional", "default-features", "features", "package"] {
+                    if let Some(value) = old_table.get(key) {
+                        new_dep.insert(key, value.clone());
+                        debug!("Preserving field '{}' = {:?}", key, value);
+                    }
+                }
+            } else if let Some(old_table) = dep.as_table_like() {
+                // Handle table-like dependencies
+                for key in ["optional", "default-features", "features", "package"] {
+                    if let Some(value) = old_table.get(key)
+                        && let Some(v) = value.as_value()
+                    {
+                        new_dep.insert(key, v.clone());
+                        debug!("Preserving field '{}' = {:?}", key, v);
+                    }
+                }
+            }
+
+            *dep = toml_edit::Item::Value(toml_edit::Value::InlineTable(new_dep));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mutate_snapshots_and_recovery_restores() {
+        let staging = tempfile::tempdir().unwrap();
+        let crate_path = staging.path().join("dep-1.0.0");
+        fs::create_dir_all(&crate_path).unwrap();
+        let pristine = "[package]\nname = \"dep\"\n\n[dependencies]\nrgb = \"0.8\"\n";
+        fs::write(crate_path.join("Cargo.toml"), pristine).unwrap();
+        let log = staging.path().join(crate::audit::AUDIT_LOG_NAME);
+        let audit = AuditLog::new(log.clone(), None);
+
+        // Nothing is written for a crate that was never changed
+        restore_cargo_toml(&crate_path, &audit).unwrap();
+        assert!(!crate_path.join(BACKUP_NAME).exists());
+
+        // Mutating snapshots the pristine manifest first
+        apply_dependency_override(&crate_path, "rgb", Path::new("/wip"), DependencyOverrideMode::Force, &audit)
+            .unwrap();
+        assert_eq!(fs::read_to_string(crate_path.join(BACKUP_NAME)).unwrap(), pristine);
+        assert!(fs::read_to_string(crate_path.join("Cargo.toml")).unwrap().contains("/wip"));
+
+        // Simulate a crash mid-write: modified manifest plus a half-written temp file
+        fs::write(crate_path.join(TEMP_NAME), "[pack").unwrap();
+        assert_eq!(recover_staging(staging.path(), &audit).unwrap(), 1);
+        assert_eq!(fs::read_to_string(crate_path.join("Cargo.toml")).unwrap(), pristine);
+        assert!(!crate_path.join(TEMP_NAME).exists());
+        assert_eq!(recover_staging(staging.path(), &audit).unwrap(), 0);
+
+        let recorded = fs::read_to_string(&log).unwrap();
+        assert!(recorded.contains("force override of rgb") && recorded.contains("restore from backup"));
+    }
+}
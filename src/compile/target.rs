@@ -0,0 +1,327 @@
+/// Build target selection for embedded (`no_std`) and wasm-only dependents
+///
+/// Some dependents only build for a target other than the host: firmware crates with a
+/// `#[panic_handler]`, or crates whose docs.rs metadata names only `thumbv7em-none-eabihf`
+/// or `wasm32-unknown-unknown`. Built like any other crate they fail on the host and show
+/// up as "build broken" rows that say nothing about the base crate. `detect` reads the
+/// dependent's own hints, in this order, and returns a plan:
+///
+/// - `.cargo/config.toml` `[build] target` (cargo applies it by itself; tests are skipped)
+/// - `[package.metadata.docs.rs]` `default-target`, or `targets` when every entry is a
+///   bare-metal (`-none`) or wasm target → check with `--target <it>`, skip tests
+/// - an unconditional `#![no_std]` with `#![no_main]` or `#[panic_handler]` in `src/lib.rs`
+///   or `src/main.rs` → check on the host, skip tests (they'd link std's panic handler)
+///
+/// `--target <TRIPLE>` overrides all of that for every dependent. Tests still run for a
+/// foreign target when there is a runner: `--runner cross` runs check and test through
+/// `cross`, `--runner "qemu-aarch64 -L /usr/aarch64-linux-gnu"` (or `wasmtime`, ...) is
+/// passed to cargo as the target runner, and a `CARGO_TARGET_<TRIPLE>_RUNNER` set in the
+/// environment works as usual.
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// How test binaries for a foreign target get run (`--runner`)
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Runner {
+    /// Run check and test through `cross`, which brings its own toolchain and emulator
+    Cross,
+    /// Program and arguments used as cargo's target runner
+    Command(Vec<String>),
+}
+
+impl Runner {
+    /// `cross`, or a command line such as `qemu-aarch64 -L /usr/aarch64-linux-gnu`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let words: Vec<String> = text.split_whitespace().map(str::to_string).collect();
+        match words.as_slice() {
+            [] => Err("--runner needs a command".to_string()),
+            [cross] if cross == "cross" => Ok(Runner::Cross),
+            _ => Ok(Runner::Command(words)),
+        }
+    }
+
+    /// The `--runner` text it was parsed from
+    pub fn describe(&self) -> String {
+        match self {
+            Runner::Cross => "cross".to_string(),
+            Runner::Command(words) => words.join(" "),
+        }
+    }
+}
+
+/// `--target` and `--runner` for the whole run, the toolchain of a `--toolchains` pass and
+/// each cargo step's share of the cores
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CrossSettings {
+    /// Build every dependent for this triple instead of detecting per dependent
+    pub target: Option<String>,
+    pub runner: Option<Runner>,
+    /// rustup toolchain every cargo step runs with (`cargo +1.70`); None: the default one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<String>,
+    /// `--docker-steps`: every cargo step runs in a throwaway container
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<crate::docker::StepSandbox>,
+    /// `CARGO_BUILD_JOBS` for every cargo step, when parallel workers share the machine
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_jobs: Option<usize>,
+}
+
+/// How to build and test one dependent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetPlan {
+    /// `--target` to pass to cargo (`None`: the host, or whatever the dependent's cargo config says)
+    pub target: Option<String>,
+    /// Whether `cargo test` can run on this machine
+    pub run_tests: bool,
+    /// Why the plan differs from a normal host build, for the console note
+    pub reason: Option<String>,
+    /// Set for foreign targets when `--runner` was given
+    pub runner: Option<Runner>,
+    /// rustup toolchain for every cargo step (`--toolchains`)
+    pub toolchain: Option<String>,
+    /// Container every cargo step runs in (`--docker-steps`)
+    pub sandbox: Option<crate::docker::StepSandbox>,
+    /// `CARGO_BUILD_JOBS` for every cargo step
+    pub build_jobs: Option<usize>,
+    /// Where cargo takes crates.io packages from (`--index-url`, `--offline`)
+    pub registry: crate::registry::Registry,
+}
+
+impl Default for TargetPlan {
+    fn default() -> Self {
+        Self::host()
+    }
+}
+
+impl TargetPlan {
+    pub fn host() -> Self {
+        Self {
+            target: None,
+            run_tests: true,
+            reason: None,
+            runner: None,
+            toolchain: None,
+            sandbox: None,
+            build_jobs: None,
+            registry: Default::default(),
+        }
+    }
+
+    /// Whether check and test go through `cross` instead of `cargo`
+    pub fn uses_cross(&self) -> bool {
+        self.runner == Some(Runner::Cross)
+    }
+
+    /// `--config` value making cargo run test binaries through a `--runner` command
+    pub fn runner_config(&self) -> Option<String> {
+        let Some(Runner::Command(words)) = &self.runner else {
+            return None;
+        };
+        let words: Vec<String> = words.iter().map(|w| toml::Value::String(w.clone()).to_string()).collect();
+        Some(format!("target.'cfg(all())'.runner=[{}]", words.join(", ")))
+    }
+
+    /// Whether the toolchain for this plan is available (cross brings its own)
+    pub fn is_available(&self) -> bool {
+        self.uses_cross() || self.target.as_deref().is_none_or(is_installed)
+    }
+}
+
+/// Work out the target for the dependent unpacked at `crate_path`
+pub fn detect(crate_path: &Path, cross: &CrossSettings) -> TargetPlan {
+    if let Some(target) = &cross.target {
+        let foreign = Some(target.as_str()) != host_triple();
+        let runner = cross.runner.clone().filter(|_| foreign);
+        return TargetPlan {
+            target: Some(target.clone()),
+            run_tests: !foreign || runner.is_some() || has_runner(target),
+            reason: None,
+            runner,
+            toolchain: cross.toolchain.clone(),
+            sandbox: cross.sandbox.clone(),
+            build_jobs: cross.build_jobs,
+            ..TargetPlan::host()
+        };
+    }
+
+    let manifest = read_toml(&crate_path.join("Cargo.toml"));
+    let config =
+        read_toml(&crate_path.join(".cargo/config.toml")).or_else(|| read_toml(&crate_path.join(".cargo/config")));
+    let sources: Vec<String> =
+        ["src/lib.rs", "src/main.rs"].iter().filter_map(|f| std::fs::read_to_string(crate_path.join(f)).ok()).collect();
+    let mut plan = plan(manifest.as_ref(), config.as_ref(), &sources);
+    // A runner only helps when the build is for another target, not for host-checked firmware
+    if plan.reason.is_some()
+        && plan.target.is_some()
+        && let Some(runner) = &cross.runner
+    {
+        plan.runner = Some(runner.clone());
+        plan.run_tests = true;
+    }
+    plan.toolchain = cross.toolchain.clone();
+    plan.sandbox = cross.sandbox.clone();
+    plan.build_jobs = cross.build_jobs;
+    plan
+}
+
+fn plan(manifest: Option<&toml::Table>, config: Option<&toml::Table>, sources: &[String]) -> TargetPlan {
+    let config_target = config.and_then(|c| c.get("build")?.get("target")?.as_str());
+    if let Some(target) = config_target
+        && is_cross_target(target)
+    {
+        return TargetPlan {
+            target: None,
+            run_tests: has_runner(target),
+            reason: Some(format!("builds for {} (its .cargo/config.toml)", target)),
+            runner: None,
+            toolchain: None,
+            sandbox: None,
+            build_jobs: None,
+            registry: Default::default(),
+        };
+    }
+
+    let docs_rs = manifest.and_then(|m| m.get("package")?.get("metadata")?.get("docs")?.get("rs"));
+    let default_target = docs_rs.and_then(|d| d.get("default-target")?.as_str()).filter(|t| is_cross_target(t));
+    let targets: Vec<&str> = docs_rs
+        .and_then(|d| d.get("targets")?.as_array())
+        .map(|a| a.iter().filter_map(|t| t.as_str()).collect())
+        .unwrap_or_default();
+    let only_target = (!targets.is_empty() && targets.iter().all(|t| is_cross_target(t))).then(|| targets[0]);
+    if let Some(target) = default_target.or(only_target) {
+        return TargetPlan {
+            target: Some(target.to_string()),
+            run_tests: has_runner(target),
+            reason: Some(format!("builds for {} (its docs.rs metadata)", target)),
+            runner: None,
+            toolchain: None,
+            sandbox: None,
+            build_jobs: None,
+            registry: Default::default(),
+        };
+    }
+
+    if sources.iter().any(|s| is_freestanding(s)) {
+        return TargetPlan {
+            target: None,
+            run_tests: false,
+            reason: Some("no_std with its own panic handler or entry point".to_string()),
+            runner: None,
+            toolchain: None,
+            sandbox: None,
+            build_jobs: None,
+            registry: Default::default(),
+        };
+    }
+    TargetPlan::host()
+}
+
+/// Bare-metal (`thumbv7em-none-eabihf`, `riscv32imac-unknown-none-elf`) or wasm
+fn is_cross_target(target: &str) -> bool {
+    (target.contains("-none") || target.starts_with("wasm")) && Some(target) != host_triple()
+}
+
+/// Unconditional `#![no_std]` plus `#![no_main]` or `#[panic_handler]`
+fn is_freestanding(source: &str) -> bool {
+    let lines: Vec<&str> = source.lines().map(str::trim).collect();
+    lines.contains(&"#![no_std]") && lines.iter().any(|l| *l == "#![no_main]" || l.starts_with("#[panic_handler]"))
+}
+
+/// `CARGO_TARGET_WASM32_WASIP1_RUNNER` and friends make cross tests runnable
+fn has_runner(target: &str) -> bool {
+    let var = format!("CARGO_TARGET_{}_RUNNER", target.to_uppercase().replace(['-', '.'], "_"));
+    std::env::var_os(var).is_some_and(|v| !v.is_empty())
+}
+
+/// Whether the active toolchain has the standard library for `target` installed
+pub fn is_installed(target: &str) -> bool {
+    Command::new("rustc")
+        .args(["--print", "target-libdir", "--target", target])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .is_some_and(|o| Path::new(String::from_utf8_lossy(&o.stdout).trim()).is_dir())
+}
+
+fn host_triple() -> Option<&'static str> {
+    static HOST: OnceLock<Option<String>> = OnceLock::new();
+    HOST.get_or_init(|| {
+        let output = Command::new("rustc").arg("-vV").output().ok()?;
+        String::from_utf8_lossy(&output.stdout).lines().find_map(|l| l.strip_prefix("host: ").map(str::to_string))
+    })
+    .as_deref()
+}
+
+fn read_toml(path: &Path) -> Option<toml::Table> {
+    std::fs::read_to_string(path).ok()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(text: &str) -> toml::Table {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_docs_rs_target() {
+        let manifest =
+            table("[package.metadata.docs.rs]\ntargets = [\"thumbv7em-none-eabihf\", \"thumbv6m-none-eabi\"]");
+        let thumb = plan(Some(&manifest), None, &[]);
+        assert_eq!(thumb.target.as_deref(), Some("thumbv7em-none-eabihf"));
+        assert!(!thumb.run_tests);
+
+        // Documented for the host as well: build it normally
+        let manifest =
+            table("[package.metadata.docs.rs]\ntargets = [\"x86_64-unknown-linux-gnu\", \"wasm32-unknown-unknown\"]");
+        assert_eq!(plan(Some(&manifest), None, &[]), TargetPlan::host());
+
+        let manifest = table("[package.metadata.docs.rs]\ndefault-target = \"wasm32-unknown-unknown\"");
+        assert_eq!(plan(Some(&manifest), None, &[]).target.as_deref(), Some("wasm32-unknown-unknown"));
+    }
+
+    #[test]
+    fn test_cargo_config_target() {
+        let config = table("[build]\ntarget = \"riscv32imac-unknown-none-elf\"");
+        let riscv = plan(None, Some(&config), &[]);
+        assert_eq!(riscv.target, None);
+        assert!(!riscv.run_tests);
+    }
+
+    #[test]
+    fn test_freestanding_no_std() {
+        let firmware = "#![no_std]\n#![no_main]\n\n#[panic_handler]\nfn panic(_: &PanicInfo) -> ! { loop {} }\n";
+        assert!(!plan(None, None, &[firmware.to_string()]).run_tests);
+
+        let optional_std = "#![cfg_attr(not(feature = \"std\"), no_std)]\n";
+        assert_eq!(plan(None, None, &[optional_std.to_string()]), TargetPlan::host());
+        assert_eq!(plan(None, None, &["#![no_std]\npub fn f() {}\n".to_string()]), TargetPlan::host());
+    }
+
+    #[test]
+    fn test_runner() {
+        assert_eq!(Runner::parse("cross"), Ok(Runner::Cross));
+        assert!(Runner::parse(" ").is_err());
+
+        let cross = CrossSettings {
+            target: Some("aarch64-unknown-linux-gnu".to_string()),
+            runner: Some(Runner::parse("qemu-aarch64 -L /usr/aarch64-linux-gnu").unwrap()),
+            toolchain: None,
+            sandbox: None,
+            build_jobs: Some(4),
+        };
+        let plan = detect(Path::new("/nonexistent"), &cross);
+        assert_eq!(plan.target.as_deref(), Some("aarch64-unknown-linux-gnu"));
+        assert_eq!(plan.build_jobs, Some(4));
+        if host_triple() != Some("aarch64-unknown-linux-gnu") {
+            assert!(plan.run_tests);
+            assert_eq!(
+                plan.runner_config().unwrap(),
+                "target.'cfg(all())'.runner=[\"qemu-aarch64\", \"-L\", \"/usr/aarch64-linux-gnu\"]"
+            );
+        }
+    }
+}
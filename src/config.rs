@@ -8,7 +8,12 @@
 use crate::api;
 use crate::cli::CliArgs;
 use crate::compile;
+use crate::disk;
+use crate::error::CopterError;
+use crate::git;
 use crate::manifest;
+use crate::paths;
+use crate::registry::Registry;
 use crate::types::*;
 use crate::version;
 use log::debug;
@@ -19,27 +24,27 @@ use std::path::PathBuf;
 ///
 /// This resolves all configuration upfront, ensuring the runner receives
 /// a fully validated, immutable test specification.
-pub fn build_test_matrix(args: &CliArgs) -> Result<TestMatrix, String> {
+pub fn build_test_matrix(args: &CliArgs, registry: &Registry) -> Result<TestMatrix, CopterError> {
     debug!("Building test matrix from CLI args");
 
     // Step 1: Determine the base crate name and get version info
-    let (base_crate_name, base_crate_version, local_manifest) = resolve_base_crate_info(args)?;
+    let (base_crate_name, base_crate_version, local_manifest) = resolve_base_crate_info(args, registry)?;
 
     debug!("Base crate: {} version {}", base_crate_name, base_crate_version);
 
     // Step 2: Build list of base crate versions to test
-    let base_versions = resolve_base_versions(args, &base_crate_name, &base_crate_version, &local_manifest)?;
+    let base_versions = resolve_base_versions(args, registry, &base_crate_name, &base_crate_version, &local_manifest)?;
 
     debug!("Resolved {} base versions to test", base_versions.len());
 
     // Step 3: Build list of dependents to test
-    let mut dependents = resolve_dependents(args, &base_crate_name)?;
+    let (mut dependents, population) = resolve_dependents(args, registry, &base_crate_name)?;
 
     debug!("Resolved {} dependents to test", dependents.len());
 
     // Step 4: Expand with additional versions if --top-versions is specified
     if let Some(budget) = args.top_versions {
-        let extra = resolve_top_versions(&dependents, budget)?;
+        let extra = resolve_top_versions(registry, &dependents, budget)?;
         if !extra.is_empty() {
             debug!("Adding {} additional (dependent, version) pairs from --top-versions", extra.len());
             dependents.extend(extra);
@@ -59,22 +64,85 @@ pub fn build_test_matrix(args: &CliArgs) -> Result<TestMatrix, String> {
         );
     }
 
+    let nightly_gates = nightly_gates(args, &base_crate_name, local_manifest.as_deref())?;
+    if let Some(toolchain) = &args.pin_toolchain {
+        crate::toolchains::pin(toolchain)?;
+    }
+
     Ok(TestMatrix {
         base_crate: base_crate_name,
         base_versions,
         dependents,
         staging_dir: args.get_staging_dir(),
-        skip_check: args.should_skip_check(),
-        skip_test: args.should_skip_test(),
+        steps: args.steps(),
         error_lines: args.error_lines,
         patch_transitive: args.patch_transitive,
+        min_free_bytes: disk::gb_to_bytes(args.min_free_gb),
+        heartbeat: compile::heartbeat::Heartbeat::from_minutes(args.stall_minutes, args.stall_sample)
+            .with_timeouts(args.timeout_per_step, args.timeout_per_dependent)
+            .with_run_budget(args.time_budget),
+        nightly_gates,
+        cross: compile::target::CrossSettings {
+            target: args.target.clone(),
+            runner: args
+                .runner
+                .as_deref()
+                .map(compile::target::Runner::parse)
+                .transpose()
+                .map_err(CopterError::Config)?,
+            toolchain: args.pin_toolchain.clone(),
+            // `--docker-steps` mounts the resolved local versions; main sets it
+            sandbox: None,
+            // Parallel workers' share of the cores; the runner sets it
+            build_jobs: None,
+        },
+        dependent_patches: args.dependent_patches,
+        simulate_update: args.simulate_update,
+        force_resolver: args.force_resolver_2,
+        toolchains: args.toolchains.clone(),
+        // Main fills this from the quarantine it applied to `args.exclude`
+        quarantined: vec![],
+        staging_registry: None,
+        dependent_settings: args.dependent_settings.clone(),
+        population,
+        baseline_cache: !args.no_baseline_cache,
+        staging_copy: args.staging_copy,
+        source_cache: crate::source_cache::Policy::new(args.refresh, args.cache_ttl),
+        registry: registry.clone(),
+        // Main points it at copter-report/manifest-audit.log
+        audit: Default::default(),
     })
 }
 
+/// `--nightly-gates` settings, with the gated features read from the local manifest when none are given
+fn nightly_gates(
+    args: &CliArgs,
+    base_crate_name: &str,
+    local_manifest: Option<&std::path::Path>,
+) -> Result<Option<compile::nightly::NightlyGates>, CopterError> {
+    let Some(features) = &args.nightly_gates else {
+        return Ok(None);
+    };
+    if !compile::nightly::toolchain_installed() {
+        return Err(CopterError::Config(
+            "--nightly-gates needs a nightly toolchain (rustup toolchain install nightly)".to_string(),
+        ));
+    }
+    let features = if features.is_empty() {
+        local_manifest.map(compile::nightly::gated_features).unwrap_or_default()
+    } else {
+        features.iter().flat_map(|f| f.split([',', ' '])).filter(|f| !f.is_empty()).map(str::to_string).collect()
+    };
+    Ok(Some(compile::nightly::NightlyGates { base_crate: base_crate_name.to_string(), features }))
+}
+
 /// Resolve base crate name, version, and optional local manifest path
 ///
 /// Returns: (crate_name, version, local_manifest_path)
-fn resolve_base_crate_info(args: &CliArgs) -> Result<(String, String, Option<PathBuf>), String> {
+fn resolve_base_crate_info(
+    args: &CliArgs,
+    registry: &Registry,
+) -> Result<(String, String, Option<PathBuf>), CopterError> {
     if let Some(ref crate_name) = args.crate_name {
         // --crate specified: use that name
         debug!("Using crate name from --crate: {}", crate_name);
@@ -82,20 +150,23 @@ fn resolve_base_crate_info(args: &CliArgs) -> Result<(String, String, Option<Pat
         // Check if --path is also specified (for "this" version)
         if let Some(ref path) = args.path {
             let manifest = if path.is_dir() { path.join("Cargo.toml") } else { path.clone() };
+            // In a workspace, --crate names the member unless --package does
+            let member = args.package.as_deref().unwrap_or(crate_name);
+            let manifest = manifest::select_package(&manifest, Some(member)).map_err(CopterError::Config)?;
             debug!("Using --path for 'this' version: {:?}", manifest);
 
             // Extract version from the manifest
-            let (manifest_crate_name, manifest_version) =
-                manifest::get_crate_info(&manifest).map_err(|e| format!("Failed to read manifest: {}", e))?;
+            let (manifest_crate_name, manifest_version) = manifest::get_crate_info(&manifest)
+                .map_err(|e| CopterError::Config(format!("Failed to read manifest: {}", e)))?;
 
             // Verify crate names match
             if manifest_crate_name != *crate_name {
-                return Err(format!(
+                return Err(CopterError::Config(format!(
                     "Crate name mismatch: --crate specifies '{}' but {} contains '{}'",
                     crate_name,
                     manifest.display(),
                     manifest_crate_name
-                ));
+                )));
             }
 
             Ok((crate_name.clone(), manifest_version, Some(manifest)))
@@ -103,36 +174,52 @@ fn resolve_base_crate_info(args: &CliArgs) -> Result<(String, String, Option<Pat
             // No --path, fetch latest version from crates.io
             debug!("No --path specified, fetching latest version from crates.io");
             let latest_version =
-                version::resolve_latest_version(crate_name, false).unwrap_or_else(|_| "0.0.0".to_string());
+                version::resolve_latest_version(registry, crate_name, false).unwrap_or_else(|_| "0.0.0".to_string());
             Ok((crate_name.clone(), latest_version, None))
         }
     } else {
-        // No --crate, use --path or ./Cargo.toml
-        let manifest = if let Some(ref path) = args.path {
-            if path.is_dir() { path.join("Cargo.toml") } else { path.clone() }
-        } else {
-            let env_manifest = env::var("COPTER_MANIFEST");
-            PathBuf::from(env_manifest.unwrap_or_else(|_| "./Cargo.toml".to_string()))
-        };
-
+        // No --crate, use --path or ./Cargo.toml (the --package member of a workspace)
+        let manifest = manifest::select_package(&local_manifest_path(args), args.package.as_deref())
+            .map_err(CopterError::Config)?;
         debug!("Using manifest {:?}", manifest);
 
-        let (crate_name, version) =
-            manifest::get_crate_info(&manifest).map_err(|e| format!("Failed to read manifest: {}", e))?;
+        let (crate_name, version) = manifest::get_crate_info(&manifest)
+            .map_err(|e| CopterError::Config(format!("Failed to read manifest: {}", e)))?;
 
         Ok((crate_name, version, Some(manifest)))
     }
 }
 
+/// `--path`, `$COPTER_MANIFEST` or `./Cargo.toml`
+fn local_manifest_path(args: &CliArgs) -> PathBuf {
+    if let Some(ref path) = args.path {
+        if path.is_dir() { path.join("Cargo.toml") } else { path.clone() }
+    } else {
+        PathBuf::from(env::var("COPTER_MANIFEST").unwrap_or_else(|_| "./Cargo.toml".to_string()))
+    }
+}
+
+/// Name of the base crate, without resolving any versions
+pub fn base_crate_name(args: &CliArgs) -> Option<String> {
+    match &args.crate_name {
+        Some(name) => Some(name.clone()),
+        None => {
+            let manifest = manifest::select_package(&local_manifest_path(args), args.package.as_deref()).ok()?;
+            manifest::get_crate_info(&manifest).ok().map(|(name, _)| name)
+        }
+    }
+}
+
 /// Resolve all base crate versions to test
 ///
 /// Returns a list of VersionSpec with the baseline first
 fn resolve_base_versions(
     args: &CliArgs,
+    registry: &Registry,
     crate_name: &str,
     local_version: &str,
     local_manifest: &Option<PathBuf>,
-) -> Result<Vec<VersionSpec>, String> {
+) -> Result<Vec<VersionSpec>, CopterError> {
     let mut versions = Vec::new();
 
     // Determine if we're in multi-version mode
@@ -141,8 +228,9 @@ fn resolve_base_versions(
     if use_multi_version {
         // Add specified versions from --test-versions
         for ver_str in &args.test_versions {
-            if let Some(version_source) = version::resolve_version_keyword(ver_str, crate_name, local_manifest.as_ref())
-                .map_err(|e| format!("Failed to resolve version '{}': {}", ver_str, e))?
+            if let Some(version_source) =
+                version::resolve_version_keyword(registry, ver_str, crate_name, local_manifest.as_ref())
+                    .map_err(|e| CopterError::Config(format!("Failed to resolve version '{}': {}", ver_str, e)))?
             {
                 let version_spec = version_source_to_spec(version_source, crate_name, false)?;
                 versions.push(version_spec);
@@ -151,8 +239,10 @@ fn resolve_base_versions(
 
         // Add versions from --force-versions and mark them as forced
         for ver_str in &args.force_versions {
-            if let Some(version_source) = version::resolve_version_keyword(ver_str, crate_name, local_manifest.as_ref())
-                .map_err(|e| format!("Failed to resolve forced version '{}': {}", ver_str, e))?
+            if let Some(version_source) =
+                version::resolve_version_keyword(registry, ver_str, crate_name, local_manifest.as_ref()).map_err(
+                    |e| CopterError::Config(format!("Failed to resolve forced version '{}': {}", ver_str, e)),
+                )?
             {
                 let mut version_spec = version_source_to_spec(version_source, crate_name, true)?;
                 version_spec.override_mode = OverrideMode::Force;
@@ -210,7 +300,7 @@ fn resolve_base_versions(
             }
         } else {
             // No local version (only --crate), add "latest" as final version if not already present
-            match version::resolve_latest_version(crate_name, false) {
+            match version::resolve_latest_version(registry, crate_name, false) {
                 Ok(ver) => {
                     let already_present =
                         versions.iter().any(|v| matches!(&v.crate_ref.version, Version::Semver(s) if s == &ver));
@@ -229,7 +319,7 @@ fn resolve_base_versions(
         // Default behavior: baseline + WIP
         if let Some(manifest_path) = local_manifest {
             // Add baseline first (latest from registry)
-            if let Ok(latest_ver) = version::resolve_latest_version(crate_name, false) {
+            if let Ok(latest_ver) = version::resolve_latest_version(registry, crate_name, false) {
                 versions.push(VersionSpec {
                     crate_ref: VersionedCrate::from_registry(crate_name, latest_ver),
                     override_mode: OverrideMode::None,
@@ -245,7 +335,7 @@ fn resolve_base_versions(
             });
         } else {
             // No local version, use latest as baseline
-            if let Ok(ver) = version::resolve_latest_version(crate_name, false) {
+            if let Ok(ver) = version::resolve_latest_version(registry, crate_name, false) {
                 versions.push(VersionSpec {
                     crate_ref: VersionedCrate::from_registry(crate_name, ver),
                     override_mode: OverrideMode::None,
@@ -256,7 +346,7 @@ fn resolve_base_versions(
     }
 
     if versions.is_empty() {
-        return Err("No versions to test".to_string());
+        return Err(CopterError::Config("No versions to test".to_string()));
     }
 
     // Ensure exactly one baseline is marked with OverrideMode::None
@@ -277,7 +367,7 @@ fn version_source_to_spec(
     source: compile::VersionSource,
     crate_name: &str,
     forced: bool,
-) -> Result<VersionSpec, String> {
+) -> Result<VersionSpec, CopterError> {
     let override_mode = if forced { OverrideMode::Force } else { OverrideMode::Patch };
 
     match source {
@@ -289,8 +379,8 @@ fn version_source_to_spec(
         compile::VersionSource::Local { path, .. } => {
             // Extract version from Cargo.toml
             let manifest = if path.ends_with("Cargo.toml") { path } else { path.join("Cargo.toml") };
-            let (_, local_version) =
-                manifest::get_crate_info(&manifest).map_err(|e| format!("Failed to read local manifest: {}", e))?;
+            let (_, local_version) = manifest::get_crate_info(&manifest)
+                .map_err(|e| CopterError::Config(format!("Failed to read local manifest: {}", e)))?;
 
             Ok(VersionSpec {
                 crate_ref: VersionedCrate::from_local(crate_name, local_version, manifest),
@@ -303,7 +393,7 @@ fn version_source_to_spec(
 
 /// Resolve all dependents to test
 /// Expand --dependent-glob and --dependent-dir into concrete paths
-fn expand_dependent_discovery(args: &CliArgs, base_crate_name: &str) -> Result<Vec<PathBuf>, String> {
+fn expand_dependent_discovery(args: &CliArgs, base_crate_name: &str) -> Result<Vec<PathBuf>, CopterError> {
     let mut discovered = Vec::new();
 
     // Expand --dependent-glob patterns
@@ -319,10 +409,11 @@ fn expand_dependent_discovery(args: &CliArgs, base_crate_name: &str) -> Result<V
             pattern.clone()
         };
 
-        let entries = glob::glob(&expanded).map_err(|e| format!("Invalid glob pattern '{}': {}", pattern, e))?;
+        let entries = glob::glob(&expanded)
+            .map_err(|e| CopterError::Config(format!("Invalid glob pattern '{}': {}", pattern, e)))?;
 
         for entry in entries {
-            let path = entry.map_err(|e| format!("Glob error: {}", e))?;
+            let path = entry.map_err(|e| CopterError::Config(format!("Glob error: {}", e)))?;
             if path.file_name().map(|n| n == "Cargo.toml").unwrap_or(false) {
                 match manifest::depends_on(&path, base_crate_name) {
                     Ok(true) => {
@@ -344,14 +435,14 @@ fn expand_dependent_discovery(args: &CliArgs, base_crate_name: &str) -> Result<V
     // Expand --dependent-dir (search one level deep for Cargo.toml)
     for dir in &args.dependent_dir {
         if !dir.is_dir() {
-            return Err(format!("--dependent-dir path is not a directory: {}", dir.display()));
+            return Err(CopterError::Config(format!("--dependent-dir path is not a directory: {}", dir.display())));
         }
 
-        let entries =
-            std::fs::read_dir(dir).map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| CopterError::Config(format!("Failed to read directory {}: {}", dir.display(), e)))?;
 
         for entry in entries {
-            let entry = entry.map_err(|e| format!("Directory read error: {}", e))?;
+            let entry = entry.map_err(|e| CopterError::Config(format!("Directory read error: {}", e)))?;
             let child = entry.path();
             if child.is_dir() {
                 let manifest = child.join("Cargo.toml");
@@ -376,7 +467,7 @@ fn expand_dependent_discovery(args: &CliArgs, base_crate_name: &str) -> Result<V
     // Deduplicate by canonical path
     let mut seen = std::collections::HashSet::new();
     discovered.retain(|p| {
-        let canonical = p.canonicalize().unwrap_or_else(|_| p.clone());
+        let canonical = paths::canonicalize(p);
         seen.insert(canonical)
     });
 
@@ -387,8 +478,20 @@ fn expand_dependent_discovery(args: &CliArgs, base_crate_name: &str) -> Result<V
     Ok(discovered)
 }
 
-fn resolve_dependents(args: &CliArgs, base_crate_name: &str) -> Result<Vec<VersionSpec>, String> {
+/// Not `--exclude`d, and in `--only-dependents` when that is given
+fn is_selected(args: &CliArgs, name: &str) -> bool {
+    !args.exclude.iter().any(|n| n == name)
+        && (args.only_dependents.is_empty() || args.only_dependents.iter().any(|n| n == name))
+}
+
+/// The dependents to test, and for `--top-dependents` the population they were taken from
+fn resolve_dependents(
+    args: &CliArgs,
+    registry: &Registry,
+    base_crate_name: &str,
+) -> Result<(Vec<VersionSpec>, Option<api::Population>), CopterError> {
     let mut dependents = Vec::new();
+    let mut population = None;
 
     // Determine which dependents to test
     // Collect local path dependents separately (they use CrateSource::Local, not Registry)
@@ -408,11 +511,19 @@ fn resolve_dependents(args: &CliArgs, base_crate_name: &str) -> Result<Vec<Versi
             } else if p.is_dir() {
                 p.join("Cargo.toml")
             } else {
-                return Err(format!("Invalid dependent path (not a directory or Cargo.toml): {}", p.display()));
+                return Err(CopterError::Config(format!(
+                    "Invalid dependent path (not a directory or Cargo.toml): {}",
+                    p.display()
+                )));
             };
 
-            let (name, version) = manifest::get_crate_info(&manifest_path)
-                .map_err(|e| format!("Failed to read dependent at {}: {}", manifest_path.display(), e))?;
+            let (name, version) = manifest::get_crate_info(&manifest_path).map_err(|e| {
+                CopterError::Config(format!("Failed to read dependent at {}: {}", manifest_path.display(), e))
+            })?;
+            if !is_selected(args, &name) {
+                debug!("Excluding local dependent {} ({})", name, manifest_path.display());
+                continue;
+            }
 
             let dir_path = if manifest_path.ends_with("Cargo.toml") {
                 manifest_path.parent().unwrap().to_path_buf()
@@ -428,16 +539,50 @@ fn resolve_dependents(args: &CliArgs, base_crate_name: &str) -> Result<Vec<Versi
         }
         // Return empty rev_deps since we handled these directly
         vec![]
-    } else if !args.dependents.is_empty() {
+    } else if !args.dependents.is_empty() || !args.dependent_git.is_empty() || !args.github_org.is_empty() {
         // Explicit crate names from crates.io (parse name:version syntax)
-        args.dependents.iter().map(|spec| manifest::parse_dependent_spec(spec)).collect()
+        args.dependents
+            .iter()
+            .map(|spec| manifest::parse_dependent_spec(spec))
+            .filter(|(name, _)| is_selected(args, name))
+            .collect()
     } else {
-        // Top N by downloads (no version spec)
-        let api_deps = api::get_top_dependents(base_crate_name, args.top_dependents)
-            .map_err(|e| format!("Failed to fetch top dependents: {}", e))?;
-        api_deps.into_iter().map(|d| (d.name, None)).collect()
+        // Top N by downloads (no version spec); fetch extra so excluded crates don't shrink the list
+        let sample = crate::dependents_cache::top_dependents(
+            registry,
+            base_crate_name,
+            args.top_dependents + args.exclude.len(),
+            args.dependents_sort,
+        )
+        .map_err(|e| CopterError::Network(format!("Failed to fetch top dependents: {}", e)))?;
+        let top: Vec<(String, Option<String>)> = sample
+            .dependents
+            .into_iter()
+            .filter(|d| !args.exclude.contains(&d.name))
+            .take(args.top_dependents)
+            .filter(|d| is_selected(args, &d.name))
+            .map(|d| (d.name, None))
+            .collect();
+        population = Some(api::Population { total: sample.total, sampled: top.len(), sort: args.dependents_sort });
+        top
     };
 
+    // Git checkouts are local sources too
+    for spec in &args.dependent_git {
+        local_dependents.extend(resolve_git_dependents(args, spec, base_crate_name)?);
+    }
+    for org in &args.github_org {
+        let repos = api::github::discover(registry, org, base_crate_name)
+            .map_err(|e| CopterError::Network(format!("--github-org {}: {}", org, e)))?;
+        eprintln!("--github-org {}: {} repositories depend on {}", org, repos.len(), base_crate_name);
+        for repo in repos {
+            match resolve_git_dependents(args, &repo.clone_url, base_crate_name) {
+                Ok(found) => local_dependents.extend(found),
+                Err(e) => eprintln!("Warning: skipping {}: {}", repo.full_name, e),
+            }
+        }
+    }
+
     // Add local dependents first (from --dependent-paths)
     for mut local_dep in local_dependents {
         local_dep.is_baseline = dependents.is_empty(); // First is baseline
@@ -466,9 +611,61 @@ fn resolve_dependents(args: &CliArgs, base_crate_name: &str) -> Result<Vec<Versi
     }
 
     if dependents.is_empty() {
-        return Err("No dependents to test".to_string());
+        return Err(CopterError::Config("No dependents to test".to_string()));
     }
 
+    Ok((dependents, population))
+}
+
+/// Clone a `--dependent-git URL[#REV]` into the staging directory and return every package
+/// in it that depends on the base crate
+fn resolve_git_dependents(args: &CliArgs, spec: &str, base_crate_name: &str) -> Result<Vec<VersionSpec>, CopterError> {
+    let (url, rev) = match spec.split_once('#') {
+        Some((url, rev)) => (url, Some(rev)),
+        None => (spec, None),
+    };
+    // One checkout per URL, named after the repository
+    let mut hash = crate::history::Fnv::new();
+    hash.write(url.as_bytes());
+    let repo = url.trim_end_matches('/').trim_end_matches(".git").rsplit(['/', ':']).next().unwrap_or("repo");
+    let checkout = args.get_staging_dir().join("git").join(format!("{}-{:08x}", repo, hash.0 as u32));
+    let commit = git::checkout(url, rev, &checkout).map_err(|e| e.context(format!("--dependent-git {}", spec)))?;
+
+    let pattern = checkout.join("**").join("Cargo.toml");
+    let manifests = glob::glob(&pattern.display().to_string())
+        .map_err(|e| CopterError::Config(format!("Invalid checkout path: {}", e)))?;
+    let mut dependents = Vec::new();
+    for manifest_path in manifests.flatten() {
+        if manifest_path.components().any(|c| c.as_os_str() == "target")
+            || !manifest::depends_on(&manifest_path, base_crate_name).unwrap_or(false)
+        {
+            continue;
+        }
+        let Ok((name, version)) = manifest::get_crate_info(&manifest_path) else { continue };
+        if !is_selected(args, &name) {
+            continue;
+        }
+        debug!("git dependent {} {} at {}", name, version, manifest_path.display());
+        dependents.push(VersionSpec {
+            crate_ref: VersionedCrate {
+                name,
+                version: Version::Semver(version),
+                source: CrateSource::Git {
+                    url: url.to_string(),
+                    rev: Some(commit.clone()),
+                    path: manifest_path.parent().unwrap().to_path_buf(),
+                },
+            },
+            override_mode: OverrideMode::None,
+            is_baseline: false,
+        });
+    }
+    if dependents.is_empty() {
+        return Err(CopterError::Config(format!(
+            "--dependent-git {}: no package in the repository depends on {}",
+            spec, base_crate_name
+        )));
+    }
     Ok(dependents)
 }
 
@@ -477,7 +674,11 @@ fn resolve_dependents(args: &CliArgs, base_crate_name: &str) -> Result<Vec<Versi
 /// Each dependent already has its latest version in the list. This function
 /// fetches version download counts and allocates Q additional slots across
 /// all dependents, ranked by downloads.
-fn resolve_top_versions(existing_dependents: &[VersionSpec], budget: usize) -> Result<Vec<VersionSpec>, String> {
+fn resolve_top_versions(
+    registry: &Registry,
+    existing_dependents: &[VersionSpec],
+    budget: usize,
+) -> Result<Vec<VersionSpec>, CopterError> {
     if budget == 0 {
         return Ok(vec![]);
     }
@@ -495,7 +696,7 @@ fn resolve_top_versions(existing_dependents: &[VersionSpec], budget: usize) -> R
 
     for dep in &registry_deps {
         let name = &dep.crate_ref.name;
-        match api::get_version_downloads(name) {
+        match api::get_version_downloads(registry, name) {
             Ok(versions) => {
                 // Skip the latest version (already in the list)
                 // The latest is the first one that's not yanked and not prerelease,
@@ -1,9 +1,9 @@
 /// Tests for config module
 #[cfg(test)]
 mod tests {
-    use crate::cli::CliArgs;
+    use crate::cli::{Backend, CliArgs, OutputFormat};
     use crate::config::build_test_matrix;
-    use crate::types::{OverrideMode, Version};
+    use crate::types::OverrideMode;
 
     #[test]
     fn test_baseline_flag_is_set() {
@@ -31,9 +31,73 @@ mod tests {
             docker: false,
             patch_transitive: false,
             simple: false,
+            min_free_gb: 2,
+            command: None,
+            force: false,
+            steps: None,
+            report_template: vec![],
+            messages: None,
+            stall_minutes: 10,
+            stall_sample: false,
+            compare_with: None,
+            exclude: vec![],
+            dependent_settings: Default::default(),
+            only_dependents: vec![],
+            backend: Backend::Local,
+            queue_dir: None,
+            queue_workers: 8,
+            target: None,
+            runner: None,
+            nightly_gates: None,
+            quarantine_after: 3,
+            include_quarantined: false,
+            scrub: false,
+            scrub_env: vec![],
+            dependent_patches: Default::default(),
+            simulate_update: false,
+            aliases: vec![],
+            timestamps: false,
+            step_times: false,
+            check_first: false,
+            resume: false,
+            from: None,
+            deterministic: false,
+            label: None,
+            staging_copy: crate::staging::CopyMethod::Auto,
+            index_url: None,
+            dependents_sort: crate::api::DependentSort::AllTime,
+            fail_on_skipped: false,
+            status_style: crate::report::StatusStyle::Symbols,
+            colorscheme: crate::console_format::ColorScheme::Default,
+            progress: crate::live::Progress::Auto,
+            save_artifacts: crate::artifacts::SaveArtifacts::Stderr,
+            json_error_limit: 16384,
+            refresh: false,
+            cache_ttl: 0,
+            dependent_git: vec![],
+            github: false,
+            timeout_per_step: None,
+            timeout_per_dependent: None,
+            time_budget: None,
+            no_baseline_cache: false,
+            schedule: Default::default(),
+            parallel: 1,
+            package: None,
+            diagnostic_counts: false,
+            toolchains: vec![],
+            docker_steps: None,
+            collapse_identical: false,
+            prefetch: None,
+            offline: None,
+            force_resolver_2: false,
+            pin_toolchain: None,
+            output_format: OutputFormat::Table,
+            github_org: vec![],
+            skip_if_seen: false,
+            queue_timeout: 120,
         };
 
-        let matrix = build_test_matrix(&args).expect("Should build matrix");
+        let matrix = build_test_matrix(&args, &Default::default()).expect("Should build matrix");
 
         // Verify exactly one baseline
         let baseline_count = matrix.base_versions.iter().filter(|v| v.is_baseline).count();
@@ -73,9 +137,73 @@ mod tests {
             docker: false,
             patch_transitive: false,
             simple: false,
+            min_free_gb: 2,
+            command: None,
+            force: false,
+            steps: None,
+            report_template: vec![],
+            messages: None,
+            stall_minutes: 10,
+            stall_sample: false,
+            compare_with: None,
+            exclude: vec![],
+            dependent_settings: Default::default(),
+            only_dependents: vec![],
+            backend: Backend::Local,
+            queue_dir: None,
+            queue_workers: 8,
+            target: None,
+            runner: None,
+            nightly_gates: None,
+            quarantine_after: 3,
+            include_quarantined: false,
+            scrub: false,
+            scrub_env: vec![],
+            dependent_patches: Default::default(),
+            simulate_update: false,
+            aliases: vec![],
+            timestamps: false,
+            step_times: false,
+            check_first: false,
+            resume: false,
+            from: None,
+            deterministic: false,
+            label: None,
+            staging_copy: crate::staging::CopyMethod::Auto,
+            index_url: None,
+            dependents_sort: crate::api::DependentSort::AllTime,
+            fail_on_skipped: false,
+            status_style: crate::report::StatusStyle::Symbols,
+            colorscheme: crate::console_format::ColorScheme::Default,
+            progress: crate::live::Progress::Auto,
+            save_artifacts: crate::artifacts::SaveArtifacts::Stderr,
+            json_error_limit: 16384,
+            refresh: false,
+            cache_ttl: 0,
+            dependent_git: vec![],
+            github: false,
+            timeout_per_step: None,
+            timeout_per_dependent: None,
+            time_budget: None,
+            no_baseline_cache: false,
+            schedule: Default::default(),
+            parallel: 1,
+            package: None,
+            diagnostic_counts: false,
+            toolchains: vec![],
+            docker_steps: None,
+            collapse_identical: false,
+            prefetch: None,
+            offline: None,
+            force_resolver_2: false,
+            pin_toolchain: None,
+            output_format: OutputFormat::Table,
+            github_org: vec![],
+            skip_if_seen: false,
+            queue_timeout: 120,
         };
 
-        let matrix = build_test_matrix(&args).expect("Should build matrix");
+        let matrix = build_test_matrix(&args, &Default::default()).expect("Should build matrix");
 
         // Find baseline version
         let baseline = matrix.base_versions.iter().find(|v| v.is_baseline).expect("Should have baseline");
@@ -109,9 +237,73 @@ mod tests {
             docker: false,
             patch_transitive: false,
             simple: false,
+            min_free_gb: 2,
+            command: None,
+            force: false,
+            steps: None,
+            report_template: vec![],
+            messages: None,
+            stall_minutes: 10,
+            stall_sample: false,
+            compare_with: None,
+            exclude: vec![],
+            dependent_settings: Default::default(),
+            only_dependents: vec![],
+            backend: Backend::Local,
+            queue_dir: None,
+            queue_workers: 8,
+            target: None,
+            runner: None,
+            nightly_gates: None,
+            quarantine_after: 3,
+            include_quarantined: false,
+            scrub: false,
+            scrub_env: vec![],
+            dependent_patches: Default::default(),
+            simulate_update: false,
+            aliases: vec![],
+            timestamps: false,
+            step_times: false,
+            check_first: false,
+            resume: false,
+            from: None,
+            deterministic: false,
+            label: None,
+            staging_copy: crate::staging::CopyMethod::Auto,
+            index_url: None,
+            dependents_sort: crate::api::DependentSort::AllTime,
+            fail_on_skipped: false,
+            status_style: crate::report::StatusStyle::Symbols,
+            colorscheme: crate::console_format::ColorScheme::Default,
+            progress: crate::live::Progress::Auto,
+            save_artifacts: crate::artifacts::SaveArtifacts::Stderr,
+            json_error_limit: 16384,
+            refresh: false,
+            cache_ttl: 0,
+            dependent_git: vec![],
+            github: false,
+            timeout_per_step: None,
+            timeout_per_dependent: None,
+            time_budget: None,
+            no_baseline_cache: false,
+            schedule: Default::default(),
+            parallel: 1,
+            package: None,
+            diagnostic_counts: false,
+            toolchains: vec![],
+            docker_steps: None,
+            collapse_identical: false,
+            prefetch: None,
+            offline: None,
+            force_resolver_2: false,
+            pin_toolchain: None,
+            output_format: OutputFormat::Table,
+            github_org: vec![],
+            skip_if_seen: false,
+            queue_timeout: 120,
         };
 
-        let matrix = build_test_matrix(&args).expect("Should build matrix");
+        let matrix = build_test_matrix(&args, &Default::default()).expect("Should build matrix");
 
         // Count baselines
         let baseline_count = matrix.base_versions.iter().filter(|v| v.is_baseline).count();
@@ -144,9 +336,73 @@ mod tests {
             docker: false,
             patch_transitive: false,
             simple: false,
+            min_free_gb: 2,
+            command: None,
+            force: false,
+            steps: None,
+            report_template: vec![],
+            messages: None,
+            stall_minutes: 10,
+            stall_sample: false,
+            compare_with: None,
+            exclude: vec![],
+            dependent_settings: Default::default(),
+            only_dependents: vec![],
+            backend: Backend::Local,
+            queue_dir: None,
+            queue_workers: 8,
+            target: None,
+            runner: None,
+            nightly_gates: None,
+            quarantine_after: 3,
+            include_quarantined: false,
+            scrub: false,
+            scrub_env: vec![],
+            dependent_patches: Default::default(),
+            simulate_update: false,
+            aliases: vec![],
+            timestamps: false,
+            step_times: false,
+            check_first: false,
+            resume: false,
+            from: None,
+            deterministic: false,
+            label: None,
+            staging_copy: crate::staging::CopyMethod::Auto,
+            index_url: None,
+            dependents_sort: crate::api::DependentSort::AllTime,
+            fail_on_skipped: false,
+            status_style: crate::report::StatusStyle::Symbols,
+            colorscheme: crate::console_format::ColorScheme::Default,
+            progress: crate::live::Progress::Auto,
+            save_artifacts: crate::artifacts::SaveArtifacts::Stderr,
+            json_error_limit: 16384,
+            refresh: false,
+            cache_ttl: 0,
+            dependent_git: vec![],
+            github: false,
+            timeout_per_step: None,
+            timeout_per_dependent: None,
+            time_budget: None,
+            no_baseline_cache: false,
+            schedule: Default::default(),
+            parallel: 1,
+            package: None,
+            diagnostic_counts: false,
+            toolchains: vec![],
+            docker_steps: None,
+            collapse_identical: false,
+            prefetch: None,
+            offline: None,
+            force_resolver_2: false,
+            pin_toolchain: None,
+            output_format: OutputFormat::Table,
+            github_org: vec![],
+            skip_if_seen: false,
+            queue_timeout: 120,
         };
 
-        let matrix = build_test_matrix(&args).expect("Should build matrix");
+        let matrix = build_test_matrix(&args, &Default::default()).expect("Should build matrix");
 
         // First dependent should be baseline
         assert!(matrix.dependents[0].is_baseline, "First dependent should be marked as baseline");
@@ -183,9 +439,73 @@ mod tests {
             docker: false,
             patch_transitive: false,
             simple: false,
+            min_free_gb: 2,
+            command: None,
+            force: false,
+            steps: None,
+            report_template: vec![],
+            messages: None,
+            stall_minutes: 10,
+            stall_sample: false,
+            compare_with: None,
+            exclude: vec![],
+            dependent_settings: Default::default(),
+            only_dependents: vec![],
+            backend: Backend::Local,
+            queue_dir: None,
+            queue_workers: 8,
+            target: None,
+            runner: None,
+            nightly_gates: None,
+            quarantine_after: 3,
+            include_quarantined: false,
+            scrub: false,
+            scrub_env: vec![],
+            dependent_patches: Default::default(),
+            simulate_update: false,
+            aliases: vec![],
+            timestamps: false,
+            step_times: false,
+            check_first: false,
+            resume: false,
+            from: None,
+            deterministic: false,
+            label: None,
+            staging_copy: crate::staging::CopyMethod::Auto,
+            index_url: None,
+            dependents_sort: crate::api::DependentSort::AllTime,
+            fail_on_skipped: false,
+            status_style: crate::report::StatusStyle::Symbols,
+            colorscheme: crate::console_format::ColorScheme::Default,
+            progress: crate::live::Progress::Auto,
+            save_artifacts: crate::artifacts::SaveArtifacts::Stderr,
+            json_error_limit: 16384,
+            refresh: false,
+            cache_ttl: 0,
+            dependent_git: vec![],
+            github: false,
+            timeout_per_step: None,
+            timeout_per_dependent: None,
+            time_budget: None,
+            no_baseline_cache: false,
+            schedule: Default::default(),
+            parallel: 1,
+            package: None,
+            diagnostic_counts: false,
+            toolchains: vec![],
+            docker_steps: None,
+            collapse_identical: false,
+            prefetch: None,
+            offline: None,
+            force_resolver_2: false,
+            pin_toolchain: None,
+            output_format: OutputFormat::Table,
+            github_org: vec![],
+            skip_if_seen: false,
+            queue_timeout: 120,
         };
 
-        let matrix = build_test_matrix(&args).expect("Should build matrix");
+        let matrix = build_test_matrix(&args, &Default::default()).expect("Should build matrix");
 
         // Should have at least 2 versions (baseline + test versions)
         assert!(matrix.base_versions.len() >= 2, "Should have baseline + test versions");
@@ -199,4 +519,97 @@ mod tests {
             assert!(!v.is_baseline, "Non-first versions should not be baseline");
         }
     }
+
+    #[test]
+    fn test_git_dependents_found_in_checkout() {
+        use crate::cli::CliArgs;
+        use crate::types::CrateSource;
+        use clap::Parser;
+        use std::process::Command;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("upstream");
+        let write = |path: &str, text: &str| {
+            let path = repo.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, text).unwrap();
+        };
+        write("Cargo.toml", "[workspace]\nmembers = [\"viewer\", \"cli\"]\n");
+        write(
+            "viewer/Cargo.toml",
+            "[package]\nname = \"viewer\"\nversion = \"0.3.0\"\n[dependencies]\nrgb = \"0.8\"\n",
+        );
+        write("cli/Cargo.toml", "[package]\nname = \"viewer-cli\"\nversion = \"0.3.0\"\n");
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(status.status.success(), "{}", String::from_utf8_lossy(&status.stderr));
+        };
+        git(&["init", "--quiet"]);
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", "init"]);
+
+        let url = repo.display().to_string();
+        let staging = tmp.path().join("staging").display().to_string();
+        let args = CliArgs::parse_from(["cargo-copter", "--staging-dir", &staging, "--dependent-git", &url]);
+        let dependents = super::super::resolve_git_dependents(&args, &url, "rgb").unwrap();
+        assert_eq!(dependents.len(), 1);
+        assert_eq!(dependents[0].crate_ref.name, "viewer");
+        match &dependents[0].crate_ref.source {
+            CrateSource::Git { rev: Some(commit), path, .. } => {
+                assert_eq!(commit.len(), 40);
+                assert!(path.join("Cargo.toml").exists() && path.ends_with("viewer"));
+            }
+            other => panic!("expected a git source, got {:?}", other),
+        }
+        assert!(super::super::resolve_git_dependents(&args, &url, "png").is_err());
+    }
+
+    #[test]
+    fn test_package_picks_workspace_member() {
+        use crate::cli::CliArgs;
+        use clap::Parser;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let write = |path: &str, text: &str| {
+            let path = tmp.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, text).unwrap();
+        };
+        write("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/old\"]\n");
+        write("crates/rgb/Cargo.toml", "[package]\nname = \"rgb\"\nversion = \"0.8.92\"\n");
+        write("crates/rgb-derive/Cargo.toml", "[package]\nname = \"rgb-derive\"\nversion = \"0.1.0\"\n");
+        write("crates/old/Cargo.toml", "[package]\nname = \"rgb-old\"\nversion = \"0.1.0\"\n");
+        let root = tmp.path().display().to_string();
+
+        let args = CliArgs::parse_from(["cargo-copter", "--path", &root, "--package", "rgb"]);
+        let (name, version, manifest) = super::super::resolve_base_crate_info(&args, &Default::default()).unwrap();
+        assert_eq!((name.as_str(), version.as_str()), ("rgb", "0.8.92"));
+        assert_eq!(manifest, Some(tmp.path().join("crates/rgb/Cargo.toml")));
+        // --crate names the member too
+        let args = CliArgs::parse_from(["cargo-copter", "--path", &root, "--crate", "rgb-derive"]);
+        assert_eq!(super::super::base_crate_name(&args).as_deref(), Some("rgb-derive"));
+        assert!(
+            super::super::resolve_base_crate_info(&args, &Default::default())
+                .unwrap()
+                .2
+                .unwrap()
+                .ends_with("rgb-derive/Cargo.toml")
+        );
+
+        let args = CliArgs::parse_from(["cargo-copter", "--path", &root]);
+        let err = super::super::resolve_base_crate_info(&args, &Default::default()).unwrap_err().to_string();
+        assert!(
+            err.ends_with("is a workspace; choose the crate to test with --package (one of: rgb, rgb-derive)"),
+            "{}",
+            err
+        );
+        let args = CliArgs::parse_from(["cargo-copter", "--path", &root, "--package", "rgb-old"]);
+        assert!(super::super::resolve_base_crate_info(&args, &Default::default()).is_err());
+    }
 }
@@ -15,33 +15,73 @@
 /// - String buffers (for markdown/HTML)
 /// - Files
 /// - Any combination via `TableWriter`
+///
+/// Column widths are a [`Layout`] value passed to every table function, not global state,
+/// so the console table and the markdown export can render at different widths at once.
+use crate::messages::Catalog;
 use std::io::{self, Write};
-use std::sync::OnceLock;
+use std::sync::Arc;
 use term::color::Color;
 use terminal_size::{Width, terminal_size};
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 // Constants for terminal formatting
-const DEFAULT_TERMINAL_WIDTH: usize = 120;
+/// Width when stdout isn't a terminal, and of the table in report.md
+pub const DEFAULT_TERMINAL_WIDTH: usize = 120;
+
+/// Console palette (`--colorscheme`)
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorScheme {
+    /// Green passes, red regressions, yellow broken and skipped rows
+    #[default]
+    Default,
+    /// Blue passes, orange regressions, purple broken and grey skipped rows, told apart
+    /// without red-green vision; results lead with a status word
+    Deuteranopia,
+    /// No colors; results lead with a status word
+    None,
+}
+
+impl ColorScheme {
+    /// Whether hue alone can't tell statuses apart, so result cells spell them out
+    pub fn needs_status_words(self) -> bool {
+        self != ColorScheme::Default
+    }
+
+    /// 24-bit replacement for one of the default palette's row colors
+    fn rgb(self, color: Color) -> Option<(u8, u8, u8)> {
+        match (self, color) {
+            // Okabe-Ito colors, distinguishable with any common color vision deficiency
+            (ColorScheme::Deuteranopia, term::color::BRIGHT_GREEN) => Some((86, 180, 233)),
+            (ColorScheme::Deuteranopia, term::color::BRIGHT_RED) => Some((230, 159, 0)),
+            (ColorScheme::Deuteranopia, term::color::BRIGHT_YELLOW) => Some((204, 121, 167)),
+            (ColorScheme::Deuteranopia, term::color::YELLOW) => Some((153, 153, 153)),
+            // RGB for bright yellow (better Windows Terminal support)
+            (_, term::color::BRIGHT_YELLOW) => Some((255, 255, 102)),
+            _ => None,
+        }
+    }
+}
 
 /// Writer for table output - configurable for color/plain text
 pub struct TableWriter<W: Write> {
     writer: W,
     use_colors: bool,
+    layout: Layout,
 }
 
 impl<W: Write> TableWriter<W> {
     /// Create a new table writer
-    pub fn new(writer: W, use_colors: bool) -> Self {
-        Self { writer, use_colors }
+    pub fn new(writer: W, use_colors: bool, layout: Layout) -> Self {
+        Self { writer, use_colors, layout }
     }
 
     /// Write formatted text, optionally with color
     fn write_colored(&mut self, text: &str, color: Color) -> io::Result<()> {
-        if self.use_colors {
-            // Use RGB for bright yellow (better Windows Terminal support)
-            if color == term::color::BRIGHT_YELLOW {
-                write!(self.writer, "\x1b[38;2;255;255;102m{}\x1b[0m", text)
+        let scheme = self.layout.color_scheme;
+        if self.use_colors && scheme != ColorScheme::None {
+            if let Some((r, g, b)) = scheme.rgb(color) {
+                write!(self.writer, "\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, text)
             } else if let Some(ref mut t) = term::stdout() {
                 let _ = t.fg(color);
                 let _ = t.write_all(text.as_bytes());
@@ -69,17 +109,18 @@ impl<W: Write> TableWriter<W> {
         test_plan: Option<&str>,
         this_path: Option<&str>,
     ) -> io::Result<()> {
-        write!(self.writer, "{}", format_table_header(crate_name, display_version, total_deps, test_plan, this_path))
+        let header = format_table_header(&self.layout, crate_name, display_version, total_deps, test_plan, this_path);
+        write!(self.writer, "{}", header)
     }
 
     /// Write table footer
     pub fn write_table_footer(&mut self) -> io::Result<()> {
-        write!(self.writer, "{}", format_table_footer())
+        write!(self.writer, "{}", format_table_footer(&self.layout))
     }
 
     /// Write separator line between dependents
     pub fn write_separator_line(&mut self) -> io::Result<()> {
-        let w = get_widths();
+        let w = self.layout.clone();
         writeln!(
             self.writer,
             "├{:─<width1$}┼{:─<width2$}┼{:─<width3$}┼{:─<width4$}┼{:─<width5$}┤",
@@ -98,7 +139,7 @@ impl<W: Write> TableWriter<W> {
 
     /// Write a main 5-column row with proper formatting and color
     pub fn write_main_row(&mut self, cells: [&str; 5], color: Color) -> io::Result<()> {
-        let w = get_widths();
+        let w = self.layout.clone();
         let displays: Vec<String> = cells
             .iter()
             .zip([w.offered, w.spec, w.resolved, w.dependent, w.result].iter())
@@ -117,7 +158,7 @@ impl<W: Write> TableWriter<W> {
             return Ok(());
         }
 
-        let w = get_widths();
+        let w = self.layout.clone();
         let last_idx = rows.len() - 1;
 
         for (i, (spec, resolved, dependent)) in rows.iter().enumerate() {
@@ -141,49 +182,6 @@ impl<W: Write> TableWriter<W> {
         Ok(())
     }
 
-    /// Write error box top
-    pub fn write_error_box_top(&mut self) -> io::Result<()> {
-        let w = get_widths();
-        let error_box_width = w.spec + w.resolved + w.dependent + 6 - 2;
-
-        writeln!(
-            self.writer,
-            "│{:width1$}│ ╭{:─<error_width$}╮ │{:width5$}│",
-            "",
-            "",
-            "",
-            width1 = w.offered,
-            error_width = error_box_width - 4,
-            width5 = w.result
-        )
-    }
-
-    /// Write error box line
-    pub fn write_error_box_line(&mut self, line: &str) -> io::Result<()> {
-        let w = get_widths();
-        let error_box_width = w.spec + w.resolved + w.dependent + 6 - 2;
-        let padded = truncate_with_padding(line, error_box_width - 6);
-
-        writeln!(self.writer, "│{:width1$}│ │ {} │ │{:width5$}│", "", padded, "", width1 = w.offered, width5 = w.result)
-    }
-
-    /// Write error box bottom
-    pub fn write_error_box_bottom(&mut self) -> io::Result<()> {
-        let w = get_widths();
-        let error_box_width = w.spec + w.resolved + w.dependent + 6 - 2;
-
-        writeln!(
-            self.writer,
-            "│{:width1$}│ ╰{:─<error_width$}╯ │{:width5$}│",
-            "",
-            "",
-            "",
-            width1 = w.offered,
-            error_width = error_box_width - 4,
-            width5 = w.result
-        )
-    }
-
     /// Write comparison table
     pub fn write_comparison_table(&mut self, stats_list: &[ComparisonStats]) -> io::Result<()> {
         if stats_list.is_empty() {
@@ -210,6 +208,9 @@ impl<W: Write> TableWriter<W> {
 
         // Write each row
         self.write_simple_row("Total tested", stats_list, |s| s.total_tested)?;
+        if stats_list.iter().any(|s| s.skipped > 0) {
+            self.write_simple_row("Skipped", stats_list, |s| s.skipped)?;
+        }
 
         // Already broken (special case - shows "-" for non-baseline)
         write!(self.writer, "{:<26}", "Already broken")?;
@@ -276,45 +277,158 @@ impl<W: Write> TableWriter<W> {
 //
 
 /// Column widths for the 5-column table
-#[derive(Clone, Copy)]
-pub struct TableWidths {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
     pub offered: usize,
     pub spec: usize,
     pub resolved: usize,
     pub dependent: usize,
     pub result: usize,
     pub total: usize, // Total table width including borders
+    /// Result cell shows each row's start time (`--timestamps`)
+    pub started: bool,
+    /// Result cell's Time shows fetch/check/test durations (`--step-times`)
+    pub step_times: bool,
+    /// Result cell shows error/warning counts, "3E 12W" (`--diagnostic-counts`)
+    pub diagnostics: bool,
+    /// How report.md shows statuses (`--status-style`)
+    pub status_style: crate::report::StatusStyle,
+    /// Row palette, and whether result cells spell statuses out (`--colorscheme`)
+    pub color_scheme: ColorScheme,
+    /// Column titles and status labels (`--messages`)
+    pub messages: Arc<Catalog>,
 }
 
-impl TableWidths {
+/// "14:02:11"
+const STARTED_WIDTH: usize = 8;
+/// "0.4/3.1/58.2s" and longer, instead of "61.7s"
+const STEP_TIMES_WIDTH: usize = 17;
+const TIME_WIDTH: usize = 5;
+/// "300E 12W"
+const DIAGNOSTICS_WIDTH: usize = 8;
+
+impl Layout {
     pub fn new(terminal_width: usize) -> Self {
         Self::new_with_offered(terminal_width, None)
     }
 
     pub fn new_with_offered(terminal_width: usize, offered_width: Option<usize>) -> Self {
-        // Borders: │ = 6 characters (1 before each column + 1 at end)
-        let borders = 6;
-        let available = terminal_width.saturating_sub(borders);
+        Self::new_learned(terminal_width, offered_width, None, Default::default())
+    }
+
+    /// Layout for the versions being tested (and the last run's cells, if known)
+    pub fn for_versions(
+        terminal_width: usize,
+        versions: &[String],
+        display_version: &str,
+        force_versions: bool,
+        observed: Option<&crate::column_widths::ObservedWidths>,
+        messages: Arc<Catalog>,
+    ) -> Self {
+        let offered_width = Self::calculate_offered_width(versions, display_version, force_versions);
+        Self::new_learned(terminal_width, Some(offered_width), observed, messages)
+    }
 
+    /// Like `new_with_offered`, with Spec and Resolved sized to the widest cells of the last run
+    /// (measured against `messages`' column titles)
+    pub fn new_learned(
+        terminal_width: usize,
+        offered_width: Option<usize>,
+        observed: Option<&crate::column_widths::ObservedWidths>,
+        messages: Arc<Catalog>,
+    ) -> Self {
         // Use fixed widths for columns with known/predictable values
         // Offered: use provided width or default to 23
         let offered = offered_width.unwrap_or(23);
         // Spec: "^0.8.52" or "→ =this" max ~12 chars
-        let spec = 12;
         // Resolved: "0.8.91-preview 📦" max ~18 chars
-        let resolved = 18;
+        // Learned widths fit the widest cell (but at least the title), up to twice the guess
+        let learned = |observed: usize, title: &str, guess: usize| {
+            (observed.max(display_width(messages.get(title))) + 2).min(guess * 2)
+        };
+        let (spec, resolved) = match observed {
+            Some(o) => (learned(o.spec, "column.spec", 12), learned(o.resolved, "column.resolved", 18)),
+            None => (12, 18),
+        };
         // Result: "build failed ✓✗-  1.3s" fixed ~25 chars
         let result = 25;
 
-        // Dependent gets remaining space (for long crate names)
-        let fixed_total = offered + spec + resolved + result;
+        Layout {
+            offered,
+            spec,
+            resolved,
+            dependent: 0,
+            result,
+            total: 0,
+            started: false,
+            step_times: false,
+            diagnostics: false,
+            status_style: Default::default(),
+            color_scheme: ColorScheme::Default,
+            messages,
+        }
+        .with_width(terminal_width)
+    }
+
+    /// Widen the Result column for a start time and/or per-step durations
+    pub fn with_time_columns(self, started: bool, step_times: bool) -> Self {
+        Layout { started, step_times, ..self }.with_result_parts()
+    }
+
+    /// Widen the Result column for error/warning counts
+    pub fn with_diagnostics_column(self, diagnostics: bool) -> Self {
+        Layout { diagnostics, ..self }.with_result_parts()
+    }
+
+    /// Statuses in report.md as `status_style`
+    pub fn with_status_style(self, status_style: crate::report::StatusStyle) -> Self {
+        Layout { status_style, ..self }
+    }
+
+    /// Rows colored with `color_scheme`
+    pub fn with_color_scheme(self, color_scheme: ColorScheme) -> Self {
+        Layout { color_scheme, ..self }
+    }
+
+    /// Result column wide enough for the optional parts turned on
+    fn with_result_parts(self) -> Self {
+        let extra = if self.started { STARTED_WIDTH + 1 } else { 0 }
+            + if self.step_times { STEP_TIMES_WIDTH - TIME_WIDTH } else { 0 }
+            + if self.diagnostics { DIAGNOSTICS_WIDTH + 1 } else { 0 };
+        Layout { result: 25 + extra, ..self }.with_width(self.total)
+    }
+
+    /// Width of the Time part of the Result cell
+    pub fn time_width(&self) -> usize {
+        if self.step_times { STEP_TIMES_WIDTH } else { TIME_WIDTH }
+    }
+
+    /// Result cell: status, time, (with `diagnostics`) error/warning counts and (with
+    /// `started`) start time
+    pub fn result_cell(&self, result: &str, time: &str, diagnostics: &str, started: &str) -> String {
+        let mut cell = format!("{:>12} {:>width$}", result, time, width = self.time_width());
+        if self.diagnostics {
+            cell.push_str(&format!(" {:>width$}", diagnostics, width = DIAGNOSTICS_WIDTH));
+        }
+        if self.started {
+            cell.push_str(&format!(" {:>width$}", started, width = STARTED_WIDTH));
+        }
+        cell
+    }
+
+    /// The same columns at `terminal_width`: the Dependent column gets whatever is left
+    /// (for long crate names)
+    pub fn with_width(self, terminal_width: usize) -> Self {
+        // Borders: │ = 6 characters (1 before each column + 1 at end)
+        let borders = 6;
+        let available = terminal_width.saturating_sub(borders);
+        let fixed_total = self.offered + self.spec + self.resolved + self.result;
         let dependent = if available > fixed_total {
             available - fixed_total
         } else {
             20 // Minimum fallback
         };
-
-        TableWidths { offered, spec, resolved, dependent, result, total: terminal_width }
+        Layout { dependent, total: terminal_width, ..self }
     }
 
     /// Calculate minimum offered column width for given versions
@@ -335,74 +449,29 @@ impl TableWidths {
         // Add 2 for cell padding (accounts for the -2 when truncating)
         max_width + 2
     }
-}
-
-/// Get terminal width or default to DEFAULT_TERMINAL_WIDTH
-fn get_terminal_width() -> usize {
-    // Check if width override is set
-    if let Ok(guard) = OVERRIDE_WIDTH.read()
-        && let Some(width) = *guard
-    {
-        return width;
-    }
-
-    if let Some((Width(w), _)) = terminal_size() { w as usize } else { DEFAULT_TERMINAL_WIDTH }
-}
-
-// Table widths - initialized once with actual version data (production), or resettable (tests)
-static WIDTHS: OnceLock<TableWidths> = OnceLock::new();
-static OVERRIDE_WIDTH: std::sync::RwLock<Option<usize>> = std::sync::RwLock::new(None);
-static OVERRIDE_WIDTHS: std::sync::RwLock<Option<TableWidths>> = std::sync::RwLock::new(None);
-
-/// Set console width override (for testing or CLI --console-width)
-pub fn set_console_width(width: usize) {
-    if let Ok(mut w) = OVERRIDE_WIDTH.write() {
-        *w = Some(width);
-    }
-    // Also clear any cached widths so they get recalculated
-    if let Ok(mut w) = OVERRIDE_WIDTHS.write() {
-        *w = None;
-    }
-}
 
-/// Clear console width override (for testing)
-#[cfg(test)]
-#[allow(dead_code)]
-pub fn clear_console_width() {
-    if let Ok(mut w) = OVERRIDE_WIDTH.write() {
-        *w = None;
-    }
-    if let Ok(mut w) = OVERRIDE_WIDTHS.write() {
-        *w = None;
+    /// Widths for a terminal that is now `terminal_width` columns wide, if that differs
+    pub fn resized(&self, terminal_width: usize) -> Option<Self> {
+        (terminal_width != self.total).then(|| self.clone().with_width(terminal_width))
     }
 }
 
-/// Initialize table widths based on versions being tested
-pub fn init_table_widths(versions: &[String], display_version: &str, force_versions: bool) {
-    let offered_width = TableWidths::calculate_offered_width(versions, display_version, force_versions);
-    let widths = TableWidths::new_with_offered(get_terminal_width(), Some(offered_width));
-
-    // For tests: use the resettable override
-    if let Ok(mut w) = OVERRIDE_WIDTHS.write() {
-        *w = Some(widths);
-        return;
-    }
-
-    // For production: use the OnceLock (first initialization wins)
-    let _ = WIDTHS.set(widths);
+/// `--console-width` when given, else the terminal's width (DEFAULT_TERMINAL_WIDTH when not a terminal)
+pub fn terminal_width(console_width: Option<usize>) -> usize {
+    console_width.or_else(|| terminal_size().map(|(Width(w), _)| w as usize)).unwrap_or(DEFAULT_TERMINAL_WIDTH)
 }
 
-/// Get table widths (with fallback to defaults if not initialized)
-pub fn get_widths() -> TableWidths {
-    // Check override first (for tests or when width is dynamically set)
-    if let Ok(guard) = OVERRIDE_WIDTHS.read()
-        && let Some(widths) = *guard
-    {
-        return widths;
-    }
-
-    // Fall back to static widths
-    *WIDTHS.get_or_init(|| TableWidths::new(get_terminal_width()))
+/// Follow a terminal resize since the last row (tmux panes, window resizes): close the table
+/// at the old width and reopen it with column titles at the new one. Called before each row,
+/// so no row is drawn with two different widths. Returns whether the width changed.
+pub fn follow_terminal_resize(layout: &mut Layout, terminal_width: usize) -> bool {
+    let Some(resized) = layout.resized(terminal_width) else {
+        return false;
+    };
+    print_table_footer(layout);
+    *layout = resized;
+    print!("{}", format_column_header(layout));
+    true
 }
 
 //
@@ -511,14 +580,13 @@ pub fn truncate_from_start_with_padding(s: &str, width: usize) -> String {
 
 /// Format table header as a string
 pub fn format_table_header(
+    layout: &Layout,
     crate_name: &str,
     display_version: &str,
     total_deps: usize,
     test_plan: Option<&str>,
     this_path: Option<&str>,
 ) -> String {
-    let w = get_widths();
-
     let mut output = String::new();
     output.push('\n');
 
@@ -540,7 +608,30 @@ pub fn format_table_header(
     output.push_str(&format!("{}\n", this_line));
 
     output.push('\n');
+    output.push_str(&format_column_header(layout));
+
+    output
+}
 
+/// "Result  Time  Diags  Started" over the Result cell's parts
+fn result_title(w: &Layout) -> String {
+    let mut title = format!("{:<15}{}", w.messages.get("column.result"), w.messages.get("column.time"));
+    let mut part_end = 13 + w.time_width();
+    for (shown, key, width) in
+        [(w.diagnostics, "column.diagnostics", DIAGNOSTICS_WIDTH), (w.started, "column.started", STARTED_WIDTH)]
+    {
+        if shown {
+            let padding = part_end.saturating_sub(display_width(&title)) + 1;
+            title.push_str(&format!("{:padding$}{}", "", w.messages.get(key), padding = padding));
+            part_end += width + 1;
+        }
+    }
+    title
+}
+
+/// Top border, column titles and the rule under them
+fn format_column_header(w: &Layout) -> String {
+    let mut output = String::new();
     output.push_str(&format!(
         "┌{:─<width1$}┬{:─<width2$}┬{:─<width3$}┬{:─<width4$}┬{:─<width5$}┐\n",
         "",
@@ -556,11 +647,11 @@ pub fn format_table_header(
     ));
     output.push_str(&format!(
         "│{:^width1$}│{:^width2$}│{:^width3$}│{:^width4$}│{:^width5$}│\n",
-        "Offered",
-        "Spec",
-        "Resolved",
-        "Dependent",
-        "Result         Time",
+        w.messages.get("column.offered"),
+        w.messages.get("column.spec"),
+        w.messages.get("column.resolved"),
+        w.messages.get("column.dependent"),
+        result_title(w),
         width1 = w.offered,
         width2 = w.spec,
         width3 = w.resolved,
@@ -586,19 +677,19 @@ pub fn format_table_header(
 
 /// Print table header to stdout with colors
 pub fn print_table_header(
+    layout: &Layout,
     crate_name: &str,
     display_version: &str,
     total_deps: usize,
     test_plan: Option<&str>,
     this_path: Option<&str>,
 ) {
-    let mut writer = TableWriter::new(io::stdout(), false); // No colors for header
+    let mut writer = TableWriter::new(io::stdout(), false, layout.clone()); // No colors for header
     let _ = writer.write_table_header(crate_name, display_version, total_deps, test_plan, this_path);
 }
 
 /// Format table footer as a string
-pub fn format_table_footer() -> String {
-    let w = get_widths();
+pub fn format_table_footer(w: &Layout) -> String {
     format!(
         "└{:─<width1$}┴{:─<width2$}┴{:─<width3$}┴{:─<width4$}┴{:─<width5$}┘\n",
         "",
@@ -615,14 +706,14 @@ pub fn format_table_footer() -> String {
 }
 
 /// Print table footer to stdout
-pub fn print_table_footer() {
-    let mut writer = TableWriter::new(io::stdout(), false);
+pub fn print_table_footer(layout: &Layout) {
+    let mut writer = TableWriter::new(io::stdout(), false, layout.clone());
     let _ = writer.write_table_footer();
 }
 
 /// Print separator line between dependents to stdout
-pub fn print_separator_line() {
-    let mut writer = TableWriter::new(io::stdout(), false);
+pub fn print_separator_line(layout: &Layout) {
+    let mut writer = TableWriter::new(io::stdout(), false, layout.clone());
     let _ = writer.write_separator_line();
 }
 
@@ -631,14 +722,14 @@ pub fn print_separator_line() {
 //
 
 /// Print a main 5-column row with proper formatting and color to stdout
-pub fn print_main_row(cells: [&str; 5], color: Color) {
-    let mut writer = TableWriter::new(io::stdout(), true); // Enable colors
+pub fn print_main_row(layout: &Layout, cells: [&str; 5], color: Color) {
+    let mut writer = TableWriter::new(io::stdout(), true, layout.clone()); // Enable colors
     let _ = writer.write_main_row(cells, color);
 }
 
 /// Print multi-version dependency rows to stdout
-pub fn print_multi_version_rows(rows: &[(String, String, String)]) {
-    let mut writer = TableWriter::new(io::stdout(), false);
+pub fn print_multi_version_rows(layout: &Layout, rows: &[(String, String, String)]) {
+    let mut writer = TableWriter::new(io::stdout(), false, layout.clone());
     let _ = writer.write_multi_version_rows(rows);
 }
 
@@ -647,8 +738,7 @@ pub fn print_multi_version_rows(rows: &[(String, String, String)]) {
 //
 
 /// Helper to print error box top border
-pub fn print_error_box_top() {
-    let w = get_widths();
+pub fn print_error_box_top(w: &Layout) {
     let shortened_offered = 4;
     let corner0_width = if shortened_offered != w.offered { w.offered - shortened_offered - 1 } else { 0 };
 
@@ -686,8 +776,7 @@ pub fn print_error_box_top() {
 }
 
 /// Helper to print error box content line
-pub fn print_error_box_line(line: &str) {
-    let w = get_widths();
+pub fn print_error_box_line(w: &Layout, line: &str) {
     let shortened_offered = 4;
     let error_text_width = w.total - 1 - shortened_offered - 1 - 1 - 1 - 1;
     let truncated = truncate_with_padding(line, error_text_width);
@@ -695,8 +784,7 @@ pub fn print_error_box_line(line: &str) {
 }
 
 /// Helper to print error box bottom border (transitioning back to main table)
-pub fn print_error_box_bottom() {
-    let w = get_widths();
+pub fn print_error_box_bottom(w: &Layout) {
     let shortened_offered = 4;
     let corner0_width = if shortened_offered != w.offered { w.offered - shortened_offered - 1 } else { 0 };
 
@@ -748,11 +836,14 @@ pub struct ComparisonStats {
     pub passed_test: usize,
     pub fully_passing: usize,
     pub regressions: Vec<String>, // List of "dependent:version" that regressed
+    /// Dependents left out of `total_tested`: the version wasn't used or no steps ran
+    #[serde(default)]
+    pub skipped: usize,
 }
 
 /// Print comparison table to stdout
 pub fn print_comparison_table(stats_list: &[ComparisonStats]) {
-    let mut writer = TableWriter::new(io::stdout(), false);
+    let mut writer = TableWriter::new(io::stdout(), false, Layout::new(DEFAULT_TERMINAL_WIDTH));
     let _ = writer.write_comparison_table(stats_list);
 }
 
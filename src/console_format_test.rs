@@ -10,9 +10,9 @@ mod tests {
     /// Standard width for tests to ensure reproducible output
     const TEST_CONSOLE_WIDTH: usize = 120;
 
-    /// Set up test environment with fixed console width
-    fn setup_test_width() {
-        set_console_width(TEST_CONSOLE_WIDTH);
+    /// Layout at the fixed test width
+    fn test_layout() -> Layout {
+        Layout::for_versions(TEST_CONSOLE_WIDTH, &[], "0.8.52", false, None, Default::default())
     }
 
     #[test]
@@ -62,8 +62,7 @@ mod tests {
 
     #[test]
     fn test_table_widths_calculation() {
-        setup_test_width();
-        let widths = TableWidths::new(TEST_CONSOLE_WIDTH);
+        let widths = Layout::new(TEST_CONSOLE_WIDTH);
 
         // Verify total adds up correctly (120 - 6 borders = 114 for content)
         let total_content = widths.offered + widths.spec + widths.resolved + widths.dependent + widths.result;
@@ -81,7 +80,7 @@ mod tests {
     fn test_calculate_offered_width() {
         let versions = vec!["0.8.50".to_string(), "0.8.51".to_string(), "0.8.52-alpha.1".to_string()];
 
-        let width = TableWidths::calculate_offered_width(&versions, "0.8.52", false);
+        let width = Layout::calculate_offered_width(&versions, "0.8.52", false);
 
         // Should be at least as wide as the longest version + formatting
         // Format: "{icon} {resolution}{version}" = 1 + 1 + 1 + len + padding
@@ -92,7 +91,7 @@ mod tests {
     fn test_calculate_offered_width_with_forced() {
         let versions = vec!["0.8.50".to_string()];
 
-        let width = TableWidths::calculate_offered_width(&versions, "0.8.52", true);
+        let width = Layout::calculate_offered_width(&versions, "0.8.52", true);
 
         // Should account for forced marker "→!" = 2 chars + cell padding = 2
         // Format: "{icon} {resolution}{version}→!"
@@ -112,6 +111,7 @@ mod tests {
             passed_test: 3,
             fully_passing: 3,
             regressions: vec!["crate1".to_string()],
+            skipped: 0,
         };
 
         // Test JSON serialization works
@@ -127,10 +127,9 @@ mod tests {
 
     #[test]
     fn test_table_header_format_contains_all_columns() {
-        setup_test_width();
-        init_table_widths(&[], "0.8.52", false);
+        let layout = test_layout();
 
-        let header = format_table_header("test-crate", "0.8.52", 5, None, None);
+        let header = format_table_header(&layout, "test-crate", "0.8.52", 5, None, None);
 
         // Should contain all column headers
         assert!(header.contains("Offered"));
@@ -148,11 +147,10 @@ mod tests {
 
     #[test]
     fn test_table_header_with_test_plan() {
-        setup_test_width();
-        init_table_widths(&[], "0.8.52", false);
+        let layout = test_layout();
 
         let test_plan = "  Dependents: foo, bar\n  versions: baseline, 0.8.51\n  2 × 2 = 4 tests";
-        let header = format_table_header("test-crate", "0.8.52", 2, Some(test_plan), None);
+        let header = format_table_header(&layout, "test-crate", "0.8.52", 2, Some(test_plan), None);
 
         // Should include test plan
         assert!(header.contains("Dependents: foo, bar"));
@@ -162,23 +160,64 @@ mod tests {
 
     #[test]
     fn test_table_header_with_this_path() {
-        setup_test_width();
-        init_table_widths(&[], "0.8.52", false);
+        let layout = test_layout();
 
-        let header = format_table_header("test-crate", "0.8.52", 1, None, Some("/path/to/crate"));
+        let header = format_table_header(&layout, "test-crate", "0.8.52", 1, None, Some("/path/to/crate"));
 
         // Should show path instead of "your work-in-progress version"
         assert!(header.contains("/path/to/crate"));
         assert!(!header.contains("work-in-progress"));
     }
 
+    #[test]
+    fn test_resized_widths_keep_fixed_columns() {
+        let widths = Layout::new_with_offered(120, Some(16));
+        assert!(widths.resized(120).is_none());
+
+        let narrower = widths.resized(100).unwrap();
+        assert_eq!(narrower.total, 100);
+        assert_eq!(narrower.offered, 16);
+        assert_eq!(narrower.dependent, widths.dependent - 20);
+    }
+
+    #[test]
+    fn test_learned_widths_go_to_the_dependent_column() {
+        let guessed = Layout::new_with_offered(120, Some(16));
+        let observed = crate::column_widths::ObservedWidths { spec: 6, resolved: 9, dependent: 40 };
+        let learned = Layout::new_learned(120, Some(16), Some(&observed), Default::default());
+        // "Spec" and "Resolved" titles still fit
+        assert_eq!((learned.spec, learned.resolved), (8, 11));
+        assert_eq!(learned.dependent, guessed.dependent + 4 + 7);
+        assert_eq!(learned.resized(100).unwrap().spec, 8);
+
+        let huge = crate::column_widths::ObservedWidths { spec: 80, resolved: 80, dependent: 10 };
+        let capped = Layout::new_learned(120, Some(16), Some(&huge), Default::default());
+        assert_eq!((capped.spec, capped.resolved), (24, 36));
+    }
+
+    #[test]
+    fn test_time_columns_widen_the_result_cell() {
+        let layout = test_layout().with_time_columns(true, true).with_diagnostics_column(true);
+        assert_eq!(layout.total, TEST_CONSOLE_WIDTH);
+        assert_eq!(layout.dependent, test_layout().dependent - 9 - 12 - 9);
+        assert_eq!(layout.clone().with_time_columns(true, true), layout);
+
+        let cell = layout.result_cell("passed ✓✓✓", "0.4/3.1/58.2s", "3E 12W", "14:02:11");
+        assert!(cell.ends_with(" 0.4/3.1/58.2s   3E 12W 14:02:11"));
+        assert!(display_width(&cell) <= layout.result - 2);
+
+        let header = format_table_header(&layout, "test", "0.8.52", 1, None, None);
+        assert!(header.contains("Time            Diags    Started"), "{}", header);
+        let widths: Vec<usize> = header.lines().skip(4).map(display_width).collect();
+        assert!(widths.iter().all(|w| *w == TEST_CONSOLE_WIDTH), "{:?}", widths);
+    }
+
     #[test]
     fn test_table_footer_matches_header_width() {
-        setup_test_width();
-        init_table_widths(&[], "0.8.52", false);
+        let layout = test_layout();
 
-        let header = format_table_header("test", "0.8.52", 1, None, None);
-        let footer = format_table_footer();
+        let header = format_table_header(&layout, "test", "0.8.52", 1, None, None);
+        let footer = format_table_footer(&layout);
 
         // Get the width of the first line (top border)
         let header_width = header.lines().nth(4).map(|l| l.len()).unwrap_or(0);
@@ -187,4 +226,32 @@ mod tests {
         // Should be same width as header border
         assert_eq!(header_width, footer_width);
     }
+
+    #[test]
+    fn test_deuteranopia_palette_avoids_red_and_green() {
+        let scheme = ColorScheme::Deuteranopia;
+        let palette: Vec<Option<(u8, u8, u8)>> =
+            [term::color::BRIGHT_GREEN, term::color::BRIGHT_RED, term::color::BRIGHT_YELLOW, term::color::YELLOW]
+                .into_iter()
+                .map(|color| scheme.rgb(color))
+                .collect();
+        assert_eq!(palette, [Some((86, 180, 233)), Some((230, 159, 0)), Some((204, 121, 167)), Some((153, 153, 153))]);
+        assert_eq!(ColorScheme::Default.rgb(term::color::BRIGHT_GREEN), None);
+        assert!(!ColorScheme::Default.needs_status_words());
+        assert!(ColorScheme::Deuteranopia.needs_status_words() && ColorScheme::None.needs_status_words());
+    }
+
+    #[test]
+    fn test_table_writer_colors_with_layout_scheme() {
+        let row = |scheme| {
+            let mut out = Vec::new();
+            let layout = Layout::new(DEFAULT_TERMINAL_WIDTH).with_color_scheme(scheme);
+            TableWriter::new(&mut out, true, layout)
+                .write_main_row(["a", "b", "c", "d", "e"], term::color::BRIGHT_RED)
+                .unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert!(row(ColorScheme::Deuteranopia).starts_with("\x1b[38;2;230;159;0m│ a"));
+        assert!(row(ColorScheme::None).starts_with("│ a"));
+    }
 }
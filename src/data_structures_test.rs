@@ -42,6 +42,8 @@ mod tests {
         let failure = CrateFailure {
             crate_name: "test-crate".to_string(),
             error_message: "error[E0432]: unresolved import".to_string(),
+            raw_stderr: false,
+            error_file: None,
         };
 
         let json = serde_json::to_string(&failure).unwrap();
@@ -60,8 +62,13 @@ mod tests {
                 CrateFailure {
                     crate_name: "dep1".to_string(),
                     error_message: "build failed".to_string(),
+                    raw_stderr: false,
+                    error_file: None,
                 },
             ],
+            timed_out: false,
+            errors: 0,
+            warnings: 0,
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -82,6 +89,9 @@ mod tests {
                 passed: true,
                 duration: 0.5,
                 failures: vec![],
+                timed_out: false,
+                errors: 0,
+                warnings: 0,
             },
         };
 
@@ -104,6 +114,9 @@ mod tests {
                         passed: true,
                         duration: 0.1,
                         failures: vec![],
+                        timed_out: false,
+                        errors: 0,
+                        warnings: 0,
                     },
                 },
                 TestCommand {
@@ -113,6 +126,9 @@ mod tests {
                         passed: true,
                         duration: 0.2,
                         failures: vec![],
+                        timed_out: false,
+                        errors: 0,
+                        warnings: 0,
                     },
                 },
                 TestCommand {
@@ -125,11 +141,18 @@ mod tests {
                             CrateFailure {
                                 crate_name: "test-dep".to_string(),
                                 error_message: "test failed".to_string(),
+                                raw_stderr: false,
+                                error_file: None,
                             },
                         ],
+                        timed_out: false,
+                        errors: 0,
+                        warnings: 0,
                     },
                 },
             ],
+            started: None,
+            toolchain: None,
         };
 
         let json = serde_json::to_string(&execution).unwrap();
@@ -165,6 +188,11 @@ mod tests {
             resolved_version: "0.8.52".to_string(),
             resolved_source: VersionSource::CratesIo,
             used_offered_version: true,
+            fork: None,
+            repository: None,
+            git: None,
+            base_features: None,
+            resolver: None,
         };
 
         let json = serde_json::to_string(&dep).unwrap();
@@ -186,8 +214,14 @@ mod tests {
                 resolved_version: "0.8.51".to_string(),
                 resolved_source: VersionSource::CratesIo,
                 used_offered_version: false,
+                fork: None,
+                repository: None,
+                git: None,
+                base_features: None,
+                resolver: None,
             },
             depth: 2,
+            path: vec![],
         };
 
         let json = serde_json::to_string(&transitive).unwrap();
@@ -209,10 +243,17 @@ mod tests {
                 resolved_version: "0.8.52".to_string(),
                 resolved_source: VersionSource::CratesIo,
                 used_offered_version: true,
+                fork: None,
+                repository: None,
+                git: None,
+                base_features: None,
+                resolver: None,
             },
             offered: None,
             test: TestExecution {
                 commands: vec![],
+                started: None,
+                toolchain: None,
             },
             transitive: vec![],
         };
@@ -237,6 +278,11 @@ mod tests {
                 resolved_version: "0.8.52".to_string(),
                 resolved_source: VersionSource::CratesIo,
                 used_offered_version: true,
+                fork: None,
+                repository: None,
+                git: None,
+                base_features: None,
+                resolver: None,
             },
             offered: Some(OfferedVersion {
                 version: "0.8.52".to_string(),
@@ -251,9 +297,14 @@ mod tests {
                             passed: true,
                             duration: 0.5,
                             failures: vec![],
+                            timed_out: false,
+                            errors: 0,
+                            warnings: 0,
                         },
                     },
                 ],
+                started: None,
+                toolchain: None,
             },
             transitive: vec![],
         };
@@ -278,6 +329,11 @@ mod tests {
                 resolved_version: "0.8.52".to_string(),
                 resolved_source: VersionSource::Local,
                 used_offered_version: true,
+                fork: None,
+                repository: None,
+                git: None,
+                base_features: None,
+                resolver: None,
             },
             offered: Some(OfferedVersion {
                 version: "0.8.52".to_string(),
@@ -292,6 +348,9 @@ mod tests {
                             passed: true,
                             duration: 1.1,
                             failures: vec![],
+                            timed_out: false,
+                            errors: 0,
+                            warnings: 0,
                         },
                     },
                     TestCommand {
@@ -304,11 +363,18 @@ mod tests {
                                 CrateFailure {
                                     crate_name: "complex-dep".to_string(),
                                     error_message: "error[E0308]: type mismatch".to_string(),
+                                    raw_stderr: false,
+                                    error_file: None,
                                 },
                             ],
+                            timed_out: false,
+                            errors: 0,
+                            warnings: 0,
                         },
                     },
                 ],
+                started: None,
+                toolchain: None,
             },
             transitive: vec![
                 TransitiveTest {
@@ -319,8 +385,14 @@ mod tests {
                         resolved_version: "0.8.51".to_string(),
                         resolved_source: VersionSource::CratesIo,
                         used_offered_version: false,
+                        fork: None,
+                        repository: None,
+                        git: None,
+                        base_features: None,
+                        resolver: None,
                     },
                     depth: 1,
+                    path: vec![],
                 },
             ],
         };
@@ -0,0 +1,182 @@
+/// Last successful reverse-dependency lookup per crate, for when crates.io's API is down
+///
+/// `--top-dependents` needs crates.io's reverse-dependency endpoint, so an API outage used
+/// to abort the whole run before anything was tested. Every successful lookup is saved to
+/// `~/.cache/cargo-copter/dependents/<crate>.json` (in `--dependents-sort` order, with the
+/// time it was fetched); when a later lookup fails, the run warns and continues with the
+/// saved list, saying how old it is. Dependents that appeared since then are missed, and a
+/// list saved by a smaller `--top-dependents` yields fewer dependents than asked for.
+///
+/// `smoke` skips the API altogether when a saved list is recent enough (`set_fresh_for`),
+/// big enough and in the same order: the top dependents rarely change within a day.
+use crate::api::{self, DependentSample, DependentSort, ReverseDependency};
+use crate::registry::Registry;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long `smoke` trusts a saved list
+pub const SMOKE_FRESH_FOR: Duration = Duration::from_secs(24 * 60 * 60);
+
+lazy_static::lazy_static! {
+    static ref FRESH_FOR: Mutex<Option<Duration>> = Mutex::new(None);
+}
+
+/// Use saved lists younger than `age` without asking the API
+pub fn set_fresh_for(age: Duration) {
+    *FRESH_FOR.lock().unwrap() = Some(age);
+}
+
+/// A saved lookup
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CachedDependents {
+    /// RFC 3339
+    pub fetched: String,
+    pub dependents: Vec<ReverseDependency>,
+    /// Dependents on the registry in all
+    #[serde(default)]
+    pub total: usize,
+    #[serde(default)]
+    pub sort: DependentSort,
+}
+
+impl CachedDependents {
+    /// "3 days", "5 hours" or "12 minutes" since the lookup
+    pub fn age(&self, now: chrono::DateTime<chrono::Local>) -> String {
+        let Ok(fetched) = chrono::DateTime::parse_from_rfc3339(&self.fetched) else {
+            return "unknown time".to_string();
+        };
+        let age = now.signed_duration_since(fetched);
+        let plural = |n: i64, unit: &str| format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" });
+        if age.num_days() > 0 {
+            plural(age.num_days(), "day")
+        } else if age.num_hours() > 0 {
+            plural(age.num_hours(), "hour")
+        } else {
+            plural(age.num_minutes().max(0), "minute")
+        }
+    }
+
+    /// Whether this lookup can stand in for a new one of `limit` dependents by `sort`
+    pub fn answers(
+        &self,
+        limit: usize,
+        sort: DependentSort,
+        max_age: Duration,
+        now: chrono::DateTime<chrono::Local>,
+    ) -> bool {
+        let Ok(fetched) = chrono::DateTime::parse_from_rfc3339(&self.fetched) else {
+            return false;
+        };
+        let fresh = now.signed_duration_since(fetched).to_std().is_ok_and(|age| age < max_age);
+        let complete = self.dependents.len() >= limit || self.dependents.len() == self.total;
+        fresh && complete && self.sort == sort
+    }
+}
+
+/// The top `limit` dependents of `crate_name` by `sort` from the API, else from the last
+/// successful lookup (with a warning); errors only when neither is available
+pub fn top_dependents(
+    registry: &Registry,
+    crate_name: &str,
+    limit: usize,
+    sort: DependentSort,
+) -> Result<DependentSample, String> {
+    let dir = cache_dir(registry);
+    if registry.is_offline() {
+        let cached = load(&dir, crate_name)
+            .ok_or_else(|| format!("the dependents of {} were not prefetched ({})", crate_name, dir.display()))?;
+        let total = cached.total.max(cached.dependents.len());
+        return Ok(DependentSample { dependents: cached.dependents.into_iter().take(limit).collect(), total });
+    }
+    if let Some(max_age) = *FRESH_FOR.lock().unwrap()
+        && let Some(cached) = load(&dir, crate_name)
+        && cached.answers(limit, sort, max_age, chrono::Local::now())
+    {
+        log::debug!("using the dependents of {} cached {} ago", crate_name, cached.age(chrono::Local::now()));
+        let total = cached.total.max(cached.dependents.len());
+        return Ok(DependentSample { dependents: cached.dependents.into_iter().take(limit).collect(), total });
+    }
+    match api::get_top_dependents_by(registry, crate_name, limit, sort) {
+        Ok(sample) => {
+            let cached = CachedDependents {
+                fetched: chrono::Local::now().to_rfc3339(),
+                dependents: sample.dependents,
+                total: sample.total,
+                sort,
+            };
+            if let Err(e) = save(&dir, crate_name, &cached) {
+                log::debug!("not caching dependents of {}: {}", crate_name, e);
+            }
+            Ok(DependentSample { dependents: cached.dependents, total: cached.total })
+        }
+        Err(e) => {
+            let cached = load(&dir, crate_name).ok_or(e.clone())?;
+            eprintln!(
+                "Warning: {}; using the {} dependents of {} (by {}) cached {} ago",
+                e,
+                cached.dependents.len(),
+                crate_name,
+                cached.sort.as_str(),
+                cached.age(chrono::Local::now())
+            );
+            let total = cached.total.max(cached.dependents.len());
+            Ok(DependentSample { dependents: cached.dependents.into_iter().take(limit).collect(), total })
+        }
+    }
+}
+
+/// The last saved lookup for `crate_name`, if any
+pub fn load(dir: &Path, crate_name: &str) -> Option<CachedDependents> {
+    let text = std::fs::read_to_string(path(dir, crate_name)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Save a lookup, replacing the previous one
+pub fn save(dir: &Path, crate_name: &str, cached: &CachedDependents) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = path(dir, crate_name);
+    let json = serde_json::to_string_pretty(cached).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn cache_dir(registry: &Registry) -> PathBuf {
+    registry.dependents_dir().unwrap_or_else(|| crate::cli::default_cache_dir().join("dependents"))
+}
+
+fn path(dir: &Path, crate_name: &str) -> PathBuf {
+    dir.join(format!("{}.json", crate_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_lookup_and_age() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load(dir.path(), "rgb"), None);
+        let cached = CachedDependents {
+            fetched: "2026-10-13T09:00:00+00:00".to_string(),
+            dependents: vec![
+                ReverseDependency { name: "image".to_string(), downloads: 900 },
+                ReverseDependency { name: "ravif".to_string(), downloads: 50 },
+            ],
+            total: 120,
+            sort: DependentSort::Recent,
+        };
+        save(dir.path(), "rgb", &cached).unwrap();
+        assert_eq!(load(dir.path(), "rgb"), Some(cached.clone()));
+
+        let at = |time: &str| chrono::DateTime::parse_from_rfc3339(time).unwrap().with_timezone(&chrono::Local);
+        assert_eq!(cached.age(at("2026-10-16T10:00:00+00:00")), "3 days");
+        assert_eq!(cached.age(at("2026-10-13T10:30:00+00:00")), "1 hour");
+        assert_eq!(cached.age(at("2026-10-13T09:05:00+00:00")), "5 minutes");
+
+        let day = Duration::from_secs(24 * 60 * 60);
+        assert!(cached.answers(2, DependentSort::Recent, day, at("2026-10-13T20:00:00+00:00")));
+        assert!(!cached.answers(2, DependentSort::Recent, day, at("2026-10-14T10:00:00+00:00")));
+        assert!(!cached.answers(3, DependentSort::Recent, day, at("2026-10-13T20:00:00+00:00")));
+        assert!(!cached.answers(2, DependentSort::AllTime, day, at("2026-10-13T20:00:00+00:00")));
+    }
+}
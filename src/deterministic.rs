@@ -0,0 +1,148 @@
+/// `--deterministic`: report.md and report.json that diff cleanly between runs
+///
+/// Two runs of the same matrix differ in much more than their results: rows arrive in a
+/// different order from queue workers, every step has a new duration and start time, and
+/// error messages carry the staging directory, the home directory and cargo's hashed
+/// registry and build directory names. With `--deterministic` the exports get rows sorted
+/// by dependent, no times (durations 0, `generated` at the Unix epoch), and those paths
+/// replaced with `<staging>`, `<base>` (a local base crate) and `~`, so the diff of two
+/// reports in code review shows exactly which dependents changed status. The console
+/// output, history and failure logs keep the real values.
+use crate::run_metadata::RunMetadata;
+use crate::types::{CrateSource, OfferedRow, TestMatrix};
+use std::path::Path;
+
+/// `generated` in deterministic reports
+pub const EPOCH: &str = "1970-01-01T00:00:00+00:00";
+
+/// Rewrites rows and run metadata for deterministic exports
+#[derive(Debug, Clone, Default)]
+pub struct Normalizer {
+    /// Literal (path, placeholder) pairs, longest path first
+    paths: Vec<(String, String)>,
+}
+
+impl Normalizer {
+    pub fn new(matrix: &TestMatrix) -> Self {
+        let bases = matrix.base_versions.iter().filter_map(|v| match &v.crate_ref.source {
+            CrateSource::Local { path } if path.ends_with("Cargo.toml") => path.parent().map(Path::to_path_buf),
+            CrateSource::Local { path } => Some(path.clone()),
+            _ => None,
+        });
+        let mut normalizer = Normalizer::default();
+        normalizer.add(&matrix.staging_dir, "<staging>");
+        for base in bases {
+            normalizer.add(&base, "<base>");
+        }
+        if let Some(home) = dirs::home_dir() {
+            normalizer.add(&home, "~");
+        }
+        normalizer
+    }
+
+    fn add(&mut self, path: &Path, placeholder: &str) {
+        for path in [path.to_path_buf(), crate::paths::canonicalize(path)] {
+            let path = path.display().to_string();
+            let path = path.trim_end_matches(['/', '\\']);
+            if path.len() > 1 && !self.paths.iter().any(|(existing, _)| existing == path) {
+                self.paths.push((path.to_string(), placeholder.to_string()));
+            }
+        }
+        self.paths.sort_by_key(|(path, _)| std::cmp::Reverse(path.len()));
+    }
+
+    /// Placeholders for known paths, and cargo's hash suffixes dropped from path components
+    pub fn text(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for (path, placeholder) in &self.paths {
+            if out.contains(path.as_str()) {
+                out = out.replace(path.as_str(), placeholder);
+            }
+        }
+        crate::scrub::normalize_path_hex_codes(&out)
+    }
+
+    /// `rows` sorted by dependent (keeping each dependent's baseline-first order), without times
+    pub fn rows(&self, rows: &[OfferedRow]) -> Vec<OfferedRow> {
+        let mut rows = rows.to_vec();
+        rows.sort_by(|a, b| {
+            (&a.primary.dependent_name, &a.primary.dependent_version)
+                .cmp(&(&b.primary.dependent_name, &b.primary.dependent_version))
+        });
+        for row in &mut rows {
+            row.test.started = None;
+            for cmd in &mut row.test.commands {
+                cmd.result.duration = 0.0;
+                for failure in &mut cmd.result.failures {
+                    failure.error_message = self.text(&failure.error_message);
+                }
+            }
+        }
+        rows
+    }
+
+    /// `run` without times or machine paths
+    pub fn run(&self, run: &RunMetadata) -> RunMetadata {
+        RunMetadata {
+            command_line: self.text(&run.command_line),
+            generated: EPOCH.to_string(),
+            base_path: run.base_path.as_deref().map(|path| self.text(path)),
+            wall_secs: None,
+            cargo_cpu_secs: None,
+            ..run.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(dependent: &str, offered: Option<&str>, error: &str) -> OfferedRow {
+        crate::test_rows::row(dependent)
+            .offered(offered)
+            .baseline(offered.map(|_| (true, true)))
+            .resolved("0.8.91")
+            .used_offered(offered.is_some())
+            .duration(12.5)
+            .started("2026-10-16T09:00:00+02:00")
+            .error(error)
+            .build()
+    }
+
+    #[test]
+    fn test_sorted_rows_without_times_or_paths() {
+        let mut normalizer = Normalizer::default();
+        normalizer.add(Path::new("/home/ci/.cache/cargo-copter/staging"), "<staging>");
+        normalizer.add(Path::new("/home/ci"), "~");
+        let rows = vec![
+            row("png", None, ""),
+            row("image", None, ""),
+            row(
+                "image",
+                Some("0.9.0"),
+                "error[E0432] --> /home/ci/.cache/cargo-copter/staging/image-1.0.0/src/lib.rs:3:5\n\
+                 /home/ci/.cargo/registry/src/index.crates.io-6f17d22bba15001f/rgb-0.9.0/src/lib.rs",
+            ),
+        ];
+        let rows = normalizer.rows(&rows);
+        let order: Vec<(&str, Option<&str>)> = rows
+            .iter()
+            .map(|r| (r.primary.dependent_name.as_str(), r.offered.as_ref().map(|o| o.version.as_str())))
+            .collect();
+        assert_eq!(order, [("image", None), ("image", Some("0.9.0")), ("png", None)]);
+        assert!(rows.iter().all(|r| r.test.started.is_none() && r.test.commands[0].result.duration == 0.0));
+        assert_eq!(
+            rows[1].test.commands[0].result.failures[0].error_message,
+            "error[E0432] --> <staging>/image-1.0.0/src/lib.rs:3:5\n~/.cargo/registry/src/index.crates.io/rgb-0.9.0/src/lib.rs"
+        );
+
+        let run = RunMetadata {
+            generated: "2026-10-16T09:00:00+02:00".to_string(),
+            wall_secs: Some(60.0),
+            ..Default::default()
+        };
+        assert_eq!(normalizer.run(&run).generated, EPOCH);
+        assert_eq!(normalizer.run(&run).wall_secs, None);
+    }
+}
@@ -0,0 +1,258 @@
+/// Disk space preflight and low-space handling
+///
+/// Every registry dependent gets its own staging directory with a full
+/// `target/` tree, which easily runs to hundreds of megabytes. When the disk
+/// fills up mid-run cargo fails with cryptic IO errors that get recorded as
+/// dependent failures. This module estimates the space a run needs up front
+/// and pauses (cleaning old build output first) when free space runs low.
+use crate::types::{CrateSource, TestMatrix};
+use log::debug;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+/// Assumed staging usage per dependent when there is no history to go on
+pub const DEFAULT_BYTES_PER_DEPENDENT: u64 = 750 * 1024 * 1024;
+
+const GIB: u64 = 1024 * 1024 * 1024;
+
+/// Convert the `--min-free-gb` CLI value into bytes
+pub fn gb_to_bytes(gb: u64) -> u64 {
+    gb.saturating_mul(GIB)
+}
+
+/// Human-readable byte count (e.g. "1.5 GB", "320 MB")
+pub fn format_bytes(bytes: u64) -> String {
+    const MIB: u64 = 1024 * 1024;
+    if bytes >= GIB {
+        format!("{:.1} GB", bytes as f64 / GIB as f64)
+    } else if bytes >= MIB {
+        format!("{} MB", bytes / MIB)
+    } else {
+        format!("{} KB", bytes / 1024)
+    }
+}
+
+/// Free space available to the current user on the filesystem holding `path`
+///
+/// `path` does not need to exist yet; the nearest existing ancestor is queried.
+pub fn free_space(path: &Path) -> Option<u64> {
+    let mut probe = path;
+    loop {
+        if probe.exists() {
+            return fs2::available_space(probe).ok();
+        }
+        probe = probe.parent()?;
+    }
+}
+
+/// Total size of all files below `path` (symlinks are not followed)
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(ft) if ft.is_dir() => dir_size(&entry.path()),
+            Ok(ft) if ft.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Staging subdirectories that contain build output from a previous run
+fn built_staging_dirs(staging_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(staging_dir) else {
+        return Vec::new();
+    };
+    entries.flatten().map(|e| e.path()).filter(|p| p.join("target").is_dir()).collect()
+}
+
+/// Average staging usage of previously built dependents, if any exist
+pub fn historical_bytes_per_dependent(staging_dir: &Path) -> Option<u64> {
+    let dirs = built_staging_dirs(staging_dir);
+    if dirs.is_empty() {
+        return None;
+    }
+    let total: u64 = dirs.iter().map(|d| dir_size(d)).sum();
+    Some(total / dirs.len() as u64)
+}
+
+/// Estimate how much staging space a run will need
+///
+/// Only registry dependents are unpacked into staging; local dependents build in place.
+/// All base versions of one dependent share a staging directory, so the estimate
+/// scales with the number of distinct dependents rather than the test count.
+pub fn estimate_required_bytes(registry_dependents: usize, bytes_per_dependent: u64) -> u64 {
+    (registry_dependents as u64).saturating_mul(bytes_per_dependent)
+}
+
+/// Warn before starting if the matrix is unlikely to fit on disk
+pub fn preflight(matrix: &TestMatrix) {
+    let Some(available) = free_space(&matrix.staging_dir) else {
+        debug!("Could not determine free space for {}", matrix.staging_dir.display());
+        return;
+    };
+
+    let mut registry_dependents: Vec<String> = matrix
+        .dependents
+        .iter()
+        .filter(|d| matches!(d.crate_ref.source, CrateSource::Registry))
+        .map(|d| format!("{}-{}", d.crate_ref.name, d.crate_ref.version.display()))
+        .collect();
+    registry_dependents.sort();
+    registry_dependents.dedup();
+
+    let per_dependent = historical_bytes_per_dependent(&matrix.staging_dir).unwrap_or(DEFAULT_BYTES_PER_DEPENDENT);
+    let required = estimate_required_bytes(registry_dependents.len(), per_dependent);
+    debug!(
+        "Disk preflight: {} free, ~{} needed ({} dependents × {})",
+        format_bytes(available),
+        format_bytes(required),
+        registry_dependents.len(),
+        format_bytes(per_dependent)
+    );
+
+    if available < required.saturating_add(matrix.min_free_bytes) {
+        eprintln!(
+            "warning: low disk space — {} free in {}, this run may need ~{} ({} dependents × ~{} each)",
+            format_bytes(available),
+            matrix.staging_dir.display(),
+            format_bytes(required),
+            registry_dependents.len(),
+            format_bytes(per_dependent)
+        );
+        eprintln!(
+            "         Old build output will be cleaned automatically if space runs low; use --clean to start fresh."
+        );
+    }
+}
+
+/// Remove `target/` directories from staged dependents, except those of the dependents
+/// named in `busy` (being built by other workers)
+///
+/// Returns the number of bytes reclaimed. Sources stay in place so a later run
+/// only needs to rebuild, not re-download.
+pub fn gc_staging(staging_dir: &Path, busy: &[String]) -> u64 {
+    let mut reclaimed = 0;
+    for dir in built_staging_dirs(staging_dir) {
+        if busy.iter().any(|name| is_staged_for(&dir, name)) {
+            continue;
+        }
+        let target = dir.join("target");
+        let size = dir_size(&target);
+        match std::fs::remove_dir_all(&target) {
+            Ok(()) => reclaimed += size,
+            Err(e) => debug!("Failed to remove {}: {}", target.display(), e),
+        }
+    }
+    reclaimed
+}
+
+/// Whether `dir` is a staged copy of dependent `name` (`<name>-<version>`)
+fn is_staged_for(dir: &Path, name: &str) -> bool {
+    dir.file_name()
+        .and_then(|f| f.to_str())
+        .and_then(|f| f.strip_prefix(name))
+        .and_then(|rest| rest.strip_prefix('-'))
+        .is_some_and(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// Make sure at least `min_free_bytes` are available before the next build
+///
+/// When space is low, old build output is cleaned from staging first. If that
+/// is not enough, an interactive session is paused until the user frees space;
+/// non-interactive runs get an error so the caller can stop cleanly and still
+/// write reports for everything tested so far. The dependents in `busy` are still
+/// building, so their output is left alone; callers with several workers must not run
+/// this from more than one at a time.
+pub fn ensure_free_space(staging_dir: &Path, min_free_bytes: u64, busy: &[String]) -> Result<(), String> {
+    if min_free_bytes == 0 {
+        return Ok(());
+    }
+    let Some(available) = free_space(staging_dir) else {
+        return Ok(());
+    };
+    if available >= min_free_bytes {
+        return Ok(());
+    }
+
+    eprintln!(
+        "warning: only {} free (threshold {}), cleaning old build output in {}",
+        format_bytes(available),
+        format_bytes(min_free_bytes),
+        staging_dir.display()
+    );
+    let reclaimed = gc_staging(staging_dir, busy);
+    eprintln!("         reclaimed {}", format_bytes(reclaimed));
+
+    loop {
+        let available = free_space(staging_dir).unwrap_or(u64::MAX);
+        if available >= min_free_bytes {
+            return Ok(());
+        }
+        if !std::io::stdin().is_terminal() {
+            return Err(format!(
+                "only {} free after cleanup (need {}); stopping before cargo runs out of space",
+                format_bytes(available),
+                format_bytes(min_free_bytes)
+            ));
+        }
+
+        eprint!(
+            "Paused: {} free, {} required. Free up space and press Enter to continue, or type 'q' to stop: ",
+            format_bytes(available),
+            format_bytes(min_free_bytes)
+        );
+        let _ = std::io::stderr().flush();
+        let mut answer = String::new();
+        if std::io::stdin().lock().read_line(&mut answer).unwrap_or(0) == 0 || answer.trim().eq_ignore_ascii_case("q") {
+            return Err(format!("stopped by user with {} free", format_bytes(available)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512 * 1024), "512 KB");
+        assert_eq!(format_bytes(300 * 1024 * 1024), "300 MB");
+        assert_eq!(format_bytes(3 * GIB / 2), "1.5 GB");
+    }
+
+    #[test]
+    fn test_estimate_required_bytes() {
+        assert_eq!(estimate_required_bytes(0, DEFAULT_BYTES_PER_DEPENDENT), 0);
+        assert_eq!(estimate_required_bytes(4, 100), 400);
+        assert_eq!(estimate_required_bytes(usize::MAX, u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_gc_staging_removes_target_dirs_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        for name in ["a-1.0.0", "b-2.0.0"] {
+            let target = tmp.path().join(name).join("target");
+            std::fs::create_dir_all(&target).unwrap();
+            std::fs::write(target.join("blob"), vec![0u8; 4096]).unwrap();
+            std::fs::write(tmp.path().join(name).join("Cargo.toml"), "[package]").unwrap();
+        }
+
+        assert_eq!(historical_bytes_per_dependent(tmp.path()), Some(4096 + 9));
+
+        let keep = tmp.path().join("b-2.0.0");
+        let reclaimed = gc_staging(tmp.path(), &["b".to_string(), "a-1".to_string()]);
+        assert_eq!(reclaimed, 4096);
+        assert!(!tmp.path().join("a-1.0.0/target").exists());
+        assert!(tmp.path().join("a-1.0.0/Cargo.toml").exists());
+        assert!(keep.join("target").exists());
+    }
+
+    #[test]
+    fn test_free_space_walks_up_to_existing_ancestor() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(free_space(&tmp.path().join("not/yet/created")).is_some());
+    }
+}
@@ -2,10 +2,39 @@
 //!
 //! This module provides the ability to run cargo-copter inside a Docker container
 //! for security isolation when testing untrusted crates.
+//!
+//! `--docker` re-runs the whole of cargo-copter in a container. `--docker-steps [IMAGE]`
+//! keeps cargo-copter on the host and runs each cargo step of each dependent in a
+//! throwaway container of IMAGE instead: the build directory is mounted at its host
+//! path, the offered crate read-only, and a CARGO_HOME under the staging directory keeps
+//! downloads between steps. Nothing else of the host (its environment, `~/.cargo`, other
+//! directories) is visible to build scripts and tests, and results come back through the
+//! normal reporting.
 
+use std::collections::BTreeMap;
 use std::env;
 use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::paths;
+use crate::project_config::DependentSettings;
+
+/// Image of `--docker-steps` without a value
+pub const DEFAULT_STEP_IMAGE: &str = "rust:latest";
+
+/// Flags whose values are filesystem paths; these are normalized before being
+/// handed to the bash wrapper so Windows paths survive `cd "$(dirname ...)"`.
+const PATH_FLAGS: &[&str] = &[
+    "--path",
+    "-p",
+    "--staging-dir",
+    "--dependent-paths",
+    "--dependent-dir",
+    "--report-template",
+    "--messages",
+    "--queue-dir",
+];
 
 /// The embedded docker wrapper script
 const EMBEDDED_DOCKER_SCRIPT: &str = include_str!("../copter-docker.sh");
@@ -36,36 +65,223 @@ fn find_local_script() -> Option<PathBuf> {
     None
 }
 
+/// One container run: the image and which dependents it builds
+#[derive(Debug, Clone, PartialEq)]
+struct DockerGroup {
+    /// `None` for the default image
+    image: Option<String>,
+    /// Dependents this group tests (image groups) or leaves to other groups (default group)
+    dependents: Vec<String>,
+    /// System packages of this group's dependents
+    system_packages: Vec<String>,
+}
+
+impl DockerGroup {
+    /// Extra cargo-copter arguments restricting the run to this group's dependents
+    fn filter_args(&self) -> Vec<String> {
+        if self.dependents.is_empty() {
+            return vec![];
+        }
+        let flag = if self.image.is_some() { "--only-dependents" } else { "--exclude" };
+        std::iter::once(flag.to_string()).chain(self.dependents.iter().cloned()).collect()
+    }
+
+    /// Environment for copter-docker.sh
+    fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![];
+        if !self.system_packages.is_empty() {
+            env.push(("COPTER_SYSTEM_PACKAGES", self.system_packages.join(" ")));
+        }
+        if let Some(image) = &self.image {
+            env.push(("COPTER_DOCKER_IMAGE", image.clone()));
+            env.push(("COPTER_PULL_ONLY", "1".to_string()));
+            let dest = paths::canonicalize(Path::new(".")).join("copter-report").join(image_dir_name(image));
+            env.push(("COPTER_REPORT_DEST", dest.display().to_string()));
+        }
+        env
+    }
+}
+
+/// Split dependents by `image` (copter.toml); the default group comes first
+fn docker_groups(settings: &BTreeMap<String, DependentSettings>) -> Vec<DockerGroup> {
+    let packages = |names: &mut dyn Iterator<Item = &String>| {
+        let mut all: Vec<String> = names.flat_map(|n| settings[n].system_deps.iter().cloned()).collect();
+        all.sort();
+        all.dedup();
+        all
+    };
+
+    let mut by_image: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (name, s) in settings {
+        if let Some(image) = &s.image {
+            by_image.entry(image).or_default().push(name.clone());
+        }
+    }
+    let overridden: Vec<String> = by_image.values().flatten().cloned().collect();
+    let mut groups = vec![DockerGroup {
+        image: None,
+        system_packages: packages(&mut settings.keys().filter(|n| !overridden.contains(n))),
+        dependents: overridden,
+    }];
+    for (image, names) in by_image {
+        groups.push(DockerGroup {
+            image: Some(image.to_string()),
+            system_packages: packages(&mut names.iter()),
+            dependents: names,
+        });
+    }
+    groups
+}
+
+/// `copter-report/image-<image with path separators replaced>`
+fn image_dir_name(image: &str) -> String {
+    let name: String =
+        image.chars().map(|c| if c.is_ascii_alphanumeric() || ".-_".contains(c) { c } else { '_' }).collect();
+    format!("image-{}", name)
+}
+
 /// Run cargo-copter inside Docker, passing through all arguments except --docker
-pub fn run_in_docker(original_args: &[String]) -> Result<ExitStatus, String> {
+///
+/// Dependents with their own `image` in copter.toml run in a separate container of that
+/// image, with reports in `copter-report/image-<image>/`; everything else runs in the
+/// default image. Each container gets its dependents' `system-deps` installed first.
+/// The exit status is the first failing run's, if any.
+pub fn run_in_docker(
+    original_args: &[String],
+    settings: &BTreeMap<String, DependentSettings>,
+) -> Result<ExitStatus, String> {
     if !is_docker_available() {
         return Err("Docker is not installed or not running. Please install Docker first.".to_string());
     }
 
-    // Filter out --docker from args
-    let filtered_args: Vec<&str> = original_args.iter().map(|s| s.as_str()).filter(|&arg| arg != "--docker").collect();
+    // Filter out --docker from args and normalize path values for bash
+    let normalized = normalize_path_args(original_args);
+    let filtered_args: Vec<String> = normalized.into_iter().filter(|arg| arg != "--docker").collect();
+
+    let local_script = find_local_script();
+    if let Some(script) = &local_script {
+        eprintln!("Using local script: {}", script.display());
+    }
+
+    let mut outcome: Option<ExitStatus> = None;
+    for group in docker_groups(settings) {
+        if let Some(image) = &group.image {
+            eprintln!("Testing {} in {}", group.dependents.join(", "), image);
+        }
+        let args: Vec<String> = filtered_args.iter().cloned().chain(group.filter_args()).collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let status = match &local_script {
+            Some(script) => run_local_script(script, &args, &group.env())?,
+            None => run_embedded_script(&args, &group.env())?,
+        };
+        if outcome.is_none_or(|s| s.success()) {
+            outcome = Some(status);
+        }
+    }
+    Ok(outcome.expect("there is always a default group"))
+}
+
+/// `--docker-steps`: the image cargo steps run in and what they see of the host
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct StepSandbox {
+    pub image: String,
+    /// Mounted writable: the staging directory (staged dependents, offered versions, CARGO_HOME)
+    pub writable: Vec<PathBuf>,
+    /// Mounted read-only: local offered versions and their path-dependency siblings
+    pub read_only: Vec<PathBuf>,
+    /// CARGO_HOME of every container, inside the staging directory
+    pub cargo_home: PathBuf,
+}
 
-    // Check for local script first
-    if let Some(local_script) = find_local_script() {
-        eprintln!("Using local script: {}", local_script.display());
-        return run_local_script(&local_script, &filtered_args);
+impl StepSandbox {
+    /// Containers of `image` seeing `staging_dir` and, read-only, `read_only`
+    pub fn new(image: &str, staging_dir: &Path, read_only: Vec<PathBuf>) -> Result<Self, String> {
+        if !is_docker_available() {
+            return Err("--docker-steps: Docker is not installed or not running".to_string());
+        }
+        let staging_dir = paths::canonicalize(staging_dir);
+        // Created here: a directory docker creates for a mount belongs to root
+        let cargo_home = staging_dir.join("docker-cargo-home");
+        std::fs::create_dir_all(&cargo_home)
+            .map_err(|e| format!("Failed to create {}: {}", cargo_home.display(), e))?;
+        Ok(Self { image: image.to_string(), writable: vec![staging_dir], read_only, cargo_home })
     }
 
-    // Use embedded script
-    run_embedded_script(&filtered_args)
+    /// `cmd` as a `docker run` named `name` of the same program, arguments, environment and
+    /// working directory (mounted writable, like `writable`; `read_only` read-only), each
+    /// directory at its host path; on Unix the container runs as the working directory's owner
+    pub fn wrap(&self, cmd: &Command, name: &str) -> Command {
+        let dir = paths::canonicalize(cmd.get_current_dir().unwrap_or(Path::new(".")));
+        let mut docker = Command::new("docker");
+        docker.args(["run", "--rm", "--name", name]);
+        #[cfg(unix)]
+        if let Ok(meta) = std::fs::metadata(&dir) {
+            use std::os::unix::fs::MetadataExt;
+            docker.arg("--user").arg(format!("{}:{}", meta.uid(), meta.gid()));
+        }
+        let mut writable: Vec<PathBuf> = self.writable.iter().map(|p| paths::canonicalize(p)).collect();
+        if !writable.iter().any(|w| dir.starts_with(w)) {
+            writable.insert(0, dir.clone());
+        }
+        for path in &writable {
+            docker.arg("-v").arg(format!("{}:{}", path.display(), path.display()));
+        }
+        let read_only = self.read_only.iter().map(|p| paths::canonicalize(p));
+        for path in read_only.filter(|p| !writable.iter().any(|w| p.starts_with(w))) {
+            docker.arg("-v").arg(format!("{}:{}:ro", path.display(), path.display()));
+        }
+        docker.arg("-e").arg(format!("CARGO_HOME={}", self.cargo_home.display()));
+        for (key, value) in cmd.get_envs() {
+            if let Some(value) = value {
+                docker.arg("-e").arg(format!("{}={}", key.to_string_lossy(), value.to_string_lossy()));
+            }
+        }
+        docker.arg("-w").arg(&dir).arg(&self.image).arg(cmd.get_program()).args(cmd.get_args());
+        docker
+    }
+}
+
+/// A container name unique to this process: "copter-<pid>-<n>"
+pub fn container_name() -> String {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    format!("copter-{}-{}", std::process::id(), NEXT.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Remove a step's container; killing `docker run` at a time limit leaves it running
+pub fn remove_container(name: &str) {
+    let _ = Command::new("docker").args(["rm", "--force", name]).output();
+}
+
+/// Normalize the values of path-taking flags (`--path C:\x`, `--path=C:\x`)
+fn normalize_path_args(args: &[String]) -> Vec<String> {
+    let mut out = Vec::with_capacity(args.len());
+    let mut in_path_value = false;
+    for arg in args {
+        if arg.starts_with('-') {
+            in_path_value = PATH_FLAGS.contains(&arg.as_str());
+            match arg.split_once('=') {
+                Some((flag, value)) if PATH_FLAGS.contains(&flag) => {
+                    out.push(format!("{}={}", flag, paths::to_shell_arg(value)));
+                    in_path_value = false;
+                }
+                _ => out.push(arg.clone()),
+            }
+        } else if in_path_value {
+            out.push(paths::to_shell_arg(arg));
+        } else {
+            out.push(arg.clone());
+        }
+    }
+    out
 }
 
 /// Run a local copter-docker.sh script
-fn run_local_script(script_path: &Path, args: &[&str]) -> Result<ExitStatus, String> {
-    Command::new("bash")
-        .arg(script_path)
-        .args(args)
-        .status()
-        .map_err(|e| format!("Failed to execute local docker script: {}", e))
+fn run_local_script(script_path: &Path, args: &[&str], env: &[(&str, String)]) -> Result<ExitStatus, String> {
+    script_command(script_path, args, env).status().map_err(|e| format!("Failed to execute local docker script: {}", e))
 }
 
 /// Run the embedded docker script
-fn run_embedded_script(args: &[&str]) -> Result<ExitStatus, String> {
+fn run_embedded_script(args: &[&str], env: &[(&str, String)]) -> Result<ExitStatus, String> {
     // Write embedded script to a temp file and execute it
     let temp_dir = env::temp_dir();
     let script_path = temp_dir.join("copter-docker-embedded.sh");
@@ -85,9 +301,7 @@ fn run_embedded_script(args: &[&str]) -> Result<ExitStatus, String> {
             .map_err(|e| format!("Failed to set script permissions: {}", e))?;
     }
 
-    let status = Command::new("bash")
-        .arg(&script_path)
-        .args(args)
+    let status = script_command(&script_path, args, env)
         .status()
         .map_err(|e| format!("Failed to execute docker script: {}", e))?;
 
@@ -97,6 +311,13 @@ fn run_embedded_script(args: &[&str]) -> Result<ExitStatus, String> {
     Ok(status)
 }
 
+/// `bash <script> <args>` with `env` set
+fn script_command(script_path: &Path, args: &[&str], env: &[(&str, String)]) -> Command {
+    let mut command = Command::new("bash");
+    command.arg(script_path).args(args).envs(env.iter().map(|(k, v)| (k, v)));
+    command
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,6 +328,84 @@ mod tests {
         assert!(EMBEDDED_DOCKER_SCRIPT.contains("cargo-copter"));
     }
 
+    #[test]
+    fn test_docker_groups() {
+        let settings = BTreeMap::from([
+            ("image".to_string(), DependentSettings { system_deps: vec!["nasm".to_string()], image: None }),
+            (
+                "cudarc".to_string(),
+                DependentSettings { system_deps: vec![], image: Some("nvidia/cuda:12.4-devel".to_string()) },
+            ),
+        ]);
+        let groups = docker_groups(&settings);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].filter_args(), vec!["--exclude", "cudarc"]);
+        assert_eq!(groups[0].system_packages, vec!["nasm"]);
+        assert_eq!(groups[1].filter_args(), vec!["--only-dependents", "cudarc"]);
+        assert!(groups[1].env().contains(&("COPTER_DOCKER_IMAGE", "nvidia/cuda:12.4-devel".to_string())));
+        assert_eq!(image_dir_name("nvidia/cuda:12.4-devel"), "image-nvidia_cuda_12.4-devel");
+
+        assert!(docker_groups(&BTreeMap::new())[0].filter_args().is_empty());
+    }
+
+    #[test]
+    fn test_normalize_path_args() {
+        let args: Vec<String> = ["--path", r"C:\src\rgb", "--dependent-paths", r"\\?\D:\a", r"D:\b", "--simple", "x"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            normalize_path_args(&args),
+            vec!["--path", "C:/src/rgb", "--dependent-paths", "D:/a", "D:/b", "--simple", "x"]
+        );
+        assert_eq!(normalize_path_args(&[r"--staging-dir=C:\s".to_string()]), vec!["--staging-dir=C:/s"]);
+    }
+
+    #[test]
+    fn test_step_sandbox_wraps_cargo() {
+        let (staging, local, offered) =
+            (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let [staging, local, offered] = [&staging, &local, &offered].map(|d| paths::canonicalize(d.path()));
+        let sandbox = StepSandbox {
+            image: "rust:1.92".to_string(),
+            writable: vec![staging.clone()],
+            read_only: vec![offered.clone(), staging.join("rgb-0.8.91")],
+            cargo_home: staging.join("docker-cargo-home"),
+        };
+        let args = |dir: &Path| {
+            let mut cargo = Command::new("cargo");
+            cargo.args(["check", "--message-format=json"]).env("RUSTFLAGS", "--cfg docsrs").current_dir(dir);
+            let docker = sandbox.wrap(&cargo, "copter-1-0");
+            docker.get_args().map(|a| a.to_string_lossy().into_owned()).collect::<Vec<_>>()
+        };
+        let after = |args: &[String], flag: &str| {
+            args.windows(2).filter(|w| w[0] == flag).map(|w| w[1].clone()).collect::<Vec<_>>()
+        };
+        let mount = |dir: &Path, mode: &str| format!("{}:{}{}", dir.display(), dir.display(), mode);
+
+        // A staged dependent: inside the staging directory
+        let staged = args(&staging);
+        assert_eq!(&staged[..4], ["run", "--rm", "--name", "copter-1-0"]);
+        assert_eq!(after(&staged, "-v"), [mount(&staging, ""), mount(&offered, ":ro")]);
+        assert_eq!(
+            after(&staged, "-e"),
+            [
+                format!("CARGO_HOME={}", staging.join("docker-cargo-home").display()),
+                "RUSTFLAGS=--cfg docsrs".to_string()
+            ]
+        );
+        assert!(staged.ends_with(&[
+            "-w".to_string(),
+            staging.display().to_string(),
+            "rust:1.92".to_string(),
+            "cargo".to_string(),
+            "check".to_string(),
+            "--message-format=json".to_string()
+        ]));
+        // A local dependent (--dependent-paths) is mounted too
+        assert_eq!(after(&args(&local), "-v"), [mount(&local, ""), mount(&staging, ""), mount(&offered, ":ro")]);
+    }
+
     #[test]
     fn test_find_local_script_returns_none_when_missing() {
         // This test assumes copter-docker.sh doesn't exist in the test directory
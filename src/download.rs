@@ -1,35 +1,54 @@
 /// Download and crate archive handling
 ///
 /// This module handles:
-/// - HTTP requests to crates.io
+/// - HTTP requests to crates.io (or the `--index-url` mirror)
 /// - Downloading .crate files
 /// - Extracting crate archives
 /// - Caching downloaded crates
 use flate2::read::GzDecoder;
-use log::debug;
 use semver::Version;
 use std::fs::{self, File};
-use std::io::{Read, Write};
+#[cfg(feature = "network")]
+use std::io::Read;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use tar::Archive;
 
 use crate::cli::default_cache_dir;
+use crate::error::CopterError;
+use crate::paths;
+use crate::registry::Registry;
 
+#[cfg(feature = "network")]
 const USER_AGENT: &str = "cargo-copter/0.1.1 (https://github.com/imazen/cargo-copter)";
 
 /// Get the crate cache directory
-fn crate_cache_dir() -> PathBuf {
-    default_cache_dir().join("crate-cache")
+///
+/// Crates from an alternative registry (`COPTER_REGISTRY_API`) are cached separately
+/// so they never shadow the real crates.io files of the same name and version.
+/// `--prefetch` and `--offline` keep them in their directory instead.
+fn crate_cache_dir(registry: &Registry) -> PathBuf {
+    if let Some(dir) = registry.crates_dir() {
+        return dir;
+    }
+    let base = default_cache_dir().join("crate-cache");
+    match &registry.api {
+        Some(api) => {
+            let key: String = api.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+            base.join("registries").join(key)
+        }
+        None => base,
+    }
 }
 
 /// Build a crates.io API URL
-pub fn crate_url(krate: &str, call: Option<&str>) -> String {
-    crate_url_with_parms(krate, call, &[])
+pub fn crate_url(registry: &Registry, krate: &str, call: Option<&str>) -> String {
+    crate_url_with_parms(registry, krate, call, &[])
 }
 
 /// Build a crates.io API URL with query parameters
-pub fn crate_url_with_parms(krate: &str, call: Option<&str>, parms: &[(&str, &str)]) -> String {
-    let url = format!("https://crates.io/api/v1/crates/{}", krate);
+pub fn crate_url_with_parms(registry: &Registry, krate: &str, call: Option<&str>, parms: &[(&str, &str)]) -> String {
+    let url = format!("{}/crates/{}", registry.api_base(), krate);
     let s = match call {
         Some(c) => format!("{}/{}", url, c),
         None => url,
@@ -45,9 +64,31 @@ pub fn crate_url_with_parms(krate: &str, call: Option<&str>, parms: &[(&str, &st
 }
 
 /// Download data from a URL using HTTP GET
+pub fn http_get_bytes(url: &str) -> Result<Vec<u8>, CopterError> {
+    http_get_bytes_with(url, &[])
+}
+
+/// HTTP GET with extra request headers (`Authorization`, `Accept`); registry lookups go
+/// through `Registry::get` instead, which refuses with `--offline`
+#[cfg(feature = "network")]
+pub fn http_get_bytes_with(url: &str, headers: &[(&str, &str)]) -> Result<Vec<u8>, CopterError> {
+    http_get_bytes_ureq(url, headers).map_err(|e| CopterError::Network(e.to_string()))
+}
+
+/// HTTP GET with extra request headers (`Authorization`, `Accept`)
+#[cfg(not(feature = "network"))]
+pub fn http_get_bytes_with(url: &str, _headers: &[(&str, &str)]) -> Result<Vec<u8>, CopterError> {
+    Err(CopterError::Network(format!("cannot fetch {}: {}", url, crate::api::OFFLINE_BUILD)))
+}
+
+#[cfg(feature = "network")]
 #[allow(clippy::result_large_err)]
-pub fn http_get_bytes(url: &str) -> Result<Vec<u8>, ureq::Error> {
-    let resp = ureq::get(url).header("User-Agent", USER_AGENT).call()?;
+fn http_get_bytes_ureq(url: &str, headers: &[(&str, &str)]) -> Result<Vec<u8>, ureq::Error> {
+    let mut request = ureq::get(url).header("User-Agent", USER_AGENT);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let resp = request.call()?;
     let len = resp
         .headers()
         .get("Content-Length")
@@ -64,8 +105,8 @@ pub struct CrateHandle(PathBuf);
 
 impl CrateHandle {
     /// Unpack the crate source to a directory
+    #[cfg(all(test, feature = "network"))]
     pub fn unpack_source_to(&self, path: &Path) -> std::io::Result<()> {
-        debug!("unpacking {:?} to {:?}", self.0, path);
         extract_crate_archive(&self.0, path)
     }
 
@@ -75,9 +116,63 @@ impl CrateHandle {
     }
 }
 
-/// Download a crate file (with caching)
-pub fn get_crate_handle(crate_name: &str, version: &Version) -> std::io::Result<CrateHandle> {
-    let cache_path = crate_cache_dir();
+/// Download a crate file (with caching), from the `--index-url` mirror's `dl` when one is set
+pub fn get_crate_handle(registry: &Registry, crate_name: &str, version: &Version) -> std::io::Result<CrateHandle> {
+    match registry.mirror.as_ref().filter(|_| registry.api.is_none()) {
+        Some(mirror) => {
+            let url = mirror.download_url(crate_name, &version.to_string());
+            fetch_url_to_cache(registry, &url, &crate_cache_dir(registry), crate_name, version)
+        }
+        None => fetch_crate_to_cache(registry, &crate_cache_dir(registry), crate_name, version),
+    }
+}
+
+/// The sha256 the registry index lists for `crate_name` `version`'s `.crate`, from the
+/// `--index-url` mirror's index or crates.io's; `None` where there is no index to ask
+/// (`COPTER_REGISTRY_API`, a mirror without an index, `--offline`, a build without the
+/// network clients)
+pub fn index_checksum(registry: &Registry, crate_name: &str, version: &Version) -> Result<Option<String>, String> {
+    if !cfg!(feature = "network") || registry.api.is_some() || registry.is_offline() {
+        return Ok(None);
+    }
+    let index = match &registry.mirror {
+        Some(mirror) => mirror.index.clone(),
+        None => Some(crate::mirror::CRATES_IO_INDEX.to_string()),
+    };
+    match index {
+        Some(index) => crate::mirror::index_checksum(&index, crate_name, &version.to_string()),
+        None => Ok(None),
+    }
+}
+
+/// Delete the downloaded `.crate` of `crate_name` `version`, so the next handle downloads it again
+pub fn forget_crate(registry: &Registry, crate_name: &str, version: &Version) -> std::io::Result<()> {
+    let crate_file = crate_cache_dir(registry).join(crate_name).join(format!("{}-{}.crate", crate_name, version));
+    match fs::remove_file(crate_file) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Download a crate file from the registry's API into `cache_path` (with caching)
+pub fn fetch_crate_to_cache(
+    registry: &Registry,
+    cache_path: &Path,
+    crate_name: &str,
+    version: &Version,
+) -> std::io::Result<CrateHandle> {
+    let url = format!("{}/crates/{}/{}/download", registry.api_base(), crate_name, version);
+    fetch_url_to_cache(registry, &url, cache_path, crate_name, version)
+}
+
+/// Download the `.crate` at `url` into `cache_path` (with caching)
+fn fetch_url_to_cache(
+    registry: &Registry,
+    url: &str,
+    cache_path: &Path,
+    crate_name: &str,
+    version: &Version,
+) -> std::io::Result<CrateHandle> {
     let crate_dir = cache_path.join(crate_name);
     fs::create_dir_all(&crate_dir)?;
 
@@ -85,8 +180,7 @@ pub fn get_crate_handle(crate_name: &str, version: &Version) -> std::io::Result<
 
     // Check if file exists
     if !crate_file.exists() {
-        let url = crate_url(crate_name, Some(&format!("{}/download", version)));
-        let body = http_get_bytes(&url).map_err(|e| std::io::Error::other(e.to_string()))?;
+        let body = registry.get(url).map_err(|e| std::io::Error::other(e.to_string()))?;
 
         // Write atomically: write to temp file, then rename
         let temp_file = crate_dir.join(format!("{}-{}.crate.tmp", crate_name, version));
@@ -101,31 +195,6 @@ pub fn get_crate_handle(crate_name: &str, version: &Version) -> std::io::Result<
     Ok(CrateHandle(crate_file))
 }
 
-/// Download and unpack a specific version of a crate for patching
-/// Returns the path to the unpacked source
-pub fn download_and_unpack_crate(crate_name: &str, version: &str, staging_dir: &Path) -> std::io::Result<PathBuf> {
-    debug!("Downloading and unpacking {} version {}", crate_name, version);
-
-    // Parse version
-    let vers =
-        Version::parse(version).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?;
-
-    // Download the crate
-    let crate_handle = get_crate_handle(crate_name, &vers)?;
-
-    // Unpack to staging directory
-    let unpack_path = staging_dir.join(format!("base-{}-{}", crate_name, version));
-    if !unpack_path.exists() {
-        fs::create_dir_all(&unpack_path)?;
-        crate_handle.unpack_source_to(&unpack_path)?;
-        debug!("Unpacked {} {} to {:?}", crate_name, version, unpack_path);
-    } else {
-        debug!("Using cached base crate at {:?}", unpack_path);
-    }
-
-    Ok(unpack_path)
-}
-
 /// Extract all files from a .crate file (gzipped tar) with --strip-components=1 behavior
 pub fn extract_crate_archive(crate_file: &Path, dest_dir: &Path) -> std::io::Result<()> {
     let file = File::open(crate_file)?;
@@ -143,41 +212,7 @@ pub fn extract_crate_archive(crate_file: &Path, dest_dir: &Path) -> std::io::Res
         }
 
         let dest_path = dest_dir.join(&stripped_pathbuf);
-
-        // Ensure parent directory exists
-        if let Some(parent) = dest_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
-        // Extract the entry
-        entry.unpack(&dest_path)?;
-    }
-
-    Ok(())
-}
-
-/// Extract only Cargo.toml from a .crate file with --strip-components=1 behavior
-pub fn extract_cargo_toml(crate_file: &Path, dest_dir: &Path) -> std::io::Result<()> {
-    let file = File::open(crate_file)?;
-    let decoder = GzDecoder::new(file);
-    let mut archive = Archive::new(decoder);
-
-    for entry in archive.entries()? {
-        let mut entry = entry?;
-        let path = entry.path()?;
-
-        // Check if this is a Cargo.toml file
-        if path.file_name() != Some(std::ffi::OsStr::new("Cargo.toml")) {
-            continue;
-        }
-
-        // Strip the first path component (equivalent to --strip-components=1)
-        let stripped_pathbuf = path.components().skip(1).collect::<PathBuf>();
-        if stripped_pathbuf.as_os_str().is_empty() {
-            continue;
-        }
-
-        let dest_path = dest_dir.join(&stripped_pathbuf);
+        paths::warn_if_exceeds_max_path(&dest_path);
 
         // Ensure parent directory exists
         if let Some(parent) = dest_path.parent() {
@@ -0,0 +1,374 @@
+/// Running copter from Rust: `CopterOptions` in, `CopterReport` out
+///
+/// Release tooling that wants copter's verdict used to shell out and scrape the table.
+/// `CopterOptions` is a builder over the same settings as the command line; the common
+/// ones have `with_*` methods, and `from_args` takes anything else in command-line form.
+/// `CliArgs` itself stays private. `run` resolves the matrix (`build_matrix`), tests it
+/// (`run_matrix`) and returns the rows with what the exports need, without printing the
+/// table or writing `copter-report/`. `CopterReport::write_json` and `write_junit` write the same report.json
+/// and junit.xml the binary does. Rows keep the report.json schema
+/// (`schema/copter-report.schema.json`), which is versioned.
+use crate::artifacts::SaveArtifacts;
+use crate::bridge;
+use crate::cli::CliArgs;
+use crate::error::CopterError;
+use crate::messages::Catalog;
+use crate::report::{self, TestSummary};
+use crate::run_metadata::RunMetadata;
+use crate::types::{OfferedRow, Steps, TestMatrix, TestResult};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// What to test and how; `run` tests it
+#[derive(Debug, Clone)]
+pub struct CopterOptions {
+    args: CliArgs,
+}
+
+impl CopterOptions {
+    /// Test the local crate at `path` (a directory or its Cargo.toml), like `--path`
+    pub fn for_path(path: impl Into<PathBuf>) -> Self {
+        let mut options = Self::defaults();
+        options.args.path = Some(path.into());
+        options
+    }
+
+    /// Test the published crate `name`, like `--crate`
+    pub fn for_crate(name: impl Into<String>) -> Self {
+        let mut options = Self::defaults();
+        options.args.crate_name = Some(name.into());
+        options
+    }
+
+    /// Options from command-line arguments, without the program name:
+    /// `from_args(["--path", "../rgb", "--top-dependents", "20"])`
+    pub fn from_args<I, T>(args: I) -> Result<Self, CopterError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let args = std::iter::once("cargo-copter".to_string()).chain(args.into_iter().map(Into::into));
+        let args = CliArgs::try_parse_args(args).map_err(|e| CopterError::Config(e.to_string()))?;
+        Ok(CopterOptions { args })
+    }
+
+    fn defaults() -> Self {
+        let args = CliArgs::try_parse_args(["cargo-copter"]).expect("no arguments always parse");
+        CopterOptions { args }
+    }
+
+    /// Dependents to test, `name` or `name:version` (`--dependents`)
+    pub fn with_dependents<S: Into<String>>(mut self, dependents: impl IntoIterator<Item = S>) -> Self {
+        self.args.dependents = dependents.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Test the `n` most downloaded dependents (`--top-dependents`)
+    pub fn with_top_dependents(mut self, n: usize) -> Self {
+        self.args.top_dependents = n;
+        self
+    }
+
+    /// Base crate versions to offer: versions or keywords like `latest` (`--test-versions`)
+    pub fn with_test_versions<S: Into<String>>(mut self, versions: impl IntoIterator<Item = S>) -> Self {
+        self.args.test_versions = versions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Steps to run, e.g. `Steps::through(CommandType::Check)` (`--steps`)
+    pub fn with_steps(mut self, steps: Steps) -> Self {
+        self.args.steps = Some(steps);
+        self.args.only_fetch = false;
+        self.args.only_check = false;
+        self
+    }
+
+    /// Where dependents are unpacked and built (`--staging-dir`)
+    pub fn with_staging_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.args.staging_dir = Some(dir.into());
+        self
+    }
+
+    /// Dependents tested at once (`--parallel`)
+    pub fn with_parallel(mut self, workers: usize) -> Self {
+        self.args.parallel = workers;
+        self
+    }
+
+    /// How much failure output the report keeps (`--save-artifacts`)
+    pub fn with_save_artifacts(mut self, level: SaveArtifacts) -> Self {
+        self.args.save_artifacts = level;
+        self
+    }
+
+    /// Bytes of a failure message kept in report.json, 0 for all (`--json-error-limit`)
+    pub fn with_json_error_limit(mut self, bytes: usize) -> Self {
+        self.args.json_error_limit = bytes;
+        self
+    }
+
+    /// Name for the run in the report (`--label`)
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.args.label = Some(label.into());
+        self
+    }
+
+    /// Message catalog for the status labels in the exports (`--messages`)
+    pub fn with_messages(mut self, path: impl Into<PathBuf>) -> Self {
+        self.args.messages = Some(path.into());
+        self
+    }
+
+    /// Look up and download crates from a crates.io mirror (`--index-url`)
+    pub fn with_index_url(mut self, url: impl Into<String>) -> Self {
+        self.args.index_url = Some(url.into());
+        self
+    }
+
+    /// Run without network, from a `--prefetch` directory (`--offline`)
+    pub fn with_offline(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.args.offline = Some(dir.into());
+        self
+    }
+
+    /// Test with each of these toolchains, e.g. `stable`, `1.70` (`--toolchains`)
+    pub fn with_toolchains<S: Into<String>>(mut self, toolchains: impl IntoIterator<Item = S>) -> Self {
+        self.args.toolchains = toolchains.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Check every dependent before testing any (`--check-first`)
+    pub fn with_check_first(mut self, check_first: bool) -> Self {
+        self.args.check_first = check_first;
+        self
+    }
+
+    /// Resolve and test the matrix
+    pub fn run(self) -> Result<CopterReport, CopterError> {
+        self.run_with(|_| {})
+    }
+
+    /// Resolve and test the matrix, handing each row to `on_row` as it finishes (with
+    /// `--check-first` or `--toolchains`, each pass's rows)
+    pub fn run_with(self, on_row: impl FnMut(&OfferedRow)) -> Result<CopterReport, CopterError> {
+        let matrix = self.build_matrix()?;
+        self.run_matrix(matrix, on_row)
+    }
+
+    /// The matrix `run` would test: versions resolved, dependents listed, nothing run yet
+    pub fn build_matrix(&self) -> Result<TestMatrix, CopterError> {
+        let args = self.resolved_args()?;
+        let registry = crate::registry::Registry::from_args(&args)?;
+        crate::config::build_test_matrix(&args, &registry)
+    }
+
+    /// Test `matrix` (from `build_matrix`, changed as needed) with these settings, handing
+    /// each row to `on_row` as it finishes
+    pub fn run_matrix(
+        self,
+        matrix: TestMatrix,
+        mut on_row: impl FnMut(&OfferedRow),
+    ) -> Result<CopterReport, CopterError> {
+        let args = self.resolved_args()?;
+        let messages = match &args.messages {
+            Some(path) => crate::messages::load(path).map_err(CopterError::Config)?,
+            None => Catalog::default(),
+        };
+        let mut run = RunMetadata { label: args.label.clone(), ..RunMetadata::collect(&matrix) };
+        let base_crate = matrix.base_crate.clone();
+        let base_version = matrix.base_versions.first().map(|v| v.crate_ref.version.display()).unwrap_or_default();
+        let dependents = matrix.dependents.len();
+
+        let started = Instant::now();
+        let executor = crate::runner::Local { failure_log: None };
+        let mut run_pass = |pass: &TestMatrix, on_result: &mut dyn FnMut(&TestResult)| {
+            let scheduler = crate::scheduler::build(args.schedule, args.parallel, pass, &[]);
+            crate::runner::run_with(pass.clone(), scheduler, &executor, on_result)
+        };
+        let mut on_result = |result: &TestResult| on_row(&bridge::test_result_to_offered_row(result));
+        let results = if args.check_first {
+            crate::lanes::run(&matrix, &mut run_pass, &mut on_result)?
+        } else if !matrix.toolchains.is_empty() {
+            crate::toolchains::run(&matrix, &matrix.toolchains, &mut run_pass, &mut on_result)?
+        } else {
+            run_pass(&matrix, &mut on_result)?
+        };
+        run.wall_secs = Some(started.elapsed().as_secs_f64());
+
+        Ok(CopterReport {
+            base_crate,
+            base_version,
+            dependents,
+            rows: results.iter().map(bridge::test_result_to_offered_row).collect(),
+            run,
+            save_artifacts: args.save_artifacts,
+            json_error_limit: args.json_error_limit,
+            messages,
+        })
+    }
+
+    /// The settings with the base crate's copter.toml filled in, checked
+    fn resolved_args(&self) -> Result<CliArgs, CopterError> {
+        let mut args = self.args.clone();
+        if let Some(option) = unsupported(&args) {
+            return Err(CopterError::Config(format!("{} needs the cargo-copter binary", option)));
+        }
+        crate::project_config::load_into(&mut args).map_err(CopterError::Config)?;
+        args.apply_prefetch();
+        args.validate().map_err(CopterError::Config)?;
+        Ok(args)
+    }
+}
+
+/// The first option in `args` that `run` can't honor: subcommands and `--docker` re-run
+/// the binary, `--resume` reads its `copter-report/`, `--docker-steps` and `--backend`
+/// need a sandbox or workers set up around the run
+fn unsupported(args: &CliArgs) -> Option<&'static str> {
+    if args.command.is_some() {
+        Some("a subcommand")
+    } else if args.docker {
+        Some("--docker")
+    } else if args.docker_steps.is_some() {
+        Some("--docker-steps")
+    } else if args.resume {
+        Some("--resume")
+    } else if args.backend != crate::cli::Backend::Local {
+        Some("--backend")
+    } else {
+        None
+    }
+}
+
+/// The rows of a finished run, and what the exports need
+#[derive(Debug, Clone)]
+pub struct CopterReport {
+    pub base_crate: String,
+    /// The baseline version
+    pub base_version: String,
+    /// Dependents in the matrix
+    pub dependents: usize,
+    /// One row per dependent and base version, baseline rows included
+    pub rows: Vec<OfferedRow>,
+    pub run: RunMetadata,
+    save_artifacts: SaveArtifacts,
+    json_error_limit: usize,
+    /// Status labels in the exports (`--messages`)
+    messages: Catalog,
+}
+
+impl CopterReport {
+    /// Rows where a dependent passed with the baseline and fails with an offered version
+    pub fn regressions(&self) -> impl Iterator<Item = &OfferedRow> {
+        self.rows.iter().filter(|row| row.is_regression())
+    }
+
+    pub fn has_regressions(&self) -> bool {
+        self.regressions().next().is_some()
+    }
+
+    /// Passed, regressed, broken, environment failures and skipped rows
+    pub fn summary(&self) -> TestSummary {
+        report::summarize_offered_rows(&self.rows)
+    }
+
+    /// The report.json document
+    pub fn to_json(&self) -> serde_json::Value {
+        report::json_report(
+            &self.rows,
+            &self.base_crate,
+            &self.base_version,
+            self.dependents,
+            &self.run,
+            self.save_artifacts,
+            &self.messages,
+        )
+    }
+
+    /// Write report.json to `path` (errors past the `--json-error-limit` go to a directory beside it)
+    pub fn write_json(&self, path: &Path) -> Result<(), CopterError> {
+        let (rows, limit) = (&self.rows, self.json_error_limit);
+        report::export_json_report(
+            rows,
+            &path.to_path_buf(),
+            &self.base_crate,
+            &self.base_version,
+            self.dependents,
+            &self.run,
+            self.save_artifacts,
+            limit,
+            &self.messages,
+        )
+        .map_err(|e| CopterError::Internal(format!("Failed to write {}: {}", path.display(), e)))
+    }
+
+    /// Write junit.xml to `path`
+    pub fn write_junit(&self, path: &Path) -> Result<(), CopterError> {
+        report::export_junit_report(&self.rows, path, &self.base_crate, &self.run, self.save_artifacts, &self.messages)
+            .map_err(|e| CopterError::Internal(format!("Failed to write {}: {}", path.display(), e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CommandType;
+
+    #[test]
+    fn test_options_builder_matches_command_line() {
+        let built = CopterOptions::for_path("../rgb")
+            .with_dependents(["image", "ravif:0.11.5"])
+            .with_test_versions(["0.8.50", "latest"])
+            .with_steps(Steps::through(CommandType::Check))
+            .with_parallel(4);
+        let parsed = CopterOptions::from_args([
+            "--path",
+            "../rgb",
+            "--dependents",
+            "image",
+            "ravif:0.11.5",
+            "--test-versions",
+            "0.8.50 latest",
+            "--steps",
+            "fetch,check",
+            "--parallel",
+            "4",
+        ])
+        .unwrap();
+        assert_eq!(format!("{:?}", built.args), format!("{:?}", parsed.args));
+        assert_eq!(CopterOptions::for_crate("rgb").args.crate_name.as_deref(), Some("rgb"));
+
+        let built = CopterOptions::for_crate("rgb")
+            .with_save_artifacts(SaveArtifacts::All)
+            .with_json_error_limit(0)
+            .with_label("nightly")
+            .with_toolchains(["1.70", "stable"]);
+        let parsed = CopterOptions::from_args([
+            "--crate",
+            "rgb",
+            "--save-artifacts",
+            "all",
+            "--json-error-limit",
+            "0",
+            "--label",
+            "nightly",
+            "--toolchains",
+            "1.70,stable",
+        ])
+        .unwrap();
+        assert_eq!(format!("{:?}", built.args), format!("{:?}", parsed.args));
+
+        let err = CopterOptions::from_args(["--no-such-option"]).unwrap_err();
+        assert_eq!(err.kind(), "configuration");
+    }
+
+    #[test]
+    fn test_run_refuses_binary_only_options() {
+        for args in
+            [&["--path", "../rgb", "--resume"][..], &["--crate", "rgb", "--docker"], &["diff", "a.json", "b.json"]]
+        {
+            let err = CopterOptions::from_args(args.iter().copied()).unwrap().run().unwrap_err();
+            assert_eq!(err.kind(), "configuration", "{:?}", args);
+            assert!(err.to_string().contains("needs the cargo-copter binary"), "{}", err);
+        }
+    }
+}
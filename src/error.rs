@@ -0,0 +1,119 @@
+/// Failures of copter itself, as opposed to a dependent's build
+///
+/// A regression exits with -2; everything here means the run couldn't do its job, and
+/// the kind says whose move it is: fix the invocation (configuration), retry later
+/// (network, git), look at the toolchain (cargo), or report a bug (patching, internal).
+/// Each kind has its own exit code, and with `--json` the error is printed as
+/// `{"error": {"kind": "network", "message": "…"}}` so CI can tell a broken crate
+/// from a broken run without parsing text. Functions that still return
+/// `Result<_, String>` can `?` a `CopterError`; it becomes its message.
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CopterError {
+    /// Flags, manifests, copter.toml: something to fix in how copter was run
+    Config(String),
+    /// crates.io, a mirror or a download failed
+    Network(String),
+    /// Cloning or checking out a git dependent failed
+    Git(String),
+    /// cargo couldn't be started, or gave output copter can't use
+    Cargo(String),
+    /// Editing or restoring a staged manifest or cargo config failed
+    Patching(String),
+    /// Unexpected state in copter
+    Internal(String),
+}
+
+impl CopterError {
+    /// "configuration", "network", "git", "cargo", "patching" or "internal"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CopterError::Config(_) => "configuration",
+            CopterError::Network(_) => "network",
+            CopterError::Git(_) => "git",
+            CopterError::Cargo(_) => "cargo",
+            CopterError::Patching(_) => "patching",
+            CopterError::Internal(_) => "internal",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            CopterError::Config(m)
+            | CopterError::Network(m)
+            | CopterError::Git(m)
+            | CopterError::Cargo(m)
+            | CopterError::Patching(m)
+            | CopterError::Internal(m) => m,
+        }
+    }
+
+    /// Process exit code (regressions exit with -2, success with 0)
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CopterError::Internal(_) => 1,
+            CopterError::Config(_) => 2,
+            CopterError::Network(_) => 3,
+            CopterError::Git(_) => 4,
+            CopterError::Cargo(_) => 5,
+            CopterError::Patching(_) => 6,
+        }
+    }
+
+    /// The same error with "`context`: " in front of its message
+    pub fn context(self, context: impl fmt::Display) -> Self {
+        let with = |message: String| format!("{}: {}", context, message);
+        match self {
+            CopterError::Config(m) => CopterError::Config(with(m)),
+            CopterError::Network(m) => CopterError::Network(with(m)),
+            CopterError::Git(m) => CopterError::Git(with(m)),
+            CopterError::Cargo(m) => CopterError::Cargo(with(m)),
+            CopterError::Patching(m) => CopterError::Patching(with(m)),
+            CopterError::Internal(m) => CopterError::Internal(with(m)),
+        }
+    }
+
+    /// `{"error": {"kind": …, "message": …, "exit_code": …}}`
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "error": { "kind": self.kind(), "message": self.message(), "exit_code": self.exit_code() }
+        })
+    }
+}
+
+impl fmt::Display for CopterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for CopterError {}
+
+impl From<CopterError> for String {
+    fn from(e: CopterError) -> Self {
+        e.message().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_context_and_exit_code() {
+        let e = CopterError::Network("HTTP 503".to_string()).context("fetching dependents of rgb");
+        assert_eq!(e.to_string(), "fetching dependents of rgb: HTTP 503");
+        assert_eq!((e.kind(), e.exit_code()), ("network", 3));
+        assert_eq!(
+            e.to_json(),
+            serde_json::json!({"error": {"kind": "network", "message": "fetching dependents of rgb: HTTP 503", "exit_code": 3}})
+        );
+
+        fn legacy() -> Result<(), String> {
+            Err(CopterError::Config("--path: no Cargo.toml".to_string()))?;
+            Ok(())
+        }
+        assert_eq!(legacy().unwrap_err(), "--path: no Cargo.toml");
+    }
+}
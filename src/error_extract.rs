@@ -9,6 +9,9 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CargoMessage {
     pub reason: String,
+    /// The package being built (`path+file:///ws/viewer#0.3.0`, `viewer 0.3.0 (path+file://…)`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<CompilerMessage>,
 }
@@ -61,6 +64,9 @@ pub struct Diagnostic {
     pub message: String,
     pub rendered: String,
     pub primary_span: Option<SpanInfo>,
+    /// Name of the package it was reported for (a workspace member, or one of its dependencies)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -72,8 +78,8 @@ pub enum DiagnosticLevel {
     Other(String),
 }
 
-impl DiagnosticLevel {
-    pub fn from_str(s: &str) -> Self {
+impl From<&str> for DiagnosticLevel {
+    fn from(s: &str) -> Self {
         match s {
             "error" => DiagnosticLevel::Error,
             "warning" => DiagnosticLevel::Warning,
@@ -82,7 +88,9 @@ impl DiagnosticLevel {
             other => DiagnosticLevel::Other(other.to_string()),
         }
     }
+}
 
+impl DiagnosticLevel {
     pub fn is_error(&self) -> bool {
         matches!(self, DiagnosticLevel::Error)
     }
@@ -108,8 +116,9 @@ pub fn parse_cargo_json(output: &str) -> Vec<Diagnostic> {
         match serde_json::from_str::<CargoMessage>(line) {
             Ok(msg) if msg.reason == "compiler-message" => {
                 if let Some(compiler_msg) = msg.message
-                    && let Some(diag) = convert_compiler_message(&compiler_msg)
+                    && let Some(mut diag) = convert_compiler_message(&compiler_msg)
                 {
+                    diag.package = msg.package_id.as_deref().and_then(package_name);
                     diagnostics.push(diag);
                 }
             }
@@ -121,7 +130,7 @@ pub fn parse_cargo_json(output: &str) -> Vec<Diagnostic> {
 }
 
 fn convert_compiler_message(msg: &CompilerMessage) -> Option<Diagnostic> {
-    let level = DiagnosticLevel::from_str(&msg.level);
+    let level = DiagnosticLevel::from(msg.level.as_str());
 
     // Only capture errors and warnings, not help/note (those are children)
     if !matches!(level, DiagnosticLevel::Error | DiagnosticLevel::Warning) {
@@ -141,7 +150,19 @@ fn convert_compiler_message(msg: &CompilerMessage) -> Option<Diagnostic> {
     // Use rendered output if available, otherwise construct from message
     let rendered = msg.rendered.clone().unwrap_or_else(|| format_diagnostic_text(msg));
 
-    Some(Diagnostic { level, code, message: msg.message.clone(), rendered, primary_span })
+    Some(Diagnostic { level, code, message: msg.message.clone(), rendered, primary_span, package: None })
+}
+
+/// The package name in a cargo package ID, in either of its formats:
+/// `path+file:///ws/viewer-cli#viewer-cli@0.3.0`, `path+file:///ws/viewer#0.3.0` (name
+/// from the directory) or `viewer 0.3.0 (path+file:///ws/viewer)`
+pub fn package_name(package_id: &str) -> Option<String> {
+    let name = match package_id.rsplit_once('#') {
+        Some((_, fragment)) if fragment.contains('@') => fragment.split('@').next()?,
+        Some((url, _)) => url.trim_end_matches('/').rsplit('/').next()?,
+        None => package_id.split(' ').next()?,
+    };
+    (!name.is_empty()).then(|| name.to_string())
 }
 
 fn format_diagnostic_text(msg: &CompilerMessage) -> String {
@@ -382,6 +403,7 @@ note: there are multiple different versions of crate `rgb` in the dependency gra
                     column: 5,
                     label: Some("not found in this scope".to_string()),
                 }),
+                package: None,
             },
             Diagnostic {
                 level: DiagnosticLevel::Warning,
@@ -389,6 +411,7 @@ note: there are multiple different versions of crate `rgb` in the dependency gra
                 message: "unused variable".to_string(),
                 rendered: "warning: unused variable".to_string(),
                 primary_span: None,
+                package: None,
             },
         ];
 
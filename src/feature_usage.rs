@@ -0,0 +1,142 @@
+/// Which features of the base crate the tested dependents enable
+///
+/// Each result records the base crate's features as cargo resolved them for the
+/// dependent's build (`resolve.nodes[].features` of `cargo metadata`: what the dependent
+/// asks for, plus `default`'s members and whatever its other dependencies enable). The
+/// summary, report.md and report.json's `feature_usage` count the dependents enabling
+/// each one, which is what to look at before removing a feature or taking it out of
+/// `default`: a feature nobody enables can go, one that half the dependents only get
+/// through `default` needs a migration note.
+use crate::types::OfferedRow;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Features per dependent, counted once per dependent (not per offered version)
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct FeatureUsage {
+    /// Dependents whose features are known (their fetch succeeded)
+    pub dependents: usize,
+    /// Most enabled first
+    pub features: Vec<FeatureCount>,
+    /// Dependents enabling no features at all, not even `default`
+    pub none: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct FeatureCount {
+    pub feature: String,
+    pub dependents: usize,
+}
+
+impl FeatureUsage {
+    pub fn from_rows(rows: &[OfferedRow]) -> Self {
+        // The first row that has them (the baseline, when it fetched) speaks for the dependent
+        let mut per_dependent: BTreeMap<(&str, &str), &Vec<String>> = BTreeMap::new();
+        for row in rows {
+            if let Some(features) = &row.primary.base_features {
+                per_dependent
+                    .entry((row.primary.dependent_name.as_str(), row.primary.dependent_version.as_str()))
+                    .or_insert(features);
+            }
+        }
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for feature in per_dependent.values().flat_map(|features| features.iter()) {
+            *counts.entry(feature).or_default() += 1;
+        }
+        let mut features: Vec<FeatureCount> = counts
+            .into_iter()
+            .map(|(feature, dependents)| FeatureCount { feature: feature.to_string(), dependents })
+            .collect();
+        features.sort_by_key(|f| std::cmp::Reverse(f.dependents));
+        FeatureUsage {
+            dependents: per_dependent.len(),
+            none: per_dependent.values().filter(|features| features.is_empty()).count(),
+            features,
+        }
+    }
+
+    /// "Features of rgb enabled by 42 dependents: default 40, serde 12; 2 enable none"
+    pub fn summary_line(&self, crate_name: &str) -> Option<String> {
+        if self.dependents == 0 {
+            return None;
+        }
+        let counts: Vec<String> = self.features.iter().map(|f| format!("{} {}", f.feature, f.dependents)).collect();
+        let mut line = format!("Features of {} enabled by {} dependents: ", crate_name, self.dependents);
+        line.push_str(&if counts.is_empty() { "none".to_string() } else { counts.join(", ") });
+        if self.none > 0 && !counts.is_empty() {
+            line.push_str(&format!("; {} enable none", self.none));
+        }
+        Some(line)
+    }
+
+    /// report.md's "Feature Usage" section
+    pub fn write_markdown(&self, out: &mut impl Write, crate_name: &str) -> std::io::Result<()> {
+        if self.dependents == 0 {
+            return Ok(());
+        }
+        writeln!(out, "## Feature Usage\n")?;
+        writeln!(
+            out,
+            "Features of {} enabled in the builds of {} dependents, as cargo resolved them.\n",
+            crate_name, self.dependents
+        )?;
+        writeln!(out, "| Feature | Dependents |")?;
+        writeln!(out, "|---------|------------|")?;
+        for f in &self.features {
+            writeln!(out, "| `{}` | {} |", f.feature, f.dependents)?;
+        }
+        if self.none > 0 {
+            writeln!(out, "| (none) | {} |", self.none)?;
+        }
+        writeln!(out)
+    }
+}
+
+/// A row's features for report.md: "default, serde", "none", or "?" when unknown
+pub fn cell(row: &OfferedRow) -> String {
+    match &row.primary.base_features {
+        Some(features) if features.is_empty() => "none".to_string(),
+        Some(features) => features.join(", "),
+        None => "?".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(dependent: &str, offered: Option<&str>, features: Option<&[&str]>) -> OfferedRow {
+        let row = crate::test_rows::row(dependent).offered(offered).resolved("0.8.91").steps(&[]);
+        match features {
+            Some(features) => row.features(features),
+            None => row,
+        }
+        .build()
+    }
+
+    #[test]
+    fn test_dependents_counted_once_per_feature() {
+        let rows = vec![
+            row("image", None, Some(&["default", "serde"])),
+            row("image", Some("0.9.0"), Some(&["default", "serde"])),
+            row("png", None, Some(&["default"])),
+            row("ravif", None, Some(&[])),
+            row("broken", None, None),
+        ];
+        let usage = FeatureUsage::from_rows(&rows);
+        assert_eq!((usage.dependents, usage.none), (3, 1));
+        assert_eq!(
+            usage.summary_line("rgb").unwrap(),
+            "Features of rgb enabled by 3 dependents: default 2, serde 1; 1 enable none"
+        );
+        assert_eq!(
+            (cell(&rows[0]), cell(&rows[3]), cell(&rows[4])),
+            ("default, serde".into(), "none".into(), "?".into())
+        );
+
+        let mut md = Vec::new();
+        usage.write_markdown(&mut md, "rgb").unwrap();
+        assert!(String::from_utf8(md).unwrap().ends_with("| `default` | 2 |\n| `serde` | 1 |\n| (none) | 1 |\n\n"));
+        assert_eq!(FeatureUsage::from_rows(&[row("broken", None, None)]).summary_line("rgb"), None);
+    }
+}
@@ -0,0 +1,190 @@
+/// Run-to-run flakiness of dependents, from history
+///
+/// Every run appends one line per result to `copter-report/history/outcomes.jsonl`:
+/// the dependent and its version, the base crate version it was tested with (a local
+/// WIP is told apart by a hash of its sources), whether every step passed, the row's
+/// status and time in cargo, when the run finished and, for the WIP, its commit. A
+/// dependent whose result flips between runs with the *same* base crate and dependent
+/// versions has a flaky build or test suite. Its score is the share of such repeat
+/// observations that flipped, and failing rows of dependents with a score get a
+/// "⚠ historically flaky (34%)" note, so one red cell from a known-flaky suite is
+/// weighed accordingly.
+use crate::run_metadata::RunMetadata;
+use crate::types::{CrateSource, OfferedRow, RowStatus, TestMatrix};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Repeat observations needed before a dependent gets a score
+pub const MIN_REPEATS: usize = 3;
+
+/// One result of one run
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Outcome {
+    pub base_crate: String,
+    /// Base crate version tested, `0.8.91+<source hash>` for a local WIP
+    pub base: String,
+    pub dependent: String,
+    pub dependent_version: String,
+    pub passed: bool,
+    /// When the run finished, RFC 3339 (not kept by older copters)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub run: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<RowStatus>,
+    /// Seconds the row's steps took
+    #[serde(default)]
+    pub duration: f64,
+    /// `git describe` of a local base crate ("v0.8.91-3-gabc1234 (dirty)")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// `--label` given for the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// Flip counts per dependent
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Flakiness {
+    /// dependent → (flips, repeat observations)
+    scores: BTreeMap<String, (usize, usize)>,
+}
+
+impl Flakiness {
+    /// Scores for `base_crate`'s dependents from earlier runs
+    pub fn load(report_dir: &Path, base_crate: &str) -> Self {
+        Self::from_outcomes(&load_outcomes(report_dir, Some(base_crate)))
+    }
+
+    fn from_outcomes(outcomes: &[Outcome]) -> Self {
+        let mut last: BTreeMap<(&str, &str, &str), bool> = BTreeMap::new();
+        let mut scores: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+        for o in outcomes {
+            let key = (o.dependent.as_str(), o.dependent_version.as_str(), o.base.as_str());
+            if let Some(previous) = last.insert(key, o.passed) {
+                let score = scores.entry(o.dependent.clone()).or_default();
+                score.0 += usize::from(previous != o.passed);
+                score.1 += 1;
+            }
+        }
+        Flakiness { scores }
+    }
+
+    /// Percentage of repeat results that flipped, once there are enough of them
+    pub fn percent(&self, dependent: &str) -> Option<usize> {
+        let (flips, repeats) = *self.scores.get(dependent)?;
+        (repeats >= MIN_REPEATS && flips > 0).then(|| (flips * 100 + repeats / 2) / repeats)
+    }
+
+    /// "⚠ historically flaky (34%)" for a failing row of a flaky dependent
+    pub fn note(&self, row: &OfferedRow) -> Option<String> {
+        if !row.status().is_failure() {
+            return None;
+        }
+        self.percent(&row.primary.dependent_name).map(|p| format!("⚠ historically flaky ({}%)", p))
+    }
+}
+
+/// Every recorded outcome, oldest first, optionally only `base_crate`'s
+pub fn load_outcomes(report_dir: &Path, base_crate: Option<&str>) -> Vec<Outcome> {
+    let text = std::fs::read_to_string(log_path(report_dir)).unwrap_or_default();
+    text.lines()
+        .filter_map(|line| serde_json::from_str::<Outcome>(line).ok())
+        .filter(|o| base_crate.is_none_or(|name| o.base_crate == name))
+        .collect()
+}
+
+/// Append this run's results to the outcome log
+pub fn record(report_dir: &Path, matrix: &TestMatrix, rows: &[OfferedRow], run: &RunMetadata) -> Result<(), String> {
+    let commit = run.git_describe.as_ref().map(|_| run.git_summary());
+    let base_keys: Vec<(String, String, bool)> = matrix
+        .base_versions
+        .iter()
+        .map(|v| {
+            let version = v.crate_ref.version.display();
+            let (key, local) = match &v.crate_ref.source {
+                CrateSource::Local { path } => (format!("{}+{}", version, crate::history::source_hash(path)), true),
+                _ => (version.clone(), false),
+            };
+            (version, key, local)
+        })
+        .collect();
+    let baseline = matrix.base_versions.iter().find(|v| v.is_baseline).map(|v| v.crate_ref.version.display());
+
+    let mut lines = String::new();
+    for row in rows {
+        let version = row.offered.as_ref().map(|o| o.version.clone()).or_else(|| baseline.clone());
+        let Some((_, base, local)) = version.and_then(|v| base_keys.iter().find(|(display, _, _)| *display == v))
+        else {
+            continue;
+        };
+        let outcome = Outcome {
+            base_crate: matrix.base_crate.clone(),
+            base: base.clone(),
+            dependent: row.primary.dependent_name.clone(),
+            dependent_version: row.primary.dependent_version.clone(),
+            passed: row.test_passed(),
+            run: run.generated.clone(),
+            status: Some(row.status()),
+            duration: row.test.commands.iter().map(|cmd| cmd.result.duration).sum(),
+            commit: commit.clone().filter(|_| *local),
+            label: run.label.clone(),
+        };
+        lines.push_str(&serde_json::to_string(&outcome).map_err(|e| e.to_string())?);
+        lines.push('\n');
+    }
+
+    let path = log_path(report_dir);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut f| f.write_all(lines.as_bytes()))
+        .map_err(|e| format!("Failed to append to {}: {}", path.display(), e))
+}
+
+fn log_path(report_dir: &Path) -> PathBuf {
+    report_dir.join(crate::history::HISTORY_DIR).join("outcomes.jsonl")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(dependent: &str, base: &str, passed: bool) -> Outcome {
+        Outcome {
+            base_crate: "rgb".to_string(),
+            base: base.to_string(),
+            dependent: dependent.to_string(),
+            dependent_version: "1.0.0".to_string(),
+            passed,
+            run: String::new(),
+            status: None,
+            duration: 0.0,
+            commit: None,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_flips_only_count_for_the_same_versions() {
+        let outcomes = vec![
+            outcome("image", "0.8.50", true),
+            outcome("image", "0.8.50", false),
+            outcome("image", "0.8.50", true),
+            outcome("image", "0.8.50", true),
+            // A different base version failing is a real change, not a flip
+            outcome("image", "0.8.91+abc", false),
+            outcome("png", "0.8.50", true),
+            outcome("png", "0.8.91+abc", false),
+            outcome("png", "0.8.91+def", true),
+        ];
+        let flakiness = Flakiness::from_outcomes(&outcomes);
+        assert_eq!(flakiness.percent("image"), Some(67));
+        assert_eq!(flakiness.percent("png"), None);
+        assert_eq!(flakiness.percent("gif"), None);
+    }
+}
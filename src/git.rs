@@ -1,29 +1,75 @@
 /// Git repository utilities
 ///
 /// This module handles:
-/// - Getting the current git commit hash
-/// - Checking for uncommitted changes
+/// - Checking a directory for uncommitted changes
+/// - Describing the commit a directory is checked out at (for run metadata)
+/// - Getting that commit's full hash (for canary snippets)
+/// - Cloning `--dependent-git` repositories at a revision
+use crate::error::CopterError;
+use std::path::Path;
 use std::process::Command;
 
-/// Get the short git commit hash (7 characters)
-pub fn get_git_hash() -> Option<String> {
-    Command::new("git")
-        .args(["rev-parse", "--short", "HEAD"])
-        .output()
-        .ok()
-        .filter(|output| output.status.success())
-        .and_then(|output| String::from_utf8(output.stdout).ok())
-        .map(|s| s.trim().to_string())
+/// `git describe --tags --always` for the repository containing `dir`
+pub fn describe_at(dir: &Path) -> Option<String> {
+    git_output(dir, &["describe", "--tags", "--always"])
 }
 
-/// Check if git working directory is dirty (has uncommitted changes)
-pub fn is_git_dirty() -> bool {
+/// Whether the repository containing `dir` has uncommitted changes; `None` outside a repository
+pub fn is_dirty_at(dir: &Path) -> Option<bool> {
+    git_output(dir, &["status", "--porcelain"]).map(|s| !s.is_empty())
+}
+
+/// Full hash of the commit checked out in the repository containing `dir`
+pub fn head_at(dir: &Path) -> Option<String> {
+    git_output(dir, &["rev-parse", "HEAD"])
+}
+
+/// Clone `url` into `dest` (or fetch, when already cloned there) and check out `rev`
+/// (a commit, tag or branch; the default branch when `None`); returns the commit's hash
+pub fn checkout(url: &str, rev: Option<&str>, dest: &Path) -> Result<String, CopterError> {
+    let run = |dir: &Path, args: &[&str]| -> Result<(), CopterError> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .map_err(|e| CopterError::Git(format!("Failed to run git: {}", e)))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(CopterError::Git(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    };
+    if dest.join(".git").exists() {
+        run(dest, &["fetch", "--quiet", "--tags", "origin"])?;
+    } else {
+        let parent = dest.parent().unwrap_or(Path::new("."));
+        std::fs::create_dir_all(parent)
+            .map_err(|e| CopterError::Git(format!("Failed to create {}: {}", parent.display(), e)))?;
+        run(parent, &["clone", "--quiet", url, &dest.display().to_string()])?;
+    }
+    match rev {
+        // A branch name checks out what the remote has now, not a stale local branch
+        Some(rev) => run(dest, &["checkout", "--quiet", "--detach", &format!("origin/{}", rev)])
+            .or_else(|_| run(dest, &["checkout", "--quiet", "--detach", rev]))?,
+        None => run(dest, &["checkout", "--quiet", "--detach", "origin/HEAD"])?,
+    }
+    head_at(dest).ok_or_else(|| CopterError::Git(format!("No commit checked out in {}", dest.display())))
+}
+
+/// Trimmed stdout of a successful `git -C <dir> <args>`
+fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
     Command::new("git")
-        .args(["status", "--porcelain"])
+        .arg("-C")
+        .arg(dir)
+        .args(args)
         .output()
         .ok()
         .filter(|output| output.status.success())
         .and_then(|output| String::from_utf8(output.stdout).ok())
-        .map(|s| !s.trim().is_empty())
-        .unwrap_or(false)
+        .map(|s| s.trim().to_string())
 }
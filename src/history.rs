@@ -0,0 +1,422 @@
+/// Run history: one record per completed run, keyed by a fingerprint of the test matrix
+///
+/// Records live in `copter-report/history/<fingerprint>/` alongside copies of that
+/// run's `report.json` and `report.md`, so re-running the identical matrix always
+/// maps to the same place. `history.jsonl` in the same directory is an append-only
+/// index of every run.
+use crate::report::TestSummary;
+use crate::types::{CrateSource, OfferedRow, OverrideMode, TestMatrix, Version};
+use crate::ui::Console;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+/// Subdirectory of the report directory holding run history
+pub const HISTORY_DIR: &str = "history";
+
+/// Versions that registry dependents left at "latest" resolved to, by name
+pub type LatestVersions = BTreeMap<String, String>;
+
+/// Summary of one completed run
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RunRecord {
+    pub fingerprint: String,
+    pub crate_name: String,
+    pub versions: Vec<String>,
+    pub dependents: Vec<String>,
+    /// Local time the run finished ("%Y-%m-%d %H:%M:%S")
+    pub finished: String,
+    pub passed: usize,
+    pub regressed: usize,
+    pub broken: usize,
+    #[serde(default)]
+    pub env_failed: usize,
+    #[serde(default)]
+    pub skipped: usize,
+    pub total: usize,
+    /// `--label` given for the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl RunRecord {
+    /// Record of a run of `matrix` whose rows add up to `summary` ("latest" dependents at
+    /// the versions in `latest`, see `tested_versions`)
+    pub fn new(matrix: &TestMatrix, summary: &TestSummary, latest: &LatestVersions) -> Self {
+        RunRecord {
+            fingerprint: fingerprint(matrix, latest),
+            crate_name: matrix.base_crate.clone(),
+            versions: matrix.base_versions.iter().map(|v| v.crate_ref.version.display()).collect(),
+            dependents: matrix.dependents.iter().map(|d| d.crate_ref.display()).collect(),
+            finished: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            passed: summary.passed,
+            regressed: summary.regressed,
+            broken: summary.broken,
+            env_failed: summary.env_failed,
+            skipped: summary.skipped,
+            total: summary.total,
+            label: None,
+        }
+    }
+
+    /// One-line result summary
+    pub fn describe(&self) -> String {
+        let mut s =
+            format!("{} passed, {} regressed, {} broken of {}", self.passed, self.regressed, self.broken, self.total);
+        if self.env_failed > 0 {
+            s.push_str(&format!(" ({} environment failures)", self.env_failed));
+        }
+        if self.skipped > 0 {
+            s.push_str(&format!(" ({} skipped)", self.skipped));
+        }
+        s
+    }
+}
+
+/// 64-bit FNV-1a; stable across Rust releases, unlike `DefaultHasher`
+pub struct Fnv(pub u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        // Field separator so ("ab", "c") and ("a", "bc") differ
+        self.0 ^= 0xff;
+        self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+}
+
+/// Hash a local crate's manifest and sources, so editing WIP code changes the fingerprint
+fn hash_local_crate(hasher: &mut Fnv, path: &Path) {
+    let dir = if path.ends_with("Cargo.toml") { path.parent().unwrap_or(path) } else { path };
+    let mut files = vec![dir.join("Cargo.toml")];
+    let mut stack = vec![dir.join("src")];
+    while let Some(d) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&d) else { continue };
+        for entry in entries.flatten() {
+            let p = entry.path();
+            if p.is_dir() { stack.push(p) } else { files.push(p) }
+        }
+    }
+    files.sort();
+    for file in files {
+        if let Ok(bytes) = fs::read(&file) {
+            hasher.write(file.strip_prefix(dir).unwrap_or(&file).to_string_lossy().as_bytes());
+            hasher.write(&bytes);
+        }
+    }
+}
+
+/// Hash of a local crate's manifest and sources, e.g. to tell WIP states apart across runs
+pub fn source_hash(path: &Path) -> String {
+    let mut h = Fnv::new();
+    hash_local_crate(&mut h, path);
+    format!("{:016x}", h.0)
+}
+
+/// What each "latest" registry dependent of `matrix` resolves to now, one registry lookup
+/// each (dependents that don't resolve are left out, as the run skips them)
+pub fn latest_versions(matrix: &TestMatrix) -> LatestVersions {
+    matrix
+        .dependents
+        .iter()
+        .filter(|d| d.crate_ref.version == Version::Latest && d.crate_ref.source == CrateSource::Registry)
+        .filter_map(|d| {
+            let latest = crate::version::resolve_latest_version(&matrix.registry, &d.crate_ref.name, false).ok()?;
+            Some((d.crate_ref.name.clone(), latest))
+        })
+        .collect()
+}
+
+/// The dependent versions a finished run tested, for `RunRecord::new`
+pub fn tested_versions(rows: &[OfferedRow]) -> LatestVersions {
+    rows.iter().map(|row| (row.primary.dependent_name.clone(), row.primary.dependent_version.clone())).collect()
+}
+
+/// Fingerprint of everything that determines a run's results
+///
+/// Covers the base crate, every base version (with its override mode and, for
+/// local sources, the manifest and source contents), every dependent at the version it
+/// resolves to (`latest`: a new release of a dependent left at "latest" is a different
+/// matrix), the rustc that builds them, and the options that change the builds or which
+/// dependents run (time limits, quarantine).
+pub fn fingerprint(matrix: &TestMatrix, latest: &LatestVersions) -> String {
+    let mut h = Fnv::new();
+    h.write(matrix.base_crate.as_bytes());
+    let rustc = crate::baseline_cache::rustc_version(Path::new("."), matrix);
+    h.write(rustc.unwrap_or_default().as_bytes());
+    for v in &matrix.base_versions {
+        h.write(v.crate_ref.display().as_bytes());
+        h.write(&[v.is_baseline as u8, matches!(v.override_mode, OverrideMode::Force) as u8]);
+        if let CrateSource::Local { path } = &v.crate_ref.source {
+            hash_local_crate(&mut h, path);
+        }
+    }
+    for d in &matrix.dependents {
+        h.write(d.crate_ref.display().as_bytes());
+        if d.crate_ref.version == Version::Latest
+            && d.crate_ref.source == CrateSource::Registry
+            && let Some(version) = latest.get(&d.crate_ref.name)
+        {
+            h.write(version.as_bytes());
+        }
+        match &d.crate_ref.source {
+            CrateSource::Local { path } => hash_local_crate(&mut h, path),
+            CrateSource::Git { url, rev, .. } => {
+                h.write(format!("{}#{}", url, rev.as_deref().unwrap_or("")).as_bytes())
+            }
+            CrateSource::Registry => {}
+        }
+    }
+    h.write(matrix.steps.to_string().as_bytes());
+    h.write(&[matrix.patch_transitive as u8]);
+    if let Some(gates) = &matrix.nightly_gates {
+        h.write(gates.describe().as_bytes());
+    }
+    if let Some(target) = &matrix.cross.target {
+        h.write(target.as_bytes());
+    }
+    if let Some(runner) = &matrix.cross.runner {
+        h.write(format!("runner {}", runner.describe()).as_bytes());
+    }
+    if let Some(sandbox) = &matrix.cross.sandbox {
+        h.write(format!("docker-steps {}", sandbox.image).as_bytes());
+    }
+    for toolchain in &matrix.toolchains {
+        h.write(format!("toolchain {}", toolchain).as_bytes());
+    }
+    if let Some(toolchain) = &matrix.cross.toolchain {
+        h.write(format!("pinned {}", toolchain).as_bytes());
+    }
+    if matrix.force_resolver {
+        h.write(b"force-resolver-2");
+    }
+    if !matrix.dependent_settings.is_empty() {
+        h.write(serde_json::to_string(&matrix.dependent_settings).unwrap_or_default().as_bytes());
+    }
+    if matrix.dependent_patches != Default::default() {
+        h.write(format!("dependent-patches {:?}", matrix.dependent_patches).as_bytes());
+    }
+    if matrix.simulate_update {
+        h.write(b"simulate-update");
+    }
+    if let Some(registry) = &matrix.staging_registry {
+        h.write(registry.index.as_bytes());
+    }
+    let limits =
+        [("step-timeout", matrix.heartbeat.step_timeout), ("dependent-timeout", matrix.heartbeat.dependent_timeout)];
+    for (name, limit) in limits {
+        if let Some(limit) = limit {
+            h.write(format!("{} {}", name, limit.as_secs()).as_bytes());
+        }
+    }
+    for name in &matrix.quarantined {
+        h.write(format!("quarantined {}", name).as_bytes());
+    }
+    format!("{:016x}", h.0)
+}
+
+/// Directory for one fingerprint's record and reports
+pub fn run_dir(report_dir: &Path, fingerprint: &str) -> PathBuf {
+    report_dir.join(HISTORY_DIR).join(fingerprint)
+}
+
+/// Prior run of the same matrix, if one was recorded
+pub fn find(report_dir: &Path, fingerprint: &str) -> Option<RunRecord> {
+    let content = fs::read_to_string(run_dir(report_dir, fingerprint).join("run.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Record a completed run and keep copies of its reports under its fingerprint
+pub fn record(report_dir: &Path, record: &RunRecord) -> Result<PathBuf, String> {
+    let dir = run_dir(report_dir, &record.fingerprint);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let json = serde_json::to_string_pretty(record).map_err(|e| e.to_string())?;
+    fs::write(dir.join("run.json"), json).map_err(|e| format!("Failed to write run record: {}", e))?;
+    for name in ["report.json", "report.md"] {
+        let src = report_dir.join(name);
+        if src.exists() {
+            let _ = fs::copy(&src, dir.join(name));
+        }
+    }
+
+    let index = report_dir.join(HISTORY_DIR).join("history.jsonl");
+    let line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&index)
+        .and_then(|mut f| writeln!(f, "{}", line))
+        .map_err(|e| format!("Failed to append to {}: {}", index.display(), e))?;
+
+    Ok(dir)
+}
+
+/// Show a prior identical run and decide whether to repeat it
+///
+/// Interactive sessions are asked; non-interactive runs (CI, cron) repeat it, unless
+/// `skip_if_seen` (`--skip-if-seen`) says to stop at the prior result.
+pub fn confirm_rerun(console: Console, prior: &RunRecord, report_dir: &Path, skip_if_seen: bool) -> bool {
+    let label = prior.label.as_ref().map(|label| format!(", label {}", label)).unwrap_or_default();
+    console.say(&format!(
+        "This exact test matrix was already run on {} (fingerprint {}{}):",
+        prior.finished, prior.fingerprint, label
+    ));
+    console.say(&format!("  {} × {} dependents: {}", prior.crate_name, prior.dependents.len(), prior.describe()));
+    console.say(&format!("  Reports: {}", run_dir(report_dir, &prior.fingerprint).display()));
+
+    if skip_if_seen {
+        console.say("Not re-running (--skip-if-seen).");
+        return false;
+    }
+    if !std::io::stdin().is_terminal() {
+        console.say("Running it again (--skip-if-seen reuses the prior result instead).");
+        return true;
+    }
+    print!("Run it again? [y/N] ");
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    let _ = std::io::stdin().lock().read_line(&mut answer);
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CommandType, Steps, VersionSpec, VersionedCrate};
+
+    fn matrix(dependents: &[&str]) -> TestMatrix {
+        TestMatrix {
+            base_crate: "rgb".to_string(),
+            base_versions: vec![VersionSpec {
+                crate_ref: VersionedCrate::from_registry("rgb", "0.8.50"),
+                override_mode: OverrideMode::None,
+                is_baseline: true,
+            }],
+            dependents: dependents
+                .iter()
+                .map(|d| VersionSpec {
+                    crate_ref: VersionedCrate::from_registry(*d, "1.0.0"),
+                    override_mode: OverrideMode::None,
+                    is_baseline: false,
+                })
+                .collect(),
+            staging_dir: PathBuf::from("/tmp/staging"),
+            steps: Steps::all(),
+            error_lines: 10,
+            patch_transitive: false,
+            min_free_bytes: 0,
+            heartbeat: Default::default(),
+            cross: Default::default(),
+            nightly_gates: None,
+            dependent_patches: Default::default(),
+            simulate_update: false,
+            staging_registry: None,
+            dependent_settings: Default::default(),
+            population: None,
+            baseline_cache: false,
+            staging_copy: crate::staging::CopyMethod::Auto,
+            source_cache: Default::default(),
+            registry: Default::default(),
+            audit: Default::default(),
+            force_resolver: false,
+            toolchains: vec![],
+            quarantined: vec![],
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable_and_sensitive() {
+        let fp = |matrix: &TestMatrix| fingerprint(matrix, &LatestVersions::new());
+        assert_eq!(fp(&matrix(&["image"])), fp(&matrix(&["image"])));
+        assert_ne!(fp(&matrix(&["image"])), fp(&matrix(&["image", "ravif"])));
+
+        let mut skipped = matrix(&["image"]);
+        skipped.steps = Steps::through(CommandType::Check);
+        assert_ne!(fp(&matrix(&["image"])), fp(&skipped));
+
+        // Every option that changes the builds makes a different matrix
+        let mut msrv = matrix(&["image"]);
+        msrv.toolchains = vec!["1.70".to_string(), "stable".to_string()];
+        assert_ne!(fp(&matrix(&["image"])), fp(&msrv));
+        let mut pinned = matrix(&["image"]);
+        pinned.cross.toolchain = Some("copter-no-such-toolchain".to_string());
+        assert_ne!(fp(&matrix(&["image"])), fp(&pinned));
+        let mut resolver = matrix(&["image"]);
+        resolver.force_resolver = true;
+        assert_ne!(fp(&matrix(&["image"])), fp(&resolver));
+        let mut stripped = matrix(&["image"]);
+        stripped.dependent_patches = crate::compile::dependent_patches::DependentPatches::Strip;
+        assert_ne!(fp(&matrix(&["image"])), fp(&stripped));
+        let mut emulated = matrix(&["image"]);
+        emulated.cross.runner = Some(crate::compile::target::Runner::parse("qemu-aarch64").unwrap());
+        assert_ne!(fp(&matrix(&["image"])), fp(&emulated));
+        let mut imaged = matrix(&["image"]);
+        let settings =
+            crate::project_config::DependentSettings { image: Some("rust:1.84".to_string()), ..Default::default() };
+        imaged.dependent_settings.insert("image".to_string(), settings);
+        assert_ne!(fp(&matrix(&["image"])), fp(&imaged));
+        let mut sandboxed = matrix(&["image"]);
+        sandboxed.cross.sandbox = Some(crate::docker::StepSandbox {
+            image: "rust:latest".to_string(),
+            writable: vec![PathBuf::from("/tmp/staging")],
+            read_only: vec![],
+            cargo_home: PathBuf::from("/tmp/staging/docker-cargo-home"),
+        });
+        assert_ne!(fp(&matrix(&["image"])), fp(&sandboxed));
+        let mut limited = matrix(&["image"]);
+        limited.heartbeat = limited.heartbeat.with_timeouts(Some(30), None);
+        assert_ne!(fp(&matrix(&["image"])), fp(&limited));
+        let mut quarantined = matrix(&["image"]);
+        quarantined.quarantined = vec!["ravif".to_string()];
+        assert_ne!(fp(&matrix(&["image"])), fp(&quarantined));
+
+        // A "latest" dependent is fingerprinted at the version it resolved to
+        let mut latest = matrix(&[]);
+        latest.dependents.push(VersionSpec {
+            crate_ref: VersionedCrate::latest_from_registry("image"),
+            override_mode: OverrideMode::None,
+            is_baseline: false,
+        });
+        let at = |version: &str| LatestVersions::from([("image".to_string(), version.to_string())]);
+        assert_eq!(fingerprint(&latest, &at("0.25.1")), fingerprint(&latest, &at("0.25.1")));
+        assert_ne!(fingerprint(&latest, &at("0.25.1")), fingerprint(&latest, &at("0.25.2")));
+    }
+
+    #[test]
+    fn test_record_and_find_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("report.json"), "{}").unwrap();
+        let summary = TestSummary { passed: 3, regressed: 1, broken: 0, env_failed: 0, skipped: 0, total: 4 };
+        let rec = RunRecord::new(&matrix(&["image"]), &summary, &LatestVersions::new());
+
+        assert!(find(dir.path(), &rec.fingerprint).is_none());
+        let run = record(dir.path(), &rec).unwrap();
+        assert!(run.join("report.json").exists());
+        assert_eq!(find(dir.path(), &rec.fingerprint), Some(rec.clone()));
+        assert_eq!(rec.describe(), "3 passed, 1 regressed, 0 broken of 4");
+        assert!(!confirm_rerun(Console::default(), &rec, dir.path(), true));
+        if !std::io::stdin().is_terminal() {
+            // CI and cron runs repeat the matrix rather than report stale results
+            assert!(confirm_rerun(Console::default(), &rec, dir.path(), false));
+        }
+
+        record(dir.path(), &rec).unwrap();
+        let index = fs::read_to_string(dir.path().join(HISTORY_DIR).join("history.jsonl")).unwrap();
+        assert_eq!(index.lines().count(), 2);
+    }
+}
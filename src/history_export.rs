@@ -0,0 +1,126 @@
+/// `history export`: the outcome log as a table for spreadsheets and pandas, or a trend page
+///
+/// Every run appends its rows to `copter-report/history/outcomes.jsonl` (see
+/// `flakiness`). `cargo copter history export --format csv` flattens that into one line
+/// per (run, dependent, base version): when the run finished and its `--label`, the status
+/// and failed step, and the seconds spent in cargo, so trend charts of a large crate's
+/// dependents are a pivot table away. Runs recorded before status and duration were kept have empty
+/// `run`/`status` cells and a duration of 0. `--format html` renders `trend`'s page instead.
+use crate::cli::HistoryFormat;
+use crate::flakiness::Outcome;
+use std::io::Write;
+use std::path::Path;
+
+const CSV_HEADER: [&str; 10] = [
+    "run",
+    "label",
+    "base_crate",
+    "dependent",
+    "dependent_version",
+    "base_version",
+    "status",
+    "step",
+    "passed",
+    "duration_secs",
+];
+
+/// Write the outcome history (optionally only `base_crate`'s, or only runs with `label`) to
+/// `output`, or stdout
+pub fn export(
+    report_dir: &Path,
+    format: HistoryFormat,
+    base_crate: Option<&str>,
+    label: Option<&str>,
+    output: Option<&Path>,
+    messages: &crate::messages::Catalog,
+) -> Result<(), String> {
+    let mut outcomes = crate::flakiness::load_outcomes(report_dir, base_crate);
+    if let Some(label) = label {
+        outcomes.retain(|o| o.label.as_deref() == Some(label));
+    }
+    if outcomes.is_empty() {
+        return Err(format!("No run history in {}", report_dir.join(crate::history::HISTORY_DIR).display()));
+    }
+    let text = match format {
+        HistoryFormat::Csv => csv(&outcomes),
+        HistoryFormat::Html => crate::trend::html(&outcomes, messages),
+    };
+    match output {
+        Some(path) => {
+            std::fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            eprintln!("Wrote {} rows to {}", outcomes.len(), path.display());
+            Ok(())
+        }
+        None => std::io::stdout().write_all(text.as_bytes()).map_err(|e| e.to_string()),
+    }
+}
+
+fn csv(outcomes: &[Outcome]) -> String {
+    let mut out = CSV_HEADER.join(",");
+    out.push('\n');
+    for o in outcomes {
+        // The status's kind ("regressed"), as in report.json
+        let status = o
+            .status
+            .and_then(|s| serde_json::to_value(s).ok())
+            .and_then(|v| v["kind"].as_str().map(str::to_string))
+            .unwrap_or_default();
+        let step = o.status.and_then(|s| s.step()).map(|s| s.as_str()).unwrap_or_default();
+        let cells = [
+            o.run.clone(),
+            o.label.clone().unwrap_or_default(),
+            o.base_crate.clone(),
+            o.dependent.clone(),
+            o.dependent_version.clone(),
+            o.base.clone(),
+            status,
+            step.to_string(),
+            o.passed.to_string(),
+            format!("{:.1}", o.duration),
+        ];
+        let cells: Vec<String> = cells.iter().map(|cell| csv_cell(cell)).collect();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Quote a cell when it contains a separator, quote or line break
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) { format!("\"{}\"", cell.replace('"', "\"\"")) } else { cell.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CommandType, RowStatus};
+
+    #[test]
+    fn test_csv_one_line_per_outcome() {
+        let outcome = |dependent: &str, status: Option<RowStatus>, run: &str| Outcome {
+            base_crate: "rgb".to_string(),
+            base: "0.8.92+1a2b".to_string(),
+            dependent: dependent.to_string(),
+            dependent_version: "1.0.0".to_string(),
+            passed: status.is_none_or(|s| !s.is_failure()),
+            run: run.to_string(),
+            status,
+            duration: 42.5,
+            commit: None,
+            label: None,
+        };
+        let mut outcomes = vec![
+            outcome("image", Some(RowStatus::Regressed { step: CommandType::Check }), "2026-10-16T09:00:00+02:00"),
+            outcome("png", Some(RowStatus::Passed), "2026-10-16T09:00:00+02:00"),
+            outcome("odd,name", None, ""),
+        ];
+        outcomes[1].label = Some("pr-1234".to_string());
+        assert_eq!(
+            csv(&outcomes),
+            "run,label,base_crate,dependent,dependent_version,base_version,status,step,passed,duration_secs\n\
+             2026-10-16T09:00:00+02:00,,rgb,image,1.0.0,0.8.92+1a2b,regressed,check,false,42.5\n\
+             2026-10-16T09:00:00+02:00,pr-1234,rgb,png,1.0.0,0.8.92+1a2b,passed,,true,42.5\n\
+             ,,rgb,\"odd,name\",1.0.0,0.8.92+1a2b,,,true,42.5\n"
+        );
+    }
+}
@@ -0,0 +1,103 @@
+/// `--output jsonl`: one JSON object per line on stdout as the run goes
+///
+/// The table can't be parsed while it is being printed (rows are padded, wrapped and followed
+/// by error boxes), and report.json only exists once the run is over. With `--output jsonl`
+/// stdout carries nothing but these lines, each flushed as soon as it is known:
+///
+/// - `{"event": "start", ...}`: the base crate and how many rows to expect
+/// - `{"event": "row", ...}`: a finished (dependent, version) pair: its status, the step
+///   that failed, how long its steps took and the error signature (`report::error_signature`)
+/// - `{"event": "summary", ...}`: the same counts as report.json's `summary`
+///
+/// Everything else copter prints goes to stderr instead (`ui::Console`), and the reports are
+/// written as usual.
+use crate::report::TestSummary;
+use crate::types::OfferedRow;
+use serde_json::{Value, json};
+use std::io::Write;
+
+/// Before the first row: what is tested and how many rows will follow
+pub fn start_line(base_crate: &str, dependents: usize, rows: usize) -> Value {
+    json!({
+        "event": "start",
+        "base_crate": base_crate,
+        "dependents": dependents,
+        "rows": rows,
+    })
+}
+
+/// One finished row
+pub fn row_line(row: &OfferedRow) -> Value {
+    let status = row.status();
+    let kind = serde_json::to_value(status).ok().and_then(|s| s.get("kind").cloned()).unwrap_or_default();
+    let duration: f64 = row.test.commands.iter().map(|cmd| cmd.result.duration).sum();
+    let signature = crate::report::extract_error_text(row).filter(|signature| !signature.is_empty());
+    json!({
+        "event": "row",
+        "dependent": row.primary.dependent_name,
+        "dependent_version": row.primary.dependent_version,
+        "offered": row.offered.as_ref().map(|offered| &offered.version),
+        "forced": row.offered.as_ref().is_some_and(|offered| offered.forced),
+        "resolved": row.primary.resolved_version,
+        "toolchain": row.test.toolchain,
+        "status": kind,
+        "status_word": status.word(),
+        "failed_step": status.step().map(|step| step.as_str()),
+        "duration_secs": (duration * 10.0).round() / 10.0,
+        "error_signature": signature,
+    })
+}
+
+/// After the last row
+pub fn summary_line(summary: &TestSummary) -> Value {
+    json!({
+        "event": "summary",
+        "passed": summary.passed,
+        "regressed": summary.regressed,
+        "broken": summary.broken,
+        "env_failed": summary.env_failed,
+        "skipped": summary.skipped,
+        "total": summary.total,
+    })
+}
+
+/// Print `line` to stdout and flush it, so a reader tailing the output gets it right away
+pub fn emit(line: &Value) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", line);
+    let _ = stdout.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CommandType;
+
+    fn row(offered: Option<&str>, error: &str) -> OfferedRow {
+        crate::test_rows::row("image")
+            .version("0.25.8")
+            .offered(offered)
+            .steps(&[(CommandType::Fetch, 1.25), (CommandType::Check, 1.25)])
+            .error(error)
+            .build()
+    }
+
+    #[test]
+    fn test_one_line_per_row() {
+        let baseline = row_line(&row(None, ""));
+        assert_eq!(baseline["offered"], Value::Null);
+        assert_eq!((baseline["status"].as_str(), baseline["failed_step"].as_str()), (Some("passed"), None));
+        assert_eq!(baseline["duration_secs"], 2.5);
+        assert_eq!(baseline["error_signature"], Value::Null);
+
+        let regressed = row_line(&row(Some("0.9.0"), "error[E0308]: mismatched types\n --> src/lib.rs:3:5"));
+        assert_eq!(
+            regressed.to_string(),
+            "{\"dependent\":\"image\",\"dependent_version\":\"0.25.8\",\"duration_secs\":2.5,\
+             \"error_signature\":\"error[E0308]: mismatched types\",\"event\":\"row\",\"failed_step\":\"check\",\
+             \"forced\":false,\"offered\":\"0.9.0\",\"resolved\":\"0.9.0\",\"status\":\"regressed\",\
+             \"status_word\":\"REGRESSED\",\"toolchain\":null}"
+        );
+        assert!(!regressed.to_string().contains('\n'));
+    }
+}
@@ -0,0 +1,290 @@
+/// `--check-first` and `--resume`: running the test step after a fetch+check pass
+///
+/// `cargo test` is where a run spends its hours, yet a compile regression is the most
+/// serious breakage and only needs `cargo check` to show. With `--check-first` the whole
+/// matrix runs through check first, streaming its rows (and regressions) within minutes;
+/// then the full steps run again for the dependents where at least one version passed
+/// check. Fetch and check are repeated in the second pass but hit the build cache, so the
+/// cost is small. Dependents that failed check everywhere keep their check-pass rows.
+///
+/// `--resume --from <step>` starts at the second half with an earlier run's results from
+/// the checkpoint: its rows are shown again, and the dependents that passed the step
+/// before `<step>` (or weren't reached at all) run with the full steps.
+use crate::error::CopterError;
+use crate::types::{CommandType, Steps, TestMatrix, TestResult, Version, VersionSpec};
+use std::collections::HashSet;
+
+/// Run `matrix` in two passes through `run_pass`, streaming both passes' rows to
+/// `on_result`; returns the check-pass results with tested dependents' rows replaced
+pub fn run<P>(
+    matrix: &TestMatrix,
+    run_pass: &mut P,
+    on_result: &mut dyn FnMut(&TestResult),
+) -> Result<Vec<TestResult>, CopterError>
+where
+    P: FnMut(&TestMatrix, &mut dyn FnMut(&TestResult)) -> Result<Vec<TestResult>, CopterError>,
+{
+    if !matrix.steps.runs(CommandType::Test) {
+        return run_pass(matrix, on_result);
+    }
+    let mut check_pass = matrix.clone();
+    check_pass.steps = Steps::through(CommandType::Check);
+    eprintln!("Check pass: fetch and check for all {} dependents", matrix.dependents.len());
+    let checked = run_pass(&check_pass, on_result)?;
+    continue_from(matrix, checked, CommandType::Test, run_pass, on_result)
+}
+
+/// Show `previous` results (from the checkpoint) and run the rest of `matrix` from `from`
+pub fn resume<P>(
+    matrix: &TestMatrix,
+    previous: Vec<TestResult>,
+    from: CommandType,
+    run_pass: &mut P,
+    on_result: &mut dyn FnMut(&TestResult),
+) -> Result<Vec<TestResult>, CopterError>
+where
+    P: FnMut(&TestMatrix, &mut dyn FnMut(&TestResult)) -> Result<Vec<TestResult>, CopterError>,
+{
+    if from == CommandType::Fetch {
+        return run_pass(matrix, on_result);
+    }
+    previous.iter().for_each(&mut *on_result);
+    continue_from(matrix, previous, from, run_pass, on_result)
+}
+
+fn continue_from<P>(
+    matrix: &TestMatrix,
+    done: Vec<TestResult>,
+    from: CommandType,
+    run_pass: &mut P,
+    on_result: &mut dyn FnMut(&TestResult),
+) -> Result<Vec<TestResult>, CopterError>
+where
+    P: FnMut(&TestMatrix, &mut dyn FnMut(&TestResult)) -> Result<Vec<TestResult>, CopterError>,
+{
+    let next = next_pass(matrix, &done, from);
+    eprintln!(
+        "{} pass: {} of {} dependents passed {} or haven't run yet",
+        capitalize(from.as_str()),
+        next.dependents.len(),
+        matrix.dependents.len(),
+        before(from).as_str()
+    );
+    if next.dependents.is_empty() {
+        return Ok(done);
+    }
+    let rerun = run_pass(&next, on_result)?;
+    Ok(merge(done, rerun))
+}
+
+/// `matrix` narrowed to the dependents with a version that passed the step before `from`,
+/// pinned to the version that run resolved, and the dependents `done` has no rows for
+fn next_pass(matrix: &TestMatrix, done: &[TestResult], from: CommandType) -> TestMatrix {
+    let passed = before(from);
+    let matches = |spec: &VersionSpec, result: &TestResult| {
+        let version = &spec.crate_ref.version;
+        spec.crate_ref.name == result.dependent.name
+            && (*version == Version::Latest || *version == result.dependent.version)
+    };
+    let mut pass = matrix.clone();
+    let mut seen = HashSet::new();
+    pass.dependents = matrix
+        .dependents
+        .iter()
+        .filter_map(|spec| {
+            let rows: Vec<&TestResult> = done.iter().filter(|result| matches(spec, result)).collect();
+            let Some(first) = rows.first() else {
+                return Some(spec.clone());
+            };
+            let passed = rows.iter().any(|result| step_passed(result, passed));
+            (passed && seen.insert(key(first)))
+                .then(|| VersionSpec { crate_ref: first.dependent.clone(), ..spec.clone() })
+        })
+        .collect();
+    // Quarantined dependents already have their skipped rows unless these came from a checkpoint
+    pass.quarantined.retain(|name| !done.iter().any(|result| result.dependent.name == *name));
+    pass
+}
+
+/// Earlier results with each rerun dependent's rows swapped for its new rows
+fn merge(done: Vec<TestResult>, rerun: Vec<TestResult>) -> Vec<TestResult> {
+    let rerun_keys: HashSet<(String, String)> = rerun.iter().map(key).collect();
+    let mut placed = HashSet::new();
+    let mut merged = Vec::new();
+    for result in done {
+        let dependent = key(&result);
+        if !rerun_keys.contains(&dependent) {
+            merged.push(result);
+        } else if placed.insert(dependent.clone()) {
+            merged.extend(rerun.iter().filter(|r| key(r) == dependent).cloned());
+        }
+    }
+    // Dependents the earlier pass never reached
+    merged.extend(rerun.into_iter().filter(|r| !placed.contains(&key(r))));
+    merged
+}
+
+fn step_passed(result: &TestResult, step: CommandType) -> bool {
+    let execution = &result.execution;
+    match step {
+        CommandType::Fetch => execution.fetch.success,
+        CommandType::Check => execution.check.as_ref().is_some_and(|check| check.success),
+        CommandType::Test => execution.test.as_ref().is_some_and(|test| test.success),
+    }
+}
+
+/// The step before `step` (fetch for fetch)
+fn before(step: CommandType) -> CommandType {
+    let i = Steps::PIPELINE.iter().position(|s| *s == step).unwrap_or(0);
+    Steps::PIPELINE[i.saturating_sub(1)]
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+fn key(result: &TestResult) -> (String, String) {
+    (result.dependent.name.clone(), result.dependent.version.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{CompileResult, CompileStep, PatchDepth, ThreeStepResult};
+    use crate::types::{OverrideMode, VersionedCrate};
+    use std::path::PathBuf;
+
+    fn step(step: CompileStep, success: bool) -> CompileResult {
+        CompileResult {
+            step,
+            success,
+            stdout: String::new(),
+            stderr: String::new(),
+            duration: std::time::Duration::from_secs(1),
+            diagnostics: vec![],
+            args: vec![],
+            manifest_diff: None,
+            timed_out: false,
+        }
+    }
+
+    fn result(dependent: &str, base: &str, check_passed: bool, tested: bool) -> TestResult {
+        TestResult {
+            base_version: VersionedCrate::from_registry("rgb", base),
+            dependent: VersionedCrate::from_registry(dependent, "1.0.0"),
+            execution: ThreeStepResult {
+                fetch: step(CompileStep::Fetch, true),
+                check: Some(step(CompileStep::Check, check_passed)),
+                test: tested.then(|| step(CompileStep::Test, true)),
+                actual_version: None,
+                expected_version: None,
+                forced_version: false,
+                original_requirement: None,
+                all_crate_versions: vec![],
+                patch_depth: PatchDepth::None,
+                started: None,
+                fork: None,
+                blocking: vec![],
+                repository: None,
+                base_features: None,
+                resolver: None,
+                toolchain: None,
+                skipped: None,
+            },
+            baseline: None,
+        }
+    }
+
+    fn matrix() -> TestMatrix {
+        let dependent = |name: &str| VersionSpec {
+            crate_ref: VersionedCrate::latest_from_registry(name),
+            override_mode: OverrideMode::None,
+            is_baseline: false,
+        };
+        TestMatrix {
+            base_crate: "rgb".to_string(),
+            base_versions: vec![VersionSpec::baseline(VersionedCrate::from_registry("rgb", "0.8.91"))],
+            dependents: vec![dependent("image"), dependent("png"), dependent("ravif")],
+            staging_dir: PathBuf::from("/staging"),
+            steps: Steps::default(),
+            error_lines: 10,
+            patch_transitive: false,
+            min_free_bytes: 0,
+            heartbeat: Default::default(),
+            cross: Default::default(),
+            nightly_gates: None,
+            dependent_patches: Default::default(),
+            simulate_update: false,
+            staging_registry: None,
+            dependent_settings: Default::default(),
+            population: None,
+            baseline_cache: false,
+            staging_copy: crate::staging::CopyMethod::Auto,
+            source_cache: Default::default(),
+            registry: Default::default(),
+            audit: Default::default(),
+            force_resolver: false,
+            toolchains: vec![],
+            quarantined: vec![],
+        }
+    }
+
+    #[test]
+    fn test_only_dependents_that_passed_check_are_tested() {
+        let matrix = matrix();
+        let mut passes = Vec::new();
+        let mut run_pass = |pass: &TestMatrix, on_result: &mut dyn FnMut(&TestResult)| {
+            passes.push(pass.clone());
+            let results = if pass.steps.runs(CommandType::Test) {
+                pass.dependents.iter().map(|d| result(&d.crate_ref.name, "0.8.91", true, true)).collect()
+            } else {
+                vec![
+                    result("image", "0.8.91", true, false),
+                    result("image", "0.9.0", false, false),
+                    result("png", "0.8.91", false, false),
+                    result("ravif", "0.8.91", true, false),
+                ]
+            };
+            results.iter().for_each(&mut *on_result);
+            Ok(results)
+        };
+        let mut streamed = 0;
+        let results = run(&matrix, &mut run_pass, &mut |_| streamed += 1).unwrap();
+
+        assert_eq!(passes.len(), 2);
+        let tested: Vec<String> = passes[1].dependents.iter().map(|d| d.crate_ref.version.display()).collect();
+        assert_eq!(
+            passes[1].dependents.iter().map(|d| d.crate_ref.name.as_str()).collect::<Vec<_>>(),
+            ["image", "ravif"]
+        );
+        assert_eq!(tested, ["1.0.0", "1.0.0"]);
+        assert_eq!(streamed, 6);
+        let rows: Vec<(&str, bool)> =
+            results.iter().map(|r| (r.dependent.name.as_str(), r.execution.test.is_some())).collect();
+        assert_eq!(rows, [("image", true), ("png", false), ("ravif", true)]);
+    }
+
+    #[test]
+    fn test_resume_runs_what_passed_check_and_what_never_ran() {
+        let matrix = matrix();
+        let previous = vec![result("image", "0.8.91", true, false), result("png", "0.8.91", false, false)];
+        let mut passes = Vec::new();
+        let mut run_pass = |pass: &TestMatrix, _: &mut dyn FnMut(&TestResult)| {
+            passes.push(pass.dependents.iter().map(|d| d.crate_ref.display()).collect::<Vec<_>>());
+            Ok(pass.dependents.iter().map(|d| result(&d.crate_ref.name, "0.8.91", true, true)).collect())
+        };
+        let mut replayed = 0;
+        let results =
+            resume(&matrix, previous.clone(), CommandType::Test, &mut run_pass, &mut |_| replayed += 1).unwrap();
+        assert_eq!(replayed, 2);
+        let rows: Vec<(&str, bool)> =
+            results.iter().map(|r| (r.dependent.name.as_str(), r.execution.test.is_some())).collect();
+        assert_eq!(rows, [("image", true), ("png", false), ("ravif", true)]);
+
+        // Resuming from fetch is a whole new run
+        resume(&matrix, previous, CommandType::Fetch, &mut run_pass, &mut |_| replayed += 1).unwrap();
+        assert_eq!(replayed, 2);
+        assert_eq!(passes, [vec!["image 1.0.0", "ravif latest"], vec!["image latest", "png latest", "ravif latest"]]);
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! cargo-copter as a library: test a crate's dependents from other Rust code
+//!
+//! [`CopterOptions`] builds a run, [`CopterOptions::run`] tests the matrix and returns a
+//! [`CopterReport`]; failures of copter itself are [`CopterError`]s. These three are the
+//! stable API. Callers that need to change the matrix in between take `run`'s steps:
+//! [`CopterOptions::build_matrix`], then [`CopterOptions::run_matrix`] (or [`run_tests`]
+//! with [`test_result_to_offered_row`] and the report exports); everything else is
+//! internal to the crate.
+
+#[cfg(all(feature = "offline-only", feature = "network"))]
+compile_error!("the `offline-only` feature needs `--no-default-features` (it excludes `network`)");
+
+mod ab;
+mod adoption;
+mod aliases;
+mod api;
+mod app;
+mod artifacts;
+mod audit;
+mod baseline_cache;
+mod bridge;
+mod categorize;
+mod checkpoint;
+mod cli;
+mod column_widths;
+mod compare;
+mod compile;
+mod config;
+mod console_format;
+mod dependents_cache;
+mod deterministic;
+mod disk;
+mod docker;
+mod download;
+mod embed;
+mod error;
+mod error_extract;
+mod feature_usage;
+mod flakiness;
+mod git;
+mod history;
+mod history_export;
+mod jsonl;
+mod lanes;
+mod live;
+mod manifest;
+mod messages;
+mod metadata;
+mod mirror;
+mod offline;
+mod partial_report;
+mod paths;
+mod project_config;
+mod quarantine;
+mod queue;
+mod reexports;
+mod registry;
+mod report;
+mod report_diff;
+mod repro;
+mod run_metadata;
+mod runner;
+mod scheduler;
+mod schema;
+mod scrub;
+mod self_test;
+mod simulate_update;
+mod source_cache;
+mod stage_publish;
+mod staging;
+mod system_deps;
+mod template;
+#[cfg(test)]
+mod test_rows;
+mod testsupport;
+mod timing;
+mod toolchains;
+mod trend;
+mod types;
+mod ui;
+mod version;
+mod what_if;
+
+pub use artifacts::SaveArtifacts;
+pub use bridge::test_result_to_offered_row;
+pub use embed::{CopterOptions, CopterReport};
+pub use error::CopterError;
+pub use messages::Catalog;
+pub use report::{TestSummary, export_json_report, export_junit_report};
+pub use run_metadata::RunMetadata;
+pub use runner::run_tests;
+pub use types::{OfferedRow, Steps, TestMatrix, TestResult};
+
+/// The `cargo-copter` binary
+#[doc(hidden)]
+pub use app::main;
@@ -0,0 +1,263 @@
+/// `--progress lines`: one reserved console line per dependent, updated in place
+///
+/// With `--backend queue`/`k8s`, results arrive from many workers in completion order, so
+/// the streamed table interleaves dependents and shows nothing for those still running.
+/// The board instead prints one line per dependent up front (spinner, name, versions done)
+/// and rewrites a dependent's line with its outcome once all its versions are in, the way
+/// buck and bazel show parallel work; the full table follows when the run ends. A ticker
+/// thread animates the spinners, so every draw goes through the board's mutex. Without a
+/// terminal, or with more dependents than the terminal has lines, results stream as before.
+/// Messages printed during the run go through `note()`, which puts them above the board.
+use crate::messages::Catalog;
+use crate::types::{OfferedRow, RowStatus};
+use lazy_static::lazy_static;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Time between spinner frames
+const TICK: Duration = Duration::from_millis(100);
+
+lazy_static! {
+    /// The board being drawn, for `note()`
+    static ref ACTIVE: Mutex<Option<Arc<Mutex<Lines<std::io::Stdout>>>>> = Mutex::new(None);
+}
+
+/// Print a message during the run: above the board when one is drawn, else on stderr
+pub fn note(message: &str) {
+    match ACTIVE.lock().unwrap().as_ref() {
+        Some(lines) => lines.lock().unwrap().note(message),
+        None => eprintln!("{}", message),
+    }
+}
+
+/// Whether a board is showing progress
+pub fn active() -> bool {
+    ACTIVE.lock().unwrap().is_some()
+}
+
+/// How results are shown while the run is going (`--progress`)
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Progress {
+    /// Lines for distributed and `--parallel` runs on a terminal, streaming otherwise
+    #[default]
+    Auto,
+    /// A reserved line per dependent (when stdout is a terminal tall enough)
+    Lines,
+    /// Table rows as results complete
+    Stream,
+}
+
+/// One dependent's line
+#[derive(Debug, Clone)]
+struct Entry {
+    name: String,
+    version: String,
+    rows: Vec<OfferedRow>,
+}
+
+/// The reserved lines and what they show; the cursor sits on the line below the last
+struct Lines<W: Write> {
+    out: W,
+    entries: Vec<Entry>,
+    /// Results per dependent (one per base version)
+    expected: usize,
+    frame: usize,
+    width: usize,
+    /// Status labels (`--messages`)
+    messages: Arc<Catalog>,
+}
+
+impl<W: Write> Lines<W> {
+    fn new(out: W, dependents: Vec<(String, String)>, expected: usize, width: usize, messages: Arc<Catalog>) -> Self {
+        let entries = dependents.into_iter().map(|(name, version)| Entry { name, version, rows: Vec::new() }).collect();
+        let mut lines = Lines { out, entries, expected: expected.max(1), frame: 0, width, messages };
+        for i in 0..lines.entries.len() {
+            let text = lines.line(i);
+            let _ = writeln!(lines.out, "{}", text);
+        }
+        let _ = lines.out.flush();
+        lines
+    }
+
+    fn done(&self, entry: &Entry) -> bool {
+        entry.rows.len() >= self.expected
+    }
+
+    /// The text of line `i`, cut to the terminal width
+    fn line(&self, i: usize) -> String {
+        let entry = &self.entries[i];
+        let text = if self.done(entry) {
+            let (mark, outcome) = outcome(&entry.rows, &self.messages);
+            format!("{} {} {}  {}", mark, entry.name, entry.version, outcome)
+        } else {
+            let progress = match entry.rows.len() {
+                0 => "waiting".to_string(),
+                n => format!("{}/{} versions done", n, self.expected),
+            };
+            format!("{} {} {}  {}", SPINNER[self.frame % SPINNER.len()], entry.name, entry.version, progress)
+        };
+        crate::console_format::truncate_with_padding(&text, self.width).trim_end().to_string()
+    }
+
+    /// Rewrite line `i` in place and return the cursor below the board
+    fn redraw(&mut self, i: usize) {
+        let up = self.entries.len() - i;
+        let text = self.line(i);
+        let _ = write!(self.out, "\x1b[{}A\r\x1b[2K{}\x1b[{}B\r", up, text, up);
+    }
+
+    fn record(&mut self, row: &OfferedRow) {
+        let name = &row.primary.dependent_name;
+        let version = &row.primary.dependent_version;
+        let found = self
+            .entries
+            .iter()
+            .position(|e| &e.name == name && &e.version == version && e.rows.len() < self.expected)
+            .or_else(|| self.entries.iter().position(|e| &e.name == name && e.rows.len() < self.expected));
+        if let Some(i) = found {
+            // Unresolved "latest" versions take the version the result reports
+            self.entries[i].version = version.clone();
+            self.entries[i].rows.push(row.clone());
+            self.redraw(i);
+            let _ = self.out.flush();
+        }
+    }
+
+    /// Clear the board, print `message` where it was and draw the board below it
+    fn note(&mut self, message: &str) {
+        let _ = write!(self.out, "\x1b[{}A\r\x1b[J{}\n", self.entries.len(), message);
+        for i in 0..self.entries.len() {
+            let text = self.line(i);
+            let _ = writeln!(self.out, "{}", text);
+        }
+        let _ = self.out.flush();
+    }
+
+    fn tick(&mut self) {
+        self.frame += 1;
+        for i in 0..self.entries.len() {
+            if !self.done(&self.entries[i]) {
+                self.redraw(i);
+            }
+        }
+        let _ = self.out.flush();
+    }
+}
+
+/// Mark and outcome of a finished dependent: "✗", "regressed on 0.9.0 (build failed)"
+fn outcome(rows: &[OfferedRow], messages: &Catalog) -> (char, String) {
+    let version = |row: &OfferedRow| row.offered.as_ref().map_or("baseline".to_string(), |o| o.version.clone());
+    let label = |row: &OfferedRow| format!("{} ({})", version(row), row.status().label(messages));
+    let regressed: Vec<String> = rows.iter().filter(|r| r.is_regression()).map(label).collect();
+    if !regressed.is_empty() {
+        return ('✗', format!("regressed on {}", regressed.join(", ")));
+    }
+    let failed: Vec<String> = rows.iter().filter(|r| r.status().is_failure()).map(label).collect();
+    if !failed.is_empty() {
+        return ('⚠', format!("failing: {}", failed.join(", ")));
+    }
+    if rows.iter().any(|r| r.offered.is_some() && r.status().is_skipped()) {
+        return ('⊘', "offered version not used".to_string());
+    }
+    let fixed = rows.iter().any(|r| r.status() == RowStatus::Fixed);
+    ('✓', if fixed { "fixed".to_string() } else { "passed".to_string() })
+}
+
+/// A running board; dropping it without `finish` leaves the lines as they are
+pub struct Board {
+    lines: Arc<Mutex<Lines<std::io::Stdout>>>,
+    stop: Arc<AtomicBool>,
+    ticker: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Board {
+    /// Draw a line per dependent (name, version) and start the spinners, if `progress`
+    /// and the terminal allow it; `expected` is the results each dependent produces
+    pub fn start(
+        progress: Progress,
+        parallel: bool,
+        dependents: Vec<(String, String)>,
+        expected: usize,
+        messages: Arc<Catalog>,
+    ) -> Option<Self> {
+        let wanted = match progress {
+            Progress::Stream => false,
+            Progress::Lines => true,
+            Progress::Auto => parallel,
+        };
+        if !wanted || !std::io::stdout().is_terminal() {
+            return None;
+        }
+        let (terminal_size::Width(width), terminal_size::Height(height)) = terminal_size::terminal_size()?;
+        // Lines scrolled off the top can't be rewritten
+        if dependents.is_empty() || dependents.len() >= height as usize {
+            return None;
+        }
+
+        let lines = Arc::new(Mutex::new(Lines::new(std::io::stdout(), dependents, expected, width as usize, messages)));
+        let stop = Arc::new(AtomicBool::new(false));
+        let ticker = {
+            let (lines, stop) = (Arc::clone(&lines), Arc::clone(&stop));
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    std::thread::sleep(TICK);
+                    lines.lock().unwrap().tick();
+                }
+            })
+        };
+        *ACTIVE.lock().unwrap() = Some(Arc::clone(&lines));
+        Some(Board { lines, stop, ticker: Some(ticker) })
+    }
+
+    /// Show a finished result on its dependent's line
+    pub fn record(&self, row: &OfferedRow) {
+        self.lines.lock().unwrap().record(row);
+    }
+
+    /// Stop the spinners; unfinished dependents (skipped or stopped early) keep their last line
+    pub fn finish(mut self) {
+        *ACTIVE.lock().unwrap() = None;
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(ticker) = self.ticker.take() {
+            let _ = ticker.join();
+        }
+        let _ = self.lines.lock().unwrap().out.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(dependent: &str, offered: Option<&str>, passed: bool) -> OfferedRow {
+        crate::test_rows::row(dependent).offered(offered).passed(passed).build()
+    }
+
+    #[test]
+    fn test_lines_rewritten_in_place() {
+        let dependents = vec![("image".to_string(), "1.0.0".to_string()), ("png".to_string(), "1.0.0".to_string())];
+        let mut lines = Lines::new(Vec::new(), dependents, 2, 80, Default::default());
+        assert_eq!(String::from_utf8_lossy(&lines.out), "⠋ image 1.0.0  waiting\n⠋ png 1.0.0  waiting\n");
+
+        lines.out.clear();
+        lines.record(&row("png", None, true));
+        assert_eq!(String::from_utf8_lossy(&lines.out), "\x1b[1A\r\x1b[2K⠋ png 1.0.0  1/2 versions done\x1b[1B\r");
+
+        lines.record(&row("image", None, true));
+        lines.record(&row("image", Some("0.9.0"), false));
+        assert_eq!(lines.line(0), "✗ image 1.0.0  regressed on 0.9.0 (build failed)");
+
+        // Only unfinished lines animate
+        lines.out.clear();
+        lines.tick();
+        assert_eq!(String::from_utf8_lossy(&lines.out), "\x1b[1A\r\x1b[2K⠙ png 1.0.0  1/2 versions done\x1b[1B\r");
+
+        lines.out.clear();
+        lines.note("queue: job 3 failed");
+        assert!(String::from_utf8_lossy(&lines.out).starts_with("\x1b[2A\r\x1b[Jqueue: job 3 failed\n✗ image"));
+    }
+}
@@ -1,13 +1,6 @@
-/// Cargo.toml manifest parsing utilities
-///
-/// This module handles:
-/// - Reading and parsing Cargo.toml files
-/// - Extracting crate name and version
-/// - Finding dependency requirements
-use semver::Version;
 use std::fs::File;
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Extract crate name and version from a Cargo.toml manifest
 pub fn get_crate_info(manifest_path: &Path) -> Result<(String, String), String> {
@@ -38,22 +31,120 @@ pub fn get_crate_info(manifest_path: &Path) -> Result<(String, String), String>
     }
 }
 
+/// `package.repository`, when given as a URL (not inherited from a workspace)
+pub fn repository(manifest_path: &Path) -> Option<String> {
+    let value: toml::Value = toml::from_str(&load_string(manifest_path).ok()?).ok()?;
+    let repository = value.get("package")?.get("repository")?.as_str()?;
+    (repository.starts_with("https://") || repository.starts_with("http://")).then(|| repository.to_string())
+}
+
+/// The dependency resolver cargo uses for the workspace whose root is `manifest_path`:
+/// `resolver` as declared, otherwise the edition's default ("1" before edition 2021, and
+/// for a virtual workspace that doesn't declare one)
+pub fn resolver(manifest_path: &Path) -> Option<String> {
+    let value: toml::Value = toml::from_str(&load_string(manifest_path).ok()?).ok()?;
+    let declared = ["workspace", "package"].into_iter().find_map(|table| value.get(table)?.get("resolver")?.as_str());
+    if let Some(declared) = declared {
+        return Some(declared.to_string());
+    }
+    let edition = value.get("package").and_then(|package| package.get("edition")).and_then(|edition| {
+        // `edition.workspace = true` takes the root's `[workspace.package]` edition
+        edition.as_str().or_else(|| value.get("workspace")?.get("package")?.get("edition")?.as_str())
+    });
+    let resolver = match edition {
+        None | Some("2015") | Some("2018") => "1",
+        Some("2021") => "2",
+        Some(_) => "3",
+    };
+    Some(resolver.to_string())
+}
+
+/// Packages of the workspace whose root is `manifest_path` as (name, Cargo.toml), the
+/// root's own package first; empty when the manifest has no `[workspace]`
+pub fn workspace_members(manifest_path: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    let value: toml::Value =
+        toml::from_str(&load_string(manifest_path)?).map_err(|e| format!("Failed to parse TOML: {}", e))?;
+    let Some(workspace) = value.get("workspace") else {
+        return Ok(vec![]);
+    };
+    let root = manifest_path.parent().unwrap_or(Path::new("."));
+    let patterns = |key: &str| -> Vec<String> {
+        let list = workspace.get(key).and_then(|list| list.as_array()).cloned().unwrap_or_default();
+        list.iter().filter_map(|p| p.as_str()).map(|p| root.join(p).display().to_string()).collect()
+    };
+    let excluded = patterns("exclude");
+
+    let mut members = Vec::new();
+    if value.get("package").is_some() {
+        members.push((get_crate_info(manifest_path)?.0, manifest_path.to_path_buf()));
+    }
+    for pattern in patterns("members") {
+        let dirs = glob::glob(&pattern).map_err(|e| format!("Invalid workspace member '{}': {}", pattern, e))?;
+        for dir in dirs.flatten() {
+            let manifest = dir.join("Cargo.toml");
+            if dir == root || excluded.iter().any(|e| dir.starts_with(e)) || !manifest.is_file() {
+                continue;
+            }
+            if let Ok((name, _)) = get_crate_info(&manifest)
+                && !members.iter().any(|(known, _)| *known == name)
+            {
+                members.push((name, manifest));
+            }
+        }
+    }
+    Ok(members)
+}
+
+/// The Cargo.toml of the package to test under `manifest_path`
+///
+/// `package` picks a workspace member by name (or the manifest's own package). Without it
+/// the manifest's own package is used, as cargo does, and a virtual workspace must have a
+/// single member.
+pub fn select_package(manifest_path: &Path, package: Option<&str>) -> Result<PathBuf, String> {
+    let own = get_crate_info(manifest_path);
+    match (&own, package) {
+        (Ok(_), None) => return Ok(manifest_path.to_path_buf()),
+        (Ok((name, _)), Some(package)) if name == package => return Ok(manifest_path.to_path_buf()),
+        _ => {}
+    }
+    let members = workspace_members(manifest_path)?;
+    let names = members.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(", ");
+    match (package, members.as_slice()) {
+        (None, []) => own.map(|_| manifest_path.to_path_buf()),
+        (Some(package), []) => Err(format!("--package {}: {} is not a workspace", package, manifest_path.display())),
+        (Some(package), members) => {
+            members.iter().find(|(name, _)| name == package).map(|(_, path)| path.clone()).ok_or_else(|| {
+                format!(
+                    "--package {}: not a member of the workspace {} (members: {})",
+                    package,
+                    manifest_path.display(),
+                    names
+                )
+            })
+        }
+        (None, [(_, only)]) => Ok(only.clone()),
+        (None, _) => Err(format!(
+            "{} is a workspace; choose the crate to test with --package (one of: {})",
+            manifest_path.display(),
+            names
+        )),
+    }
+}
+
 /// Resolve a `version.workspace = true` member by walking up to the workspace
 /// root and reading `[workspace.package].version`.
 fn resolve_workspace_version(manifest_path: &Path) -> Option<String> {
     let mut dir = manifest_path.parent()?.to_path_buf();
     loop {
-        if let Ok(s) = load_string(&dir.join("Cargo.toml")) {
-            if let Ok(v) = toml::from_str::<toml::Value>(&s) {
-                if let Some(ver) = v
-                    .get("workspace")
-                    .and_then(|w| w.get("package"))
-                    .and_then(|p| p.get("version"))
-                    .and_then(|ver| ver.as_str())
-                {
-                    return Some(ver.to_string());
-                }
-            }
+        if let Ok(s) = load_string(&dir.join("Cargo.toml"))
+            && let Ok(v) = toml::from_str::<toml::Value>(&s)
+            && let Some(ver) = v
+                .get("workspace")
+                .and_then(|w| w.get("package"))
+                .and_then(|p| p.get("version"))
+                .and_then(|ver| ver.as_str())
+        {
+            return Some(ver.to_string());
         }
         dir = dir.parent()?.to_path_buf();
     }
@@ -67,14 +158,6 @@ pub fn load_string(path: &Path) -> Result<String, String> {
     Ok(s)
 }
 
-/// Reverse dependency (dependent crate) information
-#[derive(Debug, Clone)]
-pub struct RevDep {
-    pub name: String,
-    pub vers: Version,
-    pub resolved_version: Option<String>,
-}
-
 /// Parse dependent specification in "name:version" format
 ///
 /// Returns (name, optional_version)
@@ -91,12 +174,16 @@ pub fn parse_dependent_spec(spec: &str) -> (String, Option<String>) {
 pub fn depends_on(manifest_path: &Path, crate_name: &str) -> Result<bool, String> {
     let toml_str = load_string(manifest_path)?;
     let value: toml::Value = toml::from_str(&toml_str).map_err(|e| format!("Failed to parse TOML: {}", e))?;
+    Ok(declares(&value, crate_name))
+}
 
+/// Whether a parsed manifest lists `crate_name` in any of the sections `depends_on` checks
+pub fn declares(value: &toml::Value, crate_name: &str) -> bool {
     for section in &["dependencies", "dev-dependencies", "build-dependencies"] {
         if let Some(toml::Value::Table(deps)) = value.get(section)
             && deps.contains_key(crate_name)
         {
-            return Ok(true);
+            return true;
         }
     }
 
@@ -108,12 +195,12 @@ pub fn depends_on(manifest_path: &Path, crate_name: &str) -> Result<bool, String
                     if let Some(toml::Value::Table(deps)) = target_table.get(*section)
                         && deps.contains_key(crate_name)
                     {
-                        return Ok(true);
+                        return true;
                     }
                 }
             }
         }
     }
 
-    Ok(false)
+    false
 }
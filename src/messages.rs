@@ -0,0 +1,195 @@
+/// User-facing message catalog (`--messages <FILE>`)
+///
+/// Status labels, column titles and report headings are looked up here by key instead of
+/// being hardcoded, so a TOML file can translate them or rename categories:
+///
+/// ```toml
+/// [status]
+/// failed = "{step} kaputt"
+///
+/// [simple]
+/// regression = "BREAKING"
+/// ```
+///
+/// Sections flatten to dotted keys (`status.failed`). Unknown keys, and placeholders the
+/// default text doesn't have, are rejected when the file is loaded. `cargo-copter messages`
+/// prints the full default catalog as a starting point. The loaded [`Catalog`] travels on
+/// the console `Layout` and is passed to whatever else prints or exports labels.
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Every key with its English default, grouped by section
+const DEFAULTS: &[(&str, &str)] = &[
+    // Row status labels (console Result column, markdown, JSON `status_label`)
+    ("status.passed", "passed"),
+    ("status.fixed", "fixed"),
+    ("status.not_used", "not used"),
+    ("status.uses_fork", "uses fork"),
+    ("status.skipped", "skipped"),
+    ("status.failed", "{step} failed"),
+    ("status.broken", "{step} broken"),
+    ("status.timeout", "{step} timeout"),
+    ("status.env_failure", "env failure"),
+    // Step names used by the status labels
+    ("step.fetch", "fetch"),
+    ("step.check", "build"),
+    ("step.test", "test"),
+    // Console table column titles
+    ("column.offered", "Offered"),
+    ("column.spec", "Spec"),
+    ("column.resolved", "Resolved"),
+    ("column.dependent", "Dependent"),
+    ("column.result", "Result"),
+    ("column.time", "Time"),
+    ("column.diagnostics", "Diags"),
+    ("column.started", "Started"),
+    // Baseline failure categories
+    ("category.environment", "Environment"),
+    ("category.yanked_deps", "Yanked deps"),
+    ("category.build_script", "build.rs"),
+    ("category.system_library", "System libs"),
+    ("category.nightly_feature", "Nightly"),
+    ("category.version_conflict", "Version conflicts"),
+    ("category.platform_specific", "Platform"),
+    ("category.other", "Other"),
+    // Compatibility report headings
+    ("heading.baseline_report", "BASELINE REPORT"),
+    ("heading.broken_by_category", "BROKEN BY CATEGORY:"),
+    ("heading.compatibility_report", "COMPATIBILITY REPORT"),
+    ("heading.your_changes", "YOUR CHANGES"),
+    ("heading.regressions", "REGRESSIONS (investigate these):"),
+    ("heading.fixed", "FIXED BY YOUR CHANGES:"),
+    ("heading.not_your_problem", "NOT YOUR PROBLEM"),
+    ("heading.summary", "SUMMARY"),
+    // `--simple` line prefixes
+    ("simple.regression", "REGRESSION"),
+    ("simple.ok", "OK"),
+    ("simple.env_failure", "ENV FAILURE"),
+    ("simple.not_used", "NOT USED"),
+    ("simple.uses_fork", "USES FORK"),
+    ("simple.broken", "BROKEN"),
+    ("simple.baseline_failed", "BASELINE FAILED"),
+    ("simple.regressed", "REGRESSED"),
+    ("simple.worked", "WORKED"),
+    ("simple.broken_already", "BROKEN ALREADY"),
+];
+
+/// Overrides on top of the English defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Catalog {
+    overrides: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Parse a messages file; every key must exist in the default catalog
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let table: toml::Table = text.parse().map_err(|e| format!("Invalid messages file: {}", e))?;
+        let mut overrides = HashMap::new();
+        flatten("", &table, &mut overrides)?;
+
+        for (key, value) in &overrides {
+            let default = default_text(key).ok_or_else(|| format!("Unknown message key '{}'", key))?;
+            if let Some(name) = placeholders(value).find(|p| !placeholders(default).any(|d| d == *p)) {
+                return Err(format!("Message '{}' uses {{{}}}, which is not available here", key, name));
+            }
+        }
+        Ok(Catalog { overrides })
+    }
+
+    /// Text for `key`; the key itself if it isn't in the catalog
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.overrides.get(key).map(String::as_str).or_else(|| default_text(key)).unwrap_or(key)
+    }
+
+    /// Owned text for `key`
+    pub fn text(&self, key: &str) -> String {
+        self.get(key).to_string()
+    }
+
+    /// Text for `key` with `{name}` placeholders filled in
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let mut out = self.get(key).to_string();
+        for (name, value) in args {
+            out = out.replace(&format!("{{{}}}", name), value);
+        }
+        out
+    }
+}
+
+fn flatten(prefix: &str, table: &toml::Table, out: &mut HashMap<String, String>) -> Result<(), String> {
+    for (name, value) in table {
+        let key = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+        match value {
+            toml::Value::String(s) => {
+                out.insert(key, s.clone());
+            }
+            toml::Value::Table(inner) => flatten(&key, inner, out)?,
+            _ => return Err(format!("Message '{}' must be a string", key)),
+        }
+    }
+    Ok(())
+}
+
+fn default_text(key: &str) -> Option<&'static str> {
+    DEFAULTS.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+/// `{name}` placeholders in a message
+fn placeholders(text: &str) -> impl Iterator<Item = &str> {
+    text.split('{').skip(1).filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+}
+
+/// Load the messages file at `path`
+pub fn load(path: &Path) -> Result<Catalog, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read messages {}: {}", path.display(), e))?;
+    Catalog::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// The default catalog as a messages file (`cargo-copter messages`)
+pub fn default_toml() -> String {
+    let mut out = String::new();
+    let mut section = "";
+    for (key, value) in DEFAULTS {
+        let (sec, name) = key.split_once('.').unwrap_or(("", key));
+        if sec != section {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&format!("[{}]\n", sec));
+            section = sec;
+        }
+        out.push_str(&format!("{} = {}\n", name, toml::Value::String(value.to_string())));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_overrides() {
+        let catalog =
+            Catalog::parse("[status]\nfailed = \"{step} kaputt\"\n\n[simple]\nregression = \"BREAKING\"\n").unwrap();
+        assert_eq!(catalog.format("status.failed", &[("step", "build")]), "build kaputt");
+        assert_eq!(catalog.get("simple.regression"), "BREAKING");
+        assert_eq!(catalog.get("status.passed"), "passed");
+        assert_eq!(catalog.get("no.such.key"), "no.such.key");
+    }
+
+    #[test]
+    fn test_catalog_rejects_bad_files() {
+        assert!(Catalog::parse("[status]\npased = \"ok\"\n").unwrap_err().contains("Unknown message key"));
+        assert!(Catalog::parse("[status]\npassed = \"{step} ok\"\n").unwrap_err().contains("{step}"));
+        assert!(Catalog::parse("[status]\npassed = 1\n").is_err());
+    }
+
+    #[test]
+    fn test_default_toml_round_trips() {
+        let catalog = Catalog::parse(&default_toml()).unwrap();
+        for (key, value) in DEFAULTS {
+            assert_eq!(catalog.get(key), *value);
+        }
+    }
+}
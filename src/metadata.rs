@@ -1,5 +1,6 @@
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
 
 #[derive(Debug)]
 pub struct ParsedMetadata {
@@ -102,6 +103,203 @@ pub fn find_all_versions(parsed: &ParsedMetadata, crate_name: &str) -> Vec<Versi
     versions
 }
 
+/// How the resolve root reaches a crate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Usage {
+    /// Resolved version of the crate
+    pub version: String,
+    /// Requirement on the edge that reaches it
+    pub spec: String,
+    /// Proc-macro and build-dependency packages between the root and the crate
+    pub via: Vec<String>,
+    /// Only compiled for the host: the root's build script or a proc-macro uses it
+    pub build_time: bool,
+    /// Package source: `registry+...`, `git+...`, or None for a path
+    pub source: Option<String>,
+    /// Its Cargo.toml
+    pub manifest_path: String,
+    /// Its features as resolved for the root's build (the root's own, and any other
+    /// package's that cargo unified with them), sorted
+    pub features: Vec<String>,
+}
+
+impl Usage {
+    /// "git https://github.com/someone/rgb" or "path vendor/rgb" when the crate comes from
+    /// a git or path copy rather than a registry; `ours` is copter's own patch directory
+    pub fn fork(&self, root_dir: &Path, ours: Option<&Path>) -> Option<String> {
+        match &self.source {
+            Some(source) => {
+                let url = source.strip_prefix("git+")?;
+                Some(format!("git {}", url.split(['?', '#']).next().unwrap_or(url)))
+            }
+            None => {
+                let dir = Path::new(&self.manifest_path).parent()?;
+                let canonical = crate::paths::canonicalize(dir);
+                if ours.is_some_and(|ours| canonical.starts_with(crate::paths::canonicalize(ours))) {
+                    return None;
+                }
+                Some(format!("path {}", dir.strip_prefix(root_dir).unwrap_or(dir).display()))
+            }
+        }
+    }
+}
+
+/// How the root package uses `crate_name`: directly (a normal, dev or build dependency),
+/// or through the build-time units cargo also compiles (its build-dependencies and
+/// proc-macro dependencies, and what those depend on); None when neither does
+pub fn root_usage(parsed: &ParsedMetadata, crate_name: &str) -> Option<Usage> {
+    let resolve = parsed.resolve.as_ref()?;
+    let root = resolve.get("root").and_then(|r| r.as_str())?;
+    let nodes: HashMap<&str, &Vec<Value>> = resolve
+        .get("nodes")
+        .and_then(|n| n.as_array())?
+        .iter()
+        .filter_map(|node| Some((node.get("id")?.as_str()?, node.get("deps")?.as_array()?)))
+        .collect();
+    let features: HashMap<&str, Vec<String>> = resolve
+        .get("nodes")
+        .and_then(|n| n.as_array())?
+        .iter()
+        .filter_map(|node| {
+            let features = node.get("features")?.as_array()?.iter().filter_map(|f| Some(f.as_str()?.to_string()));
+            Some((node.get("id")?.as_str()?, features.collect()))
+        })
+        .collect();
+    let package = |id: &str, field: &str| {
+        parsed.packages.get(id).and_then(|p| p.get(field)).and_then(|v| v.as_str()).unwrap_or("").to_string()
+    };
+    let is_proc_macro = |id: &str| {
+        parsed.packages.get(id).and_then(|p| p.get("targets")).and_then(|t| t.as_array()).is_some_and(|targets| {
+            targets.iter().any(|t| t["kind"].as_array().is_some_and(|k| k.iter().any(|k| k == "proc-macro")))
+        })
+    };
+    let kinds = |dep: &Value| -> Vec<String> {
+        dep.get("dep_kinds")
+            .and_then(|k| k.as_array())
+            .map(|kinds| kinds.iter().map(|k| k["kind"].as_str().unwrap_or("normal").to_string()).collect())
+            .unwrap_or_default()
+    };
+    let usage = |parent: &str, pkg: &str, via: Vec<String>, build_time: bool| Usage {
+        version: package(pkg, "version"),
+        spec: get_version_spec(parsed, parent, crate_name).unwrap_or_else(|_| "?".to_string()),
+        via,
+        build_time,
+        source: parsed.packages.get(pkg).and_then(|p| p["source"].as_str()).map(str::to_string),
+        manifest_path: package(pkg, "manifest_path"),
+        features: {
+            let mut features = features.get(pkg).cloned().unwrap_or_default();
+            features.sort();
+            features
+        },
+    };
+
+    // A direct edge wins; the build-time units are searched breadth-first after it
+    let mut host: VecDeque<(&str, Vec<String>)> = VecDeque::new();
+    for dep in nodes.get(root)?.iter() {
+        let Some(pkg) = dep.get("pkg").and_then(|p| p.as_str()) else { continue };
+        let kinds = kinds(dep);
+        if package(pkg, "name") == crate_name {
+            return Some(usage(root, pkg, vec![], !kinds.is_empty() && kinds.iter().all(|k| k == "build")));
+        }
+        if is_proc_macro(pkg) || kinds.iter().any(|k| k == "build") {
+            host.push_back((pkg, vec![package(pkg, "name")]));
+        }
+    }
+    let mut seen: HashSet<&str> = host.iter().map(|(id, _)| *id).collect();
+    while let Some((id, via)) = host.pop_front() {
+        for dep in nodes.get(id).into_iter().flat_map(|deps| deps.iter()) {
+            let Some(pkg) = dep.get("pkg").and_then(|p| p.as_str()) else { continue };
+            if package(pkg, "name") == crate_name {
+                return Some(usage(id, pkg, via, true));
+            }
+            // Dev-dependencies of a dependency are never built
+            let kinds = kinds(dep);
+            if seen.insert(pkg) && (kinds.is_empty() || kinds.iter().any(|k| k != "dev")) {
+                let mut via = via.clone();
+                via.push(package(pkg, "name"));
+                host.push_back((pkg, via));
+            }
+        }
+    }
+    None
+}
+
+/// A package in the tree that keeps the base crate on a version other than the offered one
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Blocker {
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub version: String,
+    /// Its requirement on the base crate ("=0.8.50")
+    pub spec: String,
+    /// The base crate version it resolved to
+    pub resolved: String,
+    /// Package names from the root down to it
+    pub path: Vec<String>,
+}
+
+/// Every package depending on `crate_name` at a version other than `offered`, with the
+/// shortest path to it from the resolve root
+pub fn blockers(parsed: &ParsedMetadata, crate_name: &str, offered: &str) -> Vec<Blocker> {
+    let Some(resolve) = &parsed.resolve else { return vec![] };
+    let nodes: Vec<(&str, Vec<&str>)> = resolve
+        .get("nodes")
+        .and_then(|n| n.as_array())
+        .map(|nodes| {
+            nodes
+                .iter()
+                .filter_map(|node| {
+                    let deps = node.get("deps")?.as_array()?.iter().filter_map(|d| d.get("pkg")?.as_str());
+                    Some((node.get("id")?.as_str()?, deps.collect()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let package = |id: &str, field: &str| {
+        parsed.packages.get(id).and_then(|p| p.get(field)).and_then(|v| v.as_str()).unwrap_or("").to_string()
+    };
+
+    // Breadth-first parents from the root, for the shortest path to each package
+    let deps: HashMap<&str, &Vec<&str>> = nodes.iter().map(|(id, deps)| (*id, deps)).collect();
+    let mut parents: HashMap<&str, &str> = HashMap::new();
+    let root = resolve.get("root").and_then(|r| r.as_str());
+    let mut queue: VecDeque<&str> = root.into_iter().collect();
+    while let Some(id) = queue.pop_front() {
+        for dep in deps.get(id).into_iter().flat_map(|deps| deps.iter()) {
+            if Some(*dep) != root && !parents.contains_key(dep) {
+                parents.insert(dep, id);
+                queue.push_back(dep);
+            }
+        }
+    }
+    let path = |id: &str| {
+        let mut path = vec![package(id, "name")];
+        let mut current = id;
+        while let Some(parent) = parents.get(current) {
+            path.push(package(parent, "name"));
+            current = parent;
+        }
+        path.reverse();
+        path
+    };
+
+    let mut blockers = Vec::new();
+    for (id, deps) in &nodes {
+        for pkg in deps {
+            if package(pkg, "name") == crate_name && package(pkg, "version") != offered {
+                blockers.push(Blocker {
+                    crate_name: package(id, "name"),
+                    version: package(id, "version"),
+                    spec: get_version_spec(parsed, id, crate_name).unwrap_or_else(|_| "?".to_string()),
+                    resolved: package(pkg, "version"),
+                    path: path(id),
+                });
+            }
+        }
+    }
+    blockers
+}
+
 /// Get the dependent's name and version from a node ID
 /// Returns (name, version) or None if parsing fails
 pub fn parse_node_id(node_id: &str) -> Option<(String, String)> {
@@ -141,6 +339,116 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_root_usage_through_build_time_units() {
+        let package = |id: &str, name: &str, version: &str, kind: &str, deps: &[(&str, &str)]| {
+            serde_json::json!({
+                "id": id, "name": name, "version": version,
+                "targets": [{"kind": [kind]}],
+                "dependencies": deps.iter().map(|(name, req)| serde_json::json!({"name": name, "req": req})).collect::<Vec<_>>(),
+            })
+        };
+        let edge = |pkg: &str, kind: Option<&str>| serde_json::json!({"pkg": pkg, "dep_kinds": [{"kind": kind}]});
+        let metadata = |root_deps: Vec<Value>| {
+            serde_json::json!({
+                "packages": [
+                    package("app", "app", "1.0.0", "lib", &[("app-derive", "1"), ("rgb", "0.8"), ("log", "0.4")]),
+                    package("derive", "app-derive", "1.0.0", "proc-macro", &[("palette", "0.7")]),
+                    package("palette", "palette", "0.7.0", "lib", &[("rgb", "^0.8.50")]),
+                    package("log", "log", "0.4.0", "lib", &[]),
+                    package("rgb", "rgb", "0.8.92", "lib", &[]),
+                ],
+                "resolve": {"root": "app", "nodes": [
+                    {"id": "app", "deps": root_deps},
+                    {"id": "derive", "deps": [edge("palette", None)]},
+                    {"id": "palette", "deps": [edge("rgb", None)]},
+                    {"id": "log", "deps": []},
+                    {"id": "rgb", "deps": [], "features": ["std", "default"]},
+                ]},
+            })
+            .to_string()
+        };
+
+        // Only a proc-macro dependency's own dependency uses rgb
+        let parsed = parse_metadata(&metadata(vec![edge("derive", None), edge("log", None)])).unwrap();
+        let usage = root_usage(&parsed, "rgb").unwrap();
+        assert_eq!(usage.version, "0.8.92");
+        assert_eq!(usage.spec, "^0.8.50");
+        assert_eq!(usage.via, ["app-derive", "palette"]);
+        assert_eq!(usage.features, ["default", "std"]);
+        assert!(usage.build_time);
+
+        // build.rs only
+        let parsed = parse_metadata(&metadata(vec![edge("rgb", Some("build"))])).unwrap();
+        let usage = root_usage(&parsed, "rgb").unwrap();
+        assert_eq!((usage.spec.as_str(), usage.via.len(), usage.build_time), ("0.8", 0, true));
+
+        let parsed = parse_metadata(&metadata(vec![edge("rgb", None), edge("derive", None)])).unwrap();
+        assert!(!root_usage(&parsed, "rgb").unwrap().build_time);
+        let parsed = parse_metadata(&metadata(vec![edge("log", None)])).unwrap();
+        assert_eq!(root_usage(&parsed, "rgb"), None);
+    }
+
+    #[test]
+    fn test_blockers_with_paths() {
+        let package = |name: &str, version: &str, deps: &[(&str, &str)]| {
+            serde_json::json!({
+                "id": name, "name": name, "version": version,
+                "dependencies": deps.iter().map(|(name, req)| serde_json::json!({"name": name, "req": req})).collect::<Vec<_>>(),
+            })
+        };
+        let node = |id: &str, deps: &[&str]| serde_json::json!({"id": id, "deps": deps.iter().map(|d| serde_json::json!({"pkg": d})).collect::<Vec<_>>()});
+        let json = serde_json::json!({
+            "packages": [
+                package("image", "1.0.0", &[("tiff", "0.9"), ("rgb", "0.8")]),
+                package("tiff", "0.9.0", &[("weezl", "0.1")]),
+                package("weezl", "0.1.0", &[("rgb", "=0.8.50")]),
+                {"id": "rgb", "name": "rgb", "version": "0.8.92", "dependencies": []},
+                {"id": "rgb-old", "name": "rgb", "version": "0.8.50", "dependencies": []},
+            ],
+            "resolve": {"root": "image", "nodes": [
+                node("image", &["tiff", "rgb"]),
+                node("tiff", &["weezl"]),
+                node("weezl", &["rgb-old"]),
+                node("rgb", &[]),
+                node("rgb-old", &[]),
+            ]},
+        });
+        let parsed = parse_metadata(&json.to_string()).unwrap();
+        assert_eq!(
+            blockers(&parsed, "rgb", "0.8.92"),
+            [Blocker {
+                crate_name: "weezl".to_string(),
+                version: "0.1.0".to_string(),
+                spec: "=0.8.50".to_string(),
+                resolved: "0.8.50".to_string(),
+                path: vec!["image".to_string(), "tiff".to_string(), "weezl".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_usage_fork() {
+        let usage = |source: Option<&str>, manifest_path: &str| Usage {
+            version: "0.8.91".to_string(),
+            spec: "0.8".to_string(),
+            via: vec![],
+            build_time: false,
+            source: source.map(str::to_string),
+            manifest_path: manifest_path.to_string(),
+            features: vec![],
+        };
+        let root = Path::new("/staging/image-1.0.0");
+        let registry = usage(Some("registry+https://github.com/rust-lang/crates.io-index"), "/reg/rgb/Cargo.toml");
+        assert_eq!(registry.fork(root, None), None);
+        let git = usage(Some("git+https://github.com/someone/rgb?branch=fix#abc123"), "/git/rgb/Cargo.toml");
+        assert_eq!(git.fork(root, None).as_deref(), Some("git https://github.com/someone/rgb"));
+        let vendored = usage(None, "/staging/image-1.0.0/vendor/rgb/Cargo.toml");
+        assert_eq!(vendored.fork(root, None).as_deref(), Some("path vendor/rgb"));
+        let patched = usage(None, "/src/rgb/Cargo.toml");
+        assert_eq!(patched.fork(root, Some(Path::new("/src/rgb"))), None);
+    }
+
     #[test]
     fn test_get_version_spec_not_found() {
         let json = r#"{"packages": [], "resolve": {"nodes": []}}"#;
@@ -0,0 +1,183 @@
+/// `--index-url` / `COPTER_INDEX`: a crates.io mirror for API calls, downloads and cargo
+///
+/// Where crates.io is slow or blocked, mirrors serve its sparse index and `.crate` files
+/// (some also proxy the web API). Given a sparse index (`sparse+https://mirror/index/`),
+/// copter reads the index's `config.json` for the download URL template (`dl`) and web API
+/// root (`api`): version lookups read the index files, crate downloads go to `dl`, API
+/// calls (reverse dependencies, owners) go to the mirror's `api` when it has one and to
+/// crates.io otherwise, and cargo's own fetches replace crates.io with the index through
+/// `--config source.*`. Given a URL without an index `config.json`, it is taken as a
+/// registry web API base (`https://mirror/api/v1`), like `COPTER_REGISTRY_API`.
+use crate::api::VersionDownloads;
+
+/// Environment variable read when `--index-url` is not given
+pub const INDEX_ENV: &str = "COPTER_INDEX";
+
+/// Name of the replacement source in cargo's `--config`
+const SOURCE_NAME: &str = "copter-mirror";
+
+/// crates.io's sparse index, read for `.crate` checksums when no mirror is set
+pub const CRATES_IO_INDEX: &str = "https://index.crates.io";
+
+/// A crates.io mirror's endpoints
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mirror {
+    /// Sparse index root, without `sparse+` or a trailing slash
+    pub index: Option<String>,
+    /// Download URL template (`{crate}`, `{version}`, `{prefix}`, `{lowerprefix}`), or a
+    /// base that `/{crate}/{version}/download` is appended to
+    pub dl: String,
+    /// Registry web API base (`…/api/v1`), if the mirror serves one
+    pub api: Option<String>,
+}
+
+impl Mirror {
+    /// Read a sparse index's `config.json`, or treat `url` as a registry API base
+    pub fn discover(url: &str) -> Result<Self, String> {
+        let sparse = url.starts_with("sparse+");
+        let root = url.trim_start_matches("sparse+").trim_end_matches('/');
+        if !root.starts_with("http://") && !root.starts_with("https://") {
+            return Err(format!("--index-url must be an http(s) URL, got {}", url));
+        }
+        let config_url = format!("{}/config.json", root);
+        match crate::download::http_get_bytes(&config_url) {
+            Ok(body) => {
+                let config: serde_json::Value = serde_json::from_slice(&body)
+                    .map_err(|e| format!("{} is not a registry index config: {}", config_url, e))?;
+                Self::from_index_config(root, &config).ok_or_else(|| format!("{} has no \"dl\" URL", config_url))
+            }
+            Err(e) if sparse => Err(format!("Failed to read {}: {}", config_url, e)),
+            Err(_) => Ok(Mirror { index: None, dl: format!("{}/crates", root), api: Some(root.to_string()) }),
+        }
+    }
+
+    fn from_index_config(root: &str, config: &serde_json::Value) -> Option<Self> {
+        let dl = config.get("dl")?.as_str()?.trim_end_matches('/').to_string();
+        // `api` is the web root; the endpoints live under /api/v1. crates.io's own API is
+        // reached through the API client instead
+        let api = config
+            .get("api")
+            .and_then(|a| a.as_str())
+            .map(|a| a.trim_end_matches('/'))
+            .filter(|a| !a.is_empty() && *a != "https://crates.io")
+            .map(|a| format!("{}/api/v1", a));
+        Some(Mirror { index: Some(root.to_string()), dl, api })
+    }
+
+    /// Where to download `crate_name`'s `.crate` for `version`
+    pub fn download_url(&self, crate_name: &str, version: &str) -> String {
+        let markers = ["{crate}", "{version}", "{prefix}", "{lowerprefix}"];
+        if !markers.iter().any(|m| self.dl.contains(m)) {
+            return format!("{}/{}/{}/download", self.dl, crate_name, version);
+        }
+        let prefix = index_prefix(crate_name);
+        self.dl
+            .replace("{crate}", crate_name)
+            .replace("{version}", version)
+            .replace("{lowerprefix}", &prefix.to_lowercase())
+            .replace("{prefix}", &prefix)
+    }
+
+    /// Every published version of `crate_name` from the sparse index (no download counts)
+    pub fn fetch_versions(&self, crate_name: &str) -> Result<Option<Vec<VersionDownloads>>, String> {
+        let Some(index) = &self.index else { return Ok(None) };
+        Ok(Some(parse_index_file(&fetch_index_file(index, crate_name)?)))
+    }
+
+    /// `--config` values that make cargo fetch crates.io packages from the index
+    pub fn cargo_config(&self) -> Vec<String> {
+        let Some(index) = &self.index else { return vec![] };
+        vec![
+            format!("source.crates-io.replace-with=\"{}\"", SOURCE_NAME),
+            format!("source.{}.registry=\"sparse+{}/\"", SOURCE_NAME, index),
+        ]
+    }
+}
+
+/// The index directory of a crate name: `1`, `2`, `3/a`, or `ab/cd`
+fn index_prefix(crate_name: &str) -> String {
+    match crate_name.len() {
+        0..=2 => crate_name.len().to_string(),
+        3 => format!("3/{}", &crate_name[..1]),
+        _ => format!("{}/{}", &crate_name[..2], &crate_name[2..4]),
+    }
+}
+
+/// The `cksum` (sha256 of the `.crate` file) the sparse index at `index` lists for
+/// `crate_name` `version`; `None` if the index has no such version
+pub fn index_checksum(index: &str, crate_name: &str, version: &str) -> Result<Option<String>, String> {
+    Ok(parse_checksum(&fetch_index_file(index, crate_name)?, version))
+}
+
+fn fetch_index_file(index: &str, crate_name: &str) -> Result<String, String> {
+    let lower = crate_name.to_lowercase();
+    let url = format!("{}/{}/{}", index, index_prefix(&lower), lower);
+    let body = crate::download::http_get_bytes(&url)
+        .map_err(|e| format!("Failed to fetch index entry for {}: {}", crate_name, e))?;
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+fn parse_checksum(text: &str, version: &str) -> Option<String> {
+    text.lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .find(|entry| entry.get("vers").and_then(|v| v.as_str()) == Some(version))
+        .and_then(|entry| Some(entry.get("cksum")?.as_str()?.to_string()))
+}
+
+/// One JSON object per line, one line per published version
+fn parse_index_file(text: &str) -> Vec<VersionDownloads> {
+    text.lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter_map(|entry| {
+            Some(VersionDownloads {
+                version: entry.get("vers")?.as_str()?.to_string(),
+                downloads: 0,
+                yanked: entry.get("yanked").and_then(|y| y.as_bool()).unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_config_and_urls() {
+        let config = serde_json::json!({
+            "dl": "https://mirror.example/crates/{crate}/{prefix}/{crate}-{version}.crate",
+            "api": "https://crates.io"
+        });
+        let mirror = Mirror::from_index_config("https://mirror.example/index", &config).unwrap();
+        assert_eq!(mirror.api, None);
+        assert_eq!(mirror.download_url("rgb", "0.8.50"), "https://mirror.example/crates/rgb/3/r/rgb-0.8.50.crate");
+        assert_eq!(
+            mirror.cargo_config(),
+            [
+                "source.crates-io.replace-with=\"copter-mirror\"",
+                "source.copter-mirror.registry=\"sparse+https://mirror.example/index/\""
+            ]
+        );
+        assert_eq!(index_prefix("a"), "1");
+        assert_eq!(index_prefix("image"), "im/ag");
+
+        let plain = Mirror::from_index_config(
+            "https://mirror.example/index",
+            &serde_json::json!({"dl": "https://mirror.example/api/v1/crates", "api": "https://mirror.example/"}),
+        )
+        .unwrap();
+        assert_eq!(plain.api.as_deref(), Some("https://mirror.example/api/v1"));
+        assert_eq!(plain.download_url("rgb", "0.8.50"), "https://mirror.example/api/v1/crates/rgb/0.8.50/download");
+
+        let versions = parse_index_file(
+            "{\"name\":\"rgb\",\"vers\":\"0.8.49\",\"yanked\":true}\n{\"name\":\"rgb\",\"vers\":\"0.8.50\",\"yanked\":false}\n",
+        );
+        let versions: Vec<(&str, bool)> = versions.iter().map(|v| (v.version.as_str(), v.yanked)).collect();
+        assert_eq!(versions, [("0.8.49", true), ("0.8.50", false)]);
+
+        let index = "{\"name\":\"rgb\",\"vers\":\"0.8.50\",\"cksum\":\"a0\"}\n{\"name\":\"rgb\",\"vers\":\"0.8.51\"}\n";
+        assert_eq!(parse_checksum(index, "0.8.50").as_deref(), Some("a0"));
+        assert_eq!(parse_checksum(index, "0.8.51"), None);
+        assert_eq!(parse_checksum(index, "0.8.52"), None);
+    }
+}
@@ -0,0 +1,204 @@
+/// `--prefetch DIR` and `--offline DIR`: sweeps on a machine without network access
+///
+/// `--prefetch DIR` runs the matrix online with only the fetch step and leaves in DIR
+/// everything a later run needs:
+///
+/// - `vendor/`: every registry crate the dependents resolve, baseline and offered versions
+///   alike (`cargo vendor --versioned-dirs --no-delete` after each fetch, patches applied)
+/// - `crates/`: the `.crate` files copter stages itself (dependents, published base
+///   versions), laid out like the crate cache
+/// - `versions/` and `dependents/`: the crates.io lookups the matrix was resolved from
+/// - `config.toml`: the cargo config `cargo vendor` printed, merged across dependents,
+///   replacing crates.io (and git sources) with `vendor/`
+///
+/// Copy DIR to the air-gapped machine and run the same command with `--offline DIR`
+/// instead: version and dependent lookups are answered from DIR, `.crate` files are only
+/// read from it, and every cargo step runs with `--offline` and the source replacement of
+/// `config.toml` (its `directory` pointed at DIR/vendor wherever DIR now is). A crate that
+/// wasn't prefetched is an error, never a download.
+use crate::api::VersionDownloads;
+use crate::error::CopterError;
+use lazy_static::lazy_static;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+/// The cargo config written to DIR
+const CONFIG_NAME: &str = "config.toml";
+
+lazy_static! {
+    /// Serializes updates of DIR/config.toml between parallel dependents
+    static ref CONFIG_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// `--prefetch` or `--offline`, with its directory (`Registry.offline`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Prefetch(PathBuf),
+    Offline(PathBuf),
+}
+
+impl Mode {
+    pub fn dir(&self) -> &Path {
+        match self {
+            Mode::Prefetch(dir) | Mode::Offline(dir) => dir,
+        }
+    }
+
+    /// The mode with its directory made absolute (and created, for `--prefetch`);
+    /// `--offline` needs a directory `--prefetch` filled
+    pub fn resolve(self) -> Result<Mode, CopterError> {
+        if let Mode::Prefetch(dir) = &self {
+            std::fs::create_dir_all(dir.join("vendor"))
+                .map_err(|e| CopterError::Config(format!("--prefetch: cannot create {}: {}", dir.display(), e)))?;
+        }
+        let dir = crate::paths::canonicalize(self.dir());
+        match self {
+            Mode::Prefetch(_) => Ok(Mode::Prefetch(dir)),
+            Mode::Offline(_) if !dir.join(CONFIG_NAME).is_file() => Err(CopterError::Config(format!(
+                "--offline: {} has no {} (fill it with --prefetch {} first)",
+                dir.display(),
+                CONFIG_NAME,
+                dir.display()
+            ))),
+            Mode::Offline(_) => Ok(Mode::Offline(dir)),
+        }
+    }
+
+    /// The published versions of `crate_name` as prefetched, when `--offline`
+    pub fn saved_versions(&self, crate_name: &str) -> Option<Result<Vec<VersionDownloads>, String>> {
+        let Mode::Offline(dir) = self else { return None };
+        let path = versions_path(dir, crate_name);
+        let saved = std::fs::read_to_string(&path)
+            .map_err(|_| format!("the versions of {} were not prefetched ({})", crate_name, path.display()))
+            .and_then(|text| serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e)));
+        Some(saved)
+    }
+
+    /// Keep a version lookup for `--offline`, when `--prefetch`ing
+    pub fn record_versions(&self, crate_name: &str, versions: &[VersionDownloads]) {
+        let Mode::Prefetch(dir) = self else { return };
+        let path = versions_path(dir, crate_name);
+        let written = std::fs::create_dir_all(dir.join("versions"))
+            .and_then(|_| std::fs::write(&path, serde_json::to_string(versions).unwrap_or_default()));
+        if let Err(e) = written {
+            log::warn!("--prefetch: cannot write {}: {}", path.display(), e);
+        }
+    }
+
+    /// Arguments for every cargo step when `--offline`: `--offline` and the prefetched
+    /// source replacement as `--config` values
+    pub fn cargo_args(&self) -> Vec<String> {
+        let Mode::Offline(dir) = self else { return vec![] };
+        let config = std::fs::read_to_string(dir.join(CONFIG_NAME)).ok().and_then(|text| text.parse().ok());
+        let mut args = vec!["--offline".to_string()];
+        for value in config_values(&config.unwrap_or_default(), &dir.join("vendor")) {
+            args.push("--config".to_string());
+            args.push(value);
+        }
+        args
+    }
+
+    /// When `--prefetch`ing, vendor what the fetch that ran as `fetch_args` in `crate_path`
+    /// resolved; an error holds cargo vendor's stderr
+    pub fn vendor(&self, crate_path: &Path, fetch_args: &[String]) -> Result<(), String> {
+        let Mode::Prefetch(dir) = self else { return Ok(()) };
+        let args = fetch_args.iter().map(|arg| if arg == "fetch" { "vendor" } else { arg.as_str() });
+        let output = Command::new("cargo")
+            .args(args)
+            .args(["--versioned-dirs", "--no-delete", "--respect-source-config"])
+            .arg(dir.join("vendor"))
+            .current_dir(crate_path)
+            .output()
+            .map_err(|e| format!("Failed to run cargo vendor: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("cargo vendor failed:\n{}", String::from_utf8_lossy(&output.stderr)));
+        }
+        let printed: toml::Table = String::from_utf8_lossy(&output.stdout)
+            .parse()
+            .map_err(|e| format!("cargo vendor printed an unreadable config: {}", e))?;
+
+        let _lock = CONFIG_LOCK.lock().unwrap();
+        let path = dir.join(CONFIG_NAME);
+        let mut config: toml::Table =
+            std::fs::read_to_string(&path).ok().and_then(|text| text.parse().ok()).unwrap_or_default();
+        merge_sources(&mut config, printed);
+        std::fs::write(&path, toml::to_string(&config).map_err(|e| e.to_string())?)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// "Prefetched 312 crates into /air/vendor; run the same command with --offline /air"
+    pub fn summary_line(&self) -> Option<String> {
+        let Mode::Prefetch(dir) = self else { return None };
+        let crates = std::fs::read_dir(dir.join("vendor")).map(|entries| entries.count()).unwrap_or(0);
+        Some(format!(
+            "Prefetched {} crates into {}; run the same command with --offline {}",
+            crates,
+            dir.join("vendor").display(),
+            dir.display()
+        ))
+    }
+}
+
+fn versions_path(dir: &Path, crate_name: &str) -> PathBuf {
+    dir.join("versions").join(format!("{}.json", crate_name))
+}
+
+/// `source.<name>.<key>=<value>` for each key of `config`'s sources, with every `directory`
+/// replaced by `vendor`
+fn config_values(config: &toml::Table, vendor: &Path) -> Vec<String> {
+    let Some(sources) = config.get("source").and_then(|s| s.as_table()) else { return vec![] };
+    let mut values = Vec::new();
+    for (name, source) in sources {
+        let name = if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            name.clone()
+        } else {
+            toml::Value::String(name.clone()).to_string()
+        };
+        for (key, value) in source.as_table().into_iter().flatten() {
+            let value = match key.as_str() {
+                "directory" => crate::paths::to_toml_string(vendor),
+                _ => value.to_string(),
+            };
+            values.push(format!("source.{}.{}={}", name, key, value));
+        }
+    }
+    values
+}
+
+/// Add the `[source.*]` tables of `printed` to `config`
+fn merge_sources(config: &mut toml::Table, printed: toml::Table) {
+    let Some(toml::Value::Table(printed)) = printed.get("source").cloned() else { return };
+    let sources = config.entry("source").or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    if let toml::Value::Table(sources) = sources {
+        sources.extend(printed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vendor_config_merged_and_relocated() {
+        let mut config = toml::Table::new();
+        let image = "[source.crates-io]\nreplace-with = \"vendored-sources\"\n\n\
+                     [source.vendored-sources]\ndirectory = \"/online/prefetch/vendor\"\n";
+        let png = "[source.\"git+https://github.com/x/png?rev=abc\"]\ngit = \"https://github.com/x/png\"\n\
+                   rev = \"abc\"\nreplace-with = \"vendored-sources\"\n";
+        merge_sources(&mut config, image.parse().unwrap());
+        merge_sources(&mut config, png.parse().unwrap());
+
+        let values = config_values(&config, Path::new("/air/prefetch/vendor"));
+        assert_eq!(
+            values,
+            [
+                "source.crates-io.replace-with=\"vendored-sources\"",
+                "source.\"git+https://github.com/x/png?rev=abc\".git=\"https://github.com/x/png\"",
+                "source.\"git+https://github.com/x/png?rev=abc\".replace-with=\"vendored-sources\"",
+                "source.\"git+https://github.com/x/png?rev=abc\".rev=\"abc\"",
+                "source.vendored-sources.directory=\"/air/prefetch/vendor\"",
+            ]
+        );
+    }
+}
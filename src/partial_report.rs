@@ -0,0 +1,179 @@
+/// report.md written as the run goes, for runs that crash or are killed
+///
+/// The full report.md (a section per offered version, adoption history, re-exporters)
+/// needs every result, so it used to be written only after the last dependent: a run
+/// killed after hours, or a CI job that hit its time limit, left no report at all. Until
+/// the end, report.md now holds the run's header and one table that gains a dependent's
+/// rows, flushed to disk, as soon as all its versions are in; rows of dependents still
+/// running when the run stops are appended on `finish()`. The end-of-run export replaces
+/// the file as before.
+use crate::messages::Catalog;
+use crate::report::{self, StatusStyle};
+use crate::run_metadata::RunMetadata;
+use crate::scrub::Scrubber;
+use crate::types::OfferedRow;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+pub struct PartialReport<W: Write> {
+    out: W,
+    /// Results per dependent (one per base version)
+    expected: usize,
+    /// Rows of dependents with results still to come
+    pending: Vec<Vec<OfferedRow>>,
+    style: StatusStyle,
+    /// Status labels (`--messages`)
+    messages: Arc<Catalog>,
+    /// `--scrub`: applied to each line as it is written
+    scrubber: Option<Scrubber>,
+}
+
+impl PartialReport<File> {
+    /// Start `path` with the run's header
+    pub fn create(
+        path: &Path,
+        crate_name: &str,
+        display_version: &str,
+        total_deps: usize,
+        expected: usize,
+        run: &RunMetadata,
+        scrubber: Option<Scrubber>,
+    ) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut partial = PartialReport::new(file, expected, StatusStyle::default(), scrubber);
+        partial.header(crate_name, display_version, total_deps, run).map_err(|e| e.to_string())?;
+        Ok(partial)
+    }
+}
+
+impl<W: Write> PartialReport<W> {
+    fn new(out: W, expected: usize, style: StatusStyle, scrubber: Option<Scrubber>) -> Self {
+        let messages = Default::default();
+        PartialReport { out, expected: expected.max(1), pending: Vec::new(), style, messages, scrubber }
+    }
+
+    /// Statuses as `style` (`--status-style`, from the console layout)
+    pub fn with_style(self, style: StatusStyle) -> Self {
+        PartialReport { style, ..self }
+    }
+
+    /// Statuses labeled in `messages` (`--messages`, from the console layout)
+    pub fn with_messages(self, messages: Arc<Catalog>) -> Self {
+        PartialReport { messages, ..self }
+    }
+
+    fn header(
+        &mut self,
+        crate_name: &str,
+        display_version: &str,
+        total_deps: usize,
+        run: &RunMetadata,
+    ) -> std::io::Result<()> {
+        self.write(&format!(
+            "# Cargo Copter Test Report (in progress)\n\n**Crate**: {} ({})",
+            crate_name, display_version
+        ))?;
+        self.write(&format!("**Dependents**: {}", total_deps))?;
+        self.write(&format!("**Started**: {} by cargo-copter {}", run.generated, run.copter_version))?;
+        self.write(&format!("**Command**: `{}`\n", run.command_line))?;
+        self.write("This run has not finished: each dependent's results are added below once all its versions are")?;
+        self.write("tested, and the full report replaces this file when the run ends.\n")?;
+        self.write("| Dependent | Version | Result | Baseline | First error |")?;
+        self.write("|-----------|---------|--------|----------|-------------|")?;
+        self.out.flush()
+    }
+
+    /// Hold `row` until its dependent is complete, then append the dependent's rows
+    pub fn record(&mut self, row: &OfferedRow) -> std::io::Result<()> {
+        let same = |rows: &Vec<OfferedRow>| {
+            rows[0].primary.dependent_name == row.primary.dependent_name
+                && rows[0].primary.dependent_version == row.primary.dependent_version
+        };
+        let i = match self.pending.iter().position(same) {
+            Some(i) => i,
+            None => {
+                self.pending.push(Vec::new());
+                self.pending.len() - 1
+            }
+        };
+        self.pending[i].push(row.clone());
+        if self.pending[i].len() >= self.expected {
+            let rows = self.pending.remove(i);
+            self.append(&rows)?;
+        }
+        Ok(())
+    }
+
+    /// Append the rows of dependents that never completed
+    pub fn finish(mut self) -> std::io::Result<()> {
+        for rows in std::mem::take(&mut self.pending) {
+            self.append(&rows)?;
+        }
+        Ok(())
+    }
+
+    fn append(&mut self, rows: &[OfferedRow]) -> std::io::Result<()> {
+        for row in rows {
+            let (result, baseline, error) = report::markdown_cells(row, self.style, &self.messages);
+            let version = row.offered.as_ref().map_or("baseline".to_string(), |o| o.version.clone());
+            self.write(&format!(
+                "| {}{} | {} | {} | {} | {} |",
+                row.primary.markdown_links(),
+                row.test.toolchain_suffix(),
+                version,
+                result,
+                baseline,
+                error
+            ))?;
+        }
+        self.out.flush()
+    }
+
+    fn write(&mut self, line: &str) -> std::io::Result<()> {
+        match &self.scrubber {
+            Some(scrubber) => writeln!(self.out, "{}", scrubber.text(line)),
+            None => writeln!(self.out, "{}", line),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(dependent: &str, offered: Option<&str>, error: &str) -> OfferedRow {
+        crate::test_rows::row(dependent).offered(offered).error(error).build()
+    }
+
+    #[test]
+    fn test_dependents_appended_when_complete() {
+        let mut partial = PartialReport::new(Vec::new(), 2, StatusStyle::Symbols, None);
+        partial.record(&row("png", None, "")).unwrap();
+        partial.record(&row("image", None, "")).unwrap();
+        assert!(partial.out.is_empty(), "nothing until a dependent has all its versions");
+
+        let mut regressed = row("image", Some("0.9.0"), "error[E0308]: mismatched types");
+        regressed.primary.repository = Some("https://github.com/image-rs/image".to_string());
+        partial.record(&regressed).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&partial.out),
+            "| [image 1.0.0](https://crates.io/crates/image/1.0.0) ([docs](https://docs.rs/image/1.0.0)) \
+             | baseline | passed | — |  |\n\
+             | [image 1.0.0](https://crates.io/crates/image/1.0.0) ([docs](https://docs.rs/image/1.0.0), \
+             [repo](https://github.com/image-rs/image)) \
+             | 0.9.0 | **regressed**: build failed | passed | `error[E0308]: mismatched types` |\n"
+        );
+
+        // A run that stops early still lists what finished
+        let mut out = Vec::new();
+        let mut words = PartialReport::new(&mut out, 2, StatusStyle::Words, None);
+        words.record(&row("png", None, "")).unwrap();
+        words.finish().unwrap();
+        assert!(
+            String::from_utf8_lossy(&out)
+                .ends_with("(https://docs.rs/png/1.0.0)) | baseline | PASSED (passed) | — |  |\n")
+        );
+    }
+}
@@ -0,0 +1,139 @@
+/// Path normalization for paths handed to cargo, manifests and shell scripts
+///
+/// On Windows `canonicalize` returns extended-length paths (`\\?\C:\...`) that
+/// cargo rejects in `path = ...` dependencies, and backslashes inside a
+/// `--config 'patch.crates-io.x.path="C:\Users\..."'` value are read as TOML
+/// escape sequences. Everything we write for cargo goes through this module so
+/// it comes out as a plain drive path with forward slashes.
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Classic Windows path length limit; cargo and many build scripts still hit it
+pub const WINDOWS_MAX_PATH: usize = 260;
+
+/// Remove a `\\?\` or `\\?\UNC\` prefix, leaving other paths untouched
+fn strip_verbatim_str(s: &str) -> Cow<'_, str> {
+    if let Some(rest) = s.strip_prefix(r"\\?\UNC\") {
+        Cow::Owned(format!(r"\\{}", rest))
+    } else if let Some(rest) = s.strip_prefix(r"\\?\")
+        && has_drive_prefix(rest)
+    {
+        Cow::Borrowed(rest)
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+/// `C:\...` or `C:/...`
+fn has_drive_prefix(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() >= 3 && b[0].is_ascii_alphabetic() && b[1] == b':' && (b[2] == b'\\' || b[2] == b'/')
+}
+
+/// Whether a path string uses Windows syntax (drive letter or UNC/verbatim prefix)
+fn is_windows_style(s: &str) -> bool {
+    has_drive_prefix(s) || s.starts_with(r"\\")
+}
+
+/// Strip the extended-length prefix from a path (`\\?\C:\x` → `C:\x`)
+pub fn strip_verbatim_prefix(path: &Path) -> PathBuf {
+    match strip_verbatim_str(&path.to_string_lossy()) {
+        Cow::Borrowed(_) => path.to_path_buf(),
+        Cow::Owned(s) => PathBuf::from(s),
+    }
+}
+
+/// Canonicalize without producing extended-length paths, falling back to the input
+pub fn canonicalize(path: &Path) -> PathBuf {
+    match path.canonicalize() {
+        Ok(p) => strip_verbatim_prefix(&p),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Render a path the way cargo expects it in a manifest `path = "..."` value
+///
+/// Windows paths lose their verbatim prefix and use forward slashes, which cargo
+/// accepts everywhere. Unix paths are returned as-is (backslash is a valid filename
+/// character there).
+pub fn to_manifest_string(path: &Path) -> String {
+    let lossy = path.to_string_lossy();
+    let stripped = strip_verbatim_str(&lossy);
+    if is_windows_style(&stripped) { stripped.replace('\\', "/") } else { stripped.into_owned() }
+}
+
+/// Render a path as a quoted TOML string for `cargo --config key=value`
+pub fn to_toml_string(path: &Path) -> String {
+    let s = to_manifest_string(path);
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Render a path for a bash script argument (Docker wrapper runs under bash/WSL)
+pub fn to_shell_arg(path: &str) -> String {
+    let stripped = strip_verbatim_str(path);
+    if is_windows_style(&stripped) { stripped.replace('\\', "/") } else { stripped.into_owned() }
+}
+
+/// Whether a path is at or past the Windows MAX_PATH limit
+pub fn exceeds_max_path(path: &Path) -> bool {
+    to_manifest_string(path).len() >= WINDOWS_MAX_PATH
+}
+
+/// On Windows, warn (once per run) when staging paths get deep enough to break builds
+pub fn warn_if_exceeds_max_path(path: &Path) {
+    static WARNED: AtomicBool = AtomicBool::new(false);
+    if cfg!(windows) && exceeds_max_path(path) && !WARNED.swap(true, Ordering::Relaxed) {
+        eprintln!(
+            "warning: {} exceeds the Windows {}-character path limit; builds may fail.\n         \
+             Use a shorter --staging-dir (e.g. C:\\copter) or enable long paths (git config core.longpaths true, \
+             LongPathsEnabled registry key).",
+            path.display(),
+            WINDOWS_MAX_PATH
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_verbatim_drive_path() {
+        assert_eq!(strip_verbatim_str(r"\\?\C:\Users\me\crate"), r"C:\Users\me\crate");
+        assert_eq!(strip_verbatim_str(r"\\?\UNC\server\share\crate"), r"\\server\share\crate");
+        // Non-drive verbatim paths (e.g. volume GUIDs) can't be shortened safely
+        assert_eq!(strip_verbatim_str(r"\\?\Volume{abc}\x"), r"\\?\Volume{abc}\x");
+        assert_eq!(strip_verbatim_str("/home/me/crate"), "/home/me/crate");
+    }
+
+    #[test]
+    fn test_manifest_string_uses_forward_slashes_for_windows_paths() {
+        assert_eq!(to_manifest_string(Path::new(r"\\?\C:\Users\me\my crate")), "C:/Users/me/my crate");
+        assert_eq!(to_manifest_string(Path::new(r"D:\work\rgb")), "D:/work/rgb");
+        assert_eq!(to_manifest_string(Path::new(r"\\?\UNC\srv\share\rgb")), "//srv/share/rgb");
+        assert_eq!(to_manifest_string(Path::new("/tmp/odd\\name")), "/tmp/odd\\name");
+    }
+
+    #[test]
+    fn test_toml_string_has_no_escape_sequences() {
+        assert_eq!(to_toml_string(Path::new(r"\\?\C:\Users\rgb")), "\"C:/Users/rgb\"");
+        assert_eq!(to_toml_string(Path::new("/tmp/a\"b")), "\"/tmp/a\\\"b\"");
+        let parsed: toml::Value =
+            toml::from_str(&format!("p = {}", to_toml_string(Path::new(r"C:\new\table")))).unwrap();
+        assert_eq!(parsed["p"].as_str(), Some("C:/new/table"));
+    }
+
+    #[test]
+    fn test_shell_arg() {
+        assert_eq!(to_shell_arg(r"C:\src\rgb\Cargo.toml"), "C:/src/rgb/Cargo.toml");
+        assert_eq!(to_shell_arg("./rgb"), "./rgb");
+    }
+
+    #[test]
+    fn test_exceeds_max_path() {
+        assert!(!exceeds_max_path(Path::new(r"C:\short")));
+        let deep = format!(r"\\?\C:\{}", "d\\".repeat(130));
+        assert!(exceeds_max_path(Path::new(&deep)));
+    }
+}
@@ -0,0 +1,286 @@
+/// Per-project settings in `copter.toml`, and the `init` subcommand that writes them
+///
+/// `copter.toml` sits next to the base crate's `Cargo.toml` and pins what a plain
+/// `cargo-copter` run tests, so the whole team gets the same matrix:
+///
+/// ```toml
+/// dependents = ["image", "ravif:0.11.5"]
+/// exclude = ["some-abandoned-crate"]
+/// test_versions = ["0.8.50"]
+/// aliases = ["rgb2"]
+///
+/// [dependent.image]
+/// system-deps = ["libvips-dev", "nasm"]
+/// image = "ghcr.io/org/vips-build:latest"
+/// ```
+///
+/// Command-line options win: `dependents` only applies when no dependent option is given,
+/// `test_versions` only when neither `--test-versions` nor `--force-versions` nor `--crate`
+/// is given, and `exclude` and `aliases` are merged with `--exclude` and `--alias`. `[dependent.<name>]` sections
+/// only exist here.
+use crate::cli::CliArgs;
+use crate::registry::Registry;
+use crate::types::{OfferedRow, Severity};
+use std::collections::BTreeMap;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+/// File name, looked up in the base crate's directory
+pub const PROJECT_CONFIG_NAME: &str = "copter.toml";
+
+/// Contents of `copter.toml`
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Dependents to test, `name` or `name:version` (like `--dependents`)
+    pub dependents: Vec<String>,
+    /// Dependents never to test (like `--exclude`)
+    pub exclude: Vec<String>,
+    /// Extra base crate versions to offer (like `--test-versions`)
+    pub test_versions: Vec<String>,
+    /// Forks and renames of the base crate (like `--alias`)
+    pub aliases: Vec<String>,
+    /// `[dependent.<name>]` sections
+    pub dependent: BTreeMap<String, DependentSettings>,
+}
+
+/// Settings for one dependent, by crate name
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct DependentSettings {
+    /// System packages needed to build it (installed in docker mode, checked natively)
+    pub system_deps: Vec<String>,
+    /// Docker image to build it in with `--docker` (default: the standard copter image)
+    pub image: Option<String>,
+}
+
+impl ProjectConfig {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(text).map_err(|e| e.to_string())?;
+        for (name, settings) in &config.dependent {
+            for package in &settings.system_deps {
+                crate::system_deps::validate_name(package).map_err(|e| format!("[dependent.{}] {}", name, e))?;
+            }
+        }
+        Ok(config)
+    }
+
+    /// Fill in options the command line left unset
+    pub fn apply(&self, args: &mut CliArgs) {
+        let dependents_given = !args.dependents.is_empty()
+            || !args.dependent_paths.is_empty()
+            || !args.dependent_glob.is_empty()
+            || !args.dependent_dir.is_empty();
+        if !dependents_given {
+            args.dependents = self.dependents.clone();
+        }
+        if args.test_versions.is_empty() && args.force_versions.is_empty() && args.crate_name.is_none() {
+            args.test_versions = self.test_versions.clone();
+        }
+        for name in &self.exclude {
+            if !args.exclude.contains(name) {
+                args.exclude.push(name.clone());
+            }
+        }
+        for name in &self.aliases {
+            if !args.aliases.contains(name) {
+                args.aliases.push(name.clone());
+            }
+        }
+        args.dependent_settings = self.dependent.clone();
+    }
+
+    /// The file as written by `init`, with a comment per key
+    pub fn to_toml(&self) -> String {
+        let list = |items: &[String]| {
+            let quoted: Vec<String> = items.iter().map(|s| toml::Value::String(s.clone()).to_string()).collect();
+            format!("[{}]", quoted.join(", "))
+        };
+        let mut out = format!(
+            "# cargo-copter settings for this crate; command-line options take precedence.\n\
+             \n\
+             # Dependents to test (\"name\" = latest version, \"name:version\" = that version)\n\
+             dependents = {}\n\
+             \n\
+             # Dependents never to test, e.g. ones that were already broken\n\
+             exclude = {}\n\
+             \n\
+             # Published versions to offer besides your local WIP (empty: latest published vs WIP)\n\
+             test_versions = {}\n\
+             \n\
+             # Other names the crate is published under (forks, renames), to count dependents that moved\n\
+             aliases = {}\n\
+             \n\
+             # Per-dependent settings, e.g. system packages a dependent needs to build:\n\
+             # [dependent.image]\n\
+             # system-deps = [\"libvips-dev\", \"nasm\"]\n\
+             # image = \"ghcr.io/org/vips-build:latest\"  # docker image for this dependent with --docker\n",
+            list(&self.dependents),
+            list(&self.exclude),
+            list(&self.test_versions),
+            list(&self.aliases)
+        );
+        for (name, settings) in &self.dependent {
+            out.push_str(&format!("\n[dependent.{}]\nsystem-deps = {}\n", name, list(&settings.system_deps)));
+            if let Some(image) = &settings.image {
+                out.push_str(&format!("image = {}\n", toml::Value::String(image.clone())));
+            }
+        }
+        out
+    }
+}
+
+/// Directory holding the base crate's manifest (`--path`, or the current directory)
+fn crate_dir(path: Option<&Path>) -> PathBuf {
+    match path {
+        Some(p) if p.is_file() => p.parent().map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from(".")),
+        Some(p) => p.to_path_buf(),
+        None => PathBuf::from("."),
+    }
+}
+
+/// Apply the base crate's `copter.toml`, if there is one
+pub fn load_into(args: &mut CliArgs) -> Result<(), String> {
+    if args.crate_name.is_some() && args.path.is_none() {
+        return Ok(());
+    }
+    let path = crate_dir(args.path.as_deref()).join(PROJECT_CONFIG_NAME);
+    if !path.is_file() {
+        return Ok(());
+    }
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let config = ProjectConfig::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    log::debug!("Using {}", path.display());
+    config.apply(args);
+    Ok(())
+}
+
+/// Dependents whose baseline failed in a previous report (candidates for `exclude`)
+fn already_broken(report: &Path) -> Vec<String> {
+    let Ok(report) = crate::report::import_json_report(report) else {
+        return vec![];
+    };
+    let rows: Vec<OfferedRow> =
+        report.get("test_results").and_then(|rows| serde_json::from_value(rows.clone()).ok()).unwrap_or_default();
+    let mut names: Vec<String> = Vec::new();
+    for row in rows.iter().filter(|row| row.offered.is_none() && row.status().severity() == Severity::Broken) {
+        if !names.contains(&row.primary.dependent_name) {
+            names.push(row.primary.dependent_name.clone());
+        }
+    }
+    names
+}
+
+/// Ask on the terminal; an empty answer keeps `default`, `-` clears it
+fn ask(question: &str, default: &[String]) -> Vec<String> {
+    println!("{}", question);
+    print!("  [{}]: ", default.join(" "));
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    let _ = std::io::stdin().lock().read_line(&mut answer);
+    match answer.trim() {
+        "" => default.to_vec(),
+        "-" => vec![],
+        list => list.split_whitespace().map(str::to_string).collect(),
+    }
+}
+
+/// `cargo-copter init`: propose a `copter.toml` for the crate at `path` and write it
+pub fn init(registry: &Registry, path: Option<&Path>, top: usize, yes: bool, force: bool) -> Result<(), String> {
+    let dir = crate_dir(path);
+    let target = dir.join(PROJECT_CONFIG_NAME);
+    if target.exists() && !force {
+        return Err(format!("{} already exists (pass --force to replace it)", target.display()));
+    }
+    let (crate_name, local_version) = crate::manifest::get_crate_info(&dir.join("Cargo.toml"))?;
+    println!("Setting up cargo-copter for {} {} ({})", crate_name, local_version, dir.display());
+
+    let latest = crate::version::resolve_latest_version(registry, &crate_name, false).ok();
+    match &latest {
+        Some(v) => println!("  Latest published: {} (the baseline your WIP is compared against)", v),
+        None => println!("  Not published yet; list local dependents in copter.toml or use --dependent-paths"),
+    }
+
+    let top_dependents = crate::dependents_cache::top_dependents(registry, &crate_name, top, Default::default())
+        .map(|sample| sample.dependents)
+        .unwrap_or_else(|e| {
+            println!("  Couldn't fetch dependents from crates.io: {}", e);
+            vec![]
+        });
+    for dep in &top_dependents {
+        println!("  {:<30} {:>12} downloads", dep.name, dep.downloads);
+    }
+
+    let exclude = already_broken(&PathBuf::from("copter-report").join("report.json"));
+    if !exclude.is_empty() {
+        println!("  Already broken in the last run (copter-report/report.json): {}", exclude.join(", "));
+    }
+    let mut proposal = ProjectConfig {
+        dependents: top_dependents.into_iter().map(|d| d.name).filter(|n| !exclude.contains(n)).collect(),
+        exclude,
+        test_versions: vec![],
+        aliases: vec![],
+        dependent: BTreeMap::new(),
+    };
+
+    if !yes && std::io::stdin().is_terminal() {
+        println!("\nEnter to accept, a space-separated list to replace, '-' for none.");
+        proposal.dependents = ask("Dependents to test:", &proposal.dependents);
+        proposal.exclude = ask("Dependents to exclude:", &proposal.exclude);
+        proposal.test_versions = ask("Older published versions to test as well:", &proposal.test_versions);
+    }
+
+    let text = proposal.to_toml();
+    println!("\n{}", text);
+    std::fs::write(&target, text).map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+    println!("Wrote {}. Run `cargo copter` to test against it.", target.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_init_output_round_trips() {
+        let config = ProjectConfig {
+            dependents: vec!["image".to_string(), "ravif:0.11.5".to_string()],
+            exclude: vec!["old-crate".to_string()],
+            test_versions: vec![],
+            aliases: vec!["rgb2".to_string()],
+            dependent: BTreeMap::from([(
+                "image".to_string(),
+                DependentSettings {
+                    system_deps: vec!["libvips-dev".to_string(), "nasm".to_string()],
+                    image: Some("ghcr.io/org/vips-build:latest".to_string()),
+                },
+            )]),
+        };
+        assert_eq!(ProjectConfig::parse(&config.to_toml()).unwrap(), config);
+        assert!(ProjectConfig::parse("dependants = []").unwrap_err().contains("dependants"));
+        assert!(ProjectConfig::parse("[dependent.image]\nsystem-deps = [\"a;b\"]").unwrap_err().contains("a;b"));
+    }
+
+    #[test]
+    fn test_command_line_takes_precedence() {
+        let config = ProjectConfig {
+            dependents: vec!["image".to_string()],
+            exclude: vec!["old-crate".to_string()],
+            test_versions: vec!["0.8.50".to_string()],
+            aliases: vec![],
+            dependent: BTreeMap::new(),
+        };
+
+        let mut args = CliArgs::parse_from(["cargo-copter", "--exclude", "other"]);
+        config.apply(&mut args);
+        assert_eq!(args.dependents, vec!["image"]);
+        assert_eq!(args.test_versions, vec!["0.8.50"]);
+        assert_eq!(args.exclude, vec!["other", "old-crate"]);
+
+        let mut args = CliArgs::parse_from(["cargo-copter", "--dependent-paths", "../dep", "--force-versions", "0.9"]);
+        config.apply(&mut args);
+        assert!(args.dependents.is_empty());
+        assert!(args.test_versions.is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+/// Automatic quarantine of dependents whose baseline keeps failing
+///
+/// A dependent that doesn't build against the *published* base crate says nothing about
+/// your changes, yet costs a full fetch/check/test in every run. After its baseline fails
+/// in more than `--quarantine-after` consecutive runs (default 3) it is quarantined:
+/// later runs exclude it, except that it is re-tested on the first run at least
+/// `RETEST_DAYS` after its last test, so a scheduled (e.g. nightly CI) run tests it weekly.
+/// A passing baseline releases it. State is kept per base crate in
+/// `copter-report/history/quarantine.json`; `--include-quarantined` tests everything anyway.
+use crate::cli::CliArgs;
+use crate::types::{OfferedRow, RowStatus, Severity};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Days between re-tests of a quarantined dependent
+pub const RETEST_DAYS: i64 = 7;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// A quarantined dependent
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Entry {
+    /// Date it was quarantined (YYYY-MM-DD)
+    pub since: String,
+    /// Date of the last run that tested it
+    pub last_tested: String,
+}
+
+/// Quarantine state of one base crate's dependents
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Quarantine {
+    /// Consecutive runs in which each dependent's baseline failed
+    pub streaks: BTreeMap<String, u32>,
+    pub quarantined: BTreeMap<String, Entry>,
+}
+
+impl Quarantine {
+    /// State for `base_crate` from the report directory's history
+    pub fn load(report_dir: &Path, base_crate: &str) -> Self {
+        read_all(&file_path(report_dir)).remove(base_crate).unwrap_or_default()
+    }
+
+    pub fn save(&self, report_dir: &Path, base_crate: &str) -> Result<(), String> {
+        let path = file_path(report_dir);
+        let mut all = read_all(&path);
+        all.insert(base_crate.to_string(), self.clone());
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(&all).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Quarantined dependents due for their weekly re-test
+    pub fn due(&self, today: NaiveDate) -> Vec<&str> {
+        self.quarantined
+            .iter()
+            .filter(|(_, entry)| {
+                NaiveDate::parse_from_str(&entry.last_tested, DATE_FORMAT)
+                    .is_ok_and(|last| (today - last).num_days() >= RETEST_DAYS)
+            })
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Exclude quarantined dependents that aren't due for a re-test, and say so; returns them
+    pub fn apply(&self, args: &mut CliArgs, today: NaiveDate) -> Vec<String> {
+        let console = args.console();
+        let due = self.due(today);
+        let excluded: Vec<&str> =
+            self.quarantined.keys().map(String::as_str).filter(|name| !due.contains(name)).collect();
+        if !excluded.is_empty() {
+            console.say(&format!(
+                "Quarantined (baseline keeps failing; --include-quarantined to test): {}",
+                excluded.join(", ")
+            ));
+        }
+        if !due.is_empty() {
+            console.say(&format!("Re-testing quarantined dependents (weekly): {}", due.join(", ")));
+        }
+        for name in &excluded {
+            if !args.exclude.iter().any(|n| n == name) {
+                args.exclude.push(name.to_string());
+            }
+        }
+        excluded.into_iter().map(String::from).collect()
+    }
+
+    /// Count this run's baseline failures; returns what entered or left quarantine
+    pub fn update(&mut self, rows: &[OfferedRow], after: u32, today: NaiveDate) -> Vec<String> {
+        let mut baselines: Vec<(&str, bool)> = Vec::new();
+        // A skipped baseline (quarantined, out of time) says nothing about the dependent
+        for row in rows.iter().filter(|r| r.is_baseline() && r.status() != RowStatus::Skipped) {
+            if !baselines.iter().any(|(name, _)| *name == row.primary.dependent_name) {
+                baselines.push((&row.primary.dependent_name, row.status().severity() == Severity::Broken));
+            }
+        }
+
+        let today = today.format(DATE_FORMAT).to_string();
+        let mut changes = Vec::new();
+        for (name, broken) in baselines {
+            let streak = self.streaks.entry(name.to_string()).or_default();
+            *streak = if broken { *streak + 1 } else { 0 };
+            let streak = *streak;
+            if let Some(entry) = self.quarantined.get_mut(name) {
+                entry.last_tested = today.clone();
+                if !broken {
+                    self.quarantined.remove(name);
+                    changes.push(format!("{} left quarantine (baseline passes again)", name));
+                }
+            } else if after > 0 && streak > after {
+                self.quarantined.insert(name.to_string(), Entry { since: today.clone(), last_tested: today.clone() });
+                changes.push(format!("{} quarantined (baseline failed {} runs in a row)", name, streak));
+            }
+        }
+        changes
+    }
+}
+
+fn file_path(report_dir: &Path) -> PathBuf {
+    report_dir.join(crate::history::HISTORY_DIR).join("quarantine.json")
+}
+
+fn read_all(path: &Path) -> BTreeMap<String, Quarantine> {
+    std::fs::read_to_string(path).ok().and_then(|text| serde_json::from_str(&text).ok()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+    use clap::Parser;
+
+    fn baseline(dependent: &str, passed: bool) -> OfferedRow {
+        crate::test_rows::row(dependent).resolved("0.8.50").passed(passed).build()
+    }
+
+    #[test]
+    fn test_quarantine_lifecycle() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        let mut q = Quarantine::default();
+        let rows = vec![baseline("old", false), baseline("image", true)];
+        for d in 1..=3 {
+            assert!(q.update(&rows, 3, day(d)).is_empty());
+        }
+        assert_eq!(q.update(&rows, 3, day(4)), vec!["old quarantined (baseline failed 4 runs in a row)"]);
+
+        let mut args = CliArgs::parse_from(["cargo-copter"]);
+        assert_eq!(q.apply(&mut args, day(5)), vec!["old"]);
+        assert_eq!(args.exclude, vec!["old"]);
+        assert_eq!(q.due(day(11)), vec!["old"]);
+
+        // Its skipped row while quarantined is not a passing baseline
+        let mut skipped = baseline("old", true);
+        skipped.test = TestExecution { skipped: Some("quarantined".to_string()), ..TestExecution::new() };
+        assert!(q.update(&[skipped], 3, day(6)).is_empty());
+        assert_eq!(q.quarantined["old"].last_tested, "2026-10-04");
+
+        assert_eq!(q.update(&[baseline("old", true)], 3, day(11)), vec!["old left quarantine (baseline passes again)"]);
+        assert!(q.quarantined.is_empty());
+    }
+}
@@ -0,0 +1,542 @@
+/// Distributed execution through a job queue on shared storage (`--backend queue|k8s`)
+///
+/// A crate with thousands of dependents takes days on one machine. With a queue backend
+/// the coordinator writes one job per dependent (tested against every base version, so
+/// offered rows keep their baseline) into a run directory under `--queue-dir`, waits for
+/// workers to post results there, and feeds them into the normal console output and
+/// reports as they arrive. Workers are `cargo-copter worker --queue-dir <dir>` processes
+/// on any machine that sees the same directory: an NFS share, a Kubernetes volume, or a
+/// mounted object-store bucket. A worker claims a job by renaming it into `claimed/`,
+/// writes `results/<job>.json` when done and exits once no jobs are left.
+///
+/// `--backend k8s` also writes `k8s-job.yaml`, a work-queue Job that runs `--queue-workers`
+/// workers in the copter docker image with the `copter-queue` volume claim at `/queue`,
+/// and applies it with `kubectl`.
+///
+/// Layout of `<queue-dir>/run-<timestamp>/`: `sources/` (copies of local crates, so workers
+/// don't need the coordinator's checkouts), `jobs/`, `claimed/`, `results/`. A job claimed for
+/// `--queue-timeout` minutes without a result is moved back to `jobs/` once, then reported
+/// as skipped, as is everything left when no job was claimed or finished for that long. A
+/// worker that can't run a job posts the error instead of exiting.
+use crate::cli::Backend;
+use crate::registry::Registry;
+use crate::types::{CrateSource, TestMatrix, TestResult};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How often the coordinator looks for results and idle workers look for jobs
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Image the Kubernetes workers run in (same default as copter-docker.sh)
+const DEFAULT_WORKER_IMAGE: &str = "ghcr.io/imazen/cargo-copter:latest";
+
+/// Where the queue volume is mounted in worker pods
+const POD_QUEUE_DIR: &str = "/queue";
+
+/// What a worker posts for one job
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct JobResult {
+    /// Hostname of the worker, for tracking down failures
+    worker: String,
+    results: Vec<TestResult>,
+    /// Set when the job couldn't run at all
+    error: Option<String>,
+}
+
+/// Submit `matrix` as one job per dependent, wait for the results and stream them to
+/// `on_result` in arrival order (like `runner::run_tests`)
+///
+/// A job claimed for `timeout` without a result is queued again once, then given up on; so
+/// is every job left when no job was claimed or finished for `timeout`. A job given up on,
+/// or one its worker couldn't run, gets skipped rows.
+pub fn run_distributed<F>(
+    matrix: &TestMatrix,
+    backend: Backend,
+    queue_dir: &Path,
+    workers: usize,
+    timeout: Duration,
+    mut on_result: F,
+) -> Result<Vec<TestResult>, String>
+where
+    F: FnMut(&TestResult),
+{
+    let run_dir = queue_dir.join(format!("run-{}", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    let ids = submit(matrix, &run_dir)?;
+    crate::live::note(&format!("Queued {} job(s) in {}", ids.len(), run_dir.display()));
+
+    if backend == Backend::K8s {
+        let run_name = run_dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let image = std::env::var("COPTER_DOCKER_IMAGE").unwrap_or_else(|_| DEFAULT_WORKER_IMAGE.to_string());
+        let manifest_path = run_dir.join("k8s-job.yaml");
+        fs::write(&manifest_path, kubernetes_job(&run_name, workers, &image))
+            .map_err(|e| format!("Failed to write {}: {}", manifest_path.display(), e))?;
+        apply_manifest(&manifest_path);
+    } else {
+        crate::live::note(&format!("Start workers with: cargo-copter worker --queue-dir {}", queue_dir.display()));
+    }
+
+    let mut results = Vec::new();
+    let mut emit = |result: TestResult| {
+        on_result(&result);
+        results.push(result);
+    };
+    crate::runner::emit_quarantined(matrix, &mut emit);
+    // The dependent a job id stands for
+    let skip = |id: &str, reason: &str, emit: &mut dyn FnMut(TestResult)| {
+        if let Some(idx) = ids.iter().position(|i| i == id) {
+            crate::runner::emit_skipped(matrix, &matrix.dependents[idx].crate_ref, reason, emit);
+        }
+    };
+
+    let mut seen = HashSet::new();
+    let mut claims = Claims::default();
+    let mut last_activity = Instant::now();
+    let mut last_progress = None;
+    while seen.len() < ids.len() {
+        for (id, job) in poll_results(&run_dir, &mut seen)? {
+            last_activity = Instant::now();
+            if let Some(e) = &job.error {
+                crate::live::note(&format!("warning: job {} failed on {} — {}", id, job.worker, e));
+                skip(&id, &format!("queue job failed on {}: {}", job.worker, e), &mut emit);
+            }
+            job.results.into_iter().for_each(&mut emit);
+        }
+        let (lost, claimed) = claims.check(&run_dir, &seen, timeout);
+        if claimed {
+            last_activity = Instant::now();
+        }
+        for id in lost {
+            crate::live::note(&format!("warning: job {} was claimed twice without a result; giving up on it", id));
+            seen.insert(id.clone());
+            skip(&id, "queue worker lost: claimed twice without a result", &mut emit);
+        }
+        if seen.len() < ids.len() && last_activity.elapsed() >= timeout {
+            let left: Vec<&String> = ids.iter().filter(|id| !seen.contains(*id)).collect();
+            crate::live::note(&format!(
+                "warning: no job claimed or finished for {} minutes; giving up on {} job(s)",
+                timeout.as_secs() / 60,
+                left.len()
+            ));
+            for id in left {
+                // Not worth a worker's time any more
+                let _ = fs::remove_file(run_dir.join("jobs").join(format!("{}.json", id)));
+                seen.insert(id.clone());
+                skip(id, "queue timed out: no worker ran it", &mut emit);
+            }
+        }
+        let running = count_files(&run_dir.join("claimed"));
+        // A board shows the same progress per dependent
+        if last_progress != Some((seen.len(), running)) && !crate::live::active() {
+            eprintln!("queue: {}/{} jobs done, {} running", seen.len(), ids.len(), running);
+            last_progress = Some((seen.len(), running));
+        }
+        if seen.len() < ids.len() {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+    Ok(results)
+}
+
+/// The coordinator's view of `claimed/`: when it first saw each job there, and which jobs it
+/// already queued again
+#[derive(Debug, Default)]
+struct Claims {
+    since: HashMap<String, Instant>,
+    requeued: HashSet<String>,
+}
+
+impl Claims {
+    /// Queue jobs claimed for `timeout` without a result again; returns the ones that timed
+    /// out a second time (their workers are taken to be lost) and whether a job was newly claimed
+    fn check(&mut self, run_dir: &Path, seen: &HashSet<String>, timeout: Duration) -> (Vec<String>, bool) {
+        let mut lost = Vec::new();
+        let mut newly_claimed = false;
+        for path in sorted_entries(&run_dir.join("claimed")) {
+            let id = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            if seen.contains(&id) {
+                continue;
+            }
+            let since = *self.since.entry(id.clone()).or_insert_with(|| {
+                newly_claimed = true;
+                Instant::now()
+            });
+            if since.elapsed() < timeout {
+                continue;
+            }
+            self.since.remove(&id);
+            if !self.requeued.insert(id.clone()) {
+                lost.push(id);
+            } else if fs::rename(&path, run_dir.join("jobs").join(format!("{}.json", id))).is_ok() {
+                crate::live::note(&format!("warning: job {} has had no result for too long; queued it again", id));
+            }
+        }
+        (lost, newly_claimed)
+    }
+}
+
+/// `cargo-copter worker`: run queued jobs until none are left (or forever with `wait`)
+///
+/// A job that can't run (unreadable, or its toolchain won't install) is posted with the error.
+pub fn work(registry: &Registry, queue_dir: &Path, staging_dir: &Path, wait: bool) -> Result<(), String> {
+    let mut done = 0;
+    loop {
+        let Some((run_dir, id, job)) = claim(queue_dir)? else {
+            if wait {
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            eprintln!("No jobs left in {}; ran {}", queue_dir.display(), done);
+            return Ok(());
+        };
+        eprintln!("Running job {} ({})", id, run_dir.display());
+        let (results, error) = match job.and_then(|job| run_job(job, registry, &run_dir, staging_dir)) {
+            Ok(results) => (results, None),
+            Err(e) => (vec![], Some(e)),
+        };
+        post(&run_dir, &id, &JobResult { worker: hostname(), results, error })?;
+        done += 1;
+    }
+}
+
+/// Run one claimed job on this machine, against this worker's registry
+fn run_job(
+    mut job: TestMatrix,
+    registry: &Registry,
+    run_dir: &Path,
+    staging_dir: &Path,
+) -> Result<Vec<TestResult>, String> {
+    job.staging_dir = staging_dir.to_path_buf();
+    job.registry = registry.clone();
+    if let Some(toolchain) = &job.cross.toolchain {
+        crate::toolchains::install(toolchain).map_err(|e| e.to_string())?;
+    }
+    resolve_sources(&mut job, run_dir);
+    crate::runner::run_tests(job, None, |_| {}).map_err(|e| e.to_string())
+}
+
+/// `cargo-copter worker --jobs N`: run `jobs` workers side by side on this machine
+///
+/// Each worker is a child process (claiming is safe across processes, and staged copies
+/// are per process) whose cargo invocations get an equal share of the host's cores through
+/// `CARGO_BUILD_JOBS`, so eight workers don't each start a build as wide as the machine.
+/// A `CARGO_BUILD_JOBS` already set in the environment is left alone.
+pub fn work_in_parallel(queue_dir: &Path, staging_dir: &Path, wait: bool, jobs: usize) -> Result<(), String> {
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let build_jobs =
+        std::env::var(BUILD_JOBS_ENV).ok().unwrap_or_else(|| build_jobs_per_worker(cores, jobs).to_string());
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to find the cargo-copter executable: {}", e))?;
+    eprintln!("Starting {} workers on {} cores, {} build jobs each", jobs, cores, build_jobs);
+
+    let mut children = Vec::new();
+    for _ in 0..jobs {
+        let mut cmd = std::process::Command::new(&exe);
+        cmd.arg("--staging-dir").arg(staging_dir).arg("worker").arg("--queue-dir").arg(queue_dir);
+        if wait {
+            cmd.arg("--wait");
+        }
+        cmd.env(BUILD_JOBS_ENV, &build_jobs);
+        children.push(cmd.spawn().map_err(|e| format!("Failed to start worker: {}", e))?);
+    }
+    let failed = children.into_iter().map(|mut child| child.wait().is_ok_and(|s| s.success())).filter(|ok| !ok).count();
+    if failed > 0 {
+        return Err(format!("{} of {} workers failed", failed, jobs));
+    }
+    Ok(())
+}
+
+/// Cargo's environment override for `build.jobs`
+pub const BUILD_JOBS_ENV: &str = "CARGO_BUILD_JOBS";
+
+/// Cores each of `workers` concurrent workers gets for its builds (at least one)
+pub fn build_jobs_per_worker(cores: usize, workers: usize) -> usize {
+    (cores / workers.max(1)).max(1)
+}
+
+/// Copy local sources into the run directory and write one job file per dependent; returns
+/// the job ids, in the order of `matrix.dependents`
+fn submit(matrix: &TestMatrix, run_dir: &Path) -> Result<Vec<String>, String> {
+    for dir in ["sources", "jobs", "claimed", "results"] {
+        let path = run_dir.join(dir);
+        fs::create_dir_all(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    }
+
+    // The coordinator reports the quarantined dependents itself
+    let mut template = TestMatrix { quarantined: vec![], ..matrix.clone() };
+    for spec in template.base_versions.iter_mut().chain(template.dependents.iter_mut()) {
+        let crate_ref = &mut spec.crate_ref;
+        if let CrateSource::Local { path } | CrateSource::Git { path, .. } = &mut crate_ref.source {
+            let source =
+                if path.is_file() { path.parent().unwrap_or(Path::new(".")).to_path_buf() } else { path.clone() };
+            let relative = PathBuf::from("sources").join(format!("{}-{}", crate_ref.name, crate_ref.version.display()));
+            if !run_dir.join(&relative).exists() {
+                crate::staging::copy_tree(&source, &run_dir.join(&relative), matrix.staging_copy)?;
+            }
+            *path = relative;
+        }
+    }
+
+    let mut ids = Vec::new();
+    for (idx, dependent) in template.dependents.iter().enumerate() {
+        let job = TestMatrix { dependents: vec![dependent.clone()], ..template.clone() };
+        let id = format!("{:05}-{}", idx, dependent.crate_ref.name);
+        let path = run_dir.join("jobs").join(format!("{}.json", id));
+        let json = serde_json::to_string_pretty(&job).map_err(|e| format!("Failed to serialize job {}: {}", id, e))?;
+        fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        ids.push(id);
+    }
+    Ok(ids)
+}
+
+/// (run directory, job id, the job or why it can't be read)
+type Claimed = (PathBuf, String, Result<TestMatrix, String>);
+
+/// Take the first pending job of any run; the rename makes the claim exclusive. A claimed
+/// job that can't be read comes back as its error, to be posted like any other failure
+fn claim(queue_dir: &Path) -> Result<Option<Claimed>, String> {
+    for run_dir in sorted_entries(queue_dir) {
+        for job_path in sorted_entries(&run_dir.join("jobs")) {
+            let Some(file_name) = job_path.file_name() else { continue };
+            let claimed = run_dir.join("claimed").join(file_name);
+            if fs::rename(&job_path, &claimed).is_err() {
+                continue; // another worker got it first
+            }
+            let job = fs::read_to_string(&claimed)
+                .map_err(|e| format!("Failed to read {}: {}", claimed.display(), e))
+                .and_then(|text| {
+                    serde_json::from_str(&text).map_err(|e| format!("Invalid job file {}: {}", claimed.display(), e))
+                });
+            let id = claimed.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            return Ok(Some((run_dir, id, job)));
+        }
+    }
+    Ok(None)
+}
+
+/// Write a job's result (atomically, so the coordinator never reads half a file)
+fn post(run_dir: &Path, id: &str, result: &JobResult) -> Result<(), String> {
+    let path = run_dir.join("results").join(format!("{}.json", id));
+    let tmp = run_dir.join("results").join(format!(".{}.json.tmp", id));
+    let json = serde_json::to_string(result).map_err(|e| format!("Failed to serialize result {}: {}", id, e))?;
+    fs::write(&tmp, json).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to move result into {}: {}", path.display(), e))?;
+    let _ = fs::remove_file(run_dir.join("claimed").join(format!("{}.json", id)));
+    Ok(())
+}
+
+/// Results posted since the last call
+fn poll_results(run_dir: &Path, seen: &mut HashSet<String>) -> Result<Vec<(String, JobResult)>, String> {
+    let mut new = Vec::new();
+    for path in sorted_entries(&run_dir.join("results")) {
+        let id = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        if id.starts_with('.') || seen.contains(&id) {
+            continue;
+        }
+        let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let result =
+            serde_json::from_str(&text).map_err(|e| format!("Invalid result file {}: {}", path.display(), e))?;
+        seen.insert(id.clone());
+        new.push((id, result));
+    }
+    Ok(new)
+}
+
+/// Point a job's local sources (stored relative to the run directory) at this machine's copy
+fn resolve_sources(job: &mut TestMatrix, run_dir: &Path) {
+    for spec in job.base_versions.iter_mut().chain(job.dependents.iter_mut()) {
+        if let CrateSource::Local { path } | CrateSource::Git { path, .. } = &mut spec.crate_ref.source
+            && path.is_relative()
+        {
+            *path = run_dir.join(&*path);
+        }
+    }
+}
+
+/// A work-queue Job: `parallelism` pods run workers until the queue is empty
+fn kubernetes_job(run_name: &str, parallelism: usize, image: &str) -> String {
+    format!(
+        "apiVersion: batch/v1
+kind: Job
+metadata:
+  name: cargo-copter-{run_name}
+spec:
+  parallelism: {parallelism}
+  backoffLimit: {parallelism}
+  template:
+    spec:
+      restartPolicy: OnFailure
+      containers:
+        - name: worker
+          image: {image}
+          command:
+            - bash
+            - -c
+            - command -v cargo-copter >/dev/null || cargo install cargo-copter; cargo-copter worker --queue-dir {POD_QUEUE_DIR}
+          volumeMounts:
+            - name: queue
+              mountPath: {POD_QUEUE_DIR}
+      volumes:
+        - name: queue
+          persistentVolumeClaim:
+            claimName: copter-queue
+"
+    )
+}
+
+/// `kubectl apply -f`, or say how to do it by hand
+fn apply_manifest(manifest: &Path) {
+    let applied =
+        std::process::Command::new("kubectl").args(["apply", "-f"]).arg(manifest).status().is_ok_and(|s| s.success());
+    if applied {
+        eprintln!("Started Kubernetes workers ({})", manifest.display());
+    } else {
+        eprintln!("warning: couldn't apply {}; run `kubectl apply -f` on it to start workers", manifest.display());
+    }
+}
+
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> =
+        fs::read_dir(dir).map(|rd| rd.flatten().map(|e| e.path()).collect()).unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+fn count_files(dir: &Path) -> usize {
+    sorted_entries(dir).len()
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME").ok().filter(|h| !h.is_empty()).unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OverrideMode, Steps, VersionSpec, VersionedCrate};
+
+    fn dependent(name: &str) -> VersionSpec {
+        VersionSpec {
+            crate_ref: VersionedCrate::latest_from_registry(name),
+            override_mode: OverrideMode::None,
+            is_baseline: false,
+        }
+    }
+
+    fn matrix(base_dir: PathBuf) -> TestMatrix {
+        TestMatrix {
+            base_crate: "rgb".to_string(),
+            base_versions: vec![
+                VersionSpec::baseline(VersionedCrate::latest_from_registry("rgb")),
+                VersionSpec::with_patch(VersionedCrate::from_local("rgb", "0.9.0", base_dir)),
+            ],
+            dependents: vec![dependent("image"), dependent("ravif")],
+            staging_dir: PathBuf::from("/coordinator/staging"),
+            steps: Steps::default(),
+            error_lines: 10,
+            patch_transitive: false,
+            min_free_bytes: 0,
+            heartbeat: Default::default(),
+            cross: Default::default(),
+            nightly_gates: None,
+            dependent_patches: Default::default(),
+            simulate_update: false,
+            staging_registry: None,
+            dependent_settings: Default::default(),
+            population: None,
+            baseline_cache: false,
+            staging_copy: crate::staging::CopyMethod::Auto,
+            source_cache: Default::default(),
+            registry: Default::default(),
+            audit: Default::default(),
+            force_resolver: false,
+            toolchains: vec![],
+            quarantined: vec![],
+        }
+    }
+
+    #[test]
+    fn test_submit_claim_post_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let base = tmp.path().join("rgb");
+        fs::create_dir_all(base.join("target")).unwrap();
+        fs::write(base.join("Cargo.toml"), "[package]\nname = \"rgb\"\n").unwrap();
+        let queue = tmp.path().join("queue");
+        let run_dir = queue.join("run-1");
+
+        assert_eq!(submit(&matrix(base), &run_dir).unwrap(), ["00000-image", "00001-ravif"]);
+        assert!(run_dir.join("sources/rgb-0.9.0/Cargo.toml").is_file());
+        assert!(!run_dir.join("sources/rgb-0.9.0/target").exists());
+
+        let (claimed_run, id, job) = claim(&queue).unwrap().unwrap();
+        let mut job = job.unwrap();
+        assert_eq!((claimed_run.as_path(), id.as_str()), (run_dir.as_path(), "00000-image"));
+        assert_eq!(job.dependents.len(), 1);
+        resolve_sources(&mut job, &run_dir);
+        assert_eq!(
+            job.base_versions[1].crate_ref.source,
+            CrateSource::Local { path: run_dir.join("sources/rgb-0.9.0") }
+        );
+
+        post(&run_dir, &id, &JobResult { worker: "w1".to_string(), results: vec![], error: None }).unwrap();
+        let mut seen = HashSet::new();
+        assert_eq!(poll_results(&run_dir, &mut seen).unwrap().len(), 1);
+        assert!(poll_results(&run_dir, &mut seen).unwrap().is_empty());
+        assert_eq!(count_files(&run_dir.join("claimed")), 0);
+
+        assert_eq!(claim(&queue).unwrap().unwrap().1, "00001-ravif");
+        assert!(claim(&queue).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_stale_claim_is_requeued_once_then_lost() {
+        let tmp = tempfile::tempdir().unwrap();
+        let queue = tmp.path().join("queue");
+        let run_dir = queue.join("run-1");
+        let mut m = matrix(tmp.path().join("rgb"));
+        m.base_versions.truncate(1);
+        submit(&m, &run_dir).unwrap();
+        assert!(claim(&queue).unwrap().unwrap().2.is_ok());
+
+        let seen = HashSet::new();
+        let mut claims = Claims::default();
+        assert_eq!(claims.check(&run_dir, &seen, Duration::from_secs(3600)), (vec![], true));
+        assert_eq!(claims.check(&run_dir, &seen, Duration::from_secs(3600)), (vec![], false));
+        // Timed out: back in jobs/ for another worker
+        assert_eq!(claims.check(&run_dir, &seen, Duration::ZERO), (vec![], false));
+        assert_eq!(claim(&queue).unwrap().unwrap().1, "00000-image");
+        assert_eq!(claims.check(&run_dir, &seen, Duration::ZERO), (vec!["00000-image".to_string()], true));
+    }
+
+    #[test]
+    fn test_unreadable_job_is_posted_as_failed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let queue = tmp.path().join("queue");
+        let run_dir = queue.join("run-1");
+        for dir in ["jobs", "claimed", "results"] {
+            fs::create_dir_all(run_dir.join(dir)).unwrap();
+        }
+        fs::write(run_dir.join("jobs/00000-image.json"), "{").unwrap();
+
+        work(&Default::default(), &queue, &tmp.path().join("staging"), false).unwrap();
+        let posted = poll_results(&run_dir, &mut HashSet::new()).unwrap();
+        assert_eq!(posted.len(), 1);
+        assert!(posted[0].1.error.as_deref().unwrap().starts_with("Invalid job file"));
+    }
+
+    #[test]
+    fn test_kubernetes_job() {
+        let yaml = kubernetes_job("run-20260101-120000", 8, "ghcr.io/imazen/cargo-copter:latest");
+        assert!(yaml.contains("name: cargo-copter-run-20260101-120000"));
+        assert!(yaml.contains("parallelism: 8"));
+        assert!(yaml.contains("cargo-copter worker --queue-dir /queue"));
+    }
+
+    #[test]
+    fn test_build_jobs_per_worker() {
+        assert_eq!(build_jobs_per_worker(32, 8), 4);
+        assert_eq!(build_jobs_per_worker(10, 4), 2);
+        assert_eq!(build_jobs_per_worker(4, 8), 1);
+        assert_eq!(build_jobs_per_worker(16, 0), 16);
+    }
+}
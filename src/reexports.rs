@@ -0,0 +1,164 @@
+/// Dependents that re-export the base crate's items publicly
+///
+/// A dependent with `pub use rgb::RGB8` (or `pub extern crate rgb`) puts the base crate's
+/// types into its own public API, so a semver-breaking release of the base crate is a
+/// breaking change for *its* users too. After a run the sources of every tested dependent
+/// (the staged copy, or the local path) are scanned for public `use`/`extern crate`
+/// statements naming the base crate, including renamed dependencies
+/// (`rgb_crate = { package = "rgb" }`), and listed in report.md's "Re-exporters" section.
+use crate::types::{CrateSource, OfferedRow, TestMatrix};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// One public re-export statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReExport {
+    /// Source file, relative to the dependent's root
+    pub file: String,
+    pub line: usize,
+    /// The statement without `pub use` and `;`, whitespace collapsed (`rgb::{RGB8, RGBA8}`)
+    pub item: String,
+}
+
+/// A dependent and its re-exports of the base crate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReExporter {
+    pub name: String,
+    pub version: String,
+    pub reexports: Vec<ReExport>,
+}
+
+/// Scan every dependent tested in `rows`
+pub fn find(matrix: &TestMatrix, rows: &[OfferedRow]) -> Vec<ReExporter> {
+    let mut seen: Vec<(&str, &str)> = Vec::new();
+    let mut found = Vec::new();
+    for row in rows {
+        let key = (row.primary.dependent_name.as_str(), row.primary.dependent_version.as_str());
+        if seen.contains(&key) {
+            continue;
+        }
+        seen.push(key);
+        let dir = source_dir(matrix, key.0, key.1);
+        let reexports = scan(&dir, &matrix.base_crate);
+        if !reexports.is_empty() {
+            found.push(ReExporter { name: key.0.to_string(), version: key.1.to_string(), reexports });
+        }
+    }
+    found
+}
+
+/// Local dependents are tested in place; published ones are unpacked into the staging dir
+fn source_dir(matrix: &TestMatrix, name: &str, version: &str) -> PathBuf {
+    matrix
+        .dependents
+        .iter()
+        .find_map(|d| match &d.crate_ref.source {
+            CrateSource::Local { path } if d.crate_ref.name == name => Some(path.clone()),
+            _ => None,
+        })
+        .unwrap_or_else(|| matrix.staging_dir.join(format!("{}-{}", name, version)))
+}
+
+/// Public re-exports of `base_crate` in the crate at `dir` (its `src/` tree)
+pub fn scan(dir: &Path, base_crate: &str) -> Vec<ReExport> {
+    let names = crate_idents(dir, base_crate);
+    let alternatives: Vec<String> = names.iter().map(|n| regex::escape(n)).collect();
+    let pattern = format!(
+        r"(?m)^[ \t]*pub[ \t]+(?:use[ \t\n]+(?:::)?((?:{0})\b[^;]*)|extern[ \t]+crate[ \t]+((?:{0})\b[^;]*));",
+        alternatives.join("|")
+    );
+    let Ok(re) = Regex::new(&pattern) else {
+        return vec![];
+    };
+
+    let mut files = Vec::new();
+    collect_rs_files(&dir.join("src"), &mut files);
+    files.sort();
+    let mut found = Vec::new();
+    for file in files {
+        let Ok(text) = std::fs::read_to_string(&file) else { continue };
+        for caps in re.captures_iter(&text) {
+            let (Some(whole), Some(item)) = (caps.get(0), caps.get(1).or_else(|| caps.get(2))) else { continue };
+            found.push(ReExport {
+                file: file.strip_prefix(dir).unwrap_or(&file).display().to_string().replace('\\', "/"),
+                line: text[..whole.start()].matches('\n').count() + 1,
+                item: item.as_str().split_whitespace().collect::<Vec<_>>().join(" "),
+            });
+        }
+    }
+    found
+}
+
+/// Names the dependent's code uses for the base crate: `rgb`, plus any `package = "rgb"` rename
+fn crate_idents(dir: &Path, base_crate: &str) -> Vec<String> {
+    let mut names = vec![base_crate.replace('-', "_")];
+    let Some(manifest) =
+        std::fs::read_to_string(dir.join("Cargo.toml")).ok().and_then(|t| t.parse::<toml::Table>().ok())
+    else {
+        return names;
+    };
+    let mut tables: Vec<&toml::Table> = manifest.get("dependencies").and_then(|d| d.as_table()).into_iter().collect();
+    if let Some(targets) = manifest.get("target").and_then(|t| t.as_table()) {
+        tables.extend(targets.values().filter_map(|t| t.get("dependencies")?.as_table()));
+    }
+    for (key, spec) in tables.into_iter().flatten() {
+        if spec.get("package").and_then(|p| p.as_str()) == Some(base_crate) {
+            let ident = key.replace('-', "_");
+            if !names.contains(&ident) {
+                names.push(ident);
+            }
+        }
+    }
+    names
+}
+
+fn collect_rs_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.is_dir() {
+            collect_rs_files(&path, out);
+        } else if path.extension().is_some_and(|e| e == "rs") {
+            out.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_finds_public_reexports() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::create_dir_all(dir.join("src/pixels")).unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"image\"\n\n[dependencies]\nrgb_crate = { package = \"rgb\", version = \"0.8\" }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("src/lib.rs"),
+            "use rgb::RGB8;\npub(crate) use rgb::RGBA8;\npub use rgb::{\n    RGB16,\n    RGBA16,\n};\npub extern crate rgb;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("src/pixels/mod.rs"),
+            "// pub use rgb::Gray;\n  pub use ::rgb_crate::alt::Gray as G;\npub use rgbx::Y;\n",
+        )
+        .unwrap();
+
+        let found = scan(dir, "rgb");
+        let items: Vec<(&str, usize, &str)> =
+            found.iter().map(|r| (r.file.as_str(), r.line, r.item.as_str())).collect();
+        assert_eq!(
+            items,
+            vec![
+                ("src/lib.rs", 3, "rgb::{ RGB16, RGBA16, }"),
+                ("src/lib.rs", 7, "rgb"),
+                ("src/pixels/mod.rs", 2, "rgb_crate::alt::Gray as G"),
+            ]
+        );
+        assert!(scan(dir, "serde").is_empty());
+    }
+}
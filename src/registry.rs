@@ -0,0 +1,100 @@
+/// The registry one run reads: crates.io, or `COPTER_REGISTRY_API`, or the `--index-url`
+/// mirror, with the `--prefetch` / `--offline` directory in front of it
+///
+/// Built once from the command line and passed to everything that reaches the registry
+/// (version and dependent lookups, downloads, cargo's own fetches), so two runs in one
+/// process never share a mirror or an offline directory. `get` is the only way lookups go
+/// out: with `--offline` it fails instead of touching the network.
+use crate::cli::CliArgs;
+use crate::error::CopterError;
+use crate::mirror::Mirror;
+use crate::offline::Mode;
+use log::debug;
+use std::path::PathBuf;
+
+/// Where registry lookups, downloads and cargo's fetches go
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Registry {
+    /// Another registry's web API (`COPTER_REGISTRY_API`), used for lookups and downloads
+    pub api: Option<String>,
+    /// `--index-url` / `COPTER_INDEX`
+    pub mirror: Option<Mirror>,
+    /// `--prefetch` / `--offline`
+    pub offline: Option<Mode>,
+}
+
+impl Registry {
+    /// The registry `args` name: `--index-url` (else `$COPTER_INDEX`, whose index config is
+    /// read now), `$COPTER_REGISTRY_API`, and `--prefetch` or `--offline` (checked and resolved)
+    pub fn from_args(args: &CliArgs) -> Result<Self, CopterError> {
+        let index_url = args.index_url.clone().or_else(|| std::env::var(crate::mirror::INDEX_ENV).ok());
+        let mirror = match index_url.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+            Some(url) => Some(Mirror::discover(url).map_err(CopterError::Config)?),
+            None => None,
+        };
+        debug!("using crates.io mirror {:?}", mirror);
+        let offline = match (&args.prefetch, &args.offline) {
+            (Some(dir), _) => Some(Mode::Prefetch(dir.clone()).resolve()?),
+            (_, Some(dir)) => Some(Mode::Offline(dir.clone()).resolve()?),
+            _ => None,
+        };
+        Ok(Registry { api: crate::api::registry_api_override(), mirror, offline })
+    }
+
+    /// Another registry's API at `api`, online
+    pub fn with_api(api: &str) -> Self {
+        Registry { api: Some(api.trim_end_matches('/').to_string()), ..Default::default() }
+    }
+
+    /// `COPTER_REGISTRY_API`, else the API of the `--index-url` mirror, if it serves one
+    pub fn custom_api_base(&self) -> Option<String> {
+        self.api.clone().or_else(|| self.mirror.as_ref().and_then(|m| m.api.clone()))
+    }
+
+    /// Registry API base used for direct HTTP calls (downloads, custom registries)
+    pub fn api_base(&self) -> String {
+        self.custom_api_base().unwrap_or_else(|| crate::api::CRATES_IO_API.to_string())
+    }
+
+    /// Whether nothing may be downloaded (`--offline`)
+    pub fn is_offline(&self) -> bool {
+        matches!(self.offline, Some(Mode::Offline(_)))
+    }
+
+    /// Where `.crate` files are kept instead of the crate cache, in either mode
+    pub fn crates_dir(&self) -> Option<PathBuf> {
+        self.offline.as_ref().map(|mode| mode.dir().join("crates"))
+    }
+
+    /// Where dependent lookups are kept instead of the dependents cache, in either mode
+    pub fn dependents_dir(&self) -> Option<PathBuf> {
+        self.offline.as_ref().map(|mode| mode.dir().join("dependents"))
+    }
+
+    /// HTTP GET for a registry lookup or download
+    pub fn get(&self, url: &str) -> Result<Vec<u8>, CopterError> {
+        self.get_with(url, &[])
+    }
+
+    /// HTTP GET with extra request headers (`Authorization`, `Accept`)
+    pub fn get_with(&self, url: &str, headers: &[(&str, &str)]) -> Result<Vec<u8>, CopterError> {
+        if self.is_offline() {
+            return Err(CopterError::Network(format!("cannot fetch {} with --offline", url)));
+        }
+        crate::download::http_get_bytes_with(url, headers)
+    }
+
+    /// Arguments for every cargo step: the mirror as crates.io's replacement, and with
+    /// `--offline` the prefetched vendor directory
+    pub fn cargo_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        for config in self.mirror.as_ref().map(Mirror::cargo_config).unwrap_or_default() {
+            args.push("--config".to_string());
+            args.push(config);
+        }
+        if let Some(mode) = &self.offline {
+            args.extend(mode.cargo_args());
+        }
+        args
+    }
+}
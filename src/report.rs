@@ -7,13 +7,28 @@
 /// - Error signature extraction for deduplication
 ///
 /// Console rendering is handled by the console_format module.
-use crate::console_format::{self, ComparisonStats};
-use crate::types::{CommandType, OfferedRow, TestResult, VersionSource};
+use crate::artifacts::SaveArtifacts;
+use crate::console_format::{self, ColorScheme, ComparisonStats, Layout};
+use crate::messages::Catalog;
+use crate::run_metadata::RunMetadata;
+use crate::types::{CommandType, OfferedRow, RowStatus, Severity, TestResult, VersionSource};
 use std::fs::File;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use term::color::Color;
 
+pub mod github;
+
+/// How report.md shows statuses (`--status-style`)
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StatusStyle {
+    /// ✓ ✗ ⚠ ⊘ markers, and the console table in a collapsed block
+    #[default]
+    Symbols,
+    /// PASSED, REGRESSED, FIXED, ... and no console table, for screen readers and text tooling
+    Words,
+}
+
 //
 // Rendering Model Types
 //
@@ -78,22 +93,12 @@ impl OfferedCell {
         }
 
         let offered = row.offered.as_ref().unwrap();
-        let overall_passed = row.test.commands.iter().all(|cmd| cmd.result.passed);
-
-        // Check if non-forced version wasn't actually used (cargo resolved to something else)
-        let not_used = !offered.forced && !row.primary.used_offered_version;
 
         // Determine status icon
-        let icon = if not_used {
-            StatusIcon::Skipped // Version wasn't used (cargo chose different version)
-        } else {
-            match (row.baseline_passed, overall_passed) {
-                (Some(true), true) => StatusIcon::Passed,  // PASSED
-                (Some(true), false) => StatusIcon::Failed, // REGRESSED
-                (Some(false), _) => StatusIcon::Failed,    // BROKEN (baseline failed)
-                (None, true) => StatusIcon::Passed,        // PASSED (no baseline)
-                (None, false) => StatusIcon::Failed,       // FAILED (no baseline)
-            }
+        let icon = match row.status() {
+            RowStatus::Passed | RowStatus::Fixed => StatusIcon::Passed,
+            RowStatus::NotUsed | RowStatus::UsesFork | RowStatus::Skipped => StatusIcon::Skipped, // cargo chose a different version
+            _ => StatusIcon::Failed,
         };
 
         // Determine resolution marker
@@ -137,92 +142,60 @@ impl OfferedCell {
 // Public API: Delegate to console_format module
 //
 
-/// Initialize table widths based on versions being tested
-pub fn init_table_widths(versions: &[String], display_version: &str, force_versions: bool) {
-    console_format::init_table_widths(versions, display_version, force_versions);
+/// Table layout for the versions being tested, at `terminal_width` columns
+pub fn table_layout(
+    terminal_width: usize,
+    versions: &[String],
+    display_version: &str,
+    force_versions: bool,
+    observed: Option<&crate::column_widths::ObservedWidths>,
+    messages: std::sync::Arc<Catalog>,
+) -> Layout {
+    Layout::for_versions(terminal_width, versions, display_version, force_versions, observed, messages)
+}
+
+/// Redraw the table's column header if the terminal was resized since the last row
+pub fn follow_terminal_resize(layout: &mut Layout, terminal_width: usize) -> bool {
+    console_format::follow_terminal_resize(layout, terminal_width)
 }
 
 /// Print table header
 pub fn print_table_header(
+    layout: &Layout,
     crate_name: &str,
     display_version: &str,
     total_deps: usize,
     test_plan: Option<&str>,
     this_path: Option<&str>,
 ) {
-    console_format::print_table_header(crate_name, display_version, total_deps, test_plan, this_path);
+    console_format::print_table_header(layout, crate_name, display_version, total_deps, test_plan, this_path);
 }
 
 /// Format table header as a string
 pub fn format_table_header(
+    layout: &Layout,
     crate_name: &str,
     display_version: &str,
     total_deps: usize,
     test_plan: Option<&str>,
     this_path: Option<&str>,
 ) -> String {
-    console_format::format_table_header(crate_name, display_version, total_deps, test_plan, this_path)
+    console_format::format_table_header(layout, crate_name, display_version, total_deps, test_plan, this_path)
 }
 
 /// Print separator line between dependents
-pub fn print_separator_line() {
-    console_format::print_separator_line();
+pub fn print_separator_line(layout: &Layout) {
+    console_format::print_separator_line(layout);
 }
 
 /// Format table footer as a string
-pub fn format_table_footer() -> String {
-    console_format::format_table_footer()
+pub fn format_table_footer(layout: &Layout) -> String {
+    console_format::format_table_footer(layout)
 }
 
 /// Print table footer
-pub fn print_table_footer() {
-    console_format::print_table_footer();
-}
-
-/// Normalize file paths by removing hex suffixes (e.g., file-abc123 -> file)
-/// Handles both Unix (/) and Windows (\) paths
-fn normalize_path_hex_codes(text: &str) -> String {
-    let mut result = String::with_capacity(text.len());
-    let mut i = 0;
-    let chars: Vec<char> = text.chars().collect();
-
-    while i < chars.len() {
-        result.push(chars[i]);
-
-        // Check if we just pushed a path separator
-        if chars[i] == '/' || chars[i] == '\\' {
-            // Look ahead to find the next path component
-            let mut j = i + 1;
-            let mut component = String::new();
-
-            // Collect characters until next separator, space, or end
-            while j < chars.len() && chars[j] != '/' && chars[j] != '\\' && !chars[j].is_whitespace() {
-                component.push(chars[j]);
-                j += 1;
-            }
-
-            // Check if component ends with -[hex] pattern
-            if let Some(dash_pos) = component.rfind('-') {
-                let potential_hex = &component[dash_pos + 1..];
-                // Check if it's all hex digits and at least 8 chars
-                if potential_hex.len() >= 8 && potential_hex.chars().all(|c| c.is_ascii_hexdigit()) {
-                    // Remove the -[hex] suffix
-                    result.push_str(&component[..dash_pos]);
-                    i = j;
-                    continue;
-                }
-            }
-
-            // No hex pattern, add component as-is
-            result.push_str(&component);
-            i = j;
-            continue;
-        }
-
-        i += 1;
-    }
-
-    result
+pub fn print_table_footer(layout: &Layout) {
+    console_format::print_table_footer(layout);
 }
 
 /// Extract error signature for comparison - normalizes line numbers and sorts errors
@@ -255,7 +228,7 @@ pub fn extract_error_text(row: &OfferedRow) -> Option<String> {
     // Skipped rows should match baseline since they use the same version
 
     // Extract FULL error (0 = unlimited) for comparison purposes
-    let formatted = format_offered_row(row, 0);
+    let formatted = format_offered_row(row, 0, ColorScheme::Default, &Catalog::default());
     if formatted.error_details.is_empty() {
         None
     } else {
@@ -265,40 +238,181 @@ pub fn extract_error_text(row: &OfferedRow) -> Option<String> {
     }
 }
 
+/// How a row's error relates to errors already printed in this run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorOccurrence {
+    /// Row has no error output
+    NoError,
+    /// First row with this error signature
+    First,
+    /// Seen before; `first` names the row that printed it, `count` includes this row
+    Repeat { first: String, count: usize, adjacent: bool },
+}
+
+/// Error signatures seen so far, so each distinct error is printed in full only once
+///
+/// The `--error-lines` budget goes to the first occurrence of each cluster;
+/// later rows with the same signature get a one-line reference instead.
+#[derive(Debug, Default)]
+pub struct ErrorClusters {
+    seen: std::collections::HashMap<String, (String, usize)>,
+    last: Option<String>,
+    /// Error signature of each dependent's baseline row, keyed by "name version"
+    baseline_signatures: std::collections::HashMap<String, String>,
+}
+
+impl ErrorClusters {
+    /// Record a row's error and report whether it was seen before
+    pub fn observe(&mut self, row: &OfferedRow) -> ErrorOccurrence {
+        let Some(key) = error_cluster_key(row) else {
+            self.last = None;
+            return ErrorOccurrence::NoError;
+        };
+        let adjacent = self.last.as_deref() == Some(key.as_str());
+        self.last = Some(key.clone());
+        if row.offered.is_none() {
+            let dependent = format!("{} {}", row.primary.dependent_name, row.primary.dependent_version);
+            self.baseline_signatures.insert(dependent, key.clone());
+        }
+
+        let label = match &row.offered {
+            Some(o) => format!("{} {} with {}", row.primary.dependent_name, row.primary.dependent_version, o.version),
+            None => format!("{} {} baseline", row.primary.dependent_name, row.primary.dependent_version),
+        };
+        let entry = self.seen.entry(key).or_insert_with(|| (label, 0));
+        entry.1 += 1;
+        if entry.1 == 1 {
+            ErrorOccurrence::First
+        } else {
+            ErrorOccurrence::Repeat { first: entry.0.clone(), count: entry.1, adjacent }
+        }
+    }
+
+    /// Diff of an offered row's errors against its dependent's baseline errors
+    pub fn baseline_diff(&self, row: &OfferedRow) -> Option<Vec<String>> {
+        let dependent = format!("{} {}", row.primary.dependent_name, row.primary.dependent_version);
+        let baseline = self.baseline_signatures.get(&dependent)?;
+        error_signature_diff(baseline, &error_cluster_key(row)?)
+    }
+}
+
+/// Compact diff of two error signatures (one normalized error per line)
+///
+/// Returns `None` when they are identical or either side is empty. Lines only
+/// in the baseline are prefixed `-`, lines new with the offered version `+`.
+pub fn error_signature_diff(baseline: &str, offered: &str) -> Option<Vec<String>> {
+    use std::collections::BTreeSet;
+
+    let old: BTreeSet<&str> = baseline.lines().filter(|l| !l.trim().is_empty()).collect();
+    let new: BTreeSet<&str> = offered.lines().filter(|l| !l.trim().is_empty()).collect();
+    if old.is_empty() || new.is_empty() || old == new {
+        return None;
+    }
+
+    let mut lines = vec!["errors changed vs baseline:".to_string()];
+    lines.extend(old.difference(&new).map(|l| format!("  - {}", l.trim())));
+    lines.extend(new.difference(&old).map(|l| format!("  + {}", l.trim())));
+    let unchanged = old.intersection(&new).count();
+    if unchanged > 0 {
+        lines.push(format!("  ({} unchanged)", unchanged));
+    }
+    Some(lines)
+}
+
+/// Dependents named after "and N more:" before the list is cut
+const COLLAPSED_NAMES: usize = 10;
+
+/// `--collapse-identical`: `rows` to print, with regressions that share an offered version and
+/// error signature folded into the first of them, which gets "and 11 more: b 1.0.0, c 2.1.0, …"
+///
+/// A dependent whose offered rows were all folded into other dependents' rows is left out,
+/// baseline included.
+pub fn collapse_identical<'a>(rows: &[&'a OfferedRow]) -> Vec<(&'a OfferedRow, Option<String>)> {
+    use std::collections::{HashMap, HashSet};
+
+    // (offered version, signature) → indices of its regressions
+    let mut groups: HashMap<(String, String), Vec<usize>> = HashMap::new();
+    for (i, row) in rows.iter().enumerate() {
+        if let (true, Some(offered), Some(key)) = (row.is_regression(), &row.offered, error_cluster_key(row)) {
+            groups.entry((offered.version.clone(), key)).or_default().push(i);
+        }
+    }
+
+    let mut folded = HashSet::new();
+    let mut notes = HashMap::new();
+    for members in groups.values().filter(|members| members.len() > 1) {
+        let names: Vec<String> = members[1..].iter().map(|&i| rows[i].primary.dependent_label()).collect();
+        let shown = names.iter().take(COLLAPSED_NAMES).cloned().collect::<Vec<_>>().join(", ");
+        let cut = if names.len() > COLLAPSED_NAMES { ", …" } else { "" };
+        notes.insert(members[0], format!("and {} more: {}{}", names.len(), shown, cut));
+        folded.extend(members[1..].iter().copied());
+    }
+
+    let dependent = |row: &OfferedRow| (row.primary.dependent_name.clone(), row.primary.dependent_version.clone());
+    let offered_rows = rows.iter().enumerate().filter(|(_, row)| row.offered.is_some());
+    let (gone, kept): (Vec<_>, Vec<_>) = offered_rows.partition(|(i, _)| folded.contains(i));
+    let kept: HashSet<_> = kept.into_iter().map(|(_, row)| dependent(row)).collect();
+    let gone: HashSet<_> = gone.into_iter().map(|(_, row)| dependent(row)).filter(|d| !kept.contains(d)).collect();
+    rows.iter()
+        .enumerate()
+        .filter(|(i, row)| !folded.contains(i) && (row.offered.is_some() || !gone.contains(&dependent(row))))
+        .map(|(i, row)| (*row, notes.remove(&i)))
+        .collect()
+}
+
+/// Key identifying an error cluster: the error signature, or the normalized
+/// full text when there are no `error[...]` codes (e.g. fetch failures)
+fn error_cluster_key(row: &OfferedRow) -> Option<String> {
+    let formatted = format_offered_row(row, 0, ColorScheme::Default, &Catalog::default());
+    if formatted.error_details.is_empty() {
+        return None;
+    }
+    let text = formatted.error_details.join("\n");
+    let signature = error_signature(&text);
+    Some(if signature.is_empty() { crate::scrub::normalize_path_hex_codes(&text) } else { signature })
+}
+
 /// Print an OfferedRow using the standard table format
-pub fn print_offered_row(row: &OfferedRow, is_last_in_group: bool, prev_error: Option<&str>, max_error_lines: usize) {
+pub fn print_offered_row(
+    layout: &Layout,
+    row: &OfferedRow,
+    is_last_in_group: bool,
+    clusters: &mut ErrorClusters,
+    max_error_lines: usize,
+    note: Option<&str>,
+) {
     // Convert OfferedRow to formatted data
-    let mut formatted = format_offered_row(row, max_error_lines);
+    let mut formatted = format_offered_row(row, max_error_lines, layout.color_scheme, &layout.messages);
 
     // Don't show "same failure" on baseline rows (they're the reference point)
     let is_baseline = row.offered.is_none();
 
-    // When errors match exactly, show "same failure" instead of repeating the error
-    // Applies to both regression and broken scenarios, but NOT for baseline rows
-    if !is_baseline
-        && let Some(prev) = prev_error
-        && !formatted.error_details.is_empty()
+    // Repeated errors get "same failure" instead of the full error again:
+    // nothing more when the previous row had it, a one-line reference otherwise
+    let occurrence = clusters.observe(row);
+    if let ErrorOccurrence::Repeat { first, count, adjacent } = occurrence
+        && !is_baseline
+        && formatted.status.is_failure()
+    {
+        formatted.result = format!("same failure {}", formatted.ict_marks);
+        formatted.error_details.clear();
+        if !adjacent {
+            formatted.error_details.push(format!("same as above: {} (x{})", first, count));
+        }
+    } else if row.baseline_passed == Some(false)
+        && formatted.status.is_failure()
+        && let Some(mut diff) = clusters.baseline_diff(row)
     {
-        // Extract FULL error for comparison (not truncated)
-        // prev_error is full, so we need full current error too
-        let full_formatted = format_offered_row(row, 0);
-        let current_error = full_formatted.error_details.join("\n");
-        // Use error signature for robust comparison
-        let current_signature = error_signature(&current_error);
-        if current_signature == prev {
-            // Clear error details and update result to show "same failure"
-            // Keep ICT marks and time
-            formatted.error_details.clear();
-            // Replace the failure type with "same failure", keeping ICT marks
-            // For broken scenarios, also replace "test broken" -> "same failure"
-            formatted.result = formatted
-                .result
-                .replace("test failed", "same failure")
-                .replace("build failed", "same failure")
-                .replace("fetch failed", "same failure")
-                .replace("test broken", "same failure")
-                .replace("build broken", "same failure");
+        // Failed on baseline too, but differently: show what changed rather than the whole new error
+        if max_error_lines > 0 && diff.len() > max_error_lines + 1 {
+            let hidden = diff.len() - max_error_lines - 1;
+            diff.truncate(max_error_lines + 1);
+            diff.push(format!("  ... ({} more)", hidden));
         }
+        formatted.error_details = diff;
+    }
+    if let Some(note) = note {
+        formatted.error_details.insert(0, note.to_string());
     }
 
     // Format result column
@@ -306,30 +420,31 @@ pub fn print_offered_row(row: &OfferedRow, is_last_in_group: bool, prev_error: O
         // "still failing" case - no ICT marks or time
         format!("{:>18}", formatted.result)
     } else {
-        format!("{:>12} {:>5}", formatted.result, formatted.time)
+        result_cell(layout, &formatted)
     };
 
     // Print main row with color (delegate to console_format)
     console_format::print_main_row(
+        layout,
         [&formatted.offered, &formatted.spec, &formatted.resolved, &formatted.dependent, &result_display],
         formatted.color,
     );
 
     // Print error box if present (delegate to console_format)
     if !formatted.error_details.is_empty() {
-        console_format::print_error_box_top();
+        console_format::print_error_box_top(layout);
 
         for error_line in &formatted.error_details {
-            console_format::print_error_box_line(error_line);
+            console_format::print_error_box_line(layout, error_line);
         }
 
         if !is_last_in_group {
-            console_format::print_error_box_bottom();
+            console_format::print_error_box_bottom(layout);
         }
     }
 
     // Print multi-version dependency rows (delegate to console_format)
-    console_format::print_multi_version_rows(&formatted.multi_version_rows);
+    console_format::print_multi_version_rows(layout, &formatted.multi_version_rows);
 }
 
 //
@@ -342,15 +457,40 @@ pub struct FormattedRow {
     pub spec: String,
     pub resolved: String,
     pub dependent: String,
+    pub status: RowStatus,
     pub result: String,
+    pub ict_marks: String,
     pub time: String,
+    /// Fetch/check/test durations, "0.4/3.1/58.2s" ("-" for steps that didn't run)
+    pub step_times: String,
+    /// Errors and warnings of the last step run, "3E 12W" (empty when it had none)
+    pub diagnostics: String,
+    /// Local start time, "14:02:11" (empty when unknown)
+    pub started: String,
     pub color: Color,
     pub error_details: Vec<String>,
     pub multi_version_rows: Vec<(String, String, String)>,
 }
 
 /// Convert OfferedRow to renderable row data
-fn format_offered_row(row: &OfferedRow, max_error_lines: usize) -> FormattedRow {
+/// Display widths of a row's Spec, Resolved and Dependent cells
+pub fn cell_widths(row: &OfferedRow) -> crate::column_widths::ObservedWidths {
+    let formatted = format_offered_row(row, 0, ColorScheme::Default, &Catalog::default());
+    crate::column_widths::ObservedWidths {
+        spec: console_format::display_width(&formatted.spec),
+        resolved: console_format::display_width(&formatted.resolved),
+        dependent: console_format::display_width(&formatted.dependent),
+    }
+}
+
+/// The five cells of `row`, its status labeled in `messages` (callers that read only the
+/// other cells pass the default catalog)
+fn format_offered_row(
+    row: &OfferedRow,
+    max_error_lines: usize,
+    scheme: ColorScheme,
+    messages: &Catalog,
+) -> FormattedRow {
     // Format Offered column using type-safe OfferedCell
     let offered_cell = OfferedCell::from_offered_row(row);
     let offered_str = offered_cell.format();
@@ -371,49 +511,9 @@ fn format_offered_row(row: &OfferedRow, max_error_lines: usize) -> FormattedRow
     let resolved_str = format!("{} {}", row.primary.resolved_version, source_icon);
 
     // Format Dependent column
-    let dependent_str = format!("{} {}", row.primary.dependent_name, row.primary.dependent_version);
-
-    // Determine which step failed (if any)
-    let overall_passed = row.test.commands.iter().all(|cmd| cmd.result.passed);
-    let failed_step = row.test.commands.iter().find(|cmd| !cmd.result.passed).map(|cmd| match cmd.command {
-        CommandType::Fetch => "fetch failed",
-        CommandType::Check => "build failed",
-        CommandType::Test => "test failed",
-    });
-
-    // Check if this version wasn't actually used (non-forced and cargo chose different version)
-    let not_used =
-        if let Some(ref offered) = row.offered { !offered.forced && !row.primary.used_offered_version } else { false };
+    let dependent_str = row.primary.dependent_label() + &row.test.toolchain_suffix();
 
-    // Detect if this is a baseline row (no offered version)
-    let is_baseline = row.offered.is_none();
-
-    let result_status = if not_used {
-        "not used".to_string()
-    } else if is_baseline {
-        // Baseline row: if it failed, show "build broken" or "test broken"
-        if overall_passed {
-            "passed".to_string()
-        } else if let Some(step) = failed_step {
-            // Replace "failed" with "broken" for baseline failures
-            step.replace("failed", "broken")
-        } else {
-            "broken".to_string()
-        }
-    } else {
-        // Offered row: compare against baseline
-        match (row.baseline_passed, overall_passed, failed_step) {
-            (Some(true), true, _) => "passed".to_string(),
-            (Some(true), false, Some(step)) => step.to_string(),
-            (Some(true), false, None) => "regressed".to_string(),
-            // For offered rows when baseline was broken
-            (Some(false), _, Some(step)) => step.replace("failed", "broken"),
-            (Some(false), _, None) => "broken".to_string(),
-            (None, true, _) => "passed".to_string(),
-            (None, false, Some(step)) => step.to_string(),
-            (None, false, None) => "failed".to_string(),
-        }
-    };
+    let status = row.status();
 
     // Format ICT marks
     let mut ict_marks = String::new();
@@ -429,29 +529,47 @@ fn format_offered_row(row: &OfferedRow, max_error_lines: usize) -> FormattedRow
         ict_marks.push('-');
     }
 
-    let result_str = format!("{} {}", result_status, ict_marks);
+    // Without the default palette, the word carries what the color would
+    let result_str = if scheme.needs_status_words() {
+        format!("{} {}", status.word(), ict_marks)
+    } else {
+        format!("{} {}", status.label(messages), ict_marks)
+    };
 
     // Calculate total time
     let total_time: f64 = row.test.commands.iter().map(|cmd| cmd.result.duration).sum();
     let time_str = format!("{:.1}s", total_time);
-
-    // Determine color
-    let color = if not_used {
-        term::color::YELLOW // Brown (YELLOW/33) for skipped (not used) versions
-    } else if is_baseline && !overall_passed {
-        term::color::BRIGHT_YELLOW // Bright yellow (93) for failed baseline rows
-    } else {
-        match (row.baseline_passed, overall_passed) {
-            (Some(true), true) => term::color::BRIGHT_GREEN,
-            (Some(true), false) => term::color::BRIGHT_RED,
-            (Some(false), _) => term::color::BRIGHT_YELLOW, // Bright yellow for broken (baseline was broken)
-            (None, true) => term::color::BRIGHT_GREEN,
-            (None, false) => term::color::BRIGHT_RED,
-        }
+    let step_time = |step: CommandType| {
+        row.test
+            .commands
+            .iter()
+            .find(|cmd| cmd.command == step)
+            .map(|cmd| format!("{:.1}", cmd.result.duration))
+            .unwrap_or_else(|| "-".to_string())
     };
+    let step_times = format!(
+        "{}/{}/{}s",
+        step_time(CommandType::Fetch),
+        step_time(CommandType::Check),
+        step_time(CommandType::Test)
+    );
+    let diagnostics = row.test.commands.last().map_or_else(String::new, |cmd| {
+        let (errors, warnings) = (cmd.result.errors, cmd.result.warnings);
+        let parts = [(errors > 0).then(|| format!("{}E", errors)), (warnings > 0).then(|| format!("{}W", warnings))];
+        parts.into_iter().flatten().collect::<Vec<_>>().join(" ")
+    });
+    let started = row
+        .test
+        .started
+        .as_deref()
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|t| t.with_timezone(&chrono::Local).format("%H:%M:%S").to_string())
+        .unwrap_or_default();
+
+    let color = status_color(status);
 
     // Extract error details
-    let mut error_details = Vec::new();
+    let mut error_details: Vec<String> = row.test.skipped.iter().map(|reason| format!("skipped: {}", reason)).collect();
     for cmd in &row.test.commands {
         if !cmd.result.passed {
             let cmd_name = match cmd.command {
@@ -486,6 +604,12 @@ fn format_offered_row(row: &OfferedRow, max_error_lines: usize) -> FormattedRow
         }
     }
 
+    // Resolver 1 unifies features differently; a regression there may be phantom
+    if row.is_regression() && row.primary.resolver.as_deref() == Some("1") {
+        error_details
+            .push("built with resolver 1 (its edition's default): compare with --force-resolver-2".to_string());
+    }
+
     // Format transitive dependency rows (multi-version rows)
     let mut multi_version_rows = Vec::new();
     for transitive in &row.transitive {
@@ -506,14 +630,38 @@ fn format_offered_row(row: &OfferedRow, max_error_lines: usize) -> FormattedRow
         spec: spec_str,
         resolved: resolved_str,
         dependent: dependent_str,
+        status,
         result: result_str,
+        ict_marks,
         time: time_str,
+        step_times,
+        diagnostics,
+        started,
         color,
         error_details,
         multi_version_rows,
     }
 }
 
+/// Result cell text for `layout`'s time columns
+fn result_cell(layout: &Layout, formatted: &FormattedRow) -> String {
+    let time = if layout.step_times { &formatted.step_times } else { &formatted.time };
+    layout.result_cell(&formatted.result, time, &formatted.diagnostics, &formatted.started)
+}
+
+/// Table color for a row status
+pub fn status_color(status: RowStatus) -> Color {
+    match status {
+        RowStatus::NotUsed | RowStatus::UsesFork | RowStatus::Skipped => term::color::YELLOW, // Brown (YELLOW/33) for untested versions
+        RowStatus::Failed { .. } => term::color::BRIGHT_RED,
+        _ => match status.severity() {
+            Severity::Ok => term::color::BRIGHT_GREEN,
+            Severity::Regression => term::color::BRIGHT_RED,
+            Severity::Notice | Severity::Broken => term::color::BRIGHT_YELLOW, // Bright yellow (93) for broken
+        },
+    }
+}
+
 //
 // Summary and statistics
 //
@@ -522,48 +670,46 @@ pub struct TestSummary {
     pub passed: usize,
     pub regressed: usize,
     pub broken: usize,
+    /// Failures caused by the harness/environment (not counted as broken or regressed)
+    pub env_failed: usize,
+    /// Rows where the offered version wasn't tested (not used, fork, no steps run)
+    pub skipped: usize,
     pub total: usize,
 }
 
 /// Calculate summary statistics from OfferedRows
 ///
-/// Uses baseline_check_passed to distinguish "truly broken" (check fails)
-/// from "test already failing" (check passes, test fails). Only check
-/// failures count as "broken" — test-only failures on baseline are not
-/// counted against you.
+/// Rows are bucketed by `RowStatus::severity`. Only fetch/check failures
+/// count as "broken" — test-only failures already present on baseline are
+/// not counted against you. Rows that never tested the offered version
+/// (`RowStatus::is_skipped`) are counted apart, not as passed.
 pub fn summarize_offered_rows(rows: &[OfferedRow]) -> TestSummary {
     let mut passed = 0;
     let mut regressed = 0;
     let mut broken = 0;
-
-    for row in rows {
-        // Only count non-baseline rows
-        if row.offered.is_some() {
-            let overall_passed = row.test.commands.iter().all(|cmd| cmd.result.passed);
-
-            // Use baseline_check_passed to determine if baseline truly compiles
-            // Only consider baseline "broken" if check fails — test failures don't count
-            let baseline_compiles = row.baseline_check_passed.unwrap_or_else(|| row.baseline_passed.unwrap_or(false));
-
-            match (baseline_compiles, row.baseline_passed, overall_passed) {
-                // Baseline doesn't compile = truly broken (not your problem)
-                (false, _, _) => broken += 1,
-                // Baseline compiles AND fully passed, this passed
-                (true, Some(true), true) => passed += 1,
-                // Baseline compiles AND fully passed, this failed = REGRESSED
-                (true, Some(true), false) => regressed += 1,
-                // Baseline compiles but test failed, this passed
-                (true, Some(false), true) => passed += 1,
-                // Baseline compiles but test failed, this also failed = not a regression
-                (true, Some(false), false) => passed += 1,
-                // No baseline data
-                (true, None, true) => passed += 1,
-                (true, None, false) => broken += 1,
-            }
+    let mut env_failed = 0;
+    let mut skipped = 0;
+
+    // Only count non-baseline rows
+    for row in rows.iter().filter(|r| r.offered.is_some()) {
+        let status = row.status();
+        match status.severity() {
+            _ if matches!(status, RowStatus::EnvBroken { .. }) => env_failed += 1,
+            _ if status.is_skipped() => skipped += 1,
+            Severity::Ok | Severity::Notice => passed += 1,
+            Severity::Regression => regressed += 1,
+            Severity::Broken => broken += 1,
         }
     }
 
-    TestSummary { passed, regressed, broken, total: passed + regressed + broken }
+    TestSummary {
+        passed,
+        regressed,
+        broken,
+        env_failed,
+        skipped,
+        total: passed + regressed + broken + env_failed + skipped,
+    }
 }
 
 /// Extended summary with categorized failures for the compatibility report
@@ -578,6 +724,8 @@ pub struct CompatibilityReport {
     pub regressions: Vec<RegressionInfo>,
     /// Fixed (baseline failed, offered passed)
     pub fixed: Vec<String>,
+    /// Offered rows that failed for environmental reasons (after one retry)
+    pub env_failures: Vec<String>,
     /// Baseline failures categorized by root cause
     pub baseline_failures: crate::categorize::FailureSummary,
     /// Version conflict count (subset of baseline_failures but called out separately)
@@ -597,12 +745,13 @@ pub struct RegressionInfo {
 }
 
 /// Build a compatibility report from test results
-pub fn build_compatibility_report(rows: &[OfferedRow], base_crate: &str) -> CompatibilityReport {
+pub fn build_compatibility_report(rows: &[OfferedRow]) -> CompatibilityReport {
     use std::collections::HashSet;
 
     let mut unique_dependents = HashSet::new();
     let mut regressions = Vec::new();
     let mut fixed = Vec::new();
+    let mut env_failures = Vec::new();
     let mut baseline_failures = Vec::new();
     let mut baseline_passing = 0;
     let mut version_conflict_count = 0;
@@ -631,7 +780,7 @@ pub fn build_compatibility_report(rows: &[OfferedRow], base_crate: &str) -> Comp
                 baseline_passing += 1;
             } else if !check_passed {
                 // Check/fetch failed — truly broken
-                let failure = crate::categorize::categorize_failure(row, base_crate);
+                let failure = crate::categorize::categorize_failure(row);
                 if failure.category == crate::categorize::FailureCategory::VersionConflict {
                     version_conflict_count += 1;
                 }
@@ -644,27 +793,19 @@ pub fn build_compatibility_report(rows: &[OfferedRow], base_crate: &str) -> Comp
                 target_version = row.offered.as_ref().map(|o| o.version.clone());
             }
 
-            let overall_passed = row.test_passed();
-
-            // Use baseline_check_passed to distinguish "truly broken" from "test failing"
-            let baseline_compiles = row.baseline_check_passed.unwrap_or_else(|| row.baseline_passed.unwrap_or(false));
-
-            if !baseline_compiles {
-                // Baseline doesn't compile — not your problem, skip
-            } else if row.baseline_passed == Some(true) && !overall_passed {
-                // Baseline fully passed, this failed = REGRESSION
-                let snippet = crate::categorize::categorize_failure(row, base_crate).error_snippet;
-                regressions.push(RegressionInfo {
-                    dependent_name: row.primary.dependent_name.clone(),
-                    error_snippet: snippet,
-                });
-            } else if row.baseline_passed == Some(true) && overall_passed {
-                // Both passed — fine
-            } else if row.baseline_passed == Some(false) && overall_passed {
-                // Fixed
-                fixed.push(row.primary.dependent_name.clone());
+            match row.status() {
+                RowStatus::Regressed { .. } => {
+                    let snippet = crate::categorize::categorize_failure(row).error_snippet;
+                    regressions.push(RegressionInfo {
+                        dependent_name: row.primary.dependent_name.clone(),
+                        error_snippet: snippet,
+                    });
+                }
+                RowStatus::Fixed => fixed.push(row.primary.dependent_name.clone()),
+                RowStatus::EnvBroken { .. } => env_failures.push(row.primary.dependent_name.clone()),
+                // Passing, untested, or failing the same way baseline did
+                _ => {}
             }
-            // baseline_passed == Some(false) && !overall_passed: both failing, not a regression
         }
     }
 
@@ -676,6 +817,7 @@ pub fn build_compatibility_report(rows: &[OfferedRow], base_crate: &str) -> Comp
         target_version,
         regressions,
         fixed,
+        env_failures,
         baseline_failures: failure_summary,
         version_conflict_count,
         baseline_passing,
@@ -696,7 +838,7 @@ pub fn build_compatibility_report(rows: &[OfferedRow], base_crate: &str) -> Comp
 }
 
 /// Print the compatibility report to stdout
-pub fn print_compatibility_report(report: &CompatibilityReport, report_dir: &Path) {
+pub fn print_compatibility_report(report: &CompatibilityReport, report_dir: &Path, messages: &Catalog) {
     let bar = "=".repeat(65);
     let thin_bar = "-".repeat(65);
 
@@ -704,7 +846,7 @@ pub fn print_compatibility_report(report: &CompatibilityReport, report_dir: &Pat
         // Baseline-only report
         println!();
         println!("{}", bar);
-        println!("{:^65}", "BASELINE REPORT");
+        println!("{:^65}", messages.get("heading.baseline_report"));
         println!("{}", bar);
         println!("Tested: {} dependents    Version: {} (published)", report.total_dependents, report.baseline_version);
         println!("{}", bar);
@@ -714,12 +856,12 @@ pub fn print_compatibility_report(report: &CompatibilityReport, report_dir: &Pat
 
         if report.baseline_broken_total > 0 {
             println!();
-            println!("BROKEN BY CATEGORY:");
+            println!("{}", messages.get("heading.broken_by_category"));
             for (cat, failures) in &report.baseline_failures.categories {
                 let names: Vec<&str> = failures.iter().map(|f| f.dependent_name.as_str()).collect();
                 let display =
                     if names.len() > 5 { format!("{}  ...", names[..5].join("  ")) } else { names.join("  ") };
-                println!("  {} ({}):  {}", cat.label(), failures.len(), display);
+                println!("  {} ({}):  {}", cat.label(messages), failures.len(), display);
             }
         }
     } else {
@@ -728,7 +870,7 @@ pub fn print_compatibility_report(report: &CompatibilityReport, report_dir: &Pat
 
         println!();
         println!("{}", bar);
-        println!("{:^65}", "COMPATIBILITY REPORT");
+        println!("{:^65}", messages.get("heading.compatibility_report"));
         println!("{}", bar);
         println!(
             "Tested: {} dependents    Baseline: {}    Target: {}",
@@ -738,7 +880,7 @@ pub fn print_compatibility_report(report: &CompatibilityReport, report_dir: &Pat
 
         // YOUR CHANGES section
         println!();
-        println!("YOUR CHANGES");
+        println!("{}", messages.get("heading.your_changes"));
         println!("{}", thin_bar);
 
         let reg_count = report.regressions.len();
@@ -770,7 +912,7 @@ pub fn print_compatibility_report(report: &CompatibilityReport, report_dir: &Pat
         // List regressions with error snippets
         if !report.regressions.is_empty() {
             println!();
-            println!("REGRESSIONS (investigate these):");
+            println!("{}", messages.get("heading.regressions"));
             for reg in &report.regressions {
                 if let Some(ref snippet) = reg.error_snippet {
                     println!("  {:<20} {}", reg.dependent_name, snippet);
@@ -783,18 +925,26 @@ pub fn print_compatibility_report(report: &CompatibilityReport, report_dir: &Pat
         // List fixed crates
         if !report.fixed.is_empty() {
             println!();
-            println!("FIXED BY YOUR CHANGES:");
+            println!("{}", messages.get("heading.fixed"));
             for name in &report.fixed {
                 println!("  {}  (passed)", name);
             }
         }
 
         // NOT YOUR PROBLEM section
-        if report.baseline_broken_total > 0 || report.version_conflict_count > 0 {
+        if report.baseline_broken_total > 0 || report.version_conflict_count > 0 || !report.env_failures.is_empty() {
             println!();
-            println!("NOT YOUR PROBLEM");
+            println!("{}", messages.get("heading.not_your_problem"));
             println!("{}", thin_bar);
 
+            if !report.env_failures.is_empty() {
+                println!(
+                    "  Environment:     {:>4}    (disk/network/toolchain; retried once)  {}",
+                    report.env_failures.len(),
+                    report.env_failures.join("  ")
+                );
+            }
+
             if report.baseline_broken_total > 0 {
                 println!("  Baseline broken: {:>4}    (fail with published deps too)", report.baseline_broken_total);
             }
@@ -812,7 +962,7 @@ pub fn print_compatibility_report(report: &CompatibilityReport, report_dir: &Pat
                     let names: Vec<&str> = failures.iter().map(|f| f.dependent_name.as_str()).collect();
                     let display =
                         if names.len() > 4 { format!("{}  ...", names[..4].join("  ")) } else { names.join("  ") };
-                    println!("    {} ({}):  {}", cat.label(), failures.len(), display);
+                    println!("    {} ({}):  {}", cat.label(messages), failures.len(), display);
                 }
             }
         }
@@ -844,6 +994,7 @@ pub fn generate_comparison_table(rows: &[OfferedRow]) -> Vec<ComparisonStats> {
         passed_test: 0,
         fully_passing: 0,
         regressions: vec![],
+        skipped: 0,
     };
 
     let mut seen_baseline: HashSet<String> = HashSet::new();
@@ -852,6 +1003,10 @@ pub fn generate_comparison_table(rows: &[OfferedRow]) -> Vec<ComparisonStats> {
         if !seen_baseline.insert(dep_name.clone()) {
             continue;
         }
+        if row.status().is_skipped() {
+            baseline_stats.skipped += 1;
+            continue;
+        }
 
         baseline_stats.total_tested += 1;
 
@@ -910,6 +1065,7 @@ pub fn generate_comparison_table(rows: &[OfferedRow]) -> Vec<ComparisonStats> {
             passed_test: 0,
             fully_passing: 0,
             regressions: vec![],
+            skipped: 0,
         };
 
         let mut seen: HashSet<String> = HashSet::new();
@@ -918,6 +1074,11 @@ pub fn generate_comparison_table(rows: &[OfferedRow]) -> Vec<ComparisonStats> {
             if !seen.insert(dep_name.clone()) {
                 continue;
             }
+            // Not used, fork or no steps: nothing about this version was tested
+            if row.status().is_skipped() {
+                stats.skipped += 1;
+                continue;
+            }
 
             stats.total_tested += 1;
 
@@ -946,9 +1107,6 @@ pub fn generate_comparison_table(rows: &[OfferedRow]) -> Vec<ComparisonStats> {
                 })
                 .unwrap_or(false);
 
-            let baseline_passed_test =
-                baseline_row.map(|br| br.test.commands.iter().all(|cmd| cmd.result.passed)).unwrap_or(false);
-
             if baseline_passed_check {
                 // Only count working dependents
                 if passed_fetch {
@@ -962,8 +1120,7 @@ pub fn generate_comparison_table(rows: &[OfferedRow]) -> Vec<ComparisonStats> {
                     stats.fully_passing += 1;
                 }
 
-                // Track regressions: baseline passed but offered failed
-                if baseline_passed_test && !passed_test {
+                if row.status().is_regression() {
                     let baseline_version = baseline_row.map(|br| br.primary.resolved_version.as_str()).unwrap_or("?");
                     stats.regressions.push(format!("{} ({})", dep_name, baseline_version));
                 }
@@ -985,20 +1142,325 @@ pub fn print_comparison_table(stats_list: &[ComparisonStats]) {
 // JSON Export
 //
 
+/// Directory next to report.json holding failure messages cut by `--json-error-limit`
+pub const ERRORS_DIR: &str = "errors";
+
 /// Export test results as JSON
+///
+/// Rows are serialized one at a time straight into the file instead of building the whole
+/// report as a value first: big runs used to produce reports of hundreds of MB that had to
+/// fit in memory twice. Failure messages are cut down to `level`, and those still over
+/// `error_limit` bytes (0: no limit) are cut, with their whole text in `errors/` next to the
+/// report and named in `error_file`.
+#[allow(clippy::too_many_arguments)]
 pub fn export_json_report(
     rows: &[OfferedRow],
     output_path: &PathBuf,
     crate_name: &str,
     display_version: &str,
     total_deps: usize,
+    run: &RunMetadata,
+    level: SaveArtifacts,
+    error_limit: usize,
+    messages: &Catalog,
+) -> std::io::Result<()> {
+    let head = json_report_head(rows, crate_name, display_version, total_deps, run);
+    let spill = ErrorSpill::new(output_path.parent().unwrap_or(Path::new(".")).join(ERRORS_DIR), error_limit)?;
+    let mut file = std::io::BufWriter::new(File::create(output_path)?);
+    let rows = StreamedRows { rows, level, spill, messages };
+    serde_json::to_writer_pretty(&mut file, &StreamedReport { head, rows })?;
+    file.flush()
+}
+
+/// report.json with `test_results` last, written as it is serialized
+struct StreamedReport<'a> {
+    head: serde_json::Value,
+    rows: StreamedRows<'a>,
+}
+
+impl serde::Serialize for StreamedReport<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let head = self.head.as_object().into_iter().flatten();
+        let mut map = serializer.serialize_map(None)?;
+        for (key, value) in head {
+            map.serialize_entry(key, value)?;
+        }
+        map.serialize_entry("test_results", &self.rows)?;
+        map.end()
+    }
+}
+
+/// `test_results`, each row trimmed (`--save-artifacts`, `ErrorSpill`) and converted on its own
+struct StreamedRows<'a> {
+    rows: &'a [OfferedRow],
+    level: SaveArtifacts,
+    spill: ErrorSpill,
+    messages: &'a Catalog,
+}
+
+impl serde::Serialize for StreamedRows<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeSeq};
+        let mut seq = serializer.serialize_seq(Some(self.rows.len()))?;
+        for row in self.rows {
+            let row = self.spill.row(self.level.row(row)).map_err(S::Error::custom)?;
+            seq.serialize_element(&row_to_json(&row, self.messages))?;
+        }
+        seq.end()
+    }
+}
+
+/// Cuts failure messages longer than `limit` bytes, saving the whole text under `dir`
+struct ErrorSpill {
+    dir: PathBuf,
+    limit: usize,
+}
+
+impl ErrorSpill {
+    /// Clears the previous report's files from `dir`
+    fn new(dir: PathBuf, limit: usize) -> std::io::Result<Self> {
+        match std::fs::remove_dir_all(&dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(ErrorSpill { dir, limit }),
+        }
+    }
+
+    /// `row` with long messages cut and written to `<dependent>-<version>-<offered>-<step>.txt`
+    fn row(&self, mut row: OfferedRow) -> std::io::Result<OfferedRow> {
+        if self.limit == 0 {
+            return Ok(row);
+        }
+        let offered = row.offered.as_ref().map_or("baseline", |o| o.version.as_str());
+        let stem = format!("{}-{}-{}", row.primary.dependent_name, row.primary.dependent_version, offered);
+        let mut names = std::collections::HashSet::new();
+        for cmd in &mut row.test.commands {
+            for failure in cmd.result.failures.iter_mut().filter(|f| f.error_message.len() > self.limit) {
+                let mut name = format!("{}-{}.txt", stem, cmd.command.as_str());
+                for n in 2.. {
+                    if names.insert(name.clone()) {
+                        break;
+                    }
+                    name = format!("{}-{}-{}.txt", stem, cmd.command.as_str(), n);
+                }
+                std::fs::create_dir_all(&self.dir)?;
+                std::fs::write(self.dir.join(&name), &failure.error_message)?;
+
+                let message = &failure.error_message;
+                let mut cut = self.limit;
+                while !message.is_char_boundary(cut) {
+                    cut -= 1;
+                }
+                let file = format!("{}/{}", ERRORS_DIR, name);
+                failure.error_message =
+                    format!("{}\n[cut at {} of {} bytes; full text in {}]", &message[..cut], cut, message.len(), file);
+                failure.error_file = Some(file);
+            }
+        }
+        Ok(row)
+    }
+}
+
+/// Write `regressions.json`: only the regressed rows, each with the packages still pinning
+/// the base crate (`blocking_crates`), for bots that file issues against dependents
+pub fn export_regressions_json(
+    rows: &[OfferedRow],
+    output_path: &PathBuf,
+    crate_name: &str,
+    run: &RunMetadata,
+) -> std::io::Result<()> {
+    let report = regressions_report(rows, crate_name, run);
+    let file = File::create(output_path)?;
+    serde_json::to_writer_pretty(file, &report)?;
+
+    Ok(())
+}
+
+/// Write `junit.xml` for CI systems that render JUnit results (Jenkins, GitLab): each
+/// (dependent, offered version) row is a test case, regressions are failures with the
+/// cargo error as their body, environment failures and timeouts are errors, and rows whose
+/// baseline already fails (or that tested nothing) are skipped; failure bodies are cut down to `level`
+pub fn export_junit_report(
+    rows: &[OfferedRow],
+    output_path: &Path,
+    crate_name: &str,
+    run: &RunMetadata,
+    level: SaveArtifacts,
+    messages: &Catalog,
 ) -> std::io::Result<()> {
+    let mut out = std::io::BufWriter::new(File::create(output_path)?);
+    write_junit(&mut out, &level.rows(rows), crate_name, run, messages)?;
+    out.flush()
+}
+
+fn write_junit(
+    out: &mut impl Write,
+    rows: &[OfferedRow],
+    crate_name: &str,
+    run: &RunMetadata,
+    messages: &Catalog,
+) -> std::io::Result<()> {
+    let statuses: Vec<RowStatus> = rows.iter().map(OfferedRow::status).collect();
+    let count = |pick: fn(&RowStatus) -> bool| statuses.iter().filter(|s| pick(s)).count();
+    let failures = count(|s| s.is_regression() || matches!(s, RowStatus::Failed { .. }));
+    let errors = count(|s| matches!(s, RowStatus::EnvBroken { .. } | RowStatus::TimedOut { .. }));
+    let skipped = count(|s| s.is_skipped() || matches!(s, RowStatus::Broken { .. } | RowStatus::StillBroken { .. }));
+    let time: f64 = rows.iter().flat_map(|r| &r.test.commands).map(|c| c.result.duration).sum();
+
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        out,
+        "<testsuites name=\"cargo-copter\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\">",
+        rows.len(),
+        failures,
+        errors,
+        skipped,
+        time
+    )?;
+    writeln!(
+        out,
+        "  <testsuite name=\"{} dependents\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\" timestamp=\"{}\">",
+        xml_escape(crate_name),
+        rows.len(),
+        failures,
+        errors,
+        skipped,
+        time,
+        xml_escape(&run.generated)
+    )?;
+    for (row, status) in rows.iter().zip(&statuses) {
+        let name = match &row.offered {
+            Some(offered) => format!(
+                "{} {} with {} {}",
+                row.primary.dependent_name, row.primary.dependent_version, crate_name, offered.version
+            ),
+            None => format!("{} {} baseline", row.primary.dependent_name, row.primary.dependent_version),
+        };
+        let time: f64 = row.test.commands.iter().map(|c| c.result.duration).sum();
+        write!(
+            out,
+            "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+            xml_escape(&row.primary.dependent_name),
+            xml_escape(&name),
+            time
+        )?;
+        let body = || {
+            let failures: Vec<&str> = row
+                .test
+                .first_failure()
+                .map(|cmd| cmd.result.failures.iter().map(|f| f.error_message.as_str()).collect())
+                .unwrap_or_default();
+            xml_escape(&failures.join("\n"))
+        };
+        let step = status.step().map_or("", |s| s.as_str());
+        let label = xml_escape(&status.label(messages));
+        match status {
+            RowStatus::Passed | RowStatus::Fixed => writeln!(out, "/>")?,
+            RowStatus::Regressed { .. } | RowStatus::Failed { .. } => writeln!(
+                out,
+                ">\n      <failure message=\"{}\" type=\"{}\">{}</failure>\n    </testcase>",
+                label,
+                step,
+                body()
+            )?,
+            RowStatus::EnvBroken { .. } | RowStatus::TimedOut { .. } => writeln!(
+                out,
+                ">\n      <error message=\"{}\" type=\"{}\">{}</error>\n    </testcase>",
+                label,
+                step,
+                body()
+            )?,
+            RowStatus::Broken { .. } | RowStatus::StillBroken { .. } => {
+                writeln!(out, ">\n      <skipped message=\"baseline already fails: {}\"/>\n    </testcase>", label)?
+            }
+            RowStatus::NotUsed | RowStatus::UsesFork | RowStatus::Skipped => {
+                let reason = row.test.skipped.as_deref().map_or_else(String::new, |r| format!(": {}", xml_escape(r)));
+                writeln!(out, ">\n      <skipped message=\"{}{}\"/>\n    </testcase>", label, reason)?
+            }
+        }
+    }
+    writeln!(out, "  </testsuite>")?;
+    writeln!(out, "</testsuites>")
+}
+
+/// Text for an XML attribute or element, without the control characters XML 1.0 forbids
+/// (ANSI escapes in cargo output)
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\n' | '\r' | '\t' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn regressions_report(rows: &[OfferedRow], crate_name: &str, run: &RunMetadata) -> serde_json::Value {
+    use serde_json::json;
+
+    let regressions: Vec<serde_json::Value> = rows
+        .iter()
+        .filter(|row| row.is_regression())
+        .map(|row| {
+            let status = row.status();
+            json!({
+                "dependent": row.primary.dependent_name,
+                "dependent_version": row.primary.dependent_version,
+                "spec": row.primary.spec,
+                "offered": row.offered.as_ref().map(|o| o.version.as_str()),
+                "forced": row.offered.as_ref().is_some_and(|o| o.forced),
+                "status": status,
+                "step": status.step().map(|s| s.as_str()),
+                "blocking_crates": row.blocking_crates(),
+            })
+        })
+        .collect();
+    json!({
+        "schema_version": crate::schema::SCHEMA_VERSION,
+        "run": run,
+        "crate": crate_name,
+        "regressions": regressions,
+    })
+}
+
+/// The JSON report as a value (also the context for `--report-template`), failure messages
+/// cut down to `level`
+pub fn json_report(
+    rows: &[OfferedRow],
+    crate_name: &str,
+    display_version: &str,
+    total_deps: usize,
+    run: &RunMetadata,
+    level: SaveArtifacts,
+    messages: &Catalog,
+) -> serde_json::Value {
+    let mut report = json_report_head(rows, crate_name, display_version, total_deps, run);
+    report["test_results"] = level.rows(rows).iter().map(|row| row_to_json(row, messages)).collect();
+    report
+}
+
+/// Everything in the JSON report but `test_results`
+fn json_report_head(
+    rows: &[OfferedRow],
+    crate_name: &str,
+    display_version: &str,
+    total_deps: usize,
+    run: &RunMetadata,
+) -> serde_json::Value {
     use serde_json::json;
 
     let summary = summarize_offered_rows(rows);
     let comparison_stats = generate_comparison_table(rows);
 
-    let report = json!({
+    json!({
+        "schema_version": crate::schema::SCHEMA_VERSION,
+        "run": run,
         "crate_name": crate_name,
         "crate_version": display_version,
         "total_dependents": total_deps,
@@ -1006,31 +1468,118 @@ pub fn export_json_report(
             "passed": summary.passed,
             "regressed": summary.regressed,
             "broken": summary.broken,
+            "env_failed": summary.env_failed,
+            "skipped": summary.skipped,
             "total": summary.total,
         },
         "comparison_stats": comparison_stats,
-        "test_results": rows,
-    });
+        "timing": crate::timing::Timing::from_rows(rows, run.wall_secs, run.cargo_cpu_secs),
+        "feature_usage": crate::feature_usage::FeatureUsage::from_rows(rows),
+    })
+}
 
-    let file = File::create(output_path)?;
-    serde_json::to_writer_pretty(file, &report)?;
+/// Read a `report.json` written by this or an older copter, upgraded to the current schema
+pub fn import_json_report(path: &Path) -> Result<serde_json::Value, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let report = serde_json::from_str(&text).map_err(|e| format!("{} is not valid JSON: {}", path.display(), e))?;
+    migrate_json_report(report).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Upgrade a report value one schema version at a time until it is current
+pub fn migrate_json_report(mut report: serde_json::Value) -> Result<serde_json::Value, String> {
+    use crate::schema::SCHEMA_VERSION;
+
+    let mut version = match report.get("schema_version") {
+        None => 0,
+        Some(v) => v.as_u64().ok_or_else(|| format!("schema_version {} is not a number", v))?,
+    };
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "written with schema_version {}, newer than this copter supports ({}); upgrade cargo-copter",
+            version, SCHEMA_VERSION
+        ));
+    }
+    while version < SCHEMA_VERSION {
+        match version {
+            0 => migrate_v0_to_v1(&mut report)?,
+            _ => unreachable!("no migration from schema_version {}", version),
+        }
+        version += 1;
+    }
+    Ok(report)
+}
 
+/// v0 (unversioned): rows lack `status`/`status_label`/`severity` and `baseline_check_passed`,
+/// the summary lacks `env_failed`, and very old reports lack `total_dependents`
+fn migrate_v0_to_v1(report: &mut serde_json::Value) -> Result<(), String> {
+    let obj = report.as_object_mut().ok_or("report is not a JSON object")?;
+
+    let raw_rows = obj.get("test_results").cloned().unwrap_or_else(|| serde_json::json!([]));
+    let rows: Vec<OfferedRow> =
+        serde_json::from_value(raw_rows).map_err(|e| format!("test_results can't be migrated: {}", e))?;
+
+    let messages = Catalog::default();
+    let test_results: Vec<_> = rows.iter().map(|row| row_to_json(row, &messages)).collect();
+    obj.insert("test_results".to_string(), test_results.into());
+    if !obj.contains_key("comparison_stats") {
+        obj.insert("comparison_stats".to_string(), serde_json::to_value(generate_comparison_table(&rows)).unwrap());
+    }
+    if !obj.contains_key("total_dependents") {
+        let names: std::collections::HashSet<&str> = rows.iter().map(|r| r.primary.dependent_name.as_str()).collect();
+        obj.insert("total_dependents".to_string(), names.len().into());
+    }
+    if let Some(summary) = obj.get_mut("summary").and_then(|s| s.as_object_mut()) {
+        summary.entry("env_failed").or_insert(0.into());
+    }
+    obj.insert("schema_version".to_string(), 1.into());
     Ok(())
 }
 
+/// Serialize a row with its classified status (labeled in `messages`) alongside the raw results
+fn row_to_json(row: &OfferedRow, messages: &Catalog) -> serde_json::Value {
+    let status = row.status();
+    let mut value = serde_json::to_value(row).unwrap_or_default();
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("status".to_string(), serde_json::to_value(status).unwrap_or_default());
+        obj.insert("status_label".to_string(), status.label(messages).into());
+        obj.insert("status_word".to_string(), status.word().into());
+        obj.insert("severity".to_string(), serde_json::to_value(status.severity()).unwrap_or_default());
+        obj.insert(
+            "links".to_string(),
+            serde_json::json!({
+                "crates_io": row.primary.crates_io_url(),
+                "docs_rs": row.primary.docs_rs_url(),
+                "repository": row.primary.repository,
+            }),
+        );
+        let blocking = row.blocking_crates();
+        if !blocking.is_empty() {
+            obj.insert("blocking_crates".to_string(), serde_json::to_value(blocking).unwrap_or_default());
+        }
+    }
+    value
+}
+
 //
 // Temporary compatibility stubs for old API (TO BE REMOVED)
 //
 
 /// Generate markdown report with console table in code block
+#[allow(clippy::too_many_arguments)]
 pub fn export_markdown_table_report(
     rows: &[OfferedRow],
     output_path: &PathBuf,
+    layout: &Layout,
     crate_name: &str,
     display_version: &str,
     total_deps: usize,
     test_plan: Option<&str>,
     this_path: Option<&str>,
+    contacts: &[crate::api::CrateContact],
+    reexporters: &[crate::reexports::ReExporter],
+    adoption: &[crate::adoption::Adoption],
+    forks: &[crate::aliases::Fork],
+    run: &RunMetadata,
 ) -> std::io::Result<()> {
     let mut file = File::create(output_path)?;
     let summary = summarize_offered_rows(rows);
@@ -1038,50 +1587,540 @@ pub fn export_markdown_table_report(
     // Write markdown header
     writeln!(file, "# Cargo Copter Test Report\n")?;
     writeln!(file, "**Crate**: {} ({})", crate_name, display_version)?;
-    writeln!(file, "**Dependents Tested**: {}\n", total_deps)?;
+    match &run.population {
+        Some(population) => writeln!(file, "**Dependents Tested**: {} ({})", total_deps, population.describe())?,
+        None => writeln!(file, "**Dependents Tested**: {}", total_deps)?,
+    }
+    writeln!(file, "**Generated**: {} by cargo-copter {}", run.generated, run.copter_version)?;
+    writeln!(file, "**Command**: `{}`", run.command_line)?;
+    if let Some(label) = &run.label {
+        writeln!(file, "**Label**: {}", label)?;
+    }
+    if let Some(path) = &run.base_path {
+        writeln!(file, "**Base crate path**: `{}`", path)?;
+    }
+    writeln!(file, "**Base crate commit**: {}\n", run.git_summary())?;
 
     // Write summary
     writeln!(file, "## Summary\n")?;
-    writeln!(file, "- ✓ Passed: {}", summary.passed)?;
-    writeln!(file, "- ✗ Regressed: {}", summary.regressed)?;
-    writeln!(file, "- ⚠ Broken: {}", summary.broken)?;
+    let style = layout.status_style;
+    let marker = |symbol: &str, word: &str, label: &str| match style {
+        StatusStyle::Symbols => format!("{} {}", symbol, label),
+        StatusStyle::Words => format!("{} ({})", word, label),
+    };
+    writeln!(file, "- {}: {}", marker("✓", "PASSED", "Passed"), summary.passed)?;
+    writeln!(file, "- {}: {}", marker("✗", "REGRESSED", "Regressed"), summary.regressed)?;
+    writeln!(file, "- {}: {}", marker("⚠", "BROKEN", "Broken"), summary.broken)?;
+    if summary.env_failed > 0 {
+        writeln!(file, "- {}: {}", marker("⚙", "ENV-FAILED", "Environment failures"), summary.env_failed)?;
+    }
+    if summary.skipped > 0 {
+        let label = "Skipped (offered version not tested)";
+        writeln!(file, "- {}: {}", marker("⊘", "SKIPPED", label), summary.skipped)?;
+    }
     writeln!(file, "- **Total**: {}\n", summary.total)?;
 
-    // Write console table in code block
-    writeln!(file, "## Test Results\n")?;
-    writeln!(file, "```")?;
+    write_version_sections(&mut file, rows, crate_name, style, &layout.messages)?;
+    crate::feature_usage::FeatureUsage::from_rows(rows).write_markdown(&mut file, crate_name)?;
+    // The console table, collapsed: long runs make it a wall of text on GitHub. It is
+    // drawn with symbols, so words style leaves it out
+    if style == StatusStyle::Symbols {
+        writeln!(file, "## Console Output\n")?;
+        writeln!(file, "<details><summary>Table as printed by cargo-copter</summary>\n")?;
+        writeln!(file, "```")?;
+
+        // Write table header (with test plan to match console output exactly)
+        write!(file, "{}", format_table_header(layout, crate_name, display_version, total_deps, test_plan, this_path))?;
+
+        // Write all rows
+        for row in rows.iter() {
+            // Determine if this is the last row in its group
+            // For simplicity, assume each row is its own group (no separators in markdown)
+            let is_last_in_group = true;
+
+            // Format the row (we need a string-returning version of print_offered_row)
+            write!(file, "{}", format_offered_row_string(layout, row, is_last_in_group))?;
+        }
 
-    // Write table header (with test plan to match console output exactly)
-    write!(file, "{}", format_table_header(crate_name, display_version, total_deps, test_plan, this_path))?;
+        // Write table footer
+        write!(file, "{}", format_table_footer(layout))?;
 
-    // Write all rows
-    for row in rows.iter() {
-        // Determine if this is the last row in its group
-        // For simplicity, assume each row is its own group (no separators in markdown)
-        let is_last_in_group = true;
+        // Generate and write comparison table using TableWriter
+        let comparison_stats = generate_comparison_table(rows);
+        let mut table_writer = console_format::TableWriter::new(&mut file, false, layout.clone()); // No colors for markdown
+        table_writer.write_comparison_table(&comparison_stats)?;
 
-        // Format the row (we need a string-returning version of print_offered_row)
-        write!(file, "{}", format_offered_row_string(row, is_last_in_group))?;
+        writeln!(file, "```\n")?;
+        writeln!(file, "</details>\n")?;
     }
 
-    // Write table footer
-    write!(file, "{}", format_table_footer())?;
+    write_contact_sheet(&mut file, contacts)?;
+    write_adoption_history(&mut file, crate_name, adoption)?;
+    write_forks(&mut file, crate_name, forks)?;
+    write_canary_snippets(&mut file, rows, crate_name, WipGit::from_run(run).as_ref())?;
+    write_reexporters(&mut file, crate_name, reexporters)?;
 
-    // Generate and write comparison table using TableWriter
-    let comparison_stats = generate_comparison_table(rows);
-    let mut table_writer = console_format::TableWriter::new(&mut file, false); // No colors for markdown
-    table_writer.write_comparison_table(&comparison_stats)?;
+    Ok(())
+}
 
-    writeln!(file, "```\n")?;
+/// "Test Results": an index linking one section per offered version (and the baseline),
+/// each with a table of what changed and the unchanged dependents collapsed
+fn write_version_sections(
+    out: &mut impl Write,
+    rows: &[OfferedRow],
+    crate_name: &str,
+    style: StatusStyle,
+    messages: &Catalog,
+) -> std::io::Result<()> {
+    let mut versions: Vec<Option<&str>> = Vec::new();
+    for row in rows {
+        let version = row.offered.as_ref().map(|o| o.version.as_str());
+        if !versions.contains(&version) {
+            versions.push(version);
+        }
+    }
+    // Baseline last: it is the reference, the offered versions are the news
+    versions.sort_by_key(|v| v.is_none());
+    if versions.is_empty() {
+        return Ok(());
+    }
 
+    writeln!(out, "## Test Results\n")?;
+    let sections: Vec<(Option<&str>, Vec<&OfferedRow>)> = versions
+        .iter()
+        .map(|version| {
+            let rows = rows.iter().filter(|r| r.offered.as_ref().map(|o| o.version.as_str()) == *version).collect();
+            (*version, rows)
+        })
+        .collect();
+    for (version, rows) in &sections {
+        let title = version.map_or("Baseline".to_string(), |v| format!("{} {}", crate_name, v));
+        writeln!(out, "- [{}](#{}): {}", title, version_anchor(*version), section_counts(rows, messages))?;
+    }
+    writeln!(out)?;
+
+    for (version, rows) in &sections {
+        let title = version.map_or("Baseline (published versions)".to_string(), |v| format!("{} {}", crate_name, v));
+        writeln!(out, "<a id=\"{}\"></a>\n", version_anchor(*version))?;
+        writeln!(out, "### {}\n", title)?;
+        writeln!(out, "{}\n", section_counts(rows, messages))?;
+
+        let (unchanged, mut changed): (Vec<&OfferedRow>, Vec<&OfferedRow>) =
+            rows.iter().partition(|r| r.status() == RowStatus::Passed);
+        changed.sort_by_key(|r| match r.status() {
+            RowStatus::Regressed { .. } => 0,
+            RowStatus::Fixed => 1,
+            status if status.is_skipped() => 3,
+            _ => 2,
+        });
+        if !changed.is_empty() {
+            writeln!(out, "| Dependent | Result | Baseline | {} features | First error |", crate_name)?;
+            writeln!(out, "|-----------|--------|----------|{}|-------------|", "-".repeat(crate_name.len() + 11))?;
+            for row in &changed {
+                let (result, baseline, error) = markdown_cells(row, style, messages);
+                let features = crate::feature_usage::cell(row);
+                writeln!(
+                    out,
+                    "| {}{} | {} | {} | {} | {} |",
+                    row.primary.markdown_links(),
+                    row.test.toolchain_suffix(),
+                    result,
+                    baseline,
+                    features,
+                    error
+                )?;
+            }
+            writeln!(out)?;
+        }
+        if !unchanged.is_empty() {
+            let names: Vec<String> = unchanged
+                .iter()
+                .map(|r| format!("{} {}", r.primary.dependent_name, r.primary.dependent_version))
+                .collect();
+            writeln!(out, "<details><summary>Passed ({})</summary>\n", unchanged.len())?;
+            writeln!(out, "{}\n", names.join(", "))?;
+            writeln!(out, "</details>\n")?;
+        }
+    }
+    Ok(())
+}
+
+/// A row's Result, Baseline and First error cells in report.md's tables
+pub fn markdown_cells(row: &OfferedRow, style: StatusStyle, messages: &Catalog) -> (String, &'static str, String) {
+    let status = row.status();
+    let result = match style {
+        StatusStyle::Words => format!("{} ({})", status.word(), status.label(messages)),
+        StatusStyle::Symbols if status.is_regression() => format!("**regressed**: {}", status.label(messages)),
+        StatusStyle::Symbols => status.label(messages),
+    };
+    let baseline = match row.baseline_passed {
+        Some(true) => "passed",
+        Some(false) => "failed",
+        None => "—",
+    };
+    let error = first_error_line(row).map(|e| format!("`{}`", e.replace('|', "\\|"))).unwrap_or_default();
+    (result, baseline, error)
+}
+
+/// `offered-0-9-0`, or `baseline`
+fn version_anchor(version: Option<&str>) -> String {
+    match version {
+        None => "baseline".to_string(),
+        Some(v) => {
+            let slug: String =
+                v.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' }).collect();
+            format!("offered-{}", slug)
+        }
+    }
+}
+
+/// "2 regressed, 1 fixed, 30 passed" for one section's rows (zero counts left out)
+fn section_counts(rows: &[&OfferedRow], messages: &Catalog) -> String {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for row in rows {
+        let status = row.status();
+        let label = match status {
+            RowStatus::Regressed { .. } => "regressed".to_string(),
+            RowStatus::Fixed => "fixed".to_string(),
+            RowStatus::Passed => "passed".to_string(),
+            _ if status.is_skipped() => "skipped".to_string(),
+            _ => status.label(messages),
+        };
+        match counts.iter_mut().find(|(l, _)| *l == label) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((label, 1)),
+        }
+    }
+    counts.sort_by_key(|(label, _)| match label.as_str() {
+        "regressed" => 0,
+        "fixed" => 1,
+        "passed" => 3,
+        _ => 2,
+    });
+    let parts: Vec<String> = counts.iter().map(|(label, n)| format!("{} {}", n, label)).collect();
+    if parts.is_empty() { "no results".to_string() } else { parts.join(", ") }
+}
+
+/// Look up repository, owners and last release of every regressed dependent
+pub fn regressed_contacts(registry: &crate::registry::Registry, rows: &[OfferedRow]) -> Vec<crate::api::CrateContact> {
+    let mut names: Vec<&str> = Vec::new();
+    for row in rows.iter().filter(|r| r.status().is_regression()) {
+        if !names.contains(&row.primary.dependent_name.as_str()) {
+            names.push(&row.primary.dependent_name);
+        }
+    }
+    names
+        .into_iter()
+        .map(|name| {
+            crate::api::fetch_crate_contact(registry, name).unwrap_or_else(|e| {
+                log::debug!("no contact info for {}: {}", name, e);
+                crate::api::CrateContact { name: name.to_string(), ..Default::default() }
+            })
+        })
+        .collect()
+}
+
+/// "Who to notify" table for regressed dependents (nothing when there are none)
+fn write_contact_sheet(out: &mut impl Write, contacts: &[crate::api::CrateContact]) -> std::io::Result<()> {
+    if contacts.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "## Who to Notify\n")?;
+    writeln!(out, "| Dependent | Owners | Repository | Issue tracker | Last release |")?;
+    writeln!(out, "|-----------|--------|------------|---------------|--------------|")?;
+    for c in contacts {
+        let or_dash = |s: Option<&str>| s.map(str::to_string).unwrap_or_else(|| "—".to_string());
+        let owners = if c.owners.is_empty() { "—".to_string() } else { c.owners.join(", ") };
+        let release = c.last_release.as_ref().map(|(v, date)| format!("{} ({})", v, date));
+        writeln!(
+            out,
+            "| {} | {} | {} | {} | {} |",
+            c.name,
+            owners,
+            or_dash(c.repository.as_deref()),
+            or_dash(c.issues.as_deref()),
+            or_dash(release.as_deref())
+        )?;
+    }
+    writeln!(out)
+}
+
+/// "Forks and Renames" table: dependents of each `--alias` by relationship to the base crate
+fn write_forks(out: &mut impl Write, crate_name: &str, forks: &[crate::aliases::Fork]) -> std::io::Result<()> {
+    if forks.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "## Forks and Renames\n")?;
+    writeln!(out, "| Alias | Moved from {} | Use both | Only the alias |", crate_name)?;
+    writeln!(out, "|-------|------------|----------|----------------|")?;
+    for fork in forks {
+        writeln!(out, "| {} | {} | {} | {} |", fork.alias, fork.moved.len(), fork.both.len(), fork.fork_only.len())?;
+    }
+    writeln!(out)?;
+    for fork in forks.iter().filter(|f| !f.moved.is_empty()) {
+        let moved: Vec<String> = fork
+            .moved
+            .iter()
+            .map(|(name, version)| format!("{} (last used {} in {})", name, crate_name, version))
+            .collect();
+        writeln!(out, "Moved to {}: {}\n", fork.alias, moved.join(", "))?;
+    }
     Ok(())
 }
 
+/// "Adoption History" table: days each regressed dependent took to move to earlier release lines
+fn write_adoption_history(
+    out: &mut impl Write,
+    crate_name: &str,
+    adoption: &[crate::adoption::Adoption],
+) -> std::io::Result<()> {
+    if adoption.is_empty() {
+        return Ok(());
+    }
+    // Every dependent's lines are the newest ones it was around for, so the longest list has them all
+    let lines: Vec<&str> = adoption
+        .iter()
+        .map(|a| &a.lines)
+        .max_by_key(|l| l.len())
+        .into_iter()
+        .flatten()
+        .map(|l| l.line.as_str())
+        .collect();
+
+    writeln!(out, "## Adoption History\n")?;
+    writeln!(
+        out,
+        "Days from each {} release line to the regressed dependent's first release requiring it, slowest adopters first.\n",
+        crate_name
+    )?;
+    let header: Vec<String> = lines.iter().map(|l| format!("{} {}", crate_name, l)).collect();
+    writeln!(out, "| Dependent | {} | Median |", header.join(" | "))?;
+    writeln!(out, "|-----------|{}--------|", "------|".repeat(lines.len()))?;
+    for a in adoption {
+        let cells: Vec<String> = lines
+            .iter()
+            .map(|name| match a.lines.iter().find(|l| l.line == *name) {
+                Some(l) => match &l.adopted_in {
+                    Some(version) => format!("{} days ({})", l.days, version),
+                    None => format!("not yet ({} days)", l.days),
+                },
+                None => "—".to_string(),
+            })
+            .collect();
+        let median = a.median_days().map(|d| format!("{} days", d)).unwrap_or_else(|| "—".to_string());
+        writeln!(out, "| {} | {} | {} |", a.dependent, cells.join(" | "), median)?;
+    }
+    writeln!(out)
+}
+
+/// The commit a dependent can fetch the local WIP from: the base crate's `repository` and `HEAD`
+struct WipGit {
+    repository: String,
+    rev: String,
+    /// Uncommitted changes: `rev` doesn't contain everything that was tested
+    dirty: bool,
+}
+
+impl WipGit {
+    fn from_run(run: &RunMetadata) -> Option<Self> {
+        let dir = Path::new(run.base_path.as_deref()?);
+        let manifest: toml::Table = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?.parse().ok()?;
+        let repository = manifest.get("package")?.get("repository")?.as_str()?.to_string();
+        Some(Self { repository, rev: crate::git::head_at(dir)?, dirty: run.git_dirty.unwrap_or(false) })
+    }
+}
+
+/// How a dependent requiring `spec` can try `offered` ahead of the release: `(code block language, text)`
+///
+/// `[patch.crates-io]` only takes effect when the patched version satisfies the dependent's
+/// requirement, so a breaking version is given as a replacement dependency line instead. A
+/// published version can't patch crates.io with itself; a compatible one is a `cargo update`.
+fn canary_snippet(
+    crate_name: &str,
+    spec: &str,
+    offered: &str,
+    local: bool,
+    wip: Option<&WipGit>,
+) -> (&'static str, String) {
+    let accepts =
+        semver::Version::parse(offered).is_ok_and(|v| semver::VersionReq::parse(spec).is_ok_and(|req| req.matches(&v)));
+    if !local && accepts {
+        return ("sh", format!("cargo update -p {} --precise {}", crate_name, offered));
+    }
+    let source = match (local, wip) {
+        (false, _) => format!("{{ version = \"={}\" }}", offered),
+        (true, Some(git)) => format!("{{ git = \"{}\", rev = \"{}\" }}", git.repository, git.rev),
+        (true, None) => "{ git = \"<repository URL>\", branch = \"<branch with the change>\" }".to_string(),
+    };
+    let mut text = String::new();
+    if wip.is_some_and(|git| local && git.dirty) {
+        text.push_str("# commit and push the tested changes first; rev is the last commit\n");
+    }
+    if accepts {
+        text.push_str(&format!("[patch.crates-io]\n{} = {}", crate_name, source));
+    } else {
+        text.push_str(&format!("[dependencies]\n{} = {}  # instead of {:?}", crate_name, source, spec));
+    }
+    ("toml", text)
+}
+
+/// "Canary Testing" section: per regressed dependent, how to build against the offered version
+fn write_canary_snippets(
+    out: &mut impl Write,
+    rows: &[OfferedRow],
+    crate_name: &str,
+    wip: Option<&WipGit>,
+) -> std::io::Result<()> {
+    let mut seen: Vec<(&str, &str, &str)> = Vec::new();
+    for row in rows.iter().filter(|r| r.is_regression()) {
+        let Some(offered) = &row.offered else { continue };
+        let key =
+            (row.primary.dependent_name.as_str(), row.primary.dependent_version.as_str(), offered.version.as_str());
+        if seen.contains(&key) {
+            continue;
+        }
+        if seen.is_empty() {
+            writeln!(out, "## Canary Testing\n")?;
+            writeln!(
+                out,
+                "Snippets for the issues to regressed dependents, so they can build against the new {} before it is released.\n",
+                crate_name
+            )?;
+        }
+        seen.push(key);
+        let local = row.primary.resolved_source == VersionSource::Local;
+        let (lang, snippet) = canary_snippet(crate_name, &row.primary.spec, &offered.version, local, wip);
+        writeln!(
+            out,
+            "**{} {}** (requires `{}`, regressed with {}):\n\n```{}\n{}\n```\n",
+            key.0, key.1, row.primary.spec, offered.version, lang, snippet
+        )?;
+    }
+    Ok(())
+}
+
+/// "Re-exporters" section: dependents whose public API includes the base crate's items
+fn write_reexporters(
+    out: &mut impl Write,
+    crate_name: &str,
+    reexporters: &[crate::reexports::ReExporter],
+) -> std::io::Result<()> {
+    if reexporters.is_empty() {
+        return Ok(());
+    }
+    writeln!(
+        out,
+        "## Re-exporters
+"
+    )?;
+    writeln!(
+        out,
+        "These dependents re-export {} items publicly, so a breaking {} release is a breaking change for their users too.
+",
+        crate_name, crate_name
+    )?;
+    writeln!(out, "| Dependent | Re-export | Location |")?;
+    writeln!(out, "|-----------|-----------|----------|")?;
+    for r in reexporters {
+        for e in &r.reexports {
+            writeln!(out, "| {} {} | `{}` | {}:{} |", r.name, r.version, e.item, e.file, e.line)?;
+        }
+    }
+    writeln!(out)
+}
+
+/// Write the short markdown summary meant to be pasted as a PR comment (`check-release`)
+pub fn export_pr_comment(
+    rows: &[OfferedRow],
+    output_path: &Path,
+    crate_name: &str,
+    display_version: &str,
+    total_deps: usize,
+    run: &RunMetadata,
+    messages: &Catalog,
+) -> std::io::Result<()> {
+    std::fs::write(output_path, pr_comment(rows, crate_name, display_version, total_deps, run, messages))
+}
+
+/// Verdict, counts, a table of regressions and (collapsed) the dependents that were already broken
+fn pr_comment(
+    rows: &[OfferedRow],
+    crate_name: &str,
+    display_version: &str,
+    total_deps: usize,
+    run: &RunMetadata,
+    messages: &Catalog,
+) -> String {
+    pr_comment_body(rows, crate_name, display_version, total_deps, messages) + &pr_comment_footer(run)
+}
+
+/// `pr_comment` without its footer (also the start of `--github`'s job summary)
+fn pr_comment_body(
+    rows: &[OfferedRow],
+    crate_name: &str,
+    display_version: &str,
+    total_deps: usize,
+    messages: &Catalog,
+) -> String {
+    let summary = summarize_offered_rows(rows);
+    let mut out = format!("## cargo-copter: {} {}\n\n", crate_name, display_version);
+
+    if summary.regressed == 0 {
+        out.push_str(&format!("**No regressions** in {} dependents.\n\n", total_deps));
+    } else {
+        out.push_str(&format!("**{} regression(s)** across {} dependents.\n\n", summary.regressed, total_deps));
+    }
+    out.push_str("| Passed | Regressed | Broken | Env failures | Skipped |\n|---|---|---|---|---|\n");
+    out.push_str(&format!(
+        "| {} | {} | {} | {} | {} |\n\n",
+        summary.passed, summary.regressed, summary.broken, summary.env_failed, summary.skipped
+    ));
+
+    let regressions: Vec<&OfferedRow> = rows.iter().filter(|r| r.is_regression()).collect();
+    if !regressions.is_empty() {
+        out.push_str("### Regressions\n\n| Dependent | Version | Result | First error |\n|---|---|---|---|\n");
+        for row in regressions {
+            out.push_str(&format!(
+                "| {} {} | {} | {} | {} |\n",
+                row.primary.dependent_name,
+                row.primary.dependent_version,
+                row.offered.as_ref().map(|o| o.version.as_str()).unwrap_or("baseline"),
+                row.status().label(messages),
+                first_error_line(row).map(|e| format!("`{}`", e.replace('|', "\\|"))).unwrap_or_default()
+            ));
+        }
+        out.push('\n');
+    }
+
+    let broken: Vec<String> = rows
+        .iter()
+        .filter(|r| r.offered.is_none() && r.status().severity() == Severity::Broken)
+        .map(|r| {
+            format!("{} {} ({})", r.primary.dependent_name, r.primary.dependent_version, r.status().label(messages))
+        })
+        .collect();
+    if !broken.is_empty() {
+        out.push_str(&format!("<details><summary>Already broken before this change ({})</summary>\n\n", broken.len()));
+        for line in &broken {
+            out.push_str(&format!("- {}\n", line));
+        }
+        out.push_str("\n</details>\n\n");
+    }
+    out
+}
+
+fn pr_comment_footer(run: &RunMetadata) -> String {
+    format!(
+        "<sub>cargo-copter {} · base commit {} · full report: copter-report/report.md</sub>\n",
+        run.copter_version,
+        run.git_summary()
+    )
+}
+
 /// Format an OfferedRow as a string (similar to print_offered_row but returns String)
-fn format_offered_row_string(row: &OfferedRow, is_last_in_group: bool) -> String {
+fn format_offered_row_string(w: &Layout, row: &OfferedRow, is_last_in_group: bool) -> String {
     // Use unlimited error lines for markdown export
-    let formatted = format_offered_row(row, 0);
-    let w = console_format::get_widths();
+    let formatted = format_offered_row(row, 0, w.color_scheme, &w.messages);
 
     let mut output = String::new();
 
@@ -1090,7 +2129,7 @@ fn format_offered_row_string(row: &OfferedRow, is_last_in_group: bool) -> String
     let spec_display = console_format::truncate_from_start_with_padding(&formatted.spec, w.spec - 2);
     let resolved_display = console_format::truncate_from_start_with_padding(&formatted.resolved, w.resolved - 2);
     let dependent_display = console_format::truncate_from_start_with_padding(&formatted.dependent, w.dependent - 2);
-    let result_display = format!("{:>12} {:>5}", formatted.result, formatted.time);
+    let result_display = result_cell(w, &formatted);
     let result_display = console_format::truncate_with_padding(&result_display, w.result - 2);
 
     output.push_str(&format!(
@@ -1170,14 +2209,24 @@ fn format_offered_row_string(row: &OfferedRow, is_last_in_group: bool) -> String
     output
 }
 
-/// Write raw cargo output to a failure log file
-pub fn write_failure_log(report_dir: &Path, staging_dir: &Path, result: &TestResult) {
+/// Write raw cargo output to a failure log file, as much of it as `level` keeps
+pub fn write_failure_log(
+    report_dir: &Path,
+    staging_dir: &Path,
+    result: &TestResult,
+    scrubber: Option<&crate::scrub::Scrubber>,
+    level: SaveArtifacts,
+) {
+    if !level.logs() {
+        return;
+    }
     let dependent_name = &result.dependent.name;
     let dependent_version = result.dependent.version.display();
     let base_version = result.base_version.version.display();
 
-    // Create filename: dependent-version_base-version.txt
-    let filename = format!("{}-{}_{}.txt", dependent_name, dependent_version, base_version);
+    // Create filename: dependent-version_base-version.txt (`_+toolchain` before .txt with --toolchains)
+    let toolchain = result.execution.toolchain.as_deref().map_or_else(String::new, |t| format!("_+{}", t));
+    let filename = format!("{}-{}_{}{}.txt", dependent_name, dependent_version, base_version, toolchain);
     let log_path = report_dir.join(&filename);
 
     // Build the staging path for this dependent
@@ -1189,13 +2238,15 @@ pub fn write_failure_log(report_dir: &Path, staging_dir: &Path, result: &TestRes
         dependent_name, dependent_version, base_version
     ));
     content.push_str(&format!("# Generated: {}\n", chrono::Local::now().format("%Y-%m-%d %H:%M:%S")));
-    content.push_str(&format!(
-        "# Source: {}\n\n",
-        dependent_staging_path.canonicalize().unwrap_or(dependent_staging_path).display()
-    ));
+    content.push_str(&format!("# Source: {}\n\n", crate::paths::canonicalize(&dependent_staging_path).display()));
 
     // Helper to write diagnostics or fall back to stderr
-    fn write_step_output(content: &mut String, result: &crate::compile::CompileResult, step_name: &str) {
+    fn write_step_output(
+        content: &mut String,
+        result: &crate::compile::CompileResult,
+        step_name: &str,
+        level: SaveArtifacts,
+    ) {
         content.push_str(&format!("=== {} ===\n", step_name));
         content.push_str(&format!("Status: FAILED ({:.1}s)\n\n", result.duration.as_secs_f64()));
 
@@ -1209,8 +2260,16 @@ pub fn write_failure_log(report_dir: &Path, staging_dir: &Path, result: &TestRes
             }
         } else if !result.stderr.is_empty() {
             // Fall back to stderr if no diagnostics parsed
-            content.push_str(&result.stderr);
-            if !result.stderr.ends_with('\n') {
+            let stderr = level.message(&result.stderr, true);
+            content.push_str(&stderr);
+            if !stderr.ends_with('\n') {
+                content.push('\n');
+            }
+        }
+        if level.stdout() && !result.stdout.is_empty() {
+            content.push_str("--- stdout ---\n");
+            content.push_str(&result.stdout);
+            if !result.stdout.ends_with('\n') {
                 content.push('\n');
             }
         }
@@ -1219,21 +2278,25 @@ pub fn write_failure_log(report_dir: &Path, staging_dir: &Path, result: &TestRes
 
     // Write fetch step output if it failed
     if !result.execution.fetch.success {
-        write_step_output(&mut content, &result.execution.fetch, "FETCH (cargo fetch)");
+        write_step_output(&mut content, &result.execution.fetch, "FETCH (cargo fetch)", level);
     }
 
     // Write check step output if it failed
     if let Some(ref check) = result.execution.check
         && !check.success
     {
-        write_step_output(&mut content, check, "CHECK (cargo check)");
+        write_step_output(&mut content, check, "CHECK (cargo check)", level);
     }
 
     // Write test step output if it failed
     if let Some(ref test) = result.execution.test
         && !test.success
     {
-        write_step_output(&mut content, test, "TEST (cargo test)");
+        write_step_output(&mut content, test, "TEST (cargo test)", level);
+    }
+
+    if let Some(scrubber) = scrubber {
+        content = scrubber.text(&content);
     }
 
     // Write to file
@@ -1274,21 +2337,17 @@ pub fn print_simple_header(base_crate: &str, display_version: &str, dependents:
 }
 
 /// Collect results for a dependent and print when complete
-pub fn print_simple_dependent_result(results: &DependentResults, base_crate: &str, report_dir: &Path) {
+pub fn print_simple_dependent_result(
+    results: &DependentResults,
+    base_crate: &str,
+    flakiness: &crate::flakiness::Flakiness,
+    messages: &Catalog,
+) {
     let dep = format!("{}:{}", results.dependent_name, results.dependent_version);
 
     // Get baseline status
     let baseline_row = results.baseline.as_ref();
     let baseline_passed = baseline_row.map(|r| r.test_passed()).unwrap_or(false);
-    let baseline_check_passed = baseline_row
-        .map(|r| {
-            r.test
-                .commands
-                .iter()
-                .filter(|c| c.command == CommandType::Check || c.command == CommandType::Fetch)
-                .all(|c| c.result.passed)
-        })
-        .unwrap_or(false);
 
     // Determine baseline test status (separate from build)
     let baseline_test_passed = baseline_row.map(|r| r.test.commands.iter().all(|c| c.result.passed)).unwrap_or(false);
@@ -1302,6 +2361,9 @@ pub fn print_simple_dependent_result(results: &DependentResults, base_crate: &st
     let mut test_regressions: Vec<&OfferedRow> = Vec::new();
     let mut passed_versions: Vec<String> = Vec::new();
     let mut still_broken: Vec<String> = Vec::new();
+    let mut not_used: Vec<String> = Vec::new();
+    let mut fork: Option<&str> = None;
+    let mut env_failed: Vec<String> = Vec::new();
 
     // Track versions that needed special patching for explanations
     let mut patch_explanations: Vec<(String, crate::compile::PatchDepth)> = Vec::new();
@@ -1315,36 +2377,30 @@ pub fn print_simple_dependent_result(results: &DependentResults, base_crate: &st
         } else {
             format!("{}:{}", base_crate, version)
         };
-        let this_passed = row.test_passed();
-
-        if this_passed {
-            passed_versions.push(version_display.clone());
-            // Track if patching was needed for explanation
-            if patch_depth == crate::compile::PatchDepth::Patch || patch_depth == crate::compile::PatchDepth::DeepPatch
-            {
-                patch_explanations.push((version.to_string(), patch_depth));
-            }
-        } else {
-            let failed_step = failed_step_name(row);
-
-            if failed_step == "build" || failed_step == "fetch" {
-                // BUILD/FETCH failure - this is a regression if baseline build passed
-                // Even if baseline tests failed, a check failure is a regression
-                if baseline_check_passed {
-                    build_regressions.push((row, failed_step));
-                } else {
-                    // Only "still broken" if baseline check also failed
-                    still_broken.push(version_display);
-                }
-            } else {
-                // TEST failure - only a regression if baseline tests passed
-                // If baseline tests also failed, just don't report (not broken, not regression)
-                if baseline_test_passed {
-                    test_regressions.push(row);
+        match row.status() {
+            RowStatus::Passed | RowStatus::Fixed => {
+                passed_versions.push(version_display);
+                // Track if patching was needed for explanation
+                if patch_depth == crate::compile::PatchDepth::Patch
+                    || patch_depth == crate::compile::PatchDepth::DeepPatch
+                {
+                    patch_explanations.push((version.to_string(), patch_depth));
                 }
-                // Note: if baseline tests failed and new version tests also failed,
-                // we don't report it as "still broken" - that term is reserved for check failures
             }
+            // Test regressions are reported separately (less critical than build regressions)
+            RowStatus::Regressed { step: CommandType::Test } => test_regressions.push(row),
+            // Even if baseline tests failed, a fetch/check failure is a regression
+            RowStatus::Regressed { step } => build_regressions.push((row, step.verb())),
+            RowStatus::NotUsed => not_used.push(version_display),
+            RowStatus::UsesFork => fork = row.primary.fork.as_deref(),
+            RowStatus::EnvBroken { .. } => {
+                let reason = row.test.first_failure().and_then(|c| c.result.env_failure.as_deref()).unwrap_or("?");
+                env_failed.push(format!("{} ({})", version_display, reason));
+            }
+            // Baseline tests also failed: not broken, not a regression ("broken" is reserved for check failures)
+            RowStatus::StillBroken { step: CommandType::Test } => {}
+            status if status.severity() == Severity::Broken => still_broken.push(version_display),
+            _ => {}
         }
     }
 
@@ -1364,13 +2420,22 @@ pub fn print_simple_dependent_result(results: &DependentResults, base_crate: &st
             };
 
             println!(
-                "REGRESSION: {} with {}:{}{} - {} failed ({})",
-                dep, base_crate, version, depth_marker, step, baseline_note
+                "{}: {} with {}:{}{} - {} failed ({})",
+                messages.get("simple.regression"),
+                dep,
+                base_crate,
+                version,
+                depth_marker,
+                step,
+                baseline_note
             );
             // Print first error line
             if let Some(error) = first_error_line(row) {
                 println!("  {}", error);
             }
+            if let Some(note) = flakiness.note(row) {
+                println!("  {}", note);
+            }
             // Explain what patching was attempted for [!!] cases
             if patch_depth == crate::compile::PatchDepth::Patch {
                 println!(
@@ -1393,13 +2458,21 @@ pub fn print_simple_dependent_result(results: &DependentResults, base_crate: &st
 
             let baseline_info = format!("{}:{} ({})", base_crate, baseline_version, baseline_spec);
             println!(
-                "REGRESSION: {} with {}:{}{} - tests failed (baseline {} passed)",
-                dep, base_crate, version, depth_marker, baseline_info
+                "{}: {} with {}:{}{} - tests failed (baseline {} passed)",
+                messages.get("simple.regression"),
+                dep,
+                base_crate,
+                version,
+                depth_marker,
+                baseline_info
             );
             // Print first error line
             if let Some(error) = first_error_line(row) {
                 println!("  {}", error);
             }
+            if let Some(note) = flakiness.note(row) {
+                println!("  {}", note);
+            }
             // Explain what patching was attempted for [!!] cases
             if patch_depth == crate::compile::PatchDepth::Patch {
                 println!(
@@ -1414,7 +2487,7 @@ pub fn print_simple_dependent_result(results: &DependentResults, base_crate: &st
 
     // Report passed versions
     if !passed_versions.is_empty() {
-        println!("OK: {} - passed with {}", dep, passed_versions.join(", "));
+        println!("{}: {} - passed with {}", messages.get("simple.ok"), dep, passed_versions.join(", "));
         // Explain what patching was needed for [!!] and [!!!] cases
         for (version, depth) in &patch_explanations {
             match depth {
@@ -1435,16 +2508,48 @@ pub fn print_simple_dependent_result(results: &DependentResults, base_crate: &st
         }
     }
 
+    if !env_failed.is_empty() {
+        println!(
+            "{}: {} with {} - not counted, failed again after retry",
+            messages.get("simple.env_failure"),
+            dep,
+            env_failed.join(", ")
+        );
+    }
+
+    if let Some(fork) = fork {
+        println!(
+            "{}: {} - builds against its own copy ({}), not affected by your release",
+            messages.get("simple.uses_fork"),
+            dep,
+            fork
+        );
+    }
+
+    if !not_used.is_empty() {
+        println!(
+            "{}: {} - cargo resolved a different version than {}",
+            messages.get("simple.not_used"),
+            dep,
+            not_used.join(", ")
+        );
+    }
+
     // Report still broken (baseline check failed, new version check also failed)
     if !still_broken.is_empty() && build_regressions.is_empty() && test_regressions.is_empty() {
         // Only mention if no regressions to avoid noise
-        println!("BROKEN: {} with {} (baseline check also failed)", dep, still_broken.join(", "));
+        println!(
+            "{}: {} with {} (baseline check also failed)",
+            messages.get("simple.broken"),
+            dep,
+            still_broken.join(", ")
+        );
     }
 
     // If baseline failed and no offered versions
     if results.offered_versions.is_empty() && !baseline_passed {
         let step = baseline_row.map(failed_step_name).unwrap_or("unknown");
-        println!("BASELINE FAILED: {} ({} failed)", dep, step);
+        println!("{}: {} ({} failed)", messages.get("simple.baseline_failed"), dep, step);
     }
 }
 
@@ -1464,6 +2569,9 @@ fn failed_step_name(row: &OfferedRow) -> &'static str {
 
 /// Get the first error line from a failed row (for --simple output)
 fn first_error_line(row: &OfferedRow) -> Option<String> {
+    if let Some(reason) = &row.test.skipped {
+        return Some(format!("skipped: {}", reason));
+    }
     for cmd in &row.test.commands {
         if !cmd.result.passed {
             for failure in &cmd.result.failures {
@@ -1568,14 +2676,24 @@ fn print_blocking_crates_advice(row: &OfferedRow, base_crate: &str, base_version
     }
 }
 
-/// Write combined log file with all failures
-pub fn write_combined_log(report_dir: &Path, rows: &[OfferedRow], base_crate: &str) -> PathBuf {
+/// Write combined log file with all failures, cut down to `level`
+pub fn write_combined_log(
+    report_dir: &Path,
+    rows: &[OfferedRow],
+    base_crate: &str,
+    run: &RunMetadata,
+    level: SaveArtifacts,
+) -> PathBuf {
     let log_path = report_dir.join("failures.log");
+    let rows = &level.rows(rows);
 
     let mut content = String::new();
     content.push_str("# Cargo Copter - Combined Failure Log\n");
-    content.push_str(&format!("# Generated: {}\n", chrono::Local::now().format("%Y-%m-%d %H:%M:%S")));
-    content.push_str(&format!("# Base crate: {}\n\n", base_crate));
+    content.push_str(&format!("# Base crate: {}\n", base_crate));
+    for (label, value) in run.fields() {
+        content.push_str(&format!("# {}: {}\n", label, value));
+    }
+    content.push('\n');
 
     let mut failure_count = 0;
 
@@ -1621,13 +2739,20 @@ pub fn write_combined_log(report_dir: &Path, rows: &[OfferedRow], base_crate: &s
 }
 
 /// Print simple summary at end
-pub fn print_simple_summary(rows: &[OfferedRow], report_dir: &Path, base_crate: &str, combined_log_path: &Path) {
+pub fn print_simple_summary(
+    rows: &[OfferedRow],
+    report_dir: &Path,
+    base_crate: &str,
+    combined_log_path: &Path,
+    messages: &Catalog,
+) {
     use std::collections::{HashMap, HashSet};
 
     // Group results by version
     // Key: (version_string, forced), Value: (regressed_deps, worked_deps)
     let mut by_version: HashMap<(String, bool), (Vec<String>, Vec<String>)> = HashMap::new();
     let mut broken_already: Vec<String> = Vec::new();
+    let mut env_failed: Vec<String> = Vec::new();
 
     // First pass: identify baseline failures (broken already)
     let mut baseline_failed_deps: HashSet<String> = HashSet::new();
@@ -1668,38 +2793,37 @@ pub fn print_simple_summary(rows: &[OfferedRow], report_dir: &Path, base_crate:
 
         let entry = by_version.entry((version, forced)).or_insert_with(|| (Vec::new(), Vec::new()));
 
-        if row.test_passed() {
-            entry.1.push(dep); // worked
-        } else {
-            // Check if this is a regression
-            let is_regression = if matches!(row.baseline_passed, Some(true)) {
-                true // Traditional regression: baseline fully passed
-            } else if baseline_check_passed_deps.contains(&row.primary.dependent_name) {
-                // Step-level regression: baseline check passed but offered check/fetch failed
-                let failed_step = failed_step_name(row);
-                failed_step == "fetch" || failed_step == "check"
-            } else {
-                false // Not a regression, baseline was already broken at this step
-            };
-
-            if is_regression {
-                entry.0.push(dep); // regressed
-            }
-            // If not a regression, it's already counted in broken_already
+        let status = row.status();
+        if let RowStatus::EnvBroken { .. } = status {
+            env_failed.push(format!("{} with {}:{}", dep, base_crate, row.offered.as_ref().unwrap().version));
+            continue;
+        }
+        match status.severity() {
+            Severity::Ok => entry.1.push(dep),         // worked
+            Severity::Regression => entry.0.push(dep), // regressed
+            // Broken rows are already counted in broken_already
+            Severity::Notice | Severity::Broken => {}
         }
     }
 
     // Print summary
     println!();
     println!("========================================");
-    println!("SUMMARY");
+    println!("{}", messages.get("heading.summary"));
     println!("========================================");
 
     // Print regressions by version
     for ((version, forced), (regressed, _)) in &by_version {
         if !regressed.is_empty() {
             let forced_marker = if *forced { "[forced]" } else { "" };
-            println!("REGRESSED with {}:{}{}: {}", base_crate, version, forced_marker, regressed.join(", "));
+            println!(
+                "{} with {}:{}{}: {}",
+                messages.get("simple.regressed"),
+                base_crate,
+                version,
+                forced_marker,
+                regressed.join(", ")
+            );
         }
     }
 
@@ -1707,7 +2831,14 @@ pub fn print_simple_summary(rows: &[OfferedRow], report_dir: &Path, base_crate:
     for ((version, forced), (_, worked)) in &by_version {
         if !worked.is_empty() {
             let forced_marker = if *forced { "[forced]" } else { "" };
-            println!("WORKED with {}:{}{}: {}", base_crate, version, forced_marker, worked.join(", "));
+            println!(
+                "{} with {}:{}{}: {}",
+                messages.get("simple.worked"),
+                base_crate,
+                version,
+                forced_marker,
+                worked.join(", ")
+            );
         }
     }
 
@@ -1724,7 +2855,7 @@ pub fn print_simple_summary(rows: &[OfferedRow], report_dir: &Path, base_crate:
             .collect();
 
         let categorized: Vec<crate::categorize::CategorizedFailure> =
-            baseline_failed_rows.iter().map(|r| crate::categorize::categorize_failure(r, base_crate)).collect();
+            baseline_failed_rows.iter().map(|r| crate::categorize::categorize_failure(r)).collect();
 
         let summary = crate::categorize::FailureSummary::from_failures(categorized);
 
@@ -1732,16 +2863,16 @@ pub fn print_simple_summary(rows: &[OfferedRow], report_dir: &Path, base_crate:
             || summary.categories.iter().any(|(cat, _)| *cat != crate::categorize::FailureCategory::Other)
         {
             // Show categorized breakdown
-            println!("BROKEN ALREADY ({}):", broken_already.len());
+            println!("{} ({}):", messages.get("simple.broken_already"), broken_already.len());
             for (cat, failures) in &summary.categories {
                 let names: Vec<&str> = failures.iter().map(|f| f.dependent_name.as_str()).collect();
                 let display =
                     if names.len() > 5 { format!("{}  ...", names[..5].join(", ")) } else { names.join(", ") };
-                println!("  {} ({}): {}", cat.label(), failures.len(), display);
+                println!("  {} ({}): {}", cat.label(messages), failures.len(), display);
             }
         } else {
             // Simple flat list when all failures are "Other"
-            println!("BROKEN ALREADY: {}", broken_already.join(", "));
+            println!("{}: {}", messages.get("simple.broken_already"), broken_already.join(", "));
         }
     }
 
@@ -1753,6 +2884,9 @@ pub fn print_simple_summary(rows: &[OfferedRow], report_dir: &Path, base_crate:
     println!("Regressed: {}", total_regressed);
     println!("Worked:    {}", total_worked);
     println!("Broken:    {}", broken_already.len());
+    if !env_failed.is_empty() {
+        println!("Env fail:  {} (disk/network/toolchain, retried once): {}", env_failed.len(), env_failed.join(", "));
+    }
 
     // Always show report paths
     println!();
@@ -1760,4 +2894,400 @@ pub fn print_simple_summary(rows: &[OfferedRow], report_dir: &Path, base_crate:
     println!("  Combined log: {}", combined_log_path.display());
     println!("  Markdown:     {}/report.md", report_dir.display());
     println!("  JSON:         {}/report.json", report_dir.display());
+    println!("  JUnit:        {}/junit.xml", report_dir.display());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+
+    fn failing_row(dependent: &str, offered: Option<&str>, error: &str) -> OfferedRow {
+        crate::test_rows::row(dependent).offered(offered).resolved("0.8.0").error(error).build()
+    }
+
+    #[test]
+    fn test_error_signature_diff() {
+        let baseline = "error[E0432]: unresolved import `rgb::alt`\nerror[E0599]: no method `as_slice`";
+        let offered = "error[E0308]: mismatched types\nerror[E0599]: no method `as_slice`";
+        assert_eq!(
+            error_signature_diff(baseline, offered).unwrap(),
+            vec![
+                "errors changed vs baseline:",
+                "  - error[E0432]: unresolved import `rgb::alt`",
+                "  + error[E0308]: mismatched types",
+                "  (1 unchanged)",
+            ]
+        );
+        assert_eq!(error_signature_diff(baseline, baseline), None);
+        assert_eq!(error_signature_diff("", offered), None);
+    }
+
+    #[test]
+    fn test_baseline_diff_for_dependent_failing_differently() {
+        let mut clusters = ErrorClusters::default();
+        clusters.observe(&failing_row("a", None, "error[E0432]: unresolved import `x`"));
+        let mut offered = failing_row("a", Some("0.9.0"), "error[E0308]: mismatched types");
+        offered.baseline_passed = Some(false);
+        let diff = clusters.baseline_diff(&offered).unwrap();
+        assert!(diff.contains(&"  + error[E0308]: mismatched types".to_string()));
+        assert!(clusters.baseline_diff(&failing_row("b", Some("0.9.0"), "error[E0308]: x")).is_none());
+    }
+
+    #[test]
+    fn test_collapse_identical_regressions() {
+        let e0308 = "error[E0308]: mismatched types\n --> src/lib.rs:3:5";
+        let e0599 = "error[E0599]: no method named `as_rgb`";
+        let rows = [
+            failing_row("a", None, ""),
+            failing_row("a", Some("0.9.0"), e0308),
+            failing_row("b", None, ""),
+            failing_row("b", Some("0.9.0"), e0308),
+            failing_row("c", None, ""),
+            failing_row("c", Some("0.9.0"), e0599),
+            failing_row("d", None, ""),
+            failing_row("d", Some("0.9.0"), e0308),
+            failing_row("d", Some("0.8.9"), ""),
+        ];
+        let rows: Vec<&OfferedRow> = rows.iter().collect();
+        let shown: Vec<(String, Option<String>, Option<String>)> = collapse_identical(&rows)
+            .into_iter()
+            .map(|(row, note)| {
+                (row.primary.dependent_name.clone(), row.offered.as_ref().map(|o| o.version.clone()), note)
+            })
+            .collect();
+        let row = |name: &str, offered: Option<&str>, note: Option<&str>| {
+            (name.to_string(), offered.map(str::to_string), note.map(str::to_string))
+        };
+        assert_eq!(
+            shown,
+            [
+                row("a", None, None),
+                row("a", Some("0.9.0"), Some("and 2 more: b 1.0.0, d 1.0.0")),
+                row("c", None, None),
+                row("c", Some("0.9.0"), None),
+                // d still has a row of its own
+                row("d", None, None),
+                row("d", Some("0.8.9"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error_clusters_count_repeats_across_rows() {
+        let e0308 = "error[E0308]: mismatched types\n --> src/lib.rs:3:5";
+        let e0308_elsewhere = "error[E0308]: mismatched types\n --> src/main.rs:9:1";
+        let mut clusters = ErrorClusters::default();
+
+        assert_eq!(clusters.observe(&failing_row("a", Some("0.9.0"), e0308)), ErrorOccurrence::First);
+        assert_eq!(
+            clusters.observe(&failing_row("b", Some("0.9.0"), e0308_elsewhere)),
+            ErrorOccurrence::Repeat { first: "a 1.0.0 with 0.9.0".to_string(), count: 2, adjacent: true }
+        );
+        assert_eq!(clusters.observe(&failing_row("c", Some("0.9.0"), "")), ErrorOccurrence::NoError);
+        assert_eq!(
+            clusters.observe(&failing_row("d", Some("0.9.0"), e0308)),
+            ErrorOccurrence::Repeat { first: "a 1.0.0 with 0.9.0".to_string(), count: 3, adjacent: false }
+        );
+        // Errors without codes cluster on their full text
+        assert_eq!(clusters.observe(&failing_row("e", None, "failed to select a version")), ErrorOccurrence::First);
+        assert_eq!(clusters.observe(&failing_row("f", None, "failed to select another")), ErrorOccurrence::First);
+    }
+
+    #[test]
+    fn test_json_report_matches_schema() {
+        let rows = vec![failing_row("a", None, ""), failing_row("a", Some("0.9.0"), "error[E0308]: mismatched types")];
+        let report =
+            json_report(&rows, "rgb", "0.9.0", 1, &RunMetadata::default(), SaveArtifacts::Stderr, &Catalog::default());
+        assert_eq!(crate::schema::validate(&report), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_json_export_cuts_long_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let long = format!("error[E0308]: mismatched types\n{}", "é".repeat(20));
+        let rows = vec![failing_row("a", None, "error: short"), failing_row("a", Some("0.9.0"), &long)];
+        let path = dir.path().join("report.json");
+        export_json_report(
+            &rows,
+            &path,
+            "rgb",
+            "0.9.0",
+            1,
+            &RunMetadata::default(),
+            SaveArtifacts::Stderr,
+            40,
+            &Catalog::default(),
+        )
+        .unwrap();
+
+        let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(crate::schema::validate(&report), Vec::<String>::new());
+        let failure = |i: usize| report["test_results"][i]["test"]["commands"][0]["result"]["failures"][0].clone();
+        assert_eq!(failure(0)["error_message"], "error: short");
+        assert_eq!(failure(0).get("error_file"), None);
+        assert_eq!(failure(1)["error_file"], "errors/a-1.0.0-0.9.0-check.txt");
+        assert_eq!(
+            failure(1)["error_message"],
+            "error[E0308]: mismatched types\néééé\n[cut at 39 of 71 bytes; full text in errors/a-1.0.0-0.9.0-check.txt]"
+        );
+        assert_eq!(std::fs::read_to_string(dir.path().join("errors/a-1.0.0-0.9.0-check.txt")).unwrap(), long);
+        assert_eq!(
+            report["summary"],
+            json_report(&rows, "rgb", "0.9.0", 1, &RunMetadata::default(), SaveArtifacts::Stderr, &Catalog::default())
+                ["summary"]
+        );
+    }
+
+    #[test]
+    fn test_junit_cases_per_row() {
+        let mut broken = failing_row("png", Some("0.9.0"), "error: still broken");
+        broken.baseline_passed = Some(false);
+        broken.baseline_check_passed = Some(false);
+        let rows = vec![
+            failing_row("image", None, ""),
+            failing_row("image", Some("0.9.0"), "error[E0308]: mismatched <u8> & \x1b[0mtypes"),
+            failing_row("png", None, "error: still broken"),
+            broken,
+        ];
+        let mut out = Vec::new();
+        write_junit(&mut out, &rows, "rgb", &RunMetadata::default(), &Catalog::default()).unwrap();
+        let xml = String::from_utf8(out).unwrap();
+        assert!(xml.contains("tests=\"4\" failures=\"1\" errors=\"0\" skipped=\"2\" time=\"4.000\""));
+        assert!(xml.contains("<testcase classname=\"image\" name=\"image 1.0.0 baseline\" time=\"1.000\"/>"));
+        assert!(xml.contains(
+            "<testcase classname=\"image\" name=\"image 1.0.0 with rgb 0.9.0\" time=\"1.000\">\n      \
+             <failure message=\"build failed\" type=\"check\">error[E0308]: mismatched &lt;u8&gt; &amp; [0mtypes</failure>"
+        ));
+        assert_eq!(xml.matches("<skipped message=\"baseline already fails: ").count(), 2);
+        assert!(xml.ends_with("  </testsuite>\n</testsuites>\n"));
+    }
+
+    #[test]
+    fn test_markdown_section_per_offered_version() {
+        let mut regressed = failing_row("image", Some("0.9.0"), "error[E0308]: mismatched | types");
+        regressed.primary.base_features = Some(vec!["default".to_string(), "serde".to_string()]);
+        let rows = vec![
+            failing_row("image", None, ""),
+            regressed,
+            failing_row("png", None, ""),
+            failing_row("png", Some("0.9.0"), ""),
+            failing_row("png", Some("0.10.0-rc.1"), ""),
+        ];
+        let mut out = Vec::new();
+        write_version_sections(&mut out, &rows, "rgb", StatusStyle::Symbols, &Catalog::default()).unwrap();
+        let md = String::from_utf8(out).unwrap();
+        assert!(md.starts_with(
+            "## Test Results\n\n\
+             - [rgb 0.9.0](#offered-0-9-0): 1 regressed, 1 passed\n\
+             - [rgb 0.10.0-rc.1](#offered-0-10-0-rc-1): 1 passed\n\
+             - [Baseline](#baseline): 2 passed\n"
+        ));
+        assert!(md.contains("<a id=\"offered-0-9-0\"></a>\n\n### rgb 0.9.0\n"));
+        assert!(md.contains("| Dependent | Result | Baseline | rgb features | First error |\n"));
+        assert!(md.contains(
+            "| [image 1.0.0](https://crates.io/crates/image/1.0.0) ([docs](https://docs.rs/image/1.0.0)) \
+             | **regressed**: build failed | passed | default, serde | `error[E0308]: mismatched \\| types` |\n"
+        ));
+        assert!(md.contains("<details><summary>Passed (1)</summary>\n\npng 1.0.0\n"));
+
+        let mut out = Vec::new();
+        write_version_sections(&mut out, &rows, "rgb", StatusStyle::Words, &Catalog::default()).unwrap();
+        let md = String::from_utf8(out).unwrap();
+        assert!(md.contains("(https://docs.rs/image/1.0.0)) | REGRESSED (build failed) | passed |"));
+        assert!(!md.contains(['✓', '✗', '⚠', '⊘']));
+        assert_eq!(row_to_json(&rows[1], &Catalog::default())["status_word"], "REGRESSED");
+        assert_eq!(row_to_json(&rows[1], &Catalog::default())["links"]["docs_rs"], "https://docs.rs/image/1.0.0");
+    }
+
+    #[test]
+    fn test_skipped_rows_counted_apart() {
+        let mut not_used = failing_row("png", Some("0.9.0"), "");
+        not_used.primary.used_offered_version = false;
+        let rows = vec![
+            failing_row("image", None, ""),
+            failing_row("png", None, ""),
+            failing_row("image", Some("0.9.0"), ""),
+            not_used,
+        ];
+        let summary = summarize_offered_rows(&rows);
+        assert_eq!((summary.passed, summary.skipped, summary.total), (1, 1, 2));
+        let stats = generate_comparison_table(&rows);
+        assert_eq!((stats[1].total_tested, stats[1].fully_passing, stats[1].skipped), (1, 1, 1));
+    }
+
+    #[test]
+    fn test_regressions_report_lists_blocking_crates() {
+        let mut deep = failing_row("image", Some("0.9.0"), "error[E0308]: two different versions of crate `rgb`");
+        let offered = deep.offered.as_mut().unwrap();
+        offered.forced = true;
+        offered.patch_depth = crate::compile::PatchDepth::DeepPatch;
+        deep.transitive = vec![TransitiveTest {
+            dependency: DependencyRef {
+                dependent_name: "ravif".to_string(),
+                dependent_version: "0.11.0".to_string(),
+                spec: "=0.8.50".to_string(),
+                resolved_version: "0.8.50".to_string(),
+                resolved_source: VersionSource::CratesIo,
+                used_offered_version: false,
+                fork: None,
+                repository: None,
+                git: None,
+                base_features: None,
+                resolver: None,
+            },
+            depth: 1,
+            path: vec!["image".to_string(), "ravif".to_string()],
+        }];
+        let rows = vec![failing_row("image", None, ""), deep, failing_row("png", Some("0.9.0"), "")];
+
+        let report = regressions_report(&rows, "rgb", &RunMetadata::default());
+        let regressions = report["regressions"].as_array().unwrap();
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0]["dependent"], "image");
+        assert_eq!(regressions[0]["step"], "check");
+        assert_eq!(
+            regressions[0]["blocking_crates"],
+            serde_json::json!([{
+                "crate": "ravif", "version": "0.11.0", "spec": "=0.8.50", "resolved": "0.8.50", "path": ["image", "ravif"]
+            }])
+        );
+        let full =
+            json_report(&rows, "rgb", "0.9.0", 2, &RunMetadata::default(), SaveArtifacts::Stderr, &Catalog::default());
+        assert_eq!(full["test_results"][1]["blocking_crates"], regressions[0]["blocking_crates"]);
+        assert_eq!(crate::schema::validate(&full), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_migrate_unversioned_report() {
+        let rows = vec![failing_row("a", None, ""), failing_row("a", Some("0.9.0"), "error[E0308]: mismatched types")];
+        let mut old =
+            json_report(&rows, "rgb", "0.9.0", 1, &RunMetadata::default(), SaveArtifacts::Stderr, &Catalog::default());
+        let obj = old.as_object_mut().unwrap();
+        obj.remove("schema_version");
+        obj.remove("total_dependents");
+        obj["summary"].as_object_mut().unwrap().remove("env_failed");
+        for row in obj["test_results"].as_array_mut().unwrap() {
+            let row = row.as_object_mut().unwrap();
+            for field in ["status", "status_label", "severity", "baseline_check_passed"] {
+                row.remove(field);
+            }
+        }
+        assert!(!crate::schema::validate(&old).is_empty());
+
+        let migrated = migrate_json_report(old).unwrap();
+        assert_eq!(crate::schema::validate(&migrated), Vec::<String>::new());
+        assert_eq!(migrated["test_results"][1]["status"]["kind"], "regressed");
+        assert_eq!(migrated["total_dependents"], 1);
+
+        let current =
+            json_report(&rows, "rgb", "0.9.0", 1, &RunMetadata::default(), SaveArtifacts::Stderr, &Catalog::default());
+        assert_eq!(migrate_json_report(current.clone()).unwrap(), current);
+        assert!(migrate_json_report(serde_json::json!({"schema_version": 99})).unwrap_err().contains("newer"));
+    }
+
+    #[test]
+    fn test_adoption_history_markdown() {
+        use crate::adoption::{Adoption, LineAdoption};
+        let line = |line: &str, days, adopted_in: Option<&str>| LineAdoption {
+            line: line.to_string(),
+            days,
+            adopted_in: adopted_in.map(str::to_string),
+        };
+        let adoption = vec![
+            Adoption { dependent: "ravif".to_string(), lines: vec![line("0.9", 400, None)] },
+            Adoption {
+                dependent: "image".to_string(),
+                lines: vec![line("0.8", 12, Some("0.24.0")), line("0.9", 30, Some("0.25.0"))],
+            },
+        ];
+        let mut out = Vec::new();
+        write_adoption_history(&mut out, "rgb", &adoption).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("| Dependent | rgb 0.8 | rgb 0.9 | Median |"));
+        assert!(text.contains("| ravif | — | not yet (400 days) | 400 days |"));
+        assert!(text.contains("| image | 12 days (0.24.0) | 30 days (0.25.0) | 30 days |"));
+    }
+
+    #[test]
+    fn test_canary_snippets() {
+        let wip = WipGit {
+            repository: "https://github.com/kornelski/rust-rgb".to_string(),
+            rev: "abc123".to_string(),
+            dirty: false,
+        };
+        assert_eq!(
+            canary_snippet("rgb", "^0.8.52", "0.8.91", true, Some(&wip)),
+            (
+                "toml",
+                "[patch.crates-io]\nrgb = { git = \"https://github.com/kornelski/rust-rgb\", rev = \"abc123\" }"
+                    .to_string()
+            )
+        );
+        // A breaking version isn't applied through [patch]
+        assert_eq!(
+            canary_snippet("rgb", "^0.8.52", "0.9.0-beta.1", false, None),
+            ("toml", "[dependencies]\nrgb = { version = \"=0.9.0-beta.1\" }  # instead of \"^0.8.52\"".to_string())
+        );
+        assert_eq!(
+            canary_snippet("rgb", "0.8", "0.8.91", false, None),
+            ("sh", "cargo update -p rgb --precise 0.8.91".to_string())
+        );
+    }
+
+    #[test]
+    fn test_contact_sheet_markdown() {
+        let mut out = Vec::new();
+        write_contact_sheet(&mut out, &[]).unwrap();
+        assert!(out.is_empty());
+
+        let contacts = vec![
+            crate::api::CrateContact {
+                name: "image".to_string(),
+                repository: Some("https://github.com/image-rs/image".to_string()),
+                issues: Some("https://github.com/image-rs/image/issues".to_string()),
+                owners: vec!["HeroicKatora".to_string()],
+                last_release: Some(("0.25.5".to_string(), "2025-01-10".to_string())),
+            },
+            crate::api::CrateContact { name: "ravif".to_string(), ..Default::default() },
+        ];
+        write_contact_sheet(&mut out, &contacts).unwrap();
+
+        let mut reexports = Vec::new();
+        let reexporter = crate::reexports::ReExporter {
+            name: "image".to_string(),
+            version: "0.25.5".to_string(),
+            reexports: vec![crate::reexports::ReExport {
+                file: "src/lib.rs".to_string(),
+                line: 12,
+                item: "rgb::RGB8".to_string(),
+            }],
+        };
+        write_reexporters(&mut reexports, "rgb", &[reexporter]).unwrap();
+        assert!(String::from_utf8(reexports).unwrap().contains("| image 0.25.5 | `rgb::RGB8` | src/lib.rs:12 |"));
+        let md = String::from_utf8(out).unwrap();
+        assert!(md.starts_with("## Who to Notify\n"));
+        assert!(md.contains(
+            "| image | HeroicKatora | https://github.com/image-rs/image | https://github.com/image-rs/image/issues | 0.25.5 (2025-01-10) |"
+        ));
+        assert!(md.contains("| ravif | — | — | — | — |"));
+    }
+
+    #[test]
+    fn test_pr_comment() {
+        let rows = vec![
+            failing_row("image", None, ""),
+            failing_row("image", Some("0.9.0"), "error[E0308]: mismatched types | here"),
+            failing_row("ravif", None, "error: linking with `cc` failed"),
+        ];
+        let comment = pr_comment(&rows, "rgb", "0.9.0", 2, &RunMetadata::default(), &Catalog::default());
+        assert!(comment.starts_with("## cargo-copter: rgb 0.9.0\n\n**1 regression(s)** across 2 dependents."));
+        assert!(comment.contains("| image 1.0.0 | 0.9.0 | build failed | `error[E0308]: mismatched types \\| here` |"));
+        assert!(comment.contains("<summary>Already broken before this change (1)</summary>"));
+        assert!(comment.contains("- ravif 1.0.0 (build broken)"));
+
+        let clean = pr_comment(&rows[..1], "rgb", "0.9.0", 1, &RunMetadata::default(), &Catalog::default());
+        assert!(clean.contains("**No regressions** in 1 dependents."));
+        assert!(!clean.contains("### Regressions"));
+    }
 }
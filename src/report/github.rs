@@ -0,0 +1,164 @@
+/// `--github`: workflow annotations and the job summary for GitHub Actions
+///
+/// Inside a workflow, lines like `::error title=…::message` on stdout become annotations
+/// on the run (and on the pull request's checks), and markdown appended to the file named
+/// by `$GITHUB_STEP_SUMMARY` is shown on the run's summary page. With `--github` each
+/// regression becomes an error annotation and each offered version that couldn't be
+/// judged (environment failure, time limit) a warning, and the summary gets the verdict,
+/// the version comparison as a markdown table and the regressions, so nobody has to open
+/// the report artifact to see what broke.
+use super::{first_error_line, generate_comparison_table, pr_comment_body, pr_comment_footer};
+use crate::console_format::ComparisonStats;
+use crate::messages::Catalog;
+use crate::run_metadata::RunMetadata;
+use crate::types::{OfferedRow, RowStatus};
+use crate::ui::Console;
+use std::io::Write;
+
+/// Print the annotations and append the summary to `$GITHUB_STEP_SUMMARY` (when set)
+///
+/// The runner reads workflow commands from stderr too, so with `--output jsonl` the
+/// annotations go there and stdout keeps only the JSON lines.
+pub fn emit(
+    console: Console,
+    rows: &[OfferedRow],
+    crate_name: &str,
+    display_version: &str,
+    total_deps: usize,
+    run: &RunMetadata,
+    messages: &Catalog,
+) -> Result<(), String> {
+    for line in annotations(rows, crate_name, messages) {
+        console.say(&line);
+    }
+    let Some(path) = std::env::var_os("GITHUB_STEP_SUMMARY") else {
+        return Err("GITHUB_STEP_SUMMARY is not set; no job summary written".to_string());
+    };
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.to_string_lossy(), e))?;
+    file.write_all(step_summary(rows, crate_name, display_version, total_deps, run, messages).as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path.to_string_lossy(), e))
+}
+
+/// `::error` for each regression, `::warning` for offered versions that couldn't be judged
+fn annotations(rows: &[OfferedRow], crate_name: &str, messages: &Catalog) -> Vec<String> {
+    let mut lines = Vec::new();
+    for row in rows {
+        let Some(offered) = &row.offered else { continue };
+        let status = row.status();
+        let (level, verb) = match status {
+            _ if status.is_regression() => ("error", "regressed"),
+            RowStatus::EnvBroken { .. } | RowStatus::TimedOut { .. } => ("warning", "could not be tested"),
+            _ => continue,
+        };
+        let title = format!(
+            "{} {} {} with {} {}",
+            row.primary.dependent_name, row.primary.dependent_version, verb, crate_name, offered.version
+        );
+        let mut message = status.label(messages);
+        if let Some(reason) = row.test.first_failure().and_then(|cmd| cmd.result.env_failure.as_deref()) {
+            message.push_str(&format!(" ({})", reason));
+        }
+        if let Some(error) = first_error_line(row) {
+            message.push('\n');
+            message.push_str(&error);
+        }
+        lines.push(format!("::{} title={}::{}", level, escape_property(&title), escape_data(&message)));
+    }
+    lines
+}
+
+/// The job summary: verdict and counts, the version comparison, then the regressions
+fn step_summary(
+    rows: &[OfferedRow],
+    crate_name: &str,
+    display_version: &str,
+    total_deps: usize,
+    run: &RunMetadata,
+    messages: &Catalog,
+) -> String {
+    let mut out = pr_comment_body(rows, crate_name, display_version, total_deps, messages);
+    out.push_str(&comparison_table(&generate_comparison_table(rows)));
+    out.push_str(&pr_comment_footer(run));
+    out
+}
+
+/// The console's "Version Comparison" table in markdown, one column per version
+fn comparison_table(stats: &[ComparisonStats]) -> String {
+    if stats.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from("### Version comparison\n\n|");
+    for s in stats {
+        out.push_str(&format!(" | {}", s.version_label));
+    }
+    out.push_str(" |\n|---");
+    out.push_str(&"|---:".repeat(stats.len()));
+    out.push_str("|\n");
+    let mut row = |label: &str, cell: &dyn Fn(&ComparisonStats) -> String| {
+        out.push_str(&format!("| {}", label));
+        for s in stats {
+            out.push_str(&format!(" | {}", cell(s)));
+        }
+        out.push_str(" |\n");
+    };
+    row("Total tested", &|s| s.total_tested.to_string());
+    if stats.iter().any(|s| s.skipped > 0) {
+        row("Skipped", &|s| s.skipped.to_string());
+    }
+    row("Already broken", &|s| s.already_broken.map_or("-".to_string(), |n| n.to_string()));
+    row("Passed fetch", &|s| s.passed_fetch.to_string());
+    row("Passed check", &|s| s.passed_check.to_string());
+    row("Passed test", &|s| s.passed_test.to_string());
+    row("Fully passing", &|s| s.fully_passing.to_string());
+    row("Regressed", &|s| s.regressions.len().to_string());
+    out.push('\n');
+    out
+}
+
+/// A workflow command's message: `%`, CR and LF are encoded
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// A workflow command's property value: as `escape_data`, plus `:` and `,`
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(dependent: &str, offered: Option<&str>, error: &str, env_failure: Option<&'static str>) -> OfferedRow {
+        crate::test_rows::row(dependent).offered(offered).error(error).env_failure(env_failure).build()
+    }
+
+    #[test]
+    fn test_annotations_and_summary() {
+        let rows = vec![
+            row("image", None, "", None),
+            row("image", Some("0.9.0"), "error[E0308]: mismatched types: 100%", None),
+            row("png", None, "", None),
+            row("png", Some("0.9.0"), "error: failed to download", Some("network")),
+            row("ravif", Some("0.9.0"), "", None),
+        ];
+        assert_eq!(
+            annotations(&rows, "rgb", &Catalog::default()),
+            [
+                "::error title=image 1.0.0 regressed with rgb 0.9.0::build failed%0Aerror[E0308]: mismatched types: 100%25",
+                "::warning title=png 1.0.0 could not be tested with rgb 0.9.0::env failure (network)%0Aerror: failed to download",
+            ]
+        );
+
+        let summary = step_summary(&rows, "rgb", "0.9.0", 3, &RunMetadata::default(), &Catalog::default());
+        assert!(summary.starts_with("## cargo-copter: rgb 0.9.0\n\n**1 regression(s)** across 3 dependents."));
+        assert!(summary.contains("### Version comparison\n\n| | Default | 0.9.0 |\n|---|---:|---:|\n"));
+        assert!(summary.contains("| Regressed | 0 | 1 |\n"));
+        assert!(summary.contains("### Regressions\n"));
+        assert_eq!(escape_property("a, b: c"), "a%2C b%3A c");
+    }
+}
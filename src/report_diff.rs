@@ -0,0 +1,230 @@
+/// `diff OLD NEW`: what changed between two runs, crater style
+///
+/// Tracking a release across candidates means comparing the report.json of one run with
+/// the next. Rows are matched by dependent (name and version) and offered version; when
+/// the two runs offered different versions (0.9.0-rc.1, then 0.9.0-rc.2) each dependent's
+/// last offered row is compared instead. A dependent whose version moved between the runs
+/// is still matched by name when it is the only one of that name on both sides. The diff
+/// lists the dependents that newly regressed, the ones that got fixed, and the ones still
+/// failing with a different error signature (`report::error_signature`: error codes and
+/// messages, without locations), and exits non-zero when anything newly regressed.
+use crate::messages::Catalog;
+use crate::registry::Registry;
+use crate::types::{OfferedRow, Severity};
+use std::collections::{BTreeMap, HashSet};
+
+/// One dependent's result in both reports
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// "image 0.25.8" (or "image 0.25.8 → 0.25.9" when the dependent moved)
+    pub dependent: String,
+    pub old: String,
+    pub new: String,
+    /// The offered version of the new row
+    pub offered: String,
+    /// Errors of the new row, or both rows' when the signature changed
+    pub detail: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReportDiff {
+    pub regressed: Vec<Change>,
+    pub fixed: Vec<Change>,
+    pub signature_changed: Vec<Change>,
+    /// Matched dependents whose outcome didn't change
+    pub unchanged: usize,
+    /// ... of which still regressed
+    pub still_regressed: usize,
+    pub only_old: Vec<String>,
+    pub only_new: Vec<String>,
+}
+
+/// (name, version, offered version or None for "last offered")
+type Key = (String, String, Option<String>);
+
+/// Each dependent's offered rows, keyed for matching against the other report
+fn keyed<'a>(rows: &'a [OfferedRow], shared: &HashSet<&str>) -> BTreeMap<Key, &'a OfferedRow> {
+    let mut keyed = BTreeMap::new();
+    for row in rows {
+        let Some(offered) = &row.offered else { continue };
+        let version = shared.contains(offered.version.as_str()).then(|| offered.version.clone());
+        // Later rows win, so "last offered" is the newest version tested
+        keyed.insert((row.primary.dependent_name.clone(), row.primary.dependent_version.clone(), version), row);
+    }
+    keyed
+}
+
+fn offered_versions(rows: &[OfferedRow]) -> HashSet<&str> {
+    rows.iter().filter_map(|row| row.offered.as_ref().map(|o| o.version.as_str())).collect()
+}
+
+/// The error signature of a failing row, if it has error codes
+fn signature(row: &OfferedRow) -> Option<String> {
+    crate::report::extract_error_text(row).filter(|signature| !signature.is_empty())
+}
+
+impl ReportDiff {
+    /// Diff of `old` and `new`, statuses labeled in `messages`
+    pub fn new(old: &[OfferedRow], new: &[OfferedRow], messages: &Catalog) -> Self {
+        let shared: HashSet<&str> = offered_versions(old).intersection(&offered_versions(new)).copied().collect();
+        let mut old = keyed(old, &shared);
+        let mut new = keyed(new, &shared);
+
+        let mut pairs = Vec::new();
+        old.retain(|key, old_row| match new.remove(key) {
+            Some(new_row) => {
+                pairs.push((format!("{} {}", key.0, key.1), *old_row, new_row));
+                false
+            }
+            None => true,
+        });
+        // A dependent that released in between: match by name when it's the only one on both sides
+        let unique = |map: &BTreeMap<Key, &OfferedRow>, key: &Key| {
+            map.keys().filter(|k| k.0 == key.0 && k.2 == key.2).count() == 1
+        };
+        let moved: Vec<(Key, Key)> = old
+            .keys()
+            .filter(|key| unique(&old, key))
+            .filter_map(|key| new.keys().find(|k| k.0 == key.0 && k.2 == key.2).map(|k| (key.clone(), k.clone())))
+            .filter(|(_, new_key)| unique(&new, new_key))
+            .collect();
+        for (old_key, new_key) in moved {
+            let (Some(old_row), Some(new_row)) = (old.remove(&old_key), new.remove(&new_key)) else { continue };
+            pairs.push((format!("{} {} → {}", old_key.0, old_key.1, new_key.1), old_row, new_row));
+        }
+
+        let mut diff = ReportDiff {
+            only_old: old.keys().map(|k| format!("{} {}", k.0, k.1)).collect(),
+            only_new: new.keys().map(|k| format!("{} {}", k.0, k.1)).collect(),
+            ..Default::default()
+        };
+        pairs.sort_by(|a, b| a.0.cmp(&b.0));
+        for (dependent, old_row, new_row) in pairs {
+            diff.add(dependent, old_row, new_row, messages);
+        }
+        diff
+    }
+
+    fn add(&mut self, dependent: String, old_row: &OfferedRow, new_row: &OfferedRow, messages: &Catalog) {
+        let (old_status, new_status) = (old_row.status(), new_row.status());
+        let change = |detail: Vec<String>| Change {
+            dependent: dependent.clone(),
+            old: old_status.label(messages),
+            new: new_status.label(messages),
+            offered: new_row.offered.as_ref().map_or_else(String::new, |o| o.version.clone()),
+            detail,
+        };
+        let errors =
+            |row: &OfferedRow| signature(row).map(|s| s.lines().map(str::to_string).collect()).unwrap_or_default();
+        if new_status.is_regression() && !old_status.is_regression() {
+            self.regressed.push(change(errors(new_row)));
+        } else if new_status.severity() == Severity::Ok && old_status.severity() != Severity::Ok {
+            self.fixed.push(change(vec![]));
+        } else if let (Some(old_signature), Some(new_signature)) = (signature(old_row), signature(new_row))
+            && old_status.is_failure()
+            && new_status.is_failure()
+            && old_signature != new_signature
+        {
+            let mut detail: Vec<String> = old_signature.lines().map(|l| format!("- {}", l)).collect();
+            detail.extend(new_signature.lines().map(|l| format!("+ {}", l)));
+            self.signature_changed.push(change(detail));
+        } else {
+            self.unchanged += 1;
+            self.still_regressed += usize::from(new_status.is_regression());
+        }
+    }
+
+    /// The diff as printed by `diff`
+    pub fn format(&self, old_label: &str, new_label: &str) -> String {
+        let mut out = format!("Comparing {} → {}\n", old_label, new_label);
+        for (title, changes) in [
+            ("Newly regressed", &self.regressed),
+            ("Fixed", &self.fixed),
+            ("Failure signature changed", &self.signature_changed),
+        ] {
+            if changes.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n{} ({}):\n", title, changes.len()));
+            for change in changes {
+                out.push_str(&format!(
+                    "  {}: {} → {} (with {})\n",
+                    change.dependent, change.old, change.new, change.offered
+                ));
+                for line in &change.detail {
+                    out.push_str(&format!("      {}\n", line));
+                }
+            }
+        }
+        out.push_str(&format!("\nUnchanged: {}", self.unchanged));
+        if self.still_regressed > 0 {
+            out.push_str(&format!(" ({} still regressed)", self.still_regressed));
+        }
+        if !self.only_old.is_empty() {
+            out.push_str(&format!("; only in {}: {}", old_label, self.only_old.join(", ")));
+        }
+        if !self.only_new.is_empty() {
+            out.push_str(&format!("; only in {}: {}", new_label, self.only_new.join(", ")));
+        }
+        out.push('\n');
+        out
+    }
+}
+
+/// `diff OLD NEW`: print the diff; an error when something newly regressed
+pub fn run(registry: &Registry, old: &str, new: &str, messages: &Catalog) -> Result<(), String> {
+    let load = |source| crate::compare::load_reference(registry, source);
+    let diff = ReportDiff::new(&load(old)?, &load(new)?, messages);
+    print!("{}", diff.format(old, new));
+    match diff.regressed.len() {
+        0 => Ok(()),
+        n => Err(format!("{} dependent(s) newly regressed in {}", n, new)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(dependent: &str, version: &str, offered: &str, error: &str) -> OfferedRow {
+        crate::test_rows::row(dependent).version(version).offered(offered).error(error).build()
+    }
+
+    #[test]
+    fn test_regressed_fixed_and_changed_signature() {
+        let e0308 = "error[E0308]: mismatched types\n --> src/lib.rs:3:5";
+        let e0599 = "error[E0599]: no method named `as_rgb`\n --> src/lib.rs:9:1";
+        let old = vec![
+            row("image", "0.25.8", "0.9.0-rc.1", ""),
+            row("png", "0.17.1", "0.9.0-rc.1", e0308),
+            row("ravif", "0.11.0", "0.9.0-rc.1", e0308),
+            row("gif", "0.13.0", "0.9.0-rc.1", ""),
+            row("resize", "0.8.0", "0.9.0-rc.1", ""),
+        ];
+        let new = vec![
+            row("image", "0.25.8", "0.9.0-rc.2", e0308),
+            row("png", "0.17.1", "0.9.0-rc.2", ""),
+            row("ravif", "0.11.0", "0.9.0-rc.2", e0599),
+            row("gif", "0.13.1", "0.9.0-rc.2", ""),
+            row("lodepng", "3.10.0", "0.9.0-rc.2", ""),
+        ];
+        let diff = ReportDiff::new(&old, &new, &Catalog::default());
+        assert_eq!(diff.regressed.len(), 1);
+        assert_eq!(diff.regressed[0].dependent, "image 0.25.8");
+        assert_eq!(diff.regressed[0].detail, ["error[E0308]: mismatched types"]);
+        assert_eq!(diff.fixed[0].dependent, "png 0.17.1");
+        assert_eq!(
+            diff.signature_changed[0].detail,
+            ["- error[E0308]: mismatched types", "+ error[E0599]: no method named `as_rgb`"]
+        );
+        assert_eq!((diff.unchanged, diff.still_regressed), (1, 0));
+        assert_eq!(
+            (diff.only_old.as_slice(), diff.only_new.as_slice()),
+            (&["resize 0.8.0".to_string()][..], &["lodepng 3.10.0".to_string()][..])
+        );
+
+        let text = diff.format("rc1.json", "rc2.json");
+        assert!(text.contains("\nNewly regressed (1):\n  image 0.25.8: passed → build failed (with 0.9.0-rc.2)\n"));
+        assert!(text.ends_with("Unchanged: 1; only in rc1.json: resize 0.8.0; only in rc2.json: lodepng 3.10.0\n"));
+    }
+}
@@ -0,0 +1,380 @@
+/// Standalone reproduction scripts for failed rows
+///
+/// For every failure, `{dependent}-{version}_{base-version}.repro.sh` and `.repro.ps1`
+/// are written next to the failure log. They redo exactly what copter did: download
+/// (or copy) the dependent, fetch the base crate version under test, apply the
+/// manifest changes as a patch, and run the same cargo commands. A downstream
+/// maintainer can reproduce the failure with only cargo, curl and tar.
+use crate::compile::CompileResult;
+use crate::paths;
+use crate::registry::Registry;
+use crate::scrub::Scrubber;
+use crate::types::{CrateSource, TestResult, VersionedCrate};
+use std::path::{Path, PathBuf};
+
+/// Stand-in for the base crate's directory inside args and diffs, replaced per shell
+const BASE_PATH_TOKEN: &str = "\u{1}BASE_PATH\u{1}";
+
+/// How a crate gets into the scratch directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+    /// `.crate` download that unpacks to `dir`
+    Download { url: String, dir: String },
+    /// Local directory copied (dependents) or used in place (base crate)
+    Local { path: String, dir: String },
+}
+
+impl Source {
+    fn for_crate(registry: &Registry, krate: &VersionedCrate) -> Option<Self> {
+        let dir = format!("{}-{}", krate.name, krate.version.display());
+        match &krate.source {
+            CrateSource::Registry => Some(Source::Download {
+                url: crate::download::crate_url(
+                    registry,
+                    &krate.name,
+                    Some(&format!("{}/download", krate.version.display())),
+                ),
+                dir,
+            }),
+            CrateSource::Local { path } => {
+                let path = if path.ends_with("Cargo.toml") { path.parent().unwrap_or(path) } else { path };
+                Some(Source::Local { path: paths::to_manifest_string(&paths::canonicalize(path)), dir })
+            }
+            CrateSource::Git { .. } => None,
+        }
+    }
+
+    fn dir(&self) -> &str {
+        match self {
+            Source::Download { dir, .. } | Source::Local { dir, .. } => dir,
+        }
+    }
+}
+
+/// Everything a script needs to replay one failed row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReproPlan {
+    title: String,
+    dependent: Source,
+    /// Base crate version under test, when it was patched in (not for baseline rows)
+    base: Option<Source>,
+    /// Manifest changes, with the base crate path as `BASE_PATH_TOKEN`
+    manifest_diff: Option<String>,
+    /// Cargo argument lists, in order, ending with the failing step
+    commands: Vec<Vec<String>>,
+}
+
+impl ReproPlan {
+    /// Plan for a failed result, downloading from `registry`; `None` if it passed or a
+    /// source can't be scripted
+    pub fn from_result(registry: &Registry, result: &TestResult, staging_dir: &Path) -> Option<Self> {
+        let exec = &result.execution;
+        let steps: Vec<&CompileResult> =
+            [Some(&exec.fetch), exec.check.as_ref(), exec.test.as_ref()].into_iter().flatten().collect();
+        let failed = steps.iter().position(|s| s.failed())?;
+        let steps = &steps[..=failed];
+
+        let dependent = Source::for_crate(registry, &result.dependent)?;
+        let base = Source::for_crate(registry, &result.base_version)?;
+
+        // Where copter put the base crate; registry versions are unpacked into staging
+        let base_path = match &base {
+            Source::Local { path, .. } => path.clone(),
+            Source::Download { dir, .. } => {
+                let staged = staging_dir.join(dir);
+                let staged = if staged.is_absolute() {
+                    staged
+                } else {
+                    std::env::current_dir().map(|cwd| cwd.join(&staged)).unwrap_or(staged)
+                };
+                paths::to_manifest_string(&staged)
+            }
+        };
+        let tokenize = |s: &str| s.replace(&base_path, BASE_PATH_TOKEN);
+
+        let manifest_diff = steps[failed].manifest_diff.as_deref().map(tokenize);
+        let commands: Vec<Vec<String>> = steps
+            .iter()
+            .map(|s| {
+                let args: Vec<String> =
+                    s.args.iter().filter(|a| *a != "--message-format=json").map(|a| tokenize(a)).collect();
+                if args.is_empty() { vec![s.step.cargo_subcommand().to_string()] } else { args }
+            })
+            .collect();
+        let uses_base = manifest_diff.iter().chain(commands.iter().flatten()).any(|s| s.contains(BASE_PATH_TOKEN));
+
+        Some(ReproPlan {
+            title: format!(
+                "{} with {} {} ({} failed)",
+                result.dependent.display(),
+                result.base_version.name,
+                result.base_version.version.display(),
+                steps[failed].step.as_str()
+            ),
+            dependent,
+            base: uses_base.then_some(base),
+            manifest_diff,
+            commands,
+        })
+    }
+
+    /// Bash script
+    pub fn to_sh(&self) -> String {
+        let mut s = String::from("#!/usr/bin/env bash\n");
+        s.push_str(&format!("# Reproduce: {}\n", self.title));
+        s.push_str("# Generated by cargo-copter. Needs cargo, curl and tar");
+        s.push_str(if self.manifest_diff.is_some() { " (and patch).\n" } else { ".\n" });
+        s.push_str("set -euo pipefail\n\nWORK=\"${WORK:-$(mktemp -d)}\"\ncd \"$WORK\"\necho \"Working in $WORK\"\n\n");
+
+        s.push_str("# Dependent\n");
+        match &self.dependent {
+            Source::Download { url, .. } => s.push_str(&format!("curl -sSfL {} | tar xzf -\n", sh_word(url))),
+            Source::Local { path, dir } => {
+                s.push_str(&format!("cp -R {} {}\n", sh_word(path), sh_word(dir)));
+            }
+        }
+        if let Some(base) = &self.base {
+            s.push_str("\n# Base crate version under test\n");
+            match base {
+                Source::Download { url, dir } => {
+                    s.push_str(&format!("curl -sSfL {} | tar xzf -\n", sh_word(url)));
+                    s.push_str(&format!("BASE_PATH=\"$WORK/{}\"\n", dq_escape(dir)));
+                }
+                Source::Local { path, .. } => {
+                    s.push_str("# Local work-in-progress copy: set BASE_PATH to your checkout of it\n");
+                    s.push_str(&format!("BASE_PATH=\"${{BASE_PATH:-{}}}\"\n", dq_escape(path)));
+                }
+            }
+        }
+
+        s.push_str(&format!("\ncd {}\nrm -f Cargo.lock\n", sh_word(self.dependent.dir())));
+        if let Some(diff) = &self.manifest_diff {
+            s.push_str("\n# Manifest changes cargo-copter applied\npatch -p1 <<COPTER_PATCH\n");
+            s.push_str(&heredoc_escape(diff).replace(BASE_PATH_TOKEN, "${BASE_PATH}"));
+            s.push_str("COPTER_PATCH\n");
+        }
+
+        s.push('\n');
+        for args in &self.commands {
+            let words: Vec<String> = args.iter().map(|a| sh_word(a)).collect();
+            s.push_str(&format!("cargo {}\n", words.join(" ")));
+        }
+        s
+    }
+
+    /// PowerShell script
+    pub fn to_ps1(&self) -> String {
+        let mut s = format!("# Reproduce: {}\n", self.title);
+        s.push_str("# Generated by cargo-copter. Needs cargo and tar");
+        s.push_str(if self.manifest_diff.is_some() { " (and git).\n" } else { ".\n" });
+        s.push_str("$ErrorActionPreference = 'Stop'\n\n");
+        s.push_str("$Work = if ($env:WORK) { $env:WORK } else { Join-Path ([IO.Path]::GetTempPath()) \"copter-repro-$([guid]::NewGuid())\" }\n");
+        s.push_str("New-Item -ItemType Directory -Force -Path $Work | Out-Null\nSet-Location $Work\nWrite-Host \"Working in $Work\"\n\n");
+
+        s.push_str("# Dependent\n");
+        match &self.dependent {
+            Source::Download { url, .. } => s.push_str(&ps_download(url)),
+            Source::Local { path, dir } => {
+                s.push_str(&format!("Copy-Item -Recurse -Path {} -Destination {}\n", ps_word(path), ps_word(dir)));
+            }
+        }
+        if let Some(base) = &self.base {
+            s.push_str("\n# Base crate version under test\n");
+            match base {
+                Source::Download { url, dir } => {
+                    s.push_str(&ps_download(url));
+                    s.push_str(&format!("$BasePath = (Join-Path $Work {}) -replace '\\\\', '/'\n", ps_word(dir)));
+                }
+                Source::Local { path, .. } => {
+                    s.push_str("# Local work-in-progress copy: set BASE_PATH to your checkout of it\n");
+                    s.push_str(&format!(
+                        "$BasePath = if ($env:BASE_PATH) {{ $env:BASE_PATH }} else {{ {} }}\n",
+                        ps_word(path)
+                    ));
+                }
+            }
+        }
+
+        s.push_str(&format!(
+            "\nSet-Location {}\nRemove-Item -ErrorAction SilentlyContinue Cargo.lock\n",
+            ps_word(self.dependent.dir())
+        ));
+        if let Some(diff) = &self.manifest_diff {
+            s.push_str("\n# Manifest changes cargo-copter applied\n$patch = @\"\n");
+            s.push_str(&ps_here_escape(diff).replace(BASE_PATH_TOKEN, "$BasePath"));
+            s.push_str("\"@\nSet-Content -Path copter.patch -Value $patch\ngit apply copter.patch\n");
+        }
+
+        s.push('\n');
+        for args in &self.commands {
+            let words: Vec<String> = args.iter().map(|a| ps_word(a)).collect();
+            s.push_str(&format!("& cargo {}\nif ($LASTEXITCODE -ne 0) {{ exit $LASTEXITCODE }}\n", words.join(" ")));
+        }
+        s
+    }
+}
+
+/// Write both scripts for a failed result; returns the paths written
+pub fn write_scripts(
+    registry: &Registry,
+    report_dir: &Path,
+    staging_dir: &Path,
+    result: &TestResult,
+    scrubber: Option<&Scrubber>,
+) -> Result<Vec<PathBuf>, String> {
+    let Some(plan) = ReproPlan::from_result(registry, result, staging_dir) else {
+        return Ok(vec![]);
+    };
+    let stem = format!(
+        "{}-{}_{}",
+        result.dependent.name,
+        result.dependent.version.display(),
+        result.base_version.version.display()
+    );
+    let mut written = Vec::new();
+    for (ext, content) in [("repro.sh", plan.to_sh()), ("repro.ps1", plan.to_ps1())] {
+        let path = report_dir.join(format!("{}.{}", stem, ext));
+        let content = match scrubber {
+            Some(scrubber) => scrubber.text(&content),
+            None => content,
+        };
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        #[cfg(unix)]
+        if ext == "repro.sh" {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755));
+        }
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Characters special inside bash double quotes and unquoted heredocs
+fn dq_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('$', "\\$").replace('`', "\\`")
+}
+
+fn heredoc_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('$', "\\$").replace('`', "\\`")
+}
+
+/// One bash word: bare if safe, single-quoted otherwise, double-quoted around `$BASE_PATH`
+fn sh_word(arg: &str) -> String {
+    if arg.contains(BASE_PATH_TOKEN) {
+        let parts: Vec<String> = arg.split(BASE_PATH_TOKEN).map(dq_escape).collect();
+        format!("\"{}\"", parts.join("${BASE_PATH}"))
+    } else if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@".contains(c)) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+fn ps_here_escape(s: &str) -> String {
+    s.replace('`', "``").replace('$', "`$")
+}
+
+/// One PowerShell argument: single-quoted, or double-quoted around `$BasePath`
+fn ps_word(arg: &str) -> String {
+    if arg.contains(BASE_PATH_TOKEN) {
+        let parts: Vec<String> = arg.split(BASE_PATH_TOKEN).map(|p| ps_here_escape(p).replace('"', "`\"")).collect();
+        format!("\"{}\"", parts.join("$BasePath"))
+    } else {
+        format!("'{}'", arg.replace('\'', "''"))
+    }
+}
+
+fn ps_download(url: &str) -> String {
+    format!(
+        "Invoke-WebRequest -Uri {} -OutFile copter-download.crate\ntar xzf copter-download.crate\nRemove-Item copter-download.crate\n",
+        ps_word(url)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{CompileStep, PatchDepth, ThreeStepResult};
+    use std::time::Duration;
+
+    fn step(step: CompileStep, success: bool, args: &[&str], manifest_diff: Option<&str>) -> CompileResult {
+        CompileResult {
+            step,
+            success,
+            stdout: String::new(),
+            stderr: String::new(),
+            duration: Duration::from_secs(1),
+            diagnostics: vec![],
+            args: args.iter().map(|a| a.to_string()).collect(),
+            manifest_diff: manifest_diff.map(String::from),
+            timed_out: false,
+        }
+    }
+
+    fn result(check_passed: bool) -> TestResult {
+        let diff = "--- a/Cargo.toml\n+++ b/Cargo.toml\n@@ -1,1 +1,1 @@\n-rgb = \"0.8\"\n+rgb = { path = \"/staging/rgb-0.9.0\" }\n";
+        TestResult {
+            base_version: VersionedCrate::from_registry("rgb", "0.9.0"),
+            dependent: VersionedCrate::from_registry("image", "0.25.0"),
+            execution: ThreeStepResult {
+                fetch: step(CompileStep::Fetch, true, &["fetch"], Some(diff)),
+                check: Some(step(
+                    CompileStep::Check,
+                    check_passed,
+                    &["check", "--message-format=json", "--config", "patch.crates-io.rgb.path=\"/staging/rgb-0.9.0\""],
+                    Some(diff),
+                )),
+                test: None,
+                actual_version: None,
+                expected_version: Some("0.9.0".to_string()),
+                forced_version: true,
+                original_requirement: Some("^0.8".to_string()),
+                all_crate_versions: vec![],
+                patch_depth: PatchDepth::Force,
+                started: None,
+                fork: None,
+                blocking: vec![],
+                repository: None,
+                base_features: None,
+                resolver: None,
+                toolchain: None,
+                skipped: None,
+            },
+            baseline: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_only_for_failures() {
+        assert!(ReproPlan::from_result(&Default::default(), &result(true), Path::new("/staging")).is_none());
+        let plan = ReproPlan::from_result(&Default::default(), &result(false), Path::new("/staging")).unwrap();
+        assert_eq!(plan.title, "image 0.25.0 with rgb 0.9.0 (check failed)");
+        assert_eq!(plan.commands.len(), 2);
+        assert!(plan.base.is_some());
+    }
+
+    #[test]
+    fn test_scripts_replay_patch_and_commands() {
+        let plan = ReproPlan::from_result(&Default::default(), &result(false), Path::new("/staging")).unwrap();
+
+        let sh = plan.to_sh();
+        assert!(sh.contains("crates/image/0.25.0/download"));
+        assert!(sh.contains("BASE_PATH=\"$WORK/rgb-0.9.0\""));
+        assert!(sh.contains("+rgb = { path = \"${BASE_PATH}\" }"), "{}", sh);
+        assert!(sh.contains("cargo check --config \"patch.crates-io.rgb.path=\\\"${BASE_PATH}\\\"\"\n"), "{}", sh);
+        assert!(!sh.contains("message-format"));
+        assert!(!sh.contains("/staging/"));
+
+        let ps1 = plan.to_ps1();
+        assert!(ps1.contains("+rgb = { path = \"$BasePath\" }"), "{}", ps1);
+        assert!(ps1.contains("& cargo 'check' '--config' \"patch.crates-io.rgb.path=`\"$BasePath`\"\""), "{}", ps1);
+        assert!(ps1.contains("git apply copter.patch"));
+    }
+
+    #[test]
+    fn test_sh_word_quoting() {
+        assert_eq!(sh_word("--all-features"), "--all-features");
+        assert_eq!(sh_word("it's"), "'it'\\''s'");
+        assert_eq!(sh_word(&format!("{}/src", BASE_PATH_TOKEN)), "\"${BASE_PATH}/src\"");
+    }
+}
@@ -0,0 +1,151 @@
+/// Run metadata stamped into every report artifact
+///
+/// A `report.md`, `report.json` or `failures.log` found in CI artifacts months later
+/// should say what produced it without the CI logs: the copter version, the exact
+/// command line, when it ran, and which commit of the base crate was tested (with a
+/// dirty flag when the working tree had uncommitted changes).
+use crate::types::{CrateSource, TestMatrix};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Who/what/when produced a report
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RunMetadata {
+    pub copter_version: String,
+    /// The command line as typed, shell-quoted where needed
+    pub command_line: String,
+    /// `--label` given for the run: a branch, PR or experiment name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Local time the run finished, RFC 3339
+    pub generated: String,
+    /// Local checkout of the base crate, when one was tested (`--path` or ./Cargo.toml)
+    pub base_path: Option<String>,
+    /// `git describe --tags --always` of `base_path`
+    pub git_describe: Option<String>,
+    /// Uncommitted changes in `base_path`'s repository
+    pub git_dirty: Option<bool>,
+    /// Wall clock of the test execution, seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wall_secs: Option<f64>,
+    /// CPU time of the cargo processes run during the tests, seconds (Linux, local runs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cargo_cpu_secs: Option<f64>,
+    /// `--top-dependents`: the base crate's dependents in all and how many were tested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub population: Option<crate::api::Population>,
+    /// `--pin-toolchain`: the toolchain every step ran on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<String>,
+    /// `rustc -V` of `toolchain`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rustc: Option<String>,
+}
+
+impl RunMetadata {
+    /// Metadata for the current process and `matrix`'s local base crate
+    pub fn collect(matrix: &TestMatrix) -> Self {
+        let base_dir = matrix.base_versions.iter().find_map(|v| match &v.crate_ref.source {
+            CrateSource::Local { path } => Some(manifest_dir(path)),
+            _ => None,
+        });
+        RunMetadata {
+            copter_version: env!("CARGO_PKG_VERSION").to_string(),
+            command_line: command_line(std::env::args()),
+            label: None,
+            generated: chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+            git_describe: base_dir.as_deref().and_then(crate::git::describe_at),
+            git_dirty: base_dir.as_deref().and_then(crate::git::is_dirty_at),
+            base_path: base_dir.map(|d| crate::paths::canonicalize(&d).display().to_string()),
+            wall_secs: None,
+            cargo_cpu_secs: None,
+            population: matrix.population,
+            toolchain: matrix.cross.toolchain.clone(),
+            rustc: matrix.cross.toolchain.as_deref().and_then(crate::toolchains::rustc_version),
+        }
+    }
+
+    /// "v0.8.1-3-gabc1234 (dirty)", or why there is no commit
+    pub fn git_summary(&self) -> String {
+        match (&self.git_describe, self.git_dirty, &self.base_path) {
+            (Some(describe), Some(true), _) => format!("{} (dirty)", describe),
+            (Some(describe), _, _) => describe.clone(),
+            (None, _, Some(_)) => "not a git checkout".to_string(),
+            (None, _, None) => "none (published versions only)".to_string(),
+        }
+    }
+
+    /// `(label, value)` pairs shared by the markdown and log headers
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![
+            ("Generated", format!("{} by cargo-copter {}", self.generated, self.copter_version)),
+            ("Command", self.command_line.clone()),
+        ];
+        if let Some(label) = &self.label {
+            fields.push(("Label", label.clone()));
+        }
+        if let Some(path) = &self.base_path {
+            fields.push(("Base crate path", path.clone()));
+        }
+        if let Some(population) = &self.population {
+            fields.push(("Dependents", population.describe()));
+        }
+        if let Some(toolchain) = &self.toolchain {
+            let rustc = self.rustc.as_deref().unwrap_or("rustc unknown");
+            fields.push(("Toolchain", format!("{} ({})", toolchain, rustc)));
+        }
+        fields.push(("Base crate commit", self.git_summary()));
+        fields
+    }
+}
+
+/// Directory of a `--path` value, which may name the manifest itself
+fn manifest_dir(path: &Path) -> PathBuf {
+    if path.is_file() {
+        path.parent().map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from("."))
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Join arguments back into a command line, quoting the ones a shell would split
+fn command_line(args: impl IntoIterator<Item = String>) -> String {
+    args.into_iter()
+        .map(|arg| {
+            if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=,@+%^~".contains(c)) {
+                arg
+            } else {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_line_quoting() {
+        let args = ["cargo-copter", "--dependents", "image:0.25", "--test-versions", "0.8 0.9", "", "it's"];
+        assert_eq!(
+            command_line(args.iter().map(|s| s.to_string())),
+            "cargo-copter --dependents image:0.25 --test-versions '0.8 0.9' '' 'it'\\''s'"
+        );
+    }
+
+    #[test]
+    fn test_git_summary() {
+        let mut meta = RunMetadata::default();
+        assert_eq!(meta.git_summary(), "none (published versions only)");
+        meta.base_path = Some("/src/rgb".to_string());
+        assert_eq!(meta.git_summary(), "not a git checkout");
+        meta.git_describe = Some("v0.8.50-2-gabc1234".to_string());
+        meta.git_dirty = Some(true);
+        assert_eq!(meta.git_summary(), "v0.8.50-2-gabc1234 (dirty)");
+        assert_eq!(meta.fields().last().unwrap().0, "Base crate commit");
+        meta.label = Some("pr-1234".to_string());
+        assert_eq!(meta.fields()[2], ("Label", "pr-1234".to_string()));
+    }
+}
@@ -1,21 +1,102 @@
+use crate::baseline_cache;
 use crate::compile;
-use crate::download;
+use crate::disk;
+use crate::error::CopterError;
+use crate::live;
+use crate::manifest;
+use crate::queue;
+use crate::scheduler::{self, Scheduler};
+use crate::simulate_update;
+use crate::source_cache;
+use crate::system_deps;
 use crate::types::*;
-use crate::ui;
 use crate::version;
 use log::debug;
 use semver::Version as SemverVersion;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// One (base version, dependent) pair to run
+pub struct Job<'a> {
+    pub base: &'a VersionSpec,
+    pub dependent: &'a VersionSpec,
+    /// The dependent's requirement on the base crate, as found by its baseline run
+    pub requirement: Option<String>,
+    /// Limits for this dependent's steps (`Heartbeat::for_dependent`)
+    pub heartbeat: compile::heartbeat::Heartbeat,
+    /// Which of the parallel workers runs it (0 when serial)
+    pub worker: usize,
+}
 
-/// Run all tests specified in the matrix
+/// Where a pair is built and tested
+///
+/// `Local` runs cargo on this machine. `--docker` puts the whole run in a container rather
+/// than each pair; an executor that builds in a container or on another machine over ssh
+/// implements this trait and leaves scheduling, streaming and reports as they are.
+pub trait Executor: Sync {
+    fn execute(&self, job: &Job, matrix: &TestMatrix) -> Result<compile::ThreeStepResult, CopterError>;
+}
+
+/// cargo on this machine; failed steps are sent to `failure_log` when given
+pub struct Local<'a> {
+    pub failure_log: Option<&'a compile::failure_log::FailureLog>,
+}
+
+impl Executor for Local<'_> {
+    fn execute(&self, job: &Job, matrix: &TestMatrix) -> Result<compile::ThreeStepResult, CopterError> {
+        let failure_log = self.failure_log.map(|log| log.for_worker(job.worker));
+        run_local(job, matrix, failure_log.as_ref())
+    }
+}
+
+/// Why a dependent ended the run
+enum Stop {
+    /// Keep the results so far and stop starting dependents
+    Halt(String),
+    /// Fail the run
+    Fail(CopterError),
+}
+
+/// Run all tests specified in the matrix, locally and in the matrix's order
 ///
 /// This is the main entry point for test execution.
-/// The callback is invoked for each completed test result.
-pub fn run_tests<F>(mut matrix: TestMatrix, mut on_result: F) -> Result<Vec<TestResult>, String>
+/// The callback is invoked for each completed test result. Failed steps are sent to
+/// `failure_log` when given.
+pub fn run_tests<F>(
+    matrix: TestMatrix,
+    failure_log: Option<&compile::failure_log::FailureLog>,
+    on_result: F,
+) -> Result<Vec<TestResult>, CopterError>
+where
+    F: FnMut(&TestResult),
+{
+    let scheduler = scheduler::build(scheduler::Order::Listed, 1, &matrix, &[]);
+    run_with(matrix, scheduler, &Local { failure_log }, on_result)
+}
+
+/// Run the matrix with dependents picked by `scheduler` and pairs run by `executor`
+///
+/// The callback is invoked on this thread for each completed result, in the order they
+/// finish; a dependent's baseline always comes before its offered versions.
+pub fn run_with<F>(
+    mut matrix: TestMatrix,
+    mut scheduler: Box<dyn Scheduler>,
+    executor: &dyn Executor,
+    mut on_result: F,
+) -> Result<Vec<TestResult>, CopterError>
 where
     F: FnMut(&TestResult),
 {
     debug!("Starting test execution for {} test pairs", matrix.test_count());
 
+    // Undo manifest edits left behind by an interrupted earlier run
+    match compile::patching::recover_staging(&matrix.staging_dir, &matrix.audit) {
+        Ok(0) => {}
+        Ok(n) => eprintln!("Restored {} staged Cargo.toml file(s) left modified by an interrupted run", n),
+        Err(e) => eprintln!("warning: failed to recover staged manifests: {}", e),
+    }
+
     // INVARIANT: Exactly one baseline version must exist
     let baseline_count = matrix.base_versions.iter().filter(|v| v.is_baseline).count();
     debug_assert!(baseline_count == 1, "Expected exactly 1 baseline, found {}", baseline_count);
@@ -23,150 +104,334 @@ where
     // Step 1: Resolve base version Latest entries (just a few, so do upfront)
     for base_spec in &mut matrix.base_versions {
         if let Version::Latest = base_spec.crate_ref.version {
-            let latest = version::resolve_latest_version(&base_spec.crate_ref.name, false)
-                .map_err(|e| format!("Failed to resolve latest version for {}: {}", base_spec.crate_ref.name, e))?;
+            let latest =
+                version::resolve_latest_version(&matrix.registry, &base_spec.crate_ref.name, false).map_err(|e| {
+                    CopterError::Network(e)
+                        .context(format!("Failed to resolve latest version for {}", base_spec.crate_ref.name))
+                })?;
             base_spec.crate_ref.version = Version::Semver(latest);
         }
     }
 
-    // Step 2: Execute all test pairs
-    // IMPORTANT: Must iterate dependents × base_versions (outer × inner)
-    // This ensures baseline is tested first for each dependent
+    // Step 2: Execute all test pairs, each dependent's baseline first
+    let saved_baselines = Mutex::new(baseline_cache::BaselineCache::open(&matrix.staging_dir, matrix.baseline_cache));
+    let workers = scheduler.workers().clamp(1, matrix.dependents.len().max(1));
     let mut results = Vec::new();
+    let mut started = 0;
+    let handed_out = Mutex::new(std::collections::HashSet::new());
+    emit_quarantined(&matrix, &mut |result| {
+        on_result(&result);
+        results.push(result);
+    });
+    let outcome = if workers == 1 {
+        let mut outcome = Ok(());
+        while let Some(idx) = scheduler.next(&matrix) {
+            // Stop cleanly (keeping results so far) rather than letting cargo die with IO errors
+            if let Err(e) = disk::ensure_free_space(&matrix.staging_dir, matrix.min_free_bytes, &[]) {
+                outcome = Err(Stop::Halt(e));
+                break;
+            }
+            started += 1;
+            handed_out.lock().unwrap().insert(idx);
+            let mut emit = |result: TestResult| {
+                on_result(&result); // Stream the result immediately
+                results.push(result);
+            };
+            outcome = test_dependent(&matrix, idx, 0, executor, &saved_baselines, &mut emit);
+            if outcome.is_err() {
+                break;
+            }
+        }
+        outcome
+    } else {
+        // Every worker's builds get an equal share of the cores, as `worker --jobs` gives
+        // them (a CARGO_BUILD_JOBS already in the environment is left alone)
+        if std::env::var_os(queue::BUILD_JOBS_ENV).is_none() {
+            let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+            matrix.cross.build_jobs = Some(queue::build_jobs_per_worker(cores, workers));
+        }
+        // Registry base versions are staged once up front, not by several workers at a time
+        for base_spec in &matrix.base_versions {
+            if base_spec.override_mode != OverrideMode::None
+                && base_spec.crate_ref.source == CrateSource::Registry
+                && let Err(e) = stage_base_version(&base_spec.crate_ref, &matrix)
+            {
+                debug!("not staging {} up front: {}", base_spec.crate_ref.display(), e);
+            }
+        }
+        let scheduler = Mutex::new(scheduler);
+        let stop: Mutex<Option<Stop>> = Mutex::new(None);
+        let stopping = AtomicBool::new(false);
+        // Dependent each worker is building, so a disk cleanup leaves their output alone
+        let building: Mutex<HashMap<usize, String>> = Mutex::new(HashMap::new());
+        let (tx, rx) = std::sync::mpsc::channel();
+        let matrix = &matrix;
+        let started = &mut started;
+        std::thread::scope(|scope| {
+            for worker in 0..workers {
+                let tx = tx.clone();
+                let (scheduler, stop, stopping, saved_baselines, handed_out, building) =
+                    (&scheduler, &stop, &stopping, &saved_baselines, &handed_out, &building);
+                scope.spawn(move || {
+                    while !stopping.load(Ordering::Relaxed) {
+                        // Disk check (and any pause) happens between dispatches, one worker at a time
+                        let mut scheduler = scheduler.lock().unwrap();
+                        let Some(idx) = scheduler.next(matrix) else { break };
+                        let busy: Vec<String> = building.lock().unwrap().values().cloned().collect();
+                        if let Err(e) = disk::ensure_free_space(&matrix.staging_dir, matrix.min_free_bytes, &busy) {
+                            stopping.store(true, Ordering::Relaxed);
+                            stop.lock().unwrap().get_or_insert(Stop::Halt(e));
+                            break;
+                        }
+                        building.lock().unwrap().insert(worker, matrix.dependents[idx].crate_ref.name.clone());
+                        drop(scheduler);
+                        handed_out.lock().unwrap().insert(idx);
+                        let mut emit = |result: TestResult| {
+                            let _ = tx.send(result);
+                        };
+                        let outcome = test_dependent(matrix, idx, worker, executor, saved_baselines, &mut emit);
+                        building.lock().unwrap().remove(&worker);
+                        if let Err(e) = outcome {
+                            stopping.store(true, Ordering::Relaxed);
+                            stop.lock().unwrap().get_or_insert(e);
+                        }
+                    }
+                });
+            }
+            drop(tx);
+            let mut dependents = std::collections::HashSet::new();
+            for result in rx {
+                on_result(&result);
+                dependents.insert((result.dependent.name.clone(), result.dependent.version.display()));
+                results.push(result);
+            }
+            *started = dependents.len();
+        });
+        stop.into_inner().unwrap().map_or(Ok(()), Err)
+    };
+    // Dependents never started get skipped rows, so the reports and --fail-on-skipped see them
+    let not_started = match outcome {
+        // Only the time budget ends a run before the scheduler runs out
+        Ok(()) => "time budget used up".to_string(),
+        Err(Stop::Halt(e)) => {
+            eprintln!("warning: stopping after {} of {} dependents — {e}", started, matrix.dependents.len());
+            e
+        }
+        Err(Stop::Fail(e)) => return Err(e),
+    };
+    let handed_out = handed_out.into_inner().unwrap();
+    for (_, spec) in matrix.dependents.iter().enumerate().filter(|(idx, _)| !handed_out.contains(idx)) {
+        let mut emit = |result: TestResult| {
+            on_result(&result);
+            results.push(result);
+        };
+        emit_skipped(&matrix, &spec.crate_ref, &format!("not started: {}", not_started), &mut emit);
+    }
 
-    // Use indices to allow lazy resolution per dependent (enables streaming)
-    for idx in 0..matrix.dependents.len() {
-        // Resolve this specific dependent's version lazily (just before testing it)
-        if let Version::Latest = matrix.dependents[idx].crate_ref.version {
-            let name = matrix.dependents[idx].crate_ref.name.clone();
-            match version::resolve_latest_version(&name, false) {
-                Ok(latest) => matrix.dependents[idx].crate_ref.version = Version::Semver(latest),
-                Err(e) => {
-                    // A reverse-dep with no published versions (yanked, unpublished,
-                    // or path-only) should be skipped, not abort the whole run.
-                    eprintln!("warning: skipping dependent `{name}` — could not resolve a version ({e})");
-                    continue;
-                }
+    let hits = saved_baselines.into_inner().unwrap().hits;
+    if hits > 0 {
+        eprintln!("note: reused {} baseline result(s) from earlier runs (--no-baseline-cache to run them again)", hits);
+    }
+    simulate_update::cleanup(&matrix.staging_dir);
+    Ok(results)
+}
+
+/// Test `matrix.dependents[idx]` against the baseline, then every other base version,
+/// handing each result to `emit`; a dependent that can't be tested is skipped with a warning
+fn test_dependent(
+    matrix: &TestMatrix,
+    idx: usize,
+    worker: usize,
+    executor: &dyn Executor,
+    saved_baselines: &Mutex<baseline_cache::BaselineCache>,
+    emit: &mut dyn FnMut(TestResult),
+) -> Result<(), Stop> {
+    // Resolve this specific dependent's version lazily (just before testing it)
+    let mut dependent_spec = matrix.dependents[idx].clone();
+    if let Version::Latest = dependent_spec.crate_ref.version {
+        let name = dependent_spec.crate_ref.name.clone();
+        match version::resolve_latest_version(&matrix.registry, &name, false) {
+            Ok(latest) => dependent_spec.crate_ref.version = Version::Semver(latest),
+            Err(e) => {
+                // A reverse-dep with no published versions (yanked, unpublished,
+                // or path-only) should be skipped, not abort the whole run.
+                eprintln!("warning: skipping dependent `{name}` — could not resolve a version ({e})");
+                return Ok(());
             }
         }
+    }
+    let dependent_spec = &dependent_spec;
+    // Get the dependent version (now guaranteed to be resolved)
+    let dependent = &dependent_spec.crate_ref;
+
+    // Declared system packages must be installed, or the build fails for reasons unrelated to the base crate
+    // (in the image, with --docker-steps: that can't be checked from here)
+    if let Some(settings) = matrix.dependent_settings.get(&dependent.name)
+        && matrix.cross.sandbox.is_none()
+    {
+        let missing = system_deps::missing(&settings.system_deps);
+        if !missing.is_empty() {
+            let reason = format!("missing system dep: {}", missing.join(", "));
+            live::note(&format!("warning: skipping dependent `{}` — {}", dependent.display(), reason));
+            emit_skipped(matrix, dependent, &reason, emit);
+            return Ok(());
+        }
+    }
 
-        let dependent_spec = &matrix.dependents[idx];
-        // Get the dependent version (now guaranteed to be resolved)
-        let dependent = &dependent_spec.crate_ref;
-
-        // Test baseline first, then other versions
-        let baseline_result = {
-            let baseline_spec = matrix
-                .base_versions
-                .iter()
-                .find(|v| v.is_baseline)
-                .ok_or_else(|| "No baseline version found".to_string())?;
-
-            debug!("Testing BASELINE {} against {}", baseline_spec.crate_ref.display(), dependent.display());
-
-            match run_single_test(baseline_spec, dependent_spec, &matrix) {
-                Ok(execution) => TestResult {
-                    base_version: baseline_spec.crate_ref.clone(),
-                    dependent: dependent.clone(),
-                    execution,
-                    baseline: None, // Baseline has no comparison
-                },
-                Err(e) => {
-                    // Execution error (not a build failure) — e.g. a historical
-                    // version that predates the base-crate dependency. Skip the
-                    // whole dependent rather than aborting the run.
-                    eprintln!("warning: skipping dependent `{}` — {e}", dependent.display());
-                    continue;
+    // --timeout-per-dependent: the clock starts with the baseline
+    let heartbeat = matrix.heartbeat.for_dependent();
+
+    // Test baseline first, then other versions
+    let baseline_spec = matrix
+        .base_versions
+        .iter()
+        .find(|v| v.is_baseline)
+        .ok_or_else(|| Stop::Fail(CopterError::Internal("No baseline version found".to_string())))?;
+    let baseline_result = {
+        debug!("Testing BASELINE {} against {}", baseline_spec.crate_ref.display(), dependent.display());
+
+        // A result saved by an earlier run with the same dependent, baseline and toolchain
+        let cache_key = baseline_cache::key_for(matrix, &baseline_spec.crate_ref, dependent);
+        let saved = cache_key.as_deref().and_then(|key| saved_baselines.lock().unwrap().get(key));
+        let job = Job { base: baseline_spec, dependent: dependent_spec, requirement: None, heartbeat, worker };
+        let execution = match saved {
+            // Built by the same rustc, maybe named differently (`--toolchains stable`)
+            Some(execution) => Ok(compile::ThreeStepResult { toolchain: matrix.cross.toolchain.clone(), ..execution }),
+            None => executor.execute(&job, matrix).inspect(|execution| {
+                if let Some(key) = cache_key
+                    && let Err(e) = saved_baselines.lock().unwrap().insert(key, execution)
+                {
+                    debug!("not caching the baseline of {}: {}", dependent.display(), e);
                 }
-            }
+            }),
         };
+        match execution {
+            Ok(execution) => TestResult {
+                base_version: baseline_spec.crate_ref.clone(),
+                dependent: dependent.clone(),
+                execution,
+                baseline: None, // Baseline has no comparison
+            },
+            Err(e) => {
+                // Execution error (not a build failure) — e.g. a historical
+                // version that predates the base-crate dependency. Skip the
+                // whole dependent rather than aborting the run.
+                eprintln!("warning: skipping dependent `{}` — {e}", dependent.display());
+                return Ok(());
+            }
+        }
+    };
 
-        let baseline_passed = baseline_result.execution.is_success();
+    let baseline_passed = baseline_result.execution.is_success();
 
-        // Extract step-level baseline results for nuanced regression detection
-        let baseline_fetch_passed = baseline_result.execution.fetch.success;
-        let baseline_check_passed = baseline_result.execution.check.as_ref().map(|c| c.success);
-        let baseline_test_passed = baseline_result.execution.test.as_ref().map(|t| t.success);
+    // Extract step-level baseline results for nuanced regression detection
+    let baseline_fetch_passed = baseline_result.execution.fetch.success;
+    let baseline_check_passed = baseline_result.execution.check.as_ref().map(|c| c.success);
+    let baseline_test_passed = baseline_result.execution.test.as_ref().map(|t| t.success);
 
-        // Extract the spec from baseline for use in offered version tests
-        let baseline_spec_requirement = baseline_result.execution.original_requirement.clone();
+    // Extract the spec from baseline for use in offered version tests
+    let baseline_spec_requirement = baseline_result.execution.original_requirement.clone();
 
-        on_result(&baseline_result); // Stream the result immediately
-        results.push(baseline_result);
+    emit(baseline_result);
 
-        // Then test other versions
-        for base_spec in matrix.base_versions.iter().filter(|v| !v.is_baseline) {
-            let base_version = &base_spec.crate_ref;
+    // Then test other versions
+    for base_spec in matrix.base_versions.iter().filter(|v| !v.is_baseline) {
+        let base_version = &base_spec.crate_ref;
 
-            debug!("Testing {} against {}", base_version.display(), dependent.display());
+        debug!("Testing {} against {}", base_version.display(), dependent.display());
 
-            // Run the three-step test, passing the baseline spec requirement
-            let execution = match run_single_test_with_spec(
-                base_spec,
-                dependent_spec,
-                &matrix,
-                baseline_spec_requirement.clone(),
-            ) {
-                Ok(e) => e,
-                Err(e) => {
-                    eprintln!("warning: skipping {} for `{}` — {e}", base_version.display(), dependent.display());
-                    continue;
-                }
-            };
+        // Run the three-step test, passing the baseline spec requirement
+        let job = Job {
+            base: base_spec,
+            dependent: dependent_spec,
+            requirement: baseline_spec_requirement.clone(),
+            heartbeat,
+            worker,
+        };
+        let execution = match executor.execute(&job, matrix) {
+            Ok(e) => e,
+            Err(e) => {
+                eprintln!("warning: skipping {} for `{}` — {e}", base_version.display(), dependent.display());
+                continue;
+            }
+        };
 
-            let result = TestResult {
-                base_version: base_version.clone(),
-                dependent: dependent.clone(),
-                execution,
-                baseline: Some(BaselineComparison {
-                    baseline_passed,
-                    baseline_version: matrix
-                        .base_versions
-                        .iter()
-                        .find(|v| v.is_baseline)
-                        .map(|v| v.crate_ref.version.display())
-                        .unwrap_or_else(|| "unknown".to_string()),
-                    baseline_fetch_passed,
-                    baseline_check_passed,
-                    baseline_test_passed,
-                }),
-            };
-            on_result(&result); // Stream the result immediately
-            results.push(result);
-        }
+        emit(TestResult {
+            base_version: base_version.clone(),
+            dependent: dependent.clone(),
+            execution,
+            baseline: Some(BaselineComparison {
+                baseline_passed,
+                baseline_version: baseline_spec.crate_ref.version.display(),
+                baseline_fetch_passed,
+                baseline_check_passed,
+                baseline_test_passed,
+            }),
+        });
     }
+    Ok(())
+}
 
-    Ok(results)
+/// Skipped rows for the matrix's quarantined dependents
+pub fn emit_quarantined(matrix: &TestMatrix, emit: &mut dyn FnMut(TestResult)) {
+    for name in &matrix.quarantined {
+        let dependent = VersionedCrate { name: name.clone(), version: Version::Latest, source: CrateSource::Registry };
+        emit_skipped(matrix, &dependent, "quarantined (baseline keeps failing)", emit);
+    }
 }
 
-/// Run a single test: one (base_version, dependent) pair
-fn run_single_test(
-    base_spec: &VersionSpec,
-    dependent_spec: &VersionSpec,
-    matrix: &TestMatrix,
-) -> Result<compile::ThreeStepResult, String> {
-    run_single_test_with_spec(base_spec, dependent_spec, matrix, None)
+/// A skipped row for `dependent` against every base version, baseline first, so it is
+/// reported (and counted by `--fail-on-skipped`) instead of silently missing
+pub fn emit_skipped(matrix: &TestMatrix, dependent: &VersionedCrate, reason: &str, emit: &mut dyn FnMut(TestResult)) {
+    let Some(baseline) = matrix.base_versions.iter().find(|v| v.is_baseline) else { return };
+    for base_spec in std::iter::once(baseline).chain(matrix.base_versions.iter().filter(|v| !v.is_baseline)) {
+        emit(TestResult {
+            base_version: base_spec.crate_ref.clone(),
+            dependent: dependent.clone(),
+            execution: compile::ThreeStepResult {
+                toolchain: matrix.cross.toolchain.clone(),
+                ..compile::ThreeStepResult::skipped(reason.to_string())
+            },
+            baseline: (!base_spec.is_baseline).then(|| BaselineComparison {
+                baseline_passed: false,
+                baseline_version: baseline.crate_ref.version.display(),
+                baseline_fetch_passed: false,
+                baseline_check_passed: None,
+                baseline_test_passed: None,
+            }),
+        });
+    }
 }
 
-/// Run a single test with an optional pre-extracted spec requirement
-fn run_single_test_with_spec(
-    base_spec: &VersionSpec,
-    dependent_spec: &VersionSpec,
+/// Stage a published base version for use as an override path
+fn stage_base_version(base_version: &VersionedCrate, matrix: &TestMatrix) -> Result<std::path::PathBuf, CopterError> {
+    let version = base_version.version.display();
+    let base_vers =
+        SemverVersion::parse(&version).map_err(|e| CopterError::Internal(format!("Invalid semver for base: {}", e)))?;
+    let dest = matrix.staging_dir.join(format!("{}-{}", base_version.name, version));
+    source_cache::stage(matrix, &base_version.name, &base_vers, &dest).map_err(CopterError::Network)?;
+    Ok(dest)
+}
+
+/// Run one pair with cargo on this machine
+fn run_local(
+    job: &Job,
     matrix: &TestMatrix,
-    original_requirement: Option<String>,
-) -> Result<compile::ThreeStepResult, String> {
+    failure_log: Option<&compile::failure_log::FailureLog>,
+) -> Result<compile::ThreeStepResult, CopterError> {
+    let (base_spec, dependent_spec) = (job.base, job.dependent);
     let base_version = &base_spec.crate_ref;
     let dependent = &dependent_spec.crate_ref;
 
     // Get version strings
     let base_version_str = match &base_version.version {
         Version::Semver(v) => v.clone(),
-        _ => return Err("Version not resolved".to_string()),
+        _ => return Err(CopterError::Internal("Version not resolved".to_string())),
     };
 
     let dependent_version_str = match &dependent.version {
         Version::Semver(v) => v.clone(),
-        _ => return Err("Dependent version not resolved".to_string()),
+        _ => return Err(CopterError::Internal("Dependent version not resolved".to_string())),
     };
 
     // Get dependent path or download it
@@ -174,34 +439,73 @@ fn run_single_test_with_spec(
         CrateSource::Local { path } => path.clone(),
         CrateSource::Registry => {
             // Download and unpack
-            let vers = SemverVersion::parse(&dependent_version_str).map_err(|e| format!("Invalid semver: {}", e))?;
-            let crate_handle = download::get_crate_handle(&dependent.name, &vers)
-                .map_err(|e| format!("Failed to download {}: {}", dependent.name, e))?;
-
+            let vers = SemverVersion::parse(&dependent_version_str)
+                .map_err(|e| CopterError::Internal(format!("Invalid semver: {}", e)))?;
             let dest = matrix.staging_dir.join(format!("{}-{}", dependent.name, dependent_version_str));
-            if !dest.exists() {
-                std::fs::create_dir_all(&dest).map_err(|e| format!("Failed to create staging dir: {}", e))?;
-                crate_handle
-                    .unpack_source_to(&dest)
-                    .map_err(|e| format!("Failed to unpack {}: {}", dependent.name, e))?;
-            }
-
+            source_cache::stage(matrix, &dependent.name, &vers, &dest).map_err(CopterError::Network)?;
             dest
         }
-        CrateSource::Git { .. } => {
-            return Err("Git sources not yet implemented".to_string());
-        }
+        CrateSource::Git { path, .. } => path.clone(),
+    };
+
+    // Embedded and wasm-only dependents build for their own target, without host tests
+    let target_plan = compile::target::TargetPlan {
+        registry: matrix.registry.clone(),
+        ..compile::target::detect(&dependent_path, &matrix.cross)
+    };
+    if let Some(target) = &target_plan.target
+        && !target_plan.is_available()
+    {
+        return Err(CopterError::Config(format!("needs the {} target (rustup target add {})", target, target)));
+    }
+    if base_spec.is_baseline
+        && let Some(reason) = &target_plan.reason
+    {
+        let tests = if target_plan.run_tests { "" } else { "; not running its tests" };
+        eprintln!("note: {} {}{}", dependent.display(), reason, tests);
+    }
+    let steps = if target_plan.run_tests || !matrix.steps.runs(CommandType::Test) {
+        matrix.steps.clone()
+    } else {
+        Steps::through(CommandType::Check)
+    };
+
+    // A local or git dependent in a workspace is checked and tested with all its members
+    let workspace_root = match dependent.source {
+        CrateSource::Registry => None,
+        _ => compile::workspace_root(&dependent_path),
+    };
+
+    let requirement = job.requirement.clone();
+    let test_label = if base_spec.is_baseline {
+        "baseline"
+    } else if matches!(base_version.source, CrateSource::Local { .. }) {
+        "WIP"
+    } else {
+        base_version_str.as_str()
     };
 
     // Build the TestConfig using the builder pattern
     let test_config = compile::TestConfig::new(dependent_path.as_path(), &matrix.base_crate)
-        .with_skip_flags(matrix.skip_check, matrix.skip_test)
+        .with_steps(steps)
         .with_version_info(
             Some(base_version_str.clone()),
             base_spec.override_mode == OverrideMode::Force,
-            original_requirement, // Use provided spec from baseline test (if any)
+            requirement.clone(), // Use provided spec from baseline test (if any)
         )
-        .with_patch_transitive(matrix.patch_transitive);
+        .with_patch_transitive(matrix.patch_transitive)
+        .with_logging_info(
+            Some(compile::DependentInfo { name: &dependent.name, version: &dependent_version_str }),
+            Some(test_label),
+        )
+        .with_failure_log(failure_log.cloned())
+        .with_heartbeat(job.heartbeat)
+        .with_target(target_plan)
+        .with_nightly_gates(matrix.nightly_gates.clone())
+        .with_dependent_patches(matrix.dependent_patches)
+        .with_workspace_root(workspace_root.as_deref())
+        .with_force_resolver(matrix.force_resolver)
+        .with_audit(matrix.audit.clone());
 
     // Prepare override path if needed (download registry versions)
     let override_path = if base_spec.override_mode != OverrideMode::None {
@@ -212,31 +516,42 @@ fn run_single_test_with_spec(
                     if path.ends_with("Cargo.toml") { path.parent().unwrap().to_path_buf() } else { path.clone() };
                 Some(dir_path)
             }
-            CrateSource::Registry => {
-                // Download the registry version to use as override path
-                let base_vers =
-                    SemverVersion::parse(&base_version_str).map_err(|e| format!("Invalid semver for base: {}", e))?;
-                let crate_handle = download::get_crate_handle(&base_version.name, &base_vers)
-                    .map_err(|e| format!("Failed to download {}: {}", base_version.name, e))?;
-
-                let dest = matrix.staging_dir.join(format!("{}-{}", base_version.name, base_version_str));
-                if !dest.exists() {
-                    std::fs::create_dir_all(&dest).map_err(|e| format!("Failed to create staging dir: {}", e))?;
-                    crate_handle
-                        .unpack_source_to(&dest)
-                        .map_err(|e| format!("Failed to unpack {}: {}", base_version.name, e))?;
-                }
-
-                Some(dest)
-            }
+            // Download the registry version to use as override path
+            CrateSource::Registry => Some(stage_base_version(base_version, matrix)?),
             CrateSource::Git { .. } => {
-                return Err("Git sources not yet implemented".to_string());
+                return Err(CopterError::Config("Git sources not yet implemented".to_string()));
             }
         }
     } else {
         None
     };
 
+    // --simulate-update: only where `cargo update` would pick this version, as its release
+    let simulated = if matrix.simulate_update && base_spec.override_mode == OverrideMode::Patch {
+        let published = matrix.base_versions.iter().find(|v| v.is_baseline).map(|v| v.crate_ref.version.display());
+        Some(
+            simulate_update::check(&base_version_str, requirement.as_deref(), published.as_deref())
+                .map_err(CopterError::Config)?,
+        )
+    } else {
+        None
+    };
+    let override_path = match (override_path, &simulated) {
+        (Some(path), Some(release)) => Some(
+            simulate_update::stage(&path, release, &matrix.staging_dir, matrix.staging_copy)
+                .map_err(CopterError::Patching)?,
+        ),
+        (path, _) => path,
+    };
+
+    // stage-publish: the WIP comes from the staging registry, not its directory
+    let staging_registry =
+        matrix.staging_registry.clone().filter(|_| matches!(base_version.source, CrateSource::Local { .. }));
+    let (override_path, test_config) = match staging_registry {
+        Some(registry) if !base_spec.is_baseline => (None, test_config.with_staging_registry(Some(registry))),
+        _ => (override_path, test_config),
+    };
+
     // Apply override if we have a path
     let test_config = if let Some(ref path) = override_path {
         test_config.with_override_path(path)
@@ -245,10 +560,48 @@ fn run_single_test_with_spec(
         test_config
     };
 
-    // Execute the test
-    let result = compile::run_three_step_ict(test_config).map_err(|e| format!("Test execution failed: {}", e))?;
+    // Execute the test; failures caused by the environment get one retry
+    let mut result =
+        compile::run_three_step_ict(test_config.clone()).map_err(|e| e.context("Test execution failed"))?;
+    if let Some(reason) = result.env_failure() {
+        eprintln!(
+            "warning: environment failure ({}) testing {} against {}; retrying once",
+            reason,
+            dependent.display(),
+            base_version.display()
+        );
+        result = compile::run_three_step_ict(test_config).map_err(|e| e.context("Test execution failed"))?;
+    }
     result.debug_assert_consistent();
 
+    // The staged copy resolves as the release version; report it as the version offered
+    if let Some(release) = simulated
+        && result.actual_version.as_deref() == Some(release.to_string().as_str())
+    {
+        result.actual_version = Some(base_version_str.clone());
+    }
+
+    // An offered version cargo didn't pick: the dependent may build against its own copy
+    if !base_spec.is_baseline
+        && !result.forced_version
+        && result.fetch.success
+        && result.actual_version.as_deref() != Some(base_version_str.as_str())
+    {
+        result.fork = compile::detect_fork(&dependent_path, &matrix.base_crate, override_path.as_deref());
+    }
+    let manifest_path =
+        if dependent_path.ends_with("Cargo.toml") { dependent_path.clone() } else { dependent_path.join("Cargo.toml") };
+    result.repository = manifest::repository(&manifest_path);
+    let build_dir = workspace_root.as_deref().or(manifest_path.parent());
+    result.resolver = build_dir.and_then(compile::declared_resolver).map(|declared| match declared.as_str() {
+        "1" if matrix.force_resolver => "2 (forced from 1)".to_string(),
+        _ => declared,
+    });
+    result.toolchain = matrix.cross.toolchain.clone();
+    if result.fetch.success {
+        result.base_features = compile::base_features(&dependent_path, &matrix.base_crate);
+    }
+
     Ok(result)
 }
 
@@ -2,7 +2,6 @@
 #[cfg(test)]
 mod tests {
     use crate::types::*;
-    use std::path::PathBuf;
 
     /// Helper to create a minimal test matrix
     fn create_test_matrix() -> TestMatrix {
@@ -26,10 +25,26 @@ mod tests {
                 is_baseline: true,
             }],
             staging_dir: crate::cli::default_cache_dir().join("staging"),
-            skip_check: false,
-            skip_test: false,
+            steps: Steps::all(),
             error_lines: 10,
             patch_transitive: false,
+            min_free_bytes: 0,
+            heartbeat: Default::default(),
+            cross: Default::default(),
+            nightly_gates: None,
+            dependent_patches: Default::default(),
+            simulate_update: false,
+            staging_registry: None,
+            dependent_settings: Default::default(),
+            population: None,
+            baseline_cache: false,
+            staging_copy: crate::staging::CopyMethod::Auto,
+            source_cache: Default::default(),
+            registry: Default::default(),
+            audit: Default::default(),
+            force_resolver: false,
+            toolchains: vec![],
+            quarantined: vec![],
         }
     }
 
@@ -86,6 +101,9 @@ mod tests {
                     stderr: String::new(),
                     duration: std::time::Duration::from_secs(1),
                     diagnostics: vec![],
+                    args: vec![],
+                    manifest_diff: None,
+                    timed_out: false,
                 },
                 check: None,
                 test: None,
@@ -95,6 +113,14 @@ mod tests {
                 original_requirement: None,
                 all_crate_versions: vec![],
                 patch_depth: crate::compile::PatchDepth::None,
+                started: None,
+                fork: None,
+                blocking: vec![],
+                repository: None,
+                base_features: None,
+                resolver: None,
+                toolchain: None,
+                skipped: None,
             },
             baseline: None, // Baseline has no comparison
         };
@@ -117,6 +143,9 @@ mod tests {
                     stderr: String::new(),
                     duration: std::time::Duration::from_secs(1),
                     diagnostics: vec![],
+                    args: vec![],
+                    manifest_diff: None,
+                    timed_out: false,
                 },
                 check: None,
                 test: None,
@@ -126,6 +155,14 @@ mod tests {
                 original_requirement: None,
                 all_crate_versions: vec![],
                 patch_depth: crate::compile::PatchDepth::None,
+                started: None,
+                fork: None,
+                blocking: vec![],
+                repository: None,
+                base_features: None,
+                resolver: None,
+                toolchain: None,
+                skipped: None,
             },
             baseline: Some(BaselineComparison {
                 baseline_passed: true,
@@ -153,6 +190,9 @@ mod tests {
                     stderr: String::new(),
                     duration: std::time::Duration::from_secs(1),
                     diagnostics: vec![],
+                    args: vec![],
+                    manifest_diff: None,
+                    timed_out: false,
                 },
                 check: None,
                 test: None,
@@ -162,6 +202,14 @@ mod tests {
                 original_requirement: None,
                 all_crate_versions: vec![],
                 patch_depth: crate::compile::PatchDepth::None,
+                started: None,
+                fork: None,
+                blocking: vec![],
+                repository: None,
+                base_features: None,
+                resolver: None,
+                toolchain: None,
+                skipped: None,
             },
             baseline: Some(BaselineComparison {
                 baseline_passed: true, // Baseline passed
@@ -205,6 +253,9 @@ mod tests {
                     stderr: String::new(),
                     duration: std::time::Duration::from_secs(1),
                     diagnostics: vec![],
+                    args: vec![],
+                    manifest_diff: None,
+                    timed_out: false,
                 },
                 check: Some(crate::compile::CompileResult {
                     step: crate::compile::CompileStep::Check,
@@ -213,6 +264,9 @@ mod tests {
                     stderr: "error[E0412]: cannot find type".to_string(),
                     duration: std::time::Duration::from_secs(1),
                     diagnostics: vec![],
+                    args: vec![],
+                    manifest_diff: None,
+                    timed_out: false,
                 }),
                 test: None,
                 actual_version: Some("0.2.0".to_string()),
@@ -221,6 +275,14 @@ mod tests {
                 original_requirement: None,
                 all_crate_versions: vec![],
                 patch_depth: crate::compile::PatchDepth::None,
+                started: None,
+                fork: None,
+                blocking: vec![],
+                repository: None,
+                base_features: None,
+                resolver: None,
+                toolchain: None,
+                skipped: None,
             },
             baseline: Some(BaselineComparison {
                 baseline_passed: false, // Overall baseline failed (test failed)
@@ -238,4 +300,132 @@ mod tests {
         assert!(result.is_step_regression(), "Should detect check-level regression even when baseline test failed");
         assert_eq!(result.regression_step(), Some("check"));
     }
+
+    /// Passes everything except `broken` with an offered version
+    struct FakeExecutor;
+
+    impl crate::runner::Executor for FakeExecutor {
+        fn execute(
+            &self,
+            job: &crate::runner::Job,
+            _matrix: &TestMatrix,
+        ) -> Result<crate::compile::ThreeStepResult, crate::error::CopterError> {
+            let passed = job.base.is_baseline || job.dependent.crate_ref.name != "broken";
+            Ok(crate::compile::ThreeStepResult {
+                fetch: crate::compile::CompileResult {
+                    step: crate::compile::CompileStep::Fetch,
+                    success: passed,
+                    stdout: String::new(),
+                    stderr: String::new(),
+                    duration: std::time::Duration::from_millis(10),
+                    diagnostics: vec![],
+                    args: vec![],
+                    manifest_diff: None,
+                    timed_out: false,
+                },
+                check: None,
+                test: None,
+                actual_version: Some(job.base.crate_ref.version.display()),
+                expected_version: None,
+                forced_version: false,
+                original_requirement: Some("^0.1".to_string()),
+                all_crate_versions: vec![],
+                patch_depth: crate::compile::PatchDepth::None,
+                started: None,
+                fork: None,
+                blocking: vec![],
+                repository: None,
+                base_features: None,
+                resolver: None,
+                toolchain: None,
+                skipped: None,
+            })
+        }
+    }
+
+    #[test]
+    fn test_run_with_parallel_scheduler_and_custom_executor() {
+        let staging = tempfile::tempdir().unwrap();
+        let mut matrix = create_test_matrix();
+        matrix.staging_dir = staging.path().to_path_buf();
+        matrix.dependents = ["a", "b", "broken", "c", "d"]
+            .iter()
+            .map(|name| VersionSpec {
+                crate_ref: VersionedCrate::from_local(*name, "1.0.0", staging.path().join(name)),
+                override_mode: OverrideMode::None,
+                is_baseline: false,
+            })
+            .collect();
+        let scheduler = crate::scheduler::build(crate::scheduler::Order::Listed, 3, &matrix, &[]);
+        let mut streamed = 0;
+        let results = crate::runner::run_with(matrix, scheduler, &FakeExecutor, |_| streamed += 1).unwrap();
+
+        assert_eq!((results.len(), streamed), (10, 10));
+        for (i, result) in results.iter().enumerate() {
+            // Each dependent's baseline streams before its offered version
+            if !result.is_baseline() {
+                assert!(results[..i].iter().any(|r| r.is_baseline() && r.dependent.name == result.dependent.name));
+            }
+        }
+        let regressed: Vec<&str> =
+            results.iter().filter(|r| r.status() == TestStatus::Regressed).map(|r| r.dependent.name.as_str()).collect();
+        assert_eq!(regressed, ["broken"]);
+    }
+
+    #[test]
+    fn test_missing_system_dep_emits_skipped_rows() {
+        let staging = tempfile::tempdir().unwrap();
+        let mut matrix = create_test_matrix();
+        matrix.staging_dir = staging.path().to_path_buf();
+        matrix.dependents = vec![VersionSpec {
+            crate_ref: VersionedCrate::from_local("needs-lib", "1.0.0", staging.path().join("needs-lib")),
+            override_mode: OverrideMode::None,
+            is_baseline: false,
+        }];
+        let settings = crate::project_config::DependentSettings {
+            system_deps: vec!["libcopter-no-such-package-dev".to_string()],
+            image: None,
+        };
+        matrix.dependent_settings.insert("needs-lib".to_string(), settings);
+        let scheduler = crate::scheduler::build(crate::scheduler::Order::Listed, 1, &matrix, &[]);
+        let results = crate::runner::run_with(matrix, scheduler, &FakeExecutor, |_| {}).unwrap();
+
+        assert_eq!(results.len(), 2);
+        for result in &results {
+            let row = crate::bridge::test_result_to_offered_row(result);
+            assert_eq!(RowStatus::from_row(&row), RowStatus::Skipped);
+            assert_eq!(row.test.skipped.as_deref(), Some("missing system dep: libcopter-no-such-package-dev"));
+        }
+    }
+
+    #[test]
+    fn test_quarantined_and_unstarted_dependents_emit_skipped_rows() {
+        let staging = tempfile::tempdir().unwrap();
+        let mut matrix = create_test_matrix();
+        matrix.staging_dir = staging.path().to_path_buf();
+        matrix.dependents = vec![VersionSpec {
+            crate_ref: VersionedCrate::from_local("late", "1.0.0", staging.path().join("late")),
+            override_mode: OverrideMode::None,
+            is_baseline: false,
+        }];
+        matrix.quarantined = vec!["flaky".to_string()];
+        // A budget that ran out before the first dependent
+        matrix.heartbeat = matrix.heartbeat.with_run_budget(Some(0));
+        let scheduler = crate::scheduler::build(crate::scheduler::Order::Listed, 1, &matrix, &[]);
+        let results = crate::runner::run_with(matrix, scheduler, &FakeExecutor, |_| {}).unwrap();
+
+        let skipped: Vec<(&str, Option<&str>)> =
+            results.iter().map(|r| (r.dependent.name.as_str(), r.execution.skipped.as_deref())).collect();
+        assert_eq!(
+            skipped,
+            [
+                ("flaky", Some("quarantined (baseline keeps failing)")),
+                ("flaky", Some("quarantined (baseline keeps failing)")),
+                ("late", Some("not started: time budget used up")),
+                ("late", Some("not started: time budget used up")),
+            ]
+        );
+        let rows: Vec<OfferedRow> = results.iter().map(crate::bridge::test_result_to_offered_row).collect();
+        assert_eq!(crate::report::summarize_offered_rows(&rows).skipped, 2);
+    }
 }
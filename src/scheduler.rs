@@ -0,0 +1,235 @@
+/// The order dependents are tested in, how many at once, and when the run stops
+///
+/// `runner::run_with` asks a `Scheduler` for the next dependent each time a worker is
+/// free, so a new strategy is a new `Scheduler`, not another copy of the runner's
+/// streaming and reporting. The ones here:
+///
+/// - `Listed`: the matrix's order (the default)
+/// - `Priority` (`--schedule priority`): dependents that failed with an offered version in
+///   their last recorded run first, then the ones that finished fastest, so a rerun after a
+///   fix shows within minutes whether the known breakage is gone; dependents with no
+///   recorded outcome keep their order at the end
+/// - `Parallel` (`--parallel N`): another scheduler's order with N dependents at a time
+/// - `BudgetAware` (`--time-budget`): another scheduler's order until the run's time is up
+use crate::flakiness::Outcome;
+use crate::types::TestMatrix;
+use std::collections::{BTreeMap, VecDeque};
+
+/// Picks the next dependent to test
+pub trait Scheduler: Send {
+    /// Index into `matrix.dependents` of the next dependent, or None to end the run
+    fn next(&mut self, matrix: &TestMatrix) -> Option<usize>;
+
+    /// How many dependents are tested at once
+    fn workers(&self) -> usize {
+        1
+    }
+}
+
+/// `--schedule`
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Order {
+    /// As listed (copter.toml, --dependents, or by downloads for --top-dependents)
+    #[default]
+    Listed,
+    /// Last run's failures first, then the quickest
+    Priority,
+}
+
+/// The scheduler for a run: `order`, `parallel` dependents at a time, within the matrix's
+/// time budget; `outcomes` are the recorded results `Priority` ranks by
+pub fn build(order: Order, parallel: usize, matrix: &TestMatrix, outcomes: &[Outcome]) -> Box<dyn Scheduler> {
+    let ordered: Box<dyn Scheduler> = match order {
+        Order::Listed => Box::new(Listed::default()),
+        Order::Priority => Box::new(Priority::new(matrix, outcomes)),
+    };
+    let ordered = if parallel > 1 { Box::new(Parallel { inner: ordered, workers: parallel }) } else { ordered };
+    match matrix.heartbeat.run_deadline {
+        Some(_) => Box::new(BudgetAware { inner: ordered, started: 0, stopped: false }),
+        None => ordered,
+    }
+}
+
+/// In the matrix's order
+#[derive(Debug, Default)]
+pub struct Listed {
+    next: usize,
+}
+
+impl Scheduler for Listed {
+    fn next(&mut self, matrix: &TestMatrix) -> Option<usize> {
+        let idx = self.next;
+        self.next += 1;
+        (idx < matrix.dependents.len()).then_some(idx)
+    }
+}
+
+/// Recent failures first, then by the time their last run took
+#[derive(Debug)]
+pub struct Priority {
+    order: VecDeque<usize>,
+}
+
+impl Priority {
+    pub fn new(matrix: &TestMatrix, outcomes: &[Outcome]) -> Self {
+        // Each dependent's last run: (any offered version failed, its slowest row)
+        let mut last: BTreeMap<&str, (&str, bool, f64)> = BTreeMap::new();
+        let baseline = matrix.base_versions.iter().find(|v| v.is_baseline).map(|v| v.crate_ref.version.display());
+        for o in outcomes.iter().filter(|o| o.base_crate == matrix.base_crate) {
+            let offered = baseline.as_deref() != Some(o.base.as_str());
+            let entry = last.entry(&o.dependent).or_insert((&o.run, false, 0.0));
+            if entry.0 != o.run {
+                *entry = (&o.run, false, 0.0);
+            }
+            entry.1 |= offered && !o.passed;
+            entry.2 = entry.2.max(o.duration);
+        }
+        let mut order: Vec<usize> = (0..matrix.dependents.len()).collect();
+        // Stable: ties (and dependents never run) keep the listed order
+        order.sort_by(|&a, &b| {
+            let rank = |idx: usize| {
+                let (_, failed, duration) = last.get(matrix.dependents[idx].crate_ref.name.as_str()).copied()?;
+                Some((!failed, duration))
+            };
+            match (rank(a), rank(b)) {
+                (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            }
+        });
+        Priority { order: order.into() }
+    }
+}
+
+impl Scheduler for Priority {
+    fn next(&mut self, _matrix: &TestMatrix) -> Option<usize> {
+        self.order.pop_front()
+    }
+}
+
+/// Another scheduler's order, several dependents at once
+pub struct Parallel {
+    pub inner: Box<dyn Scheduler>,
+    pub workers: usize,
+}
+
+impl Scheduler for Parallel {
+    fn next(&mut self, matrix: &TestMatrix) -> Option<usize> {
+        self.inner.next(matrix)
+    }
+
+    fn workers(&self) -> usize {
+        self.workers
+    }
+}
+
+/// Another scheduler's order, starting nothing once the matrix's time budget is used up
+/// (the runner reports the dependents never started as skipped)
+pub struct BudgetAware {
+    pub inner: Box<dyn Scheduler>,
+    started: usize,
+    stopped: bool,
+}
+
+impl Scheduler for BudgetAware {
+    fn next(&mut self, matrix: &TestMatrix) -> Option<usize> {
+        if self.stopped {
+            return None;
+        }
+        if matrix.heartbeat.out_of_time() {
+            self.stopped = true;
+            eprintln!(
+                "warning: stopping after {} of {} dependents — time budget used up",
+                self.started,
+                matrix.dependents.len()
+            );
+            return None;
+        }
+        let idx = self.inner.next(matrix)?;
+        self.started += 1;
+        Some(idx)
+    }
+
+    fn workers(&self) -> usize {
+        self.inner.workers()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OverrideMode, Steps, VersionSpec, VersionedCrate};
+
+    fn outcome(dependent: &str, base: &str, passed: bool, duration: f64) -> Outcome {
+        Outcome {
+            base_crate: "rgb".to_string(),
+            base: base.to_string(),
+            dependent: dependent.to_string(),
+            dependent_version: "1.0.0".to_string(),
+            passed,
+            run: "2026-10-15T09:00:00+00:00".to_string(),
+            status: None,
+            duration,
+            commit: None,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_priority_runs_last_failures_then_quickest_first() {
+        let mut matrix = TestMatrix {
+            base_crate: "rgb".to_string(),
+            base_versions: vec![VersionSpec::baseline(VersionedCrate::from_registry("rgb", "0.8.91"))],
+            dependents: vec![],
+            staging_dir: "/staging".into(),
+            steps: Steps::all(),
+            error_lines: 10,
+            patch_transitive: false,
+            min_free_bytes: 0,
+            heartbeat: Default::default(),
+            cross: Default::default(),
+            nightly_gates: None,
+            dependent_patches: Default::default(),
+            simulate_update: false,
+            staging_registry: None,
+            dependent_settings: Default::default(),
+            population: None,
+            baseline_cache: false,
+            staging_copy: crate::staging::CopyMethod::Auto,
+            source_cache: Default::default(),
+            registry: Default::default(),
+            audit: Default::default(),
+            force_resolver: false,
+            toolchains: vec![],
+            quarantined: vec![],
+        };
+        matrix.dependents = ["new", "slow", "fast", "broken"]
+            .iter()
+            .map(|name| VersionSpec {
+                crate_ref: VersionedCrate::from_registry(*name, "1.0.0"),
+                override_mode: OverrideMode::None,
+                is_baseline: false,
+            })
+            .collect();
+        let outcomes = [
+            outcome("slow", "0.8.91", true, 90.0),
+            outcome("slow", "0.8.92", true, 80.0),
+            outcome("fast", "0.8.92", true, 5.0),
+            // A baseline failure alone doesn't count
+            outcome("fast", "0.8.91", false, 5.0),
+            outcome("broken", "0.8.91", true, 60.0),
+            outcome("broken", "0.8.92", false, 60.0),
+        ];
+        let mut priority = build(Order::Priority, 1, &matrix, &outcomes);
+        let order: Vec<&str> = std::iter::from_fn(|| priority.next(&matrix))
+            .map(|idx| matrix.dependents[idx].crate_ref.name.as_str())
+            .collect();
+        assert_eq!(order, ["broken", "fast", "slow", "new"]);
+
+        let mut listed = build(Order::Listed, 4, &matrix, &outcomes);
+        assert_eq!((listed.workers(), listed.next(&matrix), listed.next(&matrix)), (4, Some(0), Some(1)));
+
+        matrix.heartbeat = matrix.heartbeat.with_run_budget(Some(0));
+        let mut budget = build(Order::Listed, 1, &matrix, &outcomes);
+        assert_eq!(budget.next(&matrix), None);
+    }
+}
@@ -0,0 +1,188 @@
+/// JSON Schema for `report.json` and the `validate-report` subcommand
+///
+/// Older reports are upgraded by `report::migrate_json_report`, one version step at a time.
+/// The schema lives in `schema/copter-report.schema.json` and is embedded here.
+/// `SCHEMA_VERSION` is written into every report and must be bumped together with
+/// the schema's `schema_version` const whenever a field is renamed, removed or
+/// changes type. Adding fields doesn't need a bump.
+///
+/// Validation implements the keywords the schema uses: `type`, `enum`, `const`,
+/// `required`, `properties`, `items`, `minimum` and local `$ref`s.
+use serde_json::Value;
+use std::path::Path;
+
+/// Current report shape version
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// The published schema
+pub const SCHEMA: &str = include_str!("../schema/copter-report.schema.json");
+
+/// Validate a report against the schema; returns one message per violation
+pub fn validate(report: &Value) -> Vec<String> {
+    let schema: Value = serde_json::from_str(SCHEMA).expect("embedded report schema is valid JSON");
+    let mut errors = Vec::new();
+    check(report, &schema, &schema, "", &mut errors);
+    errors
+}
+
+/// The `validate-report` subcommand; `Err` when the file doesn't match
+pub fn validate_file(path: &Path) -> Result<(), String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let report: Value =
+        serde_json::from_str(&text).map_err(|e| format!("{} is not valid JSON: {}", path.display(), e))?;
+
+    let errors = validate(&report);
+    if errors.is_empty() {
+        println!("{}: valid (schema_version {})", path.display(), SCHEMA_VERSION);
+        return Ok(());
+    }
+    for error in &errors {
+        println!("  {}", error);
+    }
+    let hint = match report.get("schema_version").and_then(Value::as_u64) {
+        Some(v) if v < SCHEMA_VERSION => {
+            format!(" (written with schema_version {}; upgrade it with --migrate <OUT>)", v)
+        }
+        Some(v) if v > SCHEMA_VERSION => {
+            format!(" (written with schema_version {}, this copter expects {})", v, SCHEMA_VERSION)
+        }
+        None => {
+            " (no schema_version; written before reports were versioned; upgrade it with --migrate <OUT>)".to_string()
+        }
+        _ => String::new(),
+    };
+    Err(format!("{}: {} schema violation(s){}", path.display(), errors.len(), hint))
+}
+
+/// `validate-report --migrate`: upgrade an older report, validate it and write it to `out`
+pub fn migrate_file(path: &Path, out: &Path) -> Result<(), String> {
+    let report = crate::report::import_json_report(path)?;
+    let errors = validate(&report);
+    if !errors.is_empty() {
+        for error in &errors {
+            println!("  {}", error);
+        }
+        return Err(format!("{}: {} schema violation(s) after migration", path.display(), errors.len()));
+    }
+    let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    std::fs::write(out, json).map_err(|e| format!("Failed to write {}: {}", out.display(), e))?;
+    println!("{}: migrated to schema_version {} -> {}", path.display(), SCHEMA_VERSION, out.display());
+    Ok(())
+}
+
+fn check(value: &Value, schema: &Value, root: &Value, path: &str, errors: &mut Vec<String>) {
+    let at = if path.is_empty() { "/" } else { path };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve(root, reference) {
+            Some(target) => check(value, target, root, path, errors),
+            None => errors.push(format!("{}: unresolvable $ref {}", at, reference)),
+        }
+        return;
+    }
+
+    if let Some(types) = schema.get("type") {
+        let allowed: Vec<&str> = match types {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        if !allowed.iter().any(|t| has_type(value, t)) {
+            errors.push(format!("{}: expected {}, found {}", at, allowed.join(" or "), type_name(value)));
+            return;
+        }
+    }
+    if let Some(expected) = schema.get("const")
+        && value != expected
+    {
+        errors.push(format!("{}: expected {}, found {}", at, expected, value));
+    }
+    if let Some(Value::Array(options)) = schema.get("enum")
+        && !options.contains(value)
+    {
+        let names: Vec<String> = options.iter().map(Value::to_string).collect();
+        errors.push(format!("{}: {} is not one of {}", at, value, names.join(", ")));
+    }
+    if let (Some(min), Some(n)) = (schema.get("minimum").and_then(Value::as_f64), value.as_f64())
+        && n < min
+    {
+        errors.push(format!("{}: {} is below the minimum {}", at, n, min));
+    }
+
+    if let Value::Object(map) = value {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !map.contains_key(name) {
+                    errors.push(format!("{}: missing required field '{}'", at, name));
+                }
+            }
+        }
+        if let Some(Value::Object(properties)) = schema.get("properties") {
+            for (name, sub) in properties {
+                if let Some(field) = map.get(name) {
+                    check(field, sub, root, &format!("{}/{}", path, name), errors);
+                }
+            }
+        }
+    }
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            check(item, item_schema, root, &format!("{}/{}", path, i), errors);
+        }
+    }
+}
+
+/// Resolve a local `#/a/b` reference
+fn resolve<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    root.pointer(reference.strip_prefix('#')?)
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "integer" => value.is_u64() || value.is_i64(),
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_version_matches_schema_file() {
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        assert_eq!(schema.pointer("/properties/schema_version/const"), Some(&json!(SCHEMA_VERSION)));
+    }
+
+    #[test]
+    fn test_validate_reports_violations() {
+        let report = json!({
+            "schema_version": SCHEMA_VERSION,
+            "crate_name": "rgb",
+            "crate_version": 8,
+            "total_dependents": 1,
+            "summary": {"passed": 1, "regressed": 0, "broken": 0, "env_failed": 0, "total": 1},
+            "comparison_stats": [],
+            "test_results": [{"status": {"kind": "exploded"}}]
+        });
+        let errors = validate(&report);
+        assert!(errors.contains(&"/crate_version: expected string, found number".to_string()));
+        assert!(errors.iter().any(|e| e.starts_with("/test_results/0: missing required field 'primary'")));
+        assert!(errors.iter().any(|e| e.starts_with("/test_results/0/status/kind: \"exploded\" is not one of")));
+
+        assert!(validate(&json!({})).iter().any(|e| e == "/: missing required field 'schema_version'"));
+    }
+}
@@ -0,0 +1,168 @@
+/// Scrubbing machine-specific and secret text out of stored logs and reports
+///
+/// Compiler output and cargo errors are full of absolute paths under the home
+/// directory, and a failing build script can echo environment variables. Reports are
+/// often uploaded as public CI artifacts, so with `--scrub` the published ones (report.md,
+/// report.json, regressions.json, junit.xml, the PR comment, the failure logs, the repro
+/// scripts and manifest-audit.log) are scrubbed as they are written:
+///
+/// - values of secret-looking environment variables (`*TOKEN*`, `*SECRET*`, `*PASSWORD*`,
+///   `*CREDENTIAL*`, `*API_KEY*`, ...) and of `--scrub-env` variables → `<$NAME>`
+/// - the home directory → `~`
+/// - the user name, as a whole word → `<user>`
+///
+/// The run's own state (history/, checkpoint.jsonl, quarantine and cache files) keeps the
+/// real values: `--resume` needs the real paths of local dependents.
+///
+/// `normalize_path_hex_codes` (used to cluster identical errors) lives here as well.
+use crate::run_metadata::RunMetadata;
+use crate::types::OfferedRow;
+use regex::Regex;
+
+/// Environment variable names whose values are treated as secrets
+const SECRET_NAME: &str = r"(?i)TOKEN|SECRET|PASSWORD|PASSWD|CREDENTIAL|API_KEY|PRIVATE_KEY|ACCESS_KEY|AUTH";
+
+/// Shorter values are left alone; replacing `1` or `true` everywhere would wreck the logs
+const MIN_SECRET_LEN: usize = 6;
+
+/// Text replacements applied to the published reports and logs
+#[derive(Debug, Clone, Default)]
+pub struct Scrubber {
+    /// Literal (needle, replacement) pairs, longest needle first
+    literals: Vec<(String, String)>,
+    user: Option<Regex>,
+}
+
+impl Scrubber {
+    /// Scrub this process's home directory, user name and secret environment values,
+    /// plus the values of `extra_vars`
+    pub fn from_env(extra_vars: &[String]) -> Self {
+        let secret_name = Regex::new(SECRET_NAME).expect("valid regex");
+        let secrets: Vec<(String, String)> =
+            std::env::vars().filter(|(name, _)| secret_name.is_match(name) || extra_vars.contains(name)).collect();
+        let home = dirs::home_dir().map(|h| h.display().to_string());
+        let user = std::env::var("USER").or_else(|_| std::env::var("USERNAME")).ok();
+        Self::new(home.as_deref(), user.as_deref(), &secrets)
+    }
+
+    fn new(home: Option<&str>, user: Option<&str>, secrets: &[(String, String)]) -> Self {
+        let mut literals: Vec<(String, String)> = Vec::new();
+        let mut add = |needle: &str, replacement: String| {
+            if !literals.iter().any(|(existing, _)| existing == needle) {
+                literals.push((needle.to_string(), replacement));
+            }
+        };
+        for (name, value) in secrets {
+            if value.len() >= MIN_SECRET_LEN {
+                add(value, format!("<${}>", name));
+            }
+        }
+        if let Some(home) = home.map(|h| h.trim_end_matches(['/', '\\'])).filter(|h| h.len() > 1) {
+            add(home, "~".to_string());
+        }
+        literals.sort_by_key(|(needle, _)| std::cmp::Reverse(needle.len()));
+        let user = user.filter(|u| u.len() >= 3).and_then(|u| Regex::new(&format!(r"\b{}\b", regex::escape(u))).ok());
+        Scrubber { literals, user }
+    }
+
+    pub fn text(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for (needle, replacement) in &self.literals {
+            if out.contains(needle.as_str()) {
+                out = out.replace(needle.as_str(), replacement);
+            }
+        }
+        if let Some(user) = &self.user {
+            out = user.replace_all(&out, "<user>").into_owned();
+        }
+        out
+    }
+
+    /// `rows` with their error messages scrubbed, for the exports
+    pub fn rows(&self, rows: &[OfferedRow]) -> Vec<OfferedRow> {
+        let mut rows = rows.to_vec();
+        for failure in rows.iter_mut().flat_map(|row| &mut row.test.commands).flat_map(|cmd| &mut cmd.result.failures) {
+            failure.error_message = self.text(&failure.error_message);
+        }
+        rows
+    }
+
+    /// `run` with its command line and base crate path scrubbed
+    pub fn run(&self, run: &RunMetadata) -> RunMetadata {
+        RunMetadata {
+            command_line: self.text(&run.command_line),
+            base_path: run.base_path.as_deref().map(|path| self.text(path)),
+            ..run.clone()
+        }
+    }
+}
+
+/// Normalize file paths by removing hex suffixes (e.g., file-abc123 -> file)
+/// Handles both Unix (/) and Windows (\) paths
+pub fn normalize_path_hex_codes(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut i = 0;
+    let chars: Vec<char> = text.chars().collect();
+
+    while i < chars.len() {
+        result.push(chars[i]);
+
+        // Check if we just pushed a path separator
+        if chars[i] == '/' || chars[i] == '\\' {
+            // Look ahead to find the next path component
+            let mut j = i + 1;
+            let mut component = String::new();
+
+            // Collect characters until next separator, space, or end
+            while j < chars.len() && chars[j] != '/' && chars[j] != '\\' && !chars[j].is_whitespace() {
+                component.push(chars[j]);
+                j += 1;
+            }
+
+            // Check if component ends with -[hex] pattern
+            if let Some(dash_pos) = component.rfind('-') {
+                let potential_hex = &component[dash_pos + 1..];
+                // Check if it's all hex digits and at least 8 chars
+                if potential_hex.len() >= 8 && potential_hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                    // Remove the -[hex] suffix
+                    result.push_str(&component[..dash_pos]);
+                    i = j;
+                    continue;
+                }
+            }
+
+            // No hex pattern, add component as-is
+            result.push_str(&component);
+            i = j;
+            continue;
+        }
+
+        i += 1;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrubber() {
+        let secrets =
+            vec![("GITHUB_TOKEN".to_string(), "ghp_abc123def456".to_string()), ("CI".to_string(), "true".to_string())];
+        let scrubber = Scrubber::new(Some("/home/lilith/"), Some("lilith"), &secrets);
+        let text = "error: /home/lilith/.cargo/registry/src/rgb-0.8.50/src/lib.rs\n\
+                    Authorization: ghp_abc123def456 (CI=true, by lilith, lilithium)\n\
+                    {\"path\": \"/home/lilith/x\"}";
+        assert_eq!(
+            scrubber.text(text),
+            "error: ~/.cargo/registry/src/rgb-0.8.50/src/lib.rs\n\
+             Authorization: <$GITHUB_TOKEN> (CI=true, by <user>, lilithium)\n\
+             {\"path\": \"~/x\"}"
+        );
+
+        let windows = Scrubber::new(Some("C:\\Users\\lilith"), None, &[]);
+        assert_eq!(windows.text("C:\\Users\\lilith\\src\\lib.rs"), "~\\src\\lib.rs");
+    }
+}
@@ -0,0 +1,257 @@
+/// End-to-end self-test against the bundled integration fixtures
+///
+/// Runs the real runner/compile pipeline (no crates.io access) against
+/// `test-crates/integration-fixtures`: base-crate v1 is the baseline, v2 and v3
+/// are forced over it, and each fixture dependent has a known expected outcome.
+/// This gives users a one-command environment check and contributors a
+/// regression suite for the override/patching strategies.
+use crate::bridge;
+use crate::manifest;
+use crate::runner;
+use crate::types::*;
+use std::path::{Path, PathBuf};
+
+/// Outcome expected for one (dependent, offered version) pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expected {
+    Passed,
+    Regressed,
+    Broken,
+}
+
+impl Expected {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Expected::Passed => "passed",
+            Expected::Regressed => "regressed",
+            Expected::Broken => "broken",
+        }
+    }
+}
+
+/// Dependents exercised by the self-test
+const DEPENDENTS: &[&str] = &[
+    "dependent-passing",
+    "dependent-regressed",
+    "dependent-broken",
+    "dependent-test-passing",
+    "dependent-test-failing",
+    "dependent-transitive-conflict",
+];
+
+/// Expected results: (dependent, offered base-crate version, outcome)
+const EXPECTATIONS: &[(&str, &str, Expected)] = &[
+    ("dependent-passing", "0.2.0", Expected::Passed),
+    ("dependent-passing", "0.3.0", Expected::Passed),
+    ("dependent-regressed", "0.2.0", Expected::Regressed),
+    ("dependent-regressed", "0.3.0", Expected::Regressed),
+    ("dependent-broken", "0.2.0", Expected::Broken),
+    ("dependent-broken", "0.3.0", Expected::Broken),
+    ("dependent-test-passing", "0.2.0", Expected::Passed),
+    ("dependent-test-passing", "0.3.0", Expected::Passed),
+    ("dependent-test-failing", "0.2.0", Expected::Regressed),
+    ("dependent-test-failing", "0.3.0", Expected::Regressed),
+    // The transitive chain keeps its own base-crate 0.1 copy; the two copies only
+    // exchange `String`s, so the duplicate must not be reported as a regression.
+    ("dependent-transitive-conflict", "0.2.0", Expected::Passed),
+    ("dependent-transitive-conflict", "0.3.0", Expected::Passed),
+];
+
+/// Default fixture location (only present in a source checkout)
+pub fn default_fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test-crates/integration-fixtures")
+}
+
+/// Classify an offered row the same way the summary counts do
+pub fn classify(row: &OfferedRow) -> Expected {
+    match row.status().severity() {
+        Severity::Ok | Severity::Notice => Expected::Passed,
+        Severity::Regression => Expected::Regressed,
+        Severity::Broken => Expected::Broken,
+    }
+}
+
+/// Build the fixture matrix rooted at a (copied) fixtures directory
+fn build_matrix(root: &Path, staging_dir: PathBuf) -> Result<TestMatrix, String> {
+    let base_spec = |dir: &str, is_baseline: bool| -> Result<VersionSpec, String> {
+        let path = root.join(dir);
+        let (name, version) = manifest::get_crate_info(&path.join("Cargo.toml"))?;
+        Ok(VersionSpec {
+            crate_ref: VersionedCrate::from_local(name, version, path),
+            override_mode: if is_baseline { OverrideMode::None } else { OverrideMode::Force },
+            is_baseline,
+        })
+    };
+
+    let mut dependents = Vec::new();
+    for dir in DEPENDENTS {
+        let path = root.join(dir);
+        let (name, version) = manifest::get_crate_info(&path.join("Cargo.toml"))?;
+        dependents.push(VersionSpec {
+            crate_ref: VersionedCrate::from_local(name, version, path),
+            override_mode: OverrideMode::None,
+            is_baseline: false,
+        });
+    }
+
+    Ok(TestMatrix {
+        base_crate: "base-crate".to_string(),
+        base_versions: vec![
+            base_spec("base-crate-v1", true)?,
+            base_spec("base-crate-v2", false)?,
+            base_spec("base-crate-v3", false)?,
+        ],
+        dependents,
+        staging_dir,
+        steps: Steps::all(),
+        error_lines: 10,
+        patch_transitive: false,
+        min_free_bytes: 0,
+        heartbeat: Default::default(),
+        cross: Default::default(),
+        nightly_gates: None,
+        dependent_patches: Default::default(),
+        simulate_update: false,
+        staging_registry: None,
+        dependent_settings: Default::default(),
+        population: None,
+        baseline_cache: false,
+        staging_copy: crate::staging::CopyMethod::Auto,
+        source_cache: Default::default(),
+        registry: Default::default(),
+        audit: Default::default(),
+        force_resolver: false,
+        toolchains: vec![],
+        quarantined: vec![],
+    })
+}
+
+/// Compare classified rows against the expectation table
+///
+/// Returns one line per expectation plus the number of mismatches.
+pub fn check_expectations(rows: &[OfferedRow]) -> (Vec<String>, usize) {
+    let mut lines = Vec::new();
+    let mut mismatches = 0;
+    for (dependent, version, expected) in EXPECTATIONS {
+        let actual = rows
+            .iter()
+            .find(|r| {
+                r.primary.dependent_name == *dependent && r.offered.as_ref().is_some_and(|o| o.version == *version)
+            })
+            .map(classify);
+        let (mark, detail) = match actual {
+            Some(a) if a == *expected => ("ok  ", a.label().to_string()),
+            Some(a) => ("FAIL", format!("expected {}, got {}", expected.label(), a.label())),
+            None => ("FAIL", format!("expected {}, but no result was produced", expected.label())),
+        };
+        if mark == "FAIL" {
+            mismatches += 1;
+        }
+        lines.push(format!("{} {} × base-crate {}: {}", mark, dependent, version, detail));
+    }
+    (lines, mismatches)
+}
+
+/// Run the self-test; `Err` if the environment is broken or any expectation fails
+pub fn run(fixtures: Option<&Path>, keep: bool) -> Result<(), String> {
+    let source = fixtures.map(Path::to_path_buf).unwrap_or_else(default_fixtures_dir);
+    if !source.join("base-crate-v1").exists() {
+        return Err(format!(
+            "Integration fixtures not found at {}. The self-test needs a source checkout of cargo-copter; \
+             pass --fixtures <DIR> pointing at test-crates/integration-fixtures.",
+            source.display()
+        ));
+    }
+
+    let work = tempfile::Builder::new()
+        .prefix("copter-self-test-")
+        .tempdir()
+        .map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let root = work.path().join("fixtures");
+    crate::staging::copy_tree(&source, &root, crate::staging::CopyMethod::Auto)?;
+
+    let matrix = build_matrix(&root, work.path().join("staging"))?;
+    println!(
+        "Self-test: {} dependents × {} base-crate versions (fixtures copied to {})",
+        matrix.dependents.len(),
+        matrix.base_versions.len(),
+        root.display()
+    );
+
+    let results = runner::run_tests(matrix, None, |result| {
+        println!("  ran {} against base-crate {}", result.dependent.display(), result.base_version.version.display());
+    })?;
+    let rows: Vec<OfferedRow> = results.iter().map(bridge::test_result_to_offered_row).collect();
+
+    let (lines, mismatches) = check_expectations(&rows);
+    println!();
+    for line in &lines {
+        println!("  {}", line);
+    }
+    println!();
+
+    if keep {
+        let kept = work.keep();
+        println!("Kept working directory: {}", kept.display());
+    }
+
+    if mismatches > 0 {
+        return Err(format!("{} of {} self-test expectations failed", mismatches, lines.len()));
+    }
+    println!("Self-test passed: {} expectations met", lines.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(dependent: &str, version: &str, baseline_check: bool, baseline_passed: bool, passed: bool) -> OfferedRow {
+        row_at(dependent, version, baseline_check, baseline_passed, CommandType::Check, passed)
+    }
+
+    fn row_at(
+        dependent: &str,
+        version: &str,
+        baseline_check: bool,
+        baseline_passed: bool,
+        step: CommandType,
+        passed: bool,
+    ) -> OfferedRow {
+        crate::test_rows::row(dependent)
+            .spec("^0.1")
+            .offered(version)
+            .forced(true)
+            .local()
+            .baseline(Some((baseline_check, baseline_passed)))
+            .steps(&[(step, 0.1)])
+            .passed(passed)
+            .build()
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(&row("a", "0.2.0", true, true, true)), Expected::Passed);
+        assert_eq!(classify(&row("a", "0.2.0", true, true, false)), Expected::Regressed);
+        assert_eq!(classify(&row("a", "0.2.0", false, false, false)), Expected::Broken);
+        // Baseline compiled but its tests failed: failing tests now are not a regression...
+        assert_eq!(classify(&row_at("a", "0.2.0", true, false, CommandType::Test, false)), Expected::Passed);
+        // ...but failing to compile is
+        assert_eq!(classify(&row("a", "0.2.0", true, false, false)), Expected::Regressed);
+    }
+
+    #[test]
+    fn test_check_expectations_reports_missing_and_wrong() {
+        let rows = vec![
+            row("dependent-passing", "0.2.0", true, true, true),
+            row("dependent-regressed", "0.2.0", true, true, true),
+        ];
+        let (lines, mismatches) = check_expectations(&rows);
+        assert_eq!(lines.len(), EXPECTATIONS.len());
+        assert!(lines[0].starts_with("ok"));
+        assert!(
+            lines.iter().any(|l| l.contains("dependent-regressed × base-crate 0.2.0: expected regressed, got passed"))
+        );
+        assert_eq!(mismatches, EXPECTATIONS.len() - 1);
+    }
+}
@@ -0,0 +1,152 @@
+/// `--simulate-update`: who would get an offered version from their next `cargo update`
+///
+/// Patch mode answers "does the dependent build against my code". This mode answers
+/// "who automatically picks up 0.8.92, and breaks, once it is published": the offered
+/// version is treated as if the index listed it as a release (`0.8.92-alpha.1` counts as
+/// `0.8.92`), and each dependent only gets it where cargo's resolver would choose it:
+/// its requirement has to accept that version, and nothing newer may be published on the
+/// same line (the baseline stands in for the latest publication). Dependents that
+/// wouldn't pick it up are skipped with the reason.
+///
+/// Cargo can't add a version to the crates.io index offline, and a `[patch]` whose
+/// version is a prerelease doesn't match a plain `^0.8` requirement, so a prerelease is
+/// staged as a copy whose manifest carries the release version, and that copy is patched
+/// in. A copy of a workspace member copies the whole workspace (without build output and
+/// ignored files, see `staging`) so inherited fields and relative paths keep working. Copies live in the
+/// staging directory for the length of one `runner::run_tests` call.
+use crate::staging::CopyMethod;
+use lazy_static::lazy_static;
+use semver::{Version, VersionReq};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+lazy_static! {
+    /// (source dir, release version) → staged copy of the crate, per process
+    static ref STAGED: Mutex<BTreeMap<(PathBuf, String), PathBuf>> = Mutex::new(BTreeMap::new());
+}
+
+/// The version `cargo update` would see for `offered`, or why a dependent wouldn't get it
+///
+/// `requirement` is the dependent's requirement on the base crate, `published` the
+/// baseline version.
+pub fn check(offered: &str, requirement: Option<&str>, published: Option<&str>) -> Result<Version, String> {
+    let mut release = Version::parse(offered).map_err(|e| format!("invalid version {}: {}", offered, e))?;
+    release.pre = semver::Prerelease::EMPTY;
+    release.build = semver::BuildMetadata::EMPTY;
+
+    if let Some(published) = published.and_then(|p| Version::parse(p).ok())
+        && crate::version::compat_line(&published) == crate::version::compat_line(&release)
+        && published >= release
+    {
+        return Err(format!("`cargo update` would stay on {}, which is not older than {}", published, release));
+    }
+    if let Some(spec) = requirement
+        && let Ok(req) = VersionReq::parse(spec)
+        && !req.matches(&release)
+    {
+        return Err(format!("`cargo update` wouldn't pick {} (requires {})", release, spec));
+    }
+    Ok(release)
+}
+
+/// Path to patch in for `release`: the source itself when its manifest already has that
+/// version, else a staged copy that does
+pub fn stage(source: &Path, release: &Version, staging_dir: &Path, method: CopyMethod) -> Result<PathBuf, String> {
+    let source = if source.ends_with("Cargo.toml") { source.parent().unwrap_or(source) } else { source };
+    let (name, version) = crate::manifest::get_crate_info(&source.join("Cargo.toml"))?;
+    if version == release.to_string() {
+        return Ok(source.to_path_buf());
+    }
+
+    let key = (source.to_path_buf(), release.to_string());
+    let mut staged = STAGED.lock().unwrap();
+    if let Some(path) = staged.get(&key) {
+        return Ok(path.clone());
+    }
+
+    let dest = staging_dir.join(format!("{}-{}-update-{}", name, release, std::process::id()));
+    let copy = copy_with_version(source, &dest, &release.to_string(), method)?;
+    log::debug!("staged {} {} as {} at {}", name, version, release, copy.display());
+    staged.insert(key, copy.clone());
+    Ok(copy)
+}
+
+/// Copy the crate at `source` (its whole workspace, for a member) to `dest`, replacing
+/// what was there, with its package version set to `version`; returns the crate's
+/// directory in the copy
+pub fn copy_with_version(source: &Path, dest: &Path, version: &str, method: CopyMethod) -> Result<PathBuf, String> {
+    let root = workspace_root(source).unwrap_or(source);
+    let member = source.strip_prefix(root).unwrap_or(Path::new(""));
+    let _ = fs::remove_dir_all(dest);
+    crate::staging::copy_tree(root, dest, method)?;
+    let copy = dest.join(member);
+    let manifest = copy.join("Cargo.toml");
+    let mut doc: toml_edit::DocumentMut = crate::manifest::load_string(&manifest)?
+        .parse()
+        .map_err(|e| format!("Failed to parse {}: {}", manifest.display(), e))?;
+    doc["package"]["version"] = toml_edit::value(version);
+    crate::compile::patching::write_atomic(&manifest, &doc.to_string())?;
+    Ok(copy)
+}
+
+/// Remove this process's staged copies
+pub fn cleanup(staging_dir: &Path) {
+    let mut staged = STAGED.lock().unwrap();
+    for ((_, release), copy) in std::mem::take(&mut *staged) {
+        // The copy may be a member inside the staged workspace; remove from the top
+        let top = copy.ancestors().find(|dir| dir.parent() == Some(staging_dir)).map(Path::to_path_buf).unwrap_or(copy);
+        if let Err(e) = fs::remove_dir_all(&top) {
+            log::debug!("failed to remove staged {} copy {}: {}", release, top.display(), e);
+        }
+    }
+}
+
+/// Nearest ancestor whose Cargo.toml has a `[workspace]` table
+pub fn workspace_root(dir: &Path) -> Option<&Path> {
+    dir.ancestors().skip(1).find(|ancestor| {
+        fs::read_to_string(ancestor.join("Cargo.toml"))
+            .ok()
+            .and_then(|text| text.parse::<toml::Table>().ok())
+            .is_some_and(|manifest| manifest.contains_key("workspace"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_who_would_update() {
+        let release = check("0.8.92-alpha.1", Some("^0.8.50"), Some("0.8.91")).unwrap();
+        assert_eq!(release.to_string(), "0.8.92");
+        assert_eq!(
+            check("0.8.92-alpha.1", Some("=0.8.50"), Some("0.8.91")).unwrap_err(),
+            "`cargo update` wouldn't pick 0.8.92 (requires =0.8.50)"
+        );
+        assert!(check("0.8.90", Some("^0.8"), Some("0.8.91")).is_err());
+        // A new line isn't held back by the old one
+        assert!(check("0.9.0", Some("^0.9"), Some("0.8.91")).is_ok());
+
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("ws");
+        fs::create_dir_all(workspace.join("rgb/src")).unwrap();
+        fs::create_dir_all(workspace.join("target/debug")).unwrap();
+        fs::write(workspace.join("Cargo.toml"), "[workspace]\nmembers = [\"rgb\"]\n").unwrap();
+        fs::write(workspace.join("README.md"), "shared").unwrap();
+        fs::write(workspace.join("rgb/Cargo.toml"), "[package]\nname = \"rgb\"\nversion = \"0.8.92-alpha.1\"\n")
+            .unwrap();
+        fs::write(workspace.join("rgb/src/lib.rs"), "").unwrap();
+        let staging = dir.path().join("staging");
+
+        let copy = stage(&workspace.join("rgb"), &release, &staging, CopyMethod::Auto).unwrap();
+        assert_eq!(crate::manifest::get_crate_info(&copy.join("Cargo.toml")).unwrap().1, "0.8.92");
+        let top = copy.parent().unwrap();
+        assert!(top.join("README.md").exists() && !top.join("target").exists());
+        assert_eq!(stage(&workspace.join("rgb"), &release, &staging, CopyMethod::Auto).unwrap(), copy);
+
+        cleanup(&staging);
+        assert!(!top.exists());
+    }
+}
@@ -0,0 +1,267 @@
+/// Unpacked registry sources, keyed by name, version and checksum (`--refresh`, `--cache-ttl`)
+///
+/// Each `.crate` is downloaded once into `crate-cache/` and unpacked once into
+/// `~/.cache/cargo-copter/sources/<name>/<version>-<sha256>/`, after its sha256 was checked
+/// against the `cksum` the registry index lists for it (a download that doesn't match is
+/// deleted and fails the lookup; without an index to ask, the file's own sha256 names the
+/// entry unchecked). Staging directories are copied from there (reflinks or links where
+/// `--staging-copy` allows) and remember which entry they came from, so a re-downloaded
+/// crate whose contents changed (a mirror that republished it, a truncated download) is
+/// staged again instead of building stale sources. `--cache-ttl DAYS` re-downloads entries
+/// older than that, `--refresh` re-downloads everything this run uses, and the summary
+/// says how many sources came from the cache.
+use crate::download;
+use crate::staging::CopyMethod;
+use crate::types::TestMatrix;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Written into each entry (as cargo does for unpacked registry sources); its modification
+/// time is when the entry was unpacked
+const MARKER: &str = ".cargo-ok";
+
+/// Written into staged copies: the entry they were copied from
+const STAGED_FROM: &str = ".copter-source";
+
+/// Length of an entry's checksum suffix
+const SHA256_HEX_LEN: usize = 64;
+
+static HITS: AtomicUsize = AtomicUsize::new(0);
+static MISSES: AtomicUsize = AtomicUsize::new(0);
+
+/// When cached sources are downloaded again (`TestMatrix.source_cache`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Policy {
+    /// Entries unpacked before this (`--refresh`: when the run started, so each entry the run
+    /// uses is downloaded once)
+    pub refresh: Option<SystemTime>,
+    /// Entries unpacked longer ago than this (`--cache-ttl`); `None` keeps them forever
+    pub ttl: Option<Duration>,
+}
+
+impl Policy {
+    /// From `--refresh` and `--cache-ttl` (days, 0 for no limit), for a run starting now
+    pub fn new(refresh: bool, ttl_days: u64) -> Self {
+        let ttl = (ttl_days > 0).then(|| Duration::from_secs(ttl_days.saturating_mul(24 * 60 * 60)));
+        Policy { refresh: refresh.then(SystemTime::now), ttl }
+    }
+
+    fn expired(&self, entry: &Path, now: SystemTime) -> bool {
+        let unpacked = std::fs::metadata(entry.join(MARKER)).and_then(|m| m.modified());
+        let Ok(unpacked) = unpacked else { return self.refresh.is_some() || self.ttl.is_some() };
+        // Whole seconds: a filesystem with coarse timestamps may date an entry unpacked this run earlier
+        let secs = |t: SystemTime| t.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        self.refresh.is_some_and(|start| secs(unpacked) < secs(start))
+            || self.ttl.is_some_and(|ttl| now.duration_since(unpacked).unwrap_or_default() > ttl)
+    }
+}
+
+/// Sources taken from the cache and sources downloaded, so far
+pub fn stats() -> (usize, usize) {
+    (HITS.load(Ordering::Relaxed), MISSES.load(Ordering::Relaxed))
+}
+
+/// "Source cache: 40 hits, 2 downloaded (…/sources)", once anything was looked up
+pub fn summary_line() -> Option<String> {
+    let (hits, misses) = stats();
+    (hits + misses > 0)
+        .then(|| format!("Source cache: {} hits, {} downloaded ({})", hits, misses, cache_dir().display()))
+}
+
+/// Put `crate_name` `version`'s sources at `dest`, from the cache or downloaded into it;
+/// a `dest` staged from the same entry is left as it is (with its build output). The
+/// matrix's registry, cache policy and copy method apply
+pub fn stage(matrix: &TestMatrix, crate_name: &str, version: &semver::Version, dest: &Path) -> Result<(), String> {
+    let registry = &matrix.registry;
+    let now = SystemTime::now();
+    let (entry, hit) = lookup(&cache_dir(), crate_name, &version.to_string(), matrix.source_cache, now, |fresh| {
+        if fresh {
+            download::forget_crate(registry, crate_name, version).map_err(|e| e.to_string())?;
+        }
+        let handle = download::get_crate_handle(registry, crate_name, version).map_err(|e| e.to_string())?;
+        Ok((handle.path().to_path_buf(), download::index_checksum(registry, crate_name, version)?))
+    })
+    .map_err(|e| format!("Failed to download {}: {}", crate_name, e))?;
+    if hit { &HITS } else { &MISSES }.fetch_add(1, Ordering::Relaxed);
+    stage_from(&entry, dest, matrix.staging_copy)
+}
+
+/// The entry for `name` `version` under `dir` and whether it was already there; `fetch`
+/// returns the `.crate` file (downloaded again when passed `true`) and the sha256 the index
+/// lists for it
+fn lookup(
+    dir: &Path,
+    name: &str,
+    version: &str,
+    policy: Policy,
+    now: SystemTime,
+    fetch: impl FnOnce(bool) -> Result<(PathBuf, Option<String>), String>,
+) -> Result<(PathBuf, bool), String> {
+    let crate_dir = dir.join(name);
+    let existing = entries(&crate_dir, version);
+    if let Some(entry) = existing.first()
+        && checksum_of(entry).is_some_and(|sum| sum.len() == SHA256_HEX_LEN)
+        && !policy.expired(entry, now)
+    {
+        return Ok((entry.clone(), true));
+    }
+
+    let (crate_file, listed) = fetch(!existing.is_empty())?;
+    let bytes = std::fs::read(&crate_file).map_err(|e| format!("Failed to read {}: {}", crate_file.display(), e))?;
+    let sum = format!("{:x}", Sha256::digest(&bytes));
+    if let Some(listed) = listed
+        && !listed.eq_ignore_ascii_case(&sum)
+    {
+        // Truncated or altered: the next lookup downloads it again
+        let _ = std::fs::remove_file(&crate_file);
+        return Err(format!("{} has sha256 {}, but the registry index lists {}", crate_file.display(), sum, listed));
+    }
+    let entry = crate_dir.join(format!("{}-{}", version, sum));
+
+    if entry.join(MARKER).exists() {
+        // Same contents as before: only the age starts over
+        std::fs::write(entry.join(MARKER), "{\"v\":1}").map_err(|e| e.to_string())?;
+    } else {
+        let partial = crate_dir.join(format!("{}.partial-{}", version, std::process::id()));
+        let _ = std::fs::remove_dir_all(&partial);
+        std::fs::create_dir_all(&partial).map_err(|e| format!("Failed to create {}: {}", partial.display(), e))?;
+        download::extract_crate_archive(&crate_file, &partial)
+            .map_err(|e| format!("Failed to unpack {}: {}", crate_file.display(), e))?;
+        std::fs::write(partial.join(MARKER), "{\"v\":1}").map_err(|e| e.to_string())?;
+        // Another process may have unpacked the same file meanwhile; either copy will do
+        if std::fs::rename(&partial, &entry).is_err() {
+            let _ = std::fs::remove_dir_all(&partial);
+        }
+    }
+    for old in existing.iter().filter(|old| **old != entry) {
+        let _ = std::fs::remove_dir_all(old);
+    }
+    Ok((entry, false))
+}
+
+/// Entries of `version` in `crate_dir`, most recently unpacked first (including those named by
+/// the FNV-1a hash earlier versions used, which are downloaded again and replaced)
+fn entries(crate_dir: &Path, version: &str) -> Vec<PathBuf> {
+    let Ok(read) = std::fs::read_dir(crate_dir) else { return vec![] };
+    let mut entries: Vec<(SystemTime, PathBuf)> = read
+        .flatten()
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            name.rsplit_once('-').is_some_and(|(v, sum)| v == version && [16, SHA256_HEX_LEN].contains(&sum.len()))
+        })
+        .filter_map(|e| {
+            let unpacked = std::fs::metadata(e.path().join(MARKER)).and_then(|m| m.modified()).ok()?;
+            Some((unpacked, e.path()))
+        })
+        .collect();
+    entries.sort_by_key(|(unpacked, _)| std::cmp::Reverse(*unpacked));
+    entries.into_iter().map(|(_, path)| path).collect()
+}
+
+/// The checksum an entry's directory is named by
+fn checksum_of(entry: &Path) -> Option<&str> {
+    entry.file_name()?.to_str()?.rsplit_once('-').map(|(_, sum)| sum)
+}
+
+/// Copy `entry` to `dest` unless `dest` was already staged from it
+fn stage_from(entry: &Path, dest: &Path, method: CopyMethod) -> Result<(), String> {
+    let key = entry.display().to_string();
+    if std::fs::read_to_string(dest.join(STAGED_FROM)).is_ok_and(|staged| staged == key) {
+        return Ok(());
+    }
+    if dest.exists() {
+        std::fs::remove_dir_all(dest).map_err(|e| format!("Failed to clear {}: {}", dest.display(), e))?;
+    }
+    crate::staging::copy_tree(entry, dest, method)?;
+    std::fs::write(dest.join(STAGED_FROM), key).map_err(|e| format!("Failed to write {}: {}", dest.display(), e))
+}
+
+fn cache_dir() -> PathBuf {
+    crate::cli::default_cache_dir().join("sources")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crate_file(dir: &Path, lib: &str) -> PathBuf {
+        let path = dir.join(format!("rgb-{}.crate", lib.len()));
+        let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(
+            std::fs::File::create(&path).unwrap(),
+            flate2::Compression::fast(),
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(lib.len() as u64);
+        header.set_mode(0o644);
+        tar.append_data(&mut header, "rgb-0.8.50/src/lib.rs", lib.as_bytes()).unwrap();
+        tar.into_inner().unwrap().finish().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_entries_reused_until_refreshed_or_expired() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (store, downloads) = (tmp.path().join("sources"), tmp.path().join("downloads"));
+        std::fs::create_dir_all(&downloads).unwrap();
+        let first = crate_file(&downloads, "pub struct RGB;");
+        let now = SystemTime::now();
+        let keep = Policy::new(false, 0);
+
+        let (entry, hit) = lookup(&store, "rgb", "0.8.50", keep, now, |_| Ok((first.clone(), None))).unwrap();
+        assert!(!hit);
+        assert_eq!(std::fs::read_to_string(entry.join("src/lib.rs")).unwrap(), "pub struct RGB;");
+        let fetched = |_| -> Result<(PathBuf, Option<String>), String> { panic!("cached entry downloaded again") };
+        assert_eq!(lookup(&store, "rgb", "0.8.50", keep, now, fetched).unwrap(), (entry.clone(), true));
+
+        // Seven days later a one-day TTL downloads again; new contents get a new entry
+        let week = now + Duration::from_secs(7 * 24 * 60 * 60);
+        let second = crate_file(&downloads, "pub struct RGB8;");
+        let (newer, hit) = lookup(&store, "rgb", "0.8.50", Policy::new(false, 1), week, |fresh| {
+            assert!(fresh);
+            Ok((second.clone(), None))
+        })
+        .unwrap();
+        assert!(!hit && newer != entry && !entry.exists());
+        // --refresh downloads entries unpacked before the run started once, then reuses them
+        let refresh = Policy { refresh: Some(week + Duration::from_secs(1)), ttl: None };
+        assert!(!lookup(&store, "rgb", "0.8.50", refresh, now, |_| Ok((second.clone(), None))).unwrap().1);
+        let refresh = Policy { refresh: Some(now), ttl: None };
+        assert!(lookup(&store, "rgb", "0.8.50", refresh, now, fetched).unwrap().1);
+
+        let dest = tmp.path().join("staging/rgb-0.8.50");
+        stage_from(&newer, &dest, CopyMethod::Auto).unwrap();
+        std::fs::create_dir_all(dest.join("target")).unwrap();
+        stage_from(&newer, &dest, CopyMethod::Auto).unwrap();
+        assert!(dest.join("target").exists(), "a dest staged from the same entry is kept");
+        assert_eq!(std::fs::read_to_string(dest.join("src/lib.rs")).unwrap(), "pub struct RGB8;");
+    }
+
+    #[test]
+    fn test_downloads_checked_against_index_checksum() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (store, downloads) = (tmp.path().join("sources"), tmp.path().join("downloads"));
+        std::fs::create_dir_all(&downloads).unwrap();
+        let file = crate_file(&downloads, "pub struct RGB;");
+        let sum = format!("{:x}", Sha256::digest(std::fs::read(&file).unwrap()));
+        let (now, keep) = (SystemTime::now(), Policy::default());
+
+        let err = lookup(&store, "rgb", "0.8.50", keep, now, |_| Ok((file.clone(), Some("0".repeat(64))))).unwrap_err();
+        assert!(err.contains("registry index lists"), "{}", err);
+        assert!(!file.exists(), "a mismatched download is deleted");
+
+        // An entry named by the old FNV hash is replaced by a checked one
+        let file = crate_file(&downloads, "pub struct RGB;");
+        let legacy = store.join("rgb/0.8.50-00000000000000ff");
+        std::fs::create_dir_all(&legacy).unwrap();
+        std::fs::write(legacy.join(MARKER), "").unwrap();
+        let (entry, hit) = lookup(&store, "rgb", "0.8.50", keep, now, |fresh| {
+            assert!(fresh);
+            Ok((file.clone(), Some(sum.to_uppercase())))
+        })
+        .unwrap();
+        assert!(!hit && !legacy.exists());
+        assert_eq!(entry, store.join(format!("rgb/0.8.50-{}", sum)));
+    }
+}
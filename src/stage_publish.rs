@@ -0,0 +1,103 @@
+/// `stage-publish`: publish the WIP to a staging registry and test dependents against it
+///
+/// Patching dependents with the WIP directory skips everything publishing does: the
+/// package include/exclude rules, the normalized manifest, and the dependency and feature
+/// metadata cargo reads from the index. This subcommand runs `cargo publish` against a
+/// staging registry (kellnr, margo, or anything else speaking the registry protocol),
+/// then runs the normal matrix with the WIP rows patched to the *published* version:
+/// `[patch.crates-io] rgb = { version = "=0.8.92", registry = "copter-staging" }`, so
+/// cargo resolves the real index entry and downloads the real `.crate`.
+///
+/// The registry is named [`REGISTRY_NAME`] for cargo; its token comes from
+/// `CARGO_REGISTRIES_COPTER_STAGING_TOKEN`, like any registry token, so it never ends up
+/// on the recorded command line.
+use crate::types::{CrateSource, TestMatrix};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Name the staging registry is configured under for cargo
+pub const REGISTRY_NAME: &str = "copter-staging";
+
+/// The published WIP that the matrix's WIP rows resolve from
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct StagingRegistry {
+    /// Index URL (`sparse+https://...` or a git URL)
+    pub index: String,
+    pub crate_name: String,
+    pub version: String,
+}
+
+impl StagingRegistry {
+    /// `--config` values that define the registry and patch the base crate to the published version
+    pub fn config_args(&self) -> Vec<String> {
+        vec![
+            format!("registries.{}.index={}", REGISTRY_NAME, toml_string(&self.index)),
+            format!("patch.crates-io.{}.registry={}", self.crate_name, toml_string(REGISTRY_NAME)),
+            format!("patch.crates-io.{}.version={}", self.crate_name, toml_string(&format!("={}", self.version))),
+        ]
+    }
+}
+
+/// The staging registry for `matrix`'s local WIP, and the manifest to publish
+pub fn prepare(matrix: &TestMatrix, index: &str) -> Result<(StagingRegistry, PathBuf), String> {
+    let wip = matrix
+        .base_versions
+        .iter()
+        .find_map(|v| match &v.crate_ref.source {
+            CrateSource::Local { path } if !v.is_baseline => Some((path, v.crate_ref.version.display())),
+            _ => None,
+        })
+        .ok_or("stage-publish needs a local crate to publish (run it in the crate's directory or pass --path)")?;
+    let manifest = if wip.0.ends_with("Cargo.toml") { wip.0.clone() } else { wip.0.join("Cargo.toml") };
+    let registry = StagingRegistry { index: index.to_string(), crate_name: matrix.base_crate.clone(), version: wip.1 };
+    Ok((registry, manifest))
+}
+
+/// `cargo publish` the crate at `manifest` to the registry at `index`
+pub fn publish(manifest: &Path, index: &str) -> Result<(), String> {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("publish")
+        .arg("--manifest-path")
+        .arg(manifest)
+        .arg("--registry")
+        .arg(REGISTRY_NAME)
+        .arg("--config")
+        .arg(format!("registries.{}.index={}", REGISTRY_NAME, toml_string(index)))
+        .arg("--allow-dirty");
+    eprintln!("Publishing {} to {} ...", manifest.display(), index);
+    let status = cmd.status().map_err(|e| format!("Failed to run cargo publish: {}", e))?;
+    if !status.success() {
+        return Err(format!(
+            "cargo publish to {} failed (a version can only be published once; is CARGO_REGISTRIES_{}_TOKEN set?)",
+            index,
+            REGISTRY_NAME.to_uppercase().replace('-', "_")
+        ));
+    }
+    Ok(())
+}
+
+fn toml_string(s: &str) -> String {
+    toml::Value::String(s.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_args_patch_to_published_version() {
+        let registry = StagingRegistry {
+            index: "sparse+http://localhost:8000/api/v1/crates/".to_string(),
+            crate_name: "rgb".to_string(),
+            version: "0.8.92-alpha.1".to_string(),
+        };
+        assert_eq!(
+            registry.config_args(),
+            vec![
+                "registries.copter-staging.index=\"sparse+http://localhost:8000/api/v1/crates/\"",
+                "patch.crates-io.rgb.registry=\"copter-staging\"",
+                "patch.crates-io.rgb.version=\"=0.8.92-alpha.1\"",
+            ]
+        );
+    }
+}
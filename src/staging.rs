@@ -0,0 +1,360 @@
+/// Copying local crate sources into the staging directory
+///
+/// Local crates are copied before cargo runs in them: the work queue's copies of local
+/// dependents and base crates, `--simulate-update`'s copy of the base crate's workspace,
+/// and the self-test fixtures. A checkout's `target/` alone can hold gigabytes, so the
+/// copy leaves out `target/`, `.git/` and whatever the checkout's `.gitignore` files
+/// ignore: those inside the copied tree, and those in its parent directories up to the
+/// repository root. `Cargo.lock` is copied even when ignored, so the copy resolves the
+/// same versions as the original. A tree with a `.cargo-ok` marker was unpacked from a
+/// `.crate` file, where `cargo package` already chose the files, and is copied whole
+/// (still without `target/`).
+///
+/// Files are reflinked where the filesystem can share their blocks (Btrfs, XFS, APFS), so
+/// each per-version copy costs metadata only; elsewhere they are copied. `--staging-copy
+/// hardlink` links them instead, which saves the space on any filesystem but lets a write
+/// to a linked file show in both trees, so manifests and lockfiles (which copter and
+/// cargo rewrite) are always copied.
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Never copied, ignored or not
+const SKIP: [&str; 2] = ["target", ".git"];
+
+/// Copied even where a `.gitignore` matches
+const KEEP: [&str; 1] = ["Cargo.lock"];
+
+/// Rewritten in staged copies, so never hard-linked
+const WRITTEN: [&str; 2] = ["Cargo.toml", "Cargo.lock"];
+
+/// Files per `cp` invocation when reflinking
+const REFLINK_BATCH: usize = 256;
+
+/// How `copy_tree` creates files (`--staging-copy`)
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CopyMethod {
+    /// Reflinks where the filesystem supports them, otherwise copies
+    #[default]
+    Auto,
+    /// Reflinks, failing on filesystems without them
+    Reflink,
+    /// Hard links (copies across filesystems); manifests and lockfiles are still copied
+    Hardlink,
+    /// Plain copies
+    Copy,
+}
+
+/// Copy the crate or workspace at `src` to `dest`, without build output, git data and ignored files
+pub fn copy_tree(src: &Path, dest: &Path, method: CopyMethod) -> Result<(), String> {
+    // Rules match against paths below the .gitignore's directory, so both are resolved
+    let src = crate::paths::canonicalize(src);
+    let mut ignore = Ignore { off: src.join(".cargo-ok").exists(), ..Default::default() };
+    ignore.add_parents(&src);
+    let mut placer = Placer { method, reflinks: cfg!(unix) };
+    copy_dir(&src, dest, &mut ignore, &mut placer)
+}
+
+fn copy_dir(src: &Path, dest: &Path, ignore: &mut Ignore, placer: &mut Placer) -> Result<(), String> {
+    fs::create_dir_all(dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+    let inherited = ignore.rules.len();
+    ignore.add_file(src);
+    let entries = fs::read_dir(src).map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let is_dir = path.is_dir();
+        if SKIP.iter().any(|skip| name == *skip)
+            || (!KEEP.iter().any(|keep| name == *keep) && ignore.is_ignored(&path, is_dir))
+        {
+            continue;
+        }
+        if is_dir {
+            copy_dir(&path, &dest.join(&name), ignore, placer)?;
+        } else {
+            files.push(path);
+        }
+    }
+    ignore.rules.truncate(inherited);
+    placer.place(&files, dest)
+}
+
+/// Creates the files of one `copy_tree` call
+struct Placer {
+    method: CopyMethod,
+    /// Whether reflinks may work here: not off Unix, or once `cp` refused them
+    reflinks: bool,
+}
+
+impl Placer {
+    /// Put `files` (all from one directory) into `dest`
+    fn place(&mut self, files: &[PathBuf], dest: &Path) -> Result<(), String> {
+        match self.method {
+            CopyMethod::Copy => {}
+            CopyMethod::Hardlink => {
+                // `.cargo/config.toml` is rewritten by `--dependent-patches`
+                let written = |file: &Path| {
+                    file.file_name().is_some_and(|name| WRITTEN.iter().any(|w| name == *w))
+                        || file.parent().is_some_and(|dir| dir.ends_with(".cargo"))
+                };
+                for file in files {
+                    let target = dest.join(file.file_name().unwrap_or_default());
+                    if written(file) || fs::hard_link(file, &target).is_err() {
+                        copy(file, &target)?;
+                    }
+                }
+                return Ok(());
+            }
+            CopyMethod::Auto | CopyMethod::Reflink => {
+                if self.reflinks {
+                    match reflink(files, dest) {
+                        Ok(()) => return Ok(()),
+                        Err(e) if self.method == CopyMethod::Reflink => return Err(e),
+                        Err(e) => {
+                            log::debug!("reflinks unavailable, copying instead: {}", e);
+                            self.reflinks = false;
+                        }
+                    }
+                } else if self.method == CopyMethod::Reflink {
+                    return Err("reflinks are only supported on Linux and macOS".to_string());
+                }
+            }
+        }
+        for file in files {
+            copy(file, &dest.join(file.file_name().unwrap_or_default()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Clone `files` into `dest` with `cp`, which knows each platform's clone call
+fn reflink(files: &[PathBuf], dest: &Path) -> Result<(), String> {
+    let flag = if cfg!(target_os = "macos") { "-c" } else { "--reflink=always" };
+    for batch in files.chunks(REFLINK_BATCH) {
+        let output = Command::new("cp")
+            .arg(flag)
+            .args(batch)
+            .arg(dest)
+            .output()
+            .map_err(|e| format!("Failed to run cp: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("cp {} failed: {}", flag, String::from_utf8_lossy(&output.stderr).trim()));
+        }
+    }
+    Ok(())
+}
+
+fn copy(file: &Path, target: &Path) -> Result<(), String> {
+    fs::copy(file, target).map(|_| ()).map_err(|e| format!("Failed to copy {}: {}", file.display(), e))
+}
+
+/// `.gitignore` rules in effect for a directory, outermost file first
+#[derive(Debug, Default)]
+struct Ignore {
+    rules: Vec<Rule>,
+    /// An unpacked `.crate`: no rules at all
+    off: bool,
+}
+
+/// One `.gitignore` line
+#[derive(Debug)]
+struct Rule {
+    /// Directory of the `.gitignore`; the pattern matches paths relative to it
+    base: PathBuf,
+    pattern: Regex,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl Ignore {
+    /// Rules of the `.gitignore` files above `dir`, when it is inside a git repository
+    fn add_parents(&mut self, dir: &Path) {
+        if self.off || dir.join(".git").exists() {
+            return;
+        }
+        let mut parents = Vec::new();
+        for parent in dir.ancestors().skip(1) {
+            parents.push(parent);
+            if parent.join(".git").exists() {
+                for parent in parents.iter().rev() {
+                    self.add_file(parent);
+                }
+                return;
+            }
+        }
+    }
+
+    fn add_file(&mut self, dir: &Path) {
+        if !self.off
+            && let Ok(text) = fs::read_to_string(dir.join(".gitignore"))
+        {
+            self.rules.extend(text.lines().filter_map(|line| Rule::parse(dir, line)));
+        }
+    }
+
+    /// The last matching rule decides, as in git
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(&rule.base) else { continue };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if rule.pattern.is_match(&relative) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+impl Rule {
+    fn parse(base: &Path, line: &str) -> Option<Rule> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        // A slash anywhere but the end anchors the pattern to the .gitignore's directory
+        let anchored = line.contains('/');
+        let glob = line.strip_prefix('/').unwrap_or(line);
+        let prefix = if anchored { "^" } else { "^(?:.*/)?" };
+        let pattern = Regex::new(&format!("{}{}$", prefix, glob_regex(glob))).ok()?;
+        Some(Rule { base: base.to_path_buf(), pattern, negated, dir_only })
+    }
+}
+
+/// A `.gitignore` glob as a regex: `*` and `?` stay within a path component, `**` crosses them
+fn glob_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    out.push_str("(?:.*/)?");
+                    i += 1;
+                } else {
+                    out.push_str(".*");
+                }
+                i += 1;
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' if chars[i..].contains(&']') => {
+                let len = chars[i..].iter().position(|c| *c == ']').unwrap_or_default();
+                let class: String = chars[i + 1..i + len].iter().collect();
+                let class = class.strip_prefix('!').map(|rest| format!("^{}", rest)).unwrap_or(class);
+                out.push_str(&format!("[{}]", class.replace('\\', "\\\\").replace('[', "\\[")));
+                i += len;
+            }
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_skips_ignored_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("repo/crates/image");
+        let write = |path: &str, text: &str| {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        };
+        fs::create_dir_all(dir.path().join("repo/.git")).unwrap();
+        write("repo/.gitignore", "*.log\n!keep.log\n/crates/image/bench-data/\nCargo.lock\n");
+        write("repo/crates/image/.gitignore", "docs/\n/generated[0-9].rs\n**/fixtures/*.bin\n");
+        for file in [
+            "Cargo.toml",
+            "Cargo.lock",
+            "src/lib.rs",
+            "src/generated1.rs",
+            "generated1.rs",
+            "build.log",
+            "keep.log",
+            "bench-data/a.png",
+            "docs/index.html",
+            "tests/fixtures/a.bin",
+            "tests/fixtures/a.txt",
+            "target/debug/libimage.rlib",
+        ] {
+            write(&format!("repo/crates/image/{}", file), "x");
+        }
+
+        let dest = dir.path().join("copy");
+        copy_tree(&src, &dest, CopyMethod::Auto).unwrap();
+        let mut copied: Vec<String> =
+            walk(&dest).iter().map(|p| p.strip_prefix(&dest).unwrap().display().to_string()).collect();
+        copied.sort();
+        assert_eq!(
+            copied,
+            [
+                ".gitignore",
+                "Cargo.lock",
+                "Cargo.toml",
+                "keep.log",
+                "src/generated1.rs",
+                "src/lib.rs",
+                "tests/fixtures/a.txt"
+            ]
+        );
+
+        // An unpacked .crate keeps everything but build output
+        write("repo/crates/image/.cargo-ok", "{\"v\":1}");
+        let dest = dir.path().join("unpacked");
+        copy_tree(&src, &dest, CopyMethod::Auto).unwrap();
+        assert!(dest.join("docs/index.html").exists() && dest.join("build.log").exists());
+        assert!(!dest.join("target").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hardlinks_leave_manifests_separate() {
+        use std::os::unix::fs::MetadataExt;
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("rgb");
+        fs::create_dir_all(src.join("src")).unwrap();
+        fs::create_dir_all(src.join(".cargo")).unwrap();
+        for file in ["Cargo.toml", "Cargo.lock", "src/lib.rs", ".cargo/config.toml"] {
+            fs::write(src.join(file), "x").unwrap();
+        }
+        let dest = dir.path().join("copy");
+        copy_tree(&src, &dest, CopyMethod::Hardlink).unwrap();
+        let linked =
+            |file: &str| fs::metadata(src.join(file)).unwrap().ino() == fs::metadata(dest.join(file)).unwrap().ino();
+        assert!(linked("src/lib.rs"));
+        assert!(!linked("Cargo.toml") && !linked("Cargo.lock") && !linked(".cargo/config.toml"));
+
+        // Without reflink support, auto falls back to copies
+        let dest = dir.path().join("auto");
+        copy_tree(&src, &dest, CopyMethod::Auto).unwrap();
+        assert_eq!(fs::read_to_string(dest.join("src/lib.rs")).unwrap(), "x");
+    }
+
+    fn walk(dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .unwrap()
+            .flatten()
+            .flat_map(|entry| if entry.path().is_dir() { walk(&entry.path()) } else { vec![entry.path()] })
+            .collect()
+    }
+}
@@ -0,0 +1,84 @@
+/// System packages a dependent needs to build (`system-deps` in copter.toml)
+///
+/// Native runs check that each declared package is present before testing the dependent
+/// and skip it otherwise, instead of reporting a confusing linker or build.rs failure.
+/// A package counts as present when any of these finds it: the system package manager
+/// (dpkg, rpm, Homebrew), `pkg-config` (`libvips-dev` → `vips`), or an executable of that
+/// name on `PATH` (`nasm`). Docker runs install the packages into the image instead
+/// (`COPTER_SYSTEM_PACKAGES`, see copter-docker.sh).
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Reject names that aren't plain package names (they end up in an `apt-get install` line)
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && !name.starts_with('-')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || "+-._:".contains(c));
+    if valid { Ok(()) } else { Err(format!("'{}' is not a valid system package name", name)) }
+}
+
+/// The packages in `packages` that don't appear to be installed
+pub fn missing(packages: &[String]) -> Vec<String> {
+    packages.iter().filter(|p| !is_installed(p)).cloned().collect()
+}
+
+fn is_installed(package: &str) -> bool {
+    on_path(package)
+        || succeeds("pkg-config", &["--exists", &pkg_config_name(package)])
+        || succeeds("rpm", &["-q", package])
+        || succeeds("brew", &["list", "--versions", package])
+        || Command::new("dpkg-query")
+            .args(["-W", "-f=${Status}", package])
+            .stderr(Stdio::null())
+            .output()
+            .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).contains("install ok installed"))
+}
+
+/// `libvips-dev` → `vips`, `openssl-devel` → `openssl`
+fn pkg_config_name(package: &str) -> String {
+    let name = package.strip_suffix("-dev").or_else(|| package.strip_suffix("-devel")).unwrap_or(package);
+    name.strip_prefix("lib").filter(|rest| !rest.is_empty()).unwrap_or(name).to_string()
+}
+
+fn succeeds(program: &str, args: &[&str]) -> bool {
+    Command::new(program).args(args).stdout(Stdio::null()).stderr(Stdio::null()).status().is_ok_and(|s| s.success())
+}
+
+fn on_path(name: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path).any(|dir| {
+        let candidate = dir.join(name);
+        candidate.is_file() || (cfg!(windows) && Path::new(&format!("{}.exe", candidate.display())).is_file())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("libvips-dev").is_ok());
+        assert!(validate_name("libstdc++6:amd64").is_ok());
+        assert!(validate_name("nasm; rm -rf /").is_err());
+        assert!(validate_name("--allow-unauthenticated").is_err());
+        assert!(validate_name("").is_err());
+    }
+
+    #[test]
+    fn test_pkg_config_name() {
+        assert_eq!(pkg_config_name("libvips-dev"), "vips");
+        assert_eq!(pkg_config_name("openssl-devel"), "openssl");
+        assert_eq!(pkg_config_name("nasm"), "nasm");
+        assert_eq!(pkg_config_name("lib"), "lib");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_missing() {
+        let packages = vec!["sh".to_string(), "copter-no-such-package-xyz".to_string()];
+        assert_eq!(missing(&packages), vec!["copter-no-such-package-xyz"]);
+    }
+}
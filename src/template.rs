@@ -0,0 +1,254 @@
+/// Handlebars-style templates for custom reports (`--report-template`)
+///
+/// The context is the full JSON report (see `report::json_report`). Supported syntax,
+/// a subset of Handlebars:
+///
+/// - `{{path.to.value}}` — dotted lookup in the current scope; `this`, `@index`, `@key`,
+///   `@first`, `@last`, `../name` (parent scope) and `@root.name` also work. Values are
+///   inserted as-is (no HTML escaping); arrays and objects render as compact JSON.
+/// - `{{#each path}}…{{else}}…{{/each}}` over arrays or object values
+/// - `{{#if path}}…{{else}}…{{/if}}` and `{{#unless path}}…{{/unless}}`; `null`, `false`,
+///   `0`, `""` and empty arrays/objects are falsy
+/// - `{{! comment }}`
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Var(String),
+    Each { path: String, body: Vec<Node>, otherwise: Vec<Node> },
+    If { path: String, negate: bool, body: Vec<Node>, otherwise: Vec<Node> },
+}
+
+/// Template file extensions dropped from the output name (`notes.md.hbs` → `notes.md`)
+const TEMPLATE_EXTENSIONS: &[&str] = &["hbs", "handlebars", "tera", "tmpl"];
+
+/// Render `template` against `context`
+pub fn render(template: &str, context: &Value) -> Result<String, String> {
+    let tokens = tokenize(template)?;
+    let mut pos = 0;
+    let (nodes, end) = parse(&tokens, &mut pos)?;
+    if let Some(tag) = end {
+        return Err(format!("unexpected {{{{{}}}}}", tag));
+    }
+    let mut out = String::new();
+    let scope = Scope { value: context, parent: None, meta: Vec::new() };
+    render_nodes(&nodes, &scope, context, &mut out);
+    Ok(out)
+}
+
+/// Render a template file into `report_dir`; returns the written path
+pub fn render_file(template_path: &Path, context: &Value, report_dir: &Path) -> Result<PathBuf, String> {
+    let template = std::fs::read_to_string(template_path)
+        .map_err(|e| format!("Failed to read template {}: {}", template_path.display(), e))?;
+    let rendered = render(&template, context).map_err(|e| format!("{}: {}", template_path.display(), e))?;
+    let out_path = report_dir.join(output_name(template_path));
+    std::fs::write(&out_path, rendered).map_err(|e| format!("Failed to write {}: {}", out_path.display(), e))?;
+    Ok(out_path)
+}
+
+/// Output file name for a template file
+pub fn output_name(template_path: &Path) -> String {
+    let name = template_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if TEMPLATE_EXTENSIONS.contains(&ext) && !stem.is_empty() => stem.to_string(),
+        _ => name,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Text(&'a str),
+    Tag(&'a str),
+}
+
+fn tokenize(template: &str) -> Result<Vec<Token<'_>>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+        }
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or_else(|| "unclosed {{".to_string())?;
+        tokens.push(Token::Tag(after[..end].trim()));
+        rest = &after[end + 2..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+    Ok(tokens)
+}
+
+/// Parse until `{{else}}` or a closing tag; returns the nodes and the tag that stopped parsing
+fn parse(tokens: &[Token], pos: &mut usize) -> Result<(Vec<Node>, Option<String>), String> {
+    let mut nodes = Vec::new();
+    while *pos < tokens.len() {
+        let token = &tokens[*pos];
+        *pos += 1;
+        let tag = match token {
+            Token::Text(text) => {
+                nodes.push(Node::Text(text.to_string()));
+                continue;
+            }
+            Token::Tag(tag) => *tag,
+        };
+
+        if tag.starts_with('!') {
+            continue;
+        } else if tag == "else" || tag.starts_with('/') {
+            return Ok((nodes, Some(tag.to_string())));
+        } else if let Some(block) = tag.strip_prefix('#') {
+            let (helper, path) = block.split_once(char::is_whitespace).unwrap_or((block, ""));
+            let path = path.trim().to_string();
+            if path.is_empty() {
+                return Err(format!("{{{{#{}}}}} needs an argument", helper));
+            }
+            let (body, mut end) = parse(tokens, pos)?;
+            let mut otherwise = Vec::new();
+            if end.as_deref() == Some("else") {
+                let (else_body, else_end) = parse(tokens, pos)?;
+                otherwise = else_body;
+                end = else_end;
+            }
+            let expected = format!("/{}", helper);
+            if end.as_deref() != Some(expected.as_str()) {
+                return Err(format!("{{{{#{} {}}}}} is not closed by {{{{{}}}}}", helper, path, expected));
+            }
+            nodes.push(match helper {
+                "each" => Node::Each { path, body, otherwise },
+                "if" => Node::If { path, negate: false, body, otherwise },
+                "unless" => Node::If { path, negate: true, body, otherwise },
+                other => return Err(format!("unknown block helper '#{}'", other)),
+            });
+        } else {
+            nodes.push(Node::Var(tag.to_string()));
+        }
+    }
+    Ok((nodes, None))
+}
+
+/// Current context plus `@index`-style loop variables
+struct Scope<'a> {
+    value: &'a Value,
+    parent: Option<&'a Scope<'a>>,
+    meta: Vec<(&'static str, Value)>,
+}
+
+fn lookup<'a>(path: &str, scope: &'a Scope<'a>, root: &'a Value) -> Option<&'a Value> {
+    if let Some(name) = path.strip_prefix('@')
+        && let Some((_, value)) = scope.meta.iter().find(|(k, _)| *k == name)
+    {
+        return Some(value);
+    }
+    if let Some(rest) = path.strip_prefix("../") {
+        return lookup(rest, scope.parent?, root);
+    }
+    let (mut value, rest) = match path.strip_prefix("@root") {
+        Some(rest) => (root, rest.trim_start_matches('.')),
+        None => (scope.value, path),
+    };
+    for part in rest.split('.').filter(|p| !p.is_empty() && *p != "this") {
+        value = match value {
+            Value::Object(map) => map.get(part)?,
+            Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+fn truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) | Some(Value::Bool(false)) => false,
+        Some(Value::Number(n)) => n.as_f64() != Some(0.0),
+        Some(Value::String(s)) => !s.is_empty(),
+        Some(Value::Array(a)) => !a.is_empty(),
+        Some(Value::Object(o)) => !o.is_empty(),
+        Some(Value::Bool(true)) => true,
+    }
+}
+
+fn render_nodes(nodes: &[Node], scope: &Scope, root: &Value, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var(path) => match lookup(path, scope, root) {
+                None | Some(Value::Null) => {}
+                Some(Value::String(s)) => out.push_str(s),
+                Some(other) => out.push_str(&other.to_string()),
+            },
+            Node::If { path, negate, body, otherwise } => {
+                let branch = if truthy(lookup(path, scope, root)) != *negate { body } else { otherwise };
+                render_nodes(branch, scope, root, out);
+            }
+            Node::Each { path, body, otherwise } => {
+                let items: Vec<(Value, &Value)> = match lookup(path, scope, root) {
+                    Some(Value::Array(items)) => items.iter().enumerate().map(|(i, v)| (Value::from(i), v)).collect(),
+                    Some(Value::Object(map)) => map.iter().map(|(k, v)| (Value::from(k.as_str()), v)).collect(),
+                    _ => Vec::new(),
+                };
+                if items.is_empty() {
+                    render_nodes(otherwise, scope, root, out);
+                }
+                let count = items.len();
+                for (i, (key, item)) in items.into_iter().enumerate() {
+                    let meta = vec![
+                        ("index", Value::from(i)),
+                        ("key", key),
+                        ("first", Value::from(i == 0)),
+                        ("last", Value::from(i + 1 == count)),
+                    ];
+                    let inner = Scope { value: item, parent: Some(scope), meta };
+                    render_nodes(body, &inner, root, out);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn context() -> Value {
+        json!({
+            "crate_name": "rgb",
+            "summary": {"passed": 2, "regressed": 1},
+            "test_results": [
+                {"primary": {"dependent_name": "image"}, "status_label": "passed"},
+                {"primary": {"dependent_name": "ravif"}, "status_label": "build failed"}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_render_variables_and_blocks() {
+        let template = "# {{crate_name}}{{! ignored }}\n\
+            {{#each test_results}}{{@index}}. {{primary.dependent_name}} ({{status_label}}) for {{../crate_name}}{{#unless @last}}, {{/unless}}{{/each}}\n\
+            {{#if summary.regressed}}regressions: {{summary.regressed}}{{else}}clean{{/if}}\n\
+            {{#each missing}}x{{else}}none{{/each}} {{@root.summary.passed}} {{test_results.1.status_label}}";
+        assert_eq!(
+            render(template, &context()).unwrap(),
+            "# rgb\n0. image (passed) for rgb, 1. ravif (build failed) for rgb\nregressions: 1\nnone 2 build failed"
+        );
+    }
+
+    #[test]
+    fn test_render_errors() {
+        assert!(render("{{#each test_results}}x", &context()).unwrap_err().contains("not closed"));
+        assert!(render("{{#if a}}x{{/each}}", &context()).is_err());
+        assert!(render("{{#with a}}x{{/with}}", &context()).unwrap_err().contains("unknown block helper"));
+        assert!(render("{{crate_name", &context()).is_err());
+        assert!(render("x{{/if}}", &context()).is_err());
+    }
+
+    #[test]
+    fn test_output_name() {
+        assert_eq!(output_name(Path::new("templates/notes.md.hbs")), "notes.md");
+        assert_eq!(output_name(Path::new("release.html.tera")), "release.html");
+        assert_eq!(output_name(Path::new("plain.md")), "plain.md");
+    }
+}
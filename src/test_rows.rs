@@ -0,0 +1,200 @@
+/// `OfferedRow`s for unit tests
+///
+/// `row("image")` starts a baseline row for `image` 1.0.0 (`^0.8`, resolved to 0.8.91 from
+/// crates.io) with one passing 1-second check; each method changes one thing about it.
+use crate::types::{
+    CommandResult, CommandType, CrateFailure, DependencyRef, OfferedRow, OfferedVersion, TestCommand, TestExecution,
+    VersionSource,
+};
+
+/// A baseline row for `dependent` with one passing check
+pub fn row(dependent: &str) -> RowBuilder {
+    RowBuilder(OfferedRow {
+        baseline_passed: None,
+        baseline_check_passed: None,
+        primary: DependencyRef {
+            dependent_name: dependent.to_string(),
+            dependent_version: "1.0.0".to_string(),
+            spec: "^0.8".to_string(),
+            resolved_version: "0.8.91".to_string(),
+            resolved_source: VersionSource::CratesIo,
+            used_offered_version: true,
+            fork: None,
+            repository: None,
+            git: None,
+            base_features: None,
+            resolver: None,
+        },
+        offered: None,
+        test: TestExecution {
+            commands: vec![command(CommandType::Check, 1.0)],
+            started: None,
+            toolchain: None,
+            skipped: None,
+        },
+        transitive: vec![],
+    })
+}
+
+fn command(command: CommandType, duration: f64) -> TestCommand {
+    TestCommand {
+        command,
+        features: vec![],
+        result: CommandResult {
+            passed: true,
+            env_failure: None,
+            timed_out: false,
+            duration,
+            failures: vec![],
+            errors: 0,
+            warnings: 0,
+        },
+    }
+}
+
+/// An `OfferedRow` under construction; `build()` finishes it
+pub struct RowBuilder(OfferedRow);
+
+impl RowBuilder {
+    /// Test `version` of the base crate (`None` keeps a baseline row), resolved to it, with
+    /// a passing baseline
+    pub fn offered<'a>(mut self, version: impl Into<Option<&'a str>>) -> Self {
+        if let Some(version) = version.into() {
+            self.0.baseline_passed = Some(true);
+            self.0.baseline_check_passed = Some(true);
+            self.0.primary.resolved_version = version.to_string();
+            self.0.offered =
+                Some(OfferedVersion { version: version.to_string(), forced: false, patch_depth: Default::default() });
+        }
+        self
+    }
+
+    /// Force the offered version past the dependent's requirement
+    pub fn forced(mut self, forced: bool) -> Self {
+        if let Some(offered) = &mut self.0.offered {
+            offered.forced = forced;
+        }
+        self
+    }
+
+    /// Resolve the base crate from a local checkout instead of crates.io
+    pub fn local(mut self) -> Self {
+        self.0.primary.resolved_source = VersionSource::Local;
+        self
+    }
+
+    /// The baseline's outcome: `(check passed, all steps passed)`, `None` for no baseline
+    pub fn baseline(mut self, baseline: Option<(bool, bool)>) -> Self {
+        self.0.baseline_check_passed = baseline.map(|(check, _)| check);
+        self.0.baseline_passed = baseline.map(|(_, passed)| passed);
+        self
+    }
+
+    /// Whether the offered version was the one cargo resolved
+    pub fn used_offered(mut self, used: bool) -> Self {
+        self.0.primary.used_offered_version = used;
+        self
+    }
+
+    /// The dependent's version
+    pub fn version(mut self, version: &str) -> Self {
+        self.0.primary.dependent_version = version.to_string();
+        self
+    }
+
+    /// The dependent's requirement on the base crate
+    pub fn spec(mut self, spec: &str) -> Self {
+        self.0.primary.spec = spec.to_string();
+        self
+    }
+
+    /// The base crate version cargo resolved
+    pub fn resolved(mut self, version: &str) -> Self {
+        self.0.primary.resolved_version = version.to_string();
+        self
+    }
+
+    /// The base crate features the dependent enables
+    pub fn features(mut self, features: &[&str]) -> Self {
+        self.0.primary.base_features = Some(features.iter().map(|f| f.to_string()).collect());
+        self
+    }
+
+    /// Run `steps` (each passing, with its duration) instead of the single check
+    pub fn steps(mut self, steps: &[(CommandType, f64)]) -> Self {
+        self.0.test.commands = steps.iter().map(|(step, duration)| command(*step, *duration)).collect();
+        self
+    }
+
+    /// Run `steps`, each passing or failing, instead of the single check
+    pub fn outcomes(mut self, steps: &[(CommandType, bool)]) -> Self {
+        self.0.test.commands = steps
+            .iter()
+            .map(|(step, passed)| {
+                let mut command = command(*step, 0.0);
+                command.result.passed = *passed;
+                command
+            })
+            .collect();
+        self
+    }
+
+    /// Whether the last step passed
+    pub fn passed(mut self, passed: bool) -> Self {
+        if let Some(last) = self.0.test.commands.last_mut() {
+            last.result.passed = passed;
+        }
+        self
+    }
+
+    /// Fail the last step with `error` in the dependent (an empty `error` leaves it passing)
+    pub fn error(mut self, error: &str) -> Self {
+        if error.is_empty() {
+            return self;
+        }
+        let crate_name = self.0.primary.dependent_name.clone();
+        if let Some(last) = self.0.test.commands.last_mut() {
+            last.result.passed = false;
+            last.result.failures.push(CrateFailure {
+                crate_name,
+                error_message: error.to_string(),
+                raw_stderr: false,
+                error_file: None,
+            });
+        }
+        self
+    }
+
+    /// Mark the last step's failure as the environment's, not the code's
+    pub fn env_failure(mut self, reason: Option<&str>) -> Self {
+        if let Some(last) = self.0.test.commands.last_mut() {
+            last.result.env_failure = reason.map(str::to_string);
+        }
+        self
+    }
+
+    /// Every step's duration in seconds
+    pub fn duration(mut self, duration: f64) -> Self {
+        for command in &mut self.0.test.commands {
+            command.result.duration = duration;
+        }
+        self
+    }
+
+    /// When the dependent's test started (RFC 3339)
+    pub fn started(mut self, started: &str) -> Self {
+        self.0.test.started = Some(started.to_string());
+        self
+    }
+
+    /// The toolchain the steps ran on
+    pub fn toolchain(mut self, toolchain: &str) -> Self {
+        self.0.test.toolchain = Some(toolchain.to_string());
+        self
+    }
+
+    /// The finished row
+    pub fn build(self) -> OfferedRow {
+        self.0
+    }
+}
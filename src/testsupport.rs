@@ -0,0 +1,296 @@
+/// Mock registry for hermetic end-to-end testing
+///
+/// Serves a tiny subset of the crates.io web API from local crate directories:
+///
+/// - `GET /api/v1/crates/{name}` — version list
+/// - `GET /api/v1/crates/{name}/reverse_dependencies` — crates that depend on `name`
+/// - `GET /api/v1/crates?ids[]={name}&…` — those crates' download counts (recent = all-time)
+/// - `GET /api/v1/crates/{name}/{version}/download` — a `.crate` tarball built on the fly
+///
+/// Point cargo-copter at it with `COPTER_REGISTRY_API=<url>` (see `api::REGISTRY_API_ENV`),
+/// or call `api::fetch_all_versions`, `api::fetch_reverse_dependencies_page` and
+/// `download::fetch_crate_to_cache` with its URL directly from tests.
+/// Only the metadata endpoints are mocked; cargo itself still resolves dependencies
+/// from its own configured registry.
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use log::debug;
+use serde_json::json;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// One crate version served by the mock registry
+#[derive(Debug, Clone)]
+pub struct MockCrate {
+    pub name: String,
+    pub version: String,
+    /// Source directory packed into the `.crate` download
+    pub source_dir: PathBuf,
+    pub downloads: u64,
+    pub yanked: bool,
+    /// Names of crates this version depends on (drives reverse dependencies)
+    pub dependencies: Vec<String>,
+}
+
+impl MockCrate {
+    /// Read name, version and dependency names from a crate directory's Cargo.toml
+    pub fn from_dir(dir: &Path) -> Result<Self, String> {
+        let manifest = dir.join("Cargo.toml");
+        let (name, version) = crate::manifest::get_crate_info(&manifest)?;
+        let content = std::fs::read_to_string(&manifest).map_err(|e| format!("{}: {}", manifest.display(), e))?;
+        let toml: toml::Value = toml::from_str(&content).map_err(|e| format!("{}: {}", manifest.display(), e))?;
+        let mut dependencies: Vec<String> = ["dependencies", "dev-dependencies", "build-dependencies"]
+            .iter()
+            .filter_map(|t| toml.get(*t).and_then(|d| d.as_table()))
+            .flat_map(|t| t.keys().cloned())
+            .collect();
+        dependencies.sort();
+        dependencies.dedup();
+        Ok(MockCrate { name, version, source_dir: dir.to_path_buf(), downloads: 0, yanked: false, dependencies })
+    }
+}
+
+/// A running mock registry; the server thread lives for the rest of the process
+pub struct MockRegistry {
+    addr: std::net::SocketAddr,
+}
+
+impl MockRegistry {
+    /// Start serving `crates` on an ephemeral localhost port
+    #[cfg(all(test, feature = "network"))]
+    pub fn start(crates: Vec<MockCrate>) -> Result<Self, String> {
+        Self::start_on("127.0.0.1:0", crates)
+    }
+
+    /// Start serving `crates` on a specific address (e.g. "127.0.0.1:8080")
+    pub fn start_on(addr: &str, crates: Vec<MockCrate>) -> Result<Self, String> {
+        let listener = TcpListener::bind(addr).map_err(|e| format!("Failed to bind {}: {}", addr, e))?;
+        let addr = listener.local_addr().map_err(|e| e.to_string())?;
+        let crates = Arc::new(crates);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let crates = Arc::clone(&crates);
+                std::thread::spawn(move || {
+                    if let Err(e) = handle_connection(stream, &crates) {
+                        debug!("mock registry connection error: {}", e);
+                    }
+                });
+            }
+        });
+        Ok(MockRegistry { addr })
+    }
+
+    /// Serve every crate directory (one level deep) found under `dir`
+    ///
+    /// Pointed at `test-crates/integration-fixtures`, this serves base-crate
+    /// 0.1.0/0.2.0/0.3.0 plus every dependent fixture.
+    #[cfg(all(test, feature = "network"))]
+    pub fn from_fixtures(dir: &Path) -> Result<Self, String> {
+        Self::start(crates_in_dir(dir)?)
+    }
+
+    /// API base URL, suitable for `COPTER_REGISTRY_API`
+    pub fn api_url(&self) -> String {
+        format!("http://{}/api/v1", self.addr)
+    }
+}
+
+/// Every crate directory (one level deep) under `dir`, sorted by name and version
+pub fn crates_in_dir(dir: &Path) -> Result<Vec<MockCrate>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    let mut crates = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.join("Cargo.toml").exists() {
+            crates.push(MockCrate::from_dir(&path)?);
+        }
+    }
+    crates.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    Ok(crates)
+}
+
+/// Run the mock registry in the foreground (the `mock-registry` subcommand)
+pub fn serve(fixtures: Option<&Path>, port: u16) -> Result<(), String> {
+    let dir = fixtures.map(Path::to_path_buf).unwrap_or_else(crate::self_test::default_fixtures_dir);
+    let crates = crates_in_dir(&dir)?;
+    let registry = MockRegistry::start_on(&format!("127.0.0.1:{}", port), crates.clone())?;
+    println!("Mock registry serving {} crate versions from {}", crates.len(), dir.display());
+    for c in &crates {
+        println!("  {} {}", c.name, c.version);
+    }
+    println!("\n  export {}={}\n\nPress Ctrl-C to stop.", crate::api::REGISTRY_API_ENV, registry.api_url());
+    loop {
+        std::thread::park();
+    }
+}
+
+/// Serve one HTTP/1.1 request and close the connection
+fn handle_connection(mut stream: TcpStream, crates: &[MockCrate]) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain headers; we never need them
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+    debug!("mock registry: {}", request_line.trim());
+    let (status, content_type, body) = route(target, crates);
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+    stream.flush()
+}
+
+/// Map a request path to (status line, content type, body)
+fn route(target: &str, crates: &[MockCrate]) -> (&'static str, &'static str, Vec<u8>) {
+    let path = target.split('?').next().unwrap_or(target);
+    if path == "/api/v1/crates" {
+        let query = target.split_once('?').map(|(_, q)| q).unwrap_or_default();
+        let ids: Vec<&str> = query.split('&').filter_map(|p| p.strip_prefix("ids[]=")).collect();
+        let found: Vec<_> = crates
+            .iter()
+            .filter(|c| ids.contains(&c.name.as_str()))
+            .map(|c| json!({"name": c.name, "downloads": c.downloads, "recent_downloads": c.downloads}))
+            .collect();
+        let body = json!({"crates": found, "meta": {"total": found.len()}});
+        return ("200 OK", "application/json", body.to_string().into_bytes());
+    }
+    let Some(rest) = path.strip_prefix("/api/v1/crates/") else {
+        return not_found();
+    };
+    let parts: Vec<&str> = rest.split('/').filter(|p| !p.is_empty()).collect();
+
+    match parts.as_slice() {
+        [name] => {
+            let versions: Vec<_> = crates
+                .iter()
+                .filter(|c| c.name == *name)
+                .map(|c| json!({"crate": c.name, "num": c.version, "downloads": c.downloads, "yanked": c.yanked}))
+                .collect();
+            if versions.is_empty() {
+                return not_found();
+            }
+            let body = json!({"crate": {"id": name, "name": name}, "versions": versions});
+            ("200 OK", "application/json", body.to_string().into_bytes())
+        }
+        [name, "reverse_dependencies"] => {
+            // Everything fits on the first page
+            let first_page = !target.contains("page=") || target.contains("page=1&") || target.ends_with("page=1");
+            let dependents: Vec<_> = crates
+                .iter()
+                .filter(|c| first_page && c.dependencies.iter().any(|d| d == name))
+                .enumerate()
+                .map(|(i, c)| json!({"id": i, "crate": c.name, "num": c.version, "downloads": c.downloads}))
+                .collect();
+            let total = dependents.len();
+            let body = json!({"dependencies": [], "versions": dependents, "meta": {"total": total}});
+            ("200 OK", "application/json", body.to_string().into_bytes())
+        }
+        [name, version, "download"] => match crates.iter().find(|c| c.name == *name && c.version == *version) {
+            Some(c) => match pack_crate(c) {
+                Ok(bytes) => ("200 OK", "application/gzip", bytes),
+                Err(e) => ("500 Internal Server Error", "text/plain", e.to_string().into_bytes()),
+            },
+            None => not_found(),
+        },
+        _ => not_found(),
+    }
+}
+
+fn not_found() -> (&'static str, &'static str, Vec<u8>) {
+    ("404 Not Found", "application/json", br#"{"errors":[{"detail":"Not Found"}]}"#.to_vec())
+}
+
+/// Build a `.crate` archive (`{name}-{version}/...` in a gzipped tar), skipping build output
+pub fn pack_crate(krate: &MockCrate) -> std::io::Result<Vec<u8>> {
+    let prefix = format!("{}-{}", krate.name, krate.version);
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::fast()));
+    append_dir(&mut builder, &krate.source_dir, Path::new(&prefix))?;
+    builder.into_inner()?.finish()
+}
+
+fn append_dir<W: Write>(builder: &mut tar::Builder<W>, dir: &Path, prefix: &Path) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let name = entry.file_name();
+        if name == "target" || name == "Cargo.lock" {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            append_dir(builder, &path, &prefix.join(&name))?;
+        } else {
+            builder.append_path_with_name(&path, prefix.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+// The tests fetch from the mock registry through copter's HTTP client
+#[cfg(all(test, feature = "network"))]
+mod tests {
+    use super::*;
+    use crate::registry::Registry;
+    use crate::{api, download};
+
+    fn fixtures() -> MockRegistry {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test-crates/integration-fixtures");
+        MockRegistry::from_fixtures(&dir).expect("start mock registry")
+    }
+
+    fn lookups(registry: &MockRegistry) -> Registry {
+        Registry::with_api(&registry.api_url())
+    }
+
+    #[test]
+    fn test_versions_endpoint() {
+        let registry = fixtures();
+        let mut versions: Vec<String> = api::fetch_all_versions(&lookups(&registry), "base-crate")
+            .unwrap()
+            .into_iter()
+            .map(|v| v.version)
+            .collect();
+        versions.sort();
+        assert_eq!(versions, vec!["0.1.0", "0.2.0", "0.3.0"]);
+
+        assert!(api::fetch_all_versions(&lookups(&registry), "no-such-crate").is_err());
+    }
+
+    #[test]
+    fn test_reverse_dependencies_endpoint() {
+        let registry = fixtures();
+        let deps = api::fetch_reverse_dependencies_page(&lookups(&registry), "base-crate", 1).unwrap();
+        let names: Vec<&str> = deps.iter().map(|d| d.name.as_str()).collect();
+        assert!(names.contains(&"dependent-passing"));
+        assert!(names.contains(&"transitive-depth-1"));
+        assert!(!names.contains(&"transitive-depth-2"), "only direct dependents are reverse deps");
+    }
+
+    #[test]
+    fn test_download_and_unpack_roundtrip() {
+        let registry = fixtures();
+        let cache = tempfile::tempdir().unwrap();
+        let version = semver::Version::parse("0.2.0").unwrap();
+        let handle = download::fetch_crate_to_cache(&lookups(&registry), cache.path(), "base-crate", &version).unwrap();
+        assert!(handle.path().ends_with("base-crate/base-crate-0.2.0.crate"));
+
+        let dest = tempfile::tempdir().unwrap();
+        handle.unpack_source_to(dest.path()).unwrap();
+        let lib = std::fs::read_to_string(dest.path().join("src/lib.rs")).unwrap();
+        assert!(lib.contains("new_api"));
+        assert_eq!(
+            crate::manifest::get_crate_info(&dest.path().join("Cargo.toml")).unwrap(),
+            ("base-crate".to_string(), "0.2.0".to_string())
+        );
+    }
+}
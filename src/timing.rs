@@ -0,0 +1,152 @@
+/// Where a run's time went: wall clock, cargo steps, and the slowest dependents
+///
+/// The summary and report.json's `timing` section add up every row's step durations
+/// (time spent in cargo, summed across parallel workers) per step, next to the run's
+/// wall clock and the CPU time of the cargo processes it waited for, and list the
+/// dependents that took longest over all their rows. That points caching and
+/// parallelism work at the actual hotspots: a run dominated by `test` on three
+/// dependents wants different fixes than one dominated by `fetch` everywhere.
+///
+/// CPU time is read from `/proc/self/stat` (children's user + system time, which
+/// includes the rustc processes cargo waited for), so it is only known on Linux and
+/// only covers local runs; queue workers are other processes.
+use crate::types::{CommandType, OfferedRow};
+use std::collections::BTreeMap;
+
+/// Dependents listed as the slowest
+pub const SLOWEST_SHOWN: usize = 10;
+
+/// Time accounting for one run, in seconds
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct Timing {
+    /// Wall clock of the test execution
+    pub wall_secs: Option<f64>,
+    /// User + system time of the cargo processes (Linux, local runs)
+    pub cargo_cpu_secs: Option<f64>,
+    /// Sum of all step durations, across parallel workers
+    pub cargo_secs: f64,
+    pub fetch_secs: f64,
+    pub check_secs: f64,
+    pub test_secs: f64,
+    /// Slowest dependents, slowest first (all their rows together)
+    pub slowest: Vec<SlowDependent>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SlowDependent {
+    pub dependent: String,
+    pub version: String,
+    pub secs: f64,
+}
+
+impl Timing {
+    pub fn from_rows(rows: &[OfferedRow], wall_secs: Option<f64>, cargo_cpu_secs: Option<f64>) -> Self {
+        let mut timing = Timing { wall_secs, cargo_cpu_secs, ..Timing::default() };
+        let mut per_dependent: BTreeMap<(&str, &str), f64> = BTreeMap::new();
+        for row in rows {
+            for cmd in &row.test.commands {
+                let secs = cmd.result.duration;
+                match cmd.command {
+                    CommandType::Fetch => timing.fetch_secs += secs,
+                    CommandType::Check => timing.check_secs += secs,
+                    CommandType::Test => timing.test_secs += secs,
+                }
+                timing.cargo_secs += secs;
+                *per_dependent
+                    .entry((row.primary.dependent_name.as_str(), row.primary.dependent_version.as_str()))
+                    .or_default() += secs;
+            }
+        }
+        let mut slowest: Vec<SlowDependent> = per_dependent
+            .into_iter()
+            .map(|((dependent, version), secs)| SlowDependent {
+                dependent: dependent.to_string(),
+                version: version.to_string(),
+                secs,
+            })
+            .collect();
+        slowest.sort_by(|a, b| b.secs.total_cmp(&a.secs));
+        slowest.truncate(SLOWEST_SHOWN);
+        timing.slowest = slowest;
+        timing
+    }
+
+    /// Console summary lines
+    pub fn summary_lines(&self) -> Vec<String> {
+        let mut time = Vec::new();
+        if let Some(wall) = self.wall_secs {
+            time.push(format!("{} wall clock", human(wall)));
+        }
+        time.push(format!(
+            "{} in cargo (fetch {}, check {}, test {})",
+            human(self.cargo_secs),
+            human(self.fetch_secs),
+            human(self.check_secs),
+            human(self.test_secs)
+        ));
+        if let Some(cpu) = self.cargo_cpu_secs {
+            time.push(format!("{} cargo CPU", human(cpu)));
+        }
+        let mut lines = vec![format!("Time: {}", time.join(", "))];
+        if !self.slowest.is_empty() {
+            let slowest: Vec<String> =
+                self.slowest.iter().map(|s| format!("{} {} ({})", s.dependent, s.version, human(s.secs))).collect();
+            lines.push(format!("Slowest dependents: {}", slowest.join(", ")));
+        }
+        lines
+    }
+}
+
+/// "45.2s", "12m04s", "1h02m"
+pub fn human(secs: f64) -> String {
+    let whole = secs.round() as u64;
+    if secs < 60.0 {
+        format!("{:.1}s", secs)
+    } else if whole < 3600 {
+        format!("{}m{:02}s", whole / 60, whole % 60)
+    } else {
+        format!("{}h{:02}m", whole / 3600, whole % 3600 / 60)
+    }
+}
+
+/// User + system CPU seconds of this process's waited-for children, where the OS says
+pub fn children_cpu_secs() -> Option<f64> {
+    // Kernel clock ticks (USER_HZ), 100 on every mainstream Linux configuration
+    const TICKS_PER_SEC: f64 = 100.0;
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // Fields after the parenthesized command name; cutime and cstime are fields 16 and 17
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let cutime: f64 = fields.get(13)?.parse().ok()?;
+    let cstime: f64 = fields.get(14)?.parse().ok()?;
+    Some((cutime + cstime) / TICKS_PER_SEC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(dependent: &str, durations: &[(CommandType, f64)]) -> OfferedRow {
+        crate::test_rows::row(dependent).steps(durations).build()
+    }
+
+    #[test]
+    fn test_time_per_step_and_slowest_dependents() {
+        let rows = vec![
+            row("image", &[(CommandType::Fetch, 1.0), (CommandType::Check, 20.0), (CommandType::Test, 100.0)]),
+            row("png", &[(CommandType::Fetch, 2.0), (CommandType::Check, 5.0)]),
+            row("image", &[(CommandType::Fetch, 0.5), (CommandType::Check, 10.0), (CommandType::Test, 90.0)]),
+        ];
+        let timing = Timing::from_rows(&rows, Some(130.0), None);
+        assert_eq!((timing.fetch_secs, timing.check_secs, timing.test_secs), (3.5, 35.0, 190.0));
+        assert_eq!(timing.cargo_secs, 228.5);
+        assert_eq!(timing.slowest[0].dependent, "image");
+        assert_eq!(timing.slowest[0].secs, 221.5);
+        assert_eq!(
+            timing.summary_lines(),
+            vec![
+                "Time: 2m10s wall clock, 3m49s in cargo (fetch 3.5s, check 35.0s, test 3m10s)",
+                "Slowest dependents: image 1.0.0 (3m42s), png 1.0.0 (7.0s)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,152 @@
+/// `--toolchains`: the whole matrix once per rustup toolchain
+///
+/// A new version can build fine on stable and still break the dependents that promise an
+/// older rustc: a newly stabilized std API, a let-chain, an edition bump. With `--toolchains
+/// 1.70,1.75,stable` the matrix, baselines included, runs once per toolchain with every
+/// cargo step as `cargo +1.70 …`, and each row carries its toolchain (`image 0.25.8 +1.70`
+/// in the tables, `test.toolchain` in report.json). A dependent whose baseline builds on
+/// 1.70 but regresses there with the offered version has had its MSRV raised; the run ends
+/// with one regression line per toolchain. Every toolchain is checked before anything is
+/// built, so a missing one stops the run right away.
+///
+/// `--pin-toolchain 1.84.0` runs the matrix once on one exact toolchain instead, installing
+/// it with rustup (minimal profile) when it's missing. A channel like `stable` names a
+/// different compiler from one week to the next, so only releases and dated nightlies or
+/// betas are taken. The toolchain and its `rustc -V` go into the run metadata of every
+/// report, so results from different machines or months say what they were built with.
+use crate::error::CopterError;
+use crate::types::{OfferedRow, TestMatrix, TestResult};
+use std::process::Command;
+
+/// Run `matrix` through `run_pass` once per toolchain, streaming every row to `on_result`
+pub fn run<P>(
+    matrix: &TestMatrix,
+    toolchains: &[String],
+    run_pass: &mut P,
+    on_result: &mut dyn FnMut(&TestResult),
+) -> Result<Vec<TestResult>, CopterError>
+where
+    P: FnMut(&TestMatrix, &mut dyn FnMut(&TestResult)) -> Result<Vec<TestResult>, CopterError>,
+{
+    for toolchain in toolchains {
+        check_installed(toolchain)?;
+    }
+    let mut results = Vec::new();
+    for toolchain in toolchains {
+        let mut pass = matrix.clone();
+        pass.cross.toolchain = Some(toolchain.clone());
+        eprintln!("Toolchain {}: all {} dependents", toolchain, matrix.dependents.len());
+        results.extend(run_pass(&pass, on_result)?);
+    }
+    Ok(results)
+}
+
+fn check_installed(toolchain: &str) -> Result<(), CopterError> {
+    let rustc = Command::new("rustc").arg(format!("+{}", toolchain)).arg("-V").output();
+    if rustc.is_ok_and(|o| o.status.success()) {
+        return Ok(());
+    }
+    Err(CopterError::Config(format!(
+        "--toolchains: {} is not installed (rustup toolchain install {})",
+        toolchain, toolchain
+    )))
+}
+
+/// `--pin-toolchain`: check that `toolchain` is exact and install it if needed
+pub fn pin(toolchain: &str) -> Result<(), CopterError> {
+    if !is_exact(toolchain) {
+        return Err(CopterError::Config(format!(
+            "--pin-toolchain: {} is not an exact toolchain; name a release like 1.84.0 or a dated \
+             nightly like nightly-2025-01-09",
+            toolchain
+        )));
+    }
+    install(toolchain)
+}
+
+/// Install `toolchain` with rustup unless it is already there
+pub fn install(toolchain: &str) -> Result<(), CopterError> {
+    if check_installed(toolchain).is_ok() {
+        return Ok(());
+    }
+    eprintln!("Installing toolchain {} (rustup, minimal profile)", toolchain);
+    let status = Command::new("rustup")
+        .args(["toolchain", "install", toolchain, "--profile", "minimal", "--no-self-update"])
+        .status()
+        .map_err(|e| CopterError::Config(format!("cannot run rustup to install {}: {}", toolchain, e)))?;
+    if !status.success() {
+        return Err(CopterError::Config(format!("rustup could not install toolchain {}", toolchain)));
+    }
+    check_installed(toolchain)
+}
+
+/// A release (`1.84.0`) or a dated `nightly`/`beta`, optionally for a host triple
+fn is_exact(toolchain: &str) -> bool {
+    let numbers = |text: &str, parts: usize, sep: char| {
+        let split: Vec<&str> = text.split(sep).collect();
+        split.len() == parts && split.iter().all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+    };
+    let mut segments = toolchain.splitn(2, '-');
+    match (segments.next(), segments.next()) {
+        (Some(release), _) if numbers(release, 3, '.') => true,
+        (Some("nightly" | "beta"), Some(rest)) => rest.get(..10).is_some_and(|date| numbers(date, 3, '-')),
+        _ => false,
+    }
+}
+
+/// `rustc -V` of `toolchain`
+pub fn rustc_version(toolchain: &str) -> Option<String> {
+    let output = Command::new("rustc").arg(format!("+{}", toolchain)).arg("-V").output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// "1.70: 2 regressed (image 0.25.8, png 0.17.1)", one line per toolchain
+pub fn summary_lines(rows: &[OfferedRow], toolchains: &[String]) -> Vec<String> {
+    toolchains
+        .iter()
+        .map(|toolchain| {
+            let regressed: Vec<String> = rows
+                .iter()
+                .filter(|row| row.test.toolchain.as_ref() == Some(toolchain) && row.is_regression())
+                .map(|row| row.primary.dependent_label())
+                .collect();
+            match regressed.len() {
+                0 => format!("{}: no regressions", toolchain),
+                n => format!("{}: {} regressed ({})", toolchain, n, regressed.join(", ")),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(dependent: &str, toolchain: &str, passed: bool) -> OfferedRow {
+        crate::test_rows::row(dependent).offered("0.8.92").passed(passed).toolchain(toolchain).build()
+    }
+
+    #[test]
+    fn test_regressions_per_toolchain() {
+        let rows = [row("image", "1.70", false), row("png", "1.70", false), row("image", "stable", true)];
+        let toolchains = ["1.70".to_string(), "stable".to_string()];
+        assert_eq!(
+            summary_lines(&rows, &toolchains),
+            ["1.70: 2 regressed (image 1.0.0, png 1.0.0)", "stable: no regressions"]
+        );
+        assert_eq!(rows[0].test.toolchain_suffix(), " +1.70");
+        assert!(check_installed("copter-no-such-toolchain").is_err());
+    }
+
+    #[test]
+    fn test_pin_takes_exact_toolchains_only() {
+        for exact in ["1.84.0", "1.84.0-x86_64-unknown-linux-gnu", "nightly-2025-01-09", "beta-2025-01-09"] {
+            assert!(is_exact(exact), "{}", exact);
+        }
+        for moving in ["stable", "1.84", "nightly", "nightly-x86_64-unknown-linux-gnu", "beta-2025-01"] {
+            assert!(!is_exact(moving), "{}", moving);
+        }
+        let err = pin("stable").unwrap_err();
+        assert!(err.to_string().contains("not an exact toolchain"), "{}", err);
+    }
+}
@@ -0,0 +1,336 @@
+/// `history export --format html`: trends across recorded runs, as one HTML page
+///
+/// The outcome log (`history/outcomes.jsonl`) has every row of every run. This page
+/// turns it into what the terminal can't show: for each base crate, a pass-rate chart
+/// per version line (`0.8`, `1`, ...) over the runs, and a grid of dependents × runs
+/// colored by each dependent's worst status in that run. When a dependent goes from
+/// anything else to regressed, the cell is marked and listed with the base version (and
+/// for a local WIP, the `git describe` commit) it regressed with, so the change that
+/// introduced it is one look away. Rows recorded before runs were timestamped are left
+/// out. The page is self-contained: inline CSS and SVG, no scripts.
+use crate::flakiness::Outcome;
+use crate::messages::Catalog;
+use crate::types::Severity;
+use std::collections::BTreeMap;
+
+const CHART_WIDTH: f64 = 640.0;
+const CHART_HEIGHT: f64 = 140.0;
+
+/// One base crate's history
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trend {
+    pub base_crate: String,
+    /// Finish times of the runs, oldest first
+    pub runs: Vec<String>,
+    /// `--label` of each run
+    pub labels: Vec<Option<String>>,
+    /// Version line → (passed, total) per run
+    pub lines: BTreeMap<String, Vec<(usize, usize)>>,
+    /// Dependent → worst result per run
+    pub dependents: BTreeMap<String, Vec<Option<Cell>>>,
+    /// Regressions, in the run where each one appeared
+    pub introduced: Vec<Introduced>,
+}
+
+/// A dependent's worst row in one run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    pub severity: Severity,
+    pub label: String,
+    /// Base crate version of that row
+    pub base: String,
+    pub commit: Option<String>,
+}
+
+/// A dependent that regressed in a run after not regressing in the one before
+#[derive(Debug, Clone, PartialEq)]
+pub struct Introduced {
+    pub dependent: String,
+    pub run: usize,
+    pub base: String,
+    pub commit: Option<String>,
+}
+
+impl Trend {
+    /// `outcomes` of one base crate, in log order, statuses labeled in `messages`
+    pub fn new(base_crate: &str, outcomes: &[&Outcome], messages: &Catalog) -> Self {
+        let mut runs: Vec<String> = Vec::new();
+        let mut labels = Vec::new();
+        for o in outcomes.iter().filter(|o| !o.run.is_empty()) {
+            if !runs.contains(&o.run) {
+                runs.push(o.run.clone());
+                labels.push(o.label.clone());
+            }
+        }
+        let mut trend = Trend {
+            base_crate: base_crate.to_string(),
+            runs,
+            labels,
+            lines: BTreeMap::new(),
+            dependents: BTreeMap::new(),
+            introduced: vec![],
+        };
+        for o in outcomes {
+            let Some(run) = trend.runs.iter().position(|r| *r == o.run) else { continue };
+            let n = trend.runs.len();
+            let line = trend.lines.entry(version_line(&o.base)).or_insert_with(|| vec![(0, 0); n]);
+            line[run].0 += usize::from(o.passed);
+            line[run].1 += 1;
+
+            let cell = Cell {
+                severity: o.status.map_or(if o.passed { Severity::Ok } else { Severity::Broken }, |s| s.severity()),
+                label: o
+                    .status
+                    .map_or_else(|| if o.passed { "passed" } else { "failed" }.to_string(), |s| s.label(messages)),
+                base: o.base.clone(),
+                commit: o.commit.clone(),
+            };
+            let cells = trend.dependents.entry(o.dependent.clone()).or_insert_with(|| vec![None; n]);
+            if cells[run].as_ref().is_none_or(|worst| cell.severity > worst.severity) {
+                cells[run] = Some(cell);
+            }
+        }
+        for (dependent, cells) in &trend.dependents {
+            let mut regressed_before = false;
+            for (run, cell) in cells.iter().enumerate() {
+                let Some(cell) = cell else { continue };
+                let regressed = cell.severity == Severity::Regression;
+                if regressed && !regressed_before {
+                    trend.introduced.push(Introduced {
+                        dependent: dependent.clone(),
+                        run,
+                        base: cell.base.clone(),
+                        commit: cell.commit.clone(),
+                    });
+                }
+                regressed_before = regressed;
+            }
+        }
+        trend.introduced.sort_by_key(|i| i.run);
+        trend
+    }
+
+    fn is_introduced(&self, dependent: &str, run: usize) -> bool {
+        self.introduced.iter().any(|i| i.dependent == dependent && i.run == run)
+    }
+
+    fn html_section(&self) -> String {
+        let mut out = format!(
+            "<h2>{}</h2>\n<p>{} runs, {} dependents</p>\n",
+            esc(&self.base_crate),
+            self.runs.len(),
+            self.dependents.len()
+        );
+
+        out.push_str("<h3>Pass rate by version line</h3>\n");
+        for (line, points) in &self.lines {
+            out.push_str(&format!("<h4>{} {}</h4>\n{}", esc(&self.base_crate), esc(line), self.chart(points)));
+        }
+
+        if !self.introduced.is_empty() {
+            out.push_str("<h3>Regressions introduced</h3>\n<ul>\n");
+            for i in &self.introduced {
+                let commit = i.commit.as_deref().map(|c| format!(" ({})", esc(c))).unwrap_or_default();
+                out.push_str(&format!(
+                    "<li><b>{}</b> regressed in the run of {} with {}{}</li>\n",
+                    esc(&i.dependent),
+                    esc(&run_label(&self.runs[i.run])),
+                    esc(&i.base),
+                    commit
+                ));
+            }
+            out.push_str("</ul>\n");
+        }
+
+        out.push_str("<h3>Dependents</h3>\n<table>\n<tr><th>Dependent</th>");
+        for (run, label) in self.runs.iter().zip(&self.labels) {
+            let label = label.as_deref().map(|l| format!("<br>{}", esc(l))).unwrap_or_default();
+            out.push_str(&format!("<th class=\"run\">{}{}</th>", esc(&run_label(run)), label));
+        }
+        out.push_str("</tr>\n");
+        for (dependent, cells) in &self.dependents {
+            out.push_str(&format!("<tr><td>{}</td>", esc(dependent)));
+            for (run, cell) in cells.iter().enumerate() {
+                match cell {
+                    None => out.push_str("<td></td>"),
+                    Some(cell) => {
+                        let marker = if self.is_introduced(dependent, run) { "▲" } else { "" };
+                        out.push_str(&format!(
+                            "<td class=\"{}\" title=\"{} with {}\">{}</td>",
+                            severity_class(cell.severity),
+                            esc(&cell.label),
+                            esc(&cell.base),
+                            marker
+                        ));
+                    }
+                }
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+        out
+    }
+
+    /// Pass percentage per run as an SVG line chart
+    fn chart(&self, points: &[(usize, usize)]) -> String {
+        let step = if self.runs.len() > 1 { CHART_WIDTH / (self.runs.len() - 1) as f64 } else { 0.0 };
+        let coords: Vec<(f64, f64, String)> = points
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, total))| *total > 0)
+            .map(|(run, (passed, total))| {
+                let rate = *passed as f64 / *total as f64;
+                let tooltip = format!("{}: {}/{} passed", run_label(&self.runs[run]), passed, total);
+                (10.0 + run as f64 * step, 10.0 + (1.0 - rate) * CHART_HEIGHT, tooltip)
+            })
+            .collect();
+        let polyline: Vec<String> = coords.iter().map(|(x, y, _)| format!("{:.1},{:.1}", x, y)).collect();
+        let mut svg = format!(
+            "<svg width=\"{}\" height=\"{}\" role=\"img\">\n\
+             <line x1=\"10\" y1=\"10\" x2=\"{}\" y2=\"10\" class=\"grid\"/><text x=\"{}\" y=\"14\">100%</text>\n\
+             <line x1=\"10\" y1=\"{}\" x2=\"{}\" y2=\"{}\" class=\"grid\"/><text x=\"{}\" y=\"{}\">0%</text>\n\
+             <polyline points=\"{}\"/>\n",
+            CHART_WIDTH + 60.0,
+            CHART_HEIGHT + 20.0,
+            CHART_WIDTH + 10.0,
+            CHART_WIDTH + 15.0,
+            CHART_HEIGHT + 10.0,
+            CHART_WIDTH + 10.0,
+            CHART_HEIGHT + 10.0,
+            CHART_WIDTH + 15.0,
+            CHART_HEIGHT + 14.0,
+            polyline.join(" ")
+        );
+        for (x, y, tooltip) in &coords {
+            svg.push_str(&format!(
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\"><title>{}</title></circle>\n",
+                x,
+                y,
+                esc(tooltip)
+            ));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// The whole page: one section per base crate in `outcomes`
+pub fn html(outcomes: &[Outcome], messages: &Catalog) -> String {
+    let mut by_crate: BTreeMap<&str, Vec<&Outcome>> = BTreeMap::new();
+    for o in outcomes {
+        by_crate.entry(o.base_crate.as_str()).or_default().push(o);
+    }
+    let names: Vec<&str> = by_crate.keys().copied().collect();
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>cargo-copter history: {}</title>\n\
+         <style>\n{}</style>\n</head>\n<body>\n<h1>cargo-copter history</h1>\n",
+        esc(&names.join(", ")),
+        STYLE
+    );
+    for (base_crate, outcomes) in &by_crate {
+        out.push_str(&Trend::new(base_crate, outcomes, messages).html_section());
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; font-size: 13px; }
+td, th { border: 1px solid #ddd; padding: 2px 6px; text-align: center; }
+td:first-child { text-align: left; }
+th.run { writing-mode: vertical-rl; font-weight: normal; }
+td.ok { background: #c8e6c9; }
+td.notice { background: #eeeeee; }
+td.broken { background: #ffe0b2; }
+td.regression { background: #ef9a9a; }
+svg polyline { fill: none; stroke: #1976d2; stroke-width: 2; }
+svg circle { fill: #1976d2; }
+svg .grid { stroke: #ccc; }
+svg text { font-size: 11px; fill: #666; }
+";
+
+fn severity_class(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Ok => "ok",
+        Severity::Notice => "notice",
+        Severity::Broken => "broken",
+        Severity::Regression => "regression",
+    }
+}
+
+/// "0.8" for "0.8.92+1a2b" (local WIP) or "0.8.91"
+fn version_line(base: &str) -> String {
+    semver::Version::parse(base).map(|v| crate::version::compat_line(&v)).unwrap_or_else(|_| base.to_string())
+}
+
+/// "2026-10-16 09:00" from an RFC 3339 time
+fn run_label(run: &str) -> String {
+    run.get(..16).unwrap_or(run).replace('T', " ")
+}
+
+fn esc(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CommandType, RowStatus};
+
+    fn outcome(run: &str, dependent: &str, base: &str, status: RowStatus) -> Outcome {
+        Outcome {
+            base_crate: "rgb".to_string(),
+            base: base.to_string(),
+            dependent: dependent.to_string(),
+            dependent_version: "1.0.0".to_string(),
+            passed: !status.is_failure(),
+            run: run.to_string(),
+            status: Some(status),
+            duration: 1.0,
+            commit: base.contains('+').then(|| "v0.8.91-3-gabc1234".to_string()),
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_pass_rates_and_introduced_regressions() {
+        let regressed = RowStatus::Regressed { step: CommandType::Check };
+        let (first, second) = ("2026-10-01T09:00:00+02:00", "2026-10-08T09:00:00+02:00");
+        let outcomes = vec![
+            outcome(first, "image", "0.8.91", RowStatus::Passed),
+            outcome(first, "image", "0.8.92+1a2b", RowStatus::Passed),
+            outcome(first, "png", "0.8.91", RowStatus::Passed),
+            outcome(second, "image", "0.8.91", RowStatus::Passed),
+            outcome(second, "image", "0.8.92+3c4d", regressed),
+            outcome(second, "png", "0.8.91", RowStatus::Passed),
+            outcome(second, "png", "0.9.0", regressed),
+            // From before runs were timestamped
+            outcome("", "png", "0.8.91", RowStatus::Passed),
+        ];
+        let refs: Vec<&Outcome> = outcomes.iter().collect();
+        let trend = Trend::new("rgb", &refs, &Catalog::default());
+
+        assert_eq!(trend.runs, [first, second]);
+        assert_eq!(trend.lines["0.8"], [(3, 3), (2, 3)]);
+        assert_eq!(trend.lines["0.9"], [(0, 0), (0, 1)]);
+        assert_eq!(trend.dependents["image"][1].as_ref().unwrap().severity, Severity::Regression);
+        assert_eq!(
+            trend.introduced[0],
+            Introduced {
+                dependent: "image".to_string(),
+                run: 1,
+                base: "0.8.92+3c4d".to_string(),
+                commit: Some("v0.8.91-3-gabc1234".to_string()),
+            }
+        );
+        assert_eq!(trend.introduced.len(), 2);
+
+        let page = html(&outcomes, &Catalog::default());
+        assert!(
+            page.contains(
+                "<b>image</b> regressed in the run of 2026-10-08 09:00 with 0.8.92+3c4d (v0.8.91-3-gabc1234)"
+            )
+        );
+        assert!(page.contains("<title>2026-10-08 09:00: 2/3 passed</title>"));
+    }
+}
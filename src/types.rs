@@ -30,9 +30,33 @@ pub struct OfferedRow {
 }
 
 impl OfferedRow {
-    /// Check if this is a regression (baseline passed but offered failed)
+    /// Check if this is a regression (see `RowStatus::from_row` for the step-level rules)
     pub fn is_regression(&self) -> bool {
-        matches!(self.baseline_passed, Some(true)) && !self.test_passed()
+        self.status().is_regression()
+    }
+
+    /// Classify this row for reporting
+    pub fn status(&self) -> RowStatus {
+        RowStatus::from_row(self)
+    }
+
+    /// The packages that kept the dependent on another version of the base crate even after
+    /// the deep patch (`!!!`), empty for every other row
+    pub fn blocking_crates(&self) -> Vec<crate::metadata::Blocker> {
+        let deep = self.offered.as_ref().is_some_and(|o| o.patch_depth == crate::compile::PatchDepth::DeepPatch);
+        if !deep {
+            return vec![];
+        }
+        self.transitive
+            .iter()
+            .map(|t| crate::metadata::Blocker {
+                crate_name: t.dependency.dependent_name.clone(),
+                version: t.dependency.dependent_version.clone(),
+                spec: t.dependency.spec.clone(),
+                resolved: t.dependency.resolved_version.clone(),
+                path: t.path.clone(),
+            })
+            .collect()
     }
 
     /// Check if all test commands passed
@@ -55,6 +79,57 @@ pub struct DependencyRef {
     pub resolved_version: String,       // "0.8.91" (what cargo chose)
     pub resolved_source: VersionSource, // CratesIo | Local | Git
     pub used_offered_version: bool,     // true if resolved == offered
+    /// "git https://..." or "path vendor/rgb" when the dependent builds against its own copy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fork: Option<String>,
+    /// The dependent's repository, from its manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// "URL#commit" of a dependent tested from git (`--dependent-git`) instead of crates.io
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    /// Features of the base crate enabled in the dependent's build, from its resolve graph
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_features: Option<Vec<String>>,
+    /// Cargo resolver the dependent built with: "1", "2", "3", or "2 (forced from 1)"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolver: Option<String>,
+}
+
+impl DependencyRef {
+    /// The dependent's crates.io page at the version tested
+    pub fn crates_io_url(&self) -> String {
+        format!("https://crates.io/crates/{}/{}", self.dependent_name, self.dependent_version)
+    }
+
+    /// The dependent's documentation at the version tested
+    pub fn docs_rs_url(&self) -> String {
+        format!("https://docs.rs/{}/{}", self.dependent_name, self.dependent_version)
+    }
+
+    /// "image 0.25.8", with 🔀 for a dependent tested from git
+    pub fn dependent_label(&self) -> String {
+        match self.git {
+            Some(_) => format!("{} {} 🔀", self.dependent_name, self.dependent_version),
+            None => format!("{} {}", self.dependent_name, self.dependent_version),
+        }
+    }
+
+    /// "[image 0.25.8](crates.io) ([docs](docs.rs), [repo](repository))" for markdown tables;
+    /// a git dependent links to its repository at the commit tested
+    pub fn markdown_links(&self) -> String {
+        if let Some(git) = &self.git {
+            let (url, commit) = git.split_once('#').unwrap_or((git, ""));
+            let url = url.trim_end_matches(".git");
+            let link = if commit.is_empty() { url.to_string() } else { format!("{}/tree/{}", url, commit) };
+            return format!("[{}]({})", self.dependent_label(), link);
+        }
+        let mut links = vec![format!("[docs]({})", self.docs_rs_url())];
+        if let Some(repository) = &self.repository {
+            links.push(format!("[repo]({})", repository));
+        }
+        format!("[{} {}]({}) ({})", self.dependent_name, self.dependent_version, self.crates_io_url(), links.join(", "))
+    }
 }
 
 /// Version offered for testing
@@ -69,12 +144,26 @@ pub struct OfferedVersion {
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct TestExecution {
     pub commands: Vec<TestCommand>, // fetch, check, test
+    /// Local time the first step started, RFC 3339
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started: Option<String>,
+    /// rustup toolchain the steps ran with (`--toolchains`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<String>,
+    /// Why no step ran, for a skipped row
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
 }
 
 impl TestExecution {
     /// Create a new empty test execution
     pub fn new() -> Self {
-        Self { commands: Vec::new() }
+        Self { commands: Vec::new(), started: None, toolchain: None, skipped: None }
+    }
+
+    /// " +1.70" after the dependent's name for a `--toolchains` row, else empty
+    pub fn toolchain_suffix(&self) -> String {
+        self.toolchain.as_deref().map_or_else(String::new, |toolchain| format!(" +{}", toolchain))
     }
 
     /// Add a test command result
@@ -123,14 +212,145 @@ impl CommandType {
             CommandType::Test => "test",
         }
     }
+
+    /// User-facing name for the step ("fetch", "build", "test")
+    pub fn verb(&self) -> &'static str {
+        match self {
+            CommandType::Fetch => "fetch",
+            CommandType::Check => "build",
+            CommandType::Test => "test",
+        }
+    }
+
+    /// Message catalog key for the step name shown in status labels
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            CommandType::Fetch => "step.fetch",
+            CommandType::Check => "step.check",
+            CommandType::Test => "step.test",
+        }
+    }
+}
+
+impl std::str::FromStr for CommandType {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Steps::PIPELINE
+            .iter()
+            .copied()
+            .find(|s| s.as_str() == name)
+            .ok_or_else(|| format!("unknown step '{}' (expected fetch, check or test)", name))
+    }
+}
+
+/// Ordered list of ICT steps a run executes (`--steps fetch,check`)
+///
+/// Each step needs the ones before it, so the list is always a prefix of
+/// fetch → check → test; `parse` rejects anything else.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Steps(Vec<CommandType>);
+
+impl Steps {
+    /// Every step in pipeline order
+    pub const PIPELINE: [CommandType; 3] = [CommandType::Fetch, CommandType::Check, CommandType::Test];
+
+    /// Fetch, check and test
+    pub fn all() -> Self {
+        Steps(Self::PIPELINE.to_vec())
+    }
+
+    /// Run the pipeline up to and including `last`
+    pub fn through(last: CommandType) -> Self {
+        Steps(Self::PIPELINE.iter().copied().take_while(|s| *s != last).chain([last]).collect())
+    }
+
+    /// Parse a comma-separated list such as "fetch,check"
+    pub fn parse(list: &str) -> Result<Self, String> {
+        let mut steps = Vec::new();
+        for name in list.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            steps.push(name.parse()?);
+        }
+        if steps.is_empty() {
+            return Err("at least one step is required".to_string());
+        }
+        if steps[..] != Self::PIPELINE[..steps.len()] {
+            return Err(format!(
+                "'{}' is not a valid step list: steps run in order fetch,check,test and each needs the ones before it",
+                list
+            ));
+        }
+        Ok(Steps(steps))
+    }
+
+    /// Whether `step` is part of this run
+    pub fn runs(&self, step: CommandType) -> bool {
+        self.0.contains(&step)
+    }
+
+    /// Last step executed
+    pub fn last(&self) -> CommandType {
+        *self.0.last().expect("Steps is never empty")
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = CommandType> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl Default for Steps {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl std::str::FromStr for Steps {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Steps::parse(s)
+    }
+}
+
+impl TryFrom<String> for Steps {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Steps::parse(&s)
+    }
+}
+
+impl From<Steps> for String {
+    fn from(steps: Steps) -> Self {
+        steps.to_string()
+    }
+}
+
+impl std::fmt::Display for Steps {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<&str> = self.0.iter().map(|s| s.as_str()).collect();
+        write!(f, "{}", names.join(","))
+    }
 }
 
 /// Result of executing a command
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct CommandResult {
     pub passed: bool,
+    /// Reason the failure is attributed to the environment rather than the dependent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_failure: Option<String>,
+    /// Killed at its time limit
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
     pub duration: f64,
     pub failures: Vec<CrateFailure>, // Which crate(s) failed
+    /// Compiler errors and warnings cargo reported (summaries like "3 warnings emitted" not counted)
+    #[serde(default)]
+    pub errors: usize,
+    #[serde(default)]
+    pub warnings: usize,
 }
 
 /// A crate that failed during testing
@@ -138,6 +358,13 @@ pub struct CommandResult {
 pub struct CrateFailure {
     pub crate_name: String,
     pub error_message: String,
+    /// `error_message` is raw stderr: cargo printed no compiler errors
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub raw_stderr: bool,
+    /// Where report.json keeps the whole message when it was cut (`--json-error-limit`),
+    /// relative to the report directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_file: Option<String>,
 }
 
 /// Transitive dependency test (depth > 0)
@@ -145,6 +372,9 @@ pub struct CrateFailure {
 pub struct TransitiveTest {
     pub dependency: DependencyRef,
     pub depth: usize,
+    /// Package names from the dependent down to this one, when known from the resolve graph
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path: Vec<String>,
 }
 
 /// Source of a version (crates.io, local, or git)
@@ -222,8 +452,8 @@ pub enum CrateSource {
     Registry,
     /// Local filesystem path
     Local { path: std::path::PathBuf },
-    /// Git repository
-    Git { url: String, rev: Option<String> },
+    /// Git repository, checked out at `rev` in `path`
+    Git { url: String, rev: Option<String>, path: std::path::PathBuf },
 }
 
 impl CrateSource {
@@ -325,14 +555,81 @@ pub struct TestMatrix {
     /// Staging directory for builds
     pub staging_dir: std::path::PathBuf,
 
-    /// Test execution flags
-    pub skip_check: bool,
-    pub skip_test: bool,
+    /// ICT steps to run for every pair
+    pub steps: Steps,
     pub error_lines: usize,
 
     /// Patch transitive dependencies using [patch.crates-io] in Cargo.toml
     /// This unifies all versions of the base crate across the dependency tree
     pub patch_transitive: bool,
+
+    /// Minimum free disk space (bytes) to keep in the staging directory; 0 disables
+    pub min_free_bytes: u64,
+
+    /// Stall warnings for silent cargo steps
+    #[serde(default)]
+    pub heartbeat: crate::compile::heartbeat::Heartbeat,
+
+    /// `--target` / `--runner`
+    #[serde(default)]
+    pub cross: crate::compile::target::CrossSettings,
+
+    /// `--nightly-gates`: every step on nightly with the base crate's gated features on
+    #[serde(default)]
+    pub nightly_gates: Option<crate::compile::nightly::NightlyGates>,
+
+    /// `--dependent-patches`: what to do with dependents' own `[patch]` and `.cargo/config.toml` overrides
+    #[serde(default)]
+    pub dependent_patches: crate::compile::dependent_patches::DependentPatches,
+
+    /// `--simulate-update`: test offered versions only where `cargo update` would pick them
+    #[serde(default)]
+    pub simulate_update: bool,
+
+    /// `--force-resolver-2`: resolver 1 dependents build with resolver 2
+    #[serde(default)]
+    pub force_resolver: bool,
+
+    /// `--toolchains`: the matrix runs once per rustup toolchain
+    #[serde(default)]
+    pub toolchains: Vec<String>,
+
+    /// Quarantined dependents left out of the run; each gets skipped rows
+    #[serde(default)]
+    pub quarantined: Vec<String>,
+
+    /// `stage-publish`: the local WIP rows resolve the base crate from this registry
+    #[serde(default)]
+    pub staging_registry: Option<crate::stage_publish::StagingRegistry>,
+
+    /// Per-dependent settings from copter.toml, by crate name
+    #[serde(default)]
+    pub dependent_settings: std::collections::BTreeMap<String, crate::project_config::DependentSettings>,
+
+    /// `--top-dependents`: how many dependents the base crate has, and how many were taken
+    #[serde(default)]
+    pub population: Option<crate::api::Population>,
+
+    /// Reuse baseline results saved by earlier runs (off with `--no-baseline-cache`)
+    #[serde(default)]
+    pub baseline_cache: bool,
+
+    /// `--staging-copy`: how local and cached sources are copied into staging
+    #[serde(default)]
+    pub staging_copy: crate::staging::CopyMethod,
+
+    /// `--refresh` / `--cache-ttl`: when cached registry sources are downloaded again
+    #[serde(default)]
+    pub source_cache: crate::source_cache::Policy,
+
+    /// `--index-url` / `--offline`: where registry lookups and downloads go. Not saved with
+    /// queued jobs; a queue worker reads its own registry from its command line
+    #[serde(skip)]
+    pub registry: crate::registry::Registry,
+
+    /// Where the dependents' manifest rewrites are recorded (main sets it; off by default)
+    #[serde(skip)]
+    pub audit: crate::audit::AuditLog,
 }
 
 impl TestMatrix {
@@ -389,6 +686,182 @@ pub enum TestStatus {
     StillBroken,
 }
 
+/// How bad a row status is; drives summary buckets and colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Works with the offered version
+    Ok,
+    /// Nothing to act on (not tested, or tests that already failed on baseline)
+    Notice,
+    /// Fails, but not because of the offered version
+    Broken,
+    /// Worked on baseline, fails with the offered version
+    Regression,
+}
+
+/// Status of one report row, shared by the table, simple, markdown and JSON outputs
+///
+/// `step` is the first step that failed for this row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RowStatus {
+    /// All steps passed
+    Passed,
+    /// Baseline failed, offered version passes
+    Fixed,
+    /// Baseline passed this step, offered version fails it
+    Regressed { step: CommandType },
+    /// Baseline row that fails (the reference point for offered rows)
+    Broken { step: CommandType },
+    /// Baseline was already failing and the offered version fails too
+    StillBroken { step: CommandType },
+    /// Failed with no baseline to compare against
+    Failed { step: CommandType },
+    /// Offered version wasn't selected by cargo, so it wasn't tested
+    NotUsed,
+    /// The dependent builds against its own git or path copy of the crate, which no release reaches
+    UsesFork,
+    /// Steps were not run
+    Skipped,
+    /// A step exceeded its time limit
+    TimedOut { step: CommandType },
+    /// A step failed for environmental reasons (network, disk, toolchain)
+    EnvBroken { step: CommandType },
+}
+
+impl RowStatus {
+    /// Classify a row against its baseline
+    ///
+    /// A fetch/check failure counts as a regression whenever the baseline got
+    /// past that step, even if the baseline's tests failed.
+    pub fn from_row(row: &OfferedRow) -> Self {
+        let first_failure = row.test.first_failure();
+        let failed_step = first_failure.map(|cmd| cmd.command);
+
+        // A step killed at its time limit proves nothing either way
+        if let Some(cmd) = first_failure
+            && cmd.result.timed_out
+        {
+            return RowStatus::TimedOut { step: cmd.command };
+        }
+
+        // Harness/environment problems are never the dependent's or the offered version's fault
+        if let Some(cmd) = first_failure
+            && cmd.result.env_failure.is_some()
+        {
+            return RowStatus::EnvBroken { step: cmd.command };
+        }
+
+        if row.test.commands.is_empty() {
+            return RowStatus::Skipped;
+        }
+
+        let Some(offered) = &row.offered else {
+            return match failed_step {
+                None => RowStatus::Passed,
+                Some(step) => RowStatus::Broken { step },
+            };
+        };
+
+        if !offered.forced && !row.primary.used_offered_version {
+            return if row.primary.fork.is_some() { RowStatus::UsesFork } else { RowStatus::NotUsed };
+        }
+
+        match (failed_step, row.baseline_passed) {
+            (None, Some(false)) => RowStatus::Fixed,
+            (None, _) => RowStatus::Passed,
+            (Some(step), Some(true)) => RowStatus::Regressed { step },
+            (Some(step), Some(false)) => {
+                let baseline_compiled = row.baseline_check_passed == Some(true);
+                if baseline_compiled && step != CommandType::Test {
+                    RowStatus::Regressed { step }
+                } else {
+                    RowStatus::StillBroken { step }
+                }
+            }
+            (Some(step), None) => RowStatus::Failed { step },
+        }
+    }
+
+    /// The failed step, if any
+    pub fn step(&self) -> Option<CommandType> {
+        match *self {
+            RowStatus::Regressed { step }
+            | RowStatus::Broken { step }
+            | RowStatus::StillBroken { step }
+            | RowStatus::Failed { step }
+            | RowStatus::TimedOut { step }
+            | RowStatus::EnvBroken { step } => Some(step),
+            _ => None,
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            RowStatus::Passed | RowStatus::Fixed => Severity::Ok,
+            RowStatus::NotUsed | RowStatus::UsesFork | RowStatus::Skipped => Severity::Notice,
+            // Tests that already failed on baseline are not counted against you
+            RowStatus::StillBroken { step: CommandType::Test } => Severity::Notice,
+            RowStatus::Regressed { .. } => Severity::Regression,
+            RowStatus::Broken { .. }
+            | RowStatus::StillBroken { .. }
+            | RowStatus::Failed { .. }
+            | RowStatus::TimedOut { .. }
+            | RowStatus::EnvBroken { .. } => Severity::Broken,
+        }
+    }
+
+    pub fn is_regression(&self) -> bool {
+        self.severity() == Severity::Regression
+    }
+
+    /// Whether the offered version never got tested: cargo didn't select it, the dependent
+    /// builds against a fork, or no steps ran (missing system dep, quarantined, out of time)
+    pub fn is_skipped(&self) -> bool {
+        matches!(self, RowStatus::NotUsed | RowStatus::UsesFork | RowStatus::Skipped)
+    }
+
+    /// Whether the row failed at some step
+    pub fn is_failure(&self) -> bool {
+        self.step().is_some()
+    }
+
+    /// Short label for the result column ("build failed", "test broken", ...) in `messages`
+    pub fn label(&self, messages: &crate::messages::Catalog) -> String {
+        let with_step =
+            |key: &str, step: &CommandType| messages.format(key, &[("step", messages.get(step.message_key()))]);
+        match self {
+            RowStatus::Passed => messages.text("status.passed"),
+            RowStatus::Fixed => messages.text("status.fixed"),
+            RowStatus::NotUsed => messages.text("status.not_used"),
+            RowStatus::UsesFork => messages.text("status.uses_fork"),
+            RowStatus::Skipped => messages.text("status.skipped"),
+            RowStatus::Regressed { step } | RowStatus::Failed { step } => with_step("status.failed", step),
+            RowStatus::Broken { step } | RowStatus::StillBroken { step } => with_step("status.broken", step),
+            RowStatus::TimedOut { step } => with_step("status.timeout", step),
+            RowStatus::EnvBroken { .. } => messages.text("status.env_failure"),
+        }
+    }
+
+    /// One untranslated word for tooling and `--status-style words` ("PASSED", "REGRESSED", ...)
+    pub fn word(&self) -> &'static str {
+        match self {
+            RowStatus::Passed => "PASSED",
+            RowStatus::Fixed => "FIXED",
+            RowStatus::NotUsed => "NOT-USED",
+            RowStatus::UsesFork => "USES-FORK",
+            RowStatus::Skipped => "SKIPPED",
+            RowStatus::Regressed { .. } => "REGRESSED",
+            RowStatus::Failed { .. } => "FAILED",
+            RowStatus::Broken { .. } => "BROKEN",
+            RowStatus::StillBroken { .. } => "STILL-BROKEN",
+            RowStatus::TimedOut { .. } => "TIMED-OUT",
+            RowStatus::EnvBroken { .. } => "ENV-FAILED",
+        }
+    }
+}
+
 impl TestResult {
     /// Determine the status for reporting
     pub fn status(&self) -> TestStatus {
@@ -493,10 +966,16 @@ pub fn compile_result_to_command(
     crate_name: &str,
     max_error_lines: usize,
 ) -> TestCommand {
+    let (errors, warnings) = compile_result.diagnostic_counts();
     let failures = if !compile_result.success {
         let error_msg =
             extract_error_with_fallback(&compile_result.diagnostics, &compile_result.stderr, max_error_lines);
-        vec![CrateFailure { crate_name: crate_name.to_string(), error_message: error_msg }]
+        vec![CrateFailure {
+            crate_name: crate_name.to_string(),
+            error_message: error_msg,
+            raw_stderr: false,
+            error_file: None,
+        }]
     } else {
         vec![]
     };
@@ -506,8 +985,106 @@ pub fn compile_result_to_command(
         features: vec![],
         result: CommandResult {
             passed: compile_result.success,
+            env_failure: compile_result.env_failure().map(String::from),
+            timed_out: compile_result.timed_out,
             duration: compile_result.duration.as_secs_f64(),
             failures,
+            errors,
+            warnings,
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(baseline: Option<(bool, bool)>, forced: bool, used: bool, steps: &[(CommandType, bool)]) -> OfferedRow {
+        crate::test_rows::row("dep")
+            .spec("^0.1")
+            .offered(baseline.map(|_| "0.2.0"))
+            .forced(forced)
+            .baseline(baseline)
+            .resolved("0.1.0")
+            .used_offered(used)
+            .outcomes(steps)
+            .build()
+    }
+
+    use CommandType::*;
+
+    #[test]
+    fn test_row_status_classification() {
+        let ok = [(Fetch, true), (Check, true), (Test, true)];
+        let check_fails = [(Fetch, true), (Check, false)];
+        let test_fails = [(Fetch, true), (Check, true), (Test, false)];
+
+        assert_eq!(row(None, false, true, &ok).status(), RowStatus::Passed);
+        assert_eq!(row(None, false, true, &test_fails).status(), RowStatus::Broken { step: Test });
+        assert_eq!(row(Some((true, true)), true, true, &ok).status(), RowStatus::Passed);
+        assert_eq!(row(Some((true, true)), true, true, &test_fails).status(), RowStatus::Regressed { step: Test });
+        assert_eq!(row(Some((false, false)), true, true, &ok).status(), RowStatus::Fixed);
+        assert_eq!(
+            row(Some((false, false)), true, true, &check_fails).status(),
+            RowStatus::StillBroken { step: Check }
+        );
+        // Baseline compiled but failed tests: a check failure is still a regression
+        assert_eq!(row(Some((true, false)), true, true, &check_fails).status(), RowStatus::Regressed { step: Check });
+        assert_eq!(row(Some((true, false)), true, true, &test_fails).status(), RowStatus::StillBroken { step: Test });
+        assert_eq!(row(Some((true, true)), false, false, &test_fails).status(), RowStatus::NotUsed);
+        let mut fork = row(Some((true, true)), false, false, &test_fails);
+        fork.primary.fork = Some("git https://github.com/someone/rgb".to_string());
+        assert_eq!(fork.status(), RowStatus::UsesFork);
+        assert_eq!(fork.status().label(&Default::default()), "uses fork");
+
+        let mut env = row(Some((true, true)), true, true, &check_fails);
+        env.test.commands[1].result.env_failure = Some("disk full".to_string());
+        assert_eq!(env.status(), RowStatus::EnvBroken { step: Check });
+        assert!(!env.is_regression());
+
+        let mut timed_out = row(Some((true, true)), true, true, &test_fails);
+        timed_out.test.commands[2].result.timed_out = true;
+        assert_eq!(timed_out.status(), RowStatus::TimedOut { step: Test });
+        assert_eq!(timed_out.status().label(&Default::default()), "test timeout");
+        assert!(!timed_out.is_regression());
+    }
+
+    #[test]
+    fn test_row_status_labels_and_severity() {
+        let english = crate::messages::Catalog::default();
+        assert_eq!(RowStatus::Regressed { step: Check }.label(&english), "build failed");
+        assert_eq!(RowStatus::StillBroken { step: Test }.label(&english), "test broken");
+        assert_eq!(RowStatus::Broken { step: Fetch }.label(&english), "fetch broken");
+        assert_eq!(RowStatus::NotUsed.label(&english), "not used");
+        let german = crate::messages::Catalog::parse("[status]\nfailed = \"{step} kaputt\"\n").unwrap();
+        assert_eq!(RowStatus::Regressed { step: Check }.label(&german), "build kaputt");
+
+        assert_eq!(RowStatus::StillBroken { step: Test }.severity(), Severity::Notice);
+        assert_eq!(RowStatus::StillBroken { step: Check }.severity(), Severity::Broken);
+        assert!(RowStatus::Regressed { step: Fetch }.is_regression());
+        assert!(!RowStatus::Fixed.is_failure());
+    }
+
+    #[test]
+    fn test_row_status_json_shape() {
+        let json = serde_json::to_value(RowStatus::Regressed { step: Check }).unwrap();
+        assert_eq!(json, serde_json::json!({"kind": "regressed", "step": "Check"}));
+        assert_eq!(serde_json::to_value(Severity::Regression).unwrap(), "regression");
+    }
+
+    #[test]
+    fn test_steps_parse() {
+        use CommandType::*;
+        assert_eq!(Steps::parse("fetch,check").unwrap(), Steps::through(Check));
+        assert_eq!(Steps::parse(" fetch , check , test ").unwrap(), Steps::all());
+        assert_eq!(Steps::through(Fetch).to_string(), "fetch");
+        assert!(!Steps::through(Check).runs(Test));
+        assert_eq!(Steps::through(Check).last(), Check);
+
+        assert!(Steps::parse("").is_err());
+        assert!(Steps::parse("fetch,build").unwrap_err().contains("unknown step 'build'"));
+        assert!(Steps::parse("check,test").is_err(), "check needs fetch");
+        assert!(Steps::parse("fetch,test").is_err(), "test needs check");
+        assert!(Steps::parse("fetch,fetch").is_err());
+    }
+}
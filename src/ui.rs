@@ -4,10 +4,53 @@
 /// - Thread-safe console output
 /// - Colored terminal text
 /// - Status message formatting
+/// - Moving all of it to stderr while stdout carries `--output jsonl`
 use lazy_static::lazy_static;
 use std::io::Write;
 use std::sync::Mutex;
 
+/// Where lines for people go: stdout, or stderr while stdout carries `--output jsonl`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Console {
+    /// Keep stdout for `--output jsonl`: `say`, `status` and `print_error` write to stderr
+    pub jsonl: bool,
+}
+
+impl Console {
+    /// Print a line for people
+    pub fn say(self, line: &str) {
+        if self.jsonl {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    /// Print a status message with "copter: " prefix (thread-safe)
+    pub fn status(self, s: &str) {
+        if self.jsonl {
+            eprintln!("copter: {}", s);
+            return;
+        }
+        status_lock(|| {
+            print_status_header();
+            println!("{}", s);
+        });
+    }
+
+    /// Print an error message with colored "error" prefix
+    pub fn print_error(self, msg: &str) {
+        if self.jsonl {
+            eprintln!("error: {}", msg);
+            return;
+        }
+        println!();
+        print_color("error", term::color::BRIGHT_RED);
+        println!(": {}", msg);
+        println!();
+    }
+}
+
 /// Execute a function with exclusive access to console output
 /// Prevents interleaved output from multiple threads
 fn status_lock<F>(f: F)
@@ -47,19 +90,3 @@ fn print_color(s: &str, fg: term::color::Color) {
         true
     }
 }
-
-/// Print a status message with "copter: " prefix (thread-safe)
-pub fn status(s: &str) {
-    status_lock(|| {
-        print_status_header();
-        println!("{}", s);
-    });
-}
-
-/// Print an error message with colored "error" prefix
-pub fn print_error(msg: &str) {
-    println!();
-    print_color("error", term::color::BRIGHT_RED);
-    println!(": {}", msg);
-    println!();
-}
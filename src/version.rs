@@ -1,95 +1,300 @@
 /// Version resolution and compatibility checking
 ///
 /// This module handles:
-/// - Resolving version keywords ("this", "latest", "latest-preview")
+/// - Resolving version keywords ("this", "latest", "prev", "major-1", "published", ...)
 /// - Checking semver compatibility
 /// - Resolving latest versions from crates.io
 /// - Determining if WIP versions satisfy dependent requirements
+/// - Warning when the planned WIP version would break semver-compatible dependents
 use crate::api;
 use crate::compile;
 use crate::manifest;
+use crate::registry::Registry;
 use log::debug;
 use semver::Version;
 use std::path::PathBuf;
 
-/// Resolve a version keyword ("this", "latest", "latest-preview") or concrete version
+/// Resolve a version keyword ("this" or a [`VersionKeyword`]) or concrete version
 ///
 /// Returns None if resolution fails (with warning printed to UI)
 pub fn resolve_version_keyword(
+    registry: &Registry,
     version_str: &str,
     crate_name: &str,
     local_manifest: Option<&PathBuf>,
 ) -> Result<Option<compile::VersionSource>, String> {
-    match version_str {
-        "this" => {
-            // User explicitly requested WIP version
-            if let Some(manifest_path) = local_manifest {
-                debug!("Resolved 'this' to local WIP at {:?}", manifest_path);
-                Ok(Some(compile::VersionSource::Local { path: manifest_path.clone(), forced: false }))
-            } else {
-                crate::ui::status("Warning: 'this' specified but no local source available (--path or --crate)");
-                Ok(None)
-            }
+    if version_str == "this" {
+        // User explicitly requested WIP version
+        if let Some(manifest_path) = local_manifest {
+            debug!("Resolved 'this' to local WIP at {:?}", manifest_path);
+            return Ok(Some(compile::VersionSource::Local { path: manifest_path.clone(), forced: false }));
         }
-        "latest" => {
-            // Resolve to latest stable version
-            match resolve_latest_version(crate_name, false) {
-                Ok(ver) => {
-                    debug!("Resolved 'latest' to {}", ver);
-                    Ok(Some(compile::VersionSource::Published { version: ver, forced: false }))
-                }
-                Err(e) => {
-                    crate::ui::status(&format!("Warning: Failed to resolve 'latest': {}", e));
-                    Ok(None)
-                }
+        eprintln!("Warning: 'this' specified but no local source available (--path or --crate)");
+        return Ok(None);
+    }
+
+    if let Some(keyword) = VersionKeyword::parse(version_str) {
+        let local_version = local_manifest
+            .and_then(|path| manifest::get_crate_info(path).ok())
+            .and_then(|(_, version)| Version::parse(&version).ok());
+        let resolved = api::get_all_versions(registry, crate_name)
+            .map_err(|e| format!("Failed to fetch crate info: {}", e))
+            .and_then(|published| keyword.pick(&published, local_version.as_ref()));
+        return match resolved {
+            Ok(ver) => {
+                debug!("Resolved '{}' to {}", version_str, ver);
+                Ok(Some(compile::VersionSource::Published { version: ver.to_string(), forced: false }))
             }
-        }
-        "latest-preview" | "latest-prerelease" => {
-            // Resolve to latest version including pre-releases
-            match resolve_latest_version(crate_name, true) {
-                Ok(ver) => {
-                    debug!("Resolved '{}' to {}", version_str, ver);
-                    Ok(Some(compile::VersionSource::Published { version: ver, forced: false }))
-                }
-                Err(e) => {
-                    crate::ui::status(&format!("Warning: Failed to resolve '{}': {}", version_str, e));
-                    Ok(None)
-                }
+            Err(e) => {
+                eprintln!("Warning: Failed to resolve '{}': {}", version_str, e);
+                Ok(None)
             }
+        };
+    }
+
+    // Validate it's a concrete version, not a version requirement
+    if version_str.starts_with('^') || version_str.starts_with('~') || version_str.starts_with('=') {
+        return Err(format!("Version requirement '{}' not allowed. Use concrete versions like '0.8.52'", version_str));
+    }
+
+    // Validate it's a valid semver version
+    Version::parse(version_str).map_err(|e| {
+        if version_str.chars().next().is_some_and(|c| c.is_ascii_alphabetic()) {
+            format!("Unknown version keyword '{}' (known: this, {})", version_str, VersionKeyword::NAMES.join(", "))
+        } else {
+            format!("Invalid version '{}': {}", version_str, e)
         }
-        _ => {
-            // Validate it's a concrete version, not a version requirement
-            if version_str.starts_with('^') || version_str.starts_with('~') || version_str.starts_with('=') {
-                return Err(format!(
-                    "Version requirement '{}' not allowed. Use concrete versions like '0.8.52'",
-                    version_str
-                ));
-            }
+    })?;
 
-            // Validate it's a valid semver version
-            Version::parse(version_str).map_err(|e| format!("Invalid version '{}': {}", version_str, e))?;
+    // Literal version string (supports hyphens like "0.8.2-alpha2")
+    Ok(Some(compile::VersionSource::Published { version: version_str.to_string(), forced: false }))
+}
 
-            // Literal version string (supports hyphens like "0.8.2-alpha2")
-            Ok(Some(compile::VersionSource::Published { version: version_str.to_string(), forced: false }))
+/// A published version named by its place among the crate's releases
+///
+/// Yanked releases never match. To add a keyword, add a variant, its name to `parse` and
+/// `NAMES`, and its rule to `pick`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionKeyword {
+    /// `latest` / `stable`: the newest release; `latest-preview` / `latest-prerelease`
+    /// include pre-releases
+    Latest { prerelease: bool },
+    /// `prev`: the stable release before the newest one
+    Prev,
+    /// `major-N`: the newest stable release N semver-incompatible lines back (from 1.4.2,
+    /// `major-1` is the newest 0.x line's; from 0.8.91, it is the newest 0.7.x)
+    MajorBack(usize),
+    /// `published`: the local Cargo.toml's version if it is out, else the newest release
+    /// before it (what dependents get today)
+    Published,
+}
+
+impl VersionKeyword {
+    /// Keyword names for help and error messages
+    pub const NAMES: [&'static str; 7] =
+        ["latest", "stable", "latest-preview", "latest-prerelease", "prev", "major-N", "published"];
+
+    pub fn parse(keyword: &str) -> Option<Self> {
+        match keyword {
+            "latest" | "stable" => Some(VersionKeyword::Latest { prerelease: false }),
+            "latest-preview" | "latest-prerelease" => Some(VersionKeyword::Latest { prerelease: true }),
+            "prev" => Some(VersionKeyword::Prev),
+            "published" => Some(VersionKeyword::Published),
+            _ => keyword.strip_prefix("major-")?.parse().ok().filter(|n| *n > 0).map(VersionKeyword::MajorBack),
         }
     }
+
+    /// The release this keyword names among `published`; `local` is the WIP version, if any
+    pub fn pick(&self, published: &[api::VersionDownloads], local: Option<&Version>) -> Result<Version, String> {
+        let mut releases: Vec<Version> = published
+            .iter()
+            .filter(|v| !v.yanked)
+            .filter_map(|v| Version::parse(&v.version).ok())
+            .filter(|v| v.pre.is_empty() || *self == VersionKeyword::Latest { prerelease: true })
+            .collect();
+        releases.sort();
+        releases.dedup();
+
+        let found = match self {
+            VersionKeyword::Latest { .. } => releases.pop(),
+            VersionKeyword::Prev => releases.iter().rev().nth(1).cloned(),
+            VersionKeyword::MajorBack(n) => {
+                let mut lines: Vec<String> = releases.iter().map(compat_line).collect();
+                lines.dedup();
+                let line = lines.iter().rev().nth(*n);
+                releases.iter().rev().find(|v| Some(&compat_line(v)) == line).cloned()
+            }
+            VersionKeyword::Published => {
+                let local = local.ok_or("'published' needs local source (--path) to compare against")?;
+                let mut all: Vec<Version> = published
+                    .iter()
+                    .filter(|v| !v.yanked)
+                    .filter_map(|v| Version::parse(&v.version).ok())
+                    .filter(|v| v <= local)
+                    .collect();
+                all.sort();
+                all.pop()
+            }
+        };
+        found.ok_or_else(|| "No matching published version".to_string())
+    }
 }
 
 /// Resolve 'latest' or 'latest-preview' keyword to actual version
-pub fn resolve_latest_version(crate_name: &str, include_prerelease: bool) -> Result<String, String> {
+pub fn resolve_latest_version(
+    registry: &Registry,
+    crate_name: &str,
+    include_prerelease: bool,
+) -> Result<String, String> {
     debug!("Resolving latest version for {} (prerelease={})", crate_name, include_prerelease);
 
-    let krate = api::get_client().get_crate(crate_name).map_err(|e| format!("Failed to fetch crate info: {}", e))?;
+    let published =
+        api::get_all_versions(registry, crate_name).map_err(|e| format!("Failed to fetch crate info: {}", e))?;
+    let keyword = VersionKeyword::Latest { prerelease: include_prerelease };
+    keyword.pick(&published, None).map(|v| v.to_string()).map_err(|_| "No versions found".to_string())
+}
 
-    // Filter and sort versions
-    let mut versions: Vec<Version> = krate
-        .versions
-        .iter()
-        .filter_map(|r| Version::parse(&r.num).ok())
-        .filter(|v| include_prerelease || v.pre.is_empty()) // Filter pre-releases unless requested
-        .collect();
+/// A planned release that semver-compatible dependents would pick up and fail with
+///
+/// `cargo update` moves every dependent with a `^0.8` requirement onto a new 0.8.x, so a
+/// patch or minor bump that regresses any of them breaks their builds without a change
+/// on their side. A version outside their range (0.9.0) is only picked up on purpose.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemverHazard {
+    /// The WIP version from the local Cargo.toml
+    pub planned: String,
+    /// The compatible range it falls into (`^0.8`)
+    pub range: String,
+    /// Regressed dependents whose requirement accepts `planned` (`image:0.25.5`)
+    pub broken: Vec<String>,
+    /// The next semver-breaking version
+    pub suggested: String,
+}
+
+impl SemverHazard {
+    /// Check the local WIP version of `matrix` against the regressions in `rows`
+    pub fn find(matrix: &crate::types::TestMatrix, rows: &[crate::types::OfferedRow]) -> Option<Self> {
+        use crate::types::{CrateSource, Version as SpecVersion};
+        let semver_of = |local: bool| {
+            matrix.base_versions.iter().find_map(|v| match (&v.crate_ref.source, &v.crate_ref.version) {
+                (CrateSource::Local { .. }, SpecVersion::Semver(s)) if local => Some(s.as_str()),
+                (CrateSource::Registry, SpecVersion::Semver(s)) if !local && v.is_baseline => Some(s.as_str()),
+                _ => None,
+            })
+        };
+        Self::check(semver_of(true)?, semver_of(false)?, rows)
+    }
 
-    versions.sort();
+    /// `planned` is the WIP version, `published` the baseline it is compared against
+    pub fn check(planned: &str, published: &str, rows: &[crate::types::OfferedRow]) -> Option<Self> {
+        let planned_version = Version::parse(planned).ok()?;
+        let published_version = Version::parse(published).ok()?;
+        if planned_version <= published_version || compat_line(&planned_version) != compat_line(&published_version) {
+            return None;
+        }
+
+        let mut broken: Vec<String> = Vec::new();
+        for row in rows.iter().filter(|r| r.is_regression()) {
+            let offered = row.offered.as_ref().map(|o| o.version.as_str());
+            let accepts = semver::VersionReq::parse(&row.primary.spec).is_ok_and(|req| req.matches(&planned_version));
+            let dep = format!("{}:{}", row.primary.dependent_name, row.primary.dependent_version);
+            if offered == Some(planned) && accepts && !broken.contains(&dep) {
+                broken.push(dep);
+            }
+        }
+        if broken.is_empty() {
+            return None;
+        }
+
+        let v = &planned_version;
+        let suggested = match (v.major, v.minor) {
+            (0, 0) => format!("0.0.{}", v.patch + 1),
+            (0, minor) => format!("0.{}.0", minor + 1),
+            (major, _) => format!("{}.0.0", major + 1),
+        };
+        Some(Self { planned: planned.to_string(), range: format!("^{}", compat_line(v)), broken, suggested })
+    }
+
+    /// "0.8.91 will break 8 crates that use ^0.8 — consider 0.9.0"
+    pub fn message(&self) -> String {
+        let crates = if self.broken.len() == 1 { "crate" } else { "crates" };
+        format!(
+            "{} will break {} {} that use {} — consider {}",
+            self.planned,
+            self.broken.len(),
+            crates,
+            self.range,
+            self.suggested
+        )
+    }
+}
 
-    versions.pop().map(|v| v.to_string()).ok_or_else(|| "No versions found".to_string())
+/// The semver-compatible release line of a version, up to its leftmost non-zero component
+/// (`0.8.91` → `0.8`, `1.3.0` → `1`, `0.0.3` → `0.0.3`); cargo treats versions on one line as compatible
+pub fn compat_line(v: &Version) -> String {
+    match (v.major, v.minor) {
+        (0, 0) => format!("0.0.{}", v.patch),
+        (0, minor) => format!("0.{}", minor),
+        (major, _) => major.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+
+    fn regressed(dependent: &str, spec: &str, offered: &str) -> OfferedRow {
+        crate::test_rows::row(dependent).spec(spec).offered(offered).forced(true).local().passed(false).build()
+    }
+
+    #[test]
+    fn test_semver_hazard() {
+        let rows = vec![
+            regressed("image", "^0.8.52", "0.8.91"),
+            regressed("ravif", "0.8", "0.8.91"),
+            // Pinned below the planned version: cargo update won't pick it up
+            regressed("old", "=0.8.50", "0.8.91"),
+            regressed("other", "^0.8", "0.8.50"),
+        ];
+        let hazard = SemverHazard::check("0.8.91", "0.8.90", &rows).unwrap();
+        assert_eq!(hazard.broken, vec!["image:1.0.0", "ravif:1.0.0"]);
+        assert_eq!(hazard.message(), "0.8.91 will break 2 crates that use ^0.8 — consider 0.9.0");
+
+        // Already a breaking bump, or nothing regressed
+        assert_eq!(SemverHazard::check("0.9.0", "0.8.90", &rows), None);
+        assert_eq!(SemverHazard::check("0.8.91", "0.8.90", &rows[3..]), None);
+
+        let rows = vec![regressed("image", "1.2", "1.3.0")];
+        assert_eq!(SemverHazard::check("1.3.0", "1.2.4", &rows).unwrap().suggested, "2.0.0");
+    }
+
+    #[test]
+    fn test_version_keywords() {
+        let published: Vec<crate::api::VersionDownloads> = [
+            ("0.7.3", false),
+            ("0.7.4", false),
+            ("0.8.50", false),
+            ("0.8.51", true),
+            ("0.8.52", false),
+            ("0.9.0-rc.1", false),
+        ]
+        .into_iter()
+        .map(|(version, yanked)| crate::api::VersionDownloads { version: version.to_string(), downloads: 0, yanked })
+        .collect();
+        let pick = |keyword: &str, local: Option<&str>| {
+            let local = local.map(|v| semver::Version::parse(v).unwrap());
+            VersionKeyword::parse(keyword).unwrap().pick(&published, local.as_ref()).map(|v| v.to_string())
+        };
+        assert_eq!(pick("stable", None).unwrap(), "0.8.52");
+        assert_eq!(pick("latest-preview", None).unwrap(), "0.9.0-rc.1");
+        assert_eq!(pick("prev", None).unwrap(), "0.8.50");
+        assert_eq!(pick("major-1", None).unwrap(), "0.7.4");
+        assert!(pick("major-2", None).is_err());
+        assert_eq!(pick("published", Some("0.8.53")).unwrap(), "0.8.52");
+        assert_eq!(pick("published", Some("0.8.51")).unwrap(), "0.8.50");
+        assert!(pick("published", None).is_err());
+        assert_eq!(VersionKeyword::parse("major-0"), None);
+        assert_eq!(VersionKeyword::parse("0.8.52"), None);
+    }
 }
@@ -0,0 +1,269 @@
+/// `what-if`: test dependents against the WIP crate with a feature or module taken out
+///
+/// Before removing a feature (or an API behind one) the question is who would break.
+/// `what-if --disable-feature simd --cfg-out legacy` stages a copy of the local crate (the
+/// whole workspace, for a member) whose manifest no longer has the `simd` feature (nor
+/// the optional dependency of that name, nor any mention of it in other features) and
+/// whose `mod legacy;` declaration, and the `use` lines of the same file that reach into
+/// it, carry `#[cfg(any())]`. The copy's version gets build metadata
+/// (`0.8.92+without-simd-legacy`), which cargo ignores when matching requirements, so it
+/// runs as one more base version next to the unmodified WIP; the summary then names the
+/// dependents that pass with the WIP and fail without the feature: exactly what the
+/// removal would cost.
+use crate::types::{CrateSource, OfferedRow, TestMatrix, Version};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// What `what-if` takes out of the crate
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Removal {
+    /// `--disable-feature`
+    pub features: Vec<String>,
+    /// `--cfg-out`: module paths from the crate root (`legacy`, `codec::png`)
+    pub modules: Vec<String>,
+}
+
+impl Removal {
+    /// Build metadata naming the removal: "without-simd-codec-png"
+    pub fn label(&self) -> String {
+        let words = self.features.iter().chain(&self.modules).flat_map(|name| {
+            name.trim_start_matches("crate::").split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty())
+        });
+        std::iter::once("without").chain(words).collect::<Vec<_>>().join("-")
+    }
+}
+
+/// Stage the WIP crate without `removal` and add it to the matrix right after the WIP
+pub fn prepare(matrix: &mut TestMatrix, removal: &Removal) -> Result<(), String> {
+    let index = matrix
+        .base_versions
+        .iter()
+        .position(|v| !v.is_baseline && matches!(v.crate_ref.source, CrateSource::Local { .. }))
+        .ok_or("what-if needs the local crate (run it in the crate's directory or pass --path)")?;
+    let wip = matrix.base_versions[index].clone();
+    let CrateSource::Local { path } = &wip.crate_ref.source else { unreachable!() };
+    let source = if path.ends_with("Cargo.toml") { path.parent().unwrap_or(path) } else { path.as_path() };
+    let wip_version = wip.crate_ref.version.display();
+    let version = format!("{}+{}", wip_version.split('+').next().unwrap_or(&wip_version), removal.label());
+
+    let dest = matrix.staging_dir.join(format!("{}-what-if", matrix.base_crate));
+    let copy = crate::simulate_update::copy_with_version(source, &dest, &version, matrix.staging_copy)?;
+    apply(&copy, removal)?;
+    check_builds(&copy, &matrix.base_crate, &version)?;
+
+    let mut what_if = wip;
+    what_if.crate_ref.version = Version::Semver(version.clone());
+    what_if.crate_ref.source = CrateSource::Local { path: copy.join("Cargo.toml") };
+    matrix.base_versions.insert(index + 1, what_if);
+    Ok(())
+}
+
+/// (WIP version, what-if version) when `prepare` added one to the matrix
+pub fn versions(matrix: &TestMatrix) -> Option<(String, String)> {
+    let index = matrix.base_versions.iter().position(|v| {
+        matches!(v.crate_ref.source, CrateSource::Local { .. }) && v.crate_ref.version.display().contains("+without-")
+    })?;
+    let wip = matrix.base_versions.get(index.checked_sub(1)?)?;
+    Some((wip.crate_ref.version.display(), matrix.base_versions[index].crate_ref.version.display()))
+}
+
+/// Edit the crate at `dir`: drop the features, cfg out the modules
+fn apply(dir: &Path, removal: &Removal) -> Result<(), String> {
+    let manifest = dir.join("Cargo.toml");
+    let mut doc: toml_edit::DocumentMut = crate::manifest::load_string(&manifest)?
+        .parse()
+        .map_err(|e| format!("Failed to parse {}: {}", manifest.display(), e))?;
+    for feature in &removal.features {
+        disable_feature(&mut doc, feature)?;
+    }
+    let lib = doc
+        .get("lib")
+        .and_then(|lib| lib.get("path"))
+        .and_then(|path| path.as_str())
+        .map_or_else(|| dir.join("src/lib.rs"), |path| dir.join(path));
+    crate::compile::patching::write_atomic(&manifest, &doc.to_string())?;
+    for module in &removal.modules {
+        cfg_out(&lib, module)?;
+    }
+    Ok(())
+}
+
+/// Remove `feature` from `[features]`, from the other features that enable it, and the
+/// optional dependency it names
+fn disable_feature(doc: &mut toml_edit::DocumentMut, feature: &str) -> Result<(), String> {
+    let mut found = false;
+    if let Some(features) = doc.get_mut("features").and_then(|f| f.as_table_like_mut()) {
+        found |= features.remove(feature).is_some();
+        let mentions = |item: &str| {
+            item == feature
+                || item == format!("dep:{}", feature)
+                || item.starts_with(&format!("{}/", feature))
+                || item.starts_with(&format!("{}?/", feature))
+        };
+        for (_, enables) in features.iter_mut() {
+            if let Some(enables) = enables.as_array_mut() {
+                enables.retain(|item| !item.as_str().is_some_and(mentions));
+            }
+        }
+    }
+    let optional = |deps: &dyn toml_edit::TableLike| {
+        deps.get(feature).and_then(|dep| dep.get("optional")).and_then(|o| o.as_bool()) == Some(true)
+    };
+    if let Some(deps) = doc.get_mut("dependencies").and_then(|d| d.as_table_like_mut())
+        && optional(deps)
+    {
+        deps.remove(feature);
+        found = true;
+    }
+    if let Some(targets) = doc.get_mut("target").and_then(|t| t.as_table_like_mut()) {
+        for (_, target) in targets.iter_mut() {
+            if let Some(deps) = target.get_mut("dependencies").and_then(|d| d.as_table_like_mut())
+                && optional(deps)
+            {
+                deps.remove(feature);
+                found = true;
+            }
+        }
+    }
+    if found { Ok(()) } else { Err(format!("--disable-feature {}: no such feature or optional dependency", feature)) }
+}
+
+/// Put `#[cfg(any())]` on the declaration of `module` (a path from the crate root, whose
+/// files are found from `lib`) and on the `use` lines of that file that reach into it
+fn cfg_out(lib: &Path, module: &str) -> Result<(), String> {
+    let parts: Vec<&str> = module.trim_start_matches("crate::").split("::").collect();
+    let (name, parents) = parts.split_last().ok_or("--cfg-out needs a module name")?;
+    let mut file = lib.to_path_buf();
+    for parent in parents {
+        file = module_file(&file, parent)
+            .ok_or_else(|| format!("--cfg-out {}: no file for module {} next to {}", module, parent, file.display()))?;
+    }
+
+    let text = std::fs::read_to_string(&file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+    let declaration = Regex::new(&format!(r"^\s*(pub(\([^)]*\))?\s+)?mod\s+{}\s*[;{{]", regex::escape(name))).unwrap();
+    let reaches_in =
+        Regex::new(&format!(r"^\s*(pub(\([^)]*\))?\s+)?use\s+((crate|self)::)?{}(::|;|\s)", regex::escape(name)))
+            .unwrap();
+    let mut out = String::with_capacity(text.len());
+    let mut found = false;
+    for line in text.split_inclusive('\n') {
+        let is_declaration = declaration.is_match(line);
+        if is_declaration || reaches_in.is_match(line) {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            out.push_str(&format!("{}#[cfg(any())] // cargo-copter what-if\n", indent));
+        }
+        found |= is_declaration;
+        out.push_str(line);
+    }
+    if !found {
+        return Err(format!("--cfg-out {}: no `mod {}` declaration in {}", module, name, file.display()));
+    }
+    Ok(crate::compile::patching::write_atomic(&file, &out)?)
+}
+
+/// The file of module `name` declared in `parent` (`name.rs` or `name/mod.rs`)
+fn module_file(parent: &Path, name: &str) -> Option<PathBuf> {
+    let stem = parent.file_stem()?.to_str()?;
+    let dir = parent.parent()?;
+    // lib.rs, main.rs and mod.rs keep their submodules beside them; foo.rs keeps them in foo/
+    let dir = if matches!(stem, "lib" | "main" | "mod") { dir.to_path_buf() } else { dir.join(stem) };
+    [dir.join(format!("{}.rs", name)), dir.join(name).join("mod.rs")].into_iter().find(|path| path.exists())
+}
+
+/// `cargo check` the staged copy, so a removal that breaks the crate itself is reported
+/// once instead of as every dependent failing
+fn check_builds(dir: &Path, crate_name: &str, version: &str) -> Result<(), String> {
+    eprintln!("what-if: checking that {} {} still builds ...", crate_name, version);
+    let output = Command::new("cargo")
+        .args(["check", "--lib", "--quiet"])
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("Failed to run cargo check: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let errors: Vec<&str> = stderr.lines().filter(|l| l.starts_with("error")).take(5).collect();
+    Err(format!(
+        "{} {} no longer builds, so every dependent would fail; remove the code using it too:\n{}",
+        crate_name,
+        version,
+        errors.join("\n")
+    ))
+}
+
+/// Dependents that pass with `wip` and fail with `what_if`
+pub fn broken_by_removal(rows: &[OfferedRow], wip: &str, what_if: &str) -> Vec<String> {
+    let offered = |row: &OfferedRow, version: &str| row.offered.as_ref().is_some_and(|o| o.version == version);
+    let passes = |row: &OfferedRow| row.status().severity() == crate::types::Severity::Ok;
+    rows.iter()
+        .filter(|row| offered(row, what_if) && !passes(row) && !row.status().is_skipped())
+        .filter(|row| {
+            rows.iter().any(|wip_row| {
+                offered(wip_row, wip)
+                    && wip_row.primary.dependent_name == row.primary.dependent_name
+                    && wip_row.primary.dependent_version == row.primary.dependent_version
+                    && passes(wip_row)
+            })
+        })
+        .map(|row| format!("{} {}", row.primary.dependent_name, row.primary.dependent_version))
+        .collect()
+}
+
+/// The summary line: how many dependents the removal breaks, and which
+pub fn summary_line(rows: &[OfferedRow], wip: &str, what_if: &str) -> String {
+    let broken = broken_by_removal(rows, wip, what_if);
+    match broken.len() {
+        0 => format!("what-if: no dependent that builds with {} breaks with {}", wip, what_if),
+        n => format!("what-if: {} dependent(s) pass with {} and fail with {}: {}", n, wip, what_if, broken.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_and_module_removed_from_copy() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::create_dir_all(dir.join("src/codec")).unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"rgb\"\nversion = \"0.8.92\"\n\n[dependencies]\nserde = { version = \"1\", optional = true }\n\n\
+             [features]\ndefault = [\"simd\", \"serde\"]\nsimd = []\nall = [\"simd\", \"serde?/std\", \"dep:serde\"]\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("src/lib.rs"), "pub mod codec;\npub mod legacy;\npub use legacy::Old;\nmod other;\n")
+            .unwrap();
+        std::fs::write(dir.join("src/codec/mod.rs"), "    pub(crate) mod png {}\n").unwrap();
+
+        let removal = Removal {
+            features: vec!["simd".to_string(), "serde".to_string()],
+            modules: vec!["legacy".to_string(), "codec::png".to_string()],
+        };
+        assert_eq!(removal.label(), "without-simd-serde-legacy-codec-png");
+        apply(dir, &removal).unwrap();
+
+        let manifest: toml::Table = std::fs::read_to_string(dir.join("Cargo.toml")).unwrap().parse().unwrap();
+        assert!(manifest["dependencies"].get("serde").is_none());
+        assert!(manifest["features"].get("simd").is_none());
+        assert_eq!(manifest["features"]["default"].as_array().unwrap().len(), 0);
+        assert_eq!(manifest["features"]["all"].as_array().unwrap().len(), 0);
+        assert_eq!(
+            std::fs::read_to_string(dir.join("src/lib.rs")).unwrap(),
+            "pub mod codec;\n#[cfg(any())] // cargo-copter what-if\npub mod legacy;\n\
+             #[cfg(any())] // cargo-copter what-if\npub use legacy::Old;\nmod other;\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("src/codec/mod.rs")).unwrap(),
+            "    #[cfg(any())] // cargo-copter what-if\n    pub(crate) mod png {}\n"
+        );
+
+        let missing = Removal { features: vec!["nope".to_string()], modules: vec![] };
+        assert!(apply(dir, &missing).unwrap_err().contains("no such feature"));
+        let missing = Removal { features: vec![], modules: vec!["gone".to_string()] };
+        assert!(apply(dir, &missing).unwrap_err().contains("no `mod gone` declaration"));
+    }
+}
@@ -1,5 +1,6 @@
 // Integration tests for crates.io API interaction
 // These tests hit the real crates.io API using the 'rgb' crate
+#![cfg(feature = "network")]
 
 use crates_io_api::SyncClient;
 use std::time::Duration;
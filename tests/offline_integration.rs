@@ -250,3 +250,19 @@ fn test_cargo_metadata_shows_base_crate_dependency() {
 
     panic!("Should find base-crate in dependent-passing's dependencies");
 }
+
+#[test]
+fn test_self_test_command_meets_all_expectations() {
+    // Runs the full pipeline (force-override v2/v3 over the v1 baseline) on a temp copy of the fixtures
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-copter"))
+        .args(["self-test", "--fixtures"])
+        .arg(fixtures_dir())
+        .output()
+        .expect("Failed to run cargo-copter self-test");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "self-test failed:\n{}\n{}", stdout, String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("ok   dependent-regressed × base-crate 0.2.0: regressed"));
+    assert!(stdout.contains("ok   dependent-broken × base-crate 0.2.0: broken"));
+    assert!(!stdout.contains("FAIL"));
+}